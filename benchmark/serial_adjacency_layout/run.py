@@ -0,0 +1,98 @@
+"""
+Compare the serial decoding time of the working tree against older revisions, to measure the effect of the adjacency
+layout of `DualModuleSerial`
+
+usage: python3 run.py <baseline_revision>...
+"""
+
+import os
+import sys
+import shutil
+import subprocess
+git_root_dir = subprocess.run("git rev-parse --show-toplevel", cwd=os.path.dirname(os.path.abspath(__file__))
+    , shell=True, check=True, capture_output=True).stdout.decode(sys.stdout.encoding).strip(" \r\n")
+# useful folders
+rust_dir = git_root_dir
+benchmark_dir = os.path.join(git_root_dir, "benchmark")
+script_dir = os.path.dirname(__file__)
+tmp_dir = os.path.join(script_dir, "tmp")
+os.makedirs(tmp_dir, exist_ok=True)  # make sure tmp directory exists
+sys.path.insert(0, benchmark_dir)
+
+import util
+from util import *
+
+baseline_revisions = sys.argv[1:]
+assert len(baseline_revisions) > 0, "usage: python3 run.py <baseline_revision>..."
+
+d = 21
+p = 0.005
+total_rounds = 2000
+noisy_measurements = d
+
+
+def build_revision(revision):
+    """ build a revision in its own worktree and return the path of the binary """
+    worktree_name = "".join(c if c.isalnum() else "_" for c in revision)
+    worktree_dir = os.path.join(tmp_dir, f"worktree-{worktree_name}")
+    if not os.path.exists(worktree_dir):
+        subprocess.run(["git", "worktree", "add", "--detach", worktree_dir, revision], cwd=rust_dir, check=True)
+        lock_file = os.path.join(rust_dir, "Cargo.lock")
+        if os.path.exists(lock_file):  # build with the same dependencies
+            shutil.copy(lock_file, worktree_dir)
+    subprocess.run(["cargo", "build", "--release", "--bin", "fusion_blossom"], cwd=worktree_dir, check=True)
+    return os.path.join(worktree_dir, "target", "release", "fusion_blossom")
+
+
+binaries = [(revision, build_revision(revision)) for revision in baseline_revisions]
+compile_code_if_necessary()
+binaries.append(("working tree", fusion_blossom_command()[0]))
+
+"""
+Generate the same syndrome data for all the revisions, using the oldest one so that all of them can read it
+"""
+
+syndrome_file_path = os.path.join(tmp_dir, f"generated-d{d}.syndromes")
+if os.path.exists(syndrome_file_path):
+    print("[warning] use existing syndrome data (if you think it's stale, delete it and rerun)")
+else:
+    command = [binaries[0][1], "benchmark", f"{d}", f"{p}", "-r", f"{total_rounds}", "-n", f"{noisy_measurements}"]
+    command += ["--code-type", "phenomenological-planar-code"]
+    command += ["--primal-dual-type", "error-pattern-logger"]
+    command += ["--verifier", "none"]
+    command += ["--primal-dual-config", f'{{"filename":"{syndrome_file_path}"}}']
+    print(command)
+    stdout, returncode = run_command_get_stdout(command)
+    print("\n" + stdout)
+    assert returncode == 0, "command fails..."
+
+"""
+Run the serial solver of each revision on the same syndrome data
+"""
+
+data_file = os.path.join(script_dir, "data.txt")
+with open(data_file, "w", encoding="utf8") as f:
+    f.write("<revision> <average_decoding_time> <average_decoding_time_per_defect> <decoding_time_relative_dev>\n")
+    for index, (revision, binary) in enumerate(binaries):
+        benchmark_profile_path = os.path.join(tmp_dir, f"{index}.profile")
+        command = [binary, "benchmark", f"{d}", f"{p}", "-r", f"{total_rounds}", "-n", f"{noisy_measurements}"]
+        command += ["--code-type", "error-pattern-reader"]
+        command += ["--code-config", f'{{"filename":"{syndrome_file_path}"}}']
+        command += ["--primal-dual-type", "serial"]
+        command += ["--verifier", "none"]
+        command += ["--benchmark-profiler-output", benchmark_profile_path]
+        print(command)
+        stdout, returncode = run_command_get_stdout(command)
+        print("\n" + stdout)
+        assert returncode == 0, "command fails..."
+        profile = Profile(benchmark_profile_path)
+        print(revision)
+        print("    average_decoding_time:", profile.average_decoding_time())
+        print("    average_decoding_time_per_defect:", profile.average_decoding_time_per_defect())
+        print("    decoding_time_relative_dev:", profile.decoding_time_relative_dev())
+        f.write("%s %.5e %.5e %.3e\n" % (
+            revision.replace(" ", "_"),
+            profile.average_decoding_time(),
+            profile.average_decoding_time_per_defect(),
+            profile.decoding_time_relative_dev(),
+        ))
@@ -7,13 +7,23 @@ fn main() {
         println!("cargo:allow=dropping_references");
     }
 
-    // even if the blossom V library exists, sometimes we don't want to compile it
+    // even if the blossom V library exists, sometimes we don't want to compile it; the tests then verify against the
+    // pure-Rust `blossom_rs` instead. Besides the `remove_blossom_v` feature, the environment variable
+    // `FUSION_BLOSSOM_NO_BLOSSOM_V=1` skips the detection without changing the features, e.g. in CI
     let mut try_include_blossom_v = true;
     if cfg!(feature = "remove_blossom_v") {
         try_include_blossom_v = false;
     }
+    let blossom_v_exists = Path::new("./blossomV/PerfectMatching.h").exists();
+    if blossom_v_exists {
+        // only then does the variable matter; printing it otherwise would stop the rerun when the folder is added
+        println!("cargo:rerun-if-env-changed=FUSION_BLOSSOM_NO_BLOSSOM_V");
+    }
+    if env::var("FUSION_BLOSSOM_NO_BLOSSOM_V").is_ok_and(|value| !value.is_empty() && value != "0") {
+        try_include_blossom_v = false;
+    }
 
-    if try_include_blossom_v && Path::new("./blossomV/PerfectMatching.h").exists() {
+    if try_include_blossom_v && blossom_v_exists {
         println!("cargo:rustc-cfg=feature=\"blossom_v\"");
 
         let target_os = env::var("CARGO_CFG_TARGET_OS");
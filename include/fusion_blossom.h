@@ -0,0 +1,46 @@
+/*
+ * C API of fusion-blossom, enabled by the `c_binding` feature:
+ *     cargo build --release --features c_binding
+ * and link against `target/release/libfusion_blossom.so` (or `.dylib` / `.dll`).
+ * See `src/capi.rs` for the documentation of each function.
+ */
+
+#ifndef FUSION_BLOSSOM_H
+#define FUSION_BLOSSOM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* status codes */
+#define FUSION_BLOSSOM_OK 0
+#define FUSION_BLOSSOM_ERROR (-1)
+
+/* opaque handle of a solver, which keeps the matching of the last solved syndrome */
+typedef struct FusionBlossomSolver fusion_blossom_solver_t;
+
+/* `edges` has `2 * edge_num` elements, the two vertices of each edge; `weights` must be even and non-negative;
+ * returns NULL if the decoding graph is invalid */
+fusion_blossom_solver_t *fusion_blossom_solver_new(size_t vertex_num, size_t edge_num, const size_t *edges,
+                                                   const int64_t *weights, size_t virtual_num,
+                                                   const size_t *virtual_vertices);
+
+/* solve the syndrome, replacing the matching of the previous call; returns FUSION_BLOSSOM_OK, or
+ * FUSION_BLOSSOM_ERROR with an empty matching if the syndrome is invalid */
+int fusion_blossom_solve(fusion_blossom_solver_t *solver, size_t defect_num, const size_t *defect_vertices);
+
+/* copy at most `capacity` matched edge indices and return the total number of matched edges;
+ * returns SIZE_MAX if `solver` is NULL, or `matched_edges` is NULL with a non-zero `capacity` */
+size_t fusion_blossom_get_matching_edges(const fusion_blossom_solver_t *solver, size_t *matched_edges, size_t capacity);
+
+/* release the solver; NULL is ignored */
+void fusion_blossom_free(fusion_blossom_solver_t *solver);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* FUSION_BLOSSOM_H */
@@ -0,0 +1,288 @@
+//! Allocation Tracker
+//!
+//! Sizing an embedded deployment requires the peak memory of the decoder, which is hard to measure from outside of
+//! the process because the decoding graph, the dual nodes and the primal nodes are all allocated on the same heap.
+//! This module installs a wrapping global allocator that counts every allocation and attributes it to the module whose
+//! phase is being executed on the current thread, see [`AllocationScope`]: constructing and growing the dual module,
+//! loading a syndrome and computing the maximum update length are attributed to the dual module, constructing the
+//! primal module and resolving the conflicts to the primal module, and everything else to `other`.
+//! A small header in front of every allocation remembers its module, so that it is released from the same module.
+//!
+//! Enable the feature `track_allocations` to use it; it replaces the global allocator of the whole process, so it
+//! cannot be used together with another `#[global_allocator]`.
+//!
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// the module an allocation is attributed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocationModule {
+    Other,
+    Dual,
+    Primal,
+}
+
+impl AllocationModule {
+    pub const ALL: [Self; 3] = [Self::Other, Self::Dual, Self::Primal];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Other => "other",
+            Self::Dual => "dual",
+            Self::Primal => "primal",
+        }
+    }
+
+    fn from_tag(tag: u8) -> Self {
+        Self::ALL[tag as usize]
+    }
+}
+
+/// the counters of a module, or of the whole process
+struct AllocationCounters {
+    /// the bytes currently allocated
+    current: AtomicUsize,
+    /// the maximum of `current` since the process started
+    peak: AtomicUsize,
+    /// the maximum of `current` since the beginning of the shot
+    shot_peak: AtomicUsize,
+    /// the number of allocations since the process started
+    allocations: AtomicUsize,
+    /// the number of allocations since the beginning of the shot
+    shot_allocations: AtomicUsize,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const EMPTY_COUNTERS: AllocationCounters = AllocationCounters {
+    current: AtomicUsize::new(0),
+    peak: AtomicUsize::new(0),
+    shot_peak: AtomicUsize::new(0),
+    allocations: AtomicUsize::new(0),
+    shot_allocations: AtomicUsize::new(0),
+};
+
+static MODULE_COUNTERS: [AllocationCounters; 3] = [EMPTY_COUNTERS; 3];
+static TOTAL_COUNTERS: AllocationCounters = EMPTY_COUNTERS;
+
+impl AllocationCounters {
+    fn allocate(&self, size: usize) {
+        let current = self.current.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak.fetch_max(current, Ordering::Relaxed);
+        self.shot_peak.fetch_max(current, Ordering::Relaxed);
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        self.shot_allocations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn deallocate(&self, size: usize) {
+        self.current.fetch_sub(size, Ordering::Relaxed);
+    }
+
+    fn begin_shot(&self) {
+        self.shot_peak.store(self.current.load(Ordering::Relaxed), Ordering::Relaxed);
+        self.shot_allocations.store(0, Ordering::Relaxed);
+    }
+
+    fn statistics(&self) -> AllocationStatistics {
+        AllocationStatistics {
+            current_bytes: self.current.load(Ordering::Relaxed),
+            peak_bytes: self.peak.load(Ordering::Relaxed),
+            shot_peak_bytes: self.shot_peak.load(Ordering::Relaxed),
+            allocations: self.allocations.load(Ordering::Relaxed),
+            shot_allocations: self.shot_allocations.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// a snapshot of the allocation counters of a module, reported in the profiler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct AllocationStatistics {
+    /// the bytes currently allocated
+    pub current_bytes: usize,
+    /// the peak allocated bytes since the process started
+    pub peak_bytes: usize,
+    /// the peak allocated bytes since the last [`begin_shot`]
+    pub shot_peak_bytes: usize,
+    /// the number of allocations since the process started
+    pub allocations: usize,
+    /// the number of allocations since the last [`begin_shot`]
+    pub shot_allocations: usize,
+}
+
+thread_local! {
+    static CURRENT_MODULE: Cell<u8> = const { Cell::new(0) };
+}
+
+fn current_module() -> u8 {
+    // the thread local may already be destroyed when a thread exits
+    CURRENT_MODULE.try_with(|module| module.get()).unwrap_or(0)
+}
+
+/// attribute the allocations of the current thread to a module until dropped, restoring the previous module
+#[must_use]
+pub struct AllocationScope {
+    previous: u8,
+}
+
+impl AllocationScope {
+    pub fn enter(module: AllocationModule) -> Self {
+        let previous = current_module();
+        let _ = CURRENT_MODULE.try_with(|current| current.set(module as u8));
+        Self { previous }
+    }
+}
+
+impl Drop for AllocationScope {
+    fn drop(&mut self) {
+        let _ = CURRENT_MODULE.try_with(|current| current.set(self.previous));
+    }
+}
+
+/// start counting the allocations and the peak bytes of a new shot; the counters are shared by the whole process, so
+/// the shot statistics are only meaningful when a single solver is running
+pub fn begin_shot() {
+    for counters in MODULE_COUNTERS.iter() {
+        counters.begin_shot();
+    }
+    TOTAL_COUNTERS.begin_shot();
+}
+
+pub fn statistics(module: AllocationModule) -> AllocationStatistics {
+    MODULE_COUNTERS[module as usize].statistics()
+}
+
+pub fn total_statistics() -> AllocationStatistics {
+    TOTAL_COUNTERS.statistics()
+}
+
+/// the statistics of all modules and of the whole process, reported as `"allocation"` in the profiler report
+pub fn profiler_report() -> serde_json::Value {
+    let mut report = serde_json::Map::new();
+    for module in AllocationModule::ALL {
+        report.insert(module.name().to_string(), json!(statistics(module)));
+    }
+    report.insert("total".to_string(), json!(total_statistics()));
+    serde_json::Value::Object(report)
+}
+
+/// wraps the system allocator and prepends a header to every allocation to remember its module
+pub struct TrackingAllocator;
+
+impl TrackingAllocator {
+    /// the header must keep the alignment of the allocation
+    fn header_size(layout: &Layout) -> usize {
+        std::cmp::max(layout.align(), 16)
+    }
+
+    fn extended_layout(layout: &Layout, size: usize) -> Option<Layout> {
+        let extended_size = size.checked_add(Self::header_size(layout))?;
+        Layout::from_size_align(extended_size, layout.align()).ok()
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let Some(extended_layout) = Self::extended_layout(&layout, layout.size()) else {
+            return std::ptr::null_mut();
+        };
+        let base = System.alloc(extended_layout);
+        if base.is_null() {
+            return base;
+        }
+        let module = current_module();
+        *base = module;
+        MODULE_COUNTERS[module as usize].allocate(layout.size());
+        TOTAL_COUNTERS.allocate(layout.size());
+        base.add(Self::header_size(&layout))
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let base = ptr.sub(Self::header_size(&layout));
+        let module = AllocationModule::from_tag(*base);
+        MODULE_COUNTERS[module as usize].deallocate(layout.size());
+        TOTAL_COUNTERS.deallocate(layout.size());
+        System.dealloc(base, Self::extended_layout(&layout, layout.size()).unwrap());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let header_size = Self::header_size(&layout);
+        let Some(new_extended_layout) = Self::extended_layout(&layout, new_size) else {
+            return std::ptr::null_mut();
+        };
+        let base = ptr.sub(header_size);
+        let new_base = System.realloc(
+            base,
+            Self::extended_layout(&layout, layout.size()).unwrap(),
+            new_extended_layout.size(),
+        );
+        if new_base.is_null() {
+            return new_base;
+        }
+        // the reallocated memory stays in the module that allocated it
+        let counters = &MODULE_COUNTERS[AllocationModule::from_tag(*new_base) as usize];
+        counters.deallocate(layout.size());
+        counters.allocate(new_size);
+        TOTAL_COUNTERS.deallocate(layout.size());
+        TOTAL_COUNTERS.allocate(new_size);
+        new_base.add(header_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL_ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the allocations are attributed to the module of the scope, and released from it even outside of the scope
+    #[test]
+    fn allocation_tracker_scope_1() {
+        // cargo test --features track_allocations allocation_tracker_scope_1 -- --nocapture
+        let size = 1 << 20;
+        let before = statistics(AllocationModule::Primal);
+        let buffer = {
+            let _scope = AllocationScope::enter(AllocationModule::Primal);
+            vec![0u8; size]
+        };
+        let allocated = statistics(AllocationModule::Primal);
+        assert!(allocated.current_bytes >= before.current_bytes + size);
+        assert!(allocated.peak_bytes >= before.current_bytes + size);
+        assert!(allocated.allocations > before.allocations);
+        drop(buffer);
+        assert!(statistics(AllocationModule::Primal).current_bytes + size <= allocated.current_bytes);
+        // the shot counters restart from the current state
+        begin_shot();
+        let shot = statistics(AllocationModule::Primal);
+        assert!(shot.shot_peak_bytes < allocated.current_bytes);
+        let report = profiler_report();
+        assert!(report["total"]["peak_bytes"].as_u64().unwrap() >= size as u64);
+    }
+
+    /// the solver reports the memory of the decoding graph in the dual module and the allocations of every shot
+    #[test]
+    fn allocation_tracker_solver_report_1() {
+        // cargo test --features track_allocations allocation_tracker_solver_report_1 -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        let graph_bytes = statistics(AllocationModule::Dual).current_bytes;
+        assert!(graph_bytes > initializer.vertex_num * std::mem::size_of::<usize>());
+        for seed in 0..3 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            let report = solver.generate_profiler_report();
+            for module in ["dual", "primal", "other", "total"] {
+                let statistics = &report["allocation"][module];
+                assert!(statistics["peak_bytes"].as_u64().unwrap() >= statistics["current_bytes"].as_u64().unwrap());
+            }
+            if !syndrome_pattern.defect_vertices.is_empty() {
+                assert!(report["allocation"]["total"]["shot_allocations"].as_u64().unwrap() > 0);
+            }
+            solver.clear();
+        }
+    }
+}
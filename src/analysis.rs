@@ -0,0 +1,762 @@
+//! Analysis
+//!
+//! Statistical routines built on top of the solvers, e.g. estimating the threshold of a code family by sampling the
+//! logical error rates and fitting the standard finite-size scaling ansatz
+//!     P_L = A + B x + C x^2, where x = (p - p_th) d^(1/nu)
+//!
+
+use super::complete_graph::*;
+use super::example_codes::*;
+use super::mwpm_solver::*;
+use super::primal_module::*;
+use super::util::*;
+use crate::rand_xoshiro::rand_core::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// the code families that have a well-defined logical observable: the parity of the error chain touching the left
+/// boundary (the virtual vertices with the smallest `j` coordinate)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ThresholdCodeFamily {
+    CodeCapacityRepetitionCode,
+    CodeCapacityPlanarCode,
+    /// `d` noisy measurement rounds
+    PhenomenologicalPlanarCode,
+    /// `d` noisy measurement rounds
+    CircuitLevelPlanarCode,
+}
+
+impl ThresholdCodeFamily {
+    pub fn code(&self, d: VertexNum, p: f64) -> Box<dyn ExampleCode> {
+        let max_half_weight = 500;
+        match self {
+            Self::CodeCapacityRepetitionCode => Box::new(CodeCapacityRepetitionCode::new(d, p, max_half_weight)),
+            Self::CodeCapacityPlanarCode => Box::new(CodeCapacityPlanarCode::new(d, p, max_half_weight)),
+            Self::PhenomenologicalPlanarCode => Box::new(PhenomenologicalPlanarCode::new(d, d, p, max_half_weight)),
+            Self::CircuitLevelPlanarCode => Box::new(CircuitLevelPlanarCode::new(d, d, p, max_half_weight)),
+        }
+    }
+}
+
+/// logical error statistics at a single point of the sweep
+#[derive(Debug, Clone, Serialize)]
+pub struct ThresholdSample {
+    pub d: VertexNum,
+    pub p: f64,
+    pub shots: usize,
+    pub logical_errors: usize,
+}
+
+impl ThresholdSample {
+    pub fn logical_error_rate(&self) -> f64 {
+        self.logical_errors as f64 / self.shots as f64
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThresholdEstimate {
+    /// the fitted threshold physical error rate
+    pub threshold: f64,
+    /// standard deviation of the threshold, estimated by bootstrapping the sampled logical error rates
+    pub threshold_error: f64,
+    /// the fitted critical exponent
+    pub nu: f64,
+    pub nu_error: f64,
+    /// the fitted coefficients (A, B, C) of the ansatz
+    pub coefficients: (f64, f64, f64),
+    pub samples: Vec<ThresholdSample>,
+}
+
+/// the edges that touch the left boundary; an error chain (combined with its correction) is a logical error
+/// if and only if it touches the left boundary an odd number of times
+#[allow(clippy::unnecessary_cast)]
+pub fn left_boundary_edges(code: &dyn ExampleCode) -> Vec<bool> {
+    let (vertices, edges) = code.immutable_vertices_edges();
+    let min_j = vertices
+        .iter()
+        .filter(|vertex| vertex.is_virtual)
+        .map(|vertex| vertex.position.j)
+        .fold(f64::INFINITY, f64::min);
+    let is_left = |vertex_index: VertexIndex| {
+        let vertex = &vertices[vertex_index as usize];
+        vertex.is_virtual && (vertex.position.j - min_j).abs() < 1e-6
+    };
+    edges
+        .iter()
+        .map(|edge| is_left(edge.vertices.0) || is_left(edge.vertices.1))
+        .collect()
+}
+
+/// samples the logical error of one shot at a time, so that the number of shots can be decided on the fly
+pub struct LogicalErrorSampler {
+    pub initializer: SolverInitializer,
+    /// whether each edge belongs to each logical operator; by default the single logical operator of a code with
+    /// boundaries, i.e. the edges touching the left boundary, see [`left_boundary_edges`]
+    pub logical_operators: Vec<Vec<bool>>,
+    /// the error and erasure probabilities of each edge
+    pub edge_probabilities: Vec<(f64, f64)>,
+    pub solver: Box<dyn PrimalDualSolver>,
+    pub rng: DeterministicRng,
+}
+
+impl LogicalErrorSampler {
+    pub fn new(code: &dyn ExampleCode, seed: u64) -> Self {
+        let initializer = code.get_initializer();
+        let solver = Box::new(SolverSerial::new(&initializer));
+        Self::new_solver(code, seed, vec![left_boundary_edges(code)], solver)
+    }
+
+    /// decode with any solver and check any logical operators, e.g. the two non-contractible cuts of a toric code
+    pub fn new_solver(
+        code: &dyn ExampleCode,
+        seed: u64,
+        logical_operators: Vec<Vec<bool>>,
+        solver: Box<dyn PrimalDualSolver>,
+    ) -> Self {
+        let initializer = code.get_initializer();
+        let (_vertices, edges) = code.immutable_vertices_edges();
+        for is_logical in logical_operators.iter() {
+            assert_eq!(is_logical.len(), edges.len(), "a logical operator must cover every edge");
+        }
+        Self {
+            initializer,
+            logical_operators,
+            edge_probabilities: edges.iter().map(|edge| (edge.p, edge.pe)).collect(),
+            solver,
+            rng: DeterministicRng::seed_from_u64(seed),
+        }
+    }
+
+    /// sample a random error, returning its syndrome and the edges that actually flipped
+    #[allow(clippy::unnecessary_cast)]
+    pub fn sample_error(&mut self) -> (SyndromePattern, Vec<EdgeIndex>) {
+        let mut error_edges = vec![];
+        let mut erasures = vec![];
+        for (edge_index, &(p, pe)) in self.edge_probabilities.iter().enumerate() {
+            let p = if self.rng.next_f64() < pe {
+                erasures.push(edge_index as EdgeIndex);
+                0.5
+            } else {
+                p
+            };
+            if self.rng.next_f64() < p {
+                error_edges.push(edge_index as EdgeIndex);
+            }
+        }
+        let defect_vertices: Vec<_> = self.initializer.syndrome_of(&error_edges).into_iter().collect();
+        (SyndromePattern::new(defect_vertices, erasures), error_edges)
+    }
+
+    /// sample a random error, decode it and return whether the correction flips each logical operator
+    pub fn sample_flips(&mut self) -> Vec<bool> {
+        let (syndrome_pattern, error_edges) = self.sample_error();
+        self.solver.solve(&syndrome_pattern);
+        let correction = self.solver.subgraph();
+        self.solver.clear();
+        debug_assert_eq!(
+            self.initializer.syndrome_of(&error_edges),
+            self.initializer.syndrome_of(&correction),
+            "the correction doesn't match the syndrome"
+        );
+        logical_flips(&self.logical_operators, &error_edges, &correction)
+    }
+
+    /// sample a random error, decode it and return whether the correction leads to a logical error
+    pub fn sample(&mut self) -> bool {
+        self.sample_flips().into_iter().any(|flipped| flipped)
+    }
+}
+
+/// whether the error chain combined with its correction crosses the logical operator, given as a cut of the decoding
+/// graph, an odd number of times
+#[allow(clippy::unnecessary_cast)]
+pub fn is_logical_error(logical_operator: &[bool], error_edges: &[EdgeIndex], correction: &[EdgeIndex]) -> bool {
+    let crossings = error_edges
+        .iter()
+        .chain(correction.iter())
+        .filter(|&&edge_index| logical_operator[edge_index as usize])
+        .count();
+    crossings % 2 == 1
+}
+
+/// [`is_logical_error`] of each logical operator
+pub fn logical_flips(logical_operators: &[Vec<bool>], error_edges: &[EdgeIndex], correction: &[EdgeIndex]) -> Vec<bool> {
+    logical_operators
+        .iter()
+        .map(|logical_operator| is_logical_error(logical_operator, error_edges, correction))
+        .collect()
+}
+
+/// sample the logical error rate of a code using a deterministic seed
+pub fn sample_logical_errors(code: &dyn ExampleCode, shots: usize, seed: u64) -> usize {
+    let mut sampler = LogicalErrorSampler::new(code, seed);
+    (0..shots).filter(|_| sampler.sample()).count()
+}
+
+/// run the sweep over `distances` x `p_grid` and fit the finite-size scaling ansatz
+pub fn estimate_threshold(
+    code_family: ThresholdCodeFamily,
+    distances: &[VertexNum],
+    p_grid: &[f64],
+    shots: usize,
+) -> ThresholdEstimate {
+    assert!(
+        distances.len() >= 2,
+        "at least two code distances are required to find the crossing"
+    );
+    assert!(p_grid.len() >= 2, "at least two physical error rates are required");
+    assert!(shots > 0, "shots must be positive");
+    let mut samples = vec![];
+    for (d_index, &d) in distances.iter().enumerate() {
+        for (p_index, &p) in p_grid.iter().enumerate() {
+            let code = code_family.code(d, p);
+            let seed = (d_index * p_grid.len() + p_index) as u64;
+            let logical_errors = sample_logical_errors(code.as_ref(), shots, seed);
+            samples.push(ThresholdSample {
+                d,
+                p,
+                shots,
+                logical_errors,
+            });
+        }
+    }
+    fit_threshold(samples)
+}
+
+/// sequential-analysis stopping rule of a Monte-Carlo estimation of a failure rate, e.g. the logical error rate, which
+/// saves most of the shots at the points of a sweep where the rate is high
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EarlyStoppingConfig {
+    /// stop when the width of the confidence interval divided by the estimated rate reaches this value
+    #[serde(default = "early_stopping_default_configs::target_relative_width")]
+    pub target_relative_width: f64,
+    /// never stop before observing this number of failures, because the interval is unreliable with few failures
+    #[serde(default = "early_stopping_default_configs::min_failures")]
+    pub min_failures: usize,
+    /// the stopping rule is first checked after this number of shots
+    #[serde(default = "early_stopping_default_configs::min_shots")]
+    pub min_shots: usize,
+    /// always stop after this number of shots, e.g. when the failure rate is too low to be estimated
+    #[serde(default = "early_stopping_default_configs::max_shots")]
+    pub max_shots: usize,
+    /// the z-score of the confidence level, 1.96 for 95%
+    #[serde(default = "early_stopping_default_configs::confidence_z")]
+    pub confidence_z: f64,
+}
+
+impl Default for EarlyStoppingConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+pub mod early_stopping_default_configs {
+    pub fn target_relative_width() -> f64 {
+        0.2
+    }
+    pub fn min_failures() -> usize {
+        10
+    }
+    pub fn min_shots() -> usize {
+        100
+    }
+    pub fn max_shots() -> usize {
+        100_000_000
+    }
+    pub fn confidence_z() -> f64 {
+        1.96
+    }
+}
+
+/// the outcome of [`run_monte_carlo`]
+#[derive(Debug, Clone, Serialize)]
+pub struct MonteCarloResult {
+    pub shots: usize,
+    pub failures: usize,
+    /// the Wilson score interval of the failure rate
+    pub confidence_interval: (f64, f64),
+    /// whether the target relative width is reached, otherwise it stopped at `max_shots`
+    pub converged: bool,
+    /// the shot counts where the stopping rule was checked
+    pub checkpoints: Vec<usize>,
+}
+
+impl MonteCarloResult {
+    pub fn failure_rate(&self) -> f64 {
+        if self.shots == 0 {
+            0.
+        } else {
+            self.failures as f64 / self.shots as f64
+        }
+    }
+
+    /// the width of the confidence interval relative to the failure rate, infinite if no failure is observed
+    pub fn relative_width(&self) -> f64 {
+        let failure_rate = self.failure_rate();
+        if failure_rate == 0. {
+            f64::INFINITY
+        } else {
+            (self.confidence_interval.1 - self.confidence_interval.0) / failure_rate
+        }
+    }
+}
+
+/// Wilson score interval of a binomial proportion, which stays within [0, 1] and is accurate even with few failures
+pub fn wilson_interval(failures: usize, shots: usize, z: f64) -> (f64, f64) {
+    if shots == 0 {
+        return (0., 1.);
+    }
+    let n = shots as f64;
+    let p = failures as f64 / n;
+    let z2 = z * z;
+    let denominator = 1. + z2 / n;
+    let center = (p + z2 / (2. * n)) / denominator;
+    let half_width = z / denominator * (p * (1. - p) / n + z2 / (4. * n * n)).sqrt();
+    ((center - half_width).max(0.), (center + half_width).min(1.))
+}
+
+/// run Monte-Carlo shots until the failure rate is estimated to the target relative precision; `shot` takes the index
+/// of the shot, usually used as the seed, and returns whether it fails. The stopping rule is only checked at shot counts
+/// spaced by the golden ratio from `min_shots`, so that the number of looks grows logarithmically with the shots,
+/// limiting both the bias of sequential testing and the overhead of checking, while overshooting the necessary shots
+/// by at most 62%
+pub fn run_monte_carlo(config: &EarlyStoppingConfig, mut shot: impl FnMut(u64) -> bool) -> MonteCarloResult {
+    assert!(config.target_relative_width > 0., "target relative width must be positive");
+    assert!(config.confidence_z > 0., "confidence z-score must be positive");
+    let golden_ratio = (1. + 5f64.sqrt()) / 2.;
+    let mut result = MonteCarloResult {
+        shots: 0,
+        failures: 0,
+        confidence_interval: (0., 1.),
+        converged: false,
+        checkpoints: vec![],
+    };
+    let mut next_checkpoint = config.min_shots.clamp(1, config.max_shots.max(1));
+    while result.shots < config.max_shots {
+        if shot(result.shots as u64) {
+            result.failures += 1;
+        }
+        result.shots += 1;
+        if result.shots == next_checkpoint {
+            result.checkpoints.push(result.shots);
+            result.confidence_interval = wilson_interval(result.failures, result.shots, config.confidence_z);
+            if result.failures >= config.min_failures && result.relative_width() <= config.target_relative_width {
+                result.converged = true;
+                return result;
+            }
+            next_checkpoint = ((next_checkpoint as f64 * golden_ratio).round() as usize)
+                .max(next_checkpoint + 1)
+                .min(config.max_shots);
+        }
+    }
+    result.confidence_interval = wilson_interval(result.failures, result.shots, config.confidence_z);
+    result
+}
+
+/// like [`estimate_threshold`], but each point of the sweep is sampled until its logical error rate is estimated to the
+/// target relative precision instead of a fixed number of shots
+pub fn estimate_threshold_early_stopping(
+    code_family: ThresholdCodeFamily,
+    distances: &[VertexNum],
+    p_grid: &[f64],
+    config: &EarlyStoppingConfig,
+) -> ThresholdEstimate {
+    assert!(
+        distances.len() >= 2,
+        "at least two code distances are required to find the crossing"
+    );
+    assert!(p_grid.len() >= 2, "at least two physical error rates are required");
+    let mut samples = vec![];
+    for (d_index, &d) in distances.iter().enumerate() {
+        for (p_index, &p) in p_grid.iter().enumerate() {
+            let code = code_family.code(d, p);
+            let seed = (d_index * p_grid.len() + p_index) as u64;
+            let mut sampler = LogicalErrorSampler::new(code.as_ref(), seed);
+            let result = run_monte_carlo(config, |_| sampler.sample());
+            samples.push(ThresholdSample {
+                d,
+                p,
+                shots: result.shots,
+                logical_errors: result.failures,
+            });
+        }
+    }
+    fit_threshold(samples)
+}
+
+/// fit the finite-size scaling ansatz to the samples, with error bars from a parametric bootstrap
+pub fn fit_threshold(samples: Vec<ThresholdSample>) -> ThresholdEstimate {
+    let points: Vec<_> = samples
+        .iter()
+        .map(|sample| (sample.d as f64, sample.p, sample.logical_error_rate(), sample.shots as f64))
+        .collect();
+    let p_mean = points.iter().map(|(_, p, _, _)| p).sum::<f64>() / points.len() as f64;
+    let (threshold, nu, coefficients) = fit_scaling_ansatz(&points, (p_mean, 1.));
+    // parametric bootstrap: resample the logical error rates with their binomial standard deviation
+    let bootstrap_count = 50;
+    let mut rng = DeterministicRng::seed_from_u64(0);
+    let mut fitted = vec![];
+    for _ in 0..bootstrap_count {
+        let resampled: Vec<_> = points
+            .iter()
+            .map(|&(d, p, rate, shots)| {
+                let sigma = (rate * (1. - rate) / shots).sqrt();
+                let z = (-2. * (1. - rng.next_f64()).ln()).sqrt() * (2. * std::f64::consts::PI * rng.next_f64()).cos();
+                (d, p, (rate + sigma * z).clamp(0., 1.), shots)
+            })
+            .collect();
+        let (threshold, nu, _) = fit_scaling_ansatz(&resampled, (threshold, nu));
+        fitted.push((threshold, nu));
+    }
+    let std_of = |values: Vec<f64>| {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        (values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+    };
+    ThresholdEstimate {
+        threshold,
+        threshold_error: std_of(fitted.iter().map(|(threshold, _)| *threshold).collect()),
+        nu,
+        nu_error: std_of(fitted.iter().map(|(_, nu)| *nu).collect()),
+        coefficients,
+        samples,
+    }
+}
+
+/// the weighted residual of the ansatz given `(threshold, nu)`; A, B, C are solved by linear least squares
+fn scaling_ansatz_residual(points: &[(f64, f64, f64, f64)], threshold: f64, nu: f64) -> (f64, (f64, f64, f64)) {
+    if nu <= 0. {
+        return (f64::INFINITY, (0., 0., 0.));
+    }
+    let mut normal = [[0.; 3]; 3];
+    let mut rhs = [0.; 3];
+    let terms: Vec<_> = points
+        .iter()
+        .map(|&(d, p, rate, shots)| {
+            let x = (p - threshold) * d.powf(1. / nu);
+            // avoid zero variance when no logical error is observed
+            let clamped_rate = rate.clamp(1. / shots, 1. - 1. / shots);
+            let weight = shots / (clamped_rate * (1. - clamped_rate));
+            ([1., x, x * x], rate, weight)
+        })
+        .collect();
+    for (basis, rate, weight) in terms.iter() {
+        for row in 0..3 {
+            for column in 0..3 {
+                normal[row][column] += weight * basis[row] * basis[column];
+            }
+            rhs[row] += weight * basis[row] * rate;
+        }
+    }
+    let Some(solution) = solve_linear_3x3(normal, rhs) else {
+        return (f64::INFINITY, (0., 0., 0.));
+    };
+    let residual = terms
+        .iter()
+        .map(|(basis, rate, weight)| {
+            let predicted = solution[0] * basis[0] + solution[1] * basis[1] + solution[2] * basis[2];
+            weight * (predicted - rate).powi(2)
+        })
+        .sum();
+    (residual, (solution[0], solution[1], solution[2]))
+}
+
+/// Gaussian elimination with partial pivoting
+#[allow(clippy::needless_range_loop)]
+fn solve_linear_3x3(mut matrix: [[f64; 3]; 3], mut rhs: [f64; 3]) -> Option<[f64; 3]> {
+    for column in 0..3 {
+        let pivot = (column..3).max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))?;
+        if matrix[pivot][column].abs() < 1e-300 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+        for row in column + 1..3 {
+            let factor = matrix[row][column] / matrix[column][column];
+            for k in column..3 {
+                matrix[row][k] -= factor * matrix[column][k];
+            }
+            rhs[row] -= factor * rhs[column];
+        }
+    }
+    let mut solution = [0.; 3];
+    for row in (0..3).rev() {
+        let mut value = rhs[row];
+        for k in row + 1..3 {
+            value -= matrix[row][k] * solution[k];
+        }
+        solution[row] = value / matrix[row][row];
+    }
+    Some(solution)
+}
+
+/// minimize the residual over `(threshold, nu)` using the Nelder-Mead simplex method
+fn fit_scaling_ansatz(points: &[(f64, f64, f64, f64)], initial: (f64, f64)) -> (f64, f64, (f64, f64, f64)) {
+    let evaluate = |vertex: [f64; 2]| scaling_ansatz_residual(points, vertex[0], vertex[1]).0;
+    let p_scale = initial.0.abs().max(1e-6) * 0.1;
+    let mut simplex = [
+        [initial.0, initial.1],
+        [initial.0 + p_scale, initial.1],
+        [initial.0, initial.1 + 0.2],
+    ];
+    let mut values = simplex.map(evaluate);
+    for _ in 0..1000 {
+        let mut order = [0, 1, 2];
+        order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+        simplex = order.map(|index| simplex[index]);
+        values = order.map(|index| values[index]);
+        if (values[2] - values[0]).abs() <= 1e-12 * (1. + values[0].abs()) {
+            break;
+        }
+        let centroid = [(simplex[0][0] + simplex[1][0]) / 2., (simplex[0][1] + simplex[1][1]) / 2.];
+        let along = |factor: f64| {
+            [
+                centroid[0] + factor * (simplex[2][0] - centroid[0]),
+                centroid[1] + factor * (simplex[2][1] - centroid[1]),
+            ]
+        };
+        let reflected = along(-1.);
+        let reflected_value = evaluate(reflected);
+        if reflected_value < values[0] {
+            let expanded = along(-2.);
+            let expanded_value = evaluate(expanded);
+            if expanded_value < reflected_value {
+                (simplex[2], values[2]) = (expanded, expanded_value);
+            } else {
+                (simplex[2], values[2]) = (reflected, reflected_value);
+            }
+        } else if reflected_value < values[1] {
+            (simplex[2], values[2]) = (reflected, reflected_value);
+        } else {
+            let contracted = along(0.5);
+            let contracted_value = evaluate(contracted);
+            if contracted_value < values[2] {
+                (simplex[2], values[2]) = (contracted, contracted_value);
+            } else {
+                // shrink towards the best vertex
+                for index in 1..3 {
+                    simplex[index] = [
+                        (simplex[0][0] + simplex[index][0]) / 2.,
+                        (simplex[0][1] + simplex[index][1]) / 2.,
+                    ];
+                    values[index] = evaluate(simplex[index]);
+                }
+            }
+        }
+    }
+    let best = (0..3).min_by(|&a, &b| values[a].total_cmp(&values[b])).unwrap();
+    let [threshold, nu] = simplex[best];
+    let (_, coefficients) = scaling_ansatz_residual(points, threshold, nu);
+    (threshold, nu, coefficients)
+}
+
+/// distribution of the graph distances between matched pairs, accumulated over a batch of shots; useful to validate
+/// noise models (the tail should decay exponentially) and to choose the window size of sliding-window decoding
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchedPairDistanceHistogram {
+    /// the decoding graph with the original weights; erasures and dynamic weights of individual shots are not considered
+    #[serde(skip)]
+    pub complete_graph: CompleteGraph,
+    /// the width of each weight bin
+    pub bin_width: Weight,
+    /// the number of shots in this batch
+    pub shots: usize,
+    /// the number of peer matchings in each weight bin, indexed by `distance / bin_width`
+    pub peer_weight_bins: BTreeMap<Weight, usize>,
+    /// the number of boundary matchings in each weight bin, indexed by `distance / bin_width`
+    pub boundary_weight_bins: BTreeMap<Weight, usize>,
+    /// the number of peer matchings indexed by the number of edges on the shortest path
+    pub peer_hop_counts: BTreeMap<usize, usize>,
+    /// the number of boundary matchings indexed by the number of edges on the shortest path
+    pub boundary_hop_counts: BTreeMap<usize, usize>,
+}
+
+impl MatchedPairDistanceHistogram {
+    pub fn new(initializer: &SolverInitializer, bin_width: Weight) -> Self {
+        assert!(bin_width > 0, "bin width must be positive");
+        Self {
+            complete_graph: CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges),
+            bin_width,
+            shots: 0,
+            peer_weight_bins: BTreeMap::new(),
+            boundary_weight_bins: BTreeMap::new(),
+            peer_hop_counts: BTreeMap::new(),
+            boundary_hop_counts: BTreeMap::new(),
+        }
+    }
+
+    /// start a new batch
+    pub fn clear(&mut self) {
+        self.shots = 0;
+        self.peer_weight_bins.clear();
+        self.boundary_weight_bins.clear();
+        self.peer_hop_counts.clear();
+        self.boundary_hop_counts.clear();
+    }
+
+    /// add the matched pairs of a single shot
+    pub fn add_perfect_matching(&mut self, perfect_matching: &PerfectMatching) {
+        self.shots += 1;
+        for (node_1, node_2) in perfect_matching.peer_matchings.iter() {
+            let (path, weight) = self
+                .complete_graph
+                .get_path(node_1.get_representative_vertex(), node_2.get_representative_vertex());
+            *self.peer_weight_bins.entry(weight / self.bin_width).or_insert(0) += 1;
+            *self.peer_hop_counts.entry(path.len()).or_insert(0) += 1;
+        }
+        for (node, virtual_vertex) in perfect_matching.virtual_matchings.iter() {
+            let (path, weight) = self
+                .complete_graph
+                .get_path(node.get_representative_vertex(), *virtual_vertex);
+            *self.boundary_weight_bins.entry(weight / self.bin_width).or_insert(0) += 1;
+            *self.boundary_hop_counts.entry(path.len()).or_insert(0) += 1;
+        }
+    }
+
+    /// export the histogram in CSV format, one row per weight bin
+    pub fn to_csv(&self) -> String {
+        let mut csv = "bin_start,bin_end,peer_count,boundary_count\n".to_string();
+        let bins: std::collections::BTreeSet<Weight> = self
+            .peer_weight_bins
+            .keys()
+            .chain(self.boundary_weight_bins.keys())
+            .cloned()
+            .collect();
+        for bin in bins {
+            csv += &format!(
+                "{},{},{},{}\n",
+                bin * self.bin_width,
+                (bin + 1) * self.bin_width,
+                self.peer_weight_bins.get(&bin).unwrap_or(&0),
+                self.boundary_weight_bins.get(&bin).unwrap_or(&0),
+            );
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// the fit recovers the parameters of synthetic data that exactly follow the ansatz
+    #[test]
+    fn analysis_fit_threshold_1() {
+        // cargo test analysis_fit_threshold_1 -- --nocapture
+        let (threshold, nu) = (0.1, 1.3);
+        let shots = 1_000_000;
+        let mut samples = vec![];
+        for d in [5, 7, 9] {
+            for p in [0.08, 0.09, 0.1, 0.11, 0.12] {
+                let x = (p - threshold) * (d as f64).powf(1. / nu);
+                let rate = 0.2 + 0.5 * x + 0.3 * x * x;
+                samples.push(ThresholdSample {
+                    d,
+                    p,
+                    shots,
+                    logical_errors: (rate * shots as f64).round() as usize,
+                });
+            }
+        }
+        let estimate = fit_threshold(samples);
+        println!("{estimate:?}");
+        assert!((estimate.threshold - threshold).abs() < 1e-3);
+        assert!((estimate.nu - nu).abs() < 0.05);
+        assert!(estimate.threshold_error < 1e-2);
+    }
+
+    /// the code capacity planar code has a threshold around 10.3%
+    #[test]
+    fn analysis_estimate_threshold_1() {
+        // cargo test analysis_estimate_threshold_1 -- --nocapture
+        let estimate = estimate_threshold(
+            ThresholdCodeFamily::CodeCapacityPlanarCode,
+            &[3, 5, 7],
+            &[0.07, 0.09, 0.11, 0.13],
+            1000,
+        );
+        println!("threshold: {} +- {}", estimate.threshold, estimate.threshold_error);
+        assert!(estimate.threshold > 0.07 && estimate.threshold < 0.14);
+    }
+
+    /// matched pairs are binned by their distances
+    #[test]
+    fn analysis_matched_pair_distance_histogram_1() {
+        // cargo test analysis_matched_pair_distance_histogram_1 -- --nocapture
+        let code = CodeCapacityRepetitionCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        let mut histogram = MatchedPairDistanceHistogram::new(&initializer, 1000);
+        for defect_vertices in [vec![2, 4], vec![0], vec![2, 3]] {
+            solver.solve(&SyndromePattern::new_vertices(defect_vertices));
+            histogram.add_perfect_matching(&solver.perfect_matching());
+            solver.clear();
+        }
+        assert_eq!(histogram.shots, 3);
+        assert_eq!(histogram.peer_hop_counts, BTreeMap::from([(1, 1), (2, 1)]));
+        assert_eq!(histogram.boundary_hop_counts, BTreeMap::from([(1, 1)]));
+        assert_eq!(histogram.peer_weight_bins, BTreeMap::from([(1, 1), (2, 1)]));
+        assert_eq!(
+            histogram.to_csv(),
+            "bin_start,bin_end,peer_count,boundary_count\n1000,2000,1,1\n2000,3000,1,0\n"
+        );
+        histogram.clear();
+        assert_eq!(histogram.shots, 0);
+    }
+
+    /// test the Monte-Carlo driver stops once the failure rate is precise enough
+    #[test]
+    fn analysis_monte_carlo_early_stopping_1() {
+        // cargo test analysis_monte_carlo_early_stopping_1 -- --nocapture
+        let (lower, upper) = wilson_interval(10, 100, 1.96);
+        assert!((lower - 0.0552).abs() < 1e-4 && (upper - 0.1744).abs() < 1e-4);
+        assert_eq!(wilson_interval(0, 0, 1.96), (0., 1.));
+        let config = EarlyStoppingConfig::default();
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let result = run_monte_carlo(&config, |_| rng.next_f64() < 0.05);
+        println!("{result:?}");
+        assert!(result.converged);
+        assert!(result.relative_width() <= config.target_relative_width);
+        assert_eq!(result.checkpoints.last(), Some(&result.shots));
+        assert_eq!(result.checkpoints[0], config.min_shots);
+        for window in result.checkpoints.windows(2) {
+            let ratio = window[1] as f64 / window[0] as f64;
+            assert!((1.6..1.64).contains(&ratio));
+        }
+        // the estimation should be consistent with the true rate
+        assert!(result.confidence_interval.0 < 0.05 && 0.05 < result.confidence_interval.1);
+        // a zero failure rate can never be estimated to a relative precision
+        let config = EarlyStoppingConfig {
+            max_shots: 1000,
+            ..Default::default()
+        };
+        let result = run_monte_carlo(&config, |_| false);
+        assert!(!result.converged);
+        assert_eq!(result.shots, 1000);
+        assert_eq!(result.checkpoints.last(), Some(&1000));
+    }
+
+    /// the early-stopping sweep spends fewer shots where the logical error rate is high
+    #[test]
+    fn analysis_estimate_threshold_early_stopping_1() {
+        // cargo test analysis_estimate_threshold_early_stopping_1 -- --nocapture
+        let config = EarlyStoppingConfig {
+            max_shots: 20000,
+            ..Default::default()
+        };
+        let estimate = estimate_threshold_early_stopping(
+            ThresholdCodeFamily::CodeCapacityPlanarCode,
+            &[3, 5, 7],
+            &[0.07, 0.09, 0.11, 0.13],
+            &config,
+        );
+        println!("threshold: {} +- {}", estimate.threshold, estimate.threshold_error);
+        assert!(estimate.threshold > 0.07 && estimate.threshold < 0.14);
+        let shots: Vec<usize> = estimate.samples.iter().map(|sample| sample.shots).collect();
+        println!("shots: {shots:?}");
+        // at the same distance, higher physical error rates converge faster
+        for d_shots in shots.chunks(4) {
+            assert!(d_shots[0] >= d_shots[3]);
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! Benchmark Results Database
+//!
+//! An sqlite store of the benchmark summaries keyed by the config hash and the crate version, so that the performance
+//! and accuracy of the same benchmark can be compared across versions; enabled by the `results_db` feature
+//!
+
+use super::util::*;
+use crate::chrono::Local;
+use crate::rusqlite::{params, Connection, OptionalExtension, Result};
+
+/// the history of benchmark summaries; each summary is a set of named metrics of a benchmark, identified by the hash of
+/// its effective config (see [`OutputFingerprint`]), recorded by a crate version. All metrics are lower-is-better,
+/// e.g. the decoding time or the logical error rate, which is what [`ResultsDb::regressions`] assumes
+pub struct ResultsDb {
+    connection: Connection,
+}
+
+/// a metric of a benchmark that got worse from one version to another, see [`ResultsDb::regressions`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricRegression {
+    pub config_hash: String,
+    pub metric: String,
+    /// the shot-weighted average of the baseline version
+    pub baseline: f64,
+    /// the shot-weighted average of the compared version
+    pub current: f64,
+    /// `(current - baseline) / baseline`
+    pub relative_change: f64,
+}
+
+impl ResultsDb {
+    /// open the database at the given path, creating it if not exists
+    pub fn open(path: &str) -> Result<Self> {
+        Self::new(Connection::open(path)?)
+    }
+
+    /// a temporary database that is dropped with this object
+    pub fn open_in_memory() -> Result<Self> {
+        Self::new(Connection::open_in_memory()?)
+    }
+
+    fn new(connection: Connection) -> Result<Self> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS configs (
+                config_hash TEXT PRIMARY KEY,
+                config TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS results (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                version TEXT NOT NULL,
+                config_hash TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                value REAL NOT NULL,
+                shots INTEGER NOT NULL,
+                recorded_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS results_key ON results (config_hash, metric, version);",
+        )?;
+        Ok(Self { connection })
+    }
+
+    /// remember the full config of a hash, so that the benchmark can be reproduced from the database alone
+    pub fn record_config(&self, fingerprint: &OutputFingerprint, config: &serde_json::Value) -> Result<()> {
+        self.connection.execute(
+            "INSERT OR IGNORE INTO configs (config_hash, config) VALUES (?1, ?2)",
+            params![fingerprint.config_hash, config.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// the config recorded by [`Self::record_config`]
+    pub fn config(&self, config_hash: &str) -> Result<Option<serde_json::Value>> {
+        let config: Option<String> = self
+            .connection
+            .query_row(
+                "SELECT config FROM configs WHERE config_hash = ?1",
+                params![config_hash],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(config.map(|config| serde_json::from_str(&config).expect("corrupted config")))
+    }
+
+    /// record a summary of `shots` shots of the benchmark; the metrics that are not finite, e.g. the time per defect
+    /// when there is no defect, are skipped
+    pub fn record(&mut self, fingerprint: &OutputFingerprint, metrics: &[(&str, f64)], shots: usize) -> Result<()> {
+        let recorded_at = Local::now().to_rfc3339();
+        let transaction = self.connection.transaction()?;
+        for (metric, value) in metrics.iter().filter(|(_, value)| value.is_finite()) {
+            transaction.execute(
+                "INSERT INTO results (version, config_hash, metric, value, shots, recorded_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    fingerprint.version,
+                    fingerprint.config_hash,
+                    metric,
+                    value,
+                    shots as i64,
+                    recorded_at
+                ],
+            )?;
+        }
+        transaction.commit()
+    }
+
+    /// all the versions in the order of their first record
+    pub fn versions(&self) -> Result<Vec<String>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT version FROM results GROUP BY version ORDER BY MIN(id)")?;
+        let versions = statement.query_map([], |row| row.get(0))?;
+        versions.collect()
+    }
+
+    /// the shot-weighted average of a metric of a benchmark in each version, in the order of their first record
+    pub fn history(&self, config_hash: &str, metric: &str) -> Result<Vec<(String, f64)>> {
+        let mut statement = self.connection.prepare(
+            "SELECT version, SUM(value * shots) / SUM(shots) FROM results
+            WHERE config_hash = ?1 AND metric = ?2 GROUP BY version ORDER BY MIN(id)",
+        )?;
+        let history = statement.query_map(params![config_hash, metric], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        history.collect()
+    }
+
+    /// the metrics of all benchmarks recorded by both versions that are worse in `version` than in `baseline_version`
+    /// by more than `tolerance`, relatively; sorted from the largest regression
+    pub fn regressions(&self, baseline_version: &str, version: &str, tolerance: f64) -> Result<Vec<MetricRegression>> {
+        let mut statement = self.connection.prepare(
+            "SELECT baseline.config_hash, baseline.metric, baseline.value, current.value FROM
+            (SELECT config_hash, metric, SUM(value * shots) / SUM(shots) AS value FROM results
+                WHERE version = ?1 GROUP BY config_hash, metric) AS baseline
+            JOIN (SELECT config_hash, metric, SUM(value * shots) / SUM(shots) AS value FROM results
+                WHERE version = ?2 GROUP BY config_hash, metric) AS current
+            ON baseline.config_hash = current.config_hash AND baseline.metric = current.metric",
+        )?;
+        let rows = statement.query_map(params![baseline_version, version], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get(2)?, row.get(3)?))
+        })?;
+        let mut regressions = vec![];
+        for row in rows {
+            let (config_hash, metric, baseline, current): (String, String, f64, f64) = row?;
+            let relative_change = if baseline == 0. {
+                if current == 0. {
+                    0.
+                } else {
+                    f64::INFINITY
+                }
+            } else {
+                (current - baseline) / baseline
+            };
+            if relative_change > tolerance {
+                regressions.push(MetricRegression {
+                    config_hash,
+                    metric,
+                    baseline,
+                    current,
+                    relative_change,
+                });
+            }
+        }
+        regressions.sort_by(|a, b| b.relative_change.total_cmp(&a.relative_change));
+        Ok(regressions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint(version: &str, config: &serde_json::Value) -> OutputFingerprint {
+        OutputFingerprint {
+            version: version.to_string(),
+            ..OutputFingerprint::new(config)
+        }
+    }
+
+    #[test]
+    fn benchmark_results_db_1() {
+        // cargo test --features results_db benchmark_results_db_1 -- --nocapture
+        let mut db = ResultsDb::open_in_memory().unwrap();
+        let (config_a, config_b) = (json!({ "d": 5 }), json!({ "d": 7 }));
+        let (a_1, b_1) = (fingerprint("0.2.9", &config_a), fingerprint("0.2.9", &config_b));
+        let (a_2, b_2) = (fingerprint("0.2.10", &config_a), fingerprint("0.2.10", &config_b));
+        db.record_config(&a_1, &config_a).unwrap();
+        db.record(&a_1, &[("round_time", 1.0), ("logical_error_rate", 0.1)], 100)
+            .unwrap();
+        db.record(&a_1, &[("round_time", 2.0)], 300).unwrap();
+        db.record(&b_1, &[("round_time", 4.0), ("defect_time", f64::NAN)], 100)
+            .unwrap();
+        db.record(&a_2, &[("round_time", 1.8), ("logical_error_rate", 0.1)], 100)
+            .unwrap();
+        db.record(&b_2, &[("round_time", 3.0)], 100).unwrap();
+        assert!(db.history(&b_1.config_hash, "defect_time").unwrap().is_empty());
+        assert_eq!(db.config(&a_1.config_hash).unwrap(), Some(config_a.clone()));
+        assert_eq!(db.config(&b_1.config_hash).unwrap(), None);
+        assert_eq!(db.versions().unwrap(), vec!["0.2.9".to_string(), "0.2.10".to_string()]);
+        assert_eq!(
+            db.history(&a_1.config_hash, "round_time").unwrap(),
+            vec![("0.2.9".to_string(), 1.75), ("0.2.10".to_string(), 1.8)]
+        );
+        // only the benchmark b got slower
+        let regressions = db.regressions("0.2.10", "0.2.9", 0.1).unwrap();
+        assert_eq!(regressions.len(), 1);
+        assert_eq!(regressions[0].config_hash, b_1.config_hash);
+        assert_eq!(regressions[0].metric, "round_time");
+        assert!((regressions[0].relative_change - 1. / 3.).abs() < 1e-9);
+        // the benchmark a got slightly slower, within the tolerance
+        assert!(db.regressions("0.2.9", "0.2.10", 0.05).unwrap().is_empty());
+        assert_eq!(db.regressions("0.2.9", "0.2.10", 0.).unwrap().len(), 1);
+    }
+}
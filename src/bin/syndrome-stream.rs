@@ -0,0 +1,300 @@
+// test the real-time pipeline (ingest -> decode -> emit correction) without hardware, either through a pipe:
+//   cargo run --release --bin syndrome-stream -- generate 5 0.01 --shots 100 | cargo run --release --bin syndrome-stream -- decode
+// or through a socket:
+//   cargo run --release --bin syndrome-stream -- decode --listen 127.0.0.1:9000
+//   cargo run --release --bin syndrome-stream -- generate 5 0.01 --shots 100 --connect 127.0.0.1:9000
+
+use clap::{Parser, Subcommand, ValueEnum};
+use fusion_blossom::example_codes::*;
+use fusion_blossom::mwpm_solver::*;
+use fusion_blossom::util::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+#[derive(Parser, Clone)]
+#[clap(author = clap::crate_authors!(", "))]
+#[clap(version = env!("CARGO_PKG_VERSION"))]
+#[clap(about = "Generate and decode timed syndrome streams, one JSON message per line")]
+#[clap(color = clap::ColorChoice::Auto)]
+#[clap(propagate_version = true)]
+#[clap(subcommand_required = true)]
+#[clap(arg_required_else_help = true)]
+pub struct SyndromeStreamCli {
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand, Clone)]
+enum Commands {
+    /// emit the measurement rounds of random shots at a fixed rate
+    Generate(GenerateParameters),
+    /// decode the rounds with a sliding-window [`SolverStream`] and emit the corrections as soon as they are committed
+    Decode(DecodeParameters),
+}
+
+#[derive(Parser, Clone)]
+struct GenerateParameters {
+    /// code distance
+    #[clap(value_parser)]
+    d: VertexNum,
+    /// physical error rate
+    #[clap(value_parser)]
+    p: f64,
+    /// measurement rounds of each shot
+    #[clap(short = 'n', long, default_value_t = 10)]
+    rounds: VertexNum,
+    /// example code type
+    #[clap(short = 'c', long, value_enum, default_value_t = StreamCodeType::PhenomenologicalPlanarCode)]
+    code_type: StreamCodeType,
+    /// maximum half weight of edges
+    #[clap(long, default_value_t = 500)]
+    max_half_weight: Weight,
+    /// the number of shots per second, whose rounds are evenly spaced in time; 0 to emit as fast as possible
+    #[clap(long, default_value_t = 1000.)]
+    shot_rate: f64,
+    /// the number of shots to emit, 0 to emit forever
+    #[clap(short = 's', long, default_value_t = 1)]
+    shots: usize,
+    /// the random seed of the first shot, incremented for each shot
+    #[clap(long, default_value_t = 0)]
+    seed: u64,
+    /// connect to this TCP address instead of writing to stdout
+    #[clap(long)]
+    connect: Option<String>,
+}
+
+#[derive(Parser, Clone)]
+struct DecodeParameters {
+    /// accept a single connection at this TCP address instead of reading from stdin
+    #[clap(long)]
+    listen: Option<String>,
+    /// the number of rounds committed each time the window is full
+    #[clap(long, default_value_t = 5)]
+    commit_rounds: usize,
+    /// the number of rounds decoded but not committed after the committed ones
+    #[clap(long, default_value_t = 5)]
+    buffer_rounds: usize,
+    /// the configuration of the parallel solver decoding each window
+    #[clap(long, default_value_t = ("{}").to_string())]
+    primal_dual_config: String,
+}
+
+/// the example codes whose vertices are ordered round by round, as required by [`SolverStream`]
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+#[allow(clippy::enum_variant_names)] // the same names as `--code-type` of the main binary
+enum StreamCodeType {
+    PhenomenologicalPlanarCode,
+    PhenomenologicalRotatedCode,
+    CircuitLevelPlanarCode,
+}
+
+/// a line of the syndrome stream
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamMessage {
+    /// the first message, from which the decoder builds the same decoding graph
+    Header {
+        code_type: StreamCodeType,
+        d: VertexNum,
+        rounds: VertexNum,
+        p: f64,
+        max_half_weight: Weight,
+    },
+    /// the defect vertices of a measurement round, given by the vertex indices in the round
+    Round {
+        shot: usize,
+        round: usize,
+        defect_vertices: Vec<VertexIndex>,
+    },
+}
+
+impl StreamCodeType {
+    fn build(&self, d: VertexNum, rounds: VertexNum, p: f64, max_half_weight: Weight) -> Box<dyn ExampleCode> {
+        assert!(
+            rounds >= 2,
+            "the stream needs at least 2 rounds per shot to learn the repeating structure"
+        );
+        let noisy_measurements = rounds - 1;
+        match self {
+            Self::PhenomenologicalPlanarCode => {
+                Box::new(PhenomenologicalPlanarCode::new(d, noisy_measurements, p, max_half_weight))
+            }
+            Self::PhenomenologicalRotatedCode => {
+                Box::new(PhenomenologicalRotatedCode::new(d, noisy_measurements, p, max_half_weight))
+            }
+            Self::CircuitLevelPlanarCode => Box::new(CircuitLevelPlanarCode::new(d, noisy_measurements, p, max_half_weight)),
+        }
+    }
+}
+
+fn round_vertex_num(code: &dyn ExampleCode, rounds: VertexNum) -> VertexNum {
+    let vertex_num = code.vertex_num();
+    assert_eq!(vertex_num % rounds, 0, "the vertices must be evenly split into rounds");
+    vertex_num / rounds
+}
+
+fn write_message(output: &mut dyn Write, message: &StreamMessage) -> std::io::Result<()> {
+    let line = serde_json::to_string(message).unwrap();
+    writeln!(output, "{line}")
+}
+
+impl GenerateParameters {
+    #[allow(clippy::unnecessary_cast)]
+    fn run(self) {
+        let mut code = self.code_type.build(self.d, self.rounds, self.p, self.max_half_weight);
+        let round_vertex_num = round_vertex_num(code.as_ref(), self.rounds);
+        let mut output: Box<dyn Write> = match &self.connect {
+            Some(address) => {
+                let stream = TcpStream::connect(address).expect("cannot connect to the decoder");
+                stream.set_nodelay(true).unwrap();
+                Box::new(LineWriter::new(stream))
+            }
+            None => Box::new(LineWriter::new(std::io::stdout())),
+        };
+        write_message(
+            output.as_mut(),
+            &StreamMessage::Header {
+                code_type: self.code_type,
+                d: self.d,
+                rounds: self.rounds,
+                p: self.p,
+                max_half_weight: self.max_half_weight,
+            },
+        )
+        .expect("cannot write the header");
+        let round_interval = if self.shot_rate > 0. {
+            Some(Duration::from_secs_f64(1. / (self.shot_rate * self.rounds as f64)))
+        } else {
+            None
+        };
+        let start_time = Instant::now();
+        let mut round_count: u32 = 0;
+        let mut shot = 0;
+        while self.shots == 0 || shot < self.shots {
+            let syndrome_pattern = code.generate_random_errors(self.seed + shot as u64);
+            let mut rounds = vec![vec![]; self.rounds as usize];
+            for &vertex_index in syndrome_pattern.defect_vertices.iter() {
+                rounds[(vertex_index / round_vertex_num) as usize].push(vertex_index % round_vertex_num);
+            }
+            for (round, defect_vertices) in rounds.into_iter().enumerate() {
+                if let Some(round_interval) = round_interval {
+                    // a round is measured at a fixed time regardless of how long the previous ones took to emit
+                    let ready_time = start_time + round_interval * round_count;
+                    let now = Instant::now();
+                    if ready_time > now {
+                        std::thread::sleep(ready_time - now);
+                    }
+                }
+                let message = StreamMessage::Round {
+                    shot,
+                    round,
+                    defect_vertices,
+                };
+                if write_message(output.as_mut(), &message).is_err() {
+                    return; // the receiver is closed
+                }
+                round_count += 1;
+            }
+            shot += 1;
+        }
+    }
+}
+
+impl DecodeParameters {
+    #[allow(clippy::unnecessary_cast)]
+    fn run(self) {
+        let input: Box<dyn BufRead> = match &self.listen {
+            Some(address) => {
+                let listener = TcpListener::bind(address).expect("cannot listen to the address");
+                let (stream, _) = listener.accept().expect("cannot accept the generator");
+                stream.set_nodelay(true).unwrap();
+                Box::new(BufReader::new(stream))
+            }
+            None => Box::new(BufReader::new(std::io::stdin())),
+        };
+        let mut output = LineWriter::new(std::io::stdout());
+        let mut lines = input.lines();
+        let header = lines.next().expect("empty stream").expect("cannot read the stream");
+        let (mut stream, rounds) = match serde_json::from_str(&header).expect("invalid message") {
+            StreamMessage::Header {
+                code_type,
+                d,
+                rounds,
+                p,
+                max_half_weight,
+            } => {
+                let code = code_type.build(d, rounds, p, max_half_weight);
+                let config = StreamConfig {
+                    commit_rounds: self.commit_rounds,
+                    buffer_rounds: self.buffer_rounds,
+                    primal_dual_config: serde_json::from_str(&self.primal_dual_config).unwrap(),
+                };
+                let round_vertex_num = round_vertex_num(code.as_ref(), rounds);
+                (SolverStream::new(&code.get_initializer(), round_vertex_num, config), rounds)
+            }
+            message => panic!("the stream must start with a header, found {message:?}"),
+        };
+        let mut latencies = vec![];
+        for line in lines {
+            let line = line.expect("cannot read the stream");
+            let (shot, round, defect_vertices) = match serde_json::from_str(&line).expect("invalid message") {
+                StreamMessage::Round {
+                    shot,
+                    round,
+                    defect_vertices,
+                } => (shot, round, defect_vertices),
+                message => panic!("unexpected message in the middle of the stream: {message:?}"),
+            };
+            let receive_time = Instant::now();
+            let mut commits: Vec<_> = stream
+                .push_round(&SyndromePattern::new_vertices(defect_vertices))
+                .into_iter()
+                .collect();
+            let is_last_round = round + 1 == rounds as usize;
+            if is_last_round {
+                commits.extend(stream.flush());
+                stream.clear();
+            }
+            // the latency of a commit counts from receiving the round that triggers it
+            let latency = receive_time.elapsed().as_secs_f64();
+            for commit in commits {
+                latencies.push(latency);
+                let line = json!({
+                    "shot": shot,
+                    "start_round": commit.start_round,
+                    "end_round": commit.end_round,
+                    "edges": commit.edges,
+                    "latency": latency,
+                });
+                writeln!(output, "{line}").expect("stdout is closed");
+            }
+        }
+        if !latencies.is_empty() {
+            let average = latencies.iter().sum::<f64>() / latencies.len() as f64;
+            let maximum = latencies.iter().cloned().fold(0., f64::max);
+            eprintln!(
+                "{} commits, latency: average {:.3e}s, maximum {:.3e}s",
+                latencies.len(),
+                average,
+                maximum
+            );
+        }
+    }
+}
+
+impl SyndromeStreamCli {
+    pub fn run(self) {
+        match self.command {
+            Commands::Generate(parameters) => parameters.run(),
+            Commands::Decode(parameters) => parameters.run(),
+        }
+    }
+}
+
+fn main() {
+    SyndromeStreamCli::parse().run();
+}
@@ -0,0 +1,651 @@
+//! Pure-Rust Blossom
+//!
+//! An exact minimum-weight perfect matching without the blossom V library, so that all the optimality tests run
+//! without the C++ dependency and its license; it's the fallback of [`crate::blossom_v`] when the library is absent,
+//! and can be chosen explicitly by [`crate::blossom_rs_mwpm`] or `--verifier blossom-rs`
+//!
+
+/// a pure-Rust exact minimum-weight perfect matching with the same interface as the blossom V library, returning the
+/// peer of each vertex; it panics if there is no perfect matching. It's Edmonds' primal-dual blossom algorithm in
+/// O(n^3) time following Galil's description, which is fast enough for the syndrome graphs in the verifiers
+pub fn minimum_weight_perfect_matching(node_num: usize, weighted_edges: &[(usize, usize, u32)]) -> Vec<usize> {
+    let mate = maximum_cardinality_matching(node_num, weighted_edges);
+    for (vertex, &peer) in mate.iter().enumerate() {
+        assert!(peer != NONE, "no perfect matching exists: vertex {} is unmatched", vertex);
+    }
+    mate
+}
+
+/// the same as [`minimum_weight_perfect_matching`] but returns `None` if there is no perfect matching
+pub fn try_minimum_weight_perfect_matching(node_num: usize, weighted_edges: &[(usize, usize, u32)]) -> Option<Vec<usize>> {
+    let mate = maximum_cardinality_matching(node_num, weighted_edges);
+    mate.iter().all(|&peer| peer != NONE).then_some(mate)
+}
+
+fn maximum_cardinality_matching(node_num: usize, weighted_edges: &[(usize, usize, u32)]) -> Vec<usize> {
+    let max_weight = weighted_edges.iter().map(|&(_, _, weight)| weight as i64).max().unwrap_or(0);
+    // a maximum-cardinality matching maximizing the complementary weights is a minimum-weight perfect matching
+    let edges = weighted_edges
+        .iter()
+        .map(|&(i, j, weight)| {
+            assert!(i < node_num && j < node_num && i != j, "invalid edge ({}, {})", i, j);
+            (i, j, max_weight - weight as i64)
+        })
+        .collect();
+    MaximumWeightMatching::new(node_num, edges).solve()
+}
+
+const NONE: usize = usize::MAX;
+
+/// maximum-cardinality maximum-weight matching on a general graph; the vertices are `0..vertex_num` and the blossoms
+/// are `vertex_num..2*vertex_num`; an edge `k` has two endpoints `2k` and `2k+1`, pointing to its two vertices
+struct MaximumWeightMatching {
+    vertex_num: usize,
+    edges: Vec<(usize, usize, i64)>,
+    /// the vertex of each endpoint
+    endpoint: Vec<usize>,
+    /// the remote endpoints of the edges incident to each vertex
+    neighbor_endpoints: Vec<Vec<usize>>,
+    /// the remote endpoint of the matched edge of each vertex
+    mate: Vec<usize>,
+    /// 0: free, 1: S (outer), 2: T (inner), 5: breadcrumb of `scan_blossom`, -1: expanded blossom
+    label: Vec<i8>,
+    /// the endpoint through which the vertex or top-level blossom got its label
+    label_end: Vec<usize>,
+    /// the top-level blossom containing each vertex
+    in_blossom: Vec<usize>,
+    blossom_parent: Vec<usize>,
+    /// the sub-blossoms of each blossom, in the order of the cycle starting from the base
+    blossom_children: Vec<Vec<usize>>,
+    blossom_base: Vec<usize>,
+    /// the endpoints of the edges connecting the sub-blossoms in the cycle
+    blossom_endpoints: Vec<Vec<usize>>,
+    /// the least-slack edge to a different S-blossom, or to an S-blossom for a free vertex
+    best_edge: Vec<usize>,
+    /// the least-slack edges from a top-level S-blossom to each of the other S-blossoms
+    blossom_best_edges: Vec<Option<Vec<usize>>>,
+    unused_blossoms: Vec<usize>,
+    /// the dual variables of vertices and blossoms, doubled so that they stay integers
+    dual_variables: Vec<i64>,
+    /// edges with zero slack, i.e. tight edges
+    allow_edge: Vec<bool>,
+    /// the S-vertices to be scanned
+    queue: Vec<usize>,
+}
+
+impl MaximumWeightMatching {
+    fn new(vertex_num: usize, edges: Vec<(usize, usize, i64)>) -> Self {
+        let max_weight = edges.iter().map(|&(_, _, weight)| weight).max().unwrap_or(0).max(0);
+        let mut endpoint = Vec::with_capacity(2 * edges.len());
+        let mut neighbor_endpoints = vec![vec![]; vertex_num];
+        for (k, &(i, j, _)) in edges.iter().enumerate() {
+            endpoint.push(i);
+            endpoint.push(j);
+            neighbor_endpoints[i].push(2 * k + 1);
+            neighbor_endpoints[j].push(2 * k);
+        }
+        let mut dual_variables = vec![max_weight; vertex_num];
+        dual_variables.resize(2 * vertex_num, 0);
+        Self {
+            vertex_num,
+            endpoint,
+            neighbor_endpoints,
+            mate: vec![NONE; vertex_num],
+            label: vec![0; 2 * vertex_num],
+            label_end: vec![NONE; 2 * vertex_num],
+            in_blossom: (0..vertex_num).collect(),
+            blossom_parent: vec![NONE; 2 * vertex_num],
+            blossom_children: vec![vec![]; 2 * vertex_num],
+            blossom_base: (0..vertex_num).chain((0..vertex_num).map(|_| NONE)).collect(),
+            blossom_endpoints: vec![vec![]; 2 * vertex_num],
+            best_edge: vec![NONE; 2 * vertex_num],
+            blossom_best_edges: vec![None; 2 * vertex_num],
+            unused_blossoms: (vertex_num..2 * vertex_num).collect(),
+            dual_variables,
+            allow_edge: vec![false; edges.len()],
+            queue: vec![],
+            edges,
+        }
+    }
+
+    fn slack(&self, k: usize) -> i64 {
+        let (i, j, weight) = self.edges[k];
+        self.dual_variables[i] + self.dual_variables[j] - 2 * weight
+    }
+
+    fn blossom_leaves(&self, b: usize) -> Vec<usize> {
+        if b < self.vertex_num {
+            return vec![b];
+        }
+        let mut leaves = vec![];
+        for &t in self.blossom_children[b].iter() {
+            leaves.extend(self.blossom_leaves(t));
+        }
+        leaves
+    }
+
+    /// index into a cycle of `length` with a possibly negative position
+    fn cycle_index(length: usize, j: isize) -> usize {
+        j.rem_euclid(length as isize) as usize
+    }
+
+    /// label the top-level blossom containing `w` with `t` through endpoint `p`
+    fn assign_label(&mut self, w: usize, t: i8, p: usize) {
+        let b = self.in_blossom[w];
+        debug_assert!(self.label[w] == 0 && self.label[b] == 0);
+        self.label[w] = t;
+        self.label[b] = t;
+        self.label_end[w] = p;
+        self.label_end[b] = p;
+        self.best_edge[w] = NONE;
+        self.best_edge[b] = NONE;
+        if t == 1 {
+            let leaves = self.blossom_leaves(b);
+            self.queue.extend(leaves);
+        } else if t == 2 {
+            let base = self.blossom_base[b];
+            debug_assert!(self.mate[base] != NONE);
+            self.assign_label(self.endpoint[self.mate[base]], 1, self.mate[base] ^ 1);
+        }
+    }
+
+    /// trace back from S-vertices `v` and `w` to find either a new blossom, returning its base, or an augmenting path
+    fn scan_blossom(&mut self, mut v: usize, mut w: usize) -> usize {
+        let mut path = vec![];
+        let mut base = NONE;
+        while v != NONE || w != NONE {
+            let mut b = self.in_blossom[v];
+            if self.label[b] & 4 != 0 {
+                base = self.blossom_base[b];
+                break;
+            }
+            debug_assert_eq!(self.label[b], 1);
+            path.push(b);
+            self.label[b] = 5;
+            if self.label_end[b] == NONE {
+                // the root of the alternating tree
+                v = NONE;
+            } else {
+                v = self.endpoint[self.label_end[b]];
+                b = self.in_blossom[v];
+                debug_assert_eq!(self.label[b], 2);
+                v = self.endpoint[self.label_end[b]];
+            }
+            if w != NONE {
+                std::mem::swap(&mut v, &mut w);
+            }
+        }
+        for b in path {
+            self.label[b] = 1;
+        }
+        base
+    }
+
+    /// construct a new blossom with the given base, through the tight edge `k` between two S-vertices
+    fn add_blossom(&mut self, base: usize, k: usize) {
+        let (mut v, mut w, _) = self.edges[k];
+        let bb = self.in_blossom[base];
+        let mut bv = self.in_blossom[v];
+        let mut bw = self.in_blossom[w];
+        let b = self.unused_blossoms.pop().unwrap();
+        self.blossom_base[b] = base;
+        self.blossom_parent[b] = NONE;
+        self.blossom_parent[bb] = b;
+        let mut path = vec![];
+        let mut endpoints = vec![];
+        while bv != bb {
+            self.blossom_parent[bv] = b;
+            path.push(bv);
+            endpoints.push(self.label_end[bv]);
+            v = self.endpoint[self.label_end[bv]];
+            bv = self.in_blossom[v];
+        }
+        path.push(bb);
+        path.reverse();
+        endpoints.reverse();
+        endpoints.push(2 * k);
+        while bw != bb {
+            self.blossom_parent[bw] = b;
+            path.push(bw);
+            endpoints.push(self.label_end[bw] ^ 1);
+            w = self.endpoint[self.label_end[bw]];
+            bw = self.in_blossom[w];
+        }
+        debug_assert_eq!(self.label[bb], 1);
+        self.blossom_children[b] = path.clone();
+        self.blossom_endpoints[b] = endpoints;
+        self.label[b] = 1;
+        self.label_end[b] = self.label_end[bb];
+        self.dual_variables[b] = 0;
+        for v in self.blossom_leaves(b) {
+            if self.label[self.in_blossom[v]] == 2 {
+                // the former T-vertices become S-vertices
+                self.queue.push(v);
+            }
+            self.in_blossom[v] = b;
+        }
+        // compute the least-slack edges to the other S-blossoms
+        let mut best_edge_to = vec![NONE; 2 * self.vertex_num];
+        for &bv in path.iter() {
+            let neighbor_edges: Vec<usize> = match self.blossom_best_edges[bv].take() {
+                Some(edges) => edges,
+                None => self
+                    .blossom_leaves(bv)
+                    .into_iter()
+                    .flat_map(|v| self.neighbor_endpoints[v].iter().map(|p| p / 2))
+                    .collect(),
+            };
+            for k in neighbor_edges {
+                let (i, j, _) = self.edges[k];
+                let j = if self.in_blossom[j] == b { i } else { j };
+                let bj = self.in_blossom[j];
+                if bj != b
+                    && self.label[bj] == 1
+                    && (best_edge_to[bj] == NONE || self.slack(k) < self.slack(best_edge_to[bj]))
+                {
+                    best_edge_to[bj] = k;
+                }
+            }
+            self.best_edge[bv] = NONE;
+        }
+        let best_edges: Vec<usize> = best_edge_to.into_iter().filter(|&k| k != NONE).collect();
+        self.best_edge[b] = NONE;
+        for &k in best_edges.iter() {
+            if self.best_edge[b] == NONE || self.slack(k) < self.slack(self.best_edge[b]) {
+                self.best_edge[b] = k;
+            }
+        }
+        self.blossom_best_edges[b] = Some(best_edges);
+    }
+
+    /// expand a top-level blossom, either during a stage when its dual variable reaches zero or at the end of a stage
+    fn expand_blossom(&mut self, b: usize, end_stage: bool) {
+        let children = self.blossom_children[b].clone();
+        for &s in children.iter() {
+            self.blossom_parent[s] = NONE;
+            if s < self.vertex_num {
+                self.in_blossom[s] = s;
+            } else if end_stage && self.dual_variables[s] == 0 {
+                self.expand_blossom(s, end_stage);
+            } else {
+                for v in self.blossom_leaves(s) {
+                    self.in_blossom[v] = s;
+                }
+            }
+        }
+        if !end_stage && self.label[b] == 2 {
+            // relabel the sub-blossoms on the even-length path from the entry child to the base
+            let length = children.len();
+            let entry_child = self.in_blossom[self.endpoint[self.label_end[b] ^ 1]];
+            let mut j = children.iter().position(|&child| child == entry_child).unwrap() as isize;
+            let (j_step, endpoint_trick) = if j & 1 == 1 {
+                j -= length as isize;
+                (1, 0)
+            } else {
+                (-1, 1)
+            };
+            let mut p = self.label_end[b];
+            while j != 0 {
+                self.label[self.endpoint[p ^ 1]] = 0;
+                let q = self.blossom_endpoints[b][Self::cycle_index(length, j - endpoint_trick as isize)];
+                self.label[self.endpoint[q ^ endpoint_trick ^ 1]] = 0;
+                self.assign_label(self.endpoint[p ^ 1], 2, p);
+                self.allow_edge[q / 2] = true;
+                j += j_step;
+                p = self.blossom_endpoints[b][Self::cycle_index(length, j - endpoint_trick as isize)] ^ endpoint_trick;
+                self.allow_edge[p / 2] = true;
+                j += j_step;
+            }
+            let bv = children[Self::cycle_index(length, j)];
+            self.label[self.endpoint[p ^ 1]] = 2;
+            self.label[bv] = 2;
+            self.label_end[self.endpoint[p ^ 1]] = p;
+            self.label_end[bv] = p;
+            self.best_edge[bv] = NONE;
+            j += j_step;
+            while children[Self::cycle_index(length, j)] != entry_child {
+                // the sub-blossoms on the odd-length path may be reachable from a T-vertex
+                let bv = children[Self::cycle_index(length, j)];
+                j += j_step;
+                if self.label[bv] == 1 {
+                    continue;
+                }
+                let labeled_leaf = self.blossom_leaves(bv).into_iter().find(|&v| self.label[v] != 0);
+                if let Some(v) = labeled_leaf {
+                    debug_assert!(self.label[v] == 2 && self.in_blossom[v] == bv);
+                    self.label[v] = 0;
+                    self.label[self.endpoint[self.mate[self.blossom_base[bv]]]] = 0;
+                    self.assign_label(v, 2, self.label_end[v]);
+                }
+            }
+        }
+        self.label[b] = -1;
+        self.label_end[b] = NONE;
+        self.blossom_children[b].clear();
+        self.blossom_endpoints[b].clear();
+        self.blossom_base[b] = NONE;
+        self.blossom_best_edges[b] = None;
+        self.best_edge[b] = NONE;
+        self.unused_blossoms.push(b);
+    }
+
+    /// swap the matched and unmatched edges on the even-length path through blossom `b` from vertex `v` to the base
+    fn augment_blossom(&mut self, b: usize, v: usize) {
+        let mut t = v;
+        while self.blossom_parent[t] != b {
+            t = self.blossom_parent[t];
+        }
+        if t >= self.vertex_num {
+            self.augment_blossom(t, v);
+        }
+        let length = self.blossom_children[b].len();
+        let i = self.blossom_children[b].iter().position(|&child| child == t).unwrap();
+        let mut j = i as isize;
+        let (j_step, endpoint_trick) = if i & 1 == 1 {
+            j -= length as isize;
+            (1, 0)
+        } else {
+            (-1, 1)
+        };
+        while j != 0 {
+            j += j_step;
+            let t = self.blossom_children[b][Self::cycle_index(length, j)];
+            let p = self.blossom_endpoints[b][Self::cycle_index(length, j - endpoint_trick as isize)] ^ endpoint_trick;
+            if t >= self.vertex_num {
+                self.augment_blossom(t, self.endpoint[p]);
+            }
+            j += j_step;
+            let t = self.blossom_children[b][Self::cycle_index(length, j)];
+            if t >= self.vertex_num {
+                self.augment_blossom(t, self.endpoint[p ^ 1]);
+            }
+            self.mate[self.endpoint[p]] = p ^ 1;
+            self.mate[self.endpoint[p ^ 1]] = p;
+        }
+        // rotate the cycle so that the new base comes first
+        self.blossom_children[b].rotate_left(i);
+        self.blossom_endpoints[b].rotate_left(i);
+        self.blossom_base[b] = self.blossom_base[self.blossom_children[b][0]];
+        debug_assert_eq!(self.blossom_base[b], v);
+    }
+
+    /// swap the matched and unmatched edges on the augmenting path through the tight edge `k`
+    fn augment_matching(&mut self, k: usize) {
+        let (v, w, _) = self.edges[k];
+        for (mut s, mut p) in [(v, 2 * k + 1), (w, 2 * k)] {
+            loop {
+                let bs = self.in_blossom[s];
+                debug_assert_eq!(self.label[bs], 1);
+                if bs >= self.vertex_num {
+                    self.augment_blossom(bs, s);
+                }
+                self.mate[s] = p;
+                if self.label_end[bs] == NONE {
+                    break; // reached the root
+                }
+                let t = self.endpoint[self.label_end[bs]];
+                let bt = self.in_blossom[t];
+                debug_assert_eq!(self.label[bt], 2);
+                s = self.endpoint[self.label_end[bt]];
+                let j = self.endpoint[self.label_end[bt] ^ 1];
+                if bt >= self.vertex_num {
+                    self.augment_blossom(bt, j);
+                }
+                self.mate[j] = self.label_end[bt];
+                p = self.label_end[bt] ^ 1;
+            }
+        }
+    }
+
+    /// returns the peer of each vertex, or [`NONE`] if it's unmatched
+    fn solve(mut self) -> Vec<usize> {
+        let vertex_num = self.vertex_num;
+        // each stage augments the matching by one edge, until no augmenting path exists
+        for _ in 0..vertex_num {
+            self.label.fill(0);
+            self.best_edge.fill(NONE);
+            for blossom_best_edges in self.blossom_best_edges[vertex_num..].iter_mut() {
+                *blossom_best_edges = None;
+            }
+            self.allow_edge.fill(false);
+            self.queue.clear();
+            for v in 0..vertex_num {
+                if self.mate[v] == NONE && self.label[self.in_blossom[v]] == 0 {
+                    self.assign_label(v, 1, NONE);
+                }
+            }
+            let mut augmented = false;
+            loop {
+                while !augmented {
+                    let v = match self.queue.pop() {
+                        Some(v) => v,
+                        None => break,
+                    };
+                    debug_assert_eq!(self.label[self.in_blossom[v]], 1);
+                    for p in self.neighbor_endpoints[v].clone() {
+                        let k = p / 2;
+                        let w = self.endpoint[p];
+                        if self.in_blossom[v] == self.in_blossom[w] {
+                            continue; // an internal edge of a blossom
+                        }
+                        let mut k_slack = 0;
+                        if !self.allow_edge[k] {
+                            k_slack = self.slack(k);
+                            if k_slack <= 0 {
+                                self.allow_edge[k] = true;
+                            }
+                        }
+                        if self.allow_edge[k] {
+                            if self.label[self.in_blossom[w]] == 0 {
+                                self.assign_label(w, 2, p ^ 1);
+                            } else if self.label[self.in_blossom[w]] == 1 {
+                                let base = self.scan_blossom(v, w);
+                                if base != NONE {
+                                    self.add_blossom(base, k);
+                                } else {
+                                    self.augment_matching(k);
+                                    augmented = true;
+                                    break;
+                                }
+                            } else if self.label[w] == 0 {
+                                // `w` is inside a T-blossom but not yet reached from outside
+                                self.label[w] = 2;
+                                self.label_end[w] = p ^ 1;
+                            }
+                        } else if self.label[self.in_blossom[w]] == 1 {
+                            let b = self.in_blossom[v];
+                            if self.best_edge[b] == NONE || k_slack < self.slack(self.best_edge[b]) {
+                                self.best_edge[b] = k;
+                            }
+                        } else if self.label[w] == 0
+                            && (self.best_edge[w] == NONE || k_slack < self.slack(self.best_edge[w]))
+                        {
+                            self.best_edge[w] = k;
+                        }
+                    }
+                }
+                if augmented {
+                    break;
+                }
+                // no augmenting path under the current duals: compute the largest dual update
+                let mut delta_type = 0;
+                let mut delta = 0;
+                let mut delta_edge = NONE;
+                let mut delta_blossom = NONE;
+                for v in 0..vertex_num {
+                    if self.label[self.in_blossom[v]] == 0 && self.best_edge[v] != NONE {
+                        let d = self.slack(self.best_edge[v]);
+                        if delta_type == 0 || d < delta {
+                            delta = d;
+                            delta_type = 2;
+                            delta_edge = self.best_edge[v];
+                        }
+                    }
+                }
+                for b in 0..2 * vertex_num {
+                    if self.blossom_parent[b] == NONE && self.label[b] == 1 && self.best_edge[b] != NONE {
+                        let k_slack = self.slack(self.best_edge[b]);
+                        debug_assert_eq!(k_slack % 2, 0);
+                        let d = k_slack / 2;
+                        if delta_type == 0 || d < delta {
+                            delta = d;
+                            delta_type = 3;
+                            delta_edge = self.best_edge[b];
+                        }
+                    }
+                }
+                for b in vertex_num..2 * vertex_num {
+                    if self.blossom_base[b] != NONE
+                        && self.blossom_parent[b] == NONE
+                        && self.label[b] == 2
+                        && (delta_type == 0 || self.dual_variables[b] < delta)
+                    {
+                        delta = self.dual_variables[b];
+                        delta_type = 4;
+                        delta_blossom = b;
+                    }
+                }
+                if delta_type == 0 {
+                    // no further improvement is possible
+                    delta_type = 1;
+                    delta = self.dual_variables[..vertex_num].iter().cloned().min().unwrap_or(0).max(0);
+                }
+                for v in 0..vertex_num {
+                    match self.label[self.in_blossom[v]] {
+                        1 => self.dual_variables[v] -= delta,
+                        2 => self.dual_variables[v] += delta,
+                        _ => {}
+                    }
+                }
+                for b in vertex_num..2 * vertex_num {
+                    if self.blossom_base[b] != NONE && self.blossom_parent[b] == NONE {
+                        match self.label[b] {
+                            1 => self.dual_variables[b] += delta,
+                            2 => self.dual_variables[b] -= delta,
+                            _ => {}
+                        }
+                    }
+                }
+                match delta_type {
+                    1 => break,
+                    2 | 3 => {
+                        self.allow_edge[delta_edge] = true;
+                        let (i, j, _) = self.edges[delta_edge];
+                        let i = if self.label[self.in_blossom[i]] == 0 { j } else { i };
+                        debug_assert_eq!(self.label[self.in_blossom[i]], 1);
+                        self.queue.push(i);
+                    }
+                    _ => self.expand_blossom(delta_blossom, false),
+                }
+            }
+            if !augmented {
+                break;
+            }
+            // expand the S-blossoms with zero dual variables at the end of the stage
+            for b in vertex_num..2 * vertex_num {
+                if self.blossom_parent[b] == NONE
+                    && self.blossom_base[b] != NONE
+                    && self.label[b] == 1
+                    && self.dual_variables[b] == 0
+                {
+                    self.expand_blossom(b, true);
+                }
+            }
+        }
+        self.mate
+            .iter()
+            .map(|&p| if p == NONE { NONE } else { self.endpoint[p] })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// brute-force minimum weight of perfect matchings, `None` if there is no perfect matching
+    fn brute_force_minimum_weight(matched: &mut [bool], weights: &[Vec<Option<u32>>]) -> Option<u64> {
+        let i = match matched.iter().position(|&is_matched| !is_matched) {
+            Some(i) => i,
+            None => return Some(0),
+        };
+        matched[i] = true;
+        let mut best: Option<u64> = None;
+        for j in i + 1..matched.len() {
+            if let (false, Some(weight)) = (matched[j], weights[i][j]) {
+                matched[j] = true;
+                if let Some(rest) = brute_force_minimum_weight(matched, weights) {
+                    best = Some(best.map_or(rest + weight as u64, |best| best.min(rest + weight as u64)));
+                }
+                matched[j] = false;
+            }
+        }
+        matched[i] = false;
+        best
+    }
+
+    #[test]
+    fn blossom_rs_minimum_weight_perfect_matching_1() {
+        // cargo test blossom_rs_minimum_weight_perfect_matching_1 -- --nocapture
+        use crate::rand_xoshiro::rand_core::SeedableRng;
+        use crate::util::*;
+        use rand::Rng;
+        let edges: Vec<(usize, usize, u32)> = vec![(0, 1, 100), (2, 3, 110), (0, 2, 500), (1, 3, 300)];
+        assert_eq!(minimum_weight_perfect_matching(4, &edges), vec![1, 0, 3, 2]);
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let mut tested = 0;
+        for _ in 0..2000 {
+            let node_num = 2 * rng.gen_range(1..=5);
+            let edge_probability = rng.gen_range(0.3..=1.);
+            let mut weighted_edges = vec![];
+            for i in 0..node_num {
+                for j in i + 1..node_num {
+                    if rng.gen_bool(edge_probability) {
+                        weighted_edges.push((i, j, rng.gen_range(0..20)));
+                    }
+                }
+            }
+            let mut weights = vec![vec![None; node_num]; node_num];
+            for &(i, j, weight) in weighted_edges.iter() {
+                weights[i][j] = Some(weight);
+            }
+            let expected = match brute_force_minimum_weight(&mut vec![false; node_num], &weights) {
+                Some(expected) => expected,
+                None => {
+                    assert_eq!(try_minimum_weight_perfect_matching(node_num, &weighted_edges), None);
+                    continue;
+                }
+            };
+            let mate = minimum_weight_perfect_matching(node_num, &weighted_edges);
+            let mut weight = 0;
+            for (i, &j) in mate.iter().enumerate() {
+                assert_eq!(mate[j], i);
+                if i < j {
+                    weight += weights[i][j].expect("matched through an existing edge") as u64;
+                }
+            }
+            assert_eq!(weight, expected, "edges: {weighted_edges:?}, mate: {mate:?}");
+            tested += 1;
+        }
+        assert!(tested > 1000);
+    }
+
+    #[test]
+    fn blossom_rs_verifier_1() {
+        // cargo test blossom_rs_verifier_1 -- --nocapture
+        use crate::cli::*;
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        let mut code = CircuitLevelPlanarCode::new(5, 5, 0.01, 500);
+        code.set_erasure_probability(0.003);
+        let initializer = code.get_initializer();
+        let mut solver: Box<dyn PrimalDualSolver> = Box::new(SolverSerial::new(&initializer));
+        let mut verifier = Verifier::BlossomRs.build(&initializer);
+        for seed in 0..20 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            verifier.verify(&mut solver, &syndrome_pattern, None);
+            solver.clear();
+        }
+    }
+}
@@ -51,8 +51,10 @@ cfg_if::cfg_if! {
 
     } else {
 
-        pub fn safe_minimum_weight_perfect_matching(_node_num: usize, _weighted_edges: &Vec<(usize, usize, u32)>) -> Vec<usize> {
-            unimplemented!("need blossom V library, see README.md")
+        /// without the blossom V library, fall back to the pure-Rust implementation so that the verifiers work in all builds
+        #[allow(clippy::ptr_arg)]  // same signature as the blossom V binding
+        pub fn safe_minimum_weight_perfect_matching(node_num: usize, weighted_edges: &Vec<(usize, usize, u32)>) -> Vec<usize> {
+            super::blossom_rs::minimum_weight_perfect_matching(node_num, weighted_edges)
         }
 
     }
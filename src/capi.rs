@@ -0,0 +1,223 @@
+//! C API
+//!
+//! `extern "C"` functions to embed the decoder in C, C++ or Julia control software without going through Python.
+//! The declarations are kept in sync with the stable header `include/fusion_blossom.h`; the indices are always
+//! `size_t` and the weights are always `int64_t`, regardless of the `u32_index` and `i32_weight` features.
+//! No panic crosses the FFI boundary: invalid inputs are reported by a NULL handle or a non-zero status code.
+//!
+//! ```c
+//! fusion_blossom_solver_t *solver = fusion_blossom_solver_new(vertex_num, edge_num, edges, weights, virtual_num, virtual_vertices);
+//! if (solver == NULL) { /* invalid decoding graph */ }
+//! if (fusion_blossom_solve(solver, defect_num, defect_vertices) != FUSION_BLOSSOM_OK) { /* invalid syndrome */ }
+//! size_t matched_num = fusion_blossom_get_matching_edges(solver, NULL, 0);  // query the number of edges first
+//! fusion_blossom_get_matching_edges(solver, matched_edges, matched_num);
+//! fusion_blossom_free(solver);
+//! ```
+//!
+
+use super::mwpm_solver::*;
+use super::util::*;
+use std::os::raw::c_int;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// the status code of a successful call
+pub const FUSION_BLOSSOM_OK: c_int = 0;
+/// the status code of a call with invalid arguments, e.g. a null solver or a defect vertex out of range
+pub const FUSION_BLOSSOM_ERROR: c_int = -1;
+
+/// opaque handle of a solver, which keeps the matching of the last solved syndrome
+pub struct FusionBlossomSolver {
+    solver: SolverSerial,
+    subgraph: Vec<EdgeIndex>,
+}
+
+/// create a solver; `edges` has `2 * edge_num` elements, the two vertices of each edge, and `weights` has `edge_num`
+/// elements which must be even and non-negative; the returned handle must be released by [`fusion_blossom_free`].
+/// Returns null if the decoding graph is invalid
+///
+/// # Safety
+///
+/// the pointers must be valid for the given number of elements; they may be null when the corresponding length is 0
+#[no_mangle]
+#[allow(clippy::unnecessary_cast)]
+pub unsafe extern "C" fn fusion_blossom_solver_new(
+    vertex_num: usize,
+    edge_num: usize,
+    edges: *const usize,
+    weights: *const i64,
+    virtual_num: usize,
+    virtual_vertices: *const usize,
+) -> *mut FusionBlossomSolver {
+    let result = catch_unwind(|| {
+        let edges = slice_from_raw(edges, 2 * edge_num);
+        let weights = slice_from_raw(weights, edge_num);
+        let weighted_edges = (0..edge_num).map(|edge_index| {
+            (
+                edges[2 * edge_index] as VertexIndex,
+                edges[2 * edge_index + 1] as VertexIndex,
+                weights[edge_index] as Weight,
+            )
+        });
+        let virtual_vertices = slice_from_raw(virtual_vertices, virtual_num)
+            .iter()
+            .map(|&vertex_index| vertex_index as VertexIndex)
+            .collect();
+        let initializer = SolverInitializer::from_edges(vertex_num as VertexNum, edge_num, weighted_edges, virtual_vertices);
+        FusionBlossomSolver {
+            solver: SolverSerial::new(&initializer),
+            subgraph: vec![],
+        }
+    });
+    match result {
+        Ok(solver) => Box::into_raw(Box::new(solver)),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// solve the syndrome given by `defect_num` defect vertices, replacing the matching of the previous call; returns
+/// [`FUSION_BLOSSOM_OK`], or [`FUSION_BLOSSOM_ERROR`] with an empty matching if the syndrome is invalid
+///
+/// # Safety
+///
+/// `solver` must be created by [`fusion_blossom_solver_new`] and not yet freed, and `defect_vertices` must be valid
+/// for `defect_num` elements
+#[no_mangle]
+#[allow(clippy::unnecessary_cast)]
+pub unsafe extern "C" fn fusion_blossom_solve(
+    solver: *mut FusionBlossomSolver,
+    defect_num: usize,
+    defect_vertices: *const usize,
+) -> c_int {
+    let Some(solver) = solver.as_mut() else {
+        return FUSION_BLOSSOM_ERROR;
+    };
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let defect_vertices = slice_from_raw(defect_vertices, defect_num)
+            .iter()
+            .map(|&vertex_index| vertex_index as VertexIndex)
+            .collect();
+        solver.solver.solve(&SyndromePattern::new_vertices(defect_vertices));
+        solver.solver.subgraph()
+    }));
+    // a failed solve may leave a partial state behind, which is dropped by clear
+    let cleared = catch_unwind(AssertUnwindSafe(|| solver.solver.clear()));
+    match (result, cleared) {
+        (Ok(subgraph), Ok(())) => {
+            solver.subgraph = subgraph;
+            FUSION_BLOSSOM_OK
+        }
+        _ => {
+            solver.subgraph.clear();
+            FUSION_BLOSSOM_ERROR
+        }
+    }
+}
+
+/// copy at most `capacity` indices of the matched edges into `matched_edges`, and return the total number of
+/// matched edges, so that the caller can pass `capacity = 0` to query the size of the buffer first; returns `SIZE_MAX`
+/// if `solver` is null, or if `matched_edges` is null with a non-zero `capacity`
+///
+/// # Safety
+///
+/// `solver` must be created by [`fusion_blossom_solver_new`] and not yet freed, and `matched_edges` must be valid
+/// for `capacity` elements
+#[no_mangle]
+#[allow(clippy::unnecessary_cast)]
+pub unsafe extern "C" fn fusion_blossom_get_matching_edges(
+    solver: *const FusionBlossomSolver,
+    matched_edges: *mut usize,
+    capacity: usize,
+) -> usize {
+    let Some(solver) = solver.as_ref() else {
+        return usize::MAX;
+    };
+    if capacity > 0 {
+        if matched_edges.is_null() {
+            return usize::MAX;
+        }
+        let matched_edges = std::slice::from_raw_parts_mut(matched_edges, capacity);
+        for (output, &edge_index) in matched_edges.iter_mut().zip(solver.subgraph.iter()) {
+            *output = edge_index as usize;
+        }
+    }
+    solver.subgraph.len()
+}
+
+/// release a solver created by [`fusion_blossom_solver_new`]; null is ignored
+///
+/// # Safety
+///
+/// `solver` must not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn fusion_blossom_free(solver: *mut FusionBlossomSolver) {
+    if !solver.is_null() {
+        drop(Box::from_raw(solver));
+    }
+}
+
+unsafe fn slice_from_raw<'a, T>(pointer: *const T, length: usize) -> &'a [T] {
+    if length == 0 {
+        &[]
+    } else {
+        assert!(!pointer.is_null(), "null pointer with non-zero length");
+        std::slice::from_raw_parts(pointer, length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the graph below is matched through the calls a C program would make
+    ///   o --- * --- * --- o
+    ///   0     1     2     3
+    #[test]
+    fn capi_solve_1() {
+        // cargo test --features c_binding capi_solve_1 -- --nocapture
+        let edges: [usize; 6] = [0, 1, 1, 2, 2, 3];
+        let weights: [i64; 3] = [100, 100, 100];
+        let virtual_vertices: [usize; 2] = [0, 3];
+        unsafe {
+            let solver = fusion_blossom_solver_new(4, 3, edges.as_ptr(), weights.as_ptr(), 2, virtual_vertices.as_ptr());
+            let defect_vertices: [usize; 2] = [1, 2];
+            fusion_blossom_solve(solver, 2, defect_vertices.as_ptr());
+            assert_eq!(fusion_blossom_get_matching_edges(solver, std::ptr::null_mut(), 0), 1);
+            let mut matched_edges = [usize::MAX; 2];
+            assert_eq!(fusion_blossom_get_matching_edges(solver, matched_edges.as_mut_ptr(), 2), 1);
+            assert_eq!(matched_edges, [1, usize::MAX]);
+            let defect_vertices: [usize; 1] = [1];
+            fusion_blossom_solve(solver, 1, defect_vertices.as_ptr());
+            assert_eq!(fusion_blossom_get_matching_edges(solver, matched_edges.as_mut_ptr(), 2), 1);
+            assert_eq!(matched_edges[0], 0);
+            fusion_blossom_solve(solver, 0, std::ptr::null());
+            assert_eq!(fusion_blossom_get_matching_edges(solver, std::ptr::null_mut(), 0), 0);
+            fusion_blossom_free(solver);
+        }
+    }
+
+    /// invalid inputs are reported instead of unwinding into the caller
+    #[test]
+    fn capi_invalid_input_1() {
+        // cargo test --features c_binding capi_invalid_input_1 -- --nocapture
+        let edges: [usize; 6] = [0, 1, 1, 2, 2, 3];
+        let virtual_vertices: [usize; 2] = [0, 3];
+        unsafe {
+            let odd_weights: [i64; 3] = [100, 101, 100];
+            let solver = fusion_blossom_solver_new(4, 3, edges.as_ptr(), odd_weights.as_ptr(), 2, virtual_vertices.as_ptr());
+            assert!(solver.is_null());
+            assert_eq!(fusion_blossom_solve(solver, 0, std::ptr::null()), FUSION_BLOSSOM_ERROR);
+            assert_eq!(fusion_blossom_get_matching_edges(solver, std::ptr::null_mut(), 0), usize::MAX);
+            let weights: [i64; 3] = [100, 100, 100];
+            let solver = fusion_blossom_solver_new(4, 3, edges.as_ptr(), weights.as_ptr(), 2, virtual_vertices.as_ptr());
+            let out_of_range: [usize; 1] = [4];
+            assert_eq!(fusion_blossom_solve(solver, 1, out_of_range.as_ptr()), FUSION_BLOSSOM_ERROR);
+            assert_eq!(fusion_blossom_get_matching_edges(solver, std::ptr::null_mut(), 0), 0);
+            // the solver is still usable after an error
+            let defect_vertices: [usize; 2] = [1, 2];
+            assert_eq!(fusion_blossom_solve(solver, 2, defect_vertices.as_ptr()), FUSION_BLOSSOM_OK);
+            assert_eq!(fusion_blossom_get_matching_edges(solver, std::ptr::null_mut(), 0), 1);
+            assert_eq!(fusion_blossom_get_matching_edges(solver, std::ptr::null_mut(), 1), usize::MAX);
+            fusion_blossom_free(solver);
+        }
+    }
+}
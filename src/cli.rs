@@ -1,6 +1,11 @@
+use super::analysis::{is_logical_error, left_boundary_edges};
+#[cfg(feature = "results_db")]
+use super::benchmark::*;
+use super::complete_graph::*;
 use super::dual_module::*;
 use super::example_codes::*;
 use super::example_partition;
+use super::fuzz;
 use super::mwpm_solver::*;
 use super::primal_module::*;
 use super::util::*;
@@ -14,6 +19,7 @@ use rand::{thread_rng, Rng};
 use serde::Serialize;
 use serde_json::json;
 use std::env;
+use std::fs::File;
 
 const TEST_EACH_ROUNDS: usize = 100;
 
@@ -30,7 +36,7 @@ pub struct Cli {
     pub command: Commands,
 }
 
-#[derive(Parser, Clone, Debug)]
+#[derive(Parser, Clone, Debug, Serialize)]
 pub struct BenchmarkParameters {
     /// code distance
     #[clap(value_parser)]
@@ -89,9 +95,31 @@ pub struct BenchmarkParameters {
     /// the benchmark profile output file path
     #[clap(long)]
     pub benchmark_profiler_output: Option<String>,
+    /// the per-shot results output file path in CSV format, written incrementally
+    #[clap(long)]
+    pub benchmark_results_output: Option<String>,
+    /// the sqlite database to append the summary of this benchmark to, keyed by the config hash and the crate version;
+    /// not part of the config, so that the same benchmark has the same hash wherever it's recorded
+    #[cfg(feature = "results_db")]
+    #[clap(long)]
+    #[serde(skip)]
+    pub benchmark_results_db: Option<String>,
+    /// the full configuration actually in effect for this run, written in JSON so that the run can be reproduced later
+    #[clap(long)]
+    pub effective_config_output: Option<String>,
+    /// memorize the results of up to this number of recent syndrome patterns, 0 to disable
+    #[clap(long, default_value_t = 0)]
+    pub solver_cache_capacity: usize,
     /// skip some iterations, useful when debugging
     #[clap(long, default_value_t = 0)]
     pub starting_iteration: usize,
+    /// the number of shots at the beginning whose latency is reported separately as cold-start and excluded from
+    /// the steady-state statistics and the output files
+    #[clap(long, default_value_t = 0)]
+    pub warmup_rounds: usize,
+    /// skip verifying the parity of the subgraph against the syndrome, for maximum performance
+    #[clap(long, action)]
+    pub disable_parity_check: bool,
 }
 
 #[derive(Subcommand, Clone, Derivative)]
@@ -109,6 +137,27 @@ pub enum Commands {
     },
     /// visualize a syndrome graph
     VisualizeSyndromes(VisualizeSyndromesParameters),
+    /// rerun a solve exported by `PrimalDualSolver::export_repro`
+    Repro(ReproParameters),
+    /// run a decoding service over TCP, see `fusion_blossom::service`
+    Serve(ServeParameters),
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct ServeParameters {
+    /// the address to listen on
+    #[clap(value_parser, default_value_t = format!("127.0.0.1:8077"))]
+    pub address: String,
+    /// the limits of the server, see [`crate::service::ServiceConfig`]
+    #[clap(long, default_value_t = ("{}").to_string())]
+    pub service_config: String,
+}
+
+#[derive(Parser, Clone, Debug)]
+pub struct ReproParameters {
+    /// the reproduction file
+    #[clap(value_parser)]
+    pub filepath: String,
 }
 
 #[derive(Parser, Clone, Debug)]
@@ -168,6 +217,21 @@ pub enum TestCommands {
         #[clap(short = 's', long, action)]
         print_syndrome_pattern: bool,
     },
+    /// compare serial and parallel solvers on random graphs, partitions and syndromes; failures are minimized
+    Fuzz {
+        /// the seed of the first case, the i-th case uses `seed + i`
+        #[clap(long, default_value_t = 0)]
+        seed: u64,
+        /// the number of random cases
+        #[clap(short = 'r', long, default_value_t = 10000)]
+        total_rounds: usize,
+        /// the configuration of the fuzzer, see [`fuzz::FuzzConfig`]
+        #[clap(long, default_value_t = ("{}").to_string())]
+        fuzz_config: String,
+        /// write the minimized failing case to this file in JSON format
+        #[clap(long)]
+        failure_output: Option<String>,
+    },
 }
 
 /// note that these code type is only for example, to test and demonstrate the correctness of the algorithm, but not for real QEC simulation;
@@ -197,6 +261,13 @@ pub enum ExampleCodeType {
     CodeCapacityRotatedCode,
     /// rotated surface code with phenomenological noise model
     PhenomenologicalRotatedCode,
+    /// XZZX surface code with perfect stabilizer measurement, pass the noise bias using `--code-config '{"eta":100}'`
+    #[serde(rename = "code-capacity-xzzx-code")]
+    CodeCapacityXZZXCode,
+    /// toric code (periodic boundaries) with perfect stabilizer measurement
+    CodeCapacityToricCode,
+    /// toric code (periodic boundaries) with phenomenological noise model
+    PhenomenologicalToricCode,
     /// code constructed by QEC-Playground, pass configurations using `--code-config`
     #[serde(rename = "qec-playground-code")]
     QECPlaygroundCode,
@@ -214,8 +285,13 @@ pub enum PartitionStrategy {
     CodeCapacityRepetitionCodePartitionHalf,
     /// partition a phenomenological (or circuit-level) planar code with time axis
     PhenomenologicalPlanarCodeTimePartition,
+    /// partition a phenomenological (or circuit-level) planar code into a grid over space and time, pass the number of
+    /// cutting rows and rounds using `--partition-config '{"space_cuts":1,"time_cuts":3}'`
+    PhenomenologicalPlanarCodeGridPartition,
     /// partition a phenomenological (or circuit-level) rotated code with time axis
     PhenomenologicalRotatedCodeTimePartition,
+    /// partition a phenomenological repetition code with time axis, the smallest graph to test fusion chains with
+    PhenomenologicalRepetitionCodeTimePartition,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Debug)]
@@ -226,6 +302,8 @@ pub enum PrimalDualType {
     DualParallel,
     /// parallel primal and dual
     Parallel,
+    /// parallel dual whose partition units are hosted by rank threads exchanging messages over channels, and serial primal
+    ChannelParallel,
     /// log error into a file for later fetch
     ErrorPatternLogger,
     /// solver using traditional blossom V
@@ -236,10 +314,15 @@ pub enum PrimalDualType {
 pub enum Verifier {
     /// disable verifier
     None,
-    /// use blossom V library to verify the correctness of result
+    /// use blossom V library to verify the correctness of result, or the pure-Rust fallback if blossom V is absent
     BlossomV,
+    /// use the pure-Rust blossom algorithm to verify the correctness of result even if blossom V is present
+    BlossomRs,
     /// use the serial version of fusion algorithm to verify the correctness of result
     FusionSerial,
+    /// compute the exact matching weight by exhaustive search, which requires neither blossom V nor another blossom
+    /// algorithm; only for small syndromes, see [`crate::exhaustive`]
+    Exhaustive,
 }
 
 pub struct RunnableBenchmarkParameters {
@@ -247,7 +330,12 @@ pub struct RunnableBenchmarkParameters {
     pub primal_dual_solver: Box<dyn PrimalDualSolver>,
     pub result_verifier: Box<dyn ResultVerifier>,
     pub benchmark_profiler: BenchmarkProfiler,
+    pub benchmark_results_writer: Option<BenchmarkResultsWriter>,
     pub parameters: BenchmarkParameters,
+    /// stamped into all the output artifacts of this benchmark
+    pub fingerprint: OutputFingerprint,
+    #[cfg(feature = "results_db")]
+    pub results_db: Option<ResultsDb>,
 }
 
 impl From<BenchmarkParameters> for RunnableBenchmarkParameters {
@@ -268,15 +356,16 @@ impl From<BenchmarkParameters> for RunnableBenchmarkParameters {
             code_config,
             partition_config,
             benchmark_profiler_output,
+            benchmark_results_output,
+            effective_config_output,
+            solver_cache_capacity,
+            warmup_rounds,
+            disable_parity_check,
             ..
         } = parameters.clone();
         let code_config: serde_json::Value = serde_json::from_str(&code_config).unwrap();
         let primal_dual_config: serde_json::Value = serde_json::from_str(&primal_dual_config).unwrap();
         let partition_config: serde_json::Value = serde_json::from_str(&partition_config).unwrap();
-        // check for dependency early
-        if matches!(verifier, Verifier::BlossomV) && cfg!(not(feature = "blossom_v")) {
-            panic!("need blossom V library, see README.md")
-        }
         let mut code: Box<dyn ExampleCode> = code_type.build(d, p, noisy_measurements, max_half_weight, code_config);
         if pe != 0. {
             code.set_erasure_probability(pe);
@@ -288,16 +377,53 @@ impl From<BenchmarkParameters> for RunnableBenchmarkParameters {
         // create initializer and solver
         let (initializer, partition_config) = partition_strategy.build(&mut *code, d, noisy_measurements, partition_config);
         let partition_info = partition_config.info();
-        let primal_dual_solver = primal_dual_type.build(&initializer, &partition_info, &*code, primal_dual_config);
-        let benchmark_profiler =
-            BenchmarkProfiler::new(noisy_measurements, benchmark_profiler_output.map(|x| (x, &partition_info)));
+        let mut primal_dual_solver = primal_dual_type.build(&initializer, &partition_info, &*code, primal_dual_config);
+        if disable_parity_check {
+            primal_dual_solver.set_parity_check(false);
+        }
+        if solver_cache_capacity > 0 {
+            primal_dual_solver = Box::new(SolverCached::new(primal_dual_solver, solver_cache_capacity));
+        }
+        let effective_config = json!({
+            "parameters": parameters,
+            "solver": primal_dual_solver.effective_config(),
+        });
+        let fingerprint = OutputFingerprint::new(&effective_config);
+        #[cfg(feature = "results_db")]
+        let results_db = parameters.benchmark_results_db.as_ref().map(|path| {
+            let results_db = ResultsDb::open(path).expect("cannot open benchmark results database");
+            results_db
+                .record_config(&fingerprint, &effective_config)
+                .expect("cannot record the config");
+            results_db
+        });
+        if let Some(filename) = effective_config_output {
+            let mut effective_config = effective_config;
+            let effective_config_object = effective_config.as_object_mut().unwrap();
+            effective_config_object.insert("version".to_string(), json!(env!("CARGO_PKG_VERSION")));
+            effective_config_object.insert("fingerprint".to_string(), json!(fingerprint));
+            let file = File::create(filename).expect("cannot create effective config output file");
+            serde_json::to_writer_pretty(file, &effective_config).unwrap();
+        }
+        let mut benchmark_profiler = BenchmarkProfiler::new(
+            noisy_measurements,
+            benchmark_profiler_output.map(|x| (x, &partition_info, &fingerprint)),
+        );
+        benchmark_profiler.warmup_rounds = warmup_rounds;
+        let benchmark_results_writer = benchmark_results_output.map(|filename| {
+            BenchmarkResultsWriter::new(&filename, fingerprint.clone()).expect("cannot create benchmark results output file")
+        });
         let result_verifier = verifier.build(&initializer);
         Self {
             code,
             primal_dual_solver,
             result_verifier,
             benchmark_profiler,
+            benchmark_results_writer,
             parameters,
+            fingerprint,
+            #[cfg(feature = "results_db")]
+            results_db,
         }
     }
 }
@@ -309,6 +435,7 @@ impl RunnableBenchmarkParameters {
             mut primal_dual_solver,
             mut result_verifier,
             mut benchmark_profiler,
+            mut benchmark_results_writer,
             parameters:
                 BenchmarkParameters {
                     starting_iteration,
@@ -320,6 +447,9 @@ impl RunnableBenchmarkParameters {
                     visualizer_filename,
                     ..
                 },
+            fingerprint,
+            #[cfg(feature = "results_db")]
+            mut results_db,
         } = self;
         // whether to disable progress bar, useful when running jobs in background
         let disable_progress_bar = env::var("DISABLE_PROGRESS_BAR").is_ok();
@@ -338,35 +468,95 @@ impl RunnableBenchmarkParameters {
         // share the same visualizer across all rounds
         let mut visualizer = None;
         if enable_visualizer {
-            let new_visualizer = Visualizer::new(
+            let new_visualizer = Visualizer::new_fingerprint(
                 Some(visualize_data_folder() + visualizer_filename.as_str()),
                 code.get_positions(),
                 true,
+                Some(fingerprint.clone()),
             )
             .unwrap();
             visualizer = Some(new_visualizer);
         }
+        let left_boundary = if benchmark_results_writer.is_some() {
+            left_boundary_edges(code.as_ref())
+        } else {
+            vec![]
+        };
         for round in (starting_iteration as u64)..(total_rounds as u64) {
             pb.as_mut().map(|pb| pb.set(round));
             let seed = if use_deterministic_seed { round } else { rng.gen() };
-            let syndrome_pattern = code.generate_random_errors(seed);
+            // the ground truth is only needed to report the success of each shot
+            let (syndrome_pattern, error_edges) = match benchmark_results_writer
+                .as_ref()
+                .and_then(|_| code.generate_random_errors_with_ground_truth(seed))
+            {
+                Some((syndrome_pattern, error_edges)) => (syndrome_pattern, Some(error_edges)),
+                None => (code.generate_random_errors(seed), None),
+            };
             if print_syndrome_pattern {
                 println!("syndrome_pattern: {:?}", syndrome_pattern);
             }
             benchmark_profiler.begin(&syndrome_pattern);
+            benchmark_profiler.set_seed(seed);
+            if let Some(visualizer) = visualizer.as_mut() {
+                visualizer.seed = Some(seed);
+            }
             primal_dual_solver.solve_visualizer(&syndrome_pattern, visualizer.as_mut());
             benchmark_profiler.event("decoded".to_string());
+            let (weight, success, conflicts) = if benchmark_results_writer.is_some() {
+                let success = error_edges
+                    .filter(|_| left_boundary.contains(&true))
+                    .map(|error_edges| !is_logical_error(&left_boundary, &error_edges, &primal_dual_solver.subgraph()));
+                let conflicts = primal_dual_solver.generate_profiler_report()["primal"]["conflicts"]
+                    .as_u64()
+                    .map(|conflicts| conflicts as usize);
+                (primal_dual_solver.sum_dual_variables(), success, conflicts)
+            } else {
+                (0, None, None)
+            };
             result_verifier.verify(&mut primal_dual_solver, &syndrome_pattern, visualizer.as_mut());
             benchmark_profiler.event("verified".to_string());
             primal_dual_solver.clear(); // also count the clear operation
             benchmark_profiler.end(Some(&*primal_dual_solver));
             primal_dual_solver.reset_profiler();
+            if benchmark_profiler.is_warmup() {
+                continue;
+            }
+            if let Some(writer) = benchmark_results_writer.as_mut() {
+                let entry = benchmark_profiler.records.last().unwrap();
+                writer
+                    .write_row(&BenchmarkResultsRow {
+                        round,
+                        seed,
+                        defect_num: syndrome_pattern.defect_vertices.len(),
+                        erasure_num: syndrome_pattern.erasures.len(),
+                        weight,
+                        success,
+                        conflicts,
+                        decode_time: entry.events[0].1,
+                        round_time: entry.round_time.unwrap(),
+                    })
+                    .unwrap();
+            }
             if let Some(pb) = pb.as_mut() {
                 if pb_message.is_empty() {
                     pb.message(format!("{} ", benchmark_profiler.brief()).as_str());
                 }
             }
         }
+        if let Some(writer) = benchmark_results_writer.as_mut() {
+            writer.flush().unwrap();
+        }
+        #[cfg(feature = "results_db")]
+        if let Some(results_db) = results_db.as_mut() {
+            results_db
+                .record(
+                    &fingerprint,
+                    &benchmark_profiler.summary_metrics(),
+                    benchmark_profiler.records_count,
+                )
+                .expect("cannot record the benchmark results");
+        }
         if disable_progress_bar {
             // always print out brief
             println!("{}", benchmark_profiler.brief());
@@ -386,6 +576,15 @@ impl Cli {
                 let runnable = RunnableBenchmarkParameters::from(benchmark_parameters);
                 runnable.run();
             }
+            Commands::Repro(parameters) => {
+                let outcome = crate::repro::run(&parameters.filepath);
+                println!("{}", serde_json::to_string(&outcome).unwrap());
+            }
+            Commands::Serve(parameters) => {
+                println!("serving on {}", parameters.address);
+                let service_config = serde_json::from_str(&parameters.service_config).unwrap();
+                crate::service::serve(&parameters.address, service_config).unwrap();
+            }
             Commands::VisualizeSyndromes(parameters) => {
                 let code_config = json!({
                     "filename": parameters.filepath
@@ -734,6 +933,26 @@ impl Cli {
                                 ]);
                             }
                         }
+                        for p in [0.001, 0.003, 0.01, 0.03, 0.1, 0.3, 0.499] {
+                            for partition_num in [2, 5, 10] {
+                                let d = 5;
+                                let noisy_measurement = 20;
+                                parameters.push(vec![
+                                    format!("{d}"),
+                                    format!("{p}"),
+                                    format!("--code-type"),
+                                    format!("phenomenological-repetition-code"),
+                                    format!("--noisy-measurements"),
+                                    format!("{noisy_measurement}"),
+                                    format!("--partition-strategy"),
+                                    format!("phenomenological-repetition-code-time-partition"),
+                                    format!("--partition-config"),
+                                    format!("{{\"partition_num\":{partition_num}}}"),
+                                    format!("--pb-message"),
+                                    format!("parallel {partition_num}-partition repetition {d} {noisy_measurement} {p}"),
+                                ]);
+                            }
+                        }
                         for p in [0.001, 0.003, 0.01, 0.03, 0.1, 0.3, 0.499] {
                             for partition_num in [2, 3, 4, 5, 6, 7, 8, 9, 10] {
                                 // test large number of fusion without tree fusion
@@ -780,6 +999,27 @@ impl Cli {
                             );
                         }
                     }
+                    TestCommands::Fuzz {
+                        seed,
+                        total_rounds,
+                        fuzz_config,
+                        failure_output,
+                    } => {
+                        let config: fuzz::FuzzConfig = serde_json::from_str(&fuzz_config).unwrap();
+                        // the failing cases panic many times while shrinking, don't flood the terminal
+                        let panic_hook = std::panic::take_hook();
+                        std::panic::set_hook(Box::new(|_| {}));
+                        let failure = fuzz::fuzz(&config, seed, total_rounds);
+                        std::panic::set_hook(panic_hook);
+                        if let Some(failure) = failure {
+                            let failure_json = serde_json::to_string(&failure).unwrap();
+                            if let Some(failure_output) = failure_output {
+                                std::fs::write(failure_output, &failure_json).unwrap();
+                            }
+                            panic!("fuzzer found a failing case: {}", failure_json);
+                        }
+                        println!("{total_rounds} random cases passed");
+                    }
                 }
             }
             #[cfg(feature = "qecp_integrate")]
@@ -819,13 +1059,19 @@ impl ExampleCodeType {
                 assert_eq!(code_config, json!({}), "config not supported");
                 Box::new(CodeCapacityRepetitionCode::new(d, p, max_half_weight))
             }
+            Self::PhenomenologicalRepetitionCode => {
+                let mut code = Box::new(PhenomenologicalRepetitionCode::new(d, noisy_measurements, p, max_half_weight));
+                open_future_time_boundary_by_config(code.as_mut(), code_config);
+                code
+            }
             Self::CodeCapacityPlanarCode => {
                 assert_eq!(code_config, json!({}), "config not supported");
                 Box::new(CodeCapacityPlanarCode::new(d, p, max_half_weight))
             }
             Self::PhenomenologicalPlanarCode => {
-                assert_eq!(code_config, json!({}), "config not supported");
-                Box::new(PhenomenologicalPlanarCode::new(d, noisy_measurements, p, max_half_weight))
+                let mut code = Box::new(PhenomenologicalPlanarCode::new(d, noisy_measurements, p, max_half_weight));
+                open_future_time_boundary_by_config(code.as_mut(), code_config);
+                code
             }
             Self::PhenomenologicalPlanarCodeParallel => {
                 let mut code_count = 1;
@@ -839,8 +1085,23 @@ impl ExampleCodeType {
                 ))
             }
             Self::CircuitLevelPlanarCode => {
-                assert_eq!(code_config, json!({}), "config not supported");
-                Box::new(CircuitLevelPlanarCode::new(d, noisy_measurements, p, max_half_weight))
+                let config = code_config.as_object_mut().expect("config must be JSON object");
+                let measurement_p = config
+                    .remove("measurement_p")
+                    .map(|value| value.as_f64().expect("measurement_p: f64"));
+                let diagonal_p = config
+                    .remove("diagonal_p")
+                    .map_or(p / 3., |value| value.as_f64().expect("diagonal_p: f64"));
+                let mut code = Box::new(CircuitLevelPlanarCode::new_noise_model(
+                    d,
+                    noisy_measurements,
+                    p,
+                    max_half_weight,
+                    measurement_p,
+                    Some(diagonal_p),
+                ));
+                open_future_time_boundary_by_config(code.as_mut(), code_config);
+                code
             }
             Self::CircuitLevelPlanarCodeParallel => {
                 let mut code_count = 1;
@@ -859,8 +1120,25 @@ impl ExampleCodeType {
                 Box::new(CodeCapacityRotatedCode::new(d, p, max_half_weight))
             }
             Self::PhenomenologicalRotatedCode => {
+                let mut code = Box::new(PhenomenologicalRotatedCode::new(d, noisy_measurements, p, max_half_weight));
+                open_future_time_boundary_by_config(code.as_mut(), code_config);
+                code
+            }
+            Self::CodeCapacityXZZXCode => {
+                let eta = (code_config.as_object_mut().expect("config must be JSON object"))
+                    .remove("eta")
+                    .map_or(0.5, |value| value.as_f64().expect("eta: f64"));
+                assert_eq!(code_config, json!({}), "config not supported");
+                Box::new(CodeCapacityXZZXCode::new(d, p, eta, max_half_weight))
+            }
+            Self::CodeCapacityToricCode => {
                 assert_eq!(code_config, json!({}), "config not supported");
-                Box::new(PhenomenologicalRotatedCode::new(d, noisy_measurements, p, max_half_weight))
+                Box::new(CodeCapacityToricCode::new(d, p, max_half_weight))
+            }
+            Self::PhenomenologicalToricCode => {
+                let mut code = Box::new(PhenomenologicalToricCode::new(d, noisy_measurements, p, max_half_weight));
+                open_future_time_boundary_by_config(code.as_mut(), code_config);
+                code
             }
             #[cfg(feature = "qecp_integrate")]
             Self::QECPlaygroundCode => Box::new(QECPlaygroundCode::new(d as usize, p, code_config)),
@@ -869,6 +1147,21 @@ impl ExampleCodeType {
     }
 }
 
+/// optionally remove the final perfect measurement round given `{"open_future": true}`
+fn open_future_time_boundary_by_config(code: &mut dyn ExampleCode, mut code_config: serde_json::Value) {
+    let config = code_config.as_object_mut().expect("config must be JSON object");
+    if let Some(value) = config.remove("open_future") {
+        if value.as_bool().expect("open_future: bool") {
+            code.open_future_time_boundary();
+        }
+    }
+    assert!(
+        config.is_empty(),
+        "unknown config keys: {:?}",
+        config.keys().collect::<Vec<&String>>()
+    );
+}
+
 impl PartitionStrategy {
     pub fn build(
         &self,
@@ -878,6 +1171,11 @@ impl PartitionStrategy {
         mut partition_config: serde_json::Value,
     ) -> (SolverInitializer, PartitionConfig) {
         use example_partition::*;
+        // the shape of the fusion tree of a time partition can be chosen to compare the latency profiles, e.g.
+        // `--partition-config '{"partition_num":8,"fusion_tree":"balanced_tree"}'`
+        let fusion_tree = partition_config
+            .as_object_mut()
+            .and_then(|config| config.remove("fusion_tree"));
         let partition_config = match self {
             Self::None => {
                 assert_eq!(partition_config, json!({}), "config not supported");
@@ -921,6 +1219,21 @@ impl PartitionStrategy {
                 )
                 .build_apply(code)
             }
+            Self::PhenomenologicalPlanarCodeGridPartition => {
+                let config = partition_config.as_object_mut().expect("config must be JSON object");
+                let mut space_cuts = 1;
+                let mut time_cuts = 1;
+                if let Some(value) = config.remove("space_cuts") {
+                    space_cuts = value.as_u64().expect("space_cuts: usize") as usize;
+                }
+                if let Some(value) = config.remove("time_cuts") {
+                    time_cuts = value.as_u64().expect("time_cuts: usize") as usize;
+                }
+                if !config.is_empty() {
+                    panic!("unknown config keys: {:?}", config.keys().collect::<Vec<&String>>());
+                }
+                PhenomenologicalPlanarCodeGridPartition::new(d, noisy_measurements, space_cuts, time_cuts).build_apply(code)
+            }
             Self::PhenomenologicalRotatedCodeTimePartition => {
                 let config = partition_config.as_object_mut().expect("config must be JSON object");
                 let mut partition_num = 10;
@@ -947,6 +1260,40 @@ impl PartitionStrategy {
                 )
                 .build_apply(code)
             }
+            Self::PhenomenologicalRepetitionCodeTimePartition => {
+                let config = partition_config.as_object_mut().expect("config must be JSON object");
+                let mut partition_num = 10;
+                if let Some(value) = config.remove("partition_num") {
+                    partition_num = value.as_u64().expect("partition_num: usize") as usize;
+                }
+                if !config.is_empty() {
+                    panic!("unknown config keys: {:?}", config.keys().collect::<Vec<&String>>());
+                }
+                PhenomenologicalPlanarCodeTimePartition::new_repetition(d, noisy_measurements, partition_num)
+                    .build_apply(code)
+            }
+        };
+        let partition_config = match fusion_tree {
+            None => partition_config,
+            Some(fusion_tree) => {
+                assert!(
+                    matches!(
+                        self,
+                        Self::PhenomenologicalPlanarCodeTimePartition
+                            | Self::PhenomenologicalRotatedCodeTimePartition
+                            | Self::PhenomenologicalRepetitionCodeTimePartition
+                    ),
+                    "fusion_tree is only supported by time partitions"
+                );
+                let PartitionConfig {
+                    vertex_num, partitions, ..
+                } = partition_config;
+                match fusion_tree.as_str().expect("fusion_tree: string") {
+                    "linear" => PartitionConfig::new_linear(vertex_num, partitions),
+                    "balanced_tree" => PartitionConfig::new_balanced_tree(vertex_num, partitions),
+                    fusion_tree => panic!("unknown fusion_tree {:?}, expecting linear or balanced_tree", fusion_tree),
+                }
+            }
         };
         (code.get_initializer(), partition_config)
     }
@@ -972,6 +1319,7 @@ impl PrimalDualType {
             }
             Self::DualParallel => Box::new(SolverDualParallel::new(initializer, partition_info, primal_dual_config)),
             Self::Parallel => Box::new(SolverParallel::new(initializer, partition_info, primal_dual_config)),
+            Self::ChannelParallel => Box::new(SolverChannelParallel::new(initializer, partition_info, primal_dual_config)),
             Self::ErrorPatternLogger => Box::new(SolverErrorPatternLogger::new(
                 initializer,
                 &code.get_positions(),
@@ -989,8 +1337,15 @@ impl Verifier {
             Self::BlossomV => Box::new(VerifierBlossomV {
                 initializer: initializer.clone(),
                 subgraph_builder: SubGraphBuilder::new(initializer),
+                pure_rust: false,
+            }),
+            Self::BlossomRs => Box::new(VerifierBlossomV {
+                initializer: initializer.clone(),
+                subgraph_builder: SubGraphBuilder::new(initializer),
+                pure_rust: true,
             }),
             Self::FusionSerial => Box::new(VerifierFusionSerial::new(initializer)),
+            Self::Exhaustive => Box::new(VerifierExhaustive::new(initializer)),
         }
     }
 }
@@ -1022,6 +1377,8 @@ impl ResultVerifier for VerifierNone {
 pub struct VerifierBlossomV {
     initializer: SolverInitializer,
     subgraph_builder: SubGraphBuilder,
+    /// use [`crate::blossom_rs`] even if the blossom V library is present
+    pure_rust: bool,
 }
 
 pub fn get_primal_dual_solver_total_weight(
@@ -1055,7 +1412,11 @@ impl ResultVerifier for VerifierBlossomV {
             self.initializer.weighted_edges[*edge_index as usize] = (*vertex_idx_1, *vertex_idx_2, 0);
         }
         // use blossom V to compute ground truth
-        let blossom_mwpm_result = super::blossom_v_mwpm(&self.initializer, &syndrome_pattern.defect_vertices);
+        let blossom_mwpm_result = if self.pure_rust {
+            super::blossom_rs_mwpm(&self.initializer, &syndrome_pattern.defect_vertices)
+        } else {
+            super::blossom_v_mwpm(&self.initializer, &syndrome_pattern.defect_vertices)
+        };
         let blossom_details =
             super::detailed_matching(&self.initializer, &syndrome_pattern.defect_vertices, &blossom_mwpm_result);
         let mut blossom_total_weight = 0;
@@ -1144,3 +1505,62 @@ impl ResultVerifier for VerifierFusionSerial {
         }
     }
 }
+
+pub struct VerifierExhaustive {
+    pub initializer: SolverInitializer,
+    pub complete_graph: CompleteGraph,
+    pub subgraph_builder: SubGraphBuilder,
+}
+
+impl VerifierExhaustive {
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        Self {
+            initializer: initializer.clone(),
+            complete_graph: CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges),
+            subgraph_builder: SubGraphBuilder::new(initializer),
+        }
+    }
+}
+
+impl ResultVerifier for VerifierExhaustive {
+    fn verify(
+        &mut self,
+        primal_dual_solver: &mut Box<dyn PrimalDualSolver>,
+        syndrome_pattern: &SyndromePattern,
+        visualizer: Option<&mut Visualizer>,
+    ) {
+        // erasures and dynamic weights cannot be provided at the same time
+        self.complete_graph.reset();
+        if syndrome_pattern.dynamic_weights.is_empty() {
+            self.complete_graph.load_erasures(&syndrome_pattern.erasures);
+        } else {
+            self.complete_graph.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+        }
+        let exact_total_weight = crate::exhaustive::exhaustive_mwpm_weight(
+            &mut self.complete_graph,
+            &self.initializer,
+            &syndrome_pattern.defect_vertices,
+        );
+        assert_eq!(
+            primal_dual_solver.sum_dual_variables(),
+            exact_total_weight,
+            "unexpected final dual variable sum"
+        );
+        self.subgraph_builder.clear();
+        if syndrome_pattern.dynamic_weights.is_empty() {
+            self.subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+        } else {
+            self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+        }
+        let mwpm = primal_dual_solver.perfect_matching();
+        self.subgraph_builder.load_perfect_matching(&mwpm);
+        assert_eq!(
+            self.subgraph_builder.total_weight(),
+            exact_total_weight,
+            "unexpected perfect matching weight"
+        );
+        if visualizer.is_some() {
+            primal_dual_solver.subgraph_visualizer(visualizer);
+        }
+    }
+}
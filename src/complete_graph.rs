@@ -2,7 +2,8 @@ use super::dual_module::EdgeWeightModifier;
 use super::util::*;
 use crate::priority_queue::PriorityQueue;
 use crate::rayon::prelude::*;
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
 
 /// build complete graph out of skeleton graph using Dijkstra's algorithm
 #[derive(Debug, Clone)]
@@ -138,46 +139,40 @@ impl CompleteGraph {
             }
             // add its neighbors to priority queue
             for (&neighbor, &neighbor_weight) in self.vertices[target as usize].edges.iter() {
-                let edge_weight = weight + neighbor_weight;
-                if let Some(PriorityElement {
-                    weight: existing_weight,
-                    previous: existing_previous,
-                }) = pq.get_priority(&neighbor)
-                {
-                    // update the priority if weight is smaller or weight is equal but distance is smaller
-                    // this is necessary if the graph has weight-0 edges, which could lead to cycles in the graph and cause deadlock
-                    let mut update = &edge_weight < existing_weight;
-                    if &edge_weight == existing_weight {
-                        let distance = if neighbor > previous {
-                            neighbor - previous
-                        } else {
-                            previous - neighbor
-                        };
-                        let existing_distance = if &neighbor > existing_previous {
-                            neighbor - existing_previous
-                        } else {
-                            existing_previous - neighbor
-                        };
-                        // prevent loop by enforcing strong non-descending
-                        if distance < existing_distance || (distance == existing_distance && &previous < existing_previous) {
-                            update = true;
-                        }
-                    }
-                    if update {
-                        pq.change_priority(&neighbor, PriorityElement::new(edge_weight, target));
-                    }
-                } else {
-                    // insert new entry only if neighbor has not been visited
-                    if self.vertices[neighbor as usize].timestamp != active_timestamp {
-                        pq.push(neighbor, PriorityElement::new(edge_weight, target));
-                    }
-                }
+                let visited = self.vertices[neighbor as usize].timestamp == active_timestamp;
+                relax_neighbor(&mut pq, neighbor, weight + neighbor_weight, target, previous, visited);
             }
         }
         // println!("[debug] computed_edges: {:?}", computed_edges);
         computed_edges
     }
 
+    /// the same as [`Self::all_edges_with_terminate`] but without touching the timestamps, so that a single complete graph
+    /// can serve the queries of many threads at the same time
+    #[allow(clippy::unnecessary_cast)]
+    pub fn all_edges_with_terminate_shared(
+        &self,
+        vertex: VertexIndex,
+        terminate: VertexIndex,
+    ) -> BTreeMap<VertexIndex, (VertexIndex, Weight)> {
+        let mut pq = PriorityQueue::<EdgeIndex, PriorityElement>::new();
+        pq.push(vertex, PriorityElement::new(0, vertex));
+        let mut computed_edges = BTreeMap::<VertexIndex, (VertexIndex, Weight)>::new(); // { peer: (previous, weight) }
+        while let Some((target, PriorityElement { weight, previous })) = pq.pop() {
+            if target != vertex {
+                computed_edges.insert(target, (previous, weight));
+                if target == terminate {
+                    break; // early terminate
+                }
+            }
+            for (&neighbor, &neighbor_weight) in self.vertices[target as usize].edges.iter() {
+                let visited = neighbor == vertex || computed_edges.contains_key(&neighbor);
+                relax_neighbor(&mut pq, neighbor, weight + neighbor_weight, target, previous, visited);
+            }
+        }
+        computed_edges
+    }
+
     /// get all complete graph edges from the specific vertex
     pub fn all_edges(&mut self, vertex: VertexIndex) -> BTreeMap<VertexIndex, (VertexIndex, Weight)> {
         self.all_edges_with_terminate(vertex, VertexIndex::MAX)
@@ -188,6 +183,58 @@ impl CompleteGraph {
         assert_ne!(a, b, "cannot get path between the same vertex");
         let edges = self.all_edges_with_terminate(a, b);
         // println!("edges: {:?}", edges);
+        Self::trace_path(&edges, a, b)
+    }
+
+    /// the same as [`Self::get_path`] but can be called from many threads at the same time
+    pub fn get_path_shared(&self, a: VertexIndex, b: VertexIndex) -> (Vec<(VertexIndex, Weight)>, Weight) {
+        assert_ne!(a, b, "cannot get path between the same vertex");
+        let edges = self.all_edges_with_terminate_shared(a, b);
+        Self::trace_path(&edges, a, b)
+    }
+
+    /// the weight of the minimum-weight paths from the specific vertex to every reachable vertex, and the number of such
+    /// paths; a path may go through zero-weight edges, e.g. erasures, only in the order that Dijkstra's algorithm visits
+    /// the vertices, so that the count stays finite. The count is a float because it grows exponentially with the weight
+    #[allow(clippy::unnecessary_cast)]
+    pub fn minimum_weight_path_counts(&self, vertex: VertexIndex) -> BTreeMap<VertexIndex, (Weight, f64)> {
+        let mut counts = BTreeMap::<VertexIndex, (Weight, f64)>::new();
+        let mut visited = BTreeSet::<VertexIndex>::new();
+        let mut heap = BinaryHeap::new();
+        counts.insert(vertex, (0, 1.));
+        heap.push(Reverse((0, vertex)));
+        while let Some(Reverse((weight, target))) = heap.pop() {
+            if !visited.insert(target) {
+                continue; // already visited with a smaller weight
+            }
+            let count = counts[&target].1;
+            for (&neighbor, &neighbor_weight) in self.vertices[target as usize].edges.iter() {
+                if visited.contains(&neighbor) {
+                    continue;
+                }
+                let neighbor_weight = weight + neighbor_weight;
+                match counts.get_mut(&neighbor) {
+                    Some((existing_weight, existing_count)) if *existing_weight <= neighbor_weight => {
+                        if *existing_weight == neighbor_weight {
+                            *existing_count += count;
+                        }
+                    }
+                    _ => {
+                        counts.insert(neighbor, (neighbor_weight, count));
+                        heap.push(Reverse((neighbor_weight, neighbor)));
+                    }
+                }
+            }
+        }
+        counts.remove(&vertex);
+        counts
+    }
+
+    fn trace_path(
+        edges: &BTreeMap<VertexIndex, (VertexIndex, Weight)>,
+        a: VertexIndex,
+        b: VertexIndex,
+    ) -> (Vec<(VertexIndex, Weight)>, Weight) {
         let mut vertex = b;
         let mut path = Vec::new();
         loop {
@@ -207,6 +254,49 @@ impl CompleteGraph {
     }
 }
 
+/// push a neighbor of `target` into the priority queue of Dijkstra's algorithm, or lower its priority if a better path
+/// is found; `previous` is the vertex before `target` on its own path, used to break ties
+fn relax_neighbor(
+    pq: &mut PriorityQueue<EdgeIndex, PriorityElement>,
+    neighbor: VertexIndex,
+    edge_weight: Weight,
+    target: VertexIndex,
+    previous: VertexIndex,
+    visited: bool,
+) {
+    if let Some(PriorityElement {
+        weight: existing_weight,
+        previous: existing_previous,
+    }) = pq.get_priority(&neighbor)
+    {
+        // update the priority if weight is smaller or weight is equal but distance is smaller
+        // this is necessary if the graph has weight-0 edges, which could lead to cycles in the graph and cause deadlock
+        let mut update = &edge_weight < existing_weight;
+        if &edge_weight == existing_weight {
+            let distance = if neighbor > previous {
+                neighbor - previous
+            } else {
+                previous - neighbor
+            };
+            let existing_distance = if &neighbor > existing_previous {
+                neighbor - existing_previous
+            } else {
+                existing_previous - neighbor
+            };
+            // prevent loop by enforcing strong non-descending
+            if distance < existing_distance || (distance == existing_distance && &previous < existing_previous) {
+                update = true;
+            }
+        }
+        if update {
+            pq.change_priority(&neighbor, PriorityElement::new(edge_weight, target));
+        }
+    } else if !visited {
+        // insert new entry only if neighbor has not been visited
+        pq.push(neighbor, PriorityElement::new(edge_weight, target));
+    }
+}
+
 #[derive(Clone)]
 pub struct PrebuiltCompleteGraph {
     /// number of vertices
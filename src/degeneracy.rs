@@ -0,0 +1,231 @@
+//! Degeneracy
+//!
+//! The corrections of a syndrome other than the minimum-weight one, in the order of increasing weight, so that the
+//! degeneracy of the syndrome can be estimated and an approximate maximum-likelihood decision can be made on top of
+//! MWPM, e.g. by summing up the probabilities of the corrections in each logical class.
+//! The defects are matched exactly like [`crate::blossom_rs_mwpm`] does, and the k best matchings are enumerated by
+//! Murty's algorithm, which partitions the remaining matchings by forcing and forbidding the matched pairs of the last
+//! enumerated one. Corrections that only differ by the paths of the same matched pairs are not enumerated but counted,
+//! see [`AlternativeCorrection::degeneracy`]
+//!
+
+use super::blossom_rs;
+use super::util::*;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap};
+
+/// a correction of the syndrome given by its matched pairs, see [`crate::primal_module::SubGraphBuilder::alternative_corrections`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlternativeCorrection {
+    /// matched pairs of defect vertices, or of a defect vertex and its nearest virtual vertex
+    pub matched_pairs: Vec<(VertexIndex, VertexIndex)>,
+    /// the minimum-weight paths of the matched pairs
+    pub subgraph: Vec<EdgeIndex>,
+    /// the sum of the weights of the matched pairs; it's the weight of the subgraph unless the paths overlap
+    pub weight: Weight,
+    /// the number of ways to choose the minimum-weight paths of the matched pairs, i.e. the number of corrections of
+    /// the same weight that this one stands for
+    pub degeneracy: f64,
+}
+
+/// the matching problem of the defects, where each defect is matched either to another defect or to the boundary
+#[derive(Debug, Clone)]
+pub struct DefectMatchingProblem {
+    pub defect_num: usize,
+    /// the weight between each pair of defects `(i, j)` where `i < j`, if connected
+    pub pair_weights: BTreeMap<(usize, usize), Weight>,
+    /// the weight from each defect to its nearest virtual vertex, if any
+    pub boundary_weights: Vec<Option<Weight>>,
+}
+
+/// a matching of [`DefectMatchingProblem`] given by the peer of each defect; a defect matched to itself is matched to
+/// the boundary
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DefectMatching {
+    pub peers: Vec<usize>,
+    pub weight: Weight,
+}
+
+impl DefectMatching {
+    /// the matched pairs `(i, j)` where `i <= j`, and `(i, i)` means matching to the boundary
+    pub fn pairs(&self) -> Vec<(usize, usize)> {
+        (0..self.peers.len())
+            .filter(|&i| i <= self.peers[i])
+            .map(|i| (i, self.peers[i]))
+            .collect()
+    }
+}
+
+impl DefectMatchingProblem {
+    /// the minimum-weight matching that contains all the `forced` pairs and none of the `forbidden` pairs, if exists
+    #[allow(clippy::unnecessary_cast)]
+    pub fn solve(&self, forced: &[(usize, usize)], forbidden: &BTreeSet<(usize, usize)>) -> Option<DefectMatching> {
+        let defect_num = self.defect_num;
+        let mut forced_peers: Vec<Option<usize>> = vec![None; defect_num];
+        for &(i, j) in forced.iter() {
+            forced_peers[i] = Some(j);
+            forced_peers[j] = Some(i);
+        }
+        let allowed = |i: usize, j: usize| {
+            !forbidden.contains(&(i, j)) && forced_peers[i].unwrap_or(j) == j && forced_peers[j].unwrap_or(i) == i
+        };
+        // the same reduction as the legacy matchers: each defect has a corresponding virtual vertex to be matched
+        let has_boundary = self.boundary_weights.iter().any(|weight| weight.is_some());
+        let mut weighted_edges = Vec::<(usize, usize, u32)>::new();
+        for (&(i, j), &weight) in self.pair_weights.iter() {
+            if allowed(i, j) {
+                weighted_edges.push((i, j, weight as u32));
+            }
+        }
+        if has_boundary {
+            for i in 0..defect_num {
+                if let Some(weight) = self.boundary_weights[i] {
+                    if allowed(i, i) {
+                        weighted_edges.push((i, i + defect_num, weight as u32));
+                    }
+                }
+                for j in (i + 1)..defect_num {
+                    weighted_edges.push((i + defect_num, j + defect_num, 0));
+                }
+            }
+        }
+        let node_num = if has_boundary { 2 * defect_num } else { defect_num };
+        let mate = blossom_rs::try_minimum_weight_perfect_matching(node_num, &weighted_edges)?;
+        let peers: Vec<usize> = (0..defect_num)
+            .map(|i| if mate[i] < defect_num { mate[i] } else { i })
+            .collect();
+        let weight = (0..defect_num)
+            .filter(|&i| i <= peers[i])
+            .map(|i| {
+                if peers[i] == i {
+                    self.boundary_weights[i].unwrap()
+                } else {
+                    self.pair_weights[&(i, peers[i])]
+                }
+            })
+            .sum();
+        Some(DefectMatching { peers, weight })
+    }
+
+    /// up to `max_count` matchings in the order of increasing weight, each at most `max_weight_gap` heavier than the
+    /// minimum-weight one; empty if there is no matching at all
+    pub fn k_best_matchings(&self, max_count: usize, max_weight_gap: Weight) -> Vec<DefectMatching> {
+        let mut matchings = vec![];
+        let Some(best) = self.solve(&[], &BTreeSet::new()) else {
+            return matchings;
+        };
+        let max_weight = best.weight.saturating_add(max_weight_gap);
+        // each subproblem is the set of matchings with all the forced pairs and none of the forbidden pairs
+        let mut subproblems = vec![(best, vec![], BTreeSet::new())];
+        let mut queue = BinaryHeap::new();
+        queue.push(Reverse((subproblems[0].0.weight, 0)));
+        while let Some(Reverse((_, index))) = queue.pop() {
+            if matchings.len() >= max_count {
+                break;
+            }
+            let (matching, forced, forbidden) = std::mem::take(&mut subproblems[index]);
+            // the matchings except this one are partitioned by the first pair that is not matched
+            let mut next_forced = forced.clone();
+            for pair in matching.pairs().into_iter().filter(|pair| !forced.contains(pair)) {
+                let mut next_forbidden = forbidden.clone();
+                next_forbidden.insert(pair);
+                if let Some(next) = self.solve(&next_forced, &next_forbidden) {
+                    if next.weight <= max_weight {
+                        queue.push(Reverse((next.weight, subproblems.len())));
+                        subproblems.push((next, next_forced.clone(), next_forbidden));
+                    }
+                }
+                next_forced.push(pair);
+            }
+            matchings.push(matching);
+        }
+        matchings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::primal_module::*;
+    use super::*;
+
+    #[test]
+    fn degeneracy_alternative_corrections_1() {
+        // cargo test degeneracy_alternative_corrections_1 -- --nocapture
+        // vertices 0..6 form a chain between the virtual vertices 7 (left) and 6 (right), each edge of weight 1000
+        let code = CodeCapacityRepetitionCode::new(7, 0.1, 500);
+        let subgraph_builder = SubGraphBuilder::new(&code.get_initializer());
+        let corrections = subgraph_builder.alternative_corrections(&[1, 4], 10, Weight::MAX);
+        assert_eq!(corrections.len(), 2);
+        assert_eq!(corrections[0].matched_pairs, vec![(1, 4)]);
+        assert_eq!(corrections[0].subgraph, vec![1, 2, 3]);
+        assert_eq!(corrections[0].weight, 3000);
+        assert_eq!(corrections[1].matched_pairs, vec![(1, 7), (4, 6)]);
+        assert_eq!(corrections[1].weight, 4000);
+        assert!(corrections.iter().all(|correction| correction.degeneracy == 1.));
+        // the heavier correction is beyond the gap
+        assert_eq!(subgraph_builder.alternative_corrections(&[1, 4], 10, 999).len(), 1);
+        assert_eq!(subgraph_builder.alternative_corrections(&[], 10, 0)[0].weight, 0);
+    }
+
+    #[test]
+    fn degeneracy_alternative_corrections_2() {
+        // cargo test degeneracy_alternative_corrections_2 -- --nocapture
+        // vertex index is row * 7 + column on a torus, each edge of weight 1000
+        let code = CodeCapacityToricCode::new(7, 0.1, 500);
+        let subgraph_builder = SubGraphBuilder::new(&code.get_initializer());
+        let corrections = subgraph_builder.alternative_corrections(&[0, 8, 24, 32], 3, Weight::MAX);
+        let weights: Vec<Weight> = corrections.iter().map(|correction| correction.weight).collect();
+        assert_eq!(weights, vec![4000, 10000, 12000]);
+        // each diagonal step of (0, 8) and (24, 32) has 2 paths
+        assert_eq!(corrections[0].degeneracy, 4.);
+        // (0, 32) wraps around 3 + 3 steps, and (8, 24) takes 2 + 2 steps
+        assert_eq!(corrections[1].matched_pairs, vec![(0, 32), (8, 24)]);
+        assert_eq!(corrections[1].degeneracy, 20. * 6.);
+    }
+
+    /// the first correction is a minimum-weight one, and the matchings are distinct and sorted
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn degeneracy_alternative_corrections_3() {
+        // cargo test degeneracy_alternative_corrections_3 -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(5, 4, 0.03, 500);
+        code.set_erasure_probability(0.01);
+        let initializer = code.get_initializer();
+        let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            subgraph_builder.clear();
+            subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+            let corrections = subgraph_builder.alternative_corrections(&syndrome_pattern.defect_vertices, 8, 2000);
+            assert!(!corrections.is_empty());
+            let expected = {
+                let mut initializer = initializer.clone();
+                for &edge_index in syndrome_pattern.erasures.iter() {
+                    initializer.weighted_edges[edge_index as usize].2 = 0;
+                }
+                let mwpm_result = super::super::blossom_rs_mwpm(&initializer, &syndrome_pattern.defect_vertices);
+                super::super::detailed_matching(&initializer, &syndrome_pattern.defect_vertices, &mwpm_result)
+                    .iter()
+                    .map(|detail| detail.weight)
+                    .sum::<Weight>()
+            };
+            assert_eq!(corrections[0].weight, expected, "seed {seed}");
+            for (index, correction) in corrections.iter().enumerate() {
+                assert!(correction.weight <= expected + 2000);
+                assert!(correction.degeneracy >= 1.);
+                subgraph_builder.load_subgraph(&correction.subgraph);
+                assert_eq!(
+                    subgraph_builder.subgraph_boundary(),
+                    syndrome_pattern.defect_vertices.iter().cloned().collect()
+                );
+                if index > 0 {
+                    assert!(correction.weight >= corrections[index - 1].weight);
+                }
+                for other in corrections[..index].iter() {
+                    assert_ne!(correction.matched_pairs, other.matched_pairs);
+                }
+            }
+        }
+    }
+}
@@ -104,6 +104,17 @@ pub enum MaxUpdateLength {
     VertexShrinkStop((DualNodePtr, Option<(DualNodePtr, DualNodePtr)>)),
 }
 
+/// the origin of an obstacle, recorded when the obstacle is created so that the conflict can be routed to the responsible
+/// unit without searching from the dual node; only recorded by [`crate::dual_module_parallel::DualModuleParallelUnit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaxUpdateLengthOrigin {
+    /// the unit whose serial module reports the obstacle, or the active unit if the obstacle is formed by two
+    /// `VertexShrinkStop` from different units
+    pub unit_index: usize,
+    /// the active unit that computes the obstacle and is responsible for resolving it
+    pub interface_index: usize,
+}
+
 cfg_if::cfg_if! {
     if #[cfg(feature="ordered_conflicts")] {
         use std::collections::BinaryHeap;
@@ -118,8 +129,14 @@ cfg_if::cfg_if! {
 pub enum GroupMaxUpdateLength {
     /// non-zero maximum update length, has_empty_boundary_node (useful in fusion)
     NonZeroGrow((Weight, bool)),
-    /// conflicting reasons and pending VertexShrinkStop events (empty in a single serial dual module)
-    Conflicts((ConflictList, BTreeMap<VertexIndex, MaxUpdateLength>)),
+    /// conflicting reasons, pending VertexShrinkStop events (empty in a single serial dual module) and the origins of them
+    Conflicts(
+        (
+            ConflictList,
+            BTreeMap<VertexIndex, MaxUpdateLength>,
+            BTreeMap<MaxUpdateLength, MaxUpdateLengthOrigin>,
+        ),
+    ),
 }
 
 impl Default for GroupMaxUpdateLength {
@@ -136,7 +153,7 @@ impl GroupMaxUpdateLength {
     /// update all the interface nodes to be up-to-date, only necessary in existence of fusion
     #[inline(never)]
     pub fn update(&self) {
-        if let Self::Conflicts((list, pending_stops)) = &self {
+        if let Self::Conflicts((list, pending_stops, _)) = &self {
             for max_update_length in list.iter() {
                 max_update_length.update();
             }
@@ -191,10 +208,10 @@ impl GroupMaxUpdateLength {
                     } else {
                         list.push(max_update_length);
                     }
-                    *self = Self::Conflicts((list, pending_stops));
+                    *self = Self::Conflicts((list, pending_stops, BTreeMap::new()));
                 }
             }
-            Self::Conflicts((list, pending_stops)) => {
+            Self::Conflicts((list, pending_stops, _)) => {
                 // only add conflicts, not NonZeroGrow
                 if !matches!(max_update_length, MaxUpdateLength::NonZeroGrow(_)) {
                     Self::add_pending_stop(list, pending_stops, max_update_length);
@@ -212,17 +229,14 @@ impl GroupMaxUpdateLength {
                 Self::NonZeroGrow(length) => {
                     *current_length = std::cmp::min(*current_length, length);
                 }
-                Self::Conflicts((mut other_list, mut other_pending_stops)) => {
-                    let mut list = ConflictList::new();
-                    let mut pending_stops = BTreeMap::new();
-                    std::mem::swap(&mut list, &mut other_list);
-                    std::mem::swap(&mut pending_stops, &mut other_pending_stops);
-                    *self = Self::Conflicts((list, pending_stops));
+                Self::Conflicts(conflicts) => {
+                    *self = Self::Conflicts(conflicts);
                 }
             },
-            Self::Conflicts((list, pending_stops)) => {
-                if let Self::Conflicts((other_list, other_pending_stops)) = other {
+            Self::Conflicts((list, pending_stops, origins)) => {
+                if let Self::Conflicts((other_list, other_pending_stops, other_origins)) = other {
                     list.extend(other_list);
+                    origins.extend(other_origins);
                     for (_, max_update_length) in other_pending_stops.into_iter() {
                         Self::add_pending_stop(list, pending_stops, max_update_length);
                     }
@@ -257,7 +271,7 @@ impl GroupMaxUpdateLength {
             Self::NonZeroGrow(_) => {
                 panic!("please call GroupMaxUpdateLength::get_none_zero_growth to check if this group is none_zero_growth");
             }
-            Self::Conflicts((list, pending_stops)) => {
+            Self::Conflicts((list, pending_stops, _)) => {
                 list.pop().or(if let Some(key) = pending_stops.keys().next().cloned() {
                     pending_stops.remove(&key)
                 } else {
@@ -272,7 +286,7 @@ impl GroupMaxUpdateLength {
             Self::NonZeroGrow(_) => {
                 panic!("please call GroupMaxUpdateLength::get_none_zero_growth to check if this group is none_zero_growth");
             }
-            Self::Conflicts((list, pending_stops)) => {
+            Self::Conflicts((list, pending_stops, _)) => {
                 cfg_if::cfg_if! {
                     if #[cfg(feature="ordered_conflicts")] {
                         let peek_element = list.peek();
@@ -288,6 +302,42 @@ impl GroupMaxUpdateLength {
             }
         }
     }
+
+    /// record the unit that creates the obstacles without an origin, called right after they're computed
+    pub fn record_origin(&mut self, unit_index: usize) {
+        if let Self::Conflicts((list, pending_stops, origins)) = self {
+            for max_update_length in list.iter().chain(pending_stops.values()) {
+                origins.entry(max_update_length.clone()).or_insert(MaxUpdateLengthOrigin {
+                    unit_index,
+                    interface_index: unit_index,
+                });
+            }
+        }
+    }
+
+    /// record the active unit that is responsible for all the obstacles; obstacles formed when merging the groups of
+    /// different units originate from this active unit
+    pub fn record_interface(&mut self, interface_index: usize) {
+        if let Self::Conflicts((list, pending_stops, origins)) = self {
+            for max_update_length in list.iter().chain(pending_stops.values()) {
+                origins
+                    .entry(max_update_length.clone())
+                    .or_insert(MaxUpdateLengthOrigin {
+                        unit_index: interface_index,
+                        interface_index,
+                    })
+                    .interface_index = interface_index;
+            }
+        }
+    }
+
+    /// the recorded origin of an obstacle in this group, `None` if not recorded, e.g. computed by a serial module
+    pub fn get_origin(&self, max_update_length: &MaxUpdateLength) -> Option<MaxUpdateLengthOrigin> {
+        match self {
+            Self::NonZeroGrow(_) => None,
+            Self::Conflicts((_, _, origins)) => origins.get(max_update_length).cloned(),
+        }
+    }
 }
 
 /// A dual node corresponds to either a vertex or a blossom (on which the dual variables are defined)
@@ -543,6 +593,13 @@ pub trait DualModuleImpl {
         self.add_dual_node(dual_node_ptr)
     }
 
+    /// add the defect nodes of a syndrome at once, which an implementation can distribute to independent parts in parallel
+    fn add_defect_nodes(&mut self, dual_node_ptrs: &[DualNodePtr]) {
+        for dual_node_ptr in dual_node_ptrs.iter() {
+            self.add_defect_node(dual_node_ptr);
+        }
+    }
+
     #[inline(always)]
     /// helper function to specifically add a blossom node
     fn add_blossom(&mut self, dual_node_ptr: &DualNodePtr) {
@@ -580,6 +637,13 @@ pub trait DualModuleImpl {
     /// this number will be 0 if any conflicting reason presents
     fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength;
 
+    /// the same as [`DualModuleImpl::compute_maximum_update_length`] but assuming all the dual nodes are already prepared
+    /// by [`DualModuleImpl::prepare_all`], so that it doesn't modify the module and can run under a shared lock;
+    /// `None` if the module doesn't support it
+    fn query_prepared_maximum_update_length(&self) -> Option<GroupMaxUpdateLength> {
+        None
+    }
+
     /// An optional function that can manipulate individual dual node, not necessarily supported by all implementations
     fn grow_dual_node(&mut self, _dual_node_ptr: &DualNodePtr, _length: Weight) {
         panic!("the dual module implementation doesn't support this function, please use another dual module")
@@ -708,6 +772,8 @@ impl FusionVisualizer for DualModuleInterfacePtr {
                         DualNodeGrowState::Stay => 0,
                     },
                     if abbrev { "p" } else { "parent_blossom" }: dual_node.parent_blossom.as_ref().map(|weak| weak.upgrade_force().read_recursive().index),
+                    // the dual module may only report the dual variables of the nodes it owns, so also include the value tracked by the interface
+                    if abbrev { "di" } else { "interface_dual_variable" }: dual_node.get_dual_variable(&interface),
                 }));
             } else {
                 dual_nodes.push(json!(null));
@@ -781,7 +847,7 @@ impl DualModuleInterface {
 impl DualModuleInterfacePtr {
     /// create an empty interface
     pub fn new_empty() -> Self {
-        Self::new_value(DualModuleInterface {
+        let interface_ptr = Self::new_value(DualModuleInterface {
             unit_index: 0, // if necessary, manually change it
             nodes: Vec::new(),
             nodes_length: 0,
@@ -793,7 +859,10 @@ impl DualModuleInterfacePtr {
             parent: None,
             index_bias: 0,
             children: None,
-        })
+        });
+        #[cfg(feature = "lock_order_audit")]
+        crate::lock_order_audit::register(&interface_ptr, "interface".to_string());
+        interface_ptr
     }
 
     /// a dual module interface MUST be created given a concrete implementation of the dual module
@@ -804,9 +873,16 @@ impl DualModuleInterfacePtr {
     }
 
     pub fn load(&self, syndrome_pattern: &SyndromePattern, dual_module_impl: &mut impl DualModuleImpl) {
-        for vertex_idx in syndrome_pattern.defect_vertices.iter() {
-            self.create_defect_node(*vertex_idx, dual_module_impl);
-        }
+        assert!(
+            syndrome_pattern.detector_confidences.is_empty(),
+            "detector_confidences must be converted by `SyndromePattern::apply_detector_confidences` before loading"
+        );
+        let defect_nodes: Vec<DualNodePtr> = syndrome_pattern
+            .defect_vertices
+            .iter()
+            .map(|vertex_idx| self.push_defect_node(*vertex_idx))
+            .collect();
+        dual_module_impl.add_defect_nodes(&defect_nodes);
         if !syndrome_pattern.erasures.is_empty() {
             assert!(
                 syndrome_pattern.dynamic_weights.is_empty(),
@@ -857,6 +933,14 @@ impl DualModuleInterfacePtr {
     }
 
     pub fn create_defect_node(&self, vertex_idx: VertexIndex, dual_module_impl: &mut impl DualModuleImpl) -> DualNodePtr {
+        let node_ptr = self.push_defect_node(vertex_idx);
+        dual_module_impl.add_defect_node(&node_ptr);
+        node_ptr
+    }
+
+    /// create a defect node in the interface without adding it to the dual module, so that multiple defect nodes can be
+    /// added at once by [`DualModuleImpl::add_defect_nodes`]
+    pub fn push_defect_node(&self, vertex_idx: VertexIndex) -> DualNodePtr {
         let belonging = self.downgrade();
         let mut interface = self.write();
         interface.sum_grow_speed += 1;
@@ -899,8 +983,6 @@ impl DualModuleInterfacePtr {
         }
         let cloned_node_ptr = node_ptr.clone();
         interface.nodes[local_node_index] = Some(node_ptr); // feature `dangerous_pointer`: must push the owner
-        drop(interface);
-        dual_module_impl.add_defect_node(&cloned_node_ptr);
         cloned_node_ptr
     }
 
@@ -926,6 +1008,7 @@ impl DualModuleInterfacePtr {
         mut touching_children: Vec<(DualNodeWeak, DualNodeWeak)>,
         dual_module_impl: &mut impl DualModuleImpl,
     ) -> DualNodePtr {
+        trace_span!("create_blossom", nodes = nodes_circle.len());
         let belonging = self.downgrade();
         let mut interface = self.write();
         if touching_children.is_empty() {
@@ -993,7 +1076,7 @@ impl DualModuleInterfacePtr {
         }
         let mut interface = self.write();
         if interface.debug_print_actions {
-            eprintln!("[create blossom] {:?} -> {}", nodes_circle, node_index);
+            diagnostic!("[create blossom] {:?} -> {}", nodes_circle, node_index);
         }
         let cloned_blossom_node_ptr = blossom_node_ptr.clone();
         {
@@ -1021,11 +1104,12 @@ impl DualModuleInterfacePtr {
     /// this is because we're growing all trees together, and due to the natural of quantum codes, this operation is not likely to cause
     /// bottleneck as long as physical error rate is well below the threshold. All internal nodes will have a [`DualNodeGrowState::Grow`] state afterwards.
     pub fn expand_blossom(&self, blossom_node_ptr: DualNodePtr, dual_module_impl: &mut impl DualModuleImpl) {
+        trace_span!("expand_blossom");
         let interface = self.read_recursive();
         if interface.debug_print_actions {
             let node = blossom_node_ptr.read_recursive();
             if let DualNodeClass::Blossom { nodes_circle, .. } = &node.class {
-                eprintln!("[expand blossom] {:?} -> {:?}", blossom_node_ptr, nodes_circle);
+                diagnostic!("[expand blossom] {:?} -> {:?}", blossom_node_ptr, nodes_circle);
             } else {
                 unreachable!()
             }
@@ -1110,7 +1194,7 @@ impl DualModuleInterfacePtr {
         }
         let mut interface = self.write();
         if interface.debug_print_actions {
-            eprintln!("[set grow state] {:?} {:?}", dual_node_ptr, grow_state);
+            diagnostic!("[set grow state] {:?} {:?}", dual_node_ptr, grow_state);
         }
         {
             // update sum_grow_speed and dual variable cache
@@ -0,0 +1,455 @@
+//! Channel Dual Module
+//!
+//! A message-passing implementation of the dual module, where each partition unit is hosted by a rank and the
+//! coordinator only talks to the ranks by message passing, following the design of [`crate::dual_module_parallel`].
+//! The coordinator (the primal module side) never writes a unit directly: operations on a single dual node are sent to
+//! the rank hosting the active ancestor of that dual node, and global operations like growing are broadcast to all
+//! ranks and gathered. Fusion follows the tree structure in [`PartitionInfo`]: a fusion unit is hosted by the same rank
+//! as its left child, and it's fused by that rank once both children are active.
+//!
+//! The ranks are threads of the same process exchanging [`RankCommand`] over in-memory channels, and the dual nodes and
+//! units are shared in memory between the coordinator and the ranks; there is no inter-process transport. It exercises
+//! the message protocol, e.g. counting the messages, and running the ranks in separate processes would additionally
+//! require serializing the commands and referring to the dual nodes by their index
+//!
+
+use super::dual_module::*;
+use super::dual_module_parallel::*;
+use super::pointers::*;
+use super::util::*;
+use super::visualize::*;
+use crate::serde_json;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DualModuleChannelConfig {
+    /// the number of ranks; by default one rank per partition, i.e. each leaf unit lives in a separate rank
+    #[serde(default = "dual_module_channel_default_configs::rank_count")]
+    pub rank_count: usize,
+    /// see [`DualModuleParallelConfig::edges_in_fusion_unit`]
+    #[serde(default = "dual_module_channel_default_configs::edges_in_fusion_unit")]
+    pub edges_in_fusion_unit: bool,
+}
+
+impl Default for DualModuleChannelConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+pub mod dual_module_channel_default_configs {
+    pub fn rank_count() -> usize {
+        0
+    } // by default to the number of partitions
+    pub fn edges_in_fusion_unit() -> bool {
+        true
+    } // the same as the parallel dual module
+}
+
+/// an operation on a unit hosted by a rank
+pub enum UnitCommand {
+    AddDualNode(DualNodePtr),
+    RemoveBlossom(DualNodePtr),
+    SetGrowState(DualNodePtr, DualNodeGrowState),
+    ComputeMaximumUpdateLengthDualNode(DualNodePtr, bool, bool),
+    GrowDualNode(DualNodePtr, Weight),
+    PrepareNodesShrink(Vec<DualNodePtr>),
+    /// fuse the two children of this unit, which must both be active
+    StaticFuse,
+}
+
+/// the messages sent from the coordinator to a rank; every message is answered by exactly one [`RankReply`]
+pub enum RankCommand {
+    /// run an operation on a unit hosted by this rank
+    Unit(usize, UnitCommand),
+    /// clear all the hosted units, and then only the leaf units are active
+    Clear,
+    /// compute the maximum update length of all the active hosted units
+    ComputeMaximumUpdateLength,
+    /// grow all the active hosted units
+    Grow(Weight),
+    /// load the edge modifier to all the hosted units, including the inactive ones
+    LoadEdgeModifier(Vec<(EdgeIndex, Weight)>),
+    /// stop the rank
+    Shutdown,
+}
+
+pub enum RankReply {
+    Done,
+    MaxUpdateLength(MaxUpdateLength),
+    GroupMaxUpdateLength(GroupMaxUpdateLength),
+}
+
+/// the coordinator side of a rank
+pub struct RankHandle {
+    sender: Sender<RankCommand>,
+    receiver: Receiver<RankReply>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// the units hosted by a rank, indexed by the unit index
+struct Rank<SerialModule: DualModuleImpl + Send + Sync> {
+    units: BTreeMap<usize, DualModuleParallelUnitPtr<SerialModule>>,
+    partition_info: Arc<PartitionInfo>,
+}
+
+impl<SerialModule: DualModuleImpl + Send + Sync> Rank<SerialModule> {
+    fn execute(&mut self, command: RankCommand) -> RankReply {
+        match command {
+            RankCommand::Unit(unit_index, unit_command) => {
+                lock_write!(unit, self.units[&unit_index]);
+                match unit_command {
+                    UnitCommand::AddDualNode(dual_node_ptr) => unit.add_dual_node(&dual_node_ptr),
+                    UnitCommand::RemoveBlossom(dual_node_ptr) => unit.remove_blossom(dual_node_ptr),
+                    UnitCommand::SetGrowState(dual_node_ptr, grow_state) => unit.set_grow_state(&dual_node_ptr, grow_state),
+                    UnitCommand::ComputeMaximumUpdateLengthDualNode(dual_node_ptr, is_grow, simultaneous_update) => {
+                        return RankReply::MaxUpdateLength(unit.compute_maximum_update_length_dual_node(
+                            &dual_node_ptr,
+                            is_grow,
+                            simultaneous_update,
+                        ))
+                    }
+                    UnitCommand::GrowDualNode(dual_node_ptr, length) => unit.grow_dual_node(&dual_node_ptr, length),
+                    UnitCommand::PrepareNodesShrink(nodes_circle) => {
+                        unit.prepare_nodes_shrink(&nodes_circle);
+                    }
+                    UnitCommand::StaticFuse => unit.static_fuse(),
+                }
+            }
+            RankCommand::Clear => {
+                for (&unit_index, unit_ptr) in self.units.iter() {
+                    lock_write!(unit, unit_ptr);
+                    unit.clear();
+                    unit.is_active = unit_index < self.partition_info.config.partitions.len(); // only partitioned serial modules are active at the beginning
+                    unit.partition_unit.write().enabled = false;
+                    unit.elevated_dual_nodes.clear();
+                }
+            }
+            RankCommand::ComputeMaximumUpdateLength => {
+                let mut group_max_update_length = GroupMaxUpdateLength::new();
+                for unit_ptr in self.units.values() {
+                    lock_write!(unit, unit_ptr);
+                    if unit.is_active {
+                        group_max_update_length.extend(unit.compute_maximum_update_length());
+                    }
+                }
+                return RankReply::GroupMaxUpdateLength(group_max_update_length);
+            }
+            RankCommand::Grow(length) => {
+                for unit_ptr in self.units.values() {
+                    lock_write!(unit, unit_ptr);
+                    if unit.is_active {
+                        unit.grow(length);
+                    }
+                }
+            }
+            RankCommand::LoadEdgeModifier(edge_modifier) => {
+                for unit_ptr in self.units.values() {
+                    lock_write!(unit, unit_ptr);
+                    unit.load_edge_modifier(&edge_modifier);
+                }
+            }
+            RankCommand::Shutdown => unreachable!("handled by the rank loop"),
+        }
+        RankReply::Done
+    }
+}
+
+pub struct DualModuleChannel<SerialModule: DualModuleImpl + Send + Sync + 'static> {
+    /// the units, only used by the coordinator to locate the active ancestor of a dual node and for visualization
+    pub parallel: DualModuleParallel<SerialModule>,
+    /// local configuration
+    pub config: DualModuleChannelConfig,
+    /// the rank hosting each unit
+    pub unit_ranks: Vec<usize>,
+    /// the channels to each rank
+    pub ranks: Vec<RankHandle>,
+    /// the number of messages sent to the ranks
+    pub message_count: usize,
+    /// an empty sync requests queue just to implement the trait
+    pub empty_sync_request: Vec<SyncRequest>,
+}
+
+impl<SerialModule: DualModuleImpl + Send + Sync + 'static> DualModuleChannel<SerialModule> {
+    /// recommended way to create a new instance, given a customized configuration
+    pub fn new_config(
+        initializer: &SolverInitializer,
+        partition_info: &PartitionInfo,
+        config: DualModuleChannelConfig,
+    ) -> Self {
+        let parallel_config: DualModuleParallelConfig = serde_json::from_value(json!({
+            "thread_pool_size": 1,
+            "edges_in_fusion_unit": config.edges_in_fusion_unit,
+        }))
+        .unwrap();
+        let parallel = DualModuleParallel::new_config(initializer, partition_info, parallel_config);
+        let partition_count = partition_info.config.partitions.len();
+        let rank_count = if config.rank_count == 0 {
+            partition_count
+        } else {
+            config.rank_count
+        };
+        assert!(rank_count > 0, "at least one rank is required");
+        // leaf units are distributed in a round-robin way, and each fusion unit lives with its left child
+        let mut unit_ranks = Vec::with_capacity(partition_info.units.len());
+        for (unit_index, unit) in partition_info.units.iter().enumerate() {
+            unit_ranks.push(match &unit.children {
+                Some((left_index, _)) => unit_ranks[*left_index],
+                None => unit_index % rank_count,
+            });
+        }
+        let mut ranks = vec![];
+        for rank_index in 0..rank_count {
+            let mut rank = Rank {
+                units: BTreeMap::new(),
+                partition_info: Arc::clone(&parallel.partition_info),
+            };
+            for (unit_index, unit_rank) in unit_ranks.iter().enumerate() {
+                if *unit_rank == rank_index {
+                    rank.units.insert(unit_index, parallel.units[unit_index].clone());
+                }
+            }
+            let (sender, command_receiver) = channel::<RankCommand>();
+            let (reply_sender, receiver) = channel::<RankReply>();
+            let thread = std::thread::Builder::new()
+                .name(format!("rank-{rank_index}"))
+                .spawn(move || {
+                    while let Ok(command) = command_receiver.recv() {
+                        if matches!(command, RankCommand::Shutdown) {
+                            break;
+                        }
+                        if reply_sender.send(rank.execute(command)).is_err() {
+                            break;
+                        }
+                    }
+                })
+                .expect("spawning rank failed");
+            ranks.push(RankHandle {
+                sender,
+                receiver,
+                thread: Some(thread),
+            });
+        }
+        Self {
+            parallel,
+            config,
+            unit_ranks,
+            ranks,
+            message_count: 0,
+            empty_sync_request: vec![],
+        }
+    }
+
+    /// send a command to a rank and wait for its reply
+    fn request(&mut self, rank_index: usize, command: RankCommand) -> RankReply {
+        self.message_count += 1;
+        let rank = &self.ranks[rank_index];
+        rank.sender.send(command).expect("rank disconnected");
+        rank.receiver.recv().expect("rank disconnected")
+    }
+
+    /// send a command to every rank and gather the replies in the order of the ranks
+    fn broadcast(&mut self, command: impl Fn() -> RankCommand) -> Vec<RankReply> {
+        self.message_count += self.ranks.len();
+        for rank in self.ranks.iter() {
+            rank.sender.send(command()).expect("rank disconnected");
+        }
+        self.ranks
+            .iter()
+            .map(|rank| rank.receiver.recv().expect("rank disconnected"))
+            .collect()
+    }
+
+    /// run an operation on the active unit that handles this dual node
+    fn request_unit(&mut self, dual_node_ptr: &DualNodePtr, unit_command: UnitCommand) -> RankReply {
+        let unit_ptr = self.parallel.find_active_ancestor(dual_node_ptr);
+        let unit_index = {
+            lock_read!(unit, unit_ptr);
+            unit.unit_index
+        };
+        self.request(self.unit_ranks[unit_index], RankCommand::Unit(unit_index, unit_command))
+    }
+
+    /// statically fuse them all following the fusion tree, see [`DualModuleParallel::static_fuse_all`]
+    pub fn static_fuse_all(&mut self) {
+        for unit_index in 0..self.parallel.units.len() {
+            let Some((left_index, right_index)) = self.parallel.partition_info.units[unit_index].children else {
+                continue;
+            };
+            let (left_is_active, right_is_active) = {
+                lock_read!(left_unit, self.parallel.units[left_index]);
+                lock_read!(right_unit, self.parallel.units[right_index]);
+                (left_unit.is_active, right_unit.is_active)
+            };
+            if !left_is_active && !right_is_active {
+                continue; // already fused, it's ok to just ignore
+            }
+            debug_assert!(
+                left_is_active && right_is_active,
+                "children must be active at the same time if fusing all together"
+            );
+            self.request(
+                self.unit_ranks[unit_index],
+                RankCommand::Unit(unit_index, UnitCommand::StaticFuse),
+            );
+        }
+    }
+}
+
+impl<SerialModule: DualModuleImpl + Send + Sync + 'static> Drop for DualModuleChannel<SerialModule> {
+    fn drop(&mut self) {
+        for rank in self.ranks.iter_mut() {
+            let _ = rank.sender.send(RankCommand::Shutdown);
+            if let Some(thread) = rank.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+impl<SerialModule: DualModuleImpl + Send + Sync + 'static> DualModuleImpl for DualModuleChannel<SerialModule> {
+    /// initialize the dual module, which is supposed to be reused for multiple decoding tasks with the same structure
+    fn new_empty(initializer: &SolverInitializer) -> Self {
+        Self::new_config(
+            initializer,
+            &PartitionConfig::new(initializer.vertex_num).info(),
+            DualModuleChannelConfig::default(),
+        )
+    }
+
+    fn clear(&mut self) {
+        self.broadcast(|| RankCommand::Clear);
+    }
+
+    fn add_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
+        self.request_unit(dual_node_ptr, UnitCommand::AddDualNode(dual_node_ptr.clone()));
+    }
+
+    fn remove_blossom(&mut self, dual_node_ptr: DualNodePtr) {
+        self.request_unit(&dual_node_ptr.clone(), UnitCommand::RemoveBlossom(dual_node_ptr));
+    }
+
+    fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
+        self.request_unit(dual_node_ptr, UnitCommand::SetGrowState(dual_node_ptr.clone(), grow_state));
+    }
+
+    fn compute_maximum_update_length_dual_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        is_grow: bool,
+        simultaneous_update: bool,
+    ) -> MaxUpdateLength {
+        match self.request_unit(
+            dual_node_ptr,
+            UnitCommand::ComputeMaximumUpdateLengthDualNode(dual_node_ptr.clone(), is_grow, simultaneous_update),
+        ) {
+            RankReply::MaxUpdateLength(max_update_length) => max_update_length,
+            _ => unreachable!("unexpected reply"),
+        }
+    }
+
+    fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        for reply in self.broadcast(|| RankCommand::ComputeMaximumUpdateLength) {
+            match reply {
+                RankReply::GroupMaxUpdateLength(local_group_max_update_length) => {
+                    group_max_update_length.extend(local_group_max_update_length)
+                }
+                _ => unreachable!("unexpected reply"),
+            }
+        }
+        group_max_update_length
+    }
+
+    fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) {
+        self.request_unit(dual_node_ptr, UnitCommand::GrowDualNode(dual_node_ptr.clone(), length));
+    }
+
+    fn grow(&mut self, length: Weight) {
+        self.broadcast(|| RankCommand::Grow(length));
+    }
+
+    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) {
+        self.broadcast(|| RankCommand::LoadEdgeModifier(edge_modifier.to_vec()));
+    }
+
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "rank_count": self.ranks.len(),
+            "message_count": self.message_count,
+        })
+    }
+
+    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> &mut Vec<SyncRequest> {
+        self.request_unit(&nodes_circle[0], UnitCommand::PrepareNodesShrink(nodes_circle.to_vec()));
+        &mut self.empty_sync_request
+    }
+}
+
+impl<SerialModule: DualModuleImpl + FusionVisualizer + Send + Sync + 'static> FusionVisualizer
+    for DualModuleChannel<SerialModule>
+{
+    fn snapshot(&self, abbrev: bool) -> serde_json::Value {
+        self.parallel.snapshot(abbrev)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::super::dual_module_serial::*;
+    use super::super::example_codes::*;
+    use super::super::primal_module::*;
+    use super::super::primal_module_serial::*;
+    use super::*;
+
+    #[test]
+    fn dual_module_channel_basic_1() {
+        // cargo test dual_module_channel_basic_1 -- --nocapture
+        let half_weight = 500;
+        let defect_vertices = vec![39, 52, 63, 90, 100];
+        for rank_count in [0, 1, 2] {
+            let mut code = CodeCapacityPlanarCode::new(11, 0.1, half_weight);
+            let initializer = code.get_initializer();
+            let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+            partition_config.partitions = vec![
+                VertexRange::new(0, 36),    // unit 0
+                VertexRange::new(48, 72),   // unit 1
+                VertexRange::new(84, 108),  // unit 2
+                VertexRange::new(120, 132), // unit 3
+            ];
+            partition_config.fusions = vec![
+                (0, 1), // unit 4, by fusing 0 and 1
+                (2, 3), // unit 5
+                (4, 5), // unit 6
+            ];
+            let partition_info = partition_config.info();
+            let config: DualModuleChannelConfig = serde_json::from_value(json!({ "rank_count": rank_count })).unwrap();
+            let mut dual_module = DualModuleChannel::<DualModuleSerial>::new_config(&initializer, &partition_info, config);
+            assert_eq!(dual_module.ranks.len(), if rank_count == 0 { 4 } else { rank_count });
+            for (unit_index, unit) in partition_info.units.iter().enumerate() {
+                if let Some((left_index, _)) = unit.children {
+                    assert_eq!(dual_module.unit_ranks[unit_index], dual_module.unit_ranks[left_index]);
+                }
+            }
+            dual_module.static_fuse_all();
+            let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+            code.set_defect_vertices(&defect_vertices);
+            let interface_ptr = DualModuleInterfacePtr::new_empty();
+            primal_module.solve(&interface_ptr, &code.get_syndrome(), &mut dual_module);
+            assert_eq!(interface_ptr.sum_dual_variables(), 9 * half_weight * 2);
+            assert!(dual_module.message_count > 0);
+            // the ranks can be reused after clearing
+            dual_module.clear();
+            primal_module.clear();
+            dual_module.static_fuse_all();
+            let interface_ptr = DualModuleInterfacePtr::new_empty();
+            primal_module.solve(&interface_ptr, &code.get_syndrome(), &mut dual_module);
+            assert_eq!(interface_ptr.sum_dual_variables(), 9 * half_weight * 2);
+        }
+    }
+}
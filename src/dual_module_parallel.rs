@@ -24,7 +24,9 @@ use crate::serde_json;
 use crate::weak_table::PtrWeakHashSet;
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeSet, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
+use std::time::Instant;
 
 pub struct DualModuleParallel<SerialModule: DualModuleImpl + Send + Sync> {
     /// the basic wrapped serial modules at the beginning, afterwards the fused units are appended after them
@@ -35,8 +37,33 @@ pub struct DualModuleParallel<SerialModule: DualModuleImpl + Send + Sync> {
     pub partition_info: Arc<PartitionInfo>,
     /// thread pool used to execute async functions in parallel
     pub thread_pool: Arc<rayon::ThreadPool>,
+    /// one thread pool per used NUMA node, whose threads are pinned to the cores of that node; empty if the threads are
+    /// not pinned, see [`DualModuleParallelConfig::thread_affinity`]
+    pub numa_pools: Vec<Arc<rayon::ThreadPool>>,
+    /// the index in [`Self::numa_pools`] of the pool that runs each unit; empty if the threads are not pinned
+    pub unit_numa_nodes: Vec<usize>,
     /// an empty sync requests queue just to implement the trait
     pub empty_sync_request: Vec<SyncRequest>,
+    /// shared by all the units
+    pub lock_contention: Arc<UnitLockContention>,
+    /// per-call latency histograms of the top-level operations; the units keep their own, see [`PhaseProfiler`]
+    pub phase_profiler: PhaseProfiler,
+    /// the number of edges of the whole decoding graph
+    pub edge_num: usize,
+    /// the boundary summaries of the units evicted by [`DualModuleParallel::evict_unit`], in the order of eviction
+    pub evicted_units: Vec<EvictedUnit>,
+}
+
+/// what is kept of a unit after [`DualModuleParallel::evict_unit`] frees its serial module
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvictedUnit {
+    /// the index of the evicted unit
+    pub unit_index: usize,
+    /// the vertices owned by the evicted unit, which can no longer hold a defect
+    pub owning_range: VertexRange,
+    /// the vertices of the ancestors that the evicted unit used to mirror, sorted; they stay in the decoding graph but no
+    /// longer connect to the evicted side
+    pub boundary_vertices: Vec<VertexIndex>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +82,48 @@ pub struct DualModuleParallelConfig {
     /// enable parallel execution of a fused dual module
     #[serde(default = "dual_module_parallel_default_configs::enable_parallel_execution")]
     pub enable_parallel_execution: bool,
+    /// run each unit on a thread pool pinned to the cores of one NUMA node, both when building it and when operating on it,
+    /// so that its vertices and edges are allocated on and accessed from that node (Linux allocates pages on the node of
+    /// the thread that first touches them); `thread_pool_size` threads are split evenly among the nodes, if not 0
+    #[serde(default = "dual_module_parallel_default_configs::thread_affinity")]
+    pub thread_affinity: ThreadAffinity,
+    /// report the partitioned initializers, the construction of every unit and the steps of a visualized solve to stderr,
+    /// or as `tracing` debug events with the `tracing_spans` feature
+    #[serde(default = "dual_module_parallel_default_configs::verbose")]
+    pub verbose: bool,
+}
+
+/// how the units of [`DualModuleParallel`] are placed on the NUMA nodes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadAffinity {
+    /// let the OS schedule the threads
+    None,
+    /// place consecutive units on the same NUMA node, so that neighboring units share a memory controller
+    PinPerUnit,
+    /// place consecutive units on different NUMA nodes in a round-robin way, to spread the units over all memory controllers
+    NumaInterleave,
+}
+
+impl ThreadAffinity {
+    /// the NUMA node of each unit, given the number of NUMA nodes; a fusion unit stays on the node of its left child, which
+    /// holds most of the vertices it operates on. `None` if the threads are not pinned
+    pub fn unit_numa_nodes(&self, partition_info: &PartitionInfo, node_count: usize) -> Option<Vec<usize>> {
+        let leaf_count = partition_info.config.partitions.len();
+        let leaf_node = |unit_index: usize| match self {
+            Self::None => unreachable!(),
+            Self::PinPerUnit => unit_index * node_count / leaf_count,
+            Self::NumaInterleave => unit_index % node_count,
+        };
+        if *self == Self::None {
+            return None;
+        }
+        let mut unit_numa_nodes: Vec<usize> = (0..leaf_count).map(leaf_node).collect();
+        for &(left_index, _) in partition_info.config.fusions.iter() {
+            unit_numa_nodes.push(unit_numa_nodes[left_index]);
+        }
+        Some(unit_numa_nodes)
+    }
 }
 
 impl Default for DualModuleParallelConfig {
@@ -63,6 +132,63 @@ impl Default for DualModuleParallelConfig {
     }
 }
 
+/// a configuration suggested by [`DualModuleParallelConfig::suggest`]
+#[derive(Debug, Clone, Serialize)]
+pub struct DualModuleParallelConfigSuggestion {
+    /// the suggested configuration
+    pub config: DualModuleParallelConfig,
+    /// the suggested number of leaf units in the partition; a binary fusion tree of them has `2 * leaf_unit_count - 1` units
+    pub leaf_unit_count: usize,
+    /// the estimated memory footprint of a serial dual module holding the whole decoding graph, in bytes
+    pub estimated_footprint: usize,
+    /// the machine this suggestion is made for
+    pub topology: CpuTopology,
+}
+
+impl DualModuleParallelConfig {
+    /// a leaf unit smaller than this spends more time fusing than solving
+    pub const SUGGEST_MIN_UNIT_VERTICES: usize = 64;
+    /// the assumed per-core cache size if it cannot be detected
+    pub const SUGGEST_DEFAULT_CACHE_SIZE: usize = 1 << 20;
+
+    /// suggest a thread pool size and a number of units for the decoding graph on the current machine
+    pub fn suggest(initializer: &SolverInitializer) -> DualModuleParallelConfigSuggestion {
+        Self::suggest_for(initializer, &CpuTopology::detect())
+    }
+
+    /// suggest a configuration given the CPU topology: each leaf unit should fit in the per-core cache, and there is no
+    /// point in using more threads than leaf units. If the whole graph already fits in the cache, a single thread is used
+    /// because the fusion overhead would outweigh the parallelism
+    #[allow(clippy::unnecessary_cast)]
+    pub fn suggest_for(initializer: &SolverInitializer, topology: &CpuTopology) -> DualModuleParallelConfigSuggestion {
+        let pointer_overhead = 2 * std::mem::size_of::<usize>() + std::mem::size_of::<usize>(); // Arc counters and lock
+        let vertex_bytes = std::mem::size_of::<Vertex>() + pointer_overhead;
+        let edge_bytes = std::mem::size_of::<Edge>() + pointer_overhead + 2 * std::mem::size_of::<EdgeWeak>();
+        let vertex_num = initializer.vertex_num as usize;
+        let estimated_footprint = vertex_num * vertex_bytes + initializer.weighted_edges.len() * edge_bytes;
+        let cache_size = topology
+            .l2_cache_size
+            .or(topology.l1d_cache_size)
+            .unwrap_or(Self::SUGGEST_DEFAULT_CACHE_SIZE);
+        let cache_leaves = estimated_footprint.div_ceil(cache_size);
+        let max_leaves = std::cmp::max(1, vertex_num / Self::SUGGEST_MIN_UNIT_VERTICES);
+        let leaf_unit_count = if cache_leaves <= 1 {
+            1
+        } else {
+            std::cmp::min(std::cmp::max(cache_leaves, topology.logical_cores), max_leaves)
+        };
+        DualModuleParallelConfigSuggestion {
+            config: Self {
+                thread_pool_size: std::cmp::max(1, std::cmp::min(topology.logical_cores, leaf_unit_count)),
+                ..Self::default()
+            },
+            leaf_unit_count,
+            estimated_footprint,
+            topology: topology.clone(),
+        }
+    }
+}
+
 pub mod dual_module_parallel_default_configs {
     pub fn thread_pool_size() -> usize {
         0
@@ -74,6 +200,12 @@ pub mod dual_module_parallel_default_configs {
     pub fn enable_parallel_execution() -> bool {
         false
     } // by default disabled: parallel execution may cause too much context switch, yet not much speed benefit
+    pub fn thread_affinity() -> super::ThreadAffinity {
+        super::ThreadAffinity::None
+    } // by default let the OS schedule the threads
+    pub fn verbose() -> bool {
+        false
+    }
 }
 
 pub struct DualModuleParallelUnit<SerialModule: DualModuleImpl + Send + Sync> {
@@ -106,13 +238,74 @@ pub struct DualModuleParallelUnit<SerialModule: DualModuleImpl + Send + Sync> {
     pub empty_sync_request: Vec<SyncRequest>,
     /// run things in thread pool
     pub enable_parallel_execution: bool,
-    /// whether any descendant unit has active dual node
-    pub has_active_node: bool,
+    /// whether any descendant unit has active dual node; atomic because querying the maximum update length may clear it
+    /// under the shared lock
+    pub has_active_node: AtomicBool,
+    /// shared by all the units of the parallel dual module
+    pub lock_contention: Arc<UnitLockContention>,
+    /// per-call latency histograms of the operations dispatched to this unit, including fusing its children
+    pub phase_profiler: PhaseProfiler,
+    /// whether the serial module is freed by [`DualModuleParallel::evict_unit`]
+    pub evicted: bool,
 }
 
 pub type DualModuleParallelUnitPtr<SerialModule> = ArcManualSafeLock<DualModuleParallelUnit<SerialModule>>;
 pub type DualModuleParallelUnitWeak<SerialModule> = WeakManualSafeLock<DualModuleParallelUnit<SerialModule>>;
 
+/// counts the exclusive acquisitions of the units and how many of them find the unit already locked; only the operations
+/// that modify a unit take its exclusive lock, while the queries and the checks for whether there's anything to do at all
+/// take the shared lock. The counters are atomic so that the units can update them concurrently
+#[derive(Debug, Default)]
+pub struct UnitLockContention {
+    /// the number of exclusive locks taken
+    pub acquisitions: AtomicUsize,
+    /// the number of exclusive locks that had to wait for another holder
+    pub contended: AtomicUsize,
+    /// the number of exclusive locks avoided because the unit has no active dual node
+    pub skipped: AtomicUsize,
+}
+
+impl UnitLockContention {
+    /// run `f` with the exclusive lock of the unit
+    #[inline]
+    pub fn write_unit<SerialModule: DualModuleImpl + Send + Sync, R>(
+        &self,
+        unit_ptr: &DualModuleParallelUnitPtr<SerialModule>,
+        f: impl FnOnce(&mut DualModuleParallelUnit<SerialModule>) -> R,
+    ) -> R {
+        self.acquisitions.fetch_add(1, Ordering::Relaxed);
+        if let Some(mut unit) = unit_ptr.try_write() {
+            return f(&mut unit);
+        }
+        self.contended.fetch_add(1, Ordering::Relaxed);
+        lock_write!(unit, unit_ptr);
+        f(&mut *unit)
+    }
+
+    /// run `f` with the exclusive lock of the unit only if it has any active dual node in its descendants, which is
+    /// checked under the shared lock first
+    #[inline]
+    pub fn write_active_unit<SerialModule: DualModuleImpl + Send + Sync, R>(
+        &self,
+        unit_ptr: &DualModuleParallelUnitPtr<SerialModule>,
+        f: impl FnOnce(&mut DualModuleParallelUnit<SerialModule>) -> R,
+    ) -> Option<R> {
+        if !unit_ptr.read_recursive().has_active_node.load(Ordering::Relaxed) {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+        Some(self.write_unit(unit_ptr, f))
+    }
+
+    pub fn report(&self) -> serde_json::Value {
+        json!({
+            "acquisitions": self.acquisitions.load(Ordering::Relaxed),
+            "contended": self.contended.load(Ordering::Relaxed),
+            "skipped": self.skipped.load(Ordering::Relaxed),
+        })
+    }
+}
+
 impl<SerialModule: DualModuleImpl + Send + Sync> std::fmt::Debug for DualModuleParallelUnitPtr<SerialModule> {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let unit = self.read_recursive();
@@ -126,21 +319,25 @@ impl<SerialModule: DualModuleImpl + Send + Sync> std::fmt::Debug for DualModuleP
     }
 }
 
-impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule> {
-    /// recommended way to create a new instance, given a customized configuration
+/// the partitioned initializers of all the units, which take noticeable time to compute for a large decoding graph; it
+/// can be saved to a file once and loaded by every worker process, see [`DualModuleParallel::new_from_cached`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DualModuleParallelCache {
+    pub partition_info: PartitionInfo,
+    /// the [`DualModuleParallelConfig::edges_in_fusion_unit`] that the partitioned initializers are built with
+    pub edges_in_fusion_unit: bool,
+    /// the initializer of each unit, indexed by the unit index
+    pub partitioned_initializers: Vec<PartitionedSolverInitializer>,
+}
+
+impl DualModuleParallelCache {
     #[allow(clippy::unnecessary_cast)]
-    pub fn new_config(
-        initializer: &SolverInitializer,
-        partition_info: &PartitionInfo,
-        config: DualModuleParallelConfig,
-    ) -> Self {
-        let partition_info = Arc::new(partition_info.clone());
-        let mut thread_pool_builder = rayon::ThreadPoolBuilder::new();
-        if config.thread_pool_size != 0 {
-            thread_pool_builder = thread_pool_builder.num_threads(config.thread_pool_size);
-        }
-        let thread_pool = thread_pool_builder.build().expect("creating thread pool failed");
-        let mut units = vec![];
+    pub fn new(initializer: &SolverInitializer, partition_info: &PartitionInfo, edges_in_fusion_unit: bool) -> Self {
+        assert!(
+            initializer.virtual_vertices_weighted.is_empty(),
+            "weighted virtual vertices are not supported by the partitioned dual module"
+        );
         let unit_count = partition_info.units.len();
         let complete_graph = CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges); // build the graph to construct the NN data structure
         let mut contained_vertices_vec: Vec<BTreeSet<VertexIndex>> = vec![]; // all vertices maintained by each unit
@@ -159,6 +356,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
         let mut partitioned_initializers: Vec<PartitionedSolverInitializer> = (0..unit_count)
             .map(|unit_index| {
                 let mut interfaces = vec![];
+                let mut detached_units = vec![];
                 let mut current_index = unit_index;
                 let owning_range = &partition_info.units[unit_index].owning_range;
                 let mut contained_vertices = BTreeSet::new();
@@ -167,7 +365,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
                 }
                 while let Some(parent_index) = &partition_info.units[current_index].parent {
                     let mut mirror_vertices = vec![];
-                    if config.edges_in_fusion_unit {
+                    if edges_in_fusion_unit {
                         for vertex_index in partition_info.units[*parent_index].owning_range.iter() {
                             let mut is_incident = false;
                             for (peer_index, _) in complete_graph.vertices[vertex_index as usize].edges.iter() {
@@ -207,6 +405,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
                     if !mirror_vertices.is_empty() {
                         // only add non-empty mirrored parents is enough
                         interfaces.push((partition_units[*parent_index].downgrade(), mirror_vertices));
+                        detached_units.push(partition_units[*parent_index].clone());
                     }
                     current_index = *parent_index;
                 }
@@ -219,6 +418,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
                     owning_interface: if unit_index < partition_info.config.partitions.len() {
                         None
                     } else {
+                        detached_units.push(partition_units[unit_index].clone());
                         Some(partition_units[unit_index].downgrade())
                     },
                     weighted_edges: vec![], // to be filled later
@@ -227,6 +427,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
                         .iter()
                         .filter(|vertex_index| is_vertex_virtual[*vertex_index as usize])
                         .collect(),
+                    detached_units,
                 } // note that all fields can be modified later
             })
             .collect();
@@ -262,7 +463,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
             );
             let ancestor_unit_index = if is_i_ancestor { i_unit_index } else { j_unit_index };
             let descendant_unit_index = if is_i_ancestor { j_unit_index } else { i_unit_index };
-            if config.edges_in_fusion_unit {
+            if edges_in_fusion_unit {
                 // the edge should be added to the descendant, and it's guaranteed that the descendant unit contains (although not necessarily owned) the vertex
                 partitioned_initializers[descendant_unit_index]
                     .weighted_edges
@@ -290,7 +491,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
                     }
                     let dfs_info = DfsInfo {
                         partition_config: &partition_info.config,
-                        partition_info: &partition_info,
+                        partition_info,
                         i,
                         j,
                         weight,
@@ -332,20 +533,137 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
                 }
             }
         }
-        // println!("partitioned_initializers: {:?}", partitioned_initializers);
+        Self {
+            partition_info: partition_info.clone(),
+            edges_in_fusion_unit,
+            partitioned_initializers,
+        }
+    }
+}
+
+impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule> {
+    /// the configuration in effect, where the thread pool size is resolved to the actual number of threads
+    pub fn effective_config(&self) -> serde_json::Value {
+        let mut config = serde_json::to_value(&self.config).unwrap();
+        config["thread_pool_size"] = json!(self.thread_pool.current_num_threads());
+        config
+    }
+
+    /// recommended way to create a new instance, given a customized configuration
+    pub fn new_config(
+        initializer: &SolverInitializer,
+        partition_info: &PartitionInfo,
+        config: DualModuleParallelConfig,
+    ) -> Self {
+        let begin = Instant::now();
+        let cache = DualModuleParallelCache::new(initializer, partition_info, config.edges_in_fusion_unit);
+        if config.verbose {
+            diagnostic!(
+                "partitioned {} units in {:.3e}s",
+                cache.partitioned_initializers.len(),
+                begin.elapsed().as_secs_f64()
+            );
+            for partitioned_initializer in cache.partitioned_initializers.iter() {
+                diagnostic!(
+                    "unit {}: owning {:?}, {} edges, {} virtual vertices, mirroring {:?}",
+                    partitioned_initializer.unit_index,
+                    partitioned_initializer.owning_range,
+                    partitioned_initializer.weighted_edges.len(),
+                    partitioned_initializer.virtual_vertices.len(),
+                    partitioned_initializer.interfaces,
+                );
+            }
+        }
+        Self::new_from_cached(cache, config)
+    }
+
+    /// create an instance from the partitioned initializers computed in advance, e.g. loaded from a file written by another
+    /// process, which skips the expensive partitioning of the decoding graph
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new_from_cached(cache: DualModuleParallelCache, config: DualModuleParallelConfig) -> Self {
+        assert_eq!(
+            cache.edges_in_fusion_unit, config.edges_in_fusion_unit,
+            "the cache is built with a different `edges_in_fusion_unit`"
+        );
+        let partition_info = Arc::new(cache.partition_info);
+        let mut thread_pool_builder = rayon::ThreadPoolBuilder::new();
+        if config.thread_pool_size != 0 {
+            thread_pool_builder = thread_pool_builder.num_threads(config.thread_pool_size);
+        }
+        let thread_pool = thread_pool_builder.build().expect("creating thread pool failed");
+        let mut numa_nodes = CpuTopology::detect_numa_nodes();
+        numa_nodes.truncate(partition_info.config.partitions.len());
+        let node_count = numa_nodes.len();
+        let unit_numa_nodes = config
+            .thread_affinity
+            .unit_numa_nodes(&partition_info, node_count)
+            .unwrap_or_default();
+        let numa_pools: Vec<Arc<rayon::ThreadPool>> = if unit_numa_nodes.is_empty() {
+            vec![]
+        } else {
+            // the shared thread pool only dispatches the work of each unit to the pool of its node
+            numa_nodes
+                .into_iter()
+                .map(|cores| {
+                    let thread_count = match config.thread_pool_size {
+                        0 => cores.len(),
+                        thread_pool_size => thread_pool_size.div_ceil(node_count).clamp(1, cores.len()),
+                    };
+                    let pool = rayon::ThreadPoolBuilder::new()
+                        .num_threads(thread_count)
+                        .start_handler(move |thread_index| {
+                            core_affinity::set_for_current(core_affinity::CoreId {
+                                id: cores[thread_index % cores.len()],
+                            });
+                        })
+                        .build()
+                        .expect("creating NUMA thread pool failed");
+                    Arc::new(pool)
+                })
+                .collect()
+        };
+        let mut units = vec![];
+        let lock_contention = Arc::new(UnitLockContention::default());
+        let unit_count = partition_info.units.len();
+        assert_eq!(
+            cache.partitioned_initializers.len(),
+            unit_count,
+            "the cache doesn't match the partition"
+        );
+        let partition_units: Vec<PartitionUnitPtr> = (0..unit_count)
+            .map(|unit_index| {
+                PartitionUnitPtr::new_value(PartitionUnit {
+                    unit_index,
+                    enabled: unit_index < partition_info.config.partitions.len(),
+                })
+            })
+            .collect();
+        let mut partitioned_initializers = cache.partitioned_initializers;
+        let edge_num = partitioned_initializers.first().map_or(0, |initializer| initializer.edge_num);
+        for partitioned_initializer in partitioned_initializers.iter_mut() {
+            partitioned_initializer.bind_partition_units(&partition_units);
+        }
+        let build_unit = |unit_index: usize| {
+            if config.verbose {
+                diagnostic!("building unit {}", unit_index);
+            }
+            let dual_module = SerialModule::new_partitioned(&partitioned_initializers[unit_index]);
+            DualModuleParallelUnitPtr::new_wrapper(
+                dual_module,
+                unit_index,
+                Arc::clone(&partition_info),
+                partition_units[unit_index].clone(),
+                config.enable_parallel_execution,
+                lock_contention.clone(),
+            )
+        };
         thread_pool.scope(|_| {
             (0..unit_count)
                 .into_par_iter()
-                .map(|unit_index| {
-                    // println!("unit_index: {unit_index}");
-                    let dual_module = SerialModule::new_partitioned(&partitioned_initializers[unit_index]);
-                    DualModuleParallelUnitPtr::new_wrapper(
-                        dual_module,
-                        unit_index,
-                        Arc::clone(&partition_info),
-                        partition_units[unit_index].clone(),
-                        config.enable_parallel_execution,
-                    )
+                .map(|unit_index| match unit_numa_nodes.get(unit_index) {
+                    // the memory of the unit is first touched on its NUMA node
+                    Some(&node_index) => numa_pools[node_index].install(|| build_unit(unit_index)),
+                    None => build_unit(unit_index),
                 })
                 .collect_into_vec(&mut units);
         });
@@ -396,7 +714,75 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
             config,
             partition_info,
             thread_pool: Arc::new(thread_pool),
+            numa_pools,
+            unit_numa_nodes,
             empty_sync_request: vec![],
+            lock_contention,
+            phase_profiler: PhaseProfiler::new(),
+            edge_num,
+            evicted_units: vec![],
+        }
+    }
+
+    /// free the serial module of a leaf unit whose time slice is committed, so that old time slices don't hold memory; the
+    /// vertices and edges of the unit are removed from the decoding graph while the vertices it mirrored from its ancestors
+    /// stay, as recorded in the returned boundary summary. It must be called between two shots, i.e. before loading a
+    /// syndrome or after [`DualModuleImpl::clear`]. The eviction is permanent: the unit stays cut from the decoding graph
+    /// for all the later shots of this module, any later defect in it is rejected, and there's no way to load it back.
+    /// It's not used by [`crate::mwpm_solver::SolverStream`], which builds a new solver for each window instead
+    pub fn evict_unit(&mut self, unit_index: usize) -> EvictedUnit {
+        let partition_unit_info = &self.partition_info.units[unit_index];
+        assert!(
+            partition_unit_info.children.is_none(),
+            "only a leaf unit can be evicted, but unit {} is a fusion unit",
+            unit_index
+        );
+        lock_write!(unit, self.units[unit_index]);
+        assert!(!unit.evicted, "unit {} is already evicted", unit_index);
+        assert!(
+            unit.is_active && unit.elevated_dual_nodes.is_empty(),
+            "unit {} can only be evicted between two shots",
+            unit_index
+        );
+        let owning_range = partition_unit_info.owning_range;
+        unit.serial_module = SerialModule::new_partitioned(&PartitionedSolverInitializer {
+            unit_index,
+            vertex_num: self.partition_info.config.vertex_num,
+            edge_num: self.edge_num,
+            owning_range: VertexRange::new(owning_range.start(), owning_range.start()),
+            owning_interface: None,
+            interfaces: vec![],
+            weighted_edges: vec![],
+            virtual_vertices: vec![],
+            detached_units: vec![],
+        });
+        unit.evicted = true;
+        let mut boundary_vertices: Vec<VertexIndex> = unit.extra_descendant_mirrored_vertices.iter().cloned().collect();
+        boundary_vertices.sort_unstable();
+        let evicted_unit = EvictedUnit {
+            unit_index,
+            owning_range,
+            boundary_vertices,
+        };
+        if self.config.verbose {
+            diagnostic!("evicted unit {}: {:?}", unit_index, evicted_unit);
+        }
+        self.evicted_units.push(evicted_unit.clone());
+        evicted_unit
+    }
+
+    /// load the defect vertices of a syndrome with the defect nodes binned by their owning units and added to the units in
+    /// parallel, cutting the serial prologue of a large syndrome; this is what [`DualModuleInterfacePtr::load`] does with
+    /// a parallel dual module
+    pub fn load_syndrome_partitioned(&mut self, interface_ptr: &DualModuleInterfacePtr, syndrome_pattern: &SyndromePattern) {
+        interface_ptr.load(syndrome_pattern, self);
+    }
+
+    /// run the work of a unit on the thread pool of its NUMA node, or on the current thread if the threads are not pinned
+    pub fn install_on_unit<R: Send>(&self, unit_index: usize, op: impl FnOnce() -> R + Send) -> R {
+        match self.unit_numa_nodes.get(unit_index) {
+            Some(&node_index) => self.numa_pools[node_index].install(op),
+            None => op(),
         }
     }
 
@@ -431,6 +817,22 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule
         Some(owning_unit_ptr)
     }
 
+    /// find the active unit to handle an obstacle, given its origin recorded by [`GroupMaxUpdateLength::get_origin`]; if
+    /// the unit that computed it has been fused since then, its active ancestor is responsible instead
+    pub fn find_origin_unit(&self, origin: &MaxUpdateLengthOrigin) -> DualModuleParallelUnitPtr<SerialModule> {
+        let mut unit_ptr = self.units[origin.interface_index].clone();
+        loop {
+            let unit = unit_ptr.read_recursive();
+            if unit.is_active {
+                break;
+            }
+            let parent_ptr = unit.parent.as_ref().expect("no active ancestor").upgrade_force();
+            drop(unit);
+            unit_ptr = parent_ptr;
+        }
+        unit_ptr
+    }
+
     /// statically fuse them all, may be called at any state (meaning each unit may not necessarily be solved locally)
     pub fn static_fuse_all(&mut self) {
         for unit_ptr in self.units.iter() {
@@ -471,11 +873,14 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
     fn clear(&mut self) {
         self.thread_pool.scope(|_| {
             self.units.par_iter().enumerate().for_each(|(unit_idx, unit_ptr)| {
-                lock_write!(unit, unit_ptr);
-                unit.clear();
-                unit.is_active = unit_idx < self.partition_info.config.partitions.len(); // only partitioned serial modules are active at the beginning
-                unit.partition_unit.write().enabled = false;
-                unit.elevated_dual_nodes.clear();
+                self.install_on_unit(unit_idx, || {
+                    self.lock_contention.write_unit(unit_ptr, |unit| {
+                        unit.clear();
+                        unit.is_active = unit_idx < self.partition_info.config.partitions.len(); // only partitioned serial modules are active at the beginning
+                        unit.partition_unit.write().enabled = false;
+                        unit.elevated_dual_nodes.clear();
+                    });
+                });
             });
         })
     }
@@ -485,24 +890,70 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
     fn add_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
         let unit_ptr = self.find_active_ancestor(dual_node_ptr);
         self.thread_pool.scope(|_| {
-            lock_write!(unit, unit_ptr);
-            unit.add_dual_node(dual_node_ptr);
+            self.lock_contention
+                .write_unit(&unit_ptr, |unit| unit.add_dual_node(dual_node_ptr));
+        })
+    }
+
+    /// bin the defect nodes by their owning units and add them to the units in parallel, instead of descending from the
+    /// active ancestor for every defect node in turn
+    #[allow(clippy::unnecessary_cast)]
+    fn add_defect_nodes(&mut self, dual_node_ptrs: &[DualNodePtr]) {
+        let mut unit_defect_nodes: Vec<Vec<&DualNodePtr>> = vec![vec![]; self.units.len()];
+        for dual_node_ptr in dual_node_ptrs.iter() {
+            let vertex_index = dual_node_ptr.get_representative_vertex();
+            unit_defect_nodes[self.partition_info.vertex_to_owning_unit[vertex_index as usize]].push(dual_node_ptr);
+        }
+        // the units from the owning unit up to its active ancestor have an active node now
+        for (unit_index, defect_nodes) in unit_defect_nodes.iter().enumerate() {
+            if defect_nodes.is_empty() {
+                continue;
+            }
+            let mut unit_ptr = self.units[unit_index].clone();
+            loop {
+                let unit = unit_ptr.read_recursive();
+                unit.has_active_node.store(true, Ordering::Relaxed);
+                if unit.is_active {
+                    break;
+                }
+                let parent_ptr = unit.parent.as_ref().expect("no active ancestor").upgrade_force();
+                drop(unit);
+                unit_ptr = parent_ptr;
+            }
+        }
+        let units = &self.units;
+        let lock_contention = &self.lock_contention;
+        self.thread_pool.install(|| {
+            unit_defect_nodes
+                .par_iter()
+                .enumerate()
+                .filter(|(_, defect_nodes)| !defect_nodes.is_empty())
+                .for_each(|(unit_index, defect_nodes)| {
+                    self.install_on_unit(unit_index, || {
+                        lock_contention.write_unit(&units[unit_index], |unit| {
+                            for dual_node_ptr in defect_nodes.iter() {
+                                let vertex_index = dual_node_ptr.get_representative_vertex();
+                                unit.iterative_add_defect_node(dual_node_ptr, vertex_index);
+                            }
+                        });
+                    });
+                });
         })
     }
 
     fn remove_blossom(&mut self, dual_node_ptr: DualNodePtr) {
         let unit_ptr = self.find_active_ancestor(&dual_node_ptr);
         self.thread_pool.scope(|_| {
-            lock_write!(unit, unit_ptr);
-            unit.remove_blossom(dual_node_ptr);
+            self.lock_contention
+                .write_unit(&unit_ptr, |unit| unit.remove_blossom(dual_node_ptr));
         })
     }
 
     fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
         let unit_ptr = self.find_active_ancestor(dual_node_ptr);
         self.thread_pool.scope(|_| {
-            lock_write!(unit, unit_ptr);
-            unit.set_grow_state(dual_node_ptr, grow_state);
+            self.lock_contention
+                .write_unit(&unit_ptr, |unit| unit.set_grow_state(dual_node_ptr, grow_state));
         })
     }
 
@@ -514,22 +965,35 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
     ) -> MaxUpdateLength {
         let unit_ptr = self.find_active_ancestor(dual_node_ptr);
         self.thread_pool.scope(|_| {
-            lock_write!(unit, unit_ptr);
-            unit.compute_maximum_update_length_dual_node(dual_node_ptr, is_grow, simultaneous_update)
+            self.lock_contention.write_unit(&unit_ptr, |unit| {
+                unit.compute_maximum_update_length_dual_node(dual_node_ptr, is_grow, simultaneous_update)
+            })
         })
     }
 
     fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
-        self.thread_pool.scope(|_| {
+        let profiler_start = self.phase_profiler.start();
+        let group_max_update_length = self.thread_pool.scope(|_| {
             let results: Vec<_> = self
                 .units
                 .par_iter()
-                .filter_map(|unit_ptr| {
-                    lock_write!(unit, unit_ptr);
-                    if !unit.is_active {
+                .enumerate()
+                .filter_map(|(unit_index, unit_ptr)| {
+                    if !unit_ptr.read_recursive().is_active {
                         return None;
                     }
-                    Some(unit.compute_maximum_update_length())
+                    Some(self.install_on_unit(unit_index, || {
+                        // only preparing the dual nodes modifies the unit, after which the query takes the shared locks
+                        self.lock_contention.write_unit(unit_ptr, |unit| {
+                            unit.prepare_all();
+                            unit.iterative_prepare_serial_modules();
+                        });
+                        let prepared = unit_ptr.read_recursive().query_prepared_maximum_update_length();
+                        prepared.unwrap_or_else(|| {
+                            self.lock_contention
+                                .write_unit(unit_ptr, |unit| unit.compute_maximum_update_length())
+                        })
+                    }))
                 })
                 .collect();
             let mut group_max_update_length = GroupMaxUpdateLength::new();
@@ -537,46 +1001,66 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
                 group_max_update_length.extend(local_group_max_update_length);
             }
             group_max_update_length
-        })
+        });
+        self.phase_profiler
+            .record(ProfilerPhase::ComputeMaximumUpdateLength, profiler_start);
+        group_max_update_length
     }
 
     fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) {
         let unit_ptr = self.find_active_ancestor(dual_node_ptr);
         self.thread_pool.scope(|_| {
-            lock_write!(unit, unit_ptr);
-            unit.grow_dual_node(dual_node_ptr, length);
+            self.lock_contention
+                .write_unit(&unit_ptr, |unit| unit.grow_dual_node(dual_node_ptr, length));
         })
     }
 
     fn grow(&mut self, length: Weight) {
+        let profiler_start = self.phase_profiler.start();
         self.thread_pool.scope(|_| {
-            self.units.par_iter().for_each(|unit_ptr| {
-                lock_write!(unit, unit_ptr);
-                if !unit.is_active {
+            self.units.par_iter().enumerate().for_each(|(unit_index, unit_ptr)| {
+                if !unit_ptr.read_recursive().is_active {
                     return;
                 }
-                unit.grow(length);
+                self.install_on_unit(unit_index, || {
+                    self.lock_contention.write_unit(unit_ptr, |unit| unit.grow(length));
+                });
             });
-        })
+        });
+        self.phase_profiler.record(ProfilerPhase::Grow, profiler_start);
     }
 
     fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) {
         self.thread_pool.scope(|_| {
-            self.units.par_iter().for_each(|unit_ptr| {
-                lock_write!(unit, unit_ptr);
-                if !unit.is_active {
-                    return;
-                }
-                unit.load_edge_modifier(edge_modifier);
+            self.units.par_iter().enumerate().for_each(|(unit_index, unit_ptr)| {
+                // inactive units still hold their own copies of the edges, so every unit must see the modifier
+                self.install_on_unit(unit_index, || {
+                    self.lock_contention
+                        .write_unit(unit_ptr, |unit| unit.load_edge_modifier(edge_modifier));
+                });
             });
         })
     }
 
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        let unit_phases: Vec<_> = self
+            .units
+            .iter()
+            .map(|unit_ptr| unit_ptr.read_recursive().phase_profiler.to_json())
+            .collect();
+        json!({
+            "lock_contention": self.lock_contention.report(),
+            "phases": self.phase_profiler.to_json(),
+            "unit_phases": unit_phases,
+        })
+    }
+
     fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> &mut Vec<SyncRequest> {
         let unit_ptr = self.find_active_ancestor(&nodes_circle[0]);
         self.thread_pool.scope(|_| {
-            lock_write!(unit, unit_ptr);
-            unit.prepare_nodes_shrink(nodes_circle);
+            self.lock_contention.write_unit(&unit_ptr, |unit| {
+                unit.prepare_nodes_shrink(nodes_circle);
+            });
         });
         &mut self.empty_sync_request
     }
@@ -607,10 +1091,65 @@ impl<SerialModule: DualModuleImpl + FusionVisualizer + Send + Sync> FusionVisual
             let value_2 = unit.snapshot(abbrev);
             snapshot_combine_values(&mut value, value_2, abbrev);
         }
+        self.snapshot_partition_overlay(&mut value, abbrev);
         value
     }
 }
 
+impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallel<SerialModule> {
+    /// tag every vertex and edge with its owning unit and the fusion level of that unit, and list the fusion state of all
+    /// the units, so that the viewer can color the partitions and animate the fusion order
+    #[allow(clippy::unnecessary_cast)]
+    fn snapshot_partition_overlay(&self, value: &mut serde_json::Value, abbrev: bool) {
+        let partition_info = &self.partition_info;
+        let fusion_levels: Vec<usize> = (0..partition_info.units.len())
+            .map(|unit_index| partition_info.fusion_level(unit_index))
+            .collect();
+        let key_unit = if abbrev { "pu" } else { "partition_unit" };
+        let key_fusion_level = if abbrev { "fl" } else { "fusion_level" };
+        let value = value.as_object_mut().expect("snapshot must be an object");
+        if let Some(vertices) = value.get_mut("vertices").and_then(|vertices| vertices.as_array_mut()) {
+            for (vertex_index, vertex) in vertices.iter_mut().enumerate() {
+                if let Some(vertex) = vertex.as_object_mut() {
+                    let unit_index = partition_info.owner_of_vertex(vertex_index as VertexIndex);
+                    vertex.insert(key_unit.to_string(), json!(unit_index));
+                    vertex.insert(key_fusion_level.to_string(), json!(fusion_levels[unit_index]));
+                }
+            }
+        }
+        if let Some(edges) = value.get_mut("edges").and_then(|edges| edges.as_array_mut()) {
+            let key_left = if abbrev { "l" } else { "left" };
+            let key_right = if abbrev { "r" } else { "right" };
+            for edge in edges.iter_mut() {
+                if let Some(edge) = edge.as_object_mut() {
+                    let vertex_owner = |key: &str| {
+                        let vertex_index = edge[key].as_u64().expect("edge vertex must be an index");
+                        partition_info.owner_of_vertex(vertex_index as VertexIndex)
+                    };
+                    let unit_index = partition_info.lowest_common_unit(vertex_owner(key_left), vertex_owner(key_right));
+                    edge.insert(key_unit.to_string(), json!(unit_index));
+                    edge.insert(key_fusion_level.to_string(), json!(fusion_levels[unit_index]));
+                }
+            }
+        }
+        let fusion_units: Vec<serde_json::Value> = self
+            .units
+            .iter()
+            .map(|unit_ptr| {
+                let unit = unit_ptr.read_recursive();
+                let unit_info = &partition_info.units[unit.unit_index];
+                json!({
+                    if abbrev { "a" } else { "is_active" }: i32::from(unit.is_active),
+                    if abbrev { "e" } else { "enabled" }: i32::from(unit.partition_unit.read_recursive().enabled),
+                    key_fusion_level: fusion_levels[unit.unit_index],
+                    if abbrev { "p" } else { "parent" }: unit_info.parent,
+                })
+            })
+            .collect();
+        value.insert("fusion_units".to_string(), json!(fusion_units));
+    }
+}
+
 impl<SerialModule: DualModuleImpl + FusionVisualizer + Send + Sync> FusionVisualizer
     for DualModuleParallelUnit<SerialModule>
 {
@@ -658,6 +1197,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         parent_interface: &DualModuleInterfacePtr,
         children_interfaces: (&DualModuleInterfacePtr, &DualModuleInterfacePtr),
     ) {
+        let profiler_start = self.phase_profiler.start();
         self.static_fuse();
         let (left_interface, right_interface) = children_interfaces;
         let right_child_ptr = self.children.as_ref().unwrap().1.upgrade_force();
@@ -666,23 +1206,33 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         let bias = left_interface.read_recursive().nodes_count();
         right_child.iterative_bias_dual_node_index(bias);
         parent_interface.fuse(left_interface, right_interface);
+        self.phase_profiler.record(ProfilerPhase::Fuse, profiler_start);
     }
 
     pub fn iterative_bias_dual_node_index(&mut self, bias: NodeIndex) {
+        let lock_contention = &self.lock_contention;
         // depth-first search
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
                 rayon::join(
                     || {
-                        left_child_weak.upgrade_force().write().iterative_bias_dual_node_index(bias);
+                        lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                            child.iterative_bias_dual_node_index(bias)
+                        });
                     },
                     || {
-                        right_child_weak.upgrade_force().write().iterative_bias_dual_node_index(bias);
+                        lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                            child.iterative_bias_dual_node_index(bias)
+                        });
                     },
                 );
             } else {
-                left_child_weak.upgrade_force().write().iterative_bias_dual_node_index(bias);
-                right_child_weak.upgrade_force().write().iterative_bias_dual_node_index(bias);
+                lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                    child.iterative_bias_dual_node_index(bias)
+                });
+                lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                    child.iterative_bias_dual_node_index(bias)
+                });
             }
         }
         // my serial module
@@ -705,7 +1255,8 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
 
     /// iteratively prepare all growing and shrinking and append the sync requests
     fn iterative_prepare_all(&mut self, sync_requests: &mut Vec<SyncRequest>) {
-        if !self.has_active_node {
+        let lock_contention = &self.lock_contention;
+        if !self.has_active_node.load(Ordering::Relaxed) {
             return; // early return to avoid going through all units
         }
         // depth-first search
@@ -714,19 +1265,24 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
                 let mut sync_requests_2 = vec![];
                 rayon::join(
                     || {
-                        left_child_weak.upgrade_force().write().iterative_prepare_all(sync_requests);
+                        lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                            child.iterative_prepare_all(sync_requests)
+                        });
                     },
                     || {
-                        right_child_weak
-                            .upgrade_force()
-                            .write()
-                            .iterative_prepare_all(&mut sync_requests_2);
+                        lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                            child.iterative_prepare_all(&mut sync_requests_2)
+                        });
                     },
                 );
                 sync_requests.append(&mut sync_requests_2);
             } else {
-                left_child_weak.upgrade_force().write().iterative_prepare_all(sync_requests);
-                right_child_weak.upgrade_force().write().iterative_prepare_all(sync_requests);
+                lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                    child.iterative_prepare_all(sync_requests)
+                });
+                lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                    child.iterative_prepare_all(sync_requests)
+                });
             }
         }
         // my serial module
@@ -741,24 +1297,21 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         grow_state: DualNodeGrowState,
         representative_vertex: VertexIndex,
     ) {
+        let lock_contention = &self.lock_contention;
         if !self.whole_range.contains(representative_vertex) && !self.elevated_dual_nodes.contains(dual_node_ptr) {
             return; // no descendant related to this dual node
         }
         if grow_state != DualNodeGrowState::Stay {
-            self.has_active_node = true;
+            self.has_active_node.store(true, Ordering::Relaxed);
         }
         // depth-first search
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
-            left_child_weak.upgrade_force().write().iterative_set_grow_state(
-                dual_node_ptr,
-                grow_state,
-                representative_vertex,
-            );
-            right_child_weak.upgrade_force().write().iterative_set_grow_state(
-                dual_node_ptr,
-                grow_state,
-                representative_vertex,
-            );
+            lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                child.iterative_set_grow_state(dual_node_ptr, grow_state, representative_vertex)
+            });
+            lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                child.iterative_set_grow_state(dual_node_ptr, grow_state, representative_vertex)
+            });
         }
         if self.owning_range.contains(representative_vertex) || self.serial_module.contains_dual_node(dual_node_ptr) {
             self.serial_module.set_grow_state(dual_node_ptr, grow_state);
@@ -782,42 +1335,35 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         nodes_circle_vertices: &[VertexIndex],
         sync_requests: &mut Vec<SyncRequest>,
     ) {
+        let lock_contention = &self.lock_contention;
         if !self.whole_range.contains_any(nodes_circle_vertices) && !self.elevated_dual_nodes_contains_any(nodes_circle) {
             return; // no descendant related to this dual node
         }
-        self.has_active_node = true;
+        self.has_active_node.store(true, Ordering::Relaxed);
         // depth-first search
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
                 let mut sync_requests_2 = vec![];
                 rayon::join(
                     || {
-                        left_child_weak.upgrade_force().write().iterative_prepare_nodes_shrink(
-                            nodes_circle,
-                            nodes_circle_vertices,
-                            sync_requests,
-                        );
+                        lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                            child.iterative_prepare_nodes_shrink(nodes_circle, nodes_circle_vertices, sync_requests)
+                        });
                     },
                     || {
-                        right_child_weak.upgrade_force().write().iterative_prepare_nodes_shrink(
-                            nodes_circle,
-                            nodes_circle_vertices,
-                            &mut sync_requests_2,
-                        );
+                        lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                            child.iterative_prepare_nodes_shrink(nodes_circle, nodes_circle_vertices, &mut sync_requests_2)
+                        });
                     },
                 );
                 sync_requests.append(&mut sync_requests_2);
             } else {
-                left_child_weak.upgrade_force().write().iterative_prepare_nodes_shrink(
-                    nodes_circle,
-                    nodes_circle_vertices,
-                    sync_requests,
-                );
-                right_child_weak.upgrade_force().write().iterative_prepare_nodes_shrink(
-                    nodes_circle,
-                    nodes_circle_vertices,
-                    sync_requests,
-                );
+                lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                    child.iterative_prepare_nodes_shrink(nodes_circle, nodes_circle_vertices, sync_requests)
+                });
+                lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                    child.iterative_prepare_nodes_shrink(nodes_circle, nodes_circle_vertices, sync_requests)
+                });
             }
         }
         let local_sync_requests = self.serial_module.prepare_nodes_shrink(nodes_circle);
@@ -831,44 +1377,43 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
         representative_vertex: VertexIndex,
         nodes_circle_vertices: &[VertexIndex],
     ) {
+        let lock_contention = &self.lock_contention;
         if !self.whole_range.contains_any(nodes_circle_vertices) && !self.elevated_dual_nodes_contains_any(nodes_circle) {
             return; // no descendant related to this dual node
         }
-        self.has_active_node = true;
+        self.has_active_node.store(true, Ordering::Relaxed);
         // depth-first search
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
                 rayon::join(
                     || {
-                        left_child_weak.upgrade_force().write().iterative_add_blossom(
-                            blossom_ptr,
-                            nodes_circle,
-                            representative_vertex,
-                            nodes_circle_vertices,
-                        );
+                        lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                            child.iterative_add_blossom(
+                                blossom_ptr,
+                                nodes_circle,
+                                representative_vertex,
+                                nodes_circle_vertices,
+                            )
+                        });
                     },
                     || {
-                        right_child_weak.upgrade_force().write().iterative_add_blossom(
-                            blossom_ptr,
-                            nodes_circle,
-                            representative_vertex,
-                            nodes_circle_vertices,
-                        );
+                        lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                            child.iterative_add_blossom(
+                                blossom_ptr,
+                                nodes_circle,
+                                representative_vertex,
+                                nodes_circle_vertices,
+                            )
+                        });
                     },
                 );
             } else {
-                left_child_weak.upgrade_force().write().iterative_add_blossom(
-                    blossom_ptr,
-                    nodes_circle,
-                    representative_vertex,
-                    nodes_circle_vertices,
-                );
-                right_child_weak.upgrade_force().write().iterative_add_blossom(
-                    blossom_ptr,
-                    nodes_circle,
-                    representative_vertex,
-                    nodes_circle_vertices,
-                );
+                lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                    child.iterative_add_blossom(blossom_ptr, nodes_circle, representative_vertex, nodes_circle_vertices)
+                });
+                lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                    child.iterative_add_blossom(blossom_ptr, nodes_circle, representative_vertex, nodes_circle_vertices)
+                });
             }
         }
         if self.owning_range.contains_any(nodes_circle_vertices) || self.serial_module.contains_dual_nodes_any(nodes_circle)
@@ -883,38 +1428,41 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
     }
 
     fn iterative_add_defect_node(&mut self, dual_node_ptr: &DualNodePtr, vertex_index: VertexIndex) {
+        let lock_contention = &self.lock_contention;
         // if the vertex is not hold by any descendant, simply return
         if !self.is_vertex_in_descendant(vertex_index) {
             return;
         }
-        self.has_active_node = true;
+        assert!(
+            !self.evicted || !self.owning_range.contains(vertex_index),
+            "defect vertex {} is in the evicted unit {}",
+            vertex_index,
+            self.unit_index
+        );
+        self.has_active_node.store(true, Ordering::Relaxed);
         // println!("sync_prepare_growth_update_sync_event: vertex {}, unit index {}", sync_event.vertex_index, self.unit_index);
         // depth-first search
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
                 rayon::join(
                     || {
-                        left_child_weak
-                            .upgrade_force()
-                            .write()
-                            .iterative_add_defect_node(dual_node_ptr, vertex_index);
+                        lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                            child.iterative_add_defect_node(dual_node_ptr, vertex_index)
+                        });
                     },
                     || {
-                        right_child_weak
-                            .upgrade_force()
-                            .write()
-                            .iterative_add_defect_node(dual_node_ptr, vertex_index);
+                        lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                            child.iterative_add_defect_node(dual_node_ptr, vertex_index)
+                        });
                     },
                 );
             } else {
-                left_child_weak
-                    .upgrade_force()
-                    .write()
-                    .iterative_add_defect_node(dual_node_ptr, vertex_index);
-                right_child_weak
-                    .upgrade_force()
-                    .write()
-                    .iterative_add_defect_node(dual_node_ptr, vertex_index);
+                lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                    child.iterative_add_defect_node(dual_node_ptr, vertex_index)
+                });
+                lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                    child.iterative_add_defect_node(dual_node_ptr, vertex_index)
+                });
             }
         }
         // update on my serial module
@@ -929,13 +1477,15 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
     }
 
     fn iterative_compute_maximum_update_length(&mut self, group_max_update_length: &mut GroupMaxUpdateLength) -> bool {
+        let lock_contention = &self.lock_contention;
         // early terminate if no active dual nodes anywhere in the descendant
-        if !self.has_active_node {
+        if !self.has_active_node.load(Ordering::Relaxed) {
             return false;
         }
-        let serial_module_group_max_update_length = self.serial_module.compute_maximum_update_length();
+        let mut serial_module_group_max_update_length = self.serial_module.compute_maximum_update_length();
+        serial_module_group_max_update_length.record_origin(self.unit_index);
         if !serial_module_group_max_update_length.is_active() {
-            self.has_active_node = false;
+            self.has_active_node.store(false, Ordering::Relaxed);
         }
         group_max_update_length.extend(serial_module_group_max_update_length);
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
@@ -943,40 +1493,122 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
                 let mut group_max_update_length_2 = GroupMaxUpdateLength::new();
                 let (left_child_has_active_node, right_child_has_active_node) = rayon::join(
                     || {
-                        left_child_weak
-                            .upgrade_force()
-                            .write()
-                            .iterative_compute_maximum_update_length(group_max_update_length)
+                        lock_contention
+                            .write_active_unit(&left_child_weak.upgrade_force(), |child| {
+                                child.iterative_compute_maximum_update_length(group_max_update_length)
+                            })
+                            .unwrap_or(false)
                     },
                     || {
-                        right_child_weak
-                            .upgrade_force()
-                            .write()
-                            .iterative_compute_maximum_update_length(&mut group_max_update_length_2)
+                        lock_contention
+                            .write_active_unit(&right_child_weak.upgrade_force(), |child| {
+                                child.iterative_compute_maximum_update_length(&mut group_max_update_length_2)
+                            })
+                            .unwrap_or(false)
                     },
                 );
                 group_max_update_length.extend(group_max_update_length_2);
                 (left_child_has_active_node, right_child_has_active_node)
             } else {
                 (
-                    left_child_weak
-                        .upgrade_force()
-                        .write()
-                        .iterative_compute_maximum_update_length(group_max_update_length),
-                    right_child_weak
-                        .upgrade_force()
-                        .write()
-                        .iterative_compute_maximum_update_length(group_max_update_length),
+                    lock_contention
+                        .write_active_unit(&left_child_weak.upgrade_force(), |child| {
+                            child.iterative_compute_maximum_update_length(group_max_update_length)
+                        })
+                        .unwrap_or(false),
+                    lock_contention
+                        .write_active_unit(&right_child_weak.upgrade_force(), |child| {
+                            child.iterative_compute_maximum_update_length(group_max_update_length)
+                        })
+                        .unwrap_or(false),
+                )
+            };
+            if left_child_has_active_node || right_child_has_active_node {
+                self.has_active_node.store(true, Ordering::Relaxed)
+            }
+        }
+        self.has_active_node.load(Ordering::Relaxed)
+    }
+
+    /// prepare the serial modules of the descendants again after they're synchronized, which is otherwise done by
+    /// [`DualModuleImpl::compute_maximum_update_length`] of each serial module
+    fn iterative_prepare_serial_modules(&mut self) {
+        let lock_contention = &self.lock_contention;
+        if !self.has_active_node.load(Ordering::Relaxed) {
+            return;
+        }
+        let sync_requests = self.serial_module.prepare_all();
+        debug_assert!(
+            sync_requests.is_empty(),
+            "no sync requests should arise here; make sure to deal with all sync requests before growing"
+        );
+        if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
+            if self.enable_parallel_execution {
+                rayon::join(
+                    || {
+                        lock_contention.write_active_unit(&left_child_weak.upgrade_force(), |child| {
+                            child.iterative_prepare_serial_modules()
+                        });
+                    },
+                    || {
+                        lock_contention.write_active_unit(&right_child_weak.upgrade_force(), |child| {
+                            child.iterative_prepare_serial_modules()
+                        });
+                    },
+                );
+            } else {
+                lock_contention.write_active_unit(&left_child_weak.upgrade_force(), |child| {
+                    child.iterative_prepare_serial_modules()
+                });
+                lock_contention.write_active_unit(&right_child_weak.upgrade_force(), |child| {
+                    child.iterative_prepare_serial_modules()
+                });
+            }
+        }
+    }
+
+    /// the same as [`Self::iterative_compute_maximum_update_length`] for the prepared dual nodes, taking only the shared
+    /// locks of the descendants; `None` if any serial module doesn't support [`DualModuleImpl::query_prepared_maximum_update_length`]
+    fn iterative_query_maximum_update_length(&self, group_max_update_length: &mut GroupMaxUpdateLength) -> Option<bool> {
+        // early terminate if no active dual nodes anywhere in the descendant
+        if !self.has_active_node.load(Ordering::Relaxed) {
+            return Some(false);
+        }
+        let mut serial_module_group_max_update_length = self.serial_module.query_prepared_maximum_update_length()?;
+        serial_module_group_max_update_length.record_origin(self.unit_index);
+        if !serial_module_group_max_update_length.is_active() {
+            self.has_active_node.store(false, Ordering::Relaxed);
+        }
+        group_max_update_length.extend(serial_module_group_max_update_length);
+        if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
+            let query_child = |child_weak: &DualModuleParallelUnitWeak<SerialModule>, group: &mut GroupMaxUpdateLength| {
+                let child_ptr = child_weak.upgrade_force();
+                lock_read!(child, child_ptr);
+                child.iterative_query_maximum_update_length(group)
+            };
+            let (left_child_has_active_node, right_child_has_active_node) = if self.enable_parallel_execution {
+                let mut group_max_update_length_2 = GroupMaxUpdateLength::new();
+                let (left_child_has_active_node, right_child_has_active_node) = rayon::join(
+                    || query_child(left_child_weak, group_max_update_length),
+                    || query_child(right_child_weak, &mut group_max_update_length_2),
+                );
+                group_max_update_length.extend(group_max_update_length_2);
+                (left_child_has_active_node?, right_child_has_active_node?)
+            } else {
+                (
+                    query_child(left_child_weak, group_max_update_length)?,
+                    query_child(right_child_weak, group_max_update_length)?,
                 )
             };
             if left_child_has_active_node || right_child_has_active_node {
-                self.has_active_node = true
+                self.has_active_node.store(true, Ordering::Relaxed)
             }
         }
-        self.has_active_node
+        Some(self.has_active_node.load(Ordering::Relaxed))
     }
 
     fn iterative_grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight, representative_vertex: VertexIndex) {
+        let lock_contention = &self.lock_contention;
         if !self.whole_range.contains(representative_vertex) && !self.elevated_dual_nodes.contains(dual_node_ptr) {
             return; // no descendant related to this dual node
         }
@@ -984,31 +1616,23 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
             if self.enable_parallel_execution {
                 rayon::join(
                     || {
-                        left_child_weak.upgrade_force().write().iterative_grow_dual_node(
-                            dual_node_ptr,
-                            length,
-                            representative_vertex,
-                        );
+                        lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                            child.iterative_grow_dual_node(dual_node_ptr, length, representative_vertex)
+                        });
                     },
                     || {
-                        right_child_weak.upgrade_force().write().iterative_grow_dual_node(
-                            dual_node_ptr,
-                            length,
-                            representative_vertex,
-                        );
+                        lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                            child.iterative_grow_dual_node(dual_node_ptr, length, representative_vertex)
+                        });
                     },
                 );
             } else {
-                left_child_weak.upgrade_force().write().iterative_grow_dual_node(
-                    dual_node_ptr,
-                    length,
-                    representative_vertex,
-                );
-                right_child_weak.upgrade_force().write().iterative_grow_dual_node(
-                    dual_node_ptr,
-                    length,
-                    representative_vertex,
-                );
+                lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                    child.iterative_grow_dual_node(dual_node_ptr, length, representative_vertex)
+                });
+                lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                    child.iterative_grow_dual_node(dual_node_ptr, length, representative_vertex)
+                });
             }
         }
         if self.owning_range.contains(representative_vertex) || self.serial_module.contains_dual_node(dual_node_ptr) {
@@ -1017,8 +1641,9 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
     }
 
     fn iterative_grow(&mut self, length: Weight) {
+        let lock_contention = &self.lock_contention;
         // early terminate if no active dual nodes anywhere in the descendant
-        if !self.has_active_node {
+        if !self.has_active_node.load(Ordering::Relaxed) {
             return;
         }
         self.serial_module.grow(length);
@@ -1026,49 +1651,48 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnit<SerialMo
             if self.enable_parallel_execution {
                 rayon::join(
                     || {
-                        left_child_weak.upgrade_force().write().iterative_grow(length);
+                        lock_contention
+                            .write_active_unit(&left_child_weak.upgrade_force(), |child| child.iterative_grow(length));
                     },
                     || {
-                        right_child_weak.upgrade_force().write().iterative_grow(length);
+                        lock_contention
+                            .write_active_unit(&right_child_weak.upgrade_force(), |child| child.iterative_grow(length));
                     },
                 );
             } else {
-                left_child_weak.upgrade_force().write().iterative_grow(length);
-                right_child_weak.upgrade_force().write().iterative_grow(length);
+                lock_contention.write_active_unit(&left_child_weak.upgrade_force(), |child| child.iterative_grow(length));
+                lock_contention.write_active_unit(&right_child_weak.upgrade_force(), |child| child.iterative_grow(length));
             }
         }
     }
 
     fn iterative_remove_blossom(&mut self, dual_node_ptr: &DualNodePtr, representative_vertex: VertexIndex) {
+        let lock_contention = &self.lock_contention;
         if !self.whole_range.contains(representative_vertex) && !self.elevated_dual_nodes.contains(dual_node_ptr) {
             return; // no descendant related to this dual node
         }
-        self.has_active_node = true;
+        self.has_active_node.store(true, Ordering::Relaxed);
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
             if self.enable_parallel_execution {
                 rayon::join(
                     || {
-                        left_child_weak
-                            .upgrade_force()
-                            .write()
-                            .iterative_remove_blossom(dual_node_ptr, representative_vertex);
+                        lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                            child.iterative_remove_blossom(dual_node_ptr, representative_vertex)
+                        });
                     },
                     || {
-                        right_child_weak
-                            .upgrade_force()
-                            .write()
-                            .iterative_remove_blossom(dual_node_ptr, representative_vertex);
+                        lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                            child.iterative_remove_blossom(dual_node_ptr, representative_vertex)
+                        });
                     },
                 );
             } else {
-                left_child_weak
-                    .upgrade_force()
-                    .write()
-                    .iterative_remove_blossom(dual_node_ptr, representative_vertex);
-                right_child_weak
-                    .upgrade_force()
-                    .write()
-                    .iterative_remove_blossom(dual_node_ptr, representative_vertex);
+                lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| {
+                    child.iterative_remove_blossom(dual_node_ptr, representative_vertex)
+                });
+                lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                    child.iterative_remove_blossom(dual_node_ptr, representative_vertex)
+                });
             }
         }
         if self.owning_range.contains(representative_vertex) || self.serial_module.contains_dual_node(dual_node_ptr) {
@@ -1085,9 +1709,10 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnitPtr<Seria
         partition_info: Arc<PartitionInfo>,
         partition_unit: PartitionUnitPtr,
         enable_parallel_execution: bool,
+        lock_contention: Arc<UnitLockContention>,
     ) -> Self {
         let partition_unit_info = &partition_info.units[unit_index];
-        Self::new_value(DualModuleParallelUnit {
+        let unit_ptr = Self::new_value(DualModuleParallelUnit {
             unit_index,
             partition_info: partition_info.clone(),
             partition_unit,
@@ -1101,8 +1726,14 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleParallelUnitPtr<Seria
             elevated_dual_nodes: PtrWeakHashSet::new(),
             empty_sync_request: vec![],
             enable_parallel_execution,
-            has_active_node: true, // by default to true, because children may have active nodes
-        })
+            has_active_node: AtomicBool::new(true), // by default to true, because children may have active nodes
+            lock_contention,
+            phase_profiler: PhaseProfiler::new(),
+            evicted: false,
+        });
+        #[cfg(feature = "lock_order_audit")]
+        crate::lock_order_audit::register(&unit_ptr, format!("dual unit {unit_index}"));
+        unit_ptr
     }
 }
 
@@ -1115,13 +1746,13 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
 
     /// clear all growth and existing dual nodes
     fn clear(&mut self) {
-        self.has_active_node = true;
+        self.has_active_node.store(true, Ordering::Relaxed);
         self.serial_module.clear()
     }
 
     /// add a new dual node from dual module root
     fn add_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
-        self.has_active_node = true;
+        self.has_active_node.store(true, Ordering::Relaxed);
         let representative_vertex = dual_node_ptr.get_representative_vertex();
         match &dual_node_ptr.read_recursive().class {
             // fast path: if dual node is a single vertex, then only add to the owning node; single vertex dual node can only add when dual variable = 0
@@ -1139,8 +1770,8 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
                         };
                         let mut is_owning_dual_node = false;
                         while !is_owning_dual_node {
-                            let mut child = child_ptr.write();
-                            child.has_active_node = true;
+                            let child = child_ptr.read_recursive();
+                            child.has_active_node.store(true, Ordering::Relaxed);
                             debug_assert!(
                                 child.whole_range.contains(representative_vertex),
                                 "selected child must contains the vertex"
@@ -1162,8 +1793,9 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
                                 child_ptr = grandson_ptr;
                             }
                         }
-                        lock_write!(child, child_ptr);
-                        child.iterative_add_defect_node(dual_node_ptr, *defect_index);
+                        self.lock_contention.write_unit(&child_ptr, |child| {
+                            child.iterative_add_defect_node(dual_node_ptr, *defect_index)
+                        });
                     } else {
                         unreachable!()
                     }
@@ -1223,6 +1855,7 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
     }
 
     fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+        let profiler_start = self.phase_profiler.start();
         // first prepare all dual node for growth and shrink accordingly and synchronize them
         self.prepare_all();
         // them do the functions independently
@@ -1232,9 +1865,22 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
             // for those base partitions without being fused, we don't need to update
             group_max_update_length.update(); // only necessary after involved in fusion
         }
+        group_max_update_length.record_interface(self.unit_index);
+        self.phase_profiler
+            .record(ProfilerPhase::ComputeMaximumUpdateLength, profiler_start);
         group_max_update_length
     }
 
+    fn query_prepared_maximum_update_length(&self) -> Option<GroupMaxUpdateLength> {
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        self.iterative_query_maximum_update_length(&mut group_max_update_length)?;
+        if !(self.children.is_none() && self.is_active) {
+            group_max_update_length.update(); // only necessary after involved in fusion
+        }
+        group_max_update_length.record_interface(self.unit_index);
+        Some(group_max_update_length)
+    }
+
     fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) {
         let representative_vertex = dual_node_ptr.get_representative_vertex();
         debug_assert!(
@@ -1245,12 +1891,14 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
     }
 
     fn grow(&mut self, length: Weight) {
+        let profiler_start = self.phase_profiler.start();
         self.iterative_grow(length);
+        self.phase_profiler.record(ProfilerPhase::Grow, profiler_start);
     }
 
     fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) {
-        // TODO: split the edge modifier and then load them to individual descendant units
-        // hint: each edge could appear in any unit that mirrors the two vertices
+        // each edge could appear in any unit that mirrors the two vertices; the serial module skips edges it doesn't hold,
+        // and the descendant units are loaded individually by [`DualModuleParallel::load_edge_modifier`]
         self.serial_module.load_edge_modifier(edge_modifier)
     }
 
@@ -1292,12 +1940,15 @@ impl<SerialModule: DualModuleImpl + Send + Sync> DualModuleImpl for DualModulePa
         if !self.is_vertex_in_descendant(sync_event.vertex_index) {
             return;
         }
-        self.has_active_node = true;
+        self.has_active_node.store(true, Ordering::Relaxed);
         // println!("sync_prepare_growth_update_sync_event: vertex {}, unit index {}", sync_event.vertex_index, self.unit_index);
         // depth-first search
         if let Some((left_child_weak, right_child_weak)) = self.children.as_ref() {
-            left_child_weak.upgrade_force().write().execute_sync_event(sync_event);
-            right_child_weak.upgrade_force().write().execute_sync_event(sync_event);
+            let lock_contention = &self.lock_contention;
+            lock_contention.write_unit(&left_child_weak.upgrade_force(), |child| child.execute_sync_event(sync_event));
+            lock_contention.write_unit(&right_child_weak.upgrade_force(), |child| {
+                child.execute_sync_event(sync_event)
+            });
         }
         // update on my serial module
         if self.serial_module.contains_vertex(sync_event.vertex_index) {
@@ -1342,6 +1993,7 @@ pub struct Interface {
 pub mod tests {
     use super::super::example_codes::*;
     use super::super::primal_module::*;
+    use super::super::primal_module_parallel::*;
     use super::super::primal_module_serial::*;
     use super::*;
 
@@ -1518,6 +2170,36 @@ pub mod tests {
         );
     }
 
+    /// the exclusive locks of the units are counted in the profiler report
+    #[test]
+    fn dual_module_parallel_lock_contention_1() {
+        // cargo test dual_module_parallel_lock_contention_1 -- --nocapture
+        let visualize_filename = "dual_module_parallel_lock_contention_1.json".to_string();
+        let defect_vertices = vec![39, 52, 63, 90, 100];
+        let half_weight = 500;
+        let (_interface_ptr, _primal_module, dual_module) = dual_module_parallel_standard_syndrome(
+            CodeCapacityPlanarCode::new(11, 0.1, half_weight),
+            visualize_filename,
+            defect_vertices,
+            9 * half_weight,
+            |_initializer, config| {
+                config.partitions = vec![
+                    VertexRange::new(0, 60),   // unit 0
+                    VertexRange::new(72, 132), // unit 1
+                ];
+                config.fusions = vec![
+                    (0, 1), // unit 2, by fusing 0 and 1
+                ];
+            },
+            None,
+        );
+        let report = dual_module.generate_profiler_report();
+        println!("{report}");
+        let lock_contention = &report["lock_contention"];
+        assert!(lock_contention["acquisitions"].as_u64().unwrap() > 0);
+        assert!(lock_contention["contended"].as_u64().unwrap() <= lock_contention["acquisitions"].as_u64().unwrap());
+    }
+
     /// split into 4, with no syndrome vertex on the interface
     #[test]
     fn dual_module_parallel_basic_4() {
@@ -1847,6 +2529,48 @@ pub mod tests {
         dual_module_parallel_debug_planar_code_common(19, visualize_filename, defect_vertices, 5);
     }
 
+    /// the origin of an obstacle should lead to the unit that reports it
+    #[test]
+    fn dual_module_parallel_find_origin_unit_1() {
+        // cargo test dual_module_parallel_find_origin_unit_1 -- --nocapture
+        let half_weight = 500;
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, half_weight);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![
+            VertexRange::new(0, 72),   // unit 0
+            VertexRange::new(84, 132), // unit 1
+        ];
+        partition_config.fusions = vec![
+            (0, 1), // unit 2, by fusing 0 and 1
+        ];
+        let partition_info = partition_config.info();
+        let mut dual_module: DualModuleParallel<DualModuleSerial> =
+            DualModuleParallel::new_config(&initializer, &partition_info, DualModuleParallelConfig::default());
+        code.set_defect_vertices(&[100, 101]);
+        let interface_ptr = DualModuleInterfacePtr::new_load(&code.get_syndrome(), &mut dual_module);
+        let mut group_max_update_length = dual_module.compute_maximum_update_length();
+        while let Some(length) = group_max_update_length.get_none_zero_growth() {
+            interface_ptr.grow(length, &mut dual_module);
+            group_max_update_length = dual_module.compute_maximum_update_length();
+        }
+        let max_update_length = group_max_update_length.peek().unwrap();
+        let origin = group_max_update_length.get_origin(max_update_length).unwrap();
+        assert_eq!(origin.unit_index, 1);
+        assert_eq!(origin.interface_index, 1);
+        let unit_ptr = dual_module.find_origin_unit(&origin);
+        assert_eq!(unit_ptr.read_recursive().unit_index, 1);
+        // once fused, the fusion unit is responsible for the obstacle
+        dual_module.static_fuse_all();
+        let unit_ptr = dual_module.find_origin_unit(&origin);
+        assert_eq!(unit_ptr.read_recursive().unit_index, 2);
+        // and the obstacles computed again originate from the fusion unit
+        let group_max_update_length = dual_module.compute_maximum_update_length();
+        let max_update_length = group_max_update_length.peek().unwrap();
+        let origin = group_max_update_length.get_origin(max_update_length).unwrap();
+        assert_eq!(origin.interface_index, 2);
+    }
+
     /// test rayon global thread pool
     #[test]
     fn dual_module_parallel_rayon_test_1() {
@@ -1920,4 +2644,300 @@ pub mod tests {
         });
         println!("results: {results:?}");
     }
+
+    #[test]
+    fn dual_module_parallel_thread_affinity_1() {
+        // cargo test dual_module_parallel_thread_affinity_1 -- --nocapture
+        let mut partition_config = PartitionConfig::new(390);
+        partition_config.partitions = (0..4).map(|i| VertexRange::new(i * 100, i * 100 + 90)).collect();
+        partition_config.fusions = vec![(0, 1), (2, 3), (4, 5)];
+        let partition_info = partition_config.info();
+        assert_eq!(ThreadAffinity::None.unit_numa_nodes(&partition_info, 2), None);
+        assert_eq!(
+            ThreadAffinity::PinPerUnit.unit_numa_nodes(&partition_info, 2),
+            Some(vec![0, 0, 1, 1, 0, 1, 0])
+        );
+        assert_eq!(
+            ThreadAffinity::NumaInterleave.unit_numa_nodes(&partition_info, 2),
+            Some(vec![0, 1, 0, 1, 0, 0, 0])
+        );
+        // the online nodes may have holes, and memory-only nodes have no cores
+        let cpu_lists = [Some("0-1\n"), None, Some("2-3\n"), Some("\n")];
+        let read_cpu_list = |node_index: usize| cpu_lists[node_index].map(str::to_string);
+        assert_eq!(
+            CpuTopology::numa_nodes_from("0,2-3\n", read_cpu_list),
+            vec![vec![0, 1], vec![2, 3]]
+        );
+        assert!(CpuTopology::numa_nodes_from("", read_cpu_list).is_empty());
+        assert_eq!(
+            CpuTopology::parse_cpu_list("0-3,8,10-11\n"),
+            Some(vec![0, 1, 2, 3, 8, 10, 11])
+        );
+        assert_eq!(CpuTopology::parse_cpu_list("0-x"), None);
+        assert!(!CpuTopology::detect_numa_nodes().is_empty());
+        // the units are built on pinned threads but the decoding result is the same
+        let half_weight = 500;
+        let defect_vertices = vec![39, 52, 63, 90, 100];
+        for thread_affinity in [ThreadAffinity::PinPerUnit, ThreadAffinity::NumaInterleave] {
+            let mut code = CodeCapacityPlanarCode::new(11, 0.1, half_weight);
+            let initializer = code.get_initializer();
+            let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+            partition_config.partitions = vec![
+                VertexRange::new(0, 60),   // unit 0
+                VertexRange::new(72, 132), // unit 1
+            ];
+            partition_config.fusions = vec![
+                (0, 1), // unit 2, by fusing 0 and 1
+            ];
+            let partition_info = partition_config.info();
+            let config: DualModuleParallelConfig = serde_json::from_value(json!({
+                "thread_pool_size": 2,
+                "thread_affinity": thread_affinity,
+            }))
+            .unwrap();
+            let mut dual_module =
+                DualModuleParallel::<DualModuleSerial>::new_config(&initializer, &partition_info, config.clone());
+            for (unit_index, unit_ptr) in dual_module.units.iter().enumerate() {
+                assert_eq!(unit_ptr.read_recursive().unit_index, unit_index);
+            }
+            dual_module.static_fuse_all();
+            let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+            code.set_defect_vertices(&defect_vertices);
+            let interface_ptr = DualModuleInterfacePtr::new_empty();
+            primal_module.solve(&interface_ptr, &code.get_syndrome(), &mut dual_module);
+            assert_eq!(interface_ptr.sum_dual_variables(), 9 * half_weight * 2);
+            // the parallel primal module solves each unit on the pool of its NUMA node, with both schedulings
+            for prioritize_base_partition in [true, false] {
+                let dual_module =
+                    DualModuleParallel::<DualModuleSerial>::new_config(&initializer, &partition_info, config.clone());
+                let primal_config: PrimalModuleParallelConfig =
+                    serde_json::from_value(json!({ "prioritize_base_partition": prioritize_base_partition })).unwrap();
+                let mut primal_module = PrimalModuleParallel::new_config(&initializer, &partition_info, primal_config);
+                primal_module.parallel_solve(&code.get_syndrome(), &dual_module);
+                let last_unit = primal_module.units.last().unwrap().read_recursive();
+                assert_eq!(last_unit.interface_ptr.sum_dual_variables(), 9 * half_weight * 2);
+            }
+        }
+    }
+
+    #[test]
+    fn dual_module_parallel_config_suggest_1() {
+        // cargo test dual_module_parallel_config_suggest_1 -- --nocapture
+        let topology = CpuTopology {
+            logical_cores: 8,
+            l1d_cache_size: Some(48 << 10),
+            l2_cache_size: Some(1 << 20),
+            l3_cache_size: None,
+        };
+        // a small graph fits in the cache: no parallelism
+        let small = CodeCapacityPlanarCode::new(7, 0.1, 500).get_initializer();
+        let suggestion = DualModuleParallelConfig::suggest_for(&small, &topology);
+        assert_eq!(suggestion.leaf_unit_count, 1);
+        assert_eq!(suggestion.config.thread_pool_size, 1);
+        // a large graph uses all the cores, with each unit fitting in the cache
+        let large = PhenomenologicalPlanarCode::new(21, 100, 0.005, 500).get_initializer();
+        let suggestion = DualModuleParallelConfig::suggest_for(&large, &topology);
+        println!("{suggestion:?}");
+        assert_eq!(suggestion.config.thread_pool_size, 8);
+        assert!(suggestion.leaf_unit_count >= 8);
+        assert!(suggestion.estimated_footprint / suggestion.leaf_unit_count <= 1 << 20);
+        // the detected topology always gives a usable configuration
+        let suggestion = DualModuleParallelConfig::suggest(&large);
+        assert!(suggestion.config.thread_pool_size >= 1);
+        assert!(suggestion.config.thread_pool_size <= suggestion.topology.logical_cores);
+        assert_eq!(CpuTopology::parse_cache_size("2048K"), Some(2 << 20));
+    }
+
+    /// a cache of the partitioned initializers survives a JSON round trip and gives the same decoding results
+    #[test]
+    fn dual_module_parallel_new_from_cached_1() {
+        // cargo test dual_module_parallel_new_from_cached_1 -- --nocapture
+        use crate::example_partition::*;
+        use crate::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(5, 7, 0.03, 500);
+        let partition_info = PhenomenologicalPlanarCodeTimePartition::new_tree(5, 7, 4, true, usize::MAX)
+            .build_apply(&mut code)
+            .info();
+        let initializer = code.get_initializer();
+        for edges_in_fusion_unit in [true, false] {
+            let cache = DualModuleParallelCache::new(&initializer, &partition_info, edges_in_fusion_unit);
+            let json = serde_json::to_string(&cache).unwrap();
+            let loaded: DualModuleParallelCache = serde_json::from_str(&json).unwrap();
+            assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+            // the deserialized initializers refer to the same units, kept alive by themselves until bound
+            let fusion_initializer = &loaded.partitioned_initializers[partition_info.units.len() - 2];
+            let owning_interface = fusion_initializer.owning_interface.as_ref().unwrap().upgrade_force();
+            assert_eq!(owning_interface.read_recursive().unit_index, partition_info.units.len() - 2);
+            assert!(!owning_interface.read_recursive().enabled);
+            let config = json!({"dual": {"edges_in_fusion_unit": edges_in_fusion_unit}});
+            let mut solver = SolverParallel::new(&initializer, &partition_info, config.clone());
+            let mut cached_solver = SolverParallel::new_from_cached(&initializer, loaded, config);
+            for seed in 0..30 {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                solver.solve(&syndrome_pattern);
+                cached_solver.solve(&syndrome_pattern);
+                assert_eq!(solver.sum_dual_variables(), cached_solver.sum_dual_variables());
+                assert_eq!(solver.subgraph(), cached_solver.subgraph());
+                solver.clear();
+                cached_solver.clear();
+            }
+        }
+    }
+
+    /// the defect nodes are loaded into their owning units in parallel and decode to the same result
+    #[test]
+    fn dual_module_parallel_load_syndrome_partitioned_1() {
+        // cargo test dual_module_parallel_load_syndrome_partitioned_1 -- --nocapture
+        use crate::example_partition::*;
+        use crate::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(5, 15, 0.03, 500);
+        let partition_info = PhenomenologicalPlanarCodeTimePartition::new_tree(5, 15, 8, true, usize::MAX)
+            .build_apply(&mut code)
+            .info();
+        let initializer = code.get_initializer();
+        let config: DualModuleParallelConfig = serde_json::from_value(json!({"thread_pool_size": 4})).unwrap();
+        let mut dual_module = DualModuleParallel::<DualModuleSerial>::new_config(&initializer, &partition_info, config);
+        let mut serial_solver = SolverSerial::new(&initializer);
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            dual_module.static_fuse_all();
+            let interface_ptr = DualModuleInterfacePtr::new_empty();
+            dual_module.load_syndrome_partitioned(&interface_ptr, &syndrome_pattern);
+            assert_eq!(
+                interface_ptr.read_recursive().nodes_length,
+                syndrome_pattern.defect_vertices.len()
+            );
+            for unit_index in 0..partition_info.config.partitions.len() {
+                let owned = syndrome_pattern
+                    .defect_vertices
+                    .iter()
+                    .filter(|&&vertex_index| partition_info.owner_of_vertex(vertex_index) == unit_index)
+                    .count();
+                let unit = dual_module.units[unit_index].read_recursive();
+                assert!(unit.serial_module.nodes_length >= owned);
+            }
+            let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+            primal_module.load(&interface_ptr);
+            primal_module.solve_step_callback_interface_loaded(&interface_ptr, &mut dual_module, |_, _, _, _| {});
+            serial_solver.solve(&syndrome_pattern);
+            assert_eq!(interface_ptr.sum_dual_variables(), serial_solver.sum_dual_variables());
+            serial_solver.clear();
+            dual_module.clear();
+        }
+    }
+
+    /// an evicted unit is cut out of the decoding graph while the rest of the units keep decoding
+    #[test]
+    fn dual_module_parallel_evict_unit_1() {
+        // cargo test dual_module_parallel_evict_unit_1 -- --nocapture
+        use crate::example_partition::*;
+        use crate::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(5, 15, 0.03, 500);
+        let partition_info = PhenomenologicalPlanarCodeTimePartition::new_tree(5, 15, 4, true, usize::MAX)
+            .build_apply(&mut code)
+            .info();
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModuleParallel::<DualModuleSerial>::new_config(
+            &initializer,
+            &partition_info,
+            DualModuleParallelConfig::default(),
+        );
+        let evicted_unit = dual_module.evict_unit(0);
+        assert_eq!(evicted_unit.owning_range, partition_info.units[0].owning_range);
+        assert!(!evicted_unit.boundary_vertices.is_empty());
+        assert!(evicted_unit
+            .boundary_vertices
+            .iter()
+            .all(|&vertex_index| partition_info.owner_of_vertex(vertex_index) >= partition_info.config.partitions.len()));
+        assert_eq!(dual_module.evicted_units, vec![evicted_unit.clone()]);
+        {
+            let unit = dual_module.units[0].read_recursive();
+            assert!(unit.serial_module.vertices.is_empty() && unit.serial_module.edges.is_empty());
+        }
+        // the same graph without the edges of the evicted unit
+        let mut evicted_initializer = initializer.clone();
+        evicted_initializer
+            .weighted_edges
+            .retain(|&(i, j, _)| !evicted_unit.owning_range.contains(i) && !evicted_unit.owning_range.contains(j));
+        let mut serial_solver = SolverSerial::new(&evicted_initializer);
+        for seed in 0..20 {
+            let mut syndrome_pattern = code.generate_random_errors(seed);
+            syndrome_pattern
+                .defect_vertices
+                .retain(|&vertex_index| !evicted_unit.owning_range.contains(vertex_index));
+            dual_module.static_fuse_all();
+            let interface_ptr = DualModuleInterfacePtr::new_empty();
+            dual_module.load_syndrome_partitioned(&interface_ptr, &syndrome_pattern);
+            let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+            primal_module.load(&interface_ptr);
+            primal_module.solve_step_callback_interface_loaded(&interface_ptr, &mut dual_module, |_, _, _, _| {});
+            serial_solver.solve(&syndrome_pattern);
+            assert_eq!(interface_ptr.sum_dual_variables(), serial_solver.sum_dual_variables());
+            serial_solver.clear();
+            dual_module.clear();
+        }
+    }
+
+    /// the construction diagnostics are only reported on demand
+    #[test]
+    fn dual_module_parallel_verbose_1() {
+        // cargo test dual_module_parallel_verbose_1 -- --nocapture
+        assert!(!DualModuleParallelConfig::default().verbose);
+        let code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![VertexRange::new(0, 24), VertexRange::new(32, 56)];
+        partition_config.fusions = vec![(0, 1)];
+        let config: DualModuleParallelConfig = serde_json::from_value(json!({"verbose": true})).unwrap();
+        let dual_module = DualModuleParallel::<DualModuleSerial>::new_config(&initializer, &partition_config.info(), config);
+        assert_eq!(dual_module.effective_config()["verbose"], json!(true));
+        assert_eq!(dual_module.units.len(), 3);
+    }
+
+    #[test]
+    fn dual_module_parallel_snapshot_partition_overlay_1() {
+        // cargo test dual_module_parallel_snapshot_partition_overlay_1 -- --nocapture
+        let code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut partition_config = PartitionConfig::new(initializer.vertex_num);
+        partition_config.partitions = vec![
+            VertexRange::new(0, 72),   // unit 0
+            VertexRange::new(84, 132), // unit 1
+        ];
+        partition_config.fusions = vec![(0, 1)]; // unit 2
+        let partition_info = partition_config.info();
+        let mut dual_module = DualModuleParallel::<DualModuleSerial>::new_config(
+            &initializer,
+            &partition_info,
+            DualModuleParallelConfig::default(),
+        );
+        let snapshot = dual_module.snapshot(true);
+        assert_eq!(snapshot["vertices"][0]["pu"], json!(0));
+        assert_eq!(snapshot["vertices"][0]["fl"], json!(0));
+        assert_eq!(snapshot["vertices"][100]["pu"], json!(1));
+        // the interface vertices are mirrored in the leaf units before fusion
+        assert_eq!(snapshot["vertices"][72]["pu"], json!(2));
+        assert_eq!(snapshot["vertices"][72]["fl"], json!(1));
+        for edge in snapshot["edges"].as_array().unwrap().iter().filter(|edge| !edge.is_null()) {
+            let (left, right) = (edge["l"].as_u64().unwrap(), edge["r"].as_u64().unwrap());
+            let owner = |vertex_index: u64| match vertex_index {
+                0..=71 => 0,
+                72..=83 => 2,
+                _ => 1,
+            };
+            let expected = if owner(left) == owner(right) { owner(left) } else { 2 };
+            assert_eq!(edge["pu"], json!(expected));
+        }
+        let fusion_units = snapshot["fusion_units"].as_array().unwrap();
+        assert_eq!(fusion_units.len(), 3);
+        assert_eq!(fusion_units[0], json!({"a": 1, "e": 1, "fl": 0, "p": 2}));
+        assert_eq!(fusion_units[2], json!({"a": 0, "e": 0, "fl": 1, "p": null}));
+        // the fusion order is visible from the activity of the units
+        dual_module.static_fuse_all();
+        let snapshot = dual_module.snapshot(false);
+        assert_eq!(snapshot["vertices"][72]["partition_unit"], json!(2));
+        assert_eq!(snapshot["fusion_units"][0]["is_active"], json!(0));
+        assert_eq!(snapshot["fusion_units"][2]["is_active"], json!(1));
+        assert_eq!(snapshot["fusion_units"][2]["enabled"], json!(1));
+    }
 }
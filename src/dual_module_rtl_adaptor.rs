@@ -0,0 +1,558 @@
+//! RTL Adaptor
+//!
+//! A dual module that drives an external hardware simulator, so that the micro-architecture of a hardware dual module
+//! can be verified against the software primal module on real syndromes. The adaptor and the simulator exchange one
+//! line of text per message, similar to a DPI call: the adaptor sends a command and the simulator always answers with
+//! exactly one line. Dual nodes are referred to by their index, which is the same on both sides.
+//!
+//! Commands (adaptor to simulator), answered by `ok` unless noted:
+//! - `graph <vertex_num> <edge_num>`, followed by `edge <i> <j> <weight>` for each edge and `virtual <v>` for each
+//!   virtual vertex, and finally `end`
+//! - `clear`
+//! - `add_defect <node> <vertex>`
+//! - `add_blossom <node> <child>,<touching_a>,<touching_b> ...`
+//! - `remove_blossom <node>`
+//! - `set_speed <node> <grow|stay|shrink>`
+//! - `prepare_shrink <node> ...`
+//! - `grow <length>` and `grow_node <node> <length>`
+//! - `set_weight <edge> <weight>`
+//! - `find_obstacle`, answered by a list of obstacles separated by `;`, see [`encode_obstacle`]
+//! - `find_node_obstacle <node> <is_grow> <simultaneous_update>`, answered by a single obstacle
+//!
+//! [`RtlReferenceSimulator`] is a golden model speaking this protocol on top of [`DualModuleSerial`], and the hardware
+//! testbench can be checked against it line by line. The adaptor drives the whole decoding graph: driving only some
+//! of the units of [`crate::dual_module_parallel::DualModuleParallel`] would also require the sync events between units
+//! to be carried by the protocol
+//!
+
+use super::dual_module::*;
+use super::dual_module_serial::*;
+use super::pointers::*;
+use super::util::*;
+use crate::serde_json;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::num::NonZeroUsize;
+
+/// the transport of the text protocol
+pub trait RtlLink: Send {
+    /// send a command line (without the line break) and return the reply line
+    fn request(&mut self, command: &str) -> String;
+}
+
+/// a link over a byte stream, e.g. a socket to the simulator or the stdio of a simulator process
+pub struct RtlStreamLink<Reader: BufRead + Send, Writer: Write + Send> {
+    pub reader: Reader,
+    pub writer: Writer,
+}
+
+impl<Reader: BufRead + Send, Writer: Write + Send> RtlLink for RtlStreamLink<Reader, Writer> {
+    fn request(&mut self, command: &str) -> String {
+        writeln!(self.writer, "{command}").expect("RTL simulator disconnected");
+        self.writer.flush().expect("RTL simulator disconnected");
+        let mut reply = String::new();
+        self.reader.read_line(&mut reply).expect("RTL simulator disconnected");
+        assert!(!reply.is_empty(), "RTL simulator closed the link");
+        reply.trim_end().to_string()
+    }
+}
+
+impl RtlLink for RtlReferenceSimulator {
+    fn request(&mut self, command: &str) -> String {
+        self.execute(command)
+    }
+}
+
+pub struct DualModuleRtlAdaptor {
+    pub link: Box<dyn RtlLink>,
+    /// the dual nodes indexed by their index, to translate the obstacles reported by the simulator
+    pub nodes: Vec<Option<DualNodeWeak>>,
+    /// the number of commands sent to the simulator
+    pub command_count: usize,
+    /// an empty sync requests queue just to implement the trait
+    pub empty_sync_request: Vec<SyncRequest>,
+}
+
+impl DualModuleRtlAdaptor {
+    /// connect to a simulator listening on a socket
+    pub fn connect(initializer: &SolverInitializer, address: impl ToSocketAddrs) -> std::io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        let link = RtlStreamLink {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        };
+        Ok(Self::new_link(initializer, Box::new(link)))
+    }
+
+    /// load the decoding graph into the simulator behind the link
+    pub fn new_link(initializer: &SolverInitializer, link: Box<dyn RtlLink>) -> Self {
+        let mut adaptor = Self {
+            link,
+            nodes: vec![],
+            command_count: 0,
+            empty_sync_request: vec![],
+        };
+        adaptor.command(&format!(
+            "graph {} {}",
+            initializer.vertex_num,
+            initializer.weighted_edges.len()
+        ));
+        for &(i, j, weight) in initializer.weighted_edges.iter() {
+            adaptor.command(&format!("edge {i} {j} {weight}"));
+        }
+        for virtual_vertex in initializer.virtual_vertices.iter() {
+            adaptor.command(&format!("virtual {virtual_vertex}"));
+        }
+        adaptor.command("end");
+        adaptor
+    }
+
+    fn request(&mut self, command: &str) -> String {
+        self.command_count += 1;
+        self.link.request(command)
+    }
+
+    /// send a command that is expected to be acknowledged by `ok`
+    fn command(&mut self, command: &str) {
+        let reply = self.request(command);
+        assert_eq!(reply, "ok", "RTL simulator rejected `{command}`");
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn register_node(&mut self, dual_node_ptr: &DualNodePtr) -> NodeIndex {
+        let node_index = dual_node_ptr.read_recursive().index;
+        if self.nodes.len() <= node_index as usize {
+            self.nodes.resize(node_index as usize + 1, None);
+        }
+        self.nodes[node_index as usize] = Some(dual_node_ptr.downgrade());
+        node_index
+    }
+
+    fn get_node(&self, node_index: &str) -> DualNodePtr {
+        let node_index: usize = node_index.parse().expect("invalid node index from RTL simulator");
+        self.nodes[node_index]
+            .as_ref()
+            .expect("unknown node index from RTL simulator")
+            .upgrade_force()
+    }
+
+    fn decode_obstacle(&self, obstacle: &str) -> MaxUpdateLength {
+        let fields: Vec<&str> = obstacle.split_whitespace().collect();
+        match fields.as_slice() {
+            ["grow", length, has_empty_boundary_node] => MaxUpdateLength::NonZeroGrow((
+                length.parse().expect("invalid length from RTL simulator"),
+                *has_empty_boundary_node == "1",
+            )),
+            ["conflict", node_1, touching_1, node_2, touching_2] => MaxUpdateLength::Conflicting(
+                (self.get_node(node_1), self.get_node(touching_1)),
+                (self.get_node(node_2), self.get_node(touching_2)),
+            ),
+            ["virtual", node, touching, virtual_vertex, is_mirror] => MaxUpdateLength::TouchingVirtual(
+                (self.get_node(node), self.get_node(touching)),
+                (
+                    virtual_vertex.parse().expect("invalid vertex from RTL simulator"),
+                    *is_mirror == "1",
+                ),
+            ),
+            ["expand", node] => MaxUpdateLength::BlossomNeedExpand(self.get_node(node)),
+            ["shrink_stop", node] => MaxUpdateLength::VertexShrinkStop((self.get_node(node), None)),
+            _ => panic!("invalid obstacle from RTL simulator: `{obstacle}`"),
+        }
+    }
+}
+
+impl DualModuleImpl for DualModuleRtlAdaptor {
+    /// drive the in-process reference simulator; use [`DualModuleRtlAdaptor::connect`] for an external one
+    fn new_empty(initializer: &SolverInitializer) -> Self {
+        Self::new_link(initializer, Box::new(RtlReferenceSimulator::new()))
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.command("clear");
+    }
+
+    fn add_dual_node(&mut self, dual_node_ptr: &DualNodePtr) {
+        let node_index = self.register_node(dual_node_ptr);
+        let command = match &dual_node_ptr.read_recursive().class {
+            DualNodeClass::DefectVertex { defect_index } => format!("add_defect {node_index} {defect_index}"),
+            DualNodeClass::Blossom {
+                nodes_circle,
+                touching_children,
+            } => {
+                let mut command = format!("add_blossom {node_index}");
+                for (child_weak, (touching_a, touching_b)) in nodes_circle.iter().zip(touching_children.iter()) {
+                    command.push_str(&format!(
+                        " {},{},{}",
+                        child_weak.upgrade_force().read_recursive().index,
+                        touching_a.upgrade_force().read_recursive().index,
+                        touching_b.upgrade_force().read_recursive().index
+                    ));
+                }
+                command
+            }
+        };
+        self.command(&command);
+    }
+
+    fn remove_blossom(&mut self, dual_node_ptr: DualNodePtr) {
+        let node_index = dual_node_ptr.read_recursive().index;
+        self.command(&format!("remove_blossom {node_index}"));
+    }
+
+    fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
+        let node_index = dual_node_ptr.read_recursive().index;
+        self.command(&format!("set_speed {node_index} {}", encode_grow_state(grow_state)));
+    }
+
+    fn compute_maximum_update_length_dual_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        is_grow: bool,
+        simultaneous_update: bool,
+    ) -> MaxUpdateLength {
+        let node_index = dual_node_ptr.read_recursive().index;
+        let reply = self.request(&format!(
+            "find_node_obstacle {node_index} {} {}",
+            is_grow as u8, simultaneous_update as u8
+        ));
+        self.decode_obstacle(&reply)
+    }
+
+    fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+        let reply = self.request("find_obstacle");
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        for obstacle in reply.split(';') {
+            group_max_update_length.add(self.decode_obstacle(obstacle));
+        }
+        group_max_update_length
+    }
+
+    fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) {
+        let node_index = dual_node_ptr.read_recursive().index;
+        self.command(&format!("grow_node {node_index} {length}"));
+    }
+
+    fn grow(&mut self, length: Weight) {
+        self.command(&format!("grow {length}"));
+    }
+
+    fn load_edge_modifier(&mut self, edge_modifier: &[(EdgeIndex, Weight)]) {
+        for (edge_index, weight) in edge_modifier.iter() {
+            self.command(&format!("set_weight {edge_index} {weight}"));
+        }
+    }
+
+    fn prepare_nodes_shrink(&mut self, nodes_circle: &[DualNodePtr]) -> &mut Vec<SyncRequest> {
+        let mut command = "prepare_shrink".to_string();
+        for node_ptr in nodes_circle.iter() {
+            command.push_str(&format!(" {}", node_ptr.read_recursive().index));
+        }
+        self.command(&command);
+        // the simulator holds the whole graph, so there is nothing to sync
+        &mut self.empty_sync_request
+    }
+
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "command_count": self.command_count,
+        })
+    }
+}
+
+pub fn encode_grow_state(grow_state: DualNodeGrowState) -> &'static str {
+    match grow_state {
+        DualNodeGrowState::Grow => "grow",
+        DualNodeGrowState::Stay => "stay",
+        DualNodeGrowState::Shrink => "shrink",
+    }
+}
+
+pub fn decode_grow_state(grow_state: &str) -> DualNodeGrowState {
+    match grow_state {
+        "grow" => DualNodeGrowState::Grow,
+        "stay" => DualNodeGrowState::Stay,
+        "shrink" => DualNodeGrowState::Shrink,
+        _ => panic!("invalid grow state `{grow_state}`"),
+    }
+}
+
+/// encode an obstacle in the text protocol: `grow <length> <has_empty_boundary_node>`,
+/// `conflict <node_1> <touching_1> <node_2> <touching_2>`, `virtual <node> <touching> <vertex> <is_mirror>`,
+/// `expand <node>` or `shrink_stop <node>`
+pub fn encode_obstacle(max_update_length: &MaxUpdateLength) -> String {
+    let index = |node_ptr: &DualNodePtr| node_ptr.read_recursive().index;
+    match max_update_length {
+        MaxUpdateLength::NonZeroGrow((length, has_empty_boundary_node)) => {
+            format!("grow {length} {}", *has_empty_boundary_node as u8)
+        }
+        MaxUpdateLength::Conflicting((node_1, touching_1), (node_2, touching_2)) => format!(
+            "conflict {} {} {} {}",
+            index(node_1),
+            index(touching_1),
+            index(node_2),
+            index(touching_2)
+        ),
+        MaxUpdateLength::TouchingVirtual((node, touching), (virtual_vertex, is_mirror)) => format!(
+            "virtual {} {} {virtual_vertex} {}",
+            index(node),
+            index(touching),
+            *is_mirror as u8
+        ),
+        MaxUpdateLength::BlossomNeedExpand(node) => format!("expand {}", index(node)),
+        MaxUpdateLength::VertexShrinkStop((node, _)) => format!("shrink_stop {}", index(node)),
+    }
+}
+
+/// a golden model of the hardware dual module: it keeps its own copy of the dual nodes, built only from the commands
+/// it receives, and runs them on a [`DualModuleSerial`]
+pub struct RtlReferenceSimulator {
+    /// the graph being loaded by `graph`, until `end`
+    pub initializer: Option<SolverInitializer>,
+    pub dual_module: Option<DualModuleSerial>,
+    pub nodes: Vec<Option<DualNodePtr>>,
+    /// the owner of the copied dual nodes
+    pub interface_ptr: DualModuleInterfacePtr,
+}
+
+impl Default for RtlReferenceSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RtlReferenceSimulator {
+    pub fn new() -> Self {
+        Self {
+            initializer: None,
+            dual_module: None,
+            nodes: vec![],
+            interface_ptr: DualModuleInterfacePtr::new_empty(),
+        }
+    }
+
+    /// serve the text protocol over a byte stream until it's closed
+    pub fn serve(&mut self, reader: impl BufRead, writer: impl Write) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        for line in reader.lines() {
+            let reply = self.execute(&line?);
+            writeln!(writer, "{reply}")?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn node(&self, node_index: &str) -> DualNodePtr {
+        self.nodes[node_index.parse::<usize>().unwrap()].clone().unwrap()
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn new_node(&mut self, node_index: NodeIndex, class: DualNodeClass, defect_size: NonZeroUsize) -> DualNodePtr {
+        let node_ptr = DualNodePtr::new_value(DualNode {
+            index: node_index,
+            class,
+            grow_state: DualNodeGrowState::Grow,
+            parent_blossom: None,
+            dual_variable_cache: (0, 0),
+            belonging: self.interface_ptr.downgrade(),
+            defect_size,
+        });
+        if self.nodes.len() <= node_index as usize {
+            self.nodes.resize(node_index as usize + 1, None);
+        }
+        self.nodes[node_index as usize] = Some(node_ptr.clone());
+        node_ptr
+    }
+
+    /// execute a command and return the reply
+    pub fn execute(&mut self, command: &str) -> String {
+        let fields: Vec<&str> = command.split_whitespace().collect();
+        if let Some(initializer) = self.initializer.as_mut() {
+            match fields.as_slice() {
+                ["edge", i, j, weight] => {
+                    initializer
+                        .weighted_edges
+                        .push((i.parse().unwrap(), j.parse().unwrap(), weight.parse().unwrap()));
+                }
+                ["virtual", virtual_vertex] => initializer.virtual_vertices.push(virtual_vertex.parse().unwrap()),
+                ["end"] => {
+                    let initializer = self.initializer.take().unwrap();
+                    self.dual_module = Some(DualModuleSerial::new_empty(&initializer));
+                }
+                _ => return format!("error: unexpected `{command}` while loading the graph"),
+            }
+            return "ok".to_string();
+        }
+        if let ["graph", vertex_num, _] = fields.as_slice() {
+            self.initializer = Some(SolverInitializer::new(vertex_num.parse().unwrap(), vec![], vec![]));
+            self.dual_module = None;
+            self.nodes.clear();
+            return "ok".to_string();
+        }
+        if self.dual_module.is_none() {
+            return "error: the graph is not loaded".to_string();
+        }
+        match fields.as_slice() {
+            ["clear"] => {
+                self.dual_module.as_mut().unwrap().clear();
+                self.nodes.clear();
+            }
+            ["add_defect", node_index, vertex] => {
+                let class = DualNodeClass::DefectVertex {
+                    defect_index: vertex.parse().unwrap(),
+                };
+                let node_ptr = self.new_node(node_index.parse().unwrap(), class, NonZeroUsize::new(1).unwrap());
+                self.dual_module.as_mut().unwrap().add_defect_node(&node_ptr);
+            }
+            ["add_blossom", node_index, children @ ..] => {
+                let mut nodes_circle = vec![];
+                let mut touching_children = vec![];
+                let mut defect_size = 0;
+                for child in children.iter() {
+                    let indices: Vec<&str> = child.split(',').collect();
+                    let child_ptr = self.node(indices[0]);
+                    defect_size += child_ptr.read_recursive().defect_size.get();
+                    nodes_circle.push(child_ptr.downgrade());
+                    touching_children.push((self.node(indices[1]).downgrade(), self.node(indices[2]).downgrade()));
+                }
+                let class = DualNodeClass::Blossom {
+                    nodes_circle: nodes_circle.clone(),
+                    touching_children,
+                };
+                let blossom_ptr = self.new_node(node_index.parse().unwrap(), class, NonZeroUsize::new(defect_size).unwrap());
+                for child_weak in nodes_circle.iter() {
+                    child_weak.upgrade_force().write().parent_blossom = Some(blossom_ptr.downgrade());
+                }
+                self.dual_module.as_mut().unwrap().add_blossom(&blossom_ptr);
+            }
+            ["remove_blossom", node_index] => {
+                let blossom_ptr = self.node(node_index);
+                self.dual_module.as_mut().unwrap().remove_blossom(blossom_ptr.clone());
+                let blossom = blossom_ptr.read_recursive();
+                if let DualNodeClass::Blossom { nodes_circle, .. } = &blossom.class {
+                    for child_weak in nodes_circle.iter() {
+                        child_weak.upgrade_force().write().parent_blossom = None;
+                    }
+                }
+            }
+            ["set_speed", node_index, grow_state] => {
+                let node_ptr = self.node(node_index);
+                let grow_state = decode_grow_state(grow_state);
+                self.dual_module.as_mut().unwrap().set_grow_state(&node_ptr, grow_state);
+                node_ptr.write().grow_state = grow_state;
+            }
+            ["prepare_shrink", node_indices @ ..] => {
+                let nodes_circle: Vec<_> = node_indices.iter().map(|node_index| self.node(node_index)).collect();
+                self.dual_module.as_mut().unwrap().prepare_nodes_shrink(&nodes_circle);
+            }
+            ["grow", length] => self.dual_module.as_mut().unwrap().grow(length.parse().unwrap()),
+            ["grow_node", node_index, length] => {
+                let node_ptr = self.node(node_index);
+                self.dual_module
+                    .as_mut()
+                    .unwrap()
+                    .grow_dual_node(&node_ptr, length.parse().unwrap());
+            }
+            ["set_weight", edge_index, weight] => {
+                let edge_modifier = [(edge_index.parse().unwrap(), weight.parse().unwrap())];
+                self.dual_module.as_mut().unwrap().load_edge_modifier(&edge_modifier);
+            }
+            ["find_obstacle"] => {
+                return match self.dual_module.as_mut().unwrap().compute_maximum_update_length() {
+                    GroupMaxUpdateLength::NonZeroGrow((length, has_empty_boundary_node)) => {
+                        encode_obstacle(&MaxUpdateLength::NonZeroGrow((length, has_empty_boundary_node)))
+                    }
+                    GroupMaxUpdateLength::Conflicts((list, pending_stops, _)) => list
+                        .iter()
+                        .chain(pending_stops.values())
+                        .map(encode_obstacle)
+                        .collect::<Vec<_>>()
+                        .join(";"),
+                };
+            }
+            ["find_node_obstacle", node_index, is_grow, simultaneous_update] => {
+                let node_ptr = self.node(node_index);
+                let max_update_length = self.dual_module.as_mut().unwrap().compute_maximum_update_length_dual_node(
+                    &node_ptr,
+                    *is_grow == "1",
+                    *simultaneous_update == "1",
+                );
+                return encode_obstacle(&max_update_length);
+            }
+            _ => return format!("error: unknown command `{command}`"),
+        }
+        "ok".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::primal_module::*;
+    use super::super::primal_module_serial::*;
+    use super::*;
+    use std::net::TcpListener;
+
+    fn rtl_adaptor_compare(code: &mut impl ExampleCode, adaptor: DualModuleRtlAdaptor, rounds: u64) {
+        let initializer = code.get_initializer();
+        let mut adaptor = adaptor;
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+        for seed in 0..rounds {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let interface_ptr = DualModuleInterfacePtr::new_empty();
+            primal_module.solve(&interface_ptr, &syndrome_pattern, &mut dual_module);
+            let expected = interface_ptr.sum_dual_variables();
+            primal_module.clear();
+            dual_module.clear();
+            let interface_ptr = DualModuleInterfacePtr::new_empty();
+            primal_module.solve(&interface_ptr, &syndrome_pattern, &mut adaptor);
+            assert_eq!(interface_ptr.sum_dual_variables(), expected, "seed {seed}");
+            primal_module.clear();
+            adaptor.clear();
+        }
+        assert!(adaptor.command_count > 0);
+    }
+
+    #[test]
+    fn dual_module_rtl_adaptor_reference_1() {
+        // cargo test dual_module_rtl_adaptor_reference_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let adaptor = DualModuleRtlAdaptor::new_empty(&code.get_initializer());
+        rtl_adaptor_compare(&mut code, adaptor, 50);
+    }
+
+    #[test]
+    fn dual_module_rtl_adaptor_socket_1() {
+        // cargo test dual_module_rtl_adaptor_socket_1 -- --nocapture
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            stream.set_nodelay(true).unwrap();
+            let reader = BufReader::new(stream.try_clone().unwrap());
+            RtlReferenceSimulator::new().serve(reader, stream).unwrap();
+        });
+        let mut code = PhenomenologicalPlanarCode::new(5, 5, 0.02, 500);
+        let adaptor = DualModuleRtlAdaptor::connect(&code.get_initializer(), address).unwrap();
+        rtl_adaptor_compare(&mut code, adaptor, 20);
+    }
+
+    #[test]
+    fn dual_module_rtl_adaptor_protocol_1() {
+        // cargo test dual_module_rtl_adaptor_protocol_1 -- --nocapture
+        let mut simulator = RtlReferenceSimulator::new();
+        assert!(simulator.execute("grow 1").starts_with("error"));
+        for command in ["graph 3 2", "edge 0 1 4", "edge 1 2 4", "virtual 2", "end", "add_defect 0 0"] {
+            assert_eq!(simulator.execute(command), "ok");
+        }
+        assert_eq!(simulator.execute("find_obstacle"), "grow 4 0");
+        assert_eq!(simulator.execute("grow 4"), "ok");
+        assert_eq!(simulator.execute("find_obstacle"), "grow 4 0");
+        assert_eq!(simulator.execute("grow 4"), "ok");
+        assert_eq!(simulator.execute("find_obstacle"), "virtual 0 0 2 0");
+        assert_eq!(simulator.execute("set_speed 0 stay"), "ok");
+        assert!(simulator.execute("unknown").starts_with("error"));
+    }
+}
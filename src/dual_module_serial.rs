@@ -9,13 +9,15 @@
 //!
 
 #![cfg_attr(feature = "unsafe_pointer", allow(dropping_references))]
+#[cfg(feature = "track_allocations")]
+use super::allocation_tracker::*;
 use super::dual_module::*;
 use super::pointers::*;
 use super::util::*;
 use super::visualize::*;
 use crate::derivative::Derivative;
 use crate::weak_table::PtrWeakKeyHashMap;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub struct DualModuleSerial {
     /// all vertices including virtual ones
@@ -24,8 +26,13 @@ pub struct DualModuleSerial {
     pub nodes: Vec<Option<DualNodeInternalPtr>>,
     /// current nodes length, to enable constant-time clear operation
     pub nodes_length: usize,
+    /// recycles the removed blossom nodes across rounds
+    pub node_pool: ObjectPool<DualNodeInternalPtr>,
     /// keep edges, which can also be accessed in [`Self::vertices`]
     pub edges: Vec<EdgePtr>,
+    /// the indices in [`Self::edges`] of the neighbor edges of all vertices in compressed sparse row (CSR) layout, indexed by
+    /// [`Vertex::edges_range`]
+    pub vertex_edges: Vec<EdgeIndex>,
     /// current timestamp
     pub active_timestamp: FastClearTimestamp,
     /// the number of all vertices (including those partitioned into other serial modules)
@@ -51,6 +58,17 @@ pub struct DualModuleSerial {
     updated_boundary: Vec<(bool, EdgeWeak)>,
     /// temporary variable to reduce reallocation
     propagating_vertices: Vec<(VertexWeak, Option<DualNodeInternalWeak>)>,
+    /// per-call latency histograms of grow and [`DualModuleImpl::compute_maximum_update_length`], see [`PhaseProfiler`]
+    pub phase_profiler: PhaseProfiler,
+}
+
+/// the part of the decoding graph covered by some dual nodes, see [`DualModuleSerial::grown_region`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GrownRegion {
+    /// the vertices inside the regions
+    pub vertices: Vec<VertexIndex>,
+    /// the edges grown from either side, as `(edge_index, growth, weight)` where the growth sums up both sides
+    pub edges: Vec<(EdgeIndex, Weight, Weight)>,
 }
 
 /// records information only available when used as a unit in the partitioned dual module
@@ -65,6 +83,8 @@ pub struct UnitModuleInfo {
     pub owning_dual_range: NodeRange,
     /// hash table for mapping [`DualNodePtr`] to internal [`DualNodeInternalPtr`]
     pub dual_node_pointers: PtrWeakKeyHashMap<DualNodeWeak, usize>,
+    /// all edges in this module mapping from their global index to the local index in [`DualModuleSerial::edges`]
+    pub local_edge_indices: HashMap<EdgeIndex, EdgeIndex>,
 }
 
 pub type DualModuleSerialPtr = ArcManualSafeLock<DualModuleSerial>;
@@ -117,9 +137,9 @@ pub struct Vertex {
     pub is_defect: bool,
     /// if it's a mirrored vertex (present on multiple units), then this is the parallel unit that exclusively owns it
     pub mirror_unit: Option<PartitionUnitWeak>,
-    /// all neighbor edges, in surface code this should be constant number of edges
+    /// the range of neighbor edges in [`DualModuleSerial::vertex_edges`], in surface code this should be constant number of edges
     #[derivative(Debug = "ignore")]
-    pub edges: Vec<EdgeWeak>,
+    pub edges_range: (usize, usize),
     /// propagated dual node
     pub propagated_dual_node: Option<DualNodeInternalWeak>,
     /// propagated grandson node: must be a syndrome node
@@ -128,6 +148,14 @@ pub struct Vertex {
     pub timestamp: FastClearTimestamp,
 }
 
+impl Vertex {
+    /// the neighbor edges of this vertex, given [`DualModuleSerial::vertex_edges`] of the module it belongs to
+    #[inline]
+    pub fn edges<'a>(&self, vertex_edges: &'a [EdgeIndex]) -> &'a [EdgeIndex] {
+        &vertex_edges[self.edges_range.0..self.edges_range.1]
+    }
+}
+
 pub type VertexPtr = FastClearArcManualSafeLockDangerous<Vertex>;
 pub type VertexWeak = FastClearWeakManualSafeLockDangerous<Vertex>;
 
@@ -163,6 +191,11 @@ pub struct Edge {
     pub left_growth: Weight,
     /// growth from the right point
     pub right_growth: Weight,
+    /// the growth of `left_growth` per unit of dual variable growing from the left point, 1 by default;
+    /// direction-dependent costs are needed by some biased-noise decoding graphs, see [`DualModuleSerial::set_growth_cost`]
+    pub left_growth_cost: Weight,
+    /// the growth of `right_growth` per unit of dual variable growing from the right point, 1 by default
+    pub right_growth_cost: Weight,
     /// left active tree node (if applicable)
     pub left_dual_node: Option<DualNodeInternalWeak>,
     /// left grandson node: must be a syndrome node
@@ -180,6 +213,17 @@ pub struct Edge {
 pub type EdgePtr = FastClearArcManualSafeLockDangerous<Edge>;
 pub type EdgeWeak = FastClearWeakManualSafeLockDangerous<Edge>;
 
+impl Edge {
+    /// the fraction of this edge covered by dual nodes from both sides, in `[0, 1]`; a zero-weight edge is always fully grown
+    pub fn growth_fraction(&self) -> f64 {
+        if self.weight == 0 {
+            1.
+        } else {
+            (self.left_growth + self.right_growth) as f64 / self.weight as f64
+        }
+    }
+}
+
 impl std::fmt::Debug for EdgePtr {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let edge = self.read_recursive_force();
@@ -199,6 +243,9 @@ impl DualModuleImpl for DualModuleSerial {
     /// initialize the dual module, which is supposed to be reused for multiple decoding tasks with the same structure
     #[allow(clippy::unnecessary_cast)]
     fn new_empty(initializer: &SolverInitializer) -> Self {
+        #[cfg(feature = "track_allocations")]
+        let _allocation_scope = AllocationScope::enter(AllocationModule::Dual);
+        let initializer = &*initializer.expand_weighted_virtual_vertices();
         let active_timestamp = 0;
         // create vertices
         let vertices: Vec<VertexPtr> = (0..initializer.vertex_num)
@@ -208,7 +255,7 @@ impl DualModuleImpl for DualModuleSerial {
                     is_virtual: false,
                     is_defect: false,
                     mirror_unit: None,
-                    edges: Vec::new(),
+                    edges_range: (0, 0),
                     propagated_dual_node: None,
                     propagated_grandson_dual_node: None,
                     timestamp: active_timestamp,
@@ -222,6 +269,7 @@ impl DualModuleImpl for DualModuleSerial {
         }
         // set edges
         let mut edges = Vec::<EdgePtr>::new();
+        let mut edge_endpoints = Vec::with_capacity(initializer.weighted_edges.len());
         for &(i, j, weight) in initializer.weighted_edges.iter() {
             assert_ne!(i, j, "invalid edge from and to the same vertex {}", i);
             assert!(
@@ -254,6 +302,8 @@ impl DualModuleImpl for DualModuleSerial {
                 right: vertices[right as usize].downgrade(),
                 left_growth: 0,
                 right_growth: 0,
+                left_growth_cost: 1,
+                right_growth_cost: 1,
                 left_dual_node: None,
                 left_grandson_dual_node: None,
                 right_dual_node: None,
@@ -261,31 +311,17 @@ impl DualModuleImpl for DualModuleSerial {
                 timestamp: 0,
                 dedup_timestamp: (0, 0),
             });
-            for (a, b) in [(i, j), (j, i)] {
-                lock_write!(vertex, vertices[a as usize], active_timestamp);
-                debug_assert!({
-                    // O(N^2) sanity check, debug mode only (actually this bug is not critical, only the shorter edge will take effect)
-                    let mut no_duplicate = true;
-                    for edge_weak in vertex.edges.iter() {
-                        let edge_ptr = edge_weak.upgrade_force();
-                        let edge = edge_ptr.read_recursive(active_timestamp);
-                        if edge.left == vertices[b as usize].downgrade() || edge.right == vertices[b as usize].downgrade() {
-                            no_duplicate = false;
-                            eprintln!("duplicated edge between {} and {} with weight w1 = {} and w2 = {}, consider merge them into a single edge", i, j, weight, edge.weight);
-                            break;
-                        }
-                    }
-                    no_duplicate
-                });
-                vertex.edges.push(edge_ptr.downgrade());
-            }
+            edge_endpoints.push((i as usize, j as usize));
             edges.push(edge_ptr);
         }
+        let vertex_edges = Self::build_vertex_edges(&vertices, &edges, &edge_endpoints);
         Self {
             vertices,
             nodes: vec![],
             nodes_length: 0,
+            node_pool: ObjectPool::new(),
             edges,
+            vertex_edges,
             active_timestamp: 0,
             vertex_num: initializer.vertex_num,
             edge_num: initializer.weighted_edges.len(),
@@ -298,6 +334,7 @@ impl DualModuleImpl for DualModuleSerial {
             sync_requests: vec![],
             updated_boundary: vec![],
             propagating_vertices: vec![],
+            phase_profiler: PhaseProfiler::new(),
         }
     }
 
@@ -313,6 +350,7 @@ impl DualModuleImpl for DualModuleSerial {
         }
         self.clear_graph();
         self.nodes_length = 0; // without actually dropping all the nodes, to enable constant time clear
+        self.node_pool.clear();
         if let Some(unit_module_info) = self.unit_module_info.as_mut() {
             unit_module_info.owning_dual_range = VertexRange::new(0, 0);
             unit_module_info.dual_node_pointers = PtrWeakKeyHashMap::<DualNodeWeak, usize>::new();
@@ -327,27 +365,7 @@ impl DualModuleImpl for DualModuleSerial {
         let active_timestamp = self.active_timestamp;
         let node = dual_node_ptr.read_recursive();
         let node_index = self.nodes_length as NodeIndex;
-        let node_internal_ptr = if node_index < self.nodes.len() as NodeIndex && self.nodes[node_index as usize].is_some() {
-            let node_ptr = self.nodes[node_index as usize].take().unwrap();
-            let mut node = node_ptr.write();
-            node.origin = dual_node_ptr.downgrade();
-            node.index = node_index;
-            node.dual_variable = 0;
-            node.boundary.clear();
-            node.overgrown_stack.clear();
-            node.last_visit_cycle = 0;
-            drop(node);
-            node_ptr
-        } else {
-            DualNodeInternalPtr::new_value(DualNodeInternal {
-                origin: dual_node_ptr.downgrade(),
-                index: node_index,
-                dual_variable: 0,
-                boundary: Vec::new(),
-                overgrown_stack: Vec::new(),
-                last_visit_cycle: 0,
-            })
-        };
+        let node_internal_ptr = self.acquire_dual_node_internal(dual_node_ptr, node_index, 0);
         {
             let boundary = &mut node_internal_ptr.write().boundary;
             match &node.class {
@@ -406,8 +424,8 @@ impl DualModuleImpl for DualModuleSerial {
                     vertex.propagated_dual_node = Some(node_internal_ptr.downgrade());
                     vertex.propagated_grandson_dual_node = Some(node_internal_ptr.downgrade());
                     vertex.is_defect = true;
-                    for edge_weak in vertex.edges.iter() {
-                        let edge_ptr = edge_weak.upgrade_force();
+                    for &edge_index in vertex.edges(&self.vertex_edges).iter() {
+                        let edge_ptr = &self.edges[edge_index as usize];
                         edge_ptr.dynamic_clear(active_timestamp);
                         let mut edge = edge_ptr.write(active_timestamp);
                         let is_left = vertex_ptr.downgrade() == edge.left;
@@ -426,7 +444,7 @@ impl DualModuleImpl for DualModuleSerial {
                             edge.right_dual_node = Some(node_internal_ptr.downgrade());
                             edge.right_grandson_dual_node = Some(node_internal_ptr.downgrade());
                         }
-                        boundary.push((is_left, edge_weak.clone()));
+                        boundary.push((is_left, edge_ptr.downgrade()));
                     }
                 }
             }
@@ -511,7 +529,9 @@ impl DualModuleImpl for DualModuleSerial {
         } else {
             unreachable!()
         }
-        self.nodes[node_idx as usize] = None; // simply remove this blossom node
+        drop(dual_node_internal);
+        let dual_node_internal_ptr = self.nodes[node_idx as usize].take().unwrap(); // simply remove this blossom node
+        self.node_pool.release(dual_node_internal_ptr);
     }
 
     fn set_grow_state(&mut self, dual_node_ptr: &DualNodePtr, grow_state: DualNodeGrowState) {
@@ -522,223 +542,22 @@ impl DualModuleImpl for DualModuleSerial {
         }
     }
 
-    #[allow(clippy::collapsible_else_if)]
     fn compute_maximum_update_length_dual_node(
         &mut self,
         dual_node_ptr: &DualNodePtr,
         is_grow: bool,
         simultaneous_update: bool,
     ) -> MaxUpdateLength {
-        let active_timestamp = self.active_timestamp;
         if !simultaneous_update {
             // when `simultaneous_update` is set, it's assumed that all nodes are prepared to grow or shrink
             // this is because if we dynamically prepare them, it would be inefficient
             self.prepare_dual_node_growth(dual_node_ptr, is_grow);
         }
-        let mut max_length_abs = Weight::MAX;
-        let dual_node_internal_ptr = self.get_dual_node_internal_ptr(dual_node_ptr);
-        let dual_node_internal = dual_node_internal_ptr.read_recursive();
-        if !is_grow {
-            if dual_node_internal.dual_variable == 0 {
-                let dual_node = dual_node_ptr.read_recursive();
-                match dual_node.class {
-                    DualNodeClass::Blossom { .. } => return MaxUpdateLength::BlossomNeedExpand(dual_node_ptr.clone()),
-                    DualNodeClass::DefectVertex { defect_index } => {
-                        // try to report Conflicting event or give a VertexShrinkStop with potential conflicting node
-                        if let Some(vertex_index) = self.get_vertex_index(defect_index) {
-                            // since propagated node is never removed, this event could happen with no vertex
-                            let vertex_ptr = &self.vertices[vertex_index];
-                            let vertex = vertex_ptr.read_recursive(active_timestamp);
-                            let mut potential_conflict: Option<(DualNodePtr, DualNodePtr)> = None;
-                            for edge_weak in vertex.edges.iter() {
-                                let edge_ptr = edge_weak.upgrade_force();
-                                let edge = edge_ptr.read_recursive(active_timestamp);
-                                let is_left = vertex_ptr.downgrade() == edge.left;
-                                let remaining_length = edge.weight - edge.left_growth - edge.right_growth;
-                                if remaining_length == 0 {
-                                    let peer_dual_node = if is_left {
-                                        &edge.right_dual_node
-                                    } else {
-                                        &edge.left_dual_node
-                                    };
-                                    if let Some(peer_dual_node_ptr) = peer_dual_node {
-                                        let peer_grandson_dual_node = if is_left {
-                                            &edge.right_grandson_dual_node
-                                        } else {
-                                            &edge.left_grandson_dual_node
-                                        };
-                                        let peer_dual_node_ptr =
-                                            peer_dual_node_ptr.upgrade_force().read_recursive().origin.upgrade_force();
-                                        let peer_grandson_dual_node_ptr = peer_grandson_dual_node
-                                            .as_ref()
-                                            .unwrap()
-                                            .upgrade_force()
-                                            .read_recursive()
-                                            .origin
-                                            .upgrade_force();
-                                        if peer_dual_node_ptr.read_recursive().grow_state == DualNodeGrowState::Grow {
-                                            if let Some((other_dual_node_ptr, other_grandson_dual_node)) =
-                                                &potential_conflict
-                                            {
-                                                if &peer_dual_node_ptr != other_dual_node_ptr {
-                                                    return MaxUpdateLength::Conflicting(
-                                                        (other_dual_node_ptr.clone(), other_grandson_dual_node.clone()),
-                                                        (peer_dual_node_ptr, peer_grandson_dual_node_ptr),
-                                                    );
-                                                }
-                                            } else {
-                                                potential_conflict = Some((peer_dual_node_ptr, peer_grandson_dual_node_ptr));
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            return MaxUpdateLength::VertexShrinkStop((dual_node_ptr.clone(), potential_conflict));
-                        } else {
-                            return MaxUpdateLength::VertexShrinkStop((dual_node_ptr.clone(), None));
-                        }
-                    }
-                }
-            }
-            if !dual_node_internal.overgrown_stack.is_empty() {
-                let last_index = dual_node_internal.overgrown_stack.len() - 1;
-                let (_, overgrown) = &dual_node_internal.overgrown_stack[last_index];
-                max_length_abs = std::cmp::min(max_length_abs, *overgrown);
-            }
-            max_length_abs = std::cmp::min(max_length_abs, dual_node_internal.dual_variable);
-        }
-        for (is_left, edge_weak) in dual_node_internal.boundary.iter() {
-            let edge_ptr = edge_weak.upgrade_force();
-            let is_left = *is_left;
-            let edge = edge_ptr.read_recursive(active_timestamp);
-            if is_grow {
-                // first check if both side belongs to the same tree node, if so, no constraint on this edge
-                let peer_dual_node_internal_ptr: Option<DualNodeInternalPtr> = if is_left {
-                    edge.right_dual_node.as_ref().map(|ptr| ptr.upgrade_force())
-                } else {
-                    edge.left_dual_node.as_ref().map(|ptr| ptr.upgrade_force())
-                };
-                match peer_dual_node_internal_ptr {
-                    Some(peer_dual_node_internal_ptr) => {
-                        if peer_dual_node_internal_ptr == dual_node_internal_ptr {
-                            continue;
-                        } else {
-                            let peer_dual_node_internal = peer_dual_node_internal_ptr.read_recursive();
-                            let peer_dual_node_ptr = peer_dual_node_internal.origin.upgrade_force();
-                            let peer_dual_node = peer_dual_node_ptr.read_recursive();
-                            let remaining_length = edge.weight - edge.left_growth - edge.right_growth;
-                            let local_max_length_abs = match peer_dual_node.grow_state {
-                                DualNodeGrowState::Grow => {
-                                    debug_assert!(remaining_length % 2 == 0, "there is odd gap between two growing nodes, please make sure all weights are even numbers");
-                                    remaining_length / 2
-                                }
-                                DualNodeGrowState::Shrink => {
-                                    // Yue 2022.9.5: remove Conflicting event detection here, move it to the 0-dual syndrome node
-                                    continue;
-                                }
-                                DualNodeGrowState::Stay => remaining_length,
-                            };
-                            if local_max_length_abs == 0 {
-                                let peer_grandson_ptr = if is_left {
-                                    edge.right_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                } else {
-                                    edge.left_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                };
-                                let grandson_ptr = if is_left {
-                                    edge.left_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                } else {
-                                    edge.right_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                };
-                                return MaxUpdateLength::Conflicting(
-                                    (peer_dual_node_ptr.clone(), peer_grandson_ptr),
-                                    (dual_node_ptr.clone(), grandson_ptr),
-                                );
-                            }
-                            max_length_abs = std::cmp::min(max_length_abs, local_max_length_abs);
-                        }
-                    }
-                    None => {
-                        let local_max_length_abs = edge.weight - edge.left_growth - edge.right_growth;
-                        if local_max_length_abs == 0 {
-                            // check if peer is virtual node
-                            let peer_vertex_ptr = if is_left {
-                                edge.right.upgrade_force()
-                            } else {
-                                edge.left.upgrade_force()
-                            };
-                            let peer_vertex = peer_vertex_ptr.read_recursive(active_timestamp);
-                            if peer_vertex.is_virtual || peer_vertex.is_mirror_blocked() {
-                                let grandson_ptr = if is_left {
-                                    edge.left_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                } else {
-                                    edge.right_grandson_dual_node
-                                        .as_ref()
-                                        .map(|ptr| ptr.upgrade_force())
-                                        .unwrap()
-                                        .read_recursive()
-                                        .origin
-                                        .upgrade_force()
-                                };
-                                return MaxUpdateLength::TouchingVirtual(
-                                    (dual_node_ptr.clone(), grandson_ptr),
-                                    (peer_vertex.vertex_index, peer_vertex.is_mirror_blocked()),
-                                );
-                            } else {
-                                println!("edge: {edge_ptr:?}, peer_vertex_ptr: {peer_vertex_ptr:?}");
-                                unreachable!("this edge should've been removed from boundary because it's already fully grown, and it's peer vertex is not virtual")
-                            }
-                        }
-                        max_length_abs = std::cmp::min(max_length_abs, local_max_length_abs);
-                    }
-                }
-            } else {
-                if is_left {
-                    if edge.left_growth == 0 {
-                        unreachable!()
-                    }
-                    max_length_abs = std::cmp::min(max_length_abs, edge.left_growth);
-                } else {
-                    if edge.right_growth == 0 {
-                        unreachable!()
-                    }
-                    max_length_abs = std::cmp::min(max_length_abs, edge.right_growth);
-                }
-            }
-        }
-        MaxUpdateLength::NonZeroGrow((max_length_abs, dual_node_internal.boundary.is_empty()))
+        self.prepared_maximum_update_length_dual_node(dual_node_ptr, is_grow)
     }
 
     fn compute_maximum_update_length(&mut self) -> GroupMaxUpdateLength {
+        let profiler_start = self.phase_profiler.start();
         // first prepare all nodes for individual grow or shrink; Stay nodes will be prepared to shrink in order to minimize effect on others
         self.prepare_all();
         // after preparing all the growth, there should be no sync requests
@@ -746,26 +565,16 @@ impl DualModuleImpl for DualModuleSerial {
             self.sync_requests.is_empty(),
             "no sync requests should arise here; make sure to deal with all sync requests before growing"
         );
-        let mut group_max_update_length = GroupMaxUpdateLength::new();
-        for i in 0..self.active_list.len() {
-            let dual_node_ptr = {
-                let internal_dual_node_ptr = self.active_list[i].upgrade_force();
-                let dual_node_internal = internal_dual_node_ptr.read_recursive();
-                dual_node_internal.origin.upgrade_force()
-            };
-            let dual_node = dual_node_ptr.read_recursive();
-            let is_grow = match dual_node.grow_state {
-                DualNodeGrowState::Grow => true,
-                DualNodeGrowState::Shrink => false,
-                DualNodeGrowState::Stay => continue,
-            };
-            drop(dual_node); // unlock, otherwise it causes deadlock when updating the dual node
-            let max_update_length = self.compute_maximum_update_length_dual_node(&dual_node_ptr, is_grow, true);
-            group_max_update_length.add(max_update_length);
-        }
+        let group_max_update_length = self.prepared_maximum_update_length();
+        self.phase_profiler
+            .record(ProfilerPhase::ComputeMaximumUpdateLength, profiler_start);
         group_max_update_length
     }
 
+    fn query_prepared_maximum_update_length(&self) -> Option<GroupMaxUpdateLength> {
+        Some(self.prepared_maximum_update_length())
+    }
+
     fn grow_dual_node(&mut self, dual_node_ptr: &DualNodePtr, length: Weight) {
         let active_timestamp = self.active_timestamp;
         if length == 0 {
@@ -800,10 +609,10 @@ impl DualModuleImpl for DualModuleSerial {
                 // minimize writer lock acquisition
                 let mut edge = edge_ptr.write(active_timestamp);
                 if is_left {
-                    edge.left_growth += length;
+                    edge.left_growth += length * edge.left_growth_cost;
                     debug_assert!(edge.left_growth >= 0, "negative growth forbidden");
                 } else {
-                    edge.right_growth += length;
+                    edge.right_growth += length * edge.right_growth_cost;
                     debug_assert!(edge.right_growth >= 0, "negative growth forbidden");
                 }
                 (edge.left_growth + edge.right_growth, edge.weight)
@@ -845,6 +654,7 @@ impl DualModuleImpl for DualModuleSerial {
 
     fn grow(&mut self, length: Weight) {
         debug_assert!(length > 0, "only positive growth is supported");
+        let profiler_start = self.phase_profiler.start();
         self.renew_active_list();
         // first handle shrinks and then grow, to make sure they don't conflict
         for i in 0..self.active_list.len() {
@@ -870,6 +680,7 @@ impl DualModuleImpl for DualModuleSerial {
                 self.grow_dual_node(&dual_node_ptr, length);
             }
         }
+        self.phase_profiler.record(ProfilerPhase::Grow, profiler_start);
     }
 
     #[allow(clippy::unnecessary_cast)]
@@ -880,12 +691,20 @@ impl DualModuleImpl for DualModuleSerial {
         );
         let active_timestamp = self.active_timestamp;
         for (edge_index, target_weight) in edge_modifier.iter() {
-            let edge_ptr = &self.edges[*edge_index as usize];
+            // when partitioned, the edge modifier is given in global edge indices and may contain edges of other units
+            let local_edge_index = match self.unit_module_info.as_ref() {
+                Some(unit_module_info) => match unit_module_info.local_edge_indices.get(edge_index) {
+                    Some(local_edge_index) => *local_edge_index,
+                    None => continue,
+                },
+                None => *edge_index,
+            };
+            let edge_ptr = &self.edges[local_edge_index as usize];
             edge_ptr.dynamic_clear(active_timestamp); // may visit stale edges
             let mut edge = edge_ptr.write(active_timestamp);
             let original_weight = edge.weight;
             edge.weight = *target_weight;
-            self.edge_modifier.push_modified_edge(*edge_index, original_weight);
+            self.edge_modifier.push_modified_edge(local_edge_index, original_weight);
         }
     }
 
@@ -951,8 +770,17 @@ impl DualModuleImpl for DualModuleSerial {
         self.get_dual_node_index(dual_node_ptr).is_some()
     }
 
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "node_pool": self.node_pool.statistics,
+            "phases": self.phase_profiler.to_json(),
+        })
+    }
+
     #[allow(clippy::unnecessary_cast)]
     fn new_partitioned(partitioned_initializer: &PartitionedSolverInitializer) -> Self {
+        #[cfg(feature = "track_allocations")]
+        let _allocation_scope = AllocationScope::enter(AllocationModule::Dual);
         let active_timestamp = 0;
         // create vertices
         let mut vertices: Vec<VertexPtr> = partitioned_initializer
@@ -964,7 +792,7 @@ impl DualModuleImpl for DualModuleSerial {
                     is_virtual: false,
                     is_defect: false,
                     mirror_unit: partitioned_initializer.owning_interface.clone(),
-                    edges: Vec::new(),
+                    edges_range: (0, 0),
                     propagated_dual_node: None,
                     propagated_grandson_dual_node: None,
                     timestamp: active_timestamp,
@@ -987,7 +815,7 @@ impl DualModuleImpl for DualModuleSerial {
                     is_virtual: *is_virtual, // interface vertices are always virtual at the beginning
                     is_defect: false,
                     mirror_unit: Some(mirror_unit.clone()),
-                    edges: Vec::new(),
+                    edges_range: (0, 0),
                     propagated_dual_node: None,
                     propagated_grandson_dual_node: None,
                     timestamp: active_timestamp,
@@ -996,6 +824,8 @@ impl DualModuleImpl for DualModuleSerial {
         }
         // set edges
         let mut edges = Vec::<EdgePtr>::new();
+        let mut edge_endpoints = Vec::with_capacity(partitioned_initializer.weighted_edges.len());
+        let mut local_edge_indices = HashMap::<EdgeIndex, EdgeIndex>::new();
         for &(i, j, weight, edge_index) in partitioned_initializer.weighted_edges.iter() {
             assert_ne!(i, j, "invalid edge from and to the same vertex {}", i);
             assert!(
@@ -1038,6 +868,8 @@ impl DualModuleImpl for DualModuleSerial {
                 right: vertices[right_index as usize].downgrade(),
                 left_growth: 0,
                 right_growth: 0,
+                left_growth_cost: 1,
+                right_growth_cost: 1,
                 left_dual_node: None,
                 left_grandson_dual_node: None,
                 right_dual_node: None,
@@ -1045,31 +877,18 @@ impl DualModuleImpl for DualModuleSerial {
                 timestamp: 0,
                 dedup_timestamp: (0, 0),
             });
-            for (a, b) in [(left_index, right_index), (right_index, left_index)] {
-                lock_write!(vertex, vertices[a as usize], active_timestamp);
-                debug_assert!({
-                    // O(N^2) sanity check, debug mode only (actually this bug is not critical, only the shorter edge will take effect)
-                    let mut no_duplicate = true;
-                    for edge_weak in vertex.edges.iter() {
-                        let edge_ptr = edge_weak.upgrade_force();
-                        let edge = edge_ptr.read_recursive(active_timestamp);
-                        if edge.left == vertices[b as usize].downgrade() || edge.right == vertices[b as usize].downgrade() {
-                            no_duplicate = false;
-                            eprintln!("duplicated edge between {} and {} with weight w1 = {} and w2 = {}, consider merge them into a single edge", i, j, weight, edge.weight);
-                            break;
-                        }
-                    }
-                    no_duplicate
-                });
-                vertex.edges.push(edge_ptr.downgrade());
-            }
+            edge_endpoints.push((left_index as usize, right_index as usize));
+            local_edge_indices.insert(edge_index, edges.len() as EdgeIndex);
             edges.push(edge_ptr);
         }
+        let vertex_edges = Self::build_vertex_edges(&vertices, &edges, &edge_endpoints);
         Self {
             vertices,
             nodes: vec![],
             nodes_length: 0,
+            node_pool: ObjectPool::new(),
             edges,
+            vertex_edges,
             active_timestamp: 0,
             vertex_num: partitioned_initializer.vertex_num,
             edge_num: partitioned_initializer.edge_num,
@@ -1079,6 +898,7 @@ impl DualModuleImpl for DualModuleSerial {
                 mirrored_vertices,
                 owning_dual_range: VertexRange::new(0, 0),
                 dual_node_pointers: PtrWeakKeyHashMap::<DualNodeWeak, usize>::new(),
+                local_edge_indices,
             }),
             active_list: vec![],
             current_cycle: 0,
@@ -1087,6 +907,7 @@ impl DualModuleImpl for DualModuleSerial {
             sync_requests: vec![],
             updated_boundary: vec![],
             propagating_vertices: vec![],
+            phase_profiler: PhaseProfiler::new(),
         }
     }
 
@@ -1098,6 +919,7 @@ impl DualModuleImpl for DualModuleSerial {
         self.unit_module_info.as_mut().unwrap().owning_dual_range.bias_by(bias);
     }
 
+    #[allow(clippy::unnecessary_cast)]
     fn execute_sync_event(&mut self, sync_event: &SyncRequest) {
         let active_timestamp = self.active_timestamp;
         debug_assert!(self.contains_vertex(sync_event.vertex_index));
@@ -1177,8 +999,8 @@ impl DualModuleImpl for DualModuleSerial {
                     }
                 }
                 // iterate over the edges around the vertex to add edges to the boundary
-                for edge_weak in vertex.edges.iter() {
-                    let edge_ptr = edge_weak.upgrade_force();
+                for &edge_index in vertex.edges(&self.vertex_edges).iter() {
+                    let edge_ptr = &self.edges[edge_index as usize];
                     edge_ptr.dynamic_clear(active_timestamp);
                     let mut edge = edge_ptr.write(active_timestamp);
                     let is_left = vertex_ptr.downgrade() == edge.left;
@@ -1198,7 +1020,7 @@ impl DualModuleImpl for DualModuleSerial {
                             edge.right_dual_node = None;
                             edge.right_grandson_dual_node = None;
                         };
-                        updated_boundary.push((!is_left, edge_weak.clone()));
+                        updated_boundary.push((!is_left, edge_ptr.downgrade()));
                     }
                 }
                 // update the boundary
@@ -1211,8 +1033,8 @@ impl DualModuleImpl for DualModuleSerial {
                 vertex.propagated_dual_node = Some(dual_node_internal_ptr.downgrade());
                 vertex.propagated_grandson_dual_node = Some(grandson_dual_node_internal_ptr.downgrade());
                 lock_write!(dual_node_internal, dual_node_internal_ptr);
-                for edge_weak in vertex.edges.iter() {
-                    let edge_ptr = edge_weak.upgrade_force();
+                for &edge_index in vertex.edges(&self.vertex_edges).iter() {
+                    let edge_ptr = &self.edges[edge_index as usize];
                     edge_ptr.dynamic_clear(active_timestamp);
                     let mut edge = edge_ptr.write(active_timestamp);
                     let is_left = vertex_ptr.downgrade() == edge.left;
@@ -1231,7 +1053,7 @@ impl DualModuleImpl for DualModuleSerial {
                         edge.right_dual_node = Some(dual_node_internal_ptr.downgrade());
                         edge.right_grandson_dual_node = Some(grandson_dual_node_internal_ptr.downgrade());
                     }
-                    dual_node_internal.boundary.push((is_left, edge_weak.clone()));
+                    dual_node_internal.boundary.push((is_left, edge_ptr.downgrade()));
                 }
                 self.active_list.push(dual_node_internal_ptr.downgrade());
             }
@@ -1558,6 +1380,7 @@ impl FusionVisualizer for DualModuleSerial {
                 if abbrev { "r" } else { "right" }: edge.right.upgrade_force().read_recursive(active_timestamp).vertex_index,
                 if abbrev { "lg" } else { "left_growth" }: edge.left_growth,
                 if abbrev { "rg" } else { "right_growth" }: edge.right_growth,
+                if abbrev { "gf" } else { "growth_fraction" }: edge.growth_fraction(),
             });
             if let Some(value) = edge.left_dual_node.as_ref().map(|weak| {
                 weak.upgrade_force()
@@ -1696,21 +1519,403 @@ impl DualModuleSerial {
         }
     }
 
-    /// get the local index of a vertex, thus has usize type
+    /// build the compressed sparse row (CSR) adjacency given the local indices of the two vertices of each edge;
+    /// the local indices of the neighbor edges of each vertex are kept in the order of edges, and stored contiguously for
+    /// cache locality
     #[allow(clippy::unnecessary_cast)]
-    pub fn get_vertex_index(&self, vertex_index: VertexIndex) -> Option<usize> {
-        if self.owning_range.contains(vertex_index) {
-            return Some((vertex_index - self.owning_range.start()) as usize);
+    fn build_vertex_edges(vertices: &[VertexPtr], edges: &[EdgePtr], edge_endpoints: &[(usize, usize)]) -> Vec<EdgeIndex> {
+        let mut offsets = vec![0; vertices.len() + 1];
+        for &(a, b) in edge_endpoints.iter() {
+            offsets[a + 1] += 1;
+            offsets[b + 1] += 1;
         }
-        if let Some(unit_module_info) = self.unit_module_info.as_ref() {
-            if let Some(index) = unit_module_info.mirrored_vertices.get(&vertex_index) {
-                return Some(*index as usize);
+        for vertex_index in 0..vertices.len() {
+            offsets[vertex_index + 1] += offsets[vertex_index];
+        }
+        let mut filled = offsets.clone();
+        let mut vertex_edges: Vec<EdgeIndex> = vec![0; offsets[vertices.len()]];
+        for (local_edge_index, &(a, b)) in edge_endpoints.iter().enumerate() {
+            for vertex_index in [a, b] {
+                vertex_edges[filled[vertex_index]] = local_edge_index as EdgeIndex;
+                filled[vertex_index] += 1;
             }
         }
-        None
-    }
-
-    pub fn get_dual_node_internal_ptr(&self, dual_node_ptr: &DualNodePtr) -> DualNodeInternalPtr {
+        debug_assert!({
+            // sanity check, debug mode only (actually this bug is not critical, only the shorter edge will take effect)
+            let mut existing = std::collections::HashSet::new();
+            let mut no_duplicate = true;
+            for (edge_ptr, &(a, b)) in edges.iter().zip(edge_endpoints.iter()) {
+                if !existing.insert((a.min(b), a.max(b))) {
+                    no_duplicate = false;
+                    let edge = edge_ptr.read_recursive_force();
+                    eprintln!(
+                        "duplicated edge between local vertices {} and {} with weight {}, consider merge them into a single edge",
+                        a, b, edge.weight
+                    );
+                }
+            }
+            no_duplicate
+        });
+        for (vertex_index, vertex_ptr) in vertices.iter().enumerate() {
+            let mut vertex = vertex_ptr.write_force();
+            vertex.edges_range = (offsets[vertex_index], offsets[vertex_index + 1]);
+        }
+        vertex_edges
+    }
+
+    /// set the direction-dependent growth cost of an edge: growing a dual node by 1 from `from_vertex` covers `cost` of the
+    /// edge weight. Growth costs persist across [`DualModuleImpl::clear`]. With non-unit costs, the weight of the edge
+    /// should be a multiple of each cost and of their sum so that the edge becomes tight at an integer dual variable;
+    /// also note that the sum of dual variables is no longer the weight of the matching
+    #[allow(clippy::unnecessary_cast)]
+    pub fn set_growth_cost(&mut self, edge_index: EdgeIndex, from_vertex: VertexIndex, cost: Weight) {
+        assert!(cost > 0, "growth cost must be positive");
+        let active_timestamp = self.active_timestamp;
+        let edge_ptr = &self.edges[edge_index as usize];
+        edge_ptr.dynamic_clear(active_timestamp);
+        let mut edge = edge_ptr.write(active_timestamp);
+        debug_assert!(
+            edge.left_growth == 0 && edge.right_growth == 0,
+            "cannot change growth cost of a growing edge"
+        );
+        if edge.left.upgrade_force().read_recursive_force().vertex_index == from_vertex {
+            edge.left_growth_cost = cost;
+        } else {
+            assert_eq!(
+                edge.right.upgrade_force().read_recursive_force().vertex_index,
+                from_vertex,
+                "vertex {} is not incident to edge {}",
+                from_vertex,
+                edge_index
+            );
+            edge.right_growth_cost = cost;
+        }
+    }
+
+    /// copy the growth costs set by [`Self::set_growth_cost`] from another module of the same decoding graph
+    pub fn copy_growth_costs(&mut self, other: &Self) {
+        assert_eq!(self.edges.len(), other.edges.len(), "decoding graph mismatch");
+        for (edge_ptr, other_edge_ptr) in self.edges.iter().zip(other.edges.iter()) {
+            let other_edge = other_edge_ptr.read_recursive_force();
+            let mut edge = edge_ptr.write_force();
+            edge.left_growth_cost = other_edge.left_growth_cost;
+            edge.right_growth_cost = other_edge.right_growth_cost;
+        }
+    }
+
+    /// whether the vertex is already inside the region of a dual node, in which case a new defect vertex cannot be
+    /// loaded there without solving again
+    pub fn is_vertex_covered(&self, vertex_index: VertexIndex) -> bool {
+        let local_index = self
+            .get_vertex_index(vertex_index)
+            .expect("vertex not belonging to this dual module");
+        let vertex = self.vertices[local_index].read_recursive_force();
+        vertex.timestamp == self.active_timestamp && vertex.propagated_dual_node.is_some()
+    }
+
+    /// the regions of the dual nodes that contain the given vertices, found by crossing the fully grown edges; the
+    /// growth of a region never crosses a virtual vertex, so they are not expanded
+    #[allow(clippy::unnecessary_cast)]
+    pub fn grown_region(&self, vertex_indices: &[VertexIndex]) -> GrownRegion {
+        let active_timestamp = self.active_timestamp;
+        let mut region = GrownRegion::default();
+        let mut visited_vertices: HashSet<VertexIndex> = vertex_indices.iter().cloned().collect();
+        let mut visited_edges = HashSet::new();
+        let mut stack = vertex_indices.to_vec();
+        while let Some(vertex_index) = stack.pop() {
+            region.vertices.push(vertex_index);
+            let local_index = self
+                .get_vertex_index(vertex_index)
+                .expect("vertex not belonging to this dual module");
+            let vertex = self.vertices[local_index].read_recursive_force();
+            if vertex.is_virtual {
+                continue;
+            }
+            for &edge_index in vertex.edges(&self.vertex_edges).iter() {
+                let edge_ptr = &self.edges[edge_index as usize];
+                let edge = edge_ptr.read_recursive_force();
+                if edge.timestamp != active_timestamp || !visited_edges.insert(edge.edge_index) {
+                    continue; // stale edges are not grown in this decoding problem
+                }
+                let growth = edge.left_growth + edge.right_growth;
+                if growth > 0 {
+                    region.edges.push((edge.edge_index, growth, edge.weight));
+                }
+                if growth >= edge.weight && (growth > 0 || edge.weight == 0) {
+                    for peer_weak in [&edge.left, &edge.right] {
+                        let peer_index = peer_weak.upgrade_force().read_recursive_force().vertex_index;
+                        if visited_vertices.insert(peer_index) {
+                            stack.push(peer_index);
+                        }
+                    }
+                }
+            }
+        }
+        region
+    }
+
+    /// the maximum update length of all the active dual nodes, which must be prepared by [`DualModuleImpl::prepare_all`]
+    pub fn prepared_maximum_update_length(&self) -> GroupMaxUpdateLength {
+        let mut group_max_update_length = GroupMaxUpdateLength::new();
+        for i in 0..self.active_list.len() {
+            let dual_node_ptr = {
+                let internal_dual_node_ptr = self.active_list[i].upgrade_force();
+                let dual_node_internal = internal_dual_node_ptr.read_recursive();
+                dual_node_internal.origin.upgrade_force()
+            };
+            let dual_node = dual_node_ptr.read_recursive();
+            let is_grow = match dual_node.grow_state {
+                DualNodeGrowState::Grow => true,
+                DualNodeGrowState::Shrink => false,
+                DualNodeGrowState::Stay => continue,
+            };
+            drop(dual_node); // unlock, otherwise it causes deadlock when updating the dual node
+            let max_update_length = self.prepared_maximum_update_length_dual_node(&dual_node_ptr, is_grow);
+            group_max_update_length.add(max_update_length);
+        }
+        group_max_update_length
+    }
+
+    /// the maximum update length of a dual node, which must be prepared to grow or shrink accordingly
+    #[allow(clippy::collapsible_else_if, clippy::unnecessary_cast)]
+    pub fn prepared_maximum_update_length_dual_node(&self, dual_node_ptr: &DualNodePtr, is_grow: bool) -> MaxUpdateLength {
+        let active_timestamp = self.active_timestamp;
+        let mut max_length_abs = Weight::MAX;
+        let dual_node_internal_ptr = self.get_dual_node_internal_ptr(dual_node_ptr);
+        let dual_node_internal = dual_node_internal_ptr.read_recursive();
+        if !is_grow {
+            if dual_node_internal.dual_variable == 0 {
+                let dual_node = dual_node_ptr.read_recursive();
+                match dual_node.class {
+                    DualNodeClass::Blossom { .. } => return MaxUpdateLength::BlossomNeedExpand(dual_node_ptr.clone()),
+                    DualNodeClass::DefectVertex { defect_index } => {
+                        // try to report Conflicting event or give a VertexShrinkStop with potential conflicting node
+                        if let Some(vertex_index) = self.get_vertex_index(defect_index) {
+                            // since propagated node is never removed, this event could happen with no vertex
+                            let vertex_ptr = &self.vertices[vertex_index];
+                            let vertex = vertex_ptr.read_recursive(active_timestamp);
+                            let mut potential_conflict: Option<(DualNodePtr, DualNodePtr)> = None;
+                            for &edge_index in vertex.edges(&self.vertex_edges).iter() {
+                                let edge_ptr = &self.edges[edge_index as usize];
+                                let edge = edge_ptr.read_recursive(active_timestamp);
+                                let is_left = vertex_ptr.downgrade() == edge.left;
+                                let remaining_length = edge.weight - edge.left_growth - edge.right_growth;
+                                if remaining_length == 0 {
+                                    let peer_dual_node = if is_left {
+                                        &edge.right_dual_node
+                                    } else {
+                                        &edge.left_dual_node
+                                    };
+                                    if let Some(peer_dual_node_ptr) = peer_dual_node {
+                                        let peer_grandson_dual_node = if is_left {
+                                            &edge.right_grandson_dual_node
+                                        } else {
+                                            &edge.left_grandson_dual_node
+                                        };
+                                        let peer_dual_node_ptr =
+                                            peer_dual_node_ptr.upgrade_force().read_recursive().origin.upgrade_force();
+                                        let peer_grandson_dual_node_ptr = peer_grandson_dual_node
+                                            .as_ref()
+                                            .unwrap()
+                                            .upgrade_force()
+                                            .read_recursive()
+                                            .origin
+                                            .upgrade_force();
+                                        if peer_dual_node_ptr.read_recursive().grow_state == DualNodeGrowState::Grow {
+                                            if let Some((other_dual_node_ptr, other_grandson_dual_node)) =
+                                                &potential_conflict
+                                            {
+                                                if &peer_dual_node_ptr != other_dual_node_ptr {
+                                                    return MaxUpdateLength::Conflicting(
+                                                        (other_dual_node_ptr.clone(), other_grandson_dual_node.clone()),
+                                                        (peer_dual_node_ptr, peer_grandson_dual_node_ptr),
+                                                    );
+                                                }
+                                            } else {
+                                                potential_conflict = Some((peer_dual_node_ptr, peer_grandson_dual_node_ptr));
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            return MaxUpdateLength::VertexShrinkStop((dual_node_ptr.clone(), potential_conflict));
+                        } else {
+                            return MaxUpdateLength::VertexShrinkStop((dual_node_ptr.clone(), None));
+                        }
+                    }
+                }
+            }
+            if !dual_node_internal.overgrown_stack.is_empty() {
+                let last_index = dual_node_internal.overgrown_stack.len() - 1;
+                let (_, overgrown) = &dual_node_internal.overgrown_stack[last_index];
+                max_length_abs = std::cmp::min(max_length_abs, *overgrown);
+            }
+            max_length_abs = std::cmp::min(max_length_abs, dual_node_internal.dual_variable);
+        }
+        for (is_left, edge_weak) in dual_node_internal.boundary.iter() {
+            let edge_ptr = edge_weak.upgrade_force();
+            let is_left = *is_left;
+            let edge = edge_ptr.read_recursive(active_timestamp);
+            if is_grow {
+                // first check if both side belongs to the same tree node, if so, no constraint on this edge
+                let peer_dual_node_internal_ptr: Option<DualNodeInternalPtr> = if is_left {
+                    edge.right_dual_node.as_ref().map(|ptr| ptr.upgrade_force())
+                } else {
+                    edge.left_dual_node.as_ref().map(|ptr| ptr.upgrade_force())
+                };
+                match peer_dual_node_internal_ptr {
+                    Some(peer_dual_node_internal_ptr) => {
+                        if peer_dual_node_internal_ptr == dual_node_internal_ptr {
+                            continue;
+                        } else {
+                            let peer_dual_node_internal = peer_dual_node_internal_ptr.read_recursive();
+                            let peer_dual_node_ptr = peer_dual_node_internal.origin.upgrade_force();
+                            let peer_dual_node = peer_dual_node_ptr.read_recursive();
+                            let remaining_length = edge.weight - edge.left_growth - edge.right_growth;
+                            let (growth_cost, peer_growth_cost) = if is_left {
+                                (edge.left_growth_cost, edge.right_growth_cost)
+                            } else {
+                                (edge.right_growth_cost, edge.left_growth_cost)
+                            };
+                            let local_max_length_abs = match peer_dual_node.grow_state {
+                                DualNodeGrowState::Grow => {
+                                    if growth_cost == 1 && peer_growth_cost == 1 {
+                                        debug_assert!(remaining_length % 2 == 0, "there is odd gap between two growing nodes, please make sure all weights are even numbers");
+                                    } else {
+                                        debug_assert!(remaining_length % (growth_cost + peer_growth_cost) == 0, "the gap between two growing nodes is not a multiple of the sum of growth costs, please make sure the weight is compatible with the costs");
+                                    }
+                                    remaining_length / (growth_cost + peer_growth_cost)
+                                }
+                                DualNodeGrowState::Shrink => {
+                                    // Yue 2022.9.5: remove Conflicting event detection here, move it to the 0-dual syndrome node
+                                    continue;
+                                }
+                                DualNodeGrowState::Stay => {
+                                    debug_assert!(remaining_length % growth_cost == 0, "the gap is not a multiple of the growth cost, please make sure the weight is compatible with the costs");
+                                    remaining_length / growth_cost
+                                }
+                            };
+                            if local_max_length_abs == 0 {
+                                let peer_grandson_ptr = if is_left {
+                                    edge.right_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                } else {
+                                    edge.left_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                };
+                                let grandson_ptr = if is_left {
+                                    edge.left_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                } else {
+                                    edge.right_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                };
+                                return MaxUpdateLength::Conflicting(
+                                    (peer_dual_node_ptr.clone(), peer_grandson_ptr),
+                                    (dual_node_ptr.clone(), grandson_ptr),
+                                );
+                            }
+                            max_length_abs = std::cmp::min(max_length_abs, local_max_length_abs);
+                        }
+                    }
+                    None => {
+                        let remaining_length = edge.weight - edge.left_growth - edge.right_growth;
+                        let growth_cost = if is_left {
+                            edge.left_growth_cost
+                        } else {
+                            edge.right_growth_cost
+                        };
+                        debug_assert!(remaining_length % growth_cost == 0, "the gap is not a multiple of the growth cost, please make sure the weight is compatible with the costs");
+                        let local_max_length_abs = remaining_length / growth_cost;
+                        if local_max_length_abs == 0 {
+                            // check if peer is virtual node
+                            let peer_vertex_ptr = if is_left {
+                                edge.right.upgrade_force()
+                            } else {
+                                edge.left.upgrade_force()
+                            };
+                            let peer_vertex = peer_vertex_ptr.read_recursive(active_timestamp);
+                            if peer_vertex.is_virtual || peer_vertex.is_mirror_blocked() {
+                                let grandson_ptr = if is_left {
+                                    edge.left_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                } else {
+                                    edge.right_grandson_dual_node
+                                        .as_ref()
+                                        .map(|ptr| ptr.upgrade_force())
+                                        .unwrap()
+                                        .read_recursive()
+                                        .origin
+                                        .upgrade_force()
+                                };
+                                return MaxUpdateLength::TouchingVirtual(
+                                    (dual_node_ptr.clone(), grandson_ptr),
+                                    (peer_vertex.vertex_index, peer_vertex.is_mirror_blocked()),
+                                );
+                            } else {
+                                unreachable!("this edge should've been removed from boundary because it's already fully grown, and it's peer vertex is not virtual; edge: {edge_ptr:?}, peer_vertex_ptr: {peer_vertex_ptr:?}")
+                            }
+                        }
+                        max_length_abs = std::cmp::min(max_length_abs, local_max_length_abs);
+                    }
+                }
+            } else {
+                if is_left {
+                    if edge.left_growth == 0 {
+                        unreachable!()
+                    }
+                    max_length_abs = std::cmp::min(max_length_abs, edge.left_growth / edge.left_growth_cost);
+                } else {
+                    if edge.right_growth == 0 {
+                        unreachable!()
+                    }
+                    max_length_abs = std::cmp::min(max_length_abs, edge.right_growth / edge.right_growth_cost);
+                }
+            }
+        }
+        MaxUpdateLength::NonZeroGrow((max_length_abs, dual_node_internal.boundary.is_empty()))
+    }
+
+    /// get the local index of a vertex, thus has usize type
+    #[allow(clippy::unnecessary_cast)]
+    pub fn get_vertex_index(&self, vertex_index: VertexIndex) -> Option<usize> {
+        if self.owning_range.contains(vertex_index) {
+            return Some((vertex_index - self.owning_range.start()) as usize);
+        }
+        if let Some(unit_module_info) = self.unit_module_info.as_ref() {
+            if let Some(index) = unit_module_info.mirrored_vertices.get(&vertex_index) {
+                return Some(*index as usize);
+            }
+        }
+        None
+    }
+
+    pub fn get_dual_node_internal_ptr(&self, dual_node_ptr: &DualNodePtr) -> DualNodeInternalPtr {
         self.get_dual_node_internal_ptr_optional(dual_node_ptr).unwrap()
     }
 
@@ -1726,6 +1931,43 @@ impl DualModuleSerial {
         })
     }
 
+    /// reuse the internal dual node left in the slot by the last round or recycled by [`Self::node_pool`], or allocate one
+    #[allow(clippy::unnecessary_cast)]
+    fn acquire_dual_node_internal(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        node_index: NodeIndex,
+        dual_variable: Weight,
+    ) -> DualNodeInternalPtr {
+        let cached = if node_index < self.nodes.len() as NodeIndex {
+            self.nodes[node_index as usize].take()
+        } else {
+            None
+        };
+        self.node_pool.acquire(
+            cached,
+            |node_ptr| {
+                let mut node = node_ptr.write();
+                node.origin = dual_node_ptr.downgrade();
+                node.index = node_index;
+                node.dual_variable = dual_variable;
+                node.boundary.clear();
+                node.overgrown_stack.clear();
+                node.last_visit_cycle = 0;
+            },
+            || {
+                DualNodeInternalPtr::new_value(DualNodeInternal {
+                    origin: dual_node_ptr.downgrade(),
+                    index: node_index,
+                    dual_variable,
+                    boundary: Vec::new(),
+                    overgrown_stack: Vec::new(),
+                    last_visit_cycle: 0,
+                })
+            },
+        )
+    }
+
     /// possibly add dual node only when sync_event is provided
     #[allow(clippy::unnecessary_cast)]
     pub fn get_otherwise_add_dual_node(
@@ -1737,28 +1979,7 @@ impl DualModuleSerial {
             // add a new internal dual node corresponding to the dual_node_ptr
             self.register_dual_node_ptr(dual_node_ptr);
             let node_index = self.nodes_length as NodeIndex;
-            let node_internal_ptr =
-                if node_index < self.nodes.len() as NodeIndex && self.nodes[node_index as usize].is_some() {
-                    let node_ptr = self.nodes[node_index as usize].as_ref().unwrap().clone();
-                    let mut node = node_ptr.write();
-                    node.origin = dual_node_ptr.downgrade();
-                    node.index = node_index;
-                    node.dual_variable = dual_variable;
-                    node.boundary.clear();
-                    node.overgrown_stack.clear();
-                    node.last_visit_cycle = 0;
-                    drop(node);
-                    node_ptr
-                } else {
-                    DualNodeInternalPtr::new_value(DualNodeInternal {
-                        origin: dual_node_ptr.downgrade(),
-                        index: node_index,
-                        dual_variable,
-                        boundary: Vec::new(),
-                        overgrown_stack: Vec::new(),
-                        last_visit_cycle: 0,
-                    })
-                };
+            let node_internal_ptr = self.acquire_dual_node_internal(dual_node_ptr, node_index, dual_variable);
             self.active_list.push(node_internal_ptr.downgrade());
             self.nodes_length += 1;
             if self.nodes.len() < self.nodes_length {
@@ -1776,6 +1997,7 @@ impl DualModuleSerial {
     }
 
     /// this is equivalent to [`DualModuleSerial::prepare_dual_node_growth`] when there are no 0 weight edges, but when it encounters zero-weight edges, it will report `true`
+    #[allow(clippy::unnecessary_cast)]
     pub fn prepare_dual_node_growth_single(&mut self, dual_node_ptr: &DualNodePtr, is_grow: bool) -> bool {
         let active_timestamp = self.active_timestamp;
         self.updated_boundary.clear();
@@ -1876,8 +2098,8 @@ impl DualModuleSerial {
                         });
                     }
                     let mut count_newly_propagated_edge = 0;
-                    for edge_weak in vertex.edges.iter() {
-                        let edge_ptr = edge_weak.upgrade_force();
+                    for &edge_index in vertex.edges(&self.vertex_edges).iter() {
+                        let edge_ptr = &self.edges[edge_index as usize];
                         let (is_left, newly_propagated_edge) = {
                             edge_ptr.dynamic_clear(active_timestamp);
                             let edge = edge_ptr.read_recursive(active_timestamp);
@@ -1891,7 +2113,7 @@ impl DualModuleSerial {
                         };
                         if newly_propagated_edge {
                             count_newly_propagated_edge += 1;
-                            self.updated_boundary.push((is_left, edge_weak.clone()));
+                            self.updated_boundary.push((is_left, edge_ptr.downgrade()));
                             let mut edge = edge_ptr.write(active_timestamp);
                             if edge.weight == 0 {
                                 newly_propagated_edge_has_zero_weight = true;
@@ -1936,8 +2158,8 @@ impl DualModuleSerial {
                                     propagated_grandson_dual_node: None,
                                 });
                             }
-                            for edge_weak in vertex.edges.iter() {
-                                let edge_ptr = edge_weak.upgrade_force();
+                            for &edge_index in vertex.edges(&self.vertex_edges).iter() {
+                                let edge_ptr = &self.edges[edge_index as usize];
                                 let mut edge = edge_ptr.write(active_timestamp);
                                 let is_left = vertex_ptr.downgrade() == edge.left;
                                 if self.unit_module_info.is_none() {
@@ -1970,7 +2192,7 @@ impl DualModuleSerial {
                                     } else {
                                         edge.dedup_timestamp.1 = self.edge_dedup_timestamp;
                                     }
-                                    self.updated_boundary.push((!is_left, edge_weak.clone()));
+                                    self.updated_boundary.push((!is_left, edge_ptr.downgrade()));
                                     // boundary has the opposite end
                                 }
                             }
@@ -2057,8 +2279,8 @@ impl DualModuleSerial {
                             propagated_grandson_dual_node: None,
                         });
                     }
-                    for edge_weak in vertex.edges.iter() {
-                        let edge_ptr = edge_weak.upgrade_force();
+                    for &edge_index in vertex.edges(&self.vertex_edges).iter() {
+                        let edge_ptr = &self.edges[edge_index as usize];
                         let (is_left, newly_propagated_edge) = {
                             let edge = edge_ptr.read_recursive(active_timestamp);
                             let is_left = vertex_ptr.downgrade() == edge.left;
@@ -2098,7 +2320,7 @@ impl DualModuleSerial {
                                 } else {
                                     edge.dedup_timestamp.1 = self.edge_dedup_timestamp;
                                 }
-                                self.updated_boundary.push((!is_left, edge_weak.clone()));
+                                self.updated_boundary.push((!is_left, edge_ptr.downgrade()));
                             } // otherwise it's duplicate and should not be added to the boundary list
                             if edge.weight == 0 {
                                 newly_propagated_edge_has_zero_weight = true;
@@ -2175,6 +2397,43 @@ mod tests {
         }
     }
 
+    /// neighbor edges are stored contiguously in the order of edges
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn dual_module_serial_vertex_edges_1() {
+        // cargo test dual_module_serial_vertex_edges_1 -- --nocapture
+        let code = CodeCapacityRepetitionCode::new(5, 0.1, 500);
+        let initializer = code.get_initializer();
+        let dual_module = DualModuleSerial::new_empty(&initializer);
+        assert_eq!(dual_module.vertex_edges.len(), 2 * initializer.weighted_edges.len());
+        let mut next_start = 0;
+        for vertex_ptr in dual_module.vertices.iter() {
+            let vertex = vertex_ptr.read_recursive_force();
+            assert_eq!(vertex.edges_range.0, next_start);
+            next_start = vertex.edges_range.1;
+            let edge_indices: Vec<_> = vertex
+                .edges(&dual_module.vertex_edges)
+                .iter()
+                .map(|&local_edge_index| {
+                    let edge_ptr = &dual_module.edges[local_edge_index as usize];
+                    let edge = edge_ptr.read_recursive_force();
+                    assert!(edge.left == vertex_ptr.downgrade() || edge.right == vertex_ptr.downgrade());
+                    edge.edge_index
+                })
+                .collect();
+            let mut expected: Vec<_> = initializer
+                .weighted_edges
+                .iter()
+                .enumerate()
+                .filter(|(_, (i, j, _))| *i == vertex.vertex_index || *j == vertex.vertex_index)
+                .map(|(edge_index, _)| edge_index as EdgeIndex)
+                .collect();
+            expected.sort();
+            assert_eq!(edge_indices, expected);
+        }
+        assert_eq!(next_start, dual_module.vertex_edges.len());
+    }
+
     #[test]
     fn dual_module_serial_basics() {
         // cargo test dual_module_serial_basics -- --nocapture
@@ -2233,6 +2492,36 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn dual_module_serial_growth_cost_1() {
+        // cargo test dual_module_serial_growth_cost_1 -- --nocapture
+        let initializer = SolverInitializer::new(3, vec![(0, 1, 12), (1, 2, 12)], vec![2]);
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        dual_module.set_growth_cost(0, 0, 2);
+        let interface_ptr = DualModuleInterfacePtr::new_load(&SyndromePattern::new_vertices(vec![0, 1]), &mut dual_module);
+        let dual_node_0_ptr = interface_ptr.read_recursive().nodes[0].clone().unwrap();
+        let dual_node_1_ptr = interface_ptr.read_recursive().nodes[1].clone().unwrap();
+        // the gap of 12 is closed by a growth of 4 from both sides, because vertex 0 covers 2 per unit
+        let max_update_length = dual_module.compute_maximum_update_length_dual_node(&dual_node_0_ptr, true, false);
+        assert_eq!(max_update_length.get_none_zero_growth(), Some(4));
+        dual_module.grow_dual_node(&dual_node_0_ptr, 4);
+        dual_module.grow_dual_node(&dual_node_1_ptr, 4);
+        {
+            let edge = dual_module.edges[0].read_recursive_force();
+            assert_eq!((edge.left_growth, edge.right_growth), (8, 4));
+        }
+        let max_update_length = dual_module.compute_maximum_update_length_dual_node(&dual_node_0_ptr, true, false);
+        assert!(matches!(max_update_length, MaxUpdateLength::Conflicting(..)));
+        // shrinking is limited by the growth divided by the cost
+        let max_update_length = dual_module.compute_maximum_update_length_dual_node(&dual_node_0_ptr, false, false);
+        assert_eq!(max_update_length.get_none_zero_growth(), Some(4));
+        dual_module.grow_dual_node(&dual_node_0_ptr, -4);
+        assert_eq!(dual_module.edges[0].read_recursive_force().left_growth, 0);
+        // the cost persists after clearing
+        dual_module.clear();
+        assert_eq!(dual_module.edges[0].read_recursive_force().left_growth_cost, 2);
+    }
+
     #[test]
     fn dual_module_serial_blossom_basics() {
         // cargo test dual_module_serial_blossom_basics -- --nocapture
@@ -0,0 +1,204 @@
+//! Dual Module Test Suite
+//!
+//! Conformance scenarios for implementers of [`DualModuleImpl`], e.g. a GPU, remote or hardware-emulated backend.
+//! Each scenario drives the dual module through [`DualModuleInterfacePtr`] exactly like a primal module would, and checks
+//! the maximum update length and the sum of dual variables after each step; the expected values are parameterized by the
+//! half weight of the edges. A new backend only needs to call [`dual_module_testsuite`] in one of its tests.
+//!
+
+use super::dual_module::*;
+use super::example_codes::*;
+use super::pointers::*;
+use super::util::*;
+
+/// a conformance scenario: it receives an empty (or cleared) dual module built on [`testsuite_initializer`]
+pub type DualModuleTestScenario<D> = fn(&mut D, Weight);
+
+/// the half weights that the scenarios are run with
+pub const TESTSUITE_HALF_WEIGHTS: [Weight; 3] = [1, 500, 7777];
+
+/// the decoding graph shared by all the scenarios: a code-capacity planar code of distance 7
+pub fn testsuite_initializer(half_weight: Weight) -> SolverInitializer {
+    CodeCapacityPlanarCode::new(7, 0.1, half_weight).get_initializer()
+}
+
+/// all the scenarios with their names
+pub fn dual_module_test_scenarios<D: DualModuleImpl>() -> Vec<(&'static str, DualModuleTestScenario<D>)> {
+    vec![
+        ("grow_to_conflict", scenario_grow_to_conflict::<D>),
+        ("grow_and_shrink", scenario_grow_and_shrink::<D>),
+        ("blossom_grow_and_shrink", scenario_blossom_grow_and_shrink::<D>),
+        ("blossom_touching_virtual", scenario_blossom_touching_virtual::<D>),
+    ]
+}
+
+/// run every scenario with every half weight; each scenario runs twice on the same dual module with a
+/// [`DualModuleImpl::clear`] in between, to make sure no state leaks from one decoding problem to the next
+pub fn dual_module_testsuite<D: DualModuleImpl>() {
+    for half_weight in TESTSUITE_HALF_WEIGHTS {
+        let initializer = testsuite_initializer(half_weight);
+        for (name, scenario) in dual_module_test_scenarios::<D>() {
+            diagnostic!("[dual module testsuite] {name} with half_weight = {half_weight}");
+            let mut dual_module = D::new_empty(&initializer);
+            scenario(&mut dual_module, half_weight);
+            dual_module.clear();
+            scenario(&mut dual_module, half_weight);
+        }
+    }
+}
+
+fn expect_growth(group_max_update_length: &GroupMaxUpdateLength, expected: Weight) {
+    assert_eq!(
+        group_max_update_length.get_none_zero_growth(),
+        Some(expected),
+        "unexpected: {:?}",
+        group_max_update_length
+    );
+}
+
+/// two defect vertices 3 edges apart grow until they touch each other
+pub fn scenario_grow_to_conflict<D: DualModuleImpl>(dual_module: &mut D, half_weight: Weight) {
+    let interface_ptr = DualModuleInterfacePtr::new_load(&SyndromePattern::new_vertices(vec![19, 25]), dual_module);
+    let dual_node_19_ptr = interface_ptr.read_recursive().nodes[0].clone().unwrap();
+    let dual_node_25_ptr = interface_ptr.read_recursive().nodes[1].clone().unwrap();
+    // the nearest boundary limits the first growth
+    expect_growth(&dual_module.compute_maximum_update_length(), 2 * half_weight);
+    interface_ptr.grow(2 * half_weight, dual_module);
+    assert_eq!(interface_ptr.sum_dual_variables(), 4 * half_weight);
+    expect_growth(&dual_module.compute_maximum_update_length(), half_weight);
+    interface_ptr.grow(half_weight, dual_module);
+    assert_eq!(interface_ptr.sum_dual_variables(), 6 * half_weight);
+    let group_max_update_length = dual_module.compute_maximum_update_length();
+    assert!(
+        group_max_update_length
+            .peek()
+            .unwrap()
+            .is_conflicting(&dual_node_19_ptr, &dual_node_25_ptr),
+        "unexpected: {:?}",
+        group_max_update_length
+    );
+}
+
+/// after touching each other, the two nodes shrink back to zero and then cannot shrink anymore
+pub fn scenario_grow_and_shrink<D: DualModuleImpl>(dual_module: &mut D, half_weight: Weight) {
+    let interface_ptr = DualModuleInterfacePtr::new_load(&SyndromePattern::new_vertices(vec![19, 25]), dual_module);
+    let dual_node_19_ptr = interface_ptr.read_recursive().nodes[0].clone().unwrap();
+    let dual_node_25_ptr = interface_ptr.read_recursive().nodes[1].clone().unwrap();
+    // a dual module may report a shorter length than necessary, but never a longer one
+    while let Some(length) = dual_module.compute_maximum_update_length().get_none_zero_growth() {
+        interface_ptr.grow(length, dual_module);
+    }
+    assert_eq!(interface_ptr.sum_dual_variables(), 6 * half_weight);
+    interface_ptr.set_grow_state(&dual_node_19_ptr, DualNodeGrowState::Shrink, dual_module);
+    interface_ptr.set_grow_state(&dual_node_25_ptr, DualNodeGrowState::Shrink, dual_module);
+    while let Some(length) = dual_module.compute_maximum_update_length().get_none_zero_growth() {
+        interface_ptr.grow(length, dual_module);
+    }
+    assert_eq!(interface_ptr.sum_dual_variables(), 0);
+    let group_max_update_length = dual_module.compute_maximum_update_length();
+    assert!(
+        !group_max_update_length.is_empty() && group_max_update_length.get_none_zero_growth().is_none(),
+        "a node with zero dual variable cannot shrink: {:?}",
+        group_max_update_length
+    );
+    // staying nodes don't report anything
+    interface_ptr.set_grow_state(&dual_node_19_ptr, DualNodeGrowState::Stay, dual_module);
+    interface_ptr.set_grow_state(&dual_node_25_ptr, DualNodeGrowState::Stay, dual_module);
+    assert!(dual_module.compute_maximum_update_length().is_empty());
+}
+
+/// a blossom of three touching nodes grows and shrinks as a whole, and is then expanded back into its children
+pub fn scenario_blossom_grow_and_shrink<D: DualModuleImpl>(dual_module: &mut D, half_weight: Weight) {
+    let interface_ptr = DualModuleInterfacePtr::new_load(&SyndromePattern::new_vertices(vec![19, 26, 35]), dual_module);
+    let dual_node_19_ptr = interface_ptr.read_recursive().nodes[0].clone().unwrap();
+    let dual_node_26_ptr = interface_ptr.read_recursive().nodes[1].clone().unwrap();
+    let dual_node_35_ptr = interface_ptr.read_recursive().nodes[2].clone().unwrap();
+    interface_ptr.grow(2 * half_weight, dual_module);
+    assert_eq!(interface_ptr.sum_dual_variables(), 6 * half_weight);
+    let nodes_circle = vec![dual_node_19_ptr.clone(), dual_node_26_ptr.clone(), dual_node_35_ptr.clone()];
+    interface_ptr.set_grow_state(&dual_node_26_ptr, DualNodeGrowState::Shrink, dual_module);
+    let dual_node_blossom = interface_ptr.create_blossom(nodes_circle, vec![], dual_module);
+    for sum_dual_variables in [7, 8, 9] {
+        interface_ptr.grow(half_weight, dual_module);
+        assert_eq!(interface_ptr.sum_dual_variables(), sum_dual_variables * half_weight);
+    }
+    interface_ptr.set_grow_state(&dual_node_blossom, DualNodeGrowState::Shrink, dual_module);
+    interface_ptr.grow(half_weight, dual_module);
+    assert_eq!(interface_ptr.sum_dual_variables(), 8 * half_weight);
+    interface_ptr.grow(2 * half_weight, dual_module);
+    assert_eq!(interface_ptr.sum_dual_variables(), 6 * half_weight);
+    assert_eq!(
+        dual_module.compute_maximum_update_length().peek(),
+        Some(&MaxUpdateLength::BlossomNeedExpand(dual_node_blossom.clone()))
+    );
+    interface_ptr.expand_blossom(dual_node_blossom, dual_module);
+    for dual_node_ptr in [&dual_node_19_ptr, &dual_node_26_ptr, &dual_node_35_ptr] {
+        interface_ptr.set_grow_state(dual_node_ptr, DualNodeGrowState::Shrink, dual_module);
+    }
+    interface_ptr.grow(half_weight, dual_module);
+    assert_eq!(interface_ptr.sum_dual_variables(), 3 * half_weight);
+}
+
+/// an alternating tree forms a blossom that grows until it touches the virtual boundary, which is an obstacle
+pub fn scenario_blossom_touching_virtual<D: DualModuleImpl>(dual_module: &mut D, half_weight: Weight) {
+    let interface_ptr = DualModuleInterfacePtr::new_load(&SyndromePattern::new_vertices(vec![18, 26, 34]), dual_module);
+    let dual_node_18_ptr = interface_ptr.read_recursive().nodes[0].clone().unwrap();
+    let dual_node_26_ptr = interface_ptr.read_recursive().nodes[1].clone().unwrap();
+    let dual_node_34_ptr = interface_ptr.read_recursive().nodes[2].clone().unwrap();
+    expect_growth(&dual_module.compute_maximum_update_length(), half_weight);
+    interface_ptr.grow(half_weight, dual_module);
+    assert_eq!(interface_ptr.sum_dual_variables(), 3 * half_weight);
+    // 18 and 26 are matched, and 34 absorbs them into its alternating tree
+    interface_ptr.set_grow_state(&dual_node_26_ptr, DualNodeGrowState::Shrink, dual_module);
+    expect_growth(&dual_module.compute_maximum_update_length(), half_weight);
+    interface_ptr.grow(half_weight, dual_module);
+    assert_eq!(interface_ptr.sum_dual_variables(), 4 * half_weight);
+    let group_max_update_length = dual_module.compute_maximum_update_length();
+    assert!(
+        group_max_update_length
+            .peek()
+            .unwrap()
+            .is_conflicting(&dual_node_18_ptr, &dual_node_34_ptr),
+        "unexpected: {:?}",
+        group_max_update_length
+    );
+    let dual_node_blossom = interface_ptr.create_blossom(
+        vec![dual_node_18_ptr.clone(), dual_node_26_ptr.clone(), dual_node_34_ptr.clone()],
+        vec![],
+        dual_module,
+    );
+    for sum_dual_variables in [6, 8] {
+        expect_growth(&dual_module.compute_maximum_update_length(), 2 * half_weight);
+        interface_ptr.grow(2 * half_weight, dual_module);
+        assert_eq!(interface_ptr.sum_dual_variables(), sum_dual_variables * half_weight);
+    }
+    let group_max_update_length = dual_module.compute_maximum_update_length();
+    let touching_virtual = group_max_update_length.peek().unwrap().get_touching_virtual();
+    assert!(
+        touching_virtual == Some((dual_node_blossom.clone(), 23))
+            || touching_virtual == Some((dual_node_blossom.clone(), 39)),
+        "unexpected: {:?}",
+        group_max_update_length
+    );
+    interface_ptr.set_grow_state(&dual_node_blossom, DualNodeGrowState::Stay, dual_module);
+    assert!(dual_module.compute_maximum_update_length().is_empty());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::dual_module_parallel::*;
+    use super::super::dual_module_serial::*;
+    use super::*;
+
+    #[test]
+    fn dual_module_testsuite_serial() {
+        // cargo test dual_module_testsuite_serial -- --nocapture
+        dual_module_testsuite::<DualModuleSerial>();
+    }
+
+    #[test]
+    fn dual_module_testsuite_parallel() {
+        // cargo test dual_module_testsuite_parallel -- --nocapture
+        dual_module_testsuite::<DualModuleParallel<DualModuleSerial>>();
+    }
+}
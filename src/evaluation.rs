@@ -0,0 +1,205 @@
+//! Evaluation
+//!
+//! The logical error rate of a decoder on an example code: each shot samples a random error together with its ground
+//! truth, decodes the syndrome and checks the homology class of the residual error, i.e. the error combined with the
+//! correction, against the logical operators of the code
+//!
+
+use super::analysis::*;
+use super::example_codes::*;
+use super::mwpm_solver::*;
+use super::util::*;
+use serde::Serialize;
+
+/// evaluates any [`PrimalDualSolver`] on any [`ExampleCode`]; a logical operator is given as a cut of the decoding
+/// graph, i.e. a set of edges such that a closed chain, or a chain between two boundaries, crosses it an odd number
+/// of times if and only if the chain is in the nontrivial homology class of that logical operator
+#[derive(Debug, Clone)]
+pub struct LogicalErrorEstimator {
+    pub initializer: SolverInitializer,
+    /// whether each edge belongs to each logical operator
+    pub logical_operators: Vec<Vec<bool>>,
+    /// the z-score of the confidence level, 1.96 for 95%
+    pub confidence_z: f64,
+}
+
+/// the outcome of [`LogicalErrorEstimator::estimate`]
+#[derive(Debug, Clone, Serialize)]
+pub struct LogicalErrorRate {
+    pub shots: usize,
+    /// the shots where at least one logical operator is flipped
+    pub failures: usize,
+    /// the number of shots that flip each logical operator
+    pub logical_failures: Vec<usize>,
+    /// the Wilson score interval of the logical error rate
+    pub confidence_interval: (f64, f64),
+}
+
+impl LogicalErrorRate {
+    pub fn logical_error_rate(&self) -> f64 {
+        if self.shots == 0 {
+            0.
+        } else {
+            self.failures as f64 / self.shots as f64
+        }
+    }
+}
+
+impl LogicalErrorEstimator {
+    /// the single logical operator of a code with boundaries, see [`left_boundary_edges`]
+    pub fn new(code: &dyn ExampleCode) -> Self {
+        let mut estimator = Self::new_logical_operators(code, &[]);
+        estimator.logical_operators.push(left_boundary_edges(code));
+        estimator
+    }
+
+    /// the logical operators given as lists of edges, e.g. the two non-contractible cuts of a toric code
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new_logical_operators(code: &dyn ExampleCode, logical_operators: &[Vec<EdgeIndex>]) -> Self {
+        let initializer = code.get_initializer();
+        let edge_num = initializer.weighted_edges.len();
+        let logical_operators = logical_operators
+            .iter()
+            .map(|edges| {
+                let mut is_logical = vec![false; edge_num];
+                for &edge_index in edges.iter() {
+                    assert!((edge_index as usize) < edge_num, "edge {edge_index} out of range");
+                    is_logical[edge_index as usize] = true;
+                }
+                is_logical
+            })
+            .collect();
+        Self {
+            initializer,
+            logical_operators,
+            confidence_z: 1.96,
+        }
+    }
+
+    /// whether the residual error flips each logical operator; the correction must generate the same defects as the
+    /// error, otherwise the residual error is not a logical operator at all
+    pub fn logical_flips(&self, error_edges: &[EdgeIndex], correction: &[EdgeIndex]) -> Vec<bool> {
+        assert_eq!(
+            self.initializer.syndrome_of(error_edges),
+            self.initializer.syndrome_of(correction),
+            "the correction doesn't match the syndrome"
+        );
+        logical_flips(&self.logical_operators, error_edges, correction)
+    }
+
+    /// decode `shots` random errors of the code sampled by a [`LogicalErrorSampler`] with `seed`, so that the same seed
+    /// reproduces the same estimate
+    pub fn estimate(
+        &self,
+        code: &dyn ExampleCode,
+        solver: Box<dyn PrimalDualSolver>,
+        shots: usize,
+        seed: u64,
+    ) -> LogicalErrorRate {
+        let mut sampler = LogicalErrorSampler::new_solver(code, seed, self.logical_operators.clone(), solver);
+        let mut logical_failures = vec![0; self.logical_operators.len()];
+        // a fixed number of shots is an early-stopping run that can only stop at `max_shots`
+        let config = EarlyStoppingConfig {
+            min_shots: shots,
+            max_shots: shots,
+            confidence_z: self.confidence_z,
+            ..Default::default()
+        };
+        let result = run_monte_carlo(&config, |_| {
+            let flips = sampler.sample_flips();
+            for (logical_failures, flipped) in logical_failures.iter_mut().zip(flips.iter()) {
+                if *flipped {
+                    *logical_failures += 1;
+                }
+            }
+            flips.iter().any(|flipped| *flipped)
+        });
+        LogicalErrorRate {
+            shots: result.shots,
+            failures: result.failures,
+            logical_failures,
+            confidence_interval: result.confidence_interval,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluation_logical_flips_1() {
+        // cargo test evaluation_logical_flips_1 -- --nocapture
+        // vertices 0..6 form a chain between the virtual vertices 7 (left) and 6 (right)
+        let code = CodeCapacityRepetitionCode::new(7, 0.1, 500);
+        let estimator = LogicalErrorEstimator::new(&code);
+        // the edge 6 connects vertex 0 to the left boundary, and the edge 5 connects vertex 5 to the right boundary
+        assert_eq!(estimator.logical_flips(&[6], &[6]), vec![false]);
+        // the error chain is corrected through the other boundary
+        assert_eq!(estimator.logical_flips(&[6, 0], &[1, 2, 3, 4, 5]), vec![true]);
+        assert_eq!(estimator.logical_flips(&[2, 3], &[2, 3]), vec![false]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the correction doesn't match the syndrome")]
+    fn evaluation_logical_flips_2() {
+        // cargo test evaluation_logical_flips_2 -- --nocapture
+        let code = CodeCapacityRepetitionCode::new(7, 0.1, 500);
+        let estimator = LogicalErrorEstimator::new(&code);
+        estimator.logical_flips(&[2], &[3]);
+    }
+
+    #[test]
+    fn evaluation_planar_code_1() {
+        // cargo test evaluation_planar_code_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(5, 0.05, 500);
+        let estimator = LogicalErrorEstimator::new(&code);
+        let solver = || Box::new(SolverSerial::new(&estimator.initializer));
+        let result = estimator.estimate(&code, solver(), 2000, 0);
+        println!("{result:?}");
+        assert!(result.failures > 0 && result.failures < 200);
+        assert_eq!(result.logical_failures, vec![result.failures]);
+        let (lower, upper) = result.confidence_interval;
+        assert!(lower < result.logical_error_rate() && result.logical_error_rate() < upper);
+        // the same seed reproduces the same estimate
+        let result_2 = estimator.estimate(&code, solver(), 2000, 0);
+        assert_eq!(result.failures, result_2.failures);
+        // no error, no logical error
+        code.set_probability(0.);
+        assert_eq!(estimator.estimate(&code, solver(), 100, 0).failures, 0);
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn evaluation_toric_code_1() {
+        // cargo test evaluation_toric_code_1 -- --nocapture
+        let code = CodeCapacityToricCode::new(5, 0.08, 500);
+        // the edges that wrap around each direction of the torus
+        let (vertices, edges) = code.immutable_vertices_edges();
+        let wrapping = |edge: &CodeEdge, horizontal: bool| {
+            let (left, right) = (&vertices[edge.vertices.0 as usize], &vertices[edge.vertices.1 as usize]);
+            if horizontal {
+                (left.position.j - right.position.j).abs() > 1.5
+            } else {
+                (left.position.i - right.position.i).abs() > 1.5
+            }
+        };
+        let logical_operators: Vec<Vec<EdgeIndex>> = [true, false]
+            .iter()
+            .map(|&horizontal| {
+                (0..edges.len() as EdgeIndex)
+                    .filter(|&edge_index| wrapping(&edges[edge_index as usize], horizontal))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(logical_operators[0].len(), 5);
+        assert_eq!(logical_operators[1].len(), 5);
+        let estimator = LogicalErrorEstimator::new_logical_operators(&code, &logical_operators);
+        let solver = Box::new(SolverSerial::new(&estimator.initializer));
+        let result = estimator.estimate(&code, solver, 1000, 0);
+        println!("{result:?}");
+        assert!(result.logical_failures.iter().all(|&failures| failures > 0));
+        assert!(result.failures <= result.logical_failures.iter().sum());
+        assert!(result.failures >= *result.logical_failures.iter().max().unwrap());
+    }
+}
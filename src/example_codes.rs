@@ -9,6 +9,7 @@
 //!
 
 use super::pointers::*;
+use super::syndrome_file::*;
 use super::util::*;
 use super::visualize::*;
 use crate::derivative::Derivative;
@@ -18,8 +19,6 @@ use crate::serde_json;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
 
 /// Vertex corresponds to a stabilizer measurement bit
 #[derive(Derivative, Clone)]
@@ -214,6 +213,22 @@ pub trait ExampleCode {
         positions
     }
 
+    /// for each vertex, the incident edges that connect to a different measurement round;
+    /// they are the edges whose weights are scaled by [`SyndromePattern::apply_detector_confidences`]
+    #[allow(clippy::unnecessary_cast)]
+    fn timelike_edges(&self) -> Vec<Vec<EdgeIndex>> {
+        let (vertices, edges) = self.immutable_vertices_edges();
+        let mut timelike_edges = vec![vec![]; vertices.len()];
+        for (edge_idx, edge) in edges.iter().enumerate() {
+            let (vertex_1, vertex_2) = (edge.vertices.0 as usize, edge.vertices.1 as usize);
+            if vertices[vertex_1].position.t != vertices[vertex_2].position.t {
+                timelike_edges[vertex_1].push(edge_idx as EdgeIndex);
+                timelike_edges[vertex_2].push(edge_idx as EdgeIndex);
+            }
+        }
+        timelike_edges
+    }
+
     /// generate standard interface to instantiate Fusion blossom solver
     fn get_initializer(&self) -> SolverInitializer {
         let (vertices, edges) = self.immutable_vertices_edges();
@@ -228,11 +243,7 @@ pub trait ExampleCode {
                 virtual_vertices.push(vertex_idx as VertexIndex);
             }
         }
-        SolverInitializer {
-            vertex_num,
-            weighted_edges,
-            virtual_vertices,
-        }
+        SolverInitializer::new(vertex_num, weighted_edges, virtual_vertices)
     }
 
     /// set defect vertices (non-trivial measurement result in case of single round of measurement,
@@ -298,14 +309,24 @@ pub trait ExampleCode {
     }
 
     /// generate random errors based on the edge probabilities and a seed for pseudo number generator
-    #[allow(clippy::unnecessary_cast)]
     fn generate_random_errors(&mut self, seed: u64) -> SyndromePattern {
+        self.generate_random_errors_with_ground_truth(seed)
+            .expect("codes without ground truth must override `generate_random_errors`")
+            .0
+    }
+
+    /// the same as [`Self::generate_random_errors`] but also returns the edges that actually flipped, so that the
+    /// correction can be checked for logical errors rather than just for the optimality of its weight;
+    /// `None` if the code cannot tell which edges flipped, e.g. when the syndrome is read from a file
+    #[allow(clippy::unnecessary_cast)]
+    fn generate_random_errors_with_ground_truth(&mut self, seed: u64) -> Option<(SyndromePattern, Vec<EdgeIndex>)> {
         let mut rng = DeterministicRng::seed_from_u64(seed);
         let (vertices, edges) = self.vertices_edges();
         for vertex in vertices.iter_mut() {
             vertex.is_defect = false;
         }
-        for edge in edges.iter_mut() {
+        let mut error_edges = vec![];
+        for (edge_index, edge) in edges.iter_mut().enumerate() {
             let p = if rng.next_f64() < edge.pe {
                 edge.is_erasure = true;
                 0.5 // when erasure happens, there are 50% chance of error
@@ -314,6 +335,7 @@ pub trait ExampleCode {
                 edge.p
             };
             if rng.next_f64() < p {
+                error_edges.push(edge_index as EdgeIndex);
                 let (v1, v2) = edge.vertices;
                 let vertex_1 = &mut vertices[v1 as usize];
                 if !vertex_1.is_virtual {
@@ -325,7 +347,7 @@ pub trait ExampleCode {
                 }
             }
         }
-        self.get_syndrome()
+        Some((self.get_syndrome(), error_edges))
     }
 
     #[allow(clippy::unnecessary_cast)]
@@ -366,6 +388,57 @@ pub trait ExampleCode {
         vertices[vertex_idx].is_defect
     }
 
+    /// remove the final perfect measurement round, i.e. the last round of detectors becomes noisy: a measurement error
+    /// in the last round flips only a single detector, so every real vertex in the last round is connected to a new
+    /// virtual vertex in the future time boundary. The new edges copy the probability and weight of the time-like edge
+    /// towards the previous round, and the new vertices are appended at the end.
+    #[allow(clippy::unnecessary_cast)]
+    fn open_future_time_boundary(&mut self) {
+        let (vertices, edges) = self.vertices_edges();
+        let last_t = vertices
+            .iter()
+            .map(|vertex| vertex.position.t)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let is_same_site =
+            |a: &VisualizePosition, b: &VisualizePosition| (a.i - b.i).abs() < 1e-6 && (a.j - b.j).abs() < 1e-6;
+        let mut future_edges = vec![];
+        for (vertex_index, vertex) in vertices.iter().enumerate() {
+            if vertex.is_virtual || (vertex.position.t - last_t).abs() > 1e-6 {
+                continue;
+            }
+            let time_like_edge = vertex
+                .neighbor_edges
+                .iter()
+                .map(|&edge_index| &edges[edge_index as usize])
+                .find(|edge| {
+                    let (v1, v2) = edge.vertices;
+                    let peer = if v1 as usize == vertex_index { v2 } else { v1 };
+                    let peer_position = &vertices[peer as usize].position;
+                    is_same_site(peer_position, &vertex.position) && peer_position.t < last_t
+                })
+                .expect("at least one noisy measurement round is required to infer the measurement error rate");
+            let mut future_position = vertex.position.clone();
+            future_position.t += 1.;
+            future_edges.push((vertex_index as VertexIndex, future_position, time_like_edge.clone()));
+        }
+        for (vertex_index, future_position, time_like_edge) in future_edges {
+            let future_vertex_index = vertices.len() as VertexIndex;
+            let edge_index = edges.len() as EdgeIndex;
+            vertices.push(CodeVertex {
+                position: future_position,
+                neighbor_edges: vec![edge_index],
+                is_virtual: true,
+                is_defect: false,
+            });
+            vertices[vertex_index as usize].neighbor_edges.push(edge_index);
+            edges.push(CodeEdge {
+                vertices: (vertex_index, future_vertex_index),
+                is_erasure: false,
+                ..time_like_edge
+            });
+        }
+    }
+
     /// reorder the vertices such that new vertices (the indices of the old order) is sequential
     #[allow(clippy::unnecessary_cast)]
     fn reorder_vertices(&mut self, sequential_vertices: &Vec<VertexIndex>) {
@@ -425,6 +498,10 @@ macro_rules! bind_trait_example_code {
             fn trait_get_positions(&self) -> Vec<VisualizePosition> {
                 self.get_positions()
             }
+            #[pyo3(name = "timelike_edges")]
+            fn trait_timelike_edges(&self) -> Vec<Vec<EdgeIndex>> {
+                self.timelike_edges()
+            }
             #[pyo3(name = "get_initializer")]
             fn trait_get_initializer(&self) -> SolverInitializer {
                 self.get_initializer()
@@ -457,6 +534,13 @@ macro_rules! bind_trait_example_code {
             fn trait_generate_random_errors(&mut self, seed: u64) -> SyndromePattern {
                 self.generate_random_errors(seed)
             }
+            #[pyo3(name = "generate_random_errors_with_ground_truth", signature = (seed=thread_rng().gen()))]
+            fn trait_generate_random_errors_with_ground_truth(
+                &mut self,
+                seed: u64,
+            ) -> Option<(SyndromePattern, Vec<EdgeIndex>)> {
+                self.generate_random_errors_with_ground_truth(seed)
+            }
             #[pyo3(name = "generate_errors")]
             fn trait_generate_errors(&mut self, edge_indices: Vec<EdgeIndex>) -> SyndromePattern {
                 self.generate_errors(&edge_indices)
@@ -473,6 +557,10 @@ macro_rules! bind_trait_example_code {
             fn trait_is_defect(&mut self, vertex_idx: usize) -> bool {
                 self.is_defect(vertex_idx)
             }
+            #[pyo3(name = "open_future_time_boundary")]
+            fn trait_open_future_time_boundary(&mut self) {
+                self.open_future_time_boundary()
+            }
             #[pyo3(name = "reorder_vertices")]
             fn trait_reorder_vertices(&mut self, sequential_vertices: Vec<VertexIndex>) {
                 self.reorder_vertices(&sequential_vertices)
@@ -582,6 +670,92 @@ impl CodeCapacityRepetitionCode {
     }
 }
 
+/// quantum repetition code with measurement errors, i.e. the phenomenological noise model; the decoding graph is a
+/// 2D lattice of space and time, the smallest one used for bring-up of hardware decoders and for testing fusion chains
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct PhenomenologicalRepetitionCode {
+    /// vertices in the code
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub vertices: Vec<CodeVertex>,
+    /// nearest-neighbor edges in the decoding graph
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub edges: Vec<CodeEdge>,
+}
+
+impl ExampleCode for PhenomenologicalRepetitionCode {
+    fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>) {
+        (&mut self.vertices, &mut self.edges)
+    }
+    fn immutable_vertices_edges(&self) -> (&Vec<CodeVertex>, &Vec<CodeEdge>) {
+        (&self.vertices, &self.edges)
+    }
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_example_code! {PhenomenologicalRepetitionCode}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl PhenomenologicalRepetitionCode {
+    #[cfg_attr(feature = "python_binding", new)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (d, noisy_measurements, p, max_half_weight = 500)))]
+    pub fn new(d: VertexNum, noisy_measurements: VertexNum, p: f64, max_half_weight: Weight) -> Self {
+        let mut code = Self::create_code(d, noisy_measurements);
+        code.set_probability(p);
+        code.compute_weights(max_half_weight);
+        code
+    }
+
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn create_code(d: VertexNum, noisy_measurements: VertexNum) -> Self {
+        assert!(d >= 3 && d % 2 == 1, "d must be odd integer >= 3");
+        let t_vertex_num = (d - 1) + 2; // two virtual vertices at left and right
+        let td = noisy_measurements + 1; // a perfect measurement round is capped at the end
+        let vertex_num = t_vertex_num * td; // `td` layers
+                                            // create edges
+        let mut edges = Vec::new();
+        for t in 0..td {
+            let bias = t * t_vertex_num;
+            for i in 0..d - 1 {
+                edges.push(CodeEdge::new(bias + i, bias + i + 1));
+            }
+            edges.push(CodeEdge::new(bias, bias + d)); // left most edge
+                                                       // inter-layer connection
+            if t + 1 < td {
+                for i in 0..d - 1 {
+                    edges.push(CodeEdge::new(bias + i, bias + i + t_vertex_num));
+                }
+            }
+        }
+        let mut code = Self {
+            vertices: Vec::new(),
+            edges,
+        };
+        // create vertices
+        code.fill_vertices(vertex_num);
+        for t in 0..td {
+            let bias = t * t_vertex_num;
+            code.vertices[(bias + d - 1) as usize].is_virtual = true;
+            code.vertices[(bias + d) as usize].is_virtual = true;
+        }
+        let mut positions = Vec::new();
+        for t in 0..td {
+            let pos_t = t as f64;
+            for i in 0..d {
+                positions.push(VisualizePosition::new(0., i as f64, pos_t));
+            }
+            positions.push(VisualizePosition::new(0., -1., pos_t));
+        }
+        for (i, position) in positions.into_iter().enumerate() {
+            code.vertices[i].position = position;
+        }
+        code
+    }
+}
+
 /// code capacity noise model is a single measurement round with perfect stabilizer measurements;
 /// e.g. this is the decoding graph of a CSS surface code (standard one, not rotated one) with X-type stabilizers
 #[derive(Clone, Debug)]
@@ -808,26 +982,46 @@ impl CircuitLevelPlanarCode {
 
     #[cfg_attr(feature = "python_binding", staticmethod)]
     #[cfg_attr(feature = "python_binding", pyo3(signature = (d, noisy_measurements, p, max_half_weight = 500, diagonal_p = None)))]
-    #[allow(clippy::unnecessary_cast)]
     pub fn new_diagonal(
         d: VertexNum,
         noisy_measurements: VertexNum,
         p: f64,
         max_half_weight: Weight,
         diagonal_p: Option<f64>,
+    ) -> Self {
+        Self::new_noise_model(d, noisy_measurements, p, max_half_weight, None, diagonal_p)
+    }
+
+    /// the data errors on the space-like edges have error rate `p`, the measurement errors on the time-like edges have
+    /// error rate `measurement_p`, and the hook errors of the two-qubit gates on the diagonal edges have error rate
+    /// `diagonal_p`; both default to `p` if not given
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (d, noisy_measurements, p, max_half_weight = 500, measurement_p = None, diagonal_p = None)))]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new_noise_model(
+        d: VertexNum,
+        noisy_measurements: VertexNum,
+        p: f64,
+        max_half_weight: Weight,
+        measurement_p: Option<f64>,
+        diagonal_p: Option<f64>,
     ) -> Self {
         let mut code = Self::create_code(d, noisy_measurements);
         code.set_probability(p);
-        if let Some(diagonal_p) = diagonal_p {
-            let (vertices, edges) = code.vertices_edges();
-            for edge in edges.iter_mut() {
-                let (v1, v2) = edge.vertices;
-                let v1p = &vertices[v1 as usize].position;
-                let v2p = &vertices[v2 as usize].position;
-                let manhattan_distance = (v1p.i - v2p.i).abs() + (v1p.j - v2p.j).abs() + (v1p.t - v2p.t).abs();
-                if manhattan_distance > 1. {
+        let (vertices, edges) = code.vertices_edges();
+        for edge in edges.iter_mut() {
+            let (v1, v2) = edge.vertices;
+            let v1p = &vertices[v1 as usize].position;
+            let v2p = &vertices[v2 as usize].position;
+            let manhattan_distance = (v1p.i - v2p.i).abs() + (v1p.j - v2p.j).abs() + (v1p.t - v2p.t).abs();
+            if manhattan_distance > 1. {
+                if let Some(diagonal_p) = diagonal_p {
                     edge.p = diagonal_p;
                 }
+            } else if v1p.t != v2p.t {
+                if let Some(measurement_p) = measurement_p {
+                    edge.p = measurement_p;
+                }
             }
         }
         code.compute_weights(max_half_weight);
@@ -1117,6 +1311,281 @@ impl PhenomenologicalRotatedCode {
     }
 }
 
+/// code capacity noise model of the XZZX surface code, whose stabilizers are all `XZZX` on the rotated lattice, under
+/// noise biased towards Z errors. A Z (X) error flips the two stabilizers along the main (anti-) diagonal, so both
+/// types of errors are decoded in a single graph whose vertices are the plaquettes of the rotated lattice:
+/// the plaquette at `(row, column)` for `0 <= row, column <= d` has index `row * (d + 1) + column`, and those on the
+/// border that are not stabilizers are virtual. See [`Self::set_bias`] for the edge probabilities
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct CodeCapacityXZZXCode {
+    /// vertices in the code
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub vertices: Vec<CodeVertex>,
+    /// diagonal edges in the decoding graph
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub edges: Vec<CodeEdge>,
+}
+
+impl ExampleCode for CodeCapacityXZZXCode {
+    fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>) {
+        (&mut self.vertices, &mut self.edges)
+    }
+    fn immutable_vertices_edges(&self) -> (&Vec<CodeVertex>, &Vec<CodeEdge>) {
+        (&self.vertices, &self.edges)
+    }
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_example_code! {CodeCapacityXZZXCode}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl CodeCapacityXZZXCode {
+    #[cfg_attr(feature = "python_binding", new)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (d, p, eta = 0.5, max_half_weight = 500)))]
+    pub fn new(d: VertexNum, p: f64, eta: f64, max_half_weight: Weight) -> Self {
+        let mut code = Self::create_code(d);
+        code.set_bias(p, eta);
+        code.compute_weights(max_half_weight);
+        code
+    }
+
+    /// set the edge probabilities given the physical error rate `p = p_x + p_y + p_z` and the bias
+    /// `eta = p_z / (p_x + p_y)` with `p_x = p_y`; `eta = 0.5` is the depolarizing noise. A Y error flips the stabilizers
+    /// of both a Z error and an X error, which is approximated by independent edges of probabilities `p_z + p_y` along
+    /// the main diagonal and `p_x + p_y` along the anti-diagonal, so the former are lighter when `eta > 0.5`
+    #[allow(clippy::unnecessary_cast)]
+    pub fn set_bias(&mut self, p: f64, eta: f64) {
+        assert!(eta > 0., "eta must be positive");
+        let p_z = p * eta / (eta + 1.);
+        let p_x = p / (2. * (eta + 1.));
+        let p_y = p_x;
+        for edge in self.edges.iter_mut() {
+            let (v1, v2) = edge.vertices;
+            let (position_1, position_2) = (&self.vertices[v1 as usize].position, &self.vertices[v2 as usize].position);
+            let is_main_diagonal = (position_2.i - position_1.i) * (position_2.j - position_1.j) > 0.;
+            edge.p = if is_main_diagonal { p_z + p_y } else { p_x + p_y };
+        }
+    }
+
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn create_code(d: VertexNum) -> Self {
+        assert!(d >= 3 && d % 2 == 1, "d must be odd integer >= 3");
+        let row_vertex_num = d + 1;
+        let vertex_num = row_vertex_num * row_vertex_num;
+        let plaquette = |row: VertexNum, column: VertexNum| row * row_vertex_num + column;
+        // the weight-2 stabilizers on the top and bottom borders alternate with those on the left and right borders
+        let is_stabilizer = |row: VertexNum, column: VertexNum| {
+            let is_row_border = row == 0 || row == d;
+            let is_column_border = column == 0 || column == d;
+            let parity = (row + column) % 2;
+            match (is_row_border, is_column_border) {
+                (false, false) => true,
+                (true, false) => parity == 0,
+                (false, true) => parity == 1,
+                (true, true) => false, // corners
+            }
+        };
+        // create edges, each qubit `(i, j)` touching the plaquettes `(i, j)`, `(i, j + 1)`, `(i + 1, j)` and `(i + 1, j + 1)`
+        let mut edges = Vec::new();
+        for i in 0..d {
+            for j in 0..d {
+                for (a, b) in [((i, j), (i + 1, j + 1)), ((i, j + 1), (i + 1, j))] {
+                    if is_stabilizer(a.0, a.1) || is_stabilizer(b.0, b.1) {
+                        edges.push(CodeEdge::new(plaquette(a.0, a.1), plaquette(b.0, b.1)));
+                    }
+                }
+            }
+        }
+        let mut code = Self {
+            vertices: Vec::new(),
+            edges,
+        };
+        // create vertices
+        code.fill_vertices(vertex_num);
+        for row in 0..row_vertex_num {
+            for column in 0..row_vertex_num {
+                if !is_stabilizer(row, column) {
+                    code.vertices[plaquette(row, column) as usize].is_virtual = true;
+                }
+            }
+        }
+        for row in 0..row_vertex_num {
+            for column in 0..row_vertex_num {
+                code.vertices[plaquette(row, column) as usize].position =
+                    VisualizePosition::new(row as f64, column as f64, 0.);
+            }
+        }
+        code
+    }
+}
+
+/// code capacity noise model of the toric code, i.e. the surface code with periodic boundaries, with X-type stabilizers;
+/// there is no virtual vertex because every error flips exactly two stabilizers, so the number of defect vertices is
+/// always even. The vertex at `(row, column)` has index `row * d + column`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct CodeCapacityToricCode {
+    /// vertices in the code
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub vertices: Vec<CodeVertex>,
+    /// nearest-neighbor edges in the decoding graph, including those wrapping around the lattice
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub edges: Vec<CodeEdge>,
+}
+
+impl ExampleCode for CodeCapacityToricCode {
+    fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>) {
+        (&mut self.vertices, &mut self.edges)
+    }
+    fn immutable_vertices_edges(&self) -> (&Vec<CodeVertex>, &Vec<CodeEdge>) {
+        (&self.vertices, &self.edges)
+    }
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_example_code! {CodeCapacityToricCode}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl CodeCapacityToricCode {
+    #[cfg_attr(feature = "python_binding", new)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (d, p, max_half_weight = 500)))]
+    pub fn new(d: VertexNum, p: f64, max_half_weight: Weight) -> Self {
+        let mut code = Self::create_code(d);
+        code.set_probability(p);
+        code.compute_weights(max_half_weight);
+        code
+    }
+
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn create_code(d: VertexNum) -> Self {
+        assert!(
+            d >= 3,
+            "d must be integer >= 3, otherwise the wrap-around edges duplicate the others"
+        );
+        let vertex_num = d * d;
+        // create edges, connecting the last row (column) back to the first one
+        let mut edges = Vec::new();
+        for row in 0..d {
+            for i in 0..d {
+                let vertex_index = row * d + i;
+                edges.push(CodeEdge::new(vertex_index, row * d + (i + 1) % d));
+                edges.push(CodeEdge::new(vertex_index, ((row + 1) % d) * d + i));
+            }
+        }
+        let mut code = Self {
+            vertices: Vec::new(),
+            edges,
+        };
+        // create vertices, none of which is virtual
+        code.fill_vertices(vertex_num);
+        let mut positions = Vec::new();
+        for row in 0..d {
+            for i in 0..d {
+                positions.push(VisualizePosition::new(row as f64, i as f64, 0.));
+            }
+        }
+        for (i, position) in positions.into_iter().enumerate() {
+            code.vertices[i].position = position;
+        }
+        code
+    }
+}
+
+/// phenomenological noise model of the toric code, see [`CodeCapacityToricCode`];
+/// the vertex at `(t, row, column)` has index `t * d * d + row * d + column`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct PhenomenologicalToricCode {
+    /// vertices in the code
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub vertices: Vec<CodeVertex>,
+    /// nearest-neighbor edges in the decoding graph, including those wrapping around the lattice
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub edges: Vec<CodeEdge>,
+}
+
+impl ExampleCode for PhenomenologicalToricCode {
+    fn vertices_edges(&mut self) -> (&mut Vec<CodeVertex>, &mut Vec<CodeEdge>) {
+        (&mut self.vertices, &mut self.edges)
+    }
+    fn immutable_vertices_edges(&self) -> (&Vec<CodeVertex>, &Vec<CodeEdge>) {
+        (&self.vertices, &self.edges)
+    }
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_example_code! {PhenomenologicalToricCode}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl PhenomenologicalToricCode {
+    #[cfg_attr(feature = "python_binding", new)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (d, noisy_measurements, p, max_half_weight = 500)))]
+    pub fn new(d: VertexNum, noisy_measurements: VertexNum, p: f64, max_half_weight: Weight) -> Self {
+        let mut code = Self::create_code(d, noisy_measurements);
+        code.set_probability(p);
+        code.compute_weights(max_half_weight);
+        code
+    }
+
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn create_code(d: VertexNum, noisy_measurements: VertexNum) -> Self {
+        assert!(
+            d >= 3,
+            "d must be integer >= 3, otherwise the wrap-around edges duplicate the others"
+        );
+        let t_vertex_num = d * d;
+        let td = noisy_measurements + 1; // a perfect measurement round is capped at the end
+        let vertex_num = t_vertex_num * td; // `td` layers
+                                            // create edges
+        let mut edges = Vec::new();
+        for t in 0..td {
+            let t_bias = t * t_vertex_num;
+            for row in 0..d {
+                for i in 0..d {
+                    let vertex_index = t_bias + row * d + i;
+                    edges.push(CodeEdge::new(vertex_index, t_bias + row * d + (i + 1) % d));
+                    edges.push(CodeEdge::new(vertex_index, t_bias + ((row + 1) % d) * d + i));
+                }
+            }
+            // inter-layer connection
+            if t + 1 < td {
+                for i in 0..t_vertex_num {
+                    edges.push(CodeEdge::new(t_bias + i, t_bias + i + t_vertex_num));
+                }
+            }
+        }
+        let mut code = Self {
+            vertices: Vec::new(),
+            edges,
+        };
+        // create vertices, none of which is virtual
+        code.fill_vertices(vertex_num);
+        let mut positions = Vec::new();
+        for t in 0..td {
+            let pos_t = t as f64;
+            for row in 0..d {
+                for i in 0..d {
+                    positions.push(VisualizePosition::new(row as f64, i as f64, pos_t));
+                }
+            }
+        }
+        for (i, position) in positions.into_iter().enumerate() {
+            code.vertices[i].position = position;
+        }
+        code
+    }
+}
+
 /// example code with QEC-Playground as simulator
 #[cfg(feature = "qecp_integrate")]
 #[cfg_attr(feature = "python_binding", cfg_eval)]
@@ -1181,6 +1650,9 @@ impl ExampleCode for QECPlaygroundCode {
         }
         self.get_syndrome()
     }
+    fn generate_random_errors_with_ground_truth(&mut self, _seed: u64) -> Option<(SyndromePattern, Vec<EdgeIndex>)> {
+        None // the simulator samples Pauli errors on qubits, which are not mapped back to the edges
+    }
 }
 
 #[cfg(all(feature = "qecp_integrate", feature = "python_binding"))]
@@ -1393,6 +1865,9 @@ impl ExampleCode for ErrorPatternReader {
         self.defect_index += 1;
         syndrome_pattern
     }
+    fn generate_random_errors_with_ground_truth(&mut self, _seed: u64) -> Option<(SyndromePattern, Vec<EdgeIndex>)> {
+        None // the syndrome file does not record the errors that cause the syndrome
+    }
 }
 
 #[cfg_attr(feature = "python_binding", cfg_eval)]
@@ -1428,31 +1903,11 @@ impl ErrorPatternReader {
         if !config.is_empty() {
             panic!("unknown config keys: {:?}", config.keys().collect::<Vec<&String>>());
         }
-        let file = File::open(filename).unwrap();
-        let mut syndrome_patterns = vec![];
-        let mut initializer: Option<SolverInitializer> = None;
-        let mut positions: Option<Vec<VisualizePosition>> = None;
-        for (line_index, line) in io::BufReader::new(file).lines().enumerate() {
-            if let Ok(value) = line {
-                match line_index {
-                    0 => {
-                        assert!(value.starts_with("Syndrome Pattern v1.0 "), "incompatible file version");
-                    }
-                    1 => {
-                        initializer = Some(serde_json::from_str(&value).unwrap());
-                    }
-                    2 => {
-                        positions = Some(serde_json::from_str(&value).unwrap());
-                    }
-                    _ => {
-                        let syndrome_pattern: SyndromePattern = serde_json::from_str(&value).unwrap();
-                        syndrome_patterns.push(syndrome_pattern);
-                    }
-                }
-            }
-        }
-        let initializer = initializer.expect("initializer not present in file");
-        let positions = positions.expect("positions not present in file");
+        let mut reader = SyndromeFileReader::open(&filename).unwrap();
+        let syndrome_patterns = reader.read_all().unwrap();
+        let SyndromeFileReader {
+            initializer, positions, ..
+        } = reader;
         assert_eq!(positions.len(), initializer.vertex_num as usize);
         let mut code = Self {
             vertices: Vec::with_capacity(initializer.vertex_num as usize),
@@ -1543,11 +1998,15 @@ pub(crate) fn register(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<CodeEdge>()?;
     m.add_function(wrap_pyfunction!(weight_of_p, m)?)?;
     m.add_class::<CodeCapacityRepetitionCode>()?;
+    m.add_class::<PhenomenologicalRepetitionCode>()?;
     m.add_class::<CodeCapacityPlanarCode>()?;
     m.add_class::<PhenomenologicalPlanarCode>()?;
     m.add_class::<CircuitLevelPlanarCode>()?;
     m.add_class::<CodeCapacityRotatedCode>()?;
     m.add_class::<PhenomenologicalRotatedCode>()?;
+    m.add_class::<CodeCapacityToricCode>()?;
+    m.add_class::<CodeCapacityXZZXCode>()?;
+    m.add_class::<PhenomenologicalToricCode>()?;
     m.add_class::<ErrorPatternReader>()?;
     Ok(())
 }
@@ -1579,6 +2038,18 @@ mod tests {
         visualize_code(&mut code, "example_code_capacity_repetition_code.json".to_string());
     }
 
+    #[test]
+    fn example_phenomenological_repetition_code() {
+        // cargo test example_phenomenological_repetition_code -- --nocapture
+        let mut code = PhenomenologicalRepetitionCode::new(7, 6, 0.05, 500);
+        code.sanity_check().unwrap();
+        assert_eq!(code.vertex_num(), 8 * 7);
+        // each layer has `d - 1` space-like edges and the left most edge, plus `d - 1` time-like edges except the last layer
+        assert_eq!(code.edges.len(), 7 * 7 + 6 * 6);
+        assert_eq!(code.timelike_edges()[0].len(), 1);
+        visualize_code(&mut code, "example_phenomenological_repetition_code.json".to_string());
+    }
+
     #[test]
     fn example_code_capacity_planar_code() {
         // cargo test example_code_capacity_planar_code -- --nocapture
@@ -1611,6 +2082,74 @@ mod tests {
         visualize_code(&mut code, "example_circuit_level_planar_code.json".to_string());
     }
 
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn example_circuit_level_planar_code_noise_model() {
+        // cargo test example_circuit_level_planar_code_noise_model -- --nocapture
+        let (p, measurement_p, diagonal_p) = (0.01, 0.001, 0.005);
+        let code = CircuitLevelPlanarCode::new_noise_model(5, 4, p, 500, Some(measurement_p), Some(diagonal_p));
+        code.sanity_check().unwrap();
+        let mut edge_counts = [0; 3];
+        let mut edge_weights = [None; 3];
+        for edge in code.edges.iter() {
+            let (v1, v2) = edge.vertices;
+            let (v1p, v2p) = (&code.vertices[v1 as usize].position, &code.vertices[v2 as usize].position);
+            let manhattan_distance = (v1p.i - v2p.i).abs() + (v1p.j - v2p.j).abs() + (v1p.t - v2p.t).abs();
+            let (class, expected_p) = if manhattan_distance > 1. {
+                (2, diagonal_p)
+            } else if v1p.t != v2p.t {
+                (1, measurement_p)
+            } else {
+                (0, p)
+            };
+            assert_eq!(edge.p, expected_p);
+            edge_counts[class] += 1;
+            assert!(edge_weights[class].map_or(true, |weight| weight == edge.half_weight));
+            edge_weights[class] = Some(edge.half_weight);
+        }
+        assert!(edge_counts.iter().all(|&count| count > 0));
+        // the rarer the error, the heavier the edge
+        assert!(edge_weights[1] > edge_weights[2] && edge_weights[2] > edge_weights[0]);
+        // the default noise model only distinguishes the diagonal edges
+        let code = CircuitLevelPlanarCode::new(5, 4, p, 500);
+        let default_code = CircuitLevelPlanarCode::new_noise_model(5, 4, p, 500, None, Some(p / 3.));
+        for (edge, default_edge) in code.edges.iter().zip(default_code.edges.iter()) {
+            assert_eq!(edge.p, default_edge.p);
+            assert_eq!(edge.half_weight, default_edge.half_weight);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn example_open_future_time_boundary() {
+        // cargo test example_open_future_time_boundary -- --nocapture
+        let (d, noisy_measurements) = (5, 3);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.01, 500);
+        let (vertex_num, edge_num) = (code.vertices.len(), code.edges.len());
+        code.open_future_time_boundary();
+        code.sanity_check().unwrap();
+        let round_real_vertex_num = (d * (d - 1)) as usize;
+        assert_eq!(code.vertices.len(), vertex_num + round_real_vertex_num);
+        assert_eq!(code.edges.len(), edge_num + round_real_vertex_num);
+        // a single measurement error in the last round now connects a real vertex to the future boundary
+        let last_vertex_index = (0..vertex_num).rev().find(|&i| !code.vertices[i].is_virtual).unwrap();
+        let future_edge_index = *code.vertices[last_vertex_index].neighbor_edges.last().unwrap();
+        let (v1, v2) = code.edges[future_edge_index as usize].vertices;
+        assert_eq!(v1 as usize, last_vertex_index);
+        assert!(code.vertices[v2 as usize].is_virtual);
+        assert_eq!(
+            code.vertices[v2 as usize].position.t,
+            code.vertices[v1 as usize].position.t + 1.
+        );
+        let mut code = CircuitLevelPlanarCode::new(d, noisy_measurements, 0.01, 500);
+        code.open_future_time_boundary();
+        code.sanity_check().unwrap();
+        let mut code = PhenomenologicalRotatedCode::new(d, noisy_measurements, 0.01, 500);
+        code.open_future_time_boundary();
+        code.sanity_check().unwrap();
+        visualize_code(&mut code, "example_open_future_time_boundary.json".to_string());
+    }
+
     #[test]
     fn example_code_capacity_rotated_code() {
         // cargo test example_code_capacity_rotated_code -- --nocapture
@@ -1627,6 +2166,93 @@ mod tests {
         visualize_code(&mut code, "example_code_phenomenological_rotated_code.json".to_string());
     }
 
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn example_code_capacity_xzzx_code() {
+        // cargo test example_code_capacity_xzzx_code -- --nocapture
+        let d = 5;
+        let mut code = CodeCapacityXZZXCode::new(d, 0.1, 100., 500);
+        code.sanity_check().unwrap();
+        let real_vertex_num = code.vertices.iter().filter(|vertex| !vertex.is_virtual).count();
+        assert_eq!(real_vertex_num, (d * d - 1) as usize);
+        // under Z-biased noise, the main-diagonal edges of Z errors are lighter than the anti-diagonal ones
+        let is_main_diagonal = |edge: &CodeEdge| {
+            let (p1, p2) = (
+                &code.vertices[edge.vertices.0 as usize].position,
+                &code.vertices[edge.vertices.1 as usize].position,
+            );
+            (p2.i - p1.i) * (p2.j - p1.j) > 0.
+        };
+        let main_weights: Vec<_> = code
+            .edges
+            .iter()
+            .filter(|e| is_main_diagonal(e))
+            .map(|e| e.half_weight)
+            .collect();
+        let anti_weights: Vec<_> = code
+            .edges
+            .iter()
+            .filter(|e| !is_main_diagonal(e))
+            .map(|e| e.half_weight)
+            .collect();
+        assert!(main_weights.iter().max().unwrap() < anti_weights.iter().min().unwrap());
+        // depolarizing noise gives uniform weights
+        let depolarizing = CodeCapacityXZZXCode::new(d, 0.1, 0.5, 500);
+        assert!(depolarizing.edges.iter().all(|edge| edge.half_weight == 500));
+        visualize_code(&mut code, "example_code_capacity_xzzx_code.json".to_string());
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn example_code_capacity_toric_code() {
+        // cargo test example_code_capacity_toric_code -- --nocapture
+        let d = 5;
+        let mut code = CodeCapacityToricCode::new(d, 0.1, 500);
+        code.sanity_check().unwrap();
+        assert_eq!(code.vertices.len(), (d * d) as usize);
+        assert_eq!(code.edges.len(), (2 * d * d) as usize);
+        assert!(code
+            .vertices
+            .iter()
+            .all(|vertex| !vertex.is_virtual && vertex.neighbor_edges.len() == 4));
+        for seed in 0..10 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            assert_eq!(syndrome_pattern.defect_vertices.len() % 2, 0, "seed {seed}");
+        }
+        visualize_code(&mut code, "example_code_capacity_toric_code.json".to_string());
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn example_code_phenomenological_toric_code() {
+        // cargo test example_code_phenomenological_toric_code -- --nocapture
+        let (d, noisy_measurements) = (5, 3);
+        let mut code = PhenomenologicalToricCode::new(d, noisy_measurements, 0.01, 500);
+        code.sanity_check().unwrap();
+        let td = noisy_measurements + 1;
+        assert_eq!(code.vertices.len(), (d * d * td) as usize);
+        assert_eq!(code.edges.len(), (2 * d * d * td + d * d * noisy_measurements) as usize);
+        assert!(code.vertices.iter().all(|vertex| !vertex.is_virtual));
+        visualize_code(&mut code, "example_code_phenomenological_toric_code.json".to_string());
+    }
+
+    #[test]
+    fn example_code_generate_random_errors_with_ground_truth() {
+        // cargo test example_code_generate_random_errors_with_ground_truth -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(7, 7, 0.05, 500);
+        code.set_erasure_probability(0.02);
+        let initializer = code.get_initializer();
+        for seed in 0..20 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let (ground_truth_syndrome, error_edges) = code.generate_random_errors_with_ground_truth(seed).unwrap();
+            // the same seed must sample the same syndrome
+            assert_eq!(syndrome_pattern.defect_vertices, ground_truth_syndrome.defect_vertices);
+            assert_eq!(syndrome_pattern.erasures, ground_truth_syndrome.erasures);
+            let defect_vertices: Vec<_> = initializer.syndrome_of(&error_edges).into_iter().collect();
+            assert_eq!(defect_vertices, ground_truth_syndrome.defect_vertices);
+        }
+    }
+
     #[cfg(feature = "qecp_integrate")]
     #[test]
     fn example_qec_playground_code() {
@@ -248,7 +248,6 @@ impl ExamplePartition for CodeCapacityRepetitionCodePartitionHalf {
 
 /// evenly partition along the time axis
 pub struct PhenomenologicalPlanarCodeTimePartition {
-    d: VertexNum,
     noisy_measurements: VertexNum,
     /// the number of partition
     partition_num: usize,
@@ -257,6 +256,8 @@ pub struct PhenomenologicalPlanarCodeTimePartition {
     /// maximum amount of tree leaf; if the total partition is greater than this, it will be cut into multiple regions and each region is a separate tree;
     /// those trees are then fused sequentially
     maximum_tree_leaf_size: usize,
+    /// the number of vertices in each measurement round, `d * (d + 1)` for the planar code
+    round_vertex_num: VertexNum,
 }
 
 impl PhenomenologicalPlanarCodeTimePartition {
@@ -268,25 +269,36 @@ impl PhenomenologicalPlanarCodeTimePartition {
         maximum_tree_leaf_size: usize,
     ) -> Self {
         Self {
-            d,
             noisy_measurements,
             partition_num,
             enable_tree_fusion,
             maximum_tree_leaf_size,
+            round_vertex_num: d * (d + 1),
         }
     }
     pub fn new(d: VertexNum, noisy_measurements: VertexNum, partition_num: usize) -> Self {
         Self::new_tree(d, noisy_measurements, partition_num, false, usize::MAX)
     }
+    /// the same time partition of a [`crate::example_codes::PhenomenologicalRepetitionCode`], whose rounds have `d + 1` vertices
+    pub fn new_repetition(d: VertexNum, noisy_measurements: VertexNum, partition_num: usize) -> Self {
+        let mut partition = Self::new(d, noisy_measurements, partition_num);
+        partition.round_vertex_num = d + 1;
+        partition
+    }
 }
 
 impl ExamplePartition for PhenomenologicalPlanarCodeTimePartition {
     #[allow(clippy::unnecessary_cast)]
     fn build_partition(&mut self, code: &dyn ExampleCode) -> PartitionConfig {
-        let (d, noisy_measurements, partition_num) = (self.d, self.noisy_measurements, self.partition_num);
-        let round_vertex_num = d * (d + 1);
-        let vertex_num = round_vertex_num * (noisy_measurements + 1);
-        assert_eq!(code.vertex_num(), vertex_num, "code size incompatible");
+        let (noisy_measurements, partition_num) = (self.noisy_measurements, self.partition_num);
+        let round_vertex_num = self.round_vertex_num;
+        let mut vertex_num = round_vertex_num * (noisy_measurements + 1);
+        // an open future time boundary (see [`ExampleCode::open_future_time_boundary`]) appends virtual vertices
+        // at the end, which only connect to the last round
+        assert!(code.vertex_num() >= vertex_num, "code size incompatible");
+        let future_vertex_num = code.vertex_num() - vertex_num;
+        assert!(future_vertex_num <= round_vertex_num, "code size incompatible");
+        vertex_num += future_vertex_num;
         assert!(partition_num >= 1 && partition_num <= noisy_measurements as usize + 1);
         // do not use fixed partition_length, because it would introduce super long partition; do it on the fly
         let mut config = PartitionConfig::new(vertex_num);
@@ -307,6 +319,7 @@ impl ExamplePartition for PhenomenologicalPlanarCodeTimePartition {
                 ));
             }
         }
+        config.partitions.last_mut().unwrap().append_by(future_vertex_num);
         config.fusions.clear();
         if !self.enable_tree_fusion || self.maximum_tree_leaf_size == 1 {
             for unit_index in partition_num..(2 * partition_num - 1) {
@@ -436,8 +449,13 @@ impl ExamplePartition for PhenomenologicalRotatedCodeTimePartition {
         let (d, noisy_measurements, partition_num) = (self.d, self.noisy_measurements, self.partition_num);
         let row_vertex_num = (d - 1) / 2 + 1;
         let round_vertex_num = row_vertex_num * (d + 1);
-        let vertex_num = round_vertex_num * (noisy_measurements + 1);
-        assert_eq!(code.vertex_num(), vertex_num, "code size incompatible");
+        let mut vertex_num = round_vertex_num * (noisy_measurements + 1);
+        // an open future time boundary (see [`ExampleCode::open_future_time_boundary`]) appends virtual vertices
+        // at the end, which only connect to the last round
+        assert!(code.vertex_num() >= vertex_num, "code size incompatible");
+        let future_vertex_num = code.vertex_num() - vertex_num;
+        assert!(future_vertex_num <= round_vertex_num, "code size incompatible");
+        vertex_num += future_vertex_num;
         assert!(partition_num >= 1 && partition_num <= noisy_measurements as usize + 1);
         // do not use fixed partition_length, because it would introduce super long partition; do it on the fly
         let mut config = PartitionConfig::new(vertex_num);
@@ -458,6 +476,7 @@ impl ExamplePartition for PhenomenologicalRotatedCodeTimePartition {
                 ));
             }
         }
+        config.partitions.last_mut().unwrap().append_by(future_vertex_num);
         config.fusions.clear();
         if !self.enable_tree_fusion || self.maximum_tree_leaf_size == 1 {
             for unit_index in partition_num..(2 * partition_num - 1) {
@@ -539,6 +558,141 @@ impl ExamplePartition for PhenomenologicalRotatedCodeTimePartition {
     }
 }
 
+/// partition a phenomenological (or circuit-level) planar code into a grid of units over space and time: the rows
+/// are split by `space_cuts` rows and the measurement rounds by `time_cuts` rounds, giving
+/// `(space_cuts + 1) * (time_cuts + 1)` units. The units are fused by a balanced tree that recursively halves the
+/// dimension with more units, whose fused unit owns the cutting row (or round) between the two halves. The vertices
+/// are reordered such that each unit owns a contiguous range between those of its children
+pub struct PhenomenologicalPlanarCodeGridPartition {
+    d: VertexNum,
+    noisy_measurements: VertexNum,
+    /// the number of cutting rows
+    space_cuts: usize,
+    /// the number of cutting rounds
+    time_cuts: usize,
+}
+
+impl PhenomenologicalPlanarCodeGridPartition {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(d: VertexNum, noisy_measurements: VertexNum, space_cuts: usize, time_cuts: usize) -> Self {
+        assert!(
+            2 * space_cuts < d as usize,
+            "at most (d - 1) / 2 space cuts, each unit needs at least one row"
+        );
+        assert!(
+            2 * time_cuts <= noisy_measurements as usize,
+            "at most (noisy_measurements + 1) / 2 time cuts, each unit needs at least one round"
+        );
+        Self {
+            d,
+            noisy_measurements,
+            space_cuts,
+            time_cuts,
+        }
+    }
+
+    /// the reordered vertices and the partition config, see [`ExamplePartition`]
+    #[allow(clippy::unnecessary_cast)]
+    fn build_grid(&self) -> (Vec<VertexIndex>, PartitionConfig) {
+        let (d, td) = (self.d, self.noisy_measurements + 1);
+        let mut builder = GridPartitionBuilder {
+            rows: GridPartitionBuilder::cutting_lines(d, self.space_cuts),
+            rounds: GridPartitionBuilder::cutting_lines(td, self.time_cuts),
+            row_vertex_num: d + 1,
+            round_vertex_num: (d + 1) * d,
+            unit_num: (self.space_cuts + 1) * (self.time_cuts + 1),
+            config: PartitionConfig::new((d + 1) * d * td),
+            reordered_vertices: vec![],
+        };
+        builder.config.partitions.clear();
+        builder.build((0, self.space_cuts + 1), (0, self.time_cuts + 1));
+        assert_eq!(builder.config.partitions.len(), builder.unit_num);
+        assert_eq!(builder.reordered_vertices.len(), ((d + 1) * d * td) as usize);
+        (builder.reordered_vertices, builder.config)
+    }
+}
+
+/// see [`PhenomenologicalPlanarCodeGridPartition`]; the grid unit `(k, l)` has the rows strictly between `rows[k]` and
+/// `rows[k + 1]` and the rounds strictly between `rounds[l]` and `rounds[l + 1]`
+struct GridPartitionBuilder {
+    rows: Vec<isize>,
+    rounds: Vec<isize>,
+    row_vertex_num: VertexNum,
+    round_vertex_num: VertexNum,
+    unit_num: usize,
+    config: PartitionConfig,
+    reordered_vertices: Vec<VertexIndex>,
+}
+
+impl GridPartitionBuilder {
+    /// evenly split `length` lines into `cuts + 1` pieces by `cuts` cutting lines, with two borders at `-1` and `length`
+    #[allow(clippy::unnecessary_cast)]
+    fn cutting_lines(length: VertexNum, cuts: usize) -> Vec<isize> {
+        let mut lines = vec![-1];
+        for k in 1..=cuts {
+            // the k-th cut follows k pieces of the `length - cuts` lines and the previous `k - 1` cuts
+            lines.push(((k * (length as usize - cuts)) / (cuts + 1) + k - 1) as isize);
+        }
+        lines.push(length as isize);
+        lines
+    }
+
+    /// append the vertices of the rows and rounds strictly between the given borders
+    #[allow(clippy::unnecessary_cast)]
+    fn push_vertices(&mut self, rows: (isize, isize), rounds: (isize, isize)) {
+        for t in (rounds.0 + 1)..rounds.1 {
+            for row in (rows.0 + 1)..rows.1 {
+                let bias = t as VertexIndex * self.round_vertex_num + row as VertexIndex * self.row_vertex_num;
+                self.reordered_vertices.extend(bias..bias + self.row_vertex_num);
+            }
+        }
+    }
+
+    /// build the block of grid units in the given ranges, pushing the vertices of its left child, the cutting line and
+    /// its right child in order; returns the unit index of the block. The leaves take the first unit indices in the
+    /// order they are pushed, and the fused units are indexed after all the leaves
+    #[allow(clippy::unnecessary_cast)]
+    fn build(&mut self, space: (usize, usize), time: (usize, usize)) -> usize {
+        let (space_count, time_count) = (space.1 - space.0, time.1 - time.0);
+        let block_rows = (self.rows[space.0], self.rows[space.1]);
+        let block_rounds = (self.rounds[time.0], self.rounds[time.1]);
+        if space_count == 1 && time_count == 1 {
+            let start = self.reordered_vertices.len() as VertexIndex;
+            self.push_vertices(block_rows, block_rounds);
+            let end = self.reordered_vertices.len() as VertexIndex;
+            self.config.partitions.push(VertexRange::new(start, end));
+            return self.config.partitions.len() - 1;
+        }
+        // halve the dimension with more units, preferring the time axis which is usually the longer one
+        let (left_index, right_index) = if time_count >= space_count {
+            let middle = time.0 + time_count / 2;
+            let cut = self.rounds[middle];
+            let left_index = self.build(space, (time.0, middle));
+            self.push_vertices(block_rows, (cut - 1, cut + 1));
+            (left_index, self.build(space, (middle, time.1)))
+        } else {
+            let middle = space.0 + space_count / 2;
+            let cut = self.rows[middle];
+            let left_index = self.build((space.0, middle), time);
+            self.push_vertices((cut - 1, cut + 1), block_rounds);
+            (left_index, self.build((middle, space.1), time))
+        };
+        self.config.fusions.push((left_index, right_index));
+        self.unit_num + self.config.fusions.len() - 1
+    }
+}
+
+impl ExamplePartition for PhenomenologicalPlanarCodeGridPartition {
+    fn build_reordered_vertices(&mut self, code: &dyn ExampleCode) -> Option<Vec<VertexIndex>> {
+        let (d, td) = (self.d, self.noisy_measurements + 1);
+        assert_eq!(code.vertex_num(), d * (d + 1) * td, "code size incompatible");
+        Some(self.build_grid().0)
+    }
+    fn build_partition(&mut self, _code: &dyn ExampleCode) -> PartitionConfig {
+        self.build_grid().1
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use super::super::dual_module::*;
@@ -737,6 +891,26 @@ pub mod tests {
         );
     }
 
+    /// phenomenological repetition code split into a chain of 4 along the time axis
+    #[test]
+    fn example_partition_basic_6() {
+        // cargo test example_partition_basic_6 -- --nocapture
+        let visualize_filename = "example_partition_basic_6.json".to_string();
+        // rounds of 8 vertices with interfaces at rounds 3, 6 and 9: a measurement error touching each interface
+        // and a data error inside the first partition
+        let defect_vertices = vec![11, 12, 26, 34, 43, 51, 73, 81];
+        let half_weight = 500;
+        let noisy_measurements = 11;
+        example_partition_standard_syndrome(
+            &mut PhenomenologicalRepetitionCode::new(7, noisy_measurements, 0.1, half_weight),
+            visualize_filename,
+            defect_vertices,
+            true,
+            4 * half_weight,
+            PhenomenologicalPlanarCodeTimePartition::new_repetition(7, noisy_measurements, 4),
+        );
+    }
+
     /// a demo to show how partition works in phenomenological planar code
     #[test]
     fn example_partition_demo_1() {
@@ -856,4 +1030,55 @@ pub mod tests {
             PhenomenologicalPlanarCodeTimePartition::new_tree(7, noisy_measurements, 8, true, 3),
         );
     }
+
+    /// every edge must stay within a unit and its ancestors, and the parallel solver must agree with the serial one
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn example_partition_grid_1() {
+        // cargo test example_partition_grid_1 -- --nocapture
+        use super::super::mwpm_solver::*;
+        let (d, noisy_measurements) = (7, 7);
+        let codes: Vec<Box<dyn ExampleCode>> = vec![
+            Box::new(PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.05, 500)),
+            Box::new(CircuitLevelPlanarCode::new(d, noisy_measurements, 0.02, 500)),
+        ];
+        for mut code in codes {
+            let mut partition = PhenomenologicalPlanarCodeGridPartition::new(d, noisy_measurements, 2, 3);
+            let partition_info = partition.build_apply(code.as_mut()).info();
+            assert_eq!(partition_info.config.partitions.len(), 12);
+            // the fusion tree is balanced: every leaf is at depth 3 or 4
+            for leaf_index in 0..12 {
+                let mut depth = 0;
+                let mut unit_index = leaf_index;
+                while let Some(parent) = partition_info.units[unit_index].parent {
+                    unit_index = parent;
+                    depth += 1;
+                }
+                assert!(depth == 3 || depth == 4, "leaf {leaf_index} at depth {depth}");
+            }
+            let initializer = code.get_initializer();
+            for &(v1, v2, _) in initializer.weighted_edges.iter() {
+                let (u1, u2) = (partition_info.owner_of_vertex(v1), partition_info.owner_of_vertex(v2));
+                let is_related = |a: usize, b: usize| a == b || partition_info.units[a].descendants.contains(&b);
+                assert!(
+                    is_related(u1, u2) || is_related(u2, u1),
+                    "edge ({v1}, {v2}) across units {u1} and {u2}"
+                );
+            }
+            let mut serial_solver = SolverSerial::new(&initializer);
+            let mut parallel_solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+            for seed in 0..50 {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                serial_solver.solve(&syndrome_pattern);
+                parallel_solver.solve(&syndrome_pattern);
+                assert_eq!(
+                    parallel_solver.sum_dual_variables(),
+                    serial_solver.sum_dual_variables(),
+                    "seed {seed}"
+                );
+                serial_solver.clear();
+                parallel_solver.clear();
+            }
+        }
+    }
 }
@@ -0,0 +1,257 @@
+//! Exhaustive Matching
+//!
+//! The exact weight of the minimum-weight perfect matching of a syndrome by branch and bound, independent of any
+//! blossom algorithm, so that the solvers can be verified without the blossom V library. The defects are first split
+//! into clusters that never benefit from matching to each other, and each cluster is searched exhaustively, so the
+//! cost depends on the size of the largest cluster rather than the number of defects
+//!
+
+use super::complete_graph::*;
+use super::util::*;
+
+/// the largest cluster of defects that is searched exhaustively
+pub const EXHAUSTIVE_MAX_CLUSTER_SIZE: usize = 30;
+
+const UNREACHABLE: Weight = Weight::MAX / 4;
+
+/// the exact minimum weight of matching the defects with each other or with the virtual vertices, given the complete
+/// graph with the erasures and dynamic weights of the syndrome already loaded
+#[allow(clippy::unnecessary_cast)]
+pub fn exhaustive_mwpm_weight(
+    complete_graph: &mut CompleteGraph,
+    initializer: &SolverInitializer,
+    defect_vertices: &[VertexIndex],
+) -> Weight {
+    let mut is_virtual = vec![false; initializer.vertex_num as usize];
+    for &virtual_vertex in initializer.virtual_vertices.iter() {
+        is_virtual[virtual_vertex as usize] = true;
+    }
+    let defect_num = defect_vertices.len();
+    // the distance between each pair of defects, and from each defect to the nearest virtual vertex
+    let mut distances = vec![vec![UNREACHABLE; defect_num]; defect_num];
+    let mut boundary_distances = vec![UNREACHABLE; defect_num];
+    for (i, &vertex_index) in defect_vertices.iter().enumerate() {
+        let edges = complete_graph.all_edges(vertex_index);
+        for (j, peer_index) in defect_vertices.iter().enumerate() {
+            if let Some(&(_, weight)) = edges.get(peer_index) {
+                distances[i][j] = weight;
+            }
+        }
+        for (&peer_index, &(_, weight)) in edges.iter() {
+            if is_virtual[peer_index as usize] && weight < boundary_distances[i] {
+                boundary_distances[i] = weight;
+            }
+        }
+    }
+    // two defects are worth matching only if it's cheaper than matching both of them to the boundary
+    let mut cluster_of: Vec<usize> = (0..defect_num).collect();
+    fn find(cluster_of: &mut [usize], index: usize) -> usize {
+        if cluster_of[index] != index {
+            cluster_of[index] = find(cluster_of, cluster_of[index]);
+        }
+        cluster_of[index]
+    }
+    for i in 0..defect_num {
+        for j in i + 1..defect_num {
+            if distances[i][j] < UNREACHABLE && distances[i][j] < boundary_distances[i] + boundary_distances[j] {
+                let (root_i, root_j) = (find(&mut cluster_of, i), find(&mut cluster_of, j));
+                cluster_of[root_i] = root_j;
+            }
+        }
+    }
+    let mut clusters: Vec<Vec<usize>> = vec![vec![]; defect_num];
+    for i in 0..defect_num {
+        let root = find(&mut cluster_of, i);
+        clusters[root].push(i);
+    }
+    let mut total_weight = 0;
+    for cluster in clusters.iter().filter(|cluster| !cluster.is_empty()) {
+        assert!(
+            cluster.len() <= EXHAUSTIVE_MAX_CLUSTER_SIZE,
+            "a cluster of {} defects is too large to search exhaustively, the limit is {}",
+            cluster.len(),
+            EXHAUSTIVE_MAX_CLUSTER_SIZE
+        );
+        let search = ExhaustiveSearch::new(cluster, &distances, &boundary_distances);
+        total_weight += search.solve();
+    }
+    total_weight
+}
+
+/// the branch and bound over a cluster: the first unmatched defect is matched either to the boundary or to another
+/// unmatched defect, in the order of increasing weight; the lower bound charges every unmatched defect the cheaper of its
+/// boundary weight and half of its cheapest pair, which never overestimates because a pair is paid by both defects
+struct ExhaustiveSearch {
+    boundary_distances: Vec<Weight>,
+    /// the candidate partners of each defect sorted by weight
+    partners: Vec<Vec<(usize, Weight)>>,
+    /// twice the lower bound of each defect, to avoid halving the weights
+    double_lower_bounds: Vec<Weight>,
+    matched: Vec<bool>,
+    best: Weight,
+}
+
+impl ExhaustiveSearch {
+    fn new(cluster: &[usize], distances: &[Vec<Weight>], boundary_distances: &[Weight]) -> Self {
+        let boundary_distances: Vec<Weight> = cluster.iter().map(|&i| boundary_distances[i]).collect();
+        let mut partners: Vec<Vec<(usize, Weight)>> = vec![];
+        let mut double_lower_bounds = vec![];
+        for (local_i, &i) in cluster.iter().enumerate() {
+            let mut candidates: Vec<(usize, Weight)> = cluster
+                .iter()
+                .enumerate()
+                .filter(|&(local_j, &j)| local_j != local_i && distances[i][j] < UNREACHABLE)
+                .map(|(local_j, &j)| (local_j, distances[i][j]))
+                .collect();
+            candidates.sort_by_key(|&(_, weight)| weight);
+            let cheapest_pair = candidates.first().map_or(UNREACHABLE, |&(_, weight)| weight);
+            double_lower_bounds.push((2 * boundary_distances[local_i]).min(cheapest_pair));
+            partners.push(candidates);
+        }
+        Self {
+            boundary_distances,
+            partners,
+            double_lower_bounds,
+            matched: vec![false; cluster.len()],
+            best: UNREACHABLE,
+        }
+    }
+
+    fn solve(mut self) -> Weight {
+        let double_lower_bound = self.double_lower_bounds.iter().sum();
+        self.search(0, 0, double_lower_bound);
+        assert!(
+            self.best < UNREACHABLE,
+            "no perfect matching exists, e.g. odd defects without boundary"
+        );
+        self.best
+    }
+
+    /// `double_lower_bound` is twice the lower bound of the unmatched defects
+    fn search(&mut self, first: usize, weight: Weight, double_lower_bound: Weight) {
+        if 2 * weight + double_lower_bound >= 2 * self.best {
+            return;
+        }
+        let Some(i) = (first..self.matched.len()).find(|&i| !self.matched[i]) else {
+            self.best = weight;
+            return;
+        };
+        self.matched[i] = true;
+        let remaining = double_lower_bound - self.double_lower_bounds[i];
+        if self.boundary_distances[i] < UNREACHABLE {
+            self.search(i + 1, weight + self.boundary_distances[i], remaining);
+        }
+        for partner_index in 0..self.partners[i].len() {
+            let (j, pair_weight) = self.partners[i][partner_index];
+            if self.matched[j] {
+                continue;
+            }
+            self.matched[j] = true;
+            self.search(i + 1, weight + pair_weight, remaining - self.double_lower_bounds[j]);
+            self.matched[j] = false;
+        }
+        self.matched[i] = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::*;
+
+    fn exhaustive_weight(code: &dyn ExampleCode, syndrome_pattern: &SyndromePattern) -> Weight {
+        let initializer = code.get_initializer();
+        let mut complete_graph = CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges);
+        complete_graph.load_erasures(&syndrome_pattern.erasures);
+        exhaustive_mwpm_weight(&mut complete_graph, &initializer, &syndrome_pattern.defect_vertices)
+    }
+
+    #[test]
+    fn exhaustive_mwpm_weight_1() {
+        // cargo test exhaustive_mwpm_weight_1 -- --nocapture
+        // vertices 0..6 form a chain between the virtual vertices 7 (left) and 6 (right), each edge of weight 1000
+        let code = CodeCapacityRepetitionCode::new(7, 0.1, 500);
+        assert_eq!(exhaustive_weight(&code, &SyndromePattern::new_vertices(vec![])), 0);
+        assert_eq!(exhaustive_weight(&code, &SyndromePattern::new_vertices(vec![1, 2])), 1000);
+        // matching to the boundaries costs 2000 + 2000
+        assert_eq!(exhaustive_weight(&code, &SyndromePattern::new_vertices(vec![1, 4])), 3000);
+        assert_eq!(exhaustive_weight(&code, &SyndromePattern::new_vertices(vec![2])), 3000);
+    }
+
+    /// the same weight as the blossom algorithm on random syndromes with erasures
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn exhaustive_mwpm_weight_2() {
+        // cargo test exhaustive_mwpm_weight_2 -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(5, 4, 0.03, 500);
+        code.set_erasure_probability(0.01);
+        let initializer = code.get_initializer();
+        let mut complete_graph = CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges);
+        for seed in 0..100 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let expected = {
+                let mut initializer = initializer.clone();
+                for &edge_index in syndrome_pattern.erasures.iter() {
+                    initializer.weighted_edges[edge_index as usize].2 = 0;
+                }
+                let mwpm_result = super::super::blossom_v_mwpm(&initializer, &syndrome_pattern.defect_vertices);
+                super::super::detailed_matching(&initializer, &syndrome_pattern.defect_vertices, &mwpm_result)
+                    .iter()
+                    .map(|detail| detail.weight)
+                    .sum::<Weight>()
+            };
+            complete_graph.reset();
+            complete_graph.load_erasures(&syndrome_pattern.erasures);
+            let weight = exhaustive_mwpm_weight(&mut complete_graph, &initializer, &syndrome_pattern.defect_vertices);
+            assert_eq!(weight, expected, "seed {seed}");
+        }
+    }
+
+    /// a code without boundary, where all the defects form a single cluster
+    #[test]
+    fn exhaustive_mwpm_weight_3() {
+        // cargo test exhaustive_mwpm_weight_3 -- --nocapture
+        let code = CodeCapacityToricCode::new(7, 0.1, 500);
+        // vertex index is row * 7 + column, and the defects are matched around the torus
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![0, 6, 21, 27]);
+        assert_eq!(exhaustive_weight(&code, &syndrome_pattern), 2000);
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![0, 3, 45, 48]);
+        assert_eq!(exhaustive_weight(&code, &syndrome_pattern), 2000 + 1000);
+    }
+
+    /// the solvers verified without blossom V; the parallel solver doesn't support erasures
+    #[test]
+    fn exhaustive_verifier_1() {
+        // cargo test exhaustive_verifier_1 -- --nocapture
+        use super::super::cli::*;
+        use super::super::example_partition::*;
+        use super::super::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(5, 4, 0.03, 500);
+        code.set_erasure_probability(0.01);
+        let initializer = code.get_initializer();
+        let mut codes: Vec<(Box<dyn ExampleCode>, Box<dyn PrimalDualSolver>)> =
+            vec![(Box::new(code), Box::new(SolverSerial::new(&initializer)))];
+        let mut code = PhenomenologicalPlanarCode::new(5, 4, 0.03, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new(5, 4, 2).build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let solver = SolverParallel::new(&initializer, &partition_config.info(), json!({}));
+        codes.push((Box::new(code), Box::new(solver)));
+        for (mut code, mut solver) in codes {
+            let mut verifier = Verifier::Exhaustive.build(&code.get_initializer());
+            for seed in 0..50 {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                solver.solve(&syndrome_pattern);
+                verifier.verify(&mut solver, &syndrome_pattern, None);
+                solver.clear();
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "no perfect matching exists")]
+    fn exhaustive_mwpm_weight_4() {
+        // cargo test exhaustive_mwpm_weight_4 -- --nocapture
+        let code = CodeCapacityToricCode::new(7, 0.1, 500);
+        exhaustive_weight(&code, &SyndromePattern::new_vertices(vec![0, 1, 2]));
+    }
+}
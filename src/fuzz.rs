@@ -0,0 +1,481 @@
+//! Fuzzer
+//!
+//! Generates random decoding graphs, partitions and syndromes, and asserts that the serial, the dual-parallel and the
+//! parallel solvers agree on the weight of the minimum-weight perfect matching. This is the most effective way to find
+//! bugs in the fusion logic, which is only exercised by rare combinations of blossoms across the partition boundaries.
+//!
+//! The graphs are planar-ish grids so that they can always be partitioned: each row has a virtual vertex at both ends,
+//! and edges only connect vertices in the same row or in adjacent rows, possibly diagonally. The graph is partitioned
+//! by interface rows, each owned by a fusion unit that fuses the partitions above and below it.
+//! A failing case is greedily shrunk by removing partitions, rows, columns, defects and edges and by simplifying the
+//! weights, as long as it still fails, so that it arrives as a minimal reproducer.
+//!
+
+use super::mwpm_solver::*;
+use super::util::*;
+use crate::rand_xoshiro::rand_core::SeedableRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// a vertex in the grid, (row, column)
+pub type FuzzPosition = (usize, usize);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FuzzConfig {
+    /// maximum number of rows in the grid
+    #[serde(default = "fuzz_default_configs::max_rows")]
+    pub max_rows: usize,
+    /// maximum number of columns in the grid, including the two virtual columns
+    #[serde(default = "fuzz_default_configs::max_columns")]
+    pub max_columns: usize,
+    /// maximum number of partitions
+    #[serde(default = "fuzz_default_configs::max_partitions")]
+    pub max_partitions: usize,
+    /// maximum half weight of edges
+    #[serde(default = "fuzz_default_configs::max_half_weight")]
+    pub max_half_weight: Weight,
+    /// the probability of having a diagonal edge between adjacent rows
+    #[serde(default = "fuzz_default_configs::diagonal_probability")]
+    pub diagonal_probability: f64,
+}
+
+pub mod fuzz_default_configs {
+    use super::*;
+    pub fn max_rows() -> usize {
+        8
+    }
+    pub fn max_columns() -> usize {
+        8
+    }
+    pub fn max_partitions() -> usize {
+        4
+    }
+    pub fn max_half_weight() -> Weight {
+        5
+    }
+    pub fn diagonal_probability() -> f64 {
+        0.2
+    }
+}
+
+impl Default for FuzzConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+/// a decoding problem generated by the fuzzer, in grid coordinates so that it's easy to shrink
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FuzzCase {
+    /// number of rows
+    pub rows: usize,
+    /// number of columns, where the first and the last column are virtual vertices
+    pub columns: usize,
+    /// weighted edges between vertices in the same row or in adjacent rows
+    pub edges: Vec<(FuzzPosition, FuzzPosition, Weight)>,
+    /// the rows owned by fusion units, strictly increasing and never adjacent, first or last
+    pub interface_rows: Vec<usize>,
+    /// the defect vertices, which are never virtual
+    pub defects: Vec<FuzzPosition>,
+}
+
+/// a failing case found by the fuzzer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzFailure {
+    /// the seed to generate the original case
+    pub seed: u64,
+    /// the failure message of the minimized case
+    pub message: String,
+    /// the case as generated
+    pub original: FuzzCase,
+    /// the minimized case that still fails
+    pub minimized: FuzzCase,
+    /// the version, the hash of the fuzzer config and the seed; absent in entries written by older versions
+    #[serde(default)]
+    pub fingerprint: Option<OutputFingerprint>,
+}
+
+impl FuzzCase {
+    /// generate a random case
+    pub fn random(config: &FuzzConfig, rng: &mut DeterministicRng) -> Self {
+        assert!(config.max_rows >= 1 && config.max_columns >= 3 && config.max_partitions >= 1);
+        assert!(config.max_half_weight >= 1);
+        let rows = rng.gen_range(1..=config.max_rows);
+        let columns = rng.gen_range(3..=config.max_columns);
+        let mut case = Self {
+            rows,
+            columns,
+            edges: vec![],
+            interface_rows: vec![],
+            defects: vec![],
+        };
+        let edge_probability = rng.gen_range(0.6..=1.);
+        let mut candidates = vec![];
+        for row in 0..rows {
+            for column in 0..columns {
+                if column + 1 < columns {
+                    // always connect each row to the boundaries, so that every defect can be matched
+                    candidates.push(((row, column), (row, column + 1), 1.));
+                }
+                if row + 1 < rows {
+                    candidates.push(((row, column), (row + 1, column), edge_probability));
+                    if column + 1 < columns {
+                        candidates.push(((row, column), (row + 1, column + 1), config.diagonal_probability));
+                        candidates.push(((row, column + 1), (row + 1, column), config.diagonal_probability));
+                    }
+                }
+            }
+        }
+        for (a, b, probability) in candidates {
+            if !(case.is_virtual(a) && case.is_virtual(b)) && rng.gen_bool(probability) {
+                let weight = 2 * rng.gen_range(1..=config.max_half_weight);
+                case.edges.push((a, b, weight));
+            }
+        }
+        let partition_num = rng.gen_range(1..=config.max_partitions);
+        for row in 1..rows.saturating_sub(1) {
+            if case.interface_rows.len() + 1 < partition_num
+                && case.interface_rows.last().map_or(true, |&last| row > last + 1)
+                && rng.gen_bool(0.5)
+            {
+                case.interface_rows.push(row);
+            }
+        }
+        let defect_probability = rng.gen_range(0.05..=0.5);
+        for row in 0..rows {
+            for column in 1..columns - 1 {
+                if rng.gen_bool(defect_probability) {
+                    case.defects.push((row, column));
+                }
+            }
+        }
+        case
+    }
+
+    pub fn is_virtual(&self, position: FuzzPosition) -> bool {
+        position.1 == 0 || position.1 == self.columns - 1
+    }
+
+    /// every defect must be able to match to a virtual vertex, otherwise the solvers never terminate
+    pub fn is_valid(&self) -> bool {
+        let mut neighbors: BTreeMap<FuzzPosition, Vec<FuzzPosition>> = BTreeMap::new();
+        for &(a, b, _) in self.edges.iter() {
+            neighbors.entry(a).or_default().push(b);
+            neighbors.entry(b).or_default().push(a);
+        }
+        let mut reachable = BTreeSet::new();
+        let mut stack: Vec<FuzzPosition> = (0..self.rows).flat_map(|row| [(row, 0), (row, self.columns - 1)]).collect();
+        while let Some(position) = stack.pop() {
+            if reachable.insert(position) {
+                stack.extend(neighbors.get(&position).into_iter().flatten());
+            }
+        }
+        self.defects.iter().all(|position| reachable.contains(position))
+    }
+
+    pub fn vertex_index(&self, position: FuzzPosition) -> VertexIndex {
+        (position.0 * self.columns + position.1) as VertexIndex
+    }
+
+    pub fn vertex_num(&self) -> VertexNum {
+        (self.rows * self.columns) as VertexNum
+    }
+
+    pub fn get_initializer(&self) -> SolverInitializer {
+        let weighted_edges = self
+            .edges
+            .iter()
+            .map(|&(a, b, weight)| (self.vertex_index(a), self.vertex_index(b), weight))
+            .collect();
+        let virtual_vertices = (0..self.rows)
+            .flat_map(|row| [(row, 0), (row, self.columns - 1)])
+            .map(|position| self.vertex_index(position))
+            .collect();
+        SolverInitializer::new(self.vertex_num(), weighted_edges, virtual_vertices)
+    }
+
+    /// the partitions are separated by the interface rows, and fused sequentially from top to bottom
+    pub fn get_partition_config(&self) -> PartitionConfig {
+        let mut config = PartitionConfig::new(self.vertex_num());
+        let partition_num = self.interface_rows.len() + 1;
+        config.partitions.clear();
+        for partition_index in 0..partition_num {
+            let start_row = if partition_index == 0 {
+                0
+            } else {
+                self.interface_rows[partition_index - 1] + 1
+            };
+            let end_row = if partition_index + 1 == partition_num {
+                self.rows
+            } else {
+                self.interface_rows[partition_index]
+            };
+            config.partitions.push(VertexRange::new(
+                self.vertex_index((start_row, 0)),
+                self.vertex_index((end_row, 0)),
+            ));
+        }
+        for unit_index in partition_num..(2 * partition_num - 1) {
+            if unit_index == partition_num {
+                config.fusions.push((0, 1));
+            } else {
+                config.fusions.push((unit_index - 1, unit_index - partition_num + 1));
+            }
+        }
+        config
+    }
+
+    pub fn get_syndrome(&self) -> SyndromePattern {
+        let mut defect_vertices: Vec<VertexIndex> =
+            self.defects.iter().map(|&position| self.vertex_index(position)).collect();
+        defect_vertices.sort_unstable();
+        SyndromePattern::new_vertices(defect_vertices)
+    }
+
+    /// solve the case with all the solvers, returning an error message if any of them panics or they disagree
+    #[allow(clippy::unnecessary_cast)]
+    pub fn check(&self) -> Result<(), String> {
+        assert!(self.is_valid(), "some defects cannot be matched");
+        let initializer = self.get_initializer();
+        let partition_info = self.get_partition_config().info();
+        let syndrome_pattern = self.get_syndrome();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let solvers: Vec<(&str, Box<dyn PrimalDualSolver>)> = vec![
+                ("serial", Box::new(SolverSerial::new(&initializer))),
+                (
+                    "dual parallel",
+                    Box::new(SolverDualParallel::new(&initializer, &partition_info, json!({}))),
+                ),
+                (
+                    "parallel",
+                    Box::new(SolverParallel::new(&initializer, &partition_info, json!({}))),
+                ),
+            ];
+            let mut weights = vec![];
+            for (name, mut solver) in solvers {
+                solver.solve(&syndrome_pattern);
+                let subgraph_weight: Weight = solver
+                    .subgraph()
+                    .iter()
+                    .map(|&edge_index| initializer.weighted_edges[edge_index as usize].2)
+                    .sum();
+                weights.push((name, solver.sum_dual_variables(), subgraph_weight));
+            }
+            weights
+        }));
+        let weights = result.map_err(|payload| {
+            if let Some(message) = payload.downcast_ref::<&str>() {
+                format!("panicked: {}", message)
+            } else if let Some(message) = payload.downcast_ref::<String>() {
+                format!("panicked: {}", message)
+            } else {
+                "panicked".to_string()
+            }
+        })?;
+        let expected = weights[0].1;
+        for &(_, dual_weight, subgraph_weight) in weights.iter() {
+            if dual_weight != expected || subgraph_weight != expected {
+                return Err(format!(
+                    "solvers disagree, (name, sum of dual variables, subgraph weight): {:?}",
+                    weights
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// remove a row together with the edges and defects on it
+    pub fn remove_row(&self, row: usize) -> Self {
+        assert!(self.rows > 1 && row < self.rows);
+        let shift = |position: FuzzPosition| {
+            if position.0 > row {
+                (position.0 - 1, position.1)
+            } else {
+                position
+            }
+        };
+        let mut case = Self {
+            rows: self.rows - 1,
+            columns: self.columns,
+            edges: self
+                .edges
+                .iter()
+                .filter(|(a, b, _)| a.0 != row && b.0 != row)
+                .map(|&(a, b, weight)| (shift(a), shift(b), weight))
+                .collect(),
+            interface_rows: vec![],
+            defects: self
+                .defects
+                .iter()
+                .filter(|position| position.0 != row)
+                .map(|&position| shift(position))
+                .collect(),
+        };
+        for &interface_row in self.interface_rows.iter() {
+            if interface_row == row {
+                continue;
+            }
+            let interface_row = if interface_row > row {
+                interface_row - 1
+            } else {
+                interface_row
+            };
+            if interface_row >= 1
+                && interface_row + 1 < case.rows
+                && case.interface_rows.last().map_or(true, |&last| interface_row > last + 1)
+            {
+                case.interface_rows.push(interface_row);
+            }
+        }
+        case
+    }
+
+    /// remove a column of real vertices together with the edges and defects on it
+    pub fn remove_column(&self, column: usize) -> Self {
+        assert!(self.columns > 3 && column >= 1 && column + 1 < self.columns);
+        let shift = |position: FuzzPosition| {
+            if position.1 > column {
+                (position.0, position.1 - 1)
+            } else {
+                position
+            }
+        };
+        Self {
+            rows: self.rows,
+            columns: self.columns - 1,
+            edges: self
+                .edges
+                .iter()
+                .filter(|(a, b, _)| a.1 != column && b.1 != column)
+                .map(|&(a, b, weight)| (shift(a), shift(b), weight))
+                .collect(),
+            interface_rows: self.interface_rows.clone(),
+            defects: self
+                .defects
+                .iter()
+                .filter(|position| position.1 != column)
+                .map(|&position| shift(position))
+                .collect(),
+        }
+    }
+
+    /// smaller cases derived from this one, the most aggressive ones first
+    pub fn shrink_candidates(&self) -> Vec<Self> {
+        let mut candidates = vec![];
+        for index in 0..self.interface_rows.len() {
+            let mut case = self.clone();
+            case.interface_rows.remove(index);
+            candidates.push(case);
+        }
+        if self.rows > 1 {
+            for row in 0..self.rows {
+                candidates.push(self.remove_row(row));
+            }
+        }
+        if self.columns > 3 {
+            for column in 1..self.columns - 1 {
+                candidates.push(self.remove_column(column));
+            }
+        }
+        for index in 0..self.defects.len() {
+            let mut case = self.clone();
+            case.defects.remove(index);
+            candidates.push(case);
+        }
+        for index in 0..self.edges.len() {
+            let mut case = self.clone();
+            case.edges.remove(index);
+            candidates.push(case);
+        }
+        for index in 0..self.edges.len() {
+            let weight = self.edges[index].2;
+            if weight > 2 {
+                let mut case = self.clone();
+                case.edges[index].2 = (weight / 4 * 2).max(2);
+                candidates.push(case);
+            }
+        }
+        candidates
+    }
+
+    /// greedily shrink the case as long as it is valid and still fails
+    pub fn shrink(&self, mut fails: impl FnMut(&Self) -> bool) -> Self {
+        let mut case = self.clone();
+        'shrink: loop {
+            for candidate in case.shrink_candidates() {
+                if candidate.is_valid() && fails(&candidate) {
+                    case = candidate;
+                    continue 'shrink;
+                }
+            }
+            return case;
+        }
+    }
+}
+
+/// run the fuzzer on `total_rounds` random cases, the i-th generated with seed `seed + i`;
+/// returns the first failure, minimized
+pub fn fuzz(config: &FuzzConfig, seed: u64, total_rounds: usize) -> Option<FuzzFailure> {
+    for round in 0..total_rounds as u64 {
+        let case_seed = seed.wrapping_add(round);
+        let mut rng = DeterministicRng::seed_from_u64(case_seed);
+        let case = FuzzCase::random(config, &mut rng);
+        if case.check().is_err() {
+            let minimized = case.shrink(|candidate| candidate.check().is_err());
+            return Some(FuzzFailure {
+                seed: case_seed,
+                message: minimized.check().unwrap_err(),
+                original: case,
+                minimized,
+                fingerprint: Some(OutputFingerprint::new(&json!(config)).with_seed(case_seed)),
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzz_solvers_1() {
+        // cargo test fuzz_solvers_1 -- --nocapture
+        let config = FuzzConfig::default();
+        if let Some(failure) = fuzz(&config, 0, 1000) {
+            panic!("{}", serde_json::to_string_pretty(&failure).unwrap());
+        }
+    }
+
+    #[test]
+    fn fuzz_shrink_1() {
+        // cargo test fuzz_shrink_1 -- --nocapture
+        let config = FuzzConfig {
+            max_rows: 10,
+            max_columns: 10,
+            ..Default::default()
+        };
+        let mut rng = DeterministicRng::seed_from_u64(1);
+        let case = (0..)
+            .map(|_| FuzzCase::random(&config, &mut rng))
+            .find(|case| case.defects.len() >= 4 && case.interface_rows.len() >= 2)
+            .unwrap();
+        // an artificial failure: at least 2 defects and an interface row
+        let minimized = case.shrink(|case| case.defects.len() >= 2 && !case.interface_rows.is_empty());
+        assert_eq!(minimized.defects.len(), 2);
+        assert_eq!(minimized.interface_rows.len(), 1);
+        assert_eq!(minimized.rows, 3);
+        assert!(minimized.columns <= 4); // the two defects may be in different columns
+                                         // only the edges connecting the defects to the boundary are kept
+        for index in 0..minimized.edges.len() {
+            let mut case = minimized.clone();
+            case.edges.remove(index);
+            assert!(!case.is_valid());
+        }
+        assert!(minimized.edges.iter().all(|&(_, _, weight)| weight == 2));
+        assert!(minimized.is_valid());
+    }
+}
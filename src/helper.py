@@ -11,6 +11,7 @@ fb = None
 def register(module):
     global fb
     fb = module
+    module.Matching = Matching
 
 """
 start a server to host the visualizer websites locally
@@ -126,3 +127,160 @@ def local_render_visualizer(
     commands = ["node", "index.js", url, f"{width}", f"{height}", image_filename]
     print(f"[run] {commands}")
     run_command_get_stdout(commands, cwd=renderer_folder)
+
+
+"""
+a drop-in replacement of `pymatching.Matching` for decoding from a check matrix, so that existing pymatching users can switch
+decoders by changing `from pymatching import Matching` to `from fusion_blossom import Matching`.
+floating-point weights are scaled to even integers such that the maximum weight becomes `2 * max_half_weight`.
+when `partition_info` is given, the decoding graph is solved by `SolverParallel` instead of `SolverSerial`.
+"""
+class Matching:
+    def __init__(self, H=None, weights=None, error_probabilities=None, repetitions=None, timelike_weights=None
+            , measurement_error_probabilities=None, max_half_weight=5000, partition_info=None, primal_dual_config=None):
+        self.solver = None
+        if H is not None:
+            self.load_from_check_matrix(H, weights, error_probabilities, repetitions, timelike_weights
+                , measurement_error_probabilities, max_half_weight, partition_info, primal_dual_config)
+
+    @staticmethod
+    def from_check_matrix(H, weights=None, error_probabilities=None, repetitions=None, timelike_weights=None
+            , measurement_error_probabilities=None, max_half_weight=5000, partition_info=None, primal_dual_config=None):
+        return Matching(H, weights, error_probabilities, repetitions, timelike_weights, measurement_error_probabilities
+            , max_half_weight, partition_info, primal_dual_config)
+
+    @staticmethod
+    def _broadcast(value, length, name):
+        if value is None:
+            return None
+        if isinstance(value, (int, float)):
+            return [float(value)] * length
+        value = [float(e) for e in value]
+        if len(value) != length:
+            raise ValueError(f"{name} has length {len(value)} but {length} is expected")
+        return value
+
+    @staticmethod
+    def _weights_of(weights, error_probabilities, length, name):
+        weights = Matching._broadcast(weights, length, name + "weights")
+        if weights is not None:
+            return weights
+        import math
+        error_probabilities = Matching._broadcast(error_probabilities, length, name + "error_probabilities")
+        if error_probabilities is not None:
+            return [math.log((1 - p) / p) if p > 0 else math.inf for p in error_probabilities]
+        return [1.] * length
+
+    @staticmethod
+    def _check_matrix_columns(H):
+        if hasattr(H, "tocsc"):  # scipy sparse matrix
+            H = H.tocsc()
+            H.eliminate_zeros()
+            columns = [[int(H.indices[k]) for k in range(H.indptr[j], H.indptr[j + 1]) if H.data[k] % 2]
+                for j in range(H.shape[1])]
+            return H.shape[0], columns
+        rows = [[int(e) for e in row] for row in H]
+        num_checks = len(rows)
+        num_columns = len(rows[0]) if num_checks > 0 else 0
+        columns = [[i for i in range(num_checks) if rows[i][j] % 2] for j in range(num_columns)]
+        return num_checks, columns
+
+    def load_from_check_matrix(self, H, weights=None, error_probabilities=None, repetitions=None, timelike_weights=None
+            , measurement_error_probabilities=None, max_half_weight=5000, partition_info=None, primal_dual_config=None):
+        num_checks, columns = Matching._check_matrix_columns(H)
+        self.num_checks = num_checks
+        self.repetitions = 1 if repetitions is None else repetitions
+        self.num_fault_ids = len(columns)
+        column_weights = Matching._weights_of(weights, error_probabilities, self.num_fault_ids, "")
+        boundary = num_checks * self.repetitions  # a single virtual vertex as the boundary
+        edges = {}  # (left, right) -> (weight, fault_id); parallel edges are merged by keeping the lighter one
+        def add_edge(left, right, weight, fault_id):
+            if weight < 0:
+                raise ValueError("negative weights are not supported")
+            if weight == float("inf"):
+                return  # the error never happens
+            key = (min(left, right), max(left, right))
+            if key not in edges or weight < edges[key][0]:
+                edges[key] = (weight, fault_id)
+        for t in range(self.repetitions):
+            bias = t * num_checks
+            for fault_id, checks in enumerate(columns):
+                if len(checks) == 1:
+                    add_edge(bias + checks[0], boundary, column_weights[fault_id], fault_id)
+                elif len(checks) == 2:
+                    add_edge(bias + checks[0], bias + checks[1], column_weights[fault_id], fault_id)
+                elif len(checks) > 2:
+                    raise ValueError(f"column {fault_id} of the check matrix has {len(checks)} non-zero elements, "
+                        + "but a matching decoder requires at most 2")
+        if self.repetitions > 1:
+            measurement_weights = Matching._weights_of(timelike_weights, measurement_error_probabilities, num_checks
+                , "timelike_")
+            for t in range(self.repetitions - 1):
+                for i in range(num_checks):
+                    add_edge(t * num_checks + i, (t + 1) * num_checks + i, measurement_weights[i], None)
+        self.edges = [(left, right, weight, fault_id) for (left, right), (weight, fault_id) in edges.items()]
+        self.num_detectors = boundary
+        initializer = fb.SolverInitializer.from_float_weights(boundary + 1
+            , [(left, right, weight) for left, right, weight, _ in self.edges], [boundary], max_half_weight)
+        if partition_info is None:
+            self.solver = fb.SolverSerial(initializer)
+        else:
+            self.solver = fb.SolverParallel(initializer, partition_info, primal_dual_config or {})
+
+    @property
+    def num_edges(self):
+        return len(self.edges)
+
+    def _decode_defects(self, defect_vertices):
+        self.solver.solve(fb.SyndromePattern(defect_vertices))
+        subgraph = self.solver.subgraph()
+        self.solver.clear()
+        correction = [0] * self.num_fault_ids
+        weight = 0.
+        for edge_index in subgraph:
+            _, _, edge_weight, fault_id = self.edges[edge_index]
+            weight += edge_weight
+            if fault_id is not None:
+                correction[fault_id] ^= 1
+        return correction, weight
+
+    def _defects_of(self, z):
+        if len(z) > 0 and hasattr(z[0], "__len__"):  # shape (num_checks, repetitions)
+            return [t * self.num_checks + i for i in range(self.num_checks) for t in range(self.repetitions)
+                if int(z[i][t]) % 2]
+        flat = [int(e) for e in z]
+        if len(flat) > self.num_detectors:
+            raise ValueError(f"syndrome has length {len(flat)} but there are only {self.num_detectors} detectors")
+        return [vertex_index for vertex_index, e in enumerate(flat) if e % 2]
+
+    @staticmethod
+    def _as_array(value):
+        try:
+            import numpy
+            return numpy.array(value, dtype=numpy.uint8)
+        except ImportError:
+            return value
+
+    def decode(self, z, return_weight=False):
+        assert self.solver is not None, "load a check matrix first"
+        correction, weight = self._decode_defects(self._defects_of(z))
+        correction = Matching._as_array(correction)
+        if return_weight:
+            return correction, weight
+        return correction
+
+    def decode_batch(self, shots, return_weights=False):
+        assert self.solver is not None, "load a check matrix first"
+        predictions = []
+        weights = []
+        for shot in shots:
+            correction, weight = self._decode_defects(self._defects_of(shot))
+            predictions.append(correction)
+            weights.append(weight)
+        predictions = Matching._as_array(predictions)
+        if return_weights:
+            return predictions, weights
+        return predictions
+
+    def __repr__(self):
+        return f"<fusion_blossom.Matching object with {self.num_detectors} detectors, {self.num_edges} edges>"
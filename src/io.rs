@@ -0,0 +1,198 @@
+//! Input and Output
+//!
+//! Readers of detection events produced by other tools, so that they can be decoded without a conversion layer.
+//! [`StimDetectionReader`] streams the output of `stim detect` in the `b8` and `dets` formats as [`SyndromePattern`]s,
+//! e.g. from a file or from the standard input when piping `stim detect --out_format b8` into fusion-blossom
+//!
+
+use super::util::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StimDetectionFormat {
+    /// bit-packed shots of `(num_detectors + num_observables + 7) / 8` bytes each, little endian within a byte
+    B8,
+    /// one shot per line, e.g. `shot D0 D5 L1`
+    Dets,
+}
+
+impl StimDetectionFormat {
+    /// guess from the extension of the file, `.b8` or `.dets`
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        if filename.ends_with(".b8") {
+            Some(Self::B8)
+        } else if filename.ends_with(".dets") {
+            Some(Self::Dets)
+        } else {
+            None
+        }
+    }
+}
+
+/// a shot of detection events
+#[derive(Debug, Clone)]
+pub struct StimDetectionShot {
+    /// the detectors that fired, as defect vertices
+    pub syndrome_pattern: SyndromePattern,
+    /// the observables that flipped, if they are appended to the shots (`stim detect --append_observables`)
+    pub observables: Vec<usize>,
+}
+
+pub struct StimDetectionReader<R: BufRead> {
+    pub reader: R,
+    pub format: StimDetectionFormat,
+    pub num_detectors: usize,
+    /// the number of observables appended after the detectors of each shot; only needed by the `b8` format, where the
+    /// bits are otherwise indistinguishable from detectors
+    pub num_observables: usize,
+    buffer: Vec<u8>,
+    line: String,
+}
+
+impl StimDetectionReader<BufReader<File>> {
+    pub fn open(
+        filename: &str,
+        format: StimDetectionFormat,
+        num_detectors: usize,
+        num_observables: usize,
+    ) -> io::Result<Self> {
+        Ok(Self::new(
+            BufReader::new(File::open(filename)?),
+            format,
+            num_detectors,
+            num_observables,
+        ))
+    }
+}
+
+impl<R: BufRead> StimDetectionReader<R> {
+    pub fn new(reader: R, format: StimDetectionFormat, num_detectors: usize, num_observables: usize) -> Self {
+        Self {
+            reader,
+            format,
+            num_detectors,
+            num_observables,
+            buffer: vec![0; (num_detectors + num_observables + 7) / 8],
+            line: String::new(),
+        }
+    }
+
+    /// read the next shot, or `None` at the end of the stream
+    #[allow(clippy::unnecessary_cast)]
+    pub fn read_shot(&mut self) -> io::Result<Option<StimDetectionShot>> {
+        let mut defect_vertices = vec![];
+        let mut observables = vec![];
+        match self.format {
+            StimDetectionFormat::B8 => {
+                if self.reader.fill_buf()?.is_empty() {
+                    return Ok(None);
+                }
+                self.reader.read_exact(&mut self.buffer)?;
+                for (i, &byte) in self.buffer.iter().enumerate() {
+                    if byte == 0 {
+                        continue;
+                    }
+                    for j in 0..8 {
+                        if byte & (1 << j) != 0 {
+                            // little endian
+                            let bit = i * 8 + j;
+                            if bit < self.num_detectors {
+                                defect_vertices.push(bit as VertexIndex);
+                            } else if bit < self.num_detectors + self.num_observables {
+                                observables.push(bit - self.num_detectors);
+                            }
+                        }
+                    }
+                }
+            }
+            StimDetectionFormat::Dets => loop {
+                self.line.clear();
+                if self.reader.read_line(&mut self.line)? == 0 {
+                    return Ok(None);
+                }
+                let mut tokens = self.line.split_whitespace();
+                match tokens.next() {
+                    None => continue, // empty line
+                    Some("shot") => {}
+                    Some(token) => {
+                        return Err(invalid_data(format!(
+                            "expect `shot` at the beginning of a line, found `{token}`"
+                        )))
+                    }
+                }
+                for token in tokens {
+                    let parse_index = |index: &str| {
+                        index
+                            .parse::<usize>()
+                            .map_err(|_| invalid_data(format!("invalid index in `{token}`")))
+                    };
+                    if let Some(index) = token.strip_prefix('D') {
+                        let index = parse_index(index)?;
+                        if index >= self.num_detectors {
+                            return Err(invalid_data(format!(
+                                "detector {index} out of range, there are only {} detectors",
+                                self.num_detectors
+                            )));
+                        }
+                        defect_vertices.push(index as VertexIndex);
+                    } else if let Some(index) = token.strip_prefix('L') {
+                        observables.push(parse_index(index)?);
+                    } else {
+                        return Err(invalid_data(format!("unknown token `{token}`")));
+                    }
+                }
+                break;
+            },
+        }
+        Ok(Some(StimDetectionShot {
+            syndrome_pattern: SyndromePattern::new_vertices(defect_vertices),
+            observables,
+        }))
+    }
+}
+
+impl<R: BufRead> Iterator for StimDetectionReader<R> {
+    type Item = io::Result<SyndromePattern>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_shot()
+            .transpose()
+            .map(|shot| shot.map(|shot| shot.syndrome_pattern))
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_stim_detection_reader_1() {
+        // cargo test io_stim_detection_reader_1 -- --nocapture
+        // 10 detectors and 2 observables: shots {D0, D9, L1}, {}, {D3, L0}
+        let b8: &[u8] = &[0b0000_0001, 0b0000_1010, 0, 0, 0b0000_1000, 0b0000_0100];
+        let dets = "shot D0 D9 L1\nshot\n\nshot D3 L0\n";
+        for (format, content) in [(StimDetectionFormat::B8, b8), (StimDetectionFormat::Dets, dets.as_bytes())] {
+            let mut reader = StimDetectionReader::new(content, format, 10, 2);
+            let shots: Vec<_> = std::iter::from_fn(|| reader.read_shot().unwrap()).collect();
+            assert_eq!(shots.len(), 3);
+            assert_eq!(shots[0].syndrome_pattern.defect_vertices, vec![0, 9]);
+            assert_eq!(shots[0].observables, vec![1]);
+            assert!(shots[1].syndrome_pattern.defect_vertices.is_empty());
+            assert!(shots[1].observables.is_empty());
+            assert_eq!(shots[2].syndrome_pattern.defect_vertices, vec![3]);
+            assert_eq!(shots[2].observables, vec![0]);
+        }
+        // a truncated shot or an invalid line is an error
+        let mut reader = StimDetectionReader::new(&b8[..3], StimDetectionFormat::B8, 10, 2);
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        let mut reader = StimDetectionReader::new("shot D10\n".as_bytes(), StimDetectionFormat::Dets, 10, 2);
+        assert!(reader.next().unwrap().is_err());
+    }
+}
@@ -23,24 +23,50 @@ extern crate pyo3;
 pub extern crate qecp;
 extern crate rand;
 extern crate rayon;
+#[cfg(feature = "results_db")]
+extern crate rusqlite;
 extern crate urlencoding;
 extern crate weak_table;
 
+#[cfg(feature = "track_allocations")]
+pub mod allocation_tracker;
+pub mod analysis;
+#[cfg(feature = "results_db")]
+pub mod benchmark;
+pub mod blossom_rs;
 pub mod blossom_v;
+#[cfg(feature = "c_binding")]
+pub mod capi;
 pub mod cli;
 pub mod complete_graph;
+pub mod degeneracy;
 pub mod dual_module;
+pub mod dual_module_channel;
 pub mod dual_module_parallel;
+pub mod dual_module_rtl_adaptor;
 pub mod dual_module_serial;
+pub mod dual_module_testsuite;
+pub mod evaluation;
 pub mod example_codes;
 pub mod example_partition;
+pub mod exhaustive;
+pub mod fuzz;
+pub mod io;
+#[cfg(feature = "lock_order_audit")]
+pub mod lock_order_audit;
 pub mod mwpm_solver;
 pub mod pointers;
 pub mod primal_module;
 pub mod primal_module_parallel;
 pub mod primal_module_serial;
+pub mod primal_module_union_find;
+pub mod repro;
+pub mod service;
+pub mod syndrome_file;
 pub mod util;
 pub mod visualize;
+pub mod visualize_sink;
+pub mod visualize_svg;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
 
@@ -84,14 +110,24 @@ pub fn fusion_mwpm(initializer: &SolverInitializer, syndrome_pattern: &SyndromeP
     mwpm_solver::LegacySolverSerial::mwpm_solve(initializer, syndrome_pattern)
 }
 
-/// fall back to use blossom V library to solve MWPM (install blossom V required)
-#[allow(clippy::unnecessary_cast)]
+/// fall back to use blossom V library to solve MWPM; without blossom V, a slower pure-Rust implementation is used instead
 pub fn blossom_v_mwpm(initializer: &SolverInitializer, defect_vertices: &Vec<VertexIndex>) -> Vec<VertexIndex> {
-    // this feature will be automatically enabled if you install blossom V source code, see README.md for more information
-    if cfg!(not(feature = "blossom_v")) {
-        panic!("need blossom V library, see README.md")
-    }
-    // sanity check
+    // the blossom V feature will be automatically enabled if you install blossom V source code, see README.md
+    check_legacy_safe_weights(initializer);
+    let mut complete_graph = CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges);
+    blossom_v_mwpm_reuse(&mut complete_graph, initializer, defect_vertices)
+}
+
+/// solve MWPM with the pure-Rust blossom algorithm regardless of whether the blossom V library exists, see [`blossom_rs`]
+pub fn blossom_rs_mwpm(initializer: &SolverInitializer, defect_vertices: &[VertexIndex]) -> Vec<VertexIndex> {
+    check_legacy_safe_weights(initializer);
+    let mut complete_graph = CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges);
+    blossom_rs_mwpm_reuse(&mut complete_graph, initializer, defect_vertices)
+}
+
+/// the legacy matchers take `u32` weights and may sum up all of them
+#[allow(clippy::unnecessary_cast)]
+fn check_legacy_safe_weights(initializer: &SolverInitializer) {
     assert!(initializer.vertex_num > 1, "at least one vertex required");
     let max_safe_weight = ((i32::MAX as usize) / initializer.vertex_num as usize) as Weight;
     for (i, j, weight) in initializer.weighted_edges.iter() {
@@ -102,8 +138,6 @@ pub fn blossom_v_mwpm(initializer: &SolverInitializer, defect_vertices: &Vec<Ver
             );
         }
     }
-    let mut complete_graph = CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges);
-    blossom_v_mwpm_reuse(&mut complete_graph, initializer, defect_vertices)
 }
 
 #[allow(clippy::unnecessary_cast)]
@@ -111,6 +145,33 @@ pub fn blossom_v_mwpm_reuse(
     complete_graph: &mut CompleteGraph,
     initializer: &SolverInitializer,
     defect_vertices: &Vec<VertexIndex>,
+) -> Vec<VertexIndex> {
+    legacy_mwpm_reuse(
+        complete_graph,
+        initializer,
+        defect_vertices,
+        blossom_v::safe_minimum_weight_perfect_matching,
+    )
+}
+
+pub fn blossom_rs_mwpm_reuse(
+    complete_graph: &mut CompleteGraph,
+    initializer: &SolverInitializer,
+    defect_vertices: &[VertexIndex],
+) -> Vec<VertexIndex> {
+    legacy_mwpm_reuse(complete_graph, initializer, defect_vertices, |node_num, weighted_edges| {
+        blossom_rs::minimum_weight_perfect_matching(node_num, weighted_edges)
+    })
+}
+
+/// reduce the MWPM problem on the decoding graph to a perfect matching problem on the defect vertices and their
+/// corresponding virtual vertices, solved by `matcher` that returns the peer of each vertex
+#[allow(clippy::unnecessary_cast)]
+fn legacy_mwpm_reuse(
+    complete_graph: &mut CompleteGraph,
+    initializer: &SolverInitializer,
+    defect_vertices: &[VertexIndex],
+    matcher: impl FnOnce(usize, &Vec<(usize, usize, u32)>) -> Vec<usize>,
 ) -> Vec<VertexIndex> {
     // first collect virtual vertices and real vertices
     let mut is_virtual: Vec<bool> = (0..initializer.vertex_num).map(|_| false).collect();
@@ -128,9 +189,21 @@ pub fn blossom_v_mwpm_reuse(
         is_defect[defect_vertex as usize] = true;
         mapping_to_defect_vertices[defect_vertex as usize] = i;
     }
-    // for each real vertex, add a corresponding virtual vertex to be matched
+    // for each real vertex, add a corresponding virtual vertex to be matched; a graph without virtual vertices, e.g. the
+    // toric code, needs no such vertex but must have an even number of defect vertices
     let defect_num = defect_vertices.len();
-    let legacy_vertex_num = defect_num * 2;
+    let has_boundary = !initializer.virtual_vertices.is_empty();
+    if !has_boundary {
+        assert_eq!(
+            defect_num % 2,
+            0,
+            "odd number of defect vertices cannot be matched without virtual vertices"
+        );
+    }
+    if defect_num == 0 {
+        return vec![];
+    }
+    let legacy_vertex_num = if has_boundary { defect_num * 2 } else { defect_num };
     let mut legacy_weighted_edges = Vec::<(usize, usize, u32)>::new();
     let mut boundaries = Vec::<Option<(VertexIndex, Weight)>>::new();
     for (i, &defect_vertex) in defect_vertices.iter().enumerate() {
@@ -156,15 +229,17 @@ pub fn blossom_v_mwpm_reuse(
                 }
             }
         }
-        for j in (i + 1)..defect_num {
-            // virtual boundaries are always fully connected with weight 0
-            legacy_weighted_edges.push((i + defect_num, j + defect_num, 0));
+        if has_boundary {
+            for j in (i + 1)..defect_num {
+                // virtual boundaries are always fully connected with weight 0
+                legacy_weighted_edges.push((i + defect_num, j + defect_num, 0));
+            }
         }
     }
     // run blossom V to get matchings
     // println!("[debug] legacy_vertex_num: {:?}", legacy_vertex_num);
     // println!("[debug] legacy_weighted_edges: {:?}", legacy_weighted_edges);
-    let matchings = blossom_v::safe_minimum_weight_perfect_matching(legacy_vertex_num, &legacy_weighted_edges);
+    let matchings = matcher(legacy_vertex_num, &legacy_weighted_edges);
     let mut mwpm_result = Vec::new();
     for i in 0..defect_num {
         let j = matchings[i];
@@ -230,31 +305,8 @@ pub fn detailed_matching(
     details
 }
 
-#[cfg(feature = "python_binding")]
-macro_rules! include_visualize_file {
-    ($mapping:ident, $filepath:expr) => {
-        $mapping.insert($filepath.to_string(), include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/visualize/", $filepath)).to_string());
-    };
-    ($mapping:ident, $filepath:expr, $($other_filepath:expr),+) => {
-        include_visualize_file!($mapping, $filepath);
-        include_visualize_file!($mapping, $($other_filepath),+);
-    };
-}
-
 #[cfg(feature = "python_binding")]
 fn generate_visualizer_website(py: Python<'_>) -> &pyo3::types::PyDict {
     use pyo3::types::IntoPyDict;
-    let mut mapping = std::collections::BTreeMap::<String, String>::new();
-    include_visualize_file!(
-        mapping,
-        "gui3d.js",
-        "index.js",
-        "patches.js",
-        "primal.js",
-        "cmd.js",
-        "mocker.js"
-    );
-    include_visualize_file!(mapping, "index.html", "partition-profile.html", "icon.svg");
-    include_visualize_file!(mapping, "package.json", "package-lock.json");
-    mapping.into_py_dict(py)
+    visualize::embedded_visualizer_website().into_py_dict(py)
 }
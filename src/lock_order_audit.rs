@@ -0,0 +1,321 @@
+//! Lock Order Audit
+//!
+//! Occasional hangs in the parallel solver are extremely hard to reproduce, because a deadlock only happens when two
+//! threads acquire the same pair of locks in opposite orders *at the same time*. This debug module instead records the
+//! order in which locks are acquired, building a global "held before" graph between lock instances, and reports any
+//! cycle in this graph as soon as it forms, even if the unlucky interleaving never happens in this particular run.
+//!
+//! Only the locks explicitly registered via [`register`] are tracked, i.e. the dual module parallel units, the primal
+//! module parallel units and the dual module interfaces; acquiring other locks costs only a lookup.
+//! Enable the feature `lock_order_audit` to use it; it is incompatible with `unsafe_pointer` which removes the locks.
+//!
+
+use super::pointers::*;
+use crate::parking_lot::{Mutex, RwLock};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::sync::Arc;
+
+#[cfg(feature = "unsafe_pointer")]
+compile_error!("feature `lock_order_audit` requires locks, it cannot be used together with `unsafe_pointer`");
+
+/// a cycle in the lock order graph, which may lead to a deadlock
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockOrderViolation {
+    /// names of the locks in the cycle, `cycle[i]` is acquired while holding `cycle[i-1]` (cyclically)
+    pub cycle: Vec<String>,
+    /// the thread that closed the cycle
+    pub thread: String,
+}
+
+impl std::fmt::Display for LockOrderViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "potential deadlock detected by thread {}: ", self.thread)?;
+        for name in self.cycle.iter() {
+            write!(f, "{} -> ", name)?;
+        }
+        write!(f, "{}", self.cycle[0])
+    }
+}
+
+#[derive(Default)]
+struct LockOrderAuditState {
+    /// the registered locks and their names
+    names: HashMap<usize, String>,
+    /// `edges[a][b]` exists if `b` has been acquired while holding `a`; the value is whether any of them is a writer lock
+    edges: BTreeMap<usize, BTreeMap<usize, bool>>,
+    /// all the detected violations
+    violations: Vec<LockOrderViolation>,
+}
+
+impl LockOrderAuditState {
+    /// remove all history of a lock, e.g. when the address is reused by a new lock
+    fn forget(&mut self, address: usize) {
+        self.edges.remove(&address);
+        for targets in self.edges.values_mut() {
+            targets.remove(&address);
+        }
+    }
+
+    /// find a path from `source` to `target` if exists, and whether any edge along the path involves a writer lock
+    fn find_path(&self, source: usize, target: usize) -> Option<(Vec<usize>, bool)> {
+        let mut previous: BTreeMap<usize, (usize, bool)> = BTreeMap::new();
+        let mut visited = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(source);
+        queue.push_back(source);
+        while let Some(current) = queue.pop_front() {
+            if current == target {
+                let mut path = vec![target];
+                let mut has_writer = false;
+                let mut cursor = target;
+                while cursor != source {
+                    let (parent, is_writer) = previous[&cursor];
+                    has_writer |= is_writer;
+                    path.push(parent);
+                    cursor = parent;
+                }
+                path.reverse();
+                return Some((path, has_writer));
+            }
+            if let Some(targets) = self.edges.get(&current) {
+                for (&next, &is_writer) in targets.iter() {
+                    if visited.insert(next) {
+                        previous.insert(next, (current, is_writer));
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref STATE: Mutex<LockOrderAuditState> = Mutex::new(LockOrderAuditState::default());
+}
+
+thread_local! {
+    /// the registered locks held by the current thread, in the order of acquisition, together with whether it's a writer
+    static HELD_LOCKS: RefCell<Vec<(usize, bool)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn address_of<T>(ptr: &Arc<RwLock<T>>) -> usize {
+    Arc::as_ptr(ptr) as *const () as usize
+}
+
+fn current_thread_name() -> String {
+    let thread = std::thread::current();
+    match thread.name() {
+        Some(name) => name.to_string(),
+        None => format!("{:?}", thread.id()),
+    }
+}
+
+/// start tracking a lock under a human-readable name; registering it again only updates the name
+pub fn register<T>(ptr: &ArcRwLock<T>, name: String) {
+    let address = address_of(ptr.ptr());
+    let mut state = STATE.lock();
+    if state.names.insert(address, name).is_none() {
+        state.forget(address); // the address may be reused from a freed lock
+    }
+}
+
+/// stop tracking a lock
+pub fn unregister<T>(ptr: &ArcRwLock<T>) {
+    let address = address_of(ptr.ptr());
+    let mut state = STATE.lock();
+    state.names.remove(&address);
+    state.forget(address);
+}
+
+/// called before acquiring a lock; returns the address if this lock is tracked
+pub fn before_acquire<T>(ptr: &Arc<RwLock<T>>, is_writer: bool) -> Option<usize> {
+    let address = address_of(ptr);
+    let mut state = STATE.lock();
+    if !state.names.contains_key(&address) {
+        return None;
+    }
+    let held_locks = HELD_LOCKS.with(|held_locks| held_locks.borrow().clone());
+    for &(held, held_is_writer) in held_locks.iter() {
+        if held == address {
+            continue; // recursive reader lock is allowed
+        }
+        let edge_is_writer = is_writer || held_is_writer;
+        let targets = state.edges.entry(held).or_default();
+        match targets.get(&address) {
+            Some(&existing) if existing || !edge_is_writer => continue, // already recorded
+            _ => {
+                targets.insert(address, edge_is_writer);
+            }
+        }
+        // a cycle consisting only of reader locks never blocks
+        if let Some((path, path_has_writer)) = state.find_path(address, held) {
+            if path_has_writer || edge_is_writer {
+                let cycle: Vec<String> = path
+                    .iter()
+                    .rev()
+                    .map(|address| state.names.get(address).cloned().unwrap_or_else(|| format!("{:#x}", address)))
+                    .collect();
+                let violation = LockOrderViolation {
+                    cycle,
+                    thread: current_thread_name(),
+                };
+                eprintln!("[lock order audit] {}", violation);
+                state.violations.push(violation);
+            }
+        }
+    }
+    Some(address)
+}
+
+/// the address of a lock if it's tracked, without recording any edge: used by non-blocking acquisitions, which never
+/// wait and thus cannot close a deadlock cycle themselves, but are still held while acquiring further locks
+pub fn tracked_address<T>(ptr: &Arc<RwLock<T>>) -> Option<usize> {
+    let address = address_of(ptr);
+    STATE.lock().names.contains_key(&address).then_some(address)
+}
+
+/// called after acquiring a tracked lock
+pub fn after_acquire(address: usize, is_writer: bool) {
+    HELD_LOCKS.with(|held_locks| held_locks.borrow_mut().push((address, is_writer)));
+}
+
+/// called after releasing a tracked lock
+pub fn after_release(address: usize) {
+    HELD_LOCKS.with(|held_locks| {
+        let mut held_locks = held_locks.borrow_mut();
+        if let Some(index) = held_locks.iter().rposition(|&(held, _)| held == address) {
+            held_locks.remove(index);
+        }
+    });
+}
+
+/// all the violations detected so far
+pub fn violations() -> Vec<LockOrderViolation> {
+    STATE.lock().violations.clone()
+}
+
+/// clear the recorded lock order and violations, but keep the registered locks
+pub fn clear() {
+    let mut state = STATE.lock();
+    state.edges.clear();
+    state.violations.clear();
+}
+
+/// a lock guard that notifies the audit when released
+pub struct LockOrderAuditGuard<Guard> {
+    guard: Guard,
+    address: Option<usize>,
+}
+
+impl<Guard> LockOrderAuditGuard<Guard> {
+    pub fn new(guard: Guard, address: Option<usize>, is_writer: bool) -> Self {
+        if let Some(address) = address {
+            after_acquire(address, is_writer);
+        }
+        Self { guard, address }
+    }
+}
+
+impl<Guard> Drop for LockOrderAuditGuard<Guard> {
+    fn drop(&mut self) {
+        if let Some(address) = self.address {
+            after_release(address);
+        }
+    }
+}
+
+impl<Guard: std::ops::Deref> std::ops::Deref for LockOrderAuditGuard<Guard> {
+    type Target = Guard::Target;
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<Guard: std::ops::DerefMut> std::ops::DerefMut for LockOrderAuditGuard<Guard> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+impl<Guard: std::fmt::Debug> std::fmt::Debug for LockOrderAuditGuard<Guard> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.guard.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_order_audit_1() {
+        // cargo test --features lock_order_audit lock_order_audit_1 -- --nocapture
+        let a = ArcRwLock::new_value(0);
+        let b = ArcRwLock::new_value(0);
+        register(&a, "lock_order_audit_1 a".to_string());
+        register(&b, "lock_order_audit_1 b".to_string());
+        let find = || {
+            violations()
+                .into_iter()
+                .filter(|violation| violation.cycle.iter().any(|name| name.starts_with("lock_order_audit_1")))
+                .count()
+        };
+        {
+            // consistent order is fine
+            let _a = a.write();
+            let _b = b.write();
+        }
+        {
+            let _a = a.read_recursive();
+            let _b = b.read_recursive();
+        }
+        assert_eq!(find(), 0);
+        // opposite order with a writer, even without actually deadlocking in this single thread
+        {
+            let _b = b.write();
+            let _a = a.read_recursive();
+        }
+        assert_eq!(find(), 1);
+        unregister(&a);
+        unregister(&b);
+    }
+    #[test]
+    fn lock_order_audit_try_write() {
+        // cargo test --features lock_order_audit lock_order_audit_try_write -- --nocapture
+        let a = ArcRwLock::new_value(0);
+        let b = ArcRwLock::new_value(0);
+        register(&a, "lock_order_audit_try_write a".to_string());
+        register(&b, "lock_order_audit_try_write b".to_string());
+        let find = || {
+            violations()
+                .into_iter()
+                .filter(|violation| {
+                    violation
+                        .cycle
+                        .iter()
+                        .any(|name| name.starts_with("lock_order_audit_try_write"))
+                })
+                .count()
+        };
+        {
+            let _a = a.write();
+            let _b = b.write();
+        }
+        // a non-blocking acquisition in the opposite order never waits, so it's not a violation
+        {
+            let _b = b.write();
+            assert!(a.try_write().is_some());
+        }
+        assert_eq!(find(), 0);
+        // but a lock acquired by `try_write` is still held when acquiring further locks
+        {
+            let _b = b.try_write().unwrap();
+            let _a = a.write();
+        }
+        assert_eq!(find(), 1);
+        unregister(&a);
+        unregister(&b);
+    }
+}
@@ -4,14 +4,16 @@
 //! Note that you can call different primal and dual modules, even interchangeably, by following the examples in this file
 //!
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::fs::File;
 use std::io::prelude::*;
-use std::io::BufWriter;
+use std::sync::Arc;
+use std::time::Instant;
 
 use nonzero::nonzero as nz;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::blossom_v;
 use crate::complete_graph::*;
@@ -19,14 +21,23 @@ use crate::derivative::Derivative;
 use crate::dual_module::*;
 
 use super::dual_module::{DualModuleImpl, DualModuleInterfacePtr};
+use super::dual_module_channel::*;
 use super::dual_module_parallel::*;
-use super::dual_module_serial::DualModuleSerial;
+use super::dual_module_serial::{DualModuleSerial, GrownRegion};
+use super::io::*;
 use super::pointers::*;
-use super::primal_module::{PerfectMatching, PrimalModuleImpl, SubGraphBuilder, VisualizeSubgraph};
+use super::primal_module::{
+    BoundaryPair, PerfectMatching, PrimalModuleImpl, SolveBudget, SubGraphBuilder, VisualizeSubgraph,
+};
 use super::primal_module_parallel::*;
 use super::primal_module_serial::PrimalModuleSerialPtr;
+use super::primal_module_union_find::PrimalModuleUnionFind;
+use super::repro::{Repro, ReproSolverType};
+use super::syndrome_file::*;
 use super::util::*;
 use super::visualize::*;
+use crate::rand_xoshiro::rand_core::SeedableRng;
+use crate::rayon::prelude::*;
 
 /// a serial solver
 #[derive(Derivative)]
@@ -96,6 +107,7 @@ impl LegacySolverSerial {
     ) -> Vec<EdgeIndex> {
         let perfect_matching = self.solve_perfect_matching(syndrome_pattern, visualizer);
         self.subgraph_builder.clear();
+        self.subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
         self.subgraph_builder.load_perfect_matching(&perfect_matching);
         self.subgraph_builder.get_subgraph()
     }
@@ -138,6 +150,29 @@ impl LegacySolverSerial {
     }
 }
 
+/// the outcome of [`PrimalDualSolver::solve_with_budget`]
+#[derive(Debug)]
+pub struct BudgetedSolution {
+    pub perfect_matching: PerfectMatching,
+    /// whether the budget was exhausted, so that the matching may be suboptimal
+    pub truncated: bool,
+}
+
+/// the profiler report of a solver made of a dual module and a primal module, including the memory of each module when
+/// the allocations are tracked with the `track_allocations` feature
+fn primal_dual_profiler_report(dual: serde_json::Value, primal: serde_json::Value) -> serde_json::Value {
+    #[allow(unused_mut)]
+    let mut report = json!({
+        "dual": dual,
+        "primal": primal,
+    });
+    #[cfg(feature = "track_allocations")]
+    {
+        report["allocation"] = crate::allocation_tracker::profiler_report();
+    }
+    report
+}
+
 pub trait PrimalDualSolver {
     fn clear(&mut self);
     fn reset_profiler(&mut self) {} // only if profiler records some information that needs to be cleared, e.g. vec![]
@@ -145,16 +180,78 @@ pub trait PrimalDualSolver {
     fn solve(&mut self, syndrome_pattern: &SyndromePattern) {
         self.solve_visualizer(syndrome_pattern, None)
     }
+    /// solve with per-shot edge weights, e.g. from soft measurement information, without constructing a new solver;
+    /// the weights must be even, and they override the erasures and the dynamic weights in the syndrome pattern
+    fn solve_with_weights_visualizer(
+        &mut self,
+        syndrome_pattern: &SyndromePattern,
+        weights: &[(EdgeIndex, Weight)],
+        visualizer: Option<&mut Visualizer>,
+    ) {
+        self.solve_visualizer(&syndrome_pattern.with_dynamic_weights(weights), visualizer)
+    }
+    fn solve_with_weights(&mut self, syndrome_pattern: &SyndromePattern, weights: &[(EdgeIndex, Weight)]) {
+        self.solve_with_weights_visualizer(syndrome_pattern, weights, None)
+    }
     fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching;
     fn perfect_matching(&mut self) -> PerfectMatching {
         self.perfect_matching_visualizer(None)
     }
+    /// solve within a hard budget for real-time decoding, see [`SolveBudget`]; if truncated, the matching is still valid
+    /// but may not be minimum-weight. The solver must be cleared before the next solve as usual. The solvers that don't
+    /// support a budget fall back to a full solve, which is never truncated
+    fn solve_with_budget(&mut self, syndrome_pattern: &SyndromePattern, _budget: SolveBudget) -> BudgetedSolution {
+        self.solve(syndrome_pattern);
+        BudgetedSolution {
+            perfect_matching: self.perfect_matching(),
+            truncated: false,
+        }
+    }
+    /// decode many independent shots, returning the perfect matching of each shot in order; the solver is cleared after
+    /// each shot. The default implementation decodes them one by one, while [`SolverSerial`] decodes them concurrently
+    fn solve_batch(&mut self, syndrome_patterns: &[SyndromePattern]) -> Vec<PerfectMatching> {
+        syndrome_patterns
+            .iter()
+            .map(|syndrome_pattern| {
+                self.solve(syndrome_pattern);
+                let perfect_matching = self.perfect_matching().detached();
+                self.clear();
+                perfect_matching
+            })
+            .collect()
+    }
     fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex>;
     fn subgraph(&mut self) -> Vec<EdgeIndex> {
         self.subgraph_visualizer(None)
     }
     fn sum_dual_variables(&self) -> Weight;
     fn generate_profiler_report(&self) -> serde_json::Value;
+    /// the full configuration actually in effect, including the defaults, so that a run can be reproduced later;
+    /// empty if the solver has nothing to configure
+    fn effective_config(&self) -> serde_json::Value {
+        json!({})
+    }
+    /// record the syndrome pattern of each solve for [`Self::repro`]; disabled by default because it copies every
+    /// syndrome, and ignored by the solvers that don't support reproductions
+    fn set_record_repro(&mut self, _enabled: bool) {}
+    /// a self-contained reproduction of the last solve, see [`crate::repro`]; `None` if the solver doesn't support it,
+    /// recording is not enabled by [`Self::set_record_repro`] or it hasn't solved anything yet
+    fn repro(&self) -> Option<Repro> {
+        None
+    }
+    /// write [`Self::repro`] into a single JSON file that can be attached to a bug report and rerun by [`crate::repro::run`]
+    fn export_repro(&self, path: &str) -> std::io::Result<()> {
+        let repro = self.repro().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "no reproduction of the last solve is available, see `set_record_repro`",
+            )
+        })?;
+        repro.save(path)
+    }
+    /// verify that the subgraph generates exactly the defect vertices, catching corrupted matchings; enabled by default
+    /// for the solvers that build a subgraph, and can be disabled in hot loops for maximum performance
+    fn set_parity_check(&mut self, _enabled: bool) {}
     #[allow(clippy::unnecessary_cast)]
     fn stim_integration_predict_bit_packed_data(
         &mut self,
@@ -165,27 +262,13 @@ pub trait PrimalDualSolver {
         num_dets: usize,
         num_obs: usize,
     ) {
-        let mut in_reader = std::io::BufReader::new(File::open(in_file).expect("in_file not found"));
+        let mut in_reader =
+            StimDetectionReader::open(&in_file, StimDetectionFormat::B8, num_dets, 0).expect("in_file not found");
         let mut out_writer = std::io::BufWriter::new(File::create(out_file).expect("out_file not found"));
-        let num_det_bytes = (num_dets + 7) / 8; // ceil
-        let mut dets_bit_packed = vec![0; num_det_bytes];
         assert!(num_obs <= 64, "too many observables");
         let prediction_bytes = (num_obs + 7) / 8; // ceil
         for _ in 0..num_shots {
-            in_reader.read_exact(&mut dets_bit_packed).expect("read success");
-            let mut defect_vertices = vec![];
-            for (i, &byte) in dets_bit_packed.iter().enumerate() {
-                if byte == 0 {
-                    continue;
-                }
-                for j in 0..8 {
-                    if byte & (1 << j) != 0 {
-                        // little endian
-                        defect_vertices.push((i * 8 + j) as VertexIndex);
-                    }
-                }
-            }
-            let syndrome_pattern = SyndromePattern::new_vertices(defect_vertices);
+            let syndrome_pattern = in_reader.next().expect("read success").expect("read success");
             self.solve(&syndrome_pattern);
             let subgraph = self.subgraph();
             let mut prediction = 0;
@@ -218,6 +301,19 @@ macro_rules! bind_trait_primal_dual_solver {
             fn trait_solve(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
                 self.solve_visualizer(syndrome_pattern, visualizer)
             }
+            #[pyo3(name = "solve_with_weights")]
+            fn trait_solve_with_weights(
+                &mut self,
+                syndrome_pattern: &SyndromePattern,
+                weights: Vec<(EdgeIndex, Weight)>,
+                visualizer: Option<&mut Visualizer>,
+            ) {
+                self.solve_with_weights_visualizer(syndrome_pattern, &weights, visualizer)
+            }
+            #[pyo3(name = "solve_batch")]
+            fn trait_solve_batch(&mut self, syndrome_patterns: Vec<SyndromePattern>) -> Vec<PerfectMatching> {
+                self.solve_batch(&syndrome_patterns)
+            }
             #[pyo3(name = "perfect_matching_visualizer")]
             fn trait_perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
                 self.perfect_matching_visualizer(visualizer)
@@ -242,6 +338,18 @@ macro_rules! bind_trait_primal_dual_solver {
             fn trait_generate_profiler_report(&self) -> PyObject {
                 json_to_pyobject(self.generate_profiler_report())
             }
+            #[pyo3(name = "effective_config")]
+            fn trait_effective_config(&self) -> PyObject {
+                json_to_pyobject(self.effective_config())
+            }
+            #[pyo3(name = "set_record_repro")]
+            fn trait_set_record_repro(&mut self, enabled: bool) {
+                self.set_record_repro(enabled)
+            }
+            #[pyo3(name = "export_repro")]
+            fn trait_export_repro(&self, path: String) -> std::io::Result<()> {
+                self.export_repro(&path)
+            }
             #[pyo3(name = "stim_integration_predict_bit_packed_data")]
             fn trait_stim_integration_predict_bit_packed_data(
                 &mut self,
@@ -265,6 +373,12 @@ pub struct SolverSerial {
     pub primal_module: PrimalModuleSerialPtr,
     pub interface_ptr: DualModuleInterfacePtr,
     pub subgraph_builder: SubGraphBuilder,
+    /// the decoding graph, shared by the solvers created by [`Self::clone_for_thread`]
+    pub initializer: Arc<SolverInitializer>,
+    /// the syndrome pattern of the last solve, for [`PrimalDualSolver::repro`]
+    pub last_syndrome_pattern: Option<SyndromePattern>,
+    /// whether [`Self::last_syndrome_pattern`] is recorded, see [`PrimalDualSolver::set_record_repro`]
+    pub record_repro: bool,
 }
 
 bind_trait_fusion_visualizer!(SolverSerial);
@@ -301,18 +415,37 @@ impl SolverSerial {
             primal_module: PrimalModuleSerialPtr::new_empty(initializer),
             interface_ptr: DualModuleInterfacePtr::new_empty(),
             subgraph_builder: SubGraphBuilder::new(initializer),
+            initializer: Arc::new(initializer.clone()),
+            last_syndrome_pattern: None,
+            record_repro: false,
         }
     }
-}
 
-impl PrimalDualSolver for SolverSerial {
-    fn clear(&mut self) {
-        self.primal_module.clear();
-        self.dual_module.clear();
-        self.interface_ptr.clear();
-        self.subgraph_builder.clear();
+    /// create an independent solver for another thread: the immutable decoding graph and lookup tables are shared,
+    /// while the mutable state is fresh; the configurations, e.g. memory bounds and growth costs, are kept
+    pub fn clone_for_thread(&self) -> Self {
+        let mut dual_module = DualModuleSerial::new_empty(&self.initializer);
+        dual_module.copy_growth_costs(&self.dual_module);
+        let primal_module = PrimalModuleSerialPtr::new_empty(&self.initializer);
+        {
+            let source = self.primal_module.read_recursive();
+            let mut primal_module = primal_module.write();
+            primal_module.max_tree_size = source.max_tree_size;
+            primal_module.max_dual_node_count = source.max_dual_node_count;
+            primal_module.max_blossom_depth = source.max_blossom_depth;
+        }
+        Self {
+            dual_module,
+            primal_module,
+            interface_ptr: DualModuleInterfacePtr::new_empty(),
+            subgraph_builder: self.subgraph_builder.clone_fresh(),
+            initializer: self.initializer.clone(),
+            last_syndrome_pattern: None,
+            record_repro: false,
+        }
     }
-    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+
+    fn load_syndrome_pattern(&mut self, syndrome_pattern: &SyndromePattern) {
         if !syndrome_pattern.erasures.is_empty() {
             assert!(
                 syndrome_pattern.dynamic_weights.is_empty(),
@@ -323,9 +456,129 @@ impl PrimalDualSolver for SolverSerial {
         if !syndrome_pattern.dynamic_weights.is_empty() {
             self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
         }
+        self.subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
+        if self.record_repro {
+            self.last_syndrome_pattern = Some(syndrome_pattern.clone());
+        }
+    }
+
+    /// decode again after the defect vertices of the last solve change, e.g. between consecutive shots, returning whether
+    /// the solved state is reused. Only adding defect vertices is incremental: they're loaded into the solved state so
+    /// that only their new alternating trees grow, as long as no dual node has grown over them. Removing any defect
+    /// vertex is not incremental, the alternating trees are not invalidated locally, and the new syndrome is solved
+    /// from scratch instead
+    pub fn solve_incremental(&mut self, added: &[VertexIndex], removed: &[VertexIndex]) -> bool {
+        let defect_vertices: Vec<VertexIndex> = {
+            let interface = self.interface_ptr.read_recursive();
+            interface.nodes[..interface.nodes_length]
+                .iter()
+                .flatten()
+                .filter_map(|node_ptr| match node_ptr.read_recursive().class {
+                    DualNodeClass::DefectVertex { defect_index } => Some(defect_index),
+                    _ => None,
+                })
+                .collect()
+        };
+        let mut syndrome_pattern =
+            SyndromePattern::new_dynamic_weights(defect_vertices, vec![], self.subgraph_builder.loaded_dynamic_weights());
+        for vertex_index in removed.iter() {
+            let position = syndrome_pattern
+                .defect_vertices
+                .iter()
+                .position(|defect_vertex| defect_vertex == vertex_index)
+                .unwrap_or_else(|| panic!("removed vertex {vertex_index} is not a defect vertex"));
+            syndrome_pattern.defect_vertices.remove(position);
+        }
+        for vertex_index in added.iter() {
+            assert!(
+                !syndrome_pattern.defect_vertices.contains(vertex_index),
+                "added vertex {vertex_index} is already a defect vertex"
+            );
+            syndrome_pattern.defect_vertices.push(*vertex_index);
+        }
+        let is_solved = self.interface_ptr.read_recursive().nodes_length > 0;
+        let reusable = is_solved
+            && removed.is_empty()
+            && added
+                .iter()
+                .all(|&vertex_index| !self.dual_module.is_vertex_covered(vertex_index));
+        if !reusable {
+            self.clear();
+            self.solve(&syndrome_pattern);
+            return false;
+        }
+        self.subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
+        if self.record_repro {
+            self.last_syndrome_pattern = Some(syndrome_pattern);
+        }
+        for &vertex_index in added.iter() {
+            self.primal_module
+                .load_defect(vertex_index, &self.interface_ptr, &mut self.dual_module);
+        }
+        self.primal_module
+            .solve_step_callback_interface_loaded(&self.interface_ptr, &mut self.dual_module, |_, _, _, _| {});
+        true
+    }
+
+    /// decode many independent shots in parallel, returning the subgraph of each shot in order;
+    /// the shots are dispatched by the work-stealing thread pool so that a slow shot doesn't block the others,
+    /// and the solvers are created on demand by [`Self::clone_for_thread`], roughly one per worker thread
+    pub fn solve_shots(&self, syndrome_patterns: &[SyndromePattern]) -> Vec<Vec<EdgeIndex>> {
+        syndrome_patterns
+            .par_iter()
+            .map_init(
+                || self.clone_for_thread(),
+                |solver, syndrome_pattern| {
+                    solver.solve(syndrome_pattern);
+                    let subgraph = solver.subgraph();
+                    solver.clear();
+                    subgraph
+                },
+            )
+            .collect()
+    }
+}
+
+impl PrimalDualSolver for SolverSerial {
+    fn clear(&mut self) {
+        #[cfg(feature = "track_allocations")]
+        crate::allocation_tracker::begin_shot();
+        self.primal_module.clear();
+        self.dual_module.clear();
+        self.interface_ptr.clear();
+        self.subgraph_builder.clear();
+    }
+    /// the shots are dispatched to the worker threads like [`Self::solve_shots`]
+    fn solve_batch(&mut self, syndrome_patterns: &[SyndromePattern]) -> Vec<PerfectMatching> {
+        let solver: &Self = self;
+        syndrome_patterns
+            .par_iter()
+            .map_init(
+                || solver.clone_for_thread(),
+                |solver, syndrome_pattern| {
+                    solver.solve(syndrome_pattern);
+                    let perfect_matching = solver.perfect_matching().detached();
+                    solver.clear();
+                    perfect_matching
+                },
+            )
+            .collect()
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        self.load_syndrome_pattern(syndrome_pattern);
         self.primal_module
             .solve_visualizer(&self.interface_ptr, syndrome_pattern, &mut self.dual_module, visualizer);
     }
+    fn solve_with_budget(&mut self, syndrome_pattern: &SyndromePattern, budget: SolveBudget) -> BudgetedSolution {
+        self.load_syndrome_pattern(syndrome_pattern);
+        let truncated =
+            self.primal_module
+                .solve_budget(&self.interface_ptr, syndrome_pattern, &mut self.dual_module, budget);
+        BudgetedSolution {
+            perfect_matching: self.perfect_matching(),
+            truncated,
+        }
+    }
     fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
         let perfect_matching = self
             .primal_module
@@ -363,11 +616,353 @@ impl PrimalDualSolver for SolverSerial {
         self.interface_ptr.read_recursive().sum_dual_variables
     }
     fn generate_profiler_report(&self) -> serde_json::Value {
+        primal_dual_profiler_report(
+            self.dual_module.generate_profiler_report(),
+            self.primal_module.generate_profiler_report(),
+        )
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        let primal_module = self.primal_module.read_recursive();
+        json!({
+            "primal": {
+                "max_tree_size": primal_module.max_tree_size,
+                "max_dual_node_count": primal_module.max_dual_node_count,
+                "max_blossom_depth": primal_module.max_blossom_depth,
+            },
+        })
+    }
+    fn set_record_repro(&mut self, enabled: bool) {
+        self.record_repro = enabled;
+    }
+    fn repro(&self) -> Option<Repro> {
+        let syndrome_pattern = self.last_syndrome_pattern.as_ref()?;
+        Some(Repro::new(
+            ReproSolverType::Serial,
+            &self.initializer,
+            None,
+            self.effective_config(),
+            syndrome_pattern,
+        ))
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.subgraph_builder.enable_parity_check = enabled;
+    }
+}
+
+/// the configuration of [`SolverClusterParallel`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SolverClusterParallelConfig {
+    /// defect vertices within this distance start in the same cluster; this is only a guess of which defects interact,
+    /// because the clusters whose solutions overlap are merged anyway. Twice the maximum edge weight by default
+    #[serde(default)]
+    pub cluster_distance: Option<Weight>,
+    /// the number of threads solving the clusters, 0 to use one per core
+    #[serde(default)]
+    pub thread_pool_size: usize,
+}
+
+/// the solution of a cluster of defect vertices, solved as if the other defect vertices didn't exist
+#[derive(Debug, Clone)]
+pub struct ClusterSolution {
+    pub defect_vertices: Vec<VertexIndex>,
+    /// detached from the solver that solves this cluster
+    pub perfect_matching: PerfectMatching,
+    pub sum_dual_variables: Weight,
+    /// the part of the decoding graph covered by the dual nodes of this cluster
+    pub region: GrownRegion,
+}
+
+/// the statistics of the last solve of [`SolverClusterParallel`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ClusterParallelStatistics {
+    /// the number of clusters before any merge
+    pub initial_clusters: usize,
+    /// the number of clusters in the final solution
+    pub final_clusters: usize,
+    /// the number of rounds of solving the clusters in parallel and merging the overlapping ones
+    pub rounds: usize,
+    /// the number of cluster solves in all rounds
+    pub solved_clusters: usize,
+}
+
+/// decodes the clusters of defect vertices of a single shot concurrently, complementary to the vertex partition of
+/// [`SolverParallel`]: within a shot, the alternating trees of far-apart defect vertices never interact.
+/// The defect vertices are first grouped by their distance, and each cluster is solved by its own [`SolverSerial`] on
+/// the rayon thread pool. The union of the solutions is a minimum-weight perfect matching as long as the union of
+/// the dual variables is still feasible, i.e. no edge is overgrown by the clusters together and no cluster grows over
+/// a defect vertex of another; otherwise the conflicting clusters are merged and solved again, until there is no
+/// conflict. In the worst case, all defect vertices end up in a single cluster.
+pub struct SolverClusterParallel {
+    pub config: SolverClusterParallelConfig,
+    /// the prototype of the workers, see [`SolverSerial::clone_for_thread`]
+    pub prototype: SolverSerial,
+    /// idle workers to be reused by the next cluster
+    workers: parking_lot::Mutex<Vec<SolverSerial>>,
+    thread_pool: rayon::ThreadPool,
+    /// the neighbors of each vertex and the weights of the edges, to group the defect vertices by distance
+    neighbors: Vec<Vec<(VertexIndex, Weight)>>,
+    is_virtual: Vec<bool>,
+    /// the effective [`SolverClusterParallelConfig::cluster_distance`]
+    pub cluster_distance: Weight,
+    subgraph_builder: SubGraphBuilder,
+    /// the solutions of the clusters of the last solve
+    pub clusters: Vec<ClusterSolution>,
+    pub statistics: ClusterParallelStatistics,
+}
+
+impl SolverClusterParallel {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer, config: serde_json::Value) -> Self {
+        let config: SolverClusterParallelConfig = serde_json::from_value(config).unwrap();
+        let mut neighbors = vec![vec![]; initializer.vertex_num as usize];
+        for &(i, j, weight) in initializer.weighted_edges.iter() {
+            neighbors[i as usize].push((j, weight));
+            neighbors[j as usize].push((i, weight));
+        }
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &vertex_index in initializer.virtual_vertices.iter() {
+            is_virtual[vertex_index as usize] = true;
+        }
+        let max_weight = initializer
+            .weighted_edges
+            .iter()
+            .map(|(_, _, weight)| *weight)
+            .max()
+            .unwrap_or(0);
+        let mut thread_pool_builder = rayon::ThreadPoolBuilder::new();
+        if config.thread_pool_size != 0 {
+            thread_pool_builder = thread_pool_builder.num_threads(config.thread_pool_size);
+        }
+        Self {
+            cluster_distance: config.cluster_distance.unwrap_or(2 * max_weight),
+            config,
+            prototype: SolverSerial::new(initializer),
+            workers: parking_lot::Mutex::new(vec![]),
+            thread_pool: thread_pool_builder.build().expect("creating thread pool failed"),
+            neighbors,
+            is_virtual,
+            subgraph_builder: SubGraphBuilder::new(initializer),
+            clusters: vec![],
+            statistics: ClusterParallelStatistics::default(),
+        }
+    }
+
+    /// group the defect vertices connected by paths no longer than [`Self::cluster_distance`], not passing through any
+    /// virtual vertex
+    #[allow(clippy::unnecessary_cast)]
+    pub fn initial_clusters(&self, defect_vertices: &[VertexIndex]) -> Vec<Vec<VertexIndex>> {
+        let defect_indices: HashMap<VertexIndex, usize> = defect_vertices
+            .iter()
+            .enumerate()
+            .map(|(defect_index, &vertex_index)| (vertex_index, defect_index))
+            .collect();
+        let mut parents: Vec<usize> = (0..defect_vertices.len()).collect();
+        for (defect_index, &source) in defect_vertices.iter().enumerate() {
+            let mut distances = HashMap::from([(source, 0)]);
+            let mut heap = std::collections::BinaryHeap::from([std::cmp::Reverse((0, source))]);
+            while let Some(std::cmp::Reverse((distance, vertex_index))) = heap.pop() {
+                if distance > distances[&vertex_index] {
+                    continue;
+                }
+                if let Some(&peer_defect_index) = defect_indices.get(&vertex_index) {
+                    cluster_union(&mut parents, defect_index, peer_defect_index);
+                }
+                if self.is_virtual[vertex_index as usize] {
+                    continue;
+                }
+                for &(peer_index, weight) in self.neighbors[vertex_index as usize].iter() {
+                    let peer_distance = distance + weight;
+                    if peer_distance <= self.cluster_distance
+                        && !matches!(distances.get(&peer_index), Some(&known) if known <= peer_distance)
+                    {
+                        distances.insert(peer_index, peer_distance);
+                        heap.push(std::cmp::Reverse((peer_distance, peer_index)));
+                    }
+                }
+            }
+        }
+        cluster_groups(&mut parents)
+            .into_iter()
+            .map(|group| group.into_iter().map(|defect_index| defect_vertices[defect_index]).collect())
+            .collect()
+    }
+
+    fn solve_cluster(&self, defect_vertices: &[VertexIndex], syndrome_pattern: &SyndromePattern) -> ClusterSolution {
+        let mut worker = self.workers.lock().pop().unwrap_or_else(|| self.prototype.clone_for_thread());
+        let mut cluster_syndrome_pattern = syndrome_pattern.clone();
+        cluster_syndrome_pattern.defect_vertices = defect_vertices.to_vec();
+        worker.solve(&cluster_syndrome_pattern);
+        // the region must be taken before the perfect matching, which expands the blossoms
+        let region = worker.dual_module.grown_region(defect_vertices);
+        let solution = ClusterSolution {
+            defect_vertices: defect_vertices.to_vec(),
+            perfect_matching: worker.perfect_matching().detached(),
+            sum_dual_variables: worker.sum_dual_variables(),
+            region,
+        };
+        worker.clear();
+        self.workers.lock().push(worker);
+        solution
+    }
+
+    /// the groups of clusters that conflict with each other, directly or indirectly
+    fn conflicting_groups(clusters: &[ClusterSolution]) -> Vec<Vec<usize>> {
+        let mut parents: Vec<usize> = (0..clusters.len()).collect();
+        let mut defect_clusters = HashMap::new();
+        for (cluster_index, cluster) in clusters.iter().enumerate() {
+            for &vertex_index in cluster.defect_vertices.iter() {
+                defect_clusters.insert(vertex_index, cluster_index);
+            }
+        }
+        let mut edge_growths: HashMap<EdgeIndex, (Weight, Weight, Vec<usize>)> = HashMap::new();
+        for (cluster_index, cluster) in clusters.iter().enumerate() {
+            for vertex_index in cluster.region.vertices.iter() {
+                if let Some(&owner_index) = defect_clusters.get(vertex_index) {
+                    cluster_union(&mut parents, cluster_index, owner_index);
+                }
+            }
+            for &(edge_index, growth, weight) in cluster.region.edges.iter() {
+                let (total_growth, _, growing_clusters) = edge_growths.entry(edge_index).or_insert((0, weight, vec![]));
+                *total_growth += growth;
+                growing_clusters.push(cluster_index);
+            }
+        }
+        for (total_growth, weight, growing_clusters) in edge_growths.values() {
+            if total_growth > weight {
+                for &cluster_index in growing_clusters.iter().skip(1) {
+                    cluster_union(&mut parents, growing_clusters[0], cluster_index);
+                }
+            }
+        }
+        cluster_groups(&mut parents)
+            .into_iter()
+            .filter(|group| group.len() > 1)
+            .collect()
+    }
+}
+
+/// the root of a cluster in a union-find forest, with path halving
+fn cluster_find(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+    index
+}
+
+fn cluster_union(parents: &mut [usize], index_1: usize, index_2: usize) {
+    let (root_1, root_2) = (cluster_find(parents, index_1), cluster_find(parents, index_2));
+    parents[root_2.max(root_1)] = root_2.min(root_1);
+}
+
+/// the members of each tree in a union-find forest, ordered by their smallest member
+fn cluster_groups(parents: &mut [usize]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = vec![];
+    let mut group_of_root = HashMap::new();
+    for index in 0..parents.len() {
+        let root = cluster_find(parents, index);
+        let group_index = *group_of_root.entry(root).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+        groups[group_index].push(index);
+    }
+    groups
+}
+
+impl PrimalDualSolver for SolverClusterParallel {
+    fn clear(&mut self) {
+        self.subgraph_builder.clear();
+        self.clusters.clear();
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        assert!(visualizer.is_none(), "not supported");
+        if !syndrome_pattern.erasures.is_empty() {
+            self.subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+        }
+        if !syndrome_pattern.dynamic_weights.is_empty() {
+            self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+        }
+        self.subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
+        let mut pending = self.initial_clusters(&syndrome_pattern.defect_vertices);
+        let mut statistics = ClusterParallelStatistics {
+            initial_clusters: pending.len(),
+            ..Default::default()
+        };
+        let mut clusters: Vec<ClusterSolution> = vec![];
+        while !pending.is_empty() {
+            statistics.rounds += 1;
+            statistics.solved_clusters += pending.len();
+            let solver: &Self = self;
+            let solutions: Vec<ClusterSolution> = self.thread_pool.install(|| {
+                pending
+                    .par_iter()
+                    .map(|defect_vertices| solver.solve_cluster(defect_vertices, syndrome_pattern))
+                    .collect()
+            });
+            clusters.extend(solutions);
+            // the conflicting clusters are merged and solved again in the next round
+            let groups = Self::conflicting_groups(&clusters);
+            let merged: BTreeSet<usize> = groups.iter().flatten().cloned().collect();
+            pending = groups
+                .iter()
+                .map(|group| {
+                    group
+                        .iter()
+                        .flat_map(|&cluster_index| clusters[cluster_index].defect_vertices.iter().cloned())
+                        .collect()
+                })
+                .collect();
+            clusters = clusters
+                .into_iter()
+                .enumerate()
+                .filter(|(cluster_index, _)| !merged.contains(cluster_index))
+                .map(|(_, cluster)| cluster)
+                .collect();
+        }
+        statistics.final_clusters = clusters.len();
+        self.clusters = clusters;
+        self.statistics = statistics;
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        assert!(visualizer.is_none(), "not supported");
+        let mut perfect_matching = PerfectMatching::new();
+        for cluster in self.clusters.iter() {
+            let mut cluster_matching = cluster.perfect_matching.clone();
+            perfect_matching.peer_matchings.append(&mut cluster_matching.peer_matchings);
+            perfect_matching
+                .virtual_matchings
+                .append(&mut cluster_matching.virtual_matchings);
+        }
+        perfect_matching
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        assert!(visualizer.is_none(), "not supported");
+        let perfect_matching = self.perfect_matching();
+        self.subgraph_builder.load_perfect_matching(&perfect_matching);
+        self.subgraph_builder.get_subgraph()
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        self.clusters.iter().map(|cluster| cluster.sum_dual_variables).sum()
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        let cluster_sizes: Vec<_> = self.clusters.iter().map(|cluster| cluster.defect_vertices.len()).collect();
+        json!({
+            "statistics": self.statistics,
+            "cluster_sizes": cluster_sizes,
+        })
+    }
+    fn effective_config(&self) -> serde_json::Value {
         json!({
-            "dual": self.dual_module.generate_profiler_report(),
-            "primal": self.primal_module.generate_profiler_report(),
+            "cluster_distance": self.cluster_distance,
+            "thread_pool_size": self.config.thread_pool_size,
+            "serial": self.prototype.effective_config(),
         })
     }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.subgraph_builder.enable_parity_check = enabled;
+    }
 }
 
 #[cfg_attr(feature = "python_binding", cfg_eval)]
@@ -377,6 +972,11 @@ pub struct SolverDualParallel {
     pub primal_module: PrimalModuleSerialPtr,
     pub interface_ptr: DualModuleInterfacePtr,
     pub subgraph_builder: SubGraphBuilder,
+    pub initializer: SolverInitializer,
+    /// the syndrome pattern of the last solve, for [`PrimalDualSolver::repro`]
+    pub last_syndrome_pattern: Option<SyndromePattern>,
+    /// whether [`Self::last_syndrome_pattern`] is recorded, see [`PrimalDualSolver::set_record_repro`]
+    pub record_repro: bool,
 }
 
 bind_trait_fusion_visualizer!(SolverDualParallel);
@@ -418,18 +1018,19 @@ impl SolverDualParallel {
             primal_module: PrimalModuleSerialPtr::new_empty(initializer),
             interface_ptr: DualModuleInterfacePtr::new_empty(),
             subgraph_builder: SubGraphBuilder::new(initializer),
+            initializer: initializer.clone(),
+            last_syndrome_pattern: None,
+            record_repro: false,
         }
     }
-}
 
-impl PrimalDualSolver for SolverDualParallel {
-    fn clear(&mut self) {
-        self.dual_module.clear();
-        self.primal_module.clear();
-        self.interface_ptr.clear();
-        self.subgraph_builder.clear();
+    /// the defect pairs matched across the interfaces, see [`PerfectMatching::boundary_pairs`]
+    pub fn boundary_pairs(&mut self) -> Vec<BoundaryPair> {
+        let partition_info = self.dual_module.partition_info.clone();
+        self.perfect_matching().boundary_pairs(&partition_info)
     }
-    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+
+    fn load_syndrome_pattern(&mut self, syndrome_pattern: &SyndromePattern) {
         if !syndrome_pattern.erasures.is_empty() {
             assert!(
                 syndrome_pattern.dynamic_weights.is_empty(),
@@ -440,10 +1041,38 @@ impl PrimalDualSolver for SolverDualParallel {
         if !syndrome_pattern.dynamic_weights.is_empty() {
             self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
         }
+        self.subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
+        if self.record_repro {
+            self.last_syndrome_pattern = Some(syndrome_pattern.clone());
+        }
         self.dual_module.static_fuse_all();
+    }
+}
+
+impl PrimalDualSolver for SolverDualParallel {
+    fn clear(&mut self) {
+        #[cfg(feature = "track_allocations")]
+        crate::allocation_tracker::begin_shot();
+        self.dual_module.clear();
+        self.primal_module.clear();
+        self.interface_ptr.clear();
+        self.subgraph_builder.clear();
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        self.load_syndrome_pattern(syndrome_pattern);
         self.primal_module
             .solve_visualizer(&self.interface_ptr, syndrome_pattern, &mut self.dual_module, visualizer);
     }
+    fn solve_with_budget(&mut self, syndrome_pattern: &SyndromePattern, budget: SolveBudget) -> BudgetedSolution {
+        self.load_syndrome_pattern(syndrome_pattern);
+        let truncated =
+            self.primal_module
+                .solve_budget(&self.interface_ptr, syndrome_pattern, &mut self.dual_module, budget);
+        BudgetedSolution {
+            perfect_matching: self.perfect_matching(),
+            truncated,
+        }
+    }
     fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
         let perfect_matching = self
             .primal_module
@@ -481,89 +1110,346 @@ impl PrimalDualSolver for SolverDualParallel {
         self.interface_ptr.read_recursive().sum_dual_variables
     }
     fn generate_profiler_report(&self) -> serde_json::Value {
+        primal_dual_profiler_report(
+            self.dual_module.generate_profiler_report(),
+            self.primal_module.generate_profiler_report(),
+        )
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        let primal_module = self.primal_module.read_recursive();
         json!({
-            "dual": self.dual_module.generate_profiler_report(),
-            "primal": self.primal_module.generate_profiler_report(),
+            "dual": self.dual_module.effective_config(),
+            "primal": {
+                "max_tree_size": primal_module.max_tree_size,
+                "max_dual_node_count": primal_module.max_dual_node_count,
+                "max_blossom_depth": primal_module.max_blossom_depth,
+            },
+            "partition": self.dual_module.partition_info.config,
         })
     }
+    fn set_record_repro(&mut self, enabled: bool) {
+        self.record_repro = enabled;
+    }
+    fn repro(&self) -> Option<Repro> {
+        let syndrome_pattern = self.last_syndrome_pattern.as_ref()?;
+        Some(Repro::new(
+            ReproSolverType::DualParallel,
+            &self.initializer,
+            Some(&self.dual_module.partition_info.config),
+            self.effective_config(),
+            syndrome_pattern,
+        ))
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.subgraph_builder.enable_parity_check = enabled;
+    }
 }
 
-#[cfg_attr(feature = "python_binding", cfg_eval)]
-#[cfg_attr(feature = "python_binding", pyclass)]
-pub struct SolverParallel {
-    pub dual_module: DualModuleParallel<DualModuleSerial>,
-    pub primal_module: PrimalModuleParallel,
+/// parallel dual module whose partition units are hosted by separate ranks, see [`DualModuleChannel`]; the ranks are
+/// threads of this process spawned when the solver is created, and all the orchestration is hidden behind the solver
+/// interface
+pub struct SolverChannelParallel {
+    pub dual_module: DualModuleChannel<DualModuleSerial>,
+    pub primal_module: PrimalModuleSerialPtr,
+    pub interface_ptr: DualModuleInterfacePtr,
     pub subgraph_builder: SubGraphBuilder,
+    pub initializer: SolverInitializer,
+    /// the syndrome pattern of the last solve, for [`PrimalDualSolver::repro`]
+    pub last_syndrome_pattern: Option<SyndromePattern>,
+    /// whether [`Self::last_syndrome_pattern`] is recorded, see [`PrimalDualSolver::set_record_repro`]
+    pub record_repro: bool,
 }
 
-bind_trait_fusion_visualizer!(SolverParallel);
-impl FusionVisualizer for SolverParallel {
+impl FusionVisualizer for SolverChannelParallel {
     fn snapshot(&self, abbrev: bool) -> serde_json::Value {
         let mut value = self.primal_module.snapshot(abbrev);
         snapshot_combine_values(&mut value, self.dual_module.snapshot(abbrev), abbrev);
+        snapshot_combine_values(&mut value, self.interface_ptr.snapshot(abbrev), abbrev);
         value
     }
 }
 
-#[cfg(feature = "python_binding")]
-bind_trait_primal_dual_solver! {SolverParallel}
-
-#[cfg(feature = "python_binding")]
-#[pymethods]
-impl SolverParallel {
-    #[new]
-    pub fn new_python(
-        initializer: &SolverInitializer,
-        partition_info: &PartitionInfo,
-        primal_dual_config: PyObject,
-    ) -> Self {
-        let primal_dual_config = pyobject_to_json(primal_dual_config);
-        Self::new(initializer, partition_info, primal_dual_config)
-    }
-}
-
-impl SolverParallel {
+impl SolverChannelParallel {
     pub fn new(
         initializer: &SolverInitializer,
         partition_info: &PartitionInfo,
-        mut primal_dual_config: serde_json::Value,
+        primal_dual_config: serde_json::Value,
     ) -> Self {
-        let primal_dual_config = primal_dual_config.as_object_mut().expect("config must be JSON object");
-        let mut dual_config = DualModuleParallelConfig::default();
-        let mut primal_config = PrimalModuleParallelConfig::default();
-        if let Some(value) = primal_dual_config.remove("dual") {
-            dual_config = serde_json::from_value(value).unwrap();
-        }
-        if let Some(value) = primal_dual_config.remove("primal") {
-            primal_config = serde_json::from_value(value).unwrap();
-        }
-        if !primal_dual_config.is_empty() {
-            panic!(
-                "unknown primal_dual_config keys: {:?}",
-                primal_dual_config.keys().collect::<Vec<&String>>()
-            );
-        }
+        let config: DualModuleChannelConfig = serde_json::from_value(primal_dual_config).unwrap();
         Self {
-            dual_module: DualModuleParallel::new_config(initializer, partition_info, dual_config),
-            primal_module: PrimalModuleParallel::new_config(initializer, partition_info, primal_config),
+            dual_module: DualModuleChannel::new_config(initializer, partition_info, config),
+            primal_module: PrimalModuleSerialPtr::new_empty(initializer),
+            interface_ptr: DualModuleInterfacePtr::new_empty(),
             subgraph_builder: SubGraphBuilder::new(initializer),
+            initializer: initializer.clone(),
+            last_syndrome_pattern: None,
+            record_repro: false,
         }
     }
-}
 
-impl PrimalDualSolver for SolverParallel {
+    fn load_syndrome_pattern(&mut self, syndrome_pattern: &SyndromePattern) {
+        if !syndrome_pattern.erasures.is_empty() {
+            assert!(
+                syndrome_pattern.dynamic_weights.is_empty(),
+                "erasures and dynamic_weights cannot be provided at the same time"
+            );
+            self.subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+        }
+        if !syndrome_pattern.dynamic_weights.is_empty() {
+            self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+        }
+        self.subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
+        if self.record_repro {
+            self.last_syndrome_pattern = Some(syndrome_pattern.clone());
+        }
+        self.dual_module.static_fuse_all();
+    }
+}
+
+impl PrimalDualSolver for SolverChannelParallel {
     fn clear(&mut self) {
+        #[cfg(feature = "track_allocations")]
+        crate::allocation_tracker::begin_shot();
         self.dual_module.clear();
         self.primal_module.clear();
+        self.interface_ptr.clear();
         self.subgraph_builder.clear();
     }
     fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        self.load_syndrome_pattern(syndrome_pattern);
+        self.primal_module
+            .solve_visualizer(&self.interface_ptr, syndrome_pattern, &mut self.dual_module, visualizer);
+    }
+    fn solve_with_budget(&mut self, syndrome_pattern: &SyndromePattern, budget: SolveBudget) -> BudgetedSolution {
+        self.load_syndrome_pattern(syndrome_pattern);
+        let truncated =
+            self.primal_module
+                .solve_budget(&self.interface_ptr, syndrome_pattern, &mut self.dual_module, budget);
+        BudgetedSolution {
+            perfect_matching: self.perfect_matching(),
+            truncated,
+        }
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        let perfect_matching = self
+            .primal_module
+            .perfect_matching(&self.interface_ptr, &mut self.dual_module);
+        if let Some(visualizer) = visualizer {
+            visualizer
+                .snapshot_combined(
+                    "perfect matching".to_string(),
+                    vec![&self.interface_ptr, &self.dual_module, &perfect_matching],
+                )
+                .unwrap();
+        }
+        perfect_matching
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        let perfect_matching = self.perfect_matching();
+        self.subgraph_builder.load_perfect_matching(&perfect_matching);
+        let subgraph = self.subgraph_builder.get_subgraph();
+        if let Some(visualizer) = visualizer {
+            visualizer
+                .snapshot_combined(
+                    "perfect matching and subgraph".to_string(),
+                    vec![
+                        &self.interface_ptr,
+                        &self.dual_module,
+                        &perfect_matching,
+                        &VisualizeSubgraph::new(&subgraph),
+                    ],
+                )
+                .unwrap();
+        }
+        subgraph
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        self.interface_ptr.read_recursive().sum_dual_variables
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        primal_dual_profiler_report(
+            self.dual_module.generate_profiler_report(),
+            self.primal_module.generate_profiler_report(),
+        )
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        let primal_module = self.primal_module.read_recursive();
+        json!({
+            "dual": {
+                "rank_count": self.dual_module.ranks.len(),
+                "edges_in_fusion_unit": self.dual_module.config.edges_in_fusion_unit,
+            },
+            "primal": {
+                "max_tree_size": primal_module.max_tree_size,
+                "max_dual_node_count": primal_module.max_dual_node_count,
+                "max_blossom_depth": primal_module.max_blossom_depth,
+            },
+            "partition": self.dual_module.parallel.partition_info.config,
+        })
+    }
+    fn set_record_repro(&mut self, enabled: bool) {
+        self.record_repro = enabled;
+    }
+    fn repro(&self) -> Option<Repro> {
+        let syndrome_pattern = self.last_syndrome_pattern.as_ref()?;
+        Some(Repro::new(
+            ReproSolverType::ChannelParallel,
+            &self.initializer,
+            Some(&self.dual_module.parallel.partition_info.config),
+            self.effective_config(),
+            syndrome_pattern,
+        ))
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.subgraph_builder.enable_parity_check = enabled;
+    }
+}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct SolverParallel {
+    pub dual_module: DualModuleParallel<DualModuleSerial>,
+    pub primal_module: PrimalModuleParallel,
+    pub subgraph_builder: SubGraphBuilder,
+    pub initializer: SolverInitializer,
+    /// the syndrome pattern of the last solve, for [`PrimalDualSolver::repro`]
+    pub last_syndrome_pattern: Option<SyndromePattern>,
+    /// whether [`Self::last_syndrome_pattern`] is recorded, see [`PrimalDualSolver::set_record_repro`]
+    pub record_repro: bool,
+}
+
+bind_trait_fusion_visualizer!(SolverParallel);
+impl FusionVisualizer for SolverParallel {
+    fn snapshot(&self, abbrev: bool) -> serde_json::Value {
+        let mut value = self.primal_module.snapshot(abbrev);
+        snapshot_combine_values(&mut value, self.dual_module.snapshot(abbrev), abbrev);
+        value
+    }
+}
+
+#[cfg(feature = "python_binding")]
+bind_trait_primal_dual_solver! {SolverParallel}
+
+#[cfg(feature = "python_binding")]
+#[pymethods]
+impl SolverParallel {
+    #[new]
+    pub fn new_python(
+        initializer: &SolverInitializer,
+        partition_info: &PartitionInfo,
+        primal_dual_config: PyObject,
+    ) -> Self {
+        let primal_dual_config = pyobject_to_json(primal_dual_config);
+        Self::new(initializer, partition_info, primal_dual_config)
+    }
+}
+
+impl SolverParallel {
+    pub fn new(
+        initializer: &SolverInitializer,
+        partition_info: &PartitionInfo,
+        primal_dual_config: serde_json::Value,
+    ) -> Self {
+        let (dual_config, primal_config) = Self::parse_config(primal_dual_config);
+        let cache = DualModuleParallelCache::new(initializer, partition_info, dual_config.edges_in_fusion_unit);
+        Self::new_modules(initializer, cache, dual_config, primal_config)
+    }
+
+    /// create a solver from the partitioned initializers computed in advance, see [`DualModuleParallel::new_from_cached`]
+    pub fn new_from_cached(
+        initializer: &SolverInitializer,
+        cache: DualModuleParallelCache,
+        primal_dual_config: serde_json::Value,
+    ) -> Self {
+        let (dual_config, primal_config) = Self::parse_config(primal_dual_config);
+        Self::new_modules(initializer, cache, dual_config, primal_config)
+    }
+
+    fn parse_config(mut primal_dual_config: serde_json::Value) -> (DualModuleParallelConfig, PrimalModuleParallelConfig) {
+        let primal_dual_config = primal_dual_config.as_object_mut().expect("config must be JSON object");
+        let mut dual_config = DualModuleParallelConfig::default();
+        let mut primal_config = PrimalModuleParallelConfig::default();
+        if let Some(value) = primal_dual_config.remove("dual") {
+            dual_config = serde_json::from_value(value).unwrap();
+        }
+        if let Some(value) = primal_dual_config.remove("primal") {
+            primal_config = serde_json::from_value(value).unwrap();
+        }
+        if !primal_dual_config.is_empty() {
+            panic!(
+                "unknown primal_dual_config keys: {:?}",
+                primal_dual_config.keys().collect::<Vec<&String>>()
+            );
+        }
+        (dual_config, primal_config)
+    }
+
+    fn new_modules(
+        initializer: &SolverInitializer,
+        cache: DualModuleParallelCache,
+        dual_config: DualModuleParallelConfig,
+        primal_config: PrimalModuleParallelConfig,
+    ) -> Self {
+        let primal_module = PrimalModuleParallel::new_config(initializer, &cache.partition_info, primal_config);
+        Self {
+            dual_module: DualModuleParallel::new_from_cached(cache, dual_config),
+            primal_module,
+            subgraph_builder: SubGraphBuilder::new(initializer),
+            initializer: initializer.clone(),
+            last_syndrome_pattern: None,
+            record_repro: false,
+        }
+    }
+
+    /// the defect pairs matched across the interfaces, see [`PerfectMatching::boundary_pairs`]
+    pub fn boundary_pairs(&mut self) -> Vec<BoundaryPair> {
+        let partition_info = self.primal_module.partition_info.clone();
+        self.perfect_matching().boundary_pairs(&partition_info)
+    }
+
+    fn load_syndrome_pattern(&mut self, syndrome_pattern: &SyndromePattern) {
         if !syndrome_pattern.erasures.is_empty() {
+            assert!(
+                syndrome_pattern.dynamic_weights.is_empty(),
+                "erasures and dynamic_weights cannot be provided at the same time"
+            );
             self.subgraph_builder.load_erasures(&syndrome_pattern.erasures);
+            self.dual_module.load_erasures(&syndrome_pattern.erasures);
+        }
+        if !syndrome_pattern.dynamic_weights.is_empty() {
+            self.subgraph_builder.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+            self.dual_module.load_dynamic_weights(&syndrome_pattern.dynamic_weights);
+        }
+        self.subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
+        if self.record_repro {
+            self.last_syndrome_pattern = Some(syndrome_pattern.clone());
         }
+    }
+}
+
+impl PrimalDualSolver for SolverParallel {
+    fn clear(&mut self) {
+        #[cfg(feature = "track_allocations")]
+        crate::allocation_tracker::begin_shot();
+        self.dual_module.clear();
+        self.primal_module.clear();
+        self.subgraph_builder.clear();
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        self.load_syndrome_pattern(syndrome_pattern);
         self.primal_module
             .parallel_solve_visualizer(syndrome_pattern, &self.dual_module, visualizer);
     }
+    fn solve_with_budget(&mut self, syndrome_pattern: &SyndromePattern, budget: SolveBudget) -> BudgetedSolution {
+        self.load_syndrome_pattern(syndrome_pattern);
+        let truncated = self
+            .primal_module
+            .parallel_solve_budget(syndrome_pattern, &self.dual_module, budget);
+        BudgetedSolution {
+            perfect_matching: self.perfect_matching(),
+            truncated,
+        }
+    }
     fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
         let useless_interface_ptr = DualModuleInterfacePtr::new_empty(); // don't actually use it
         let perfect_matching = self
@@ -606,55 +1492,69 @@ impl PrimalDualSolver for SolverParallel {
         sum_dual_variables
     }
     fn generate_profiler_report(&self) -> serde_json::Value {
+        primal_dual_profiler_report(
+            self.dual_module.generate_profiler_report(),
+            self.primal_module.generate_profiler_report(),
+        )
+    }
+    fn effective_config(&self) -> serde_json::Value {
         json!({
-            "dual": self.dual_module.generate_profiler_report(),
-            "primal": self.primal_module.generate_profiler_report(),
+            "dual": self.dual_module.effective_config(),
+            "primal": self.primal_module.effective_config(),
+            "partition": self.primal_module.partition_info.config,
         })
     }
+    fn set_record_repro(&mut self, enabled: bool) {
+        self.record_repro = enabled;
+    }
+    fn repro(&self) -> Option<Repro> {
+        let syndrome_pattern = self.last_syndrome_pattern.as_ref()?;
+        Some(Repro::new(
+            ReproSolverType::Parallel,
+            &self.initializer,
+            Some(&self.primal_module.partition_info.config),
+            self.effective_config(),
+            syndrome_pattern,
+        ))
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.subgraph_builder.enable_parity_check = enabled;
+    }
 }
 
 #[cfg_attr(feature = "python_binding", cfg_eval)]
 #[cfg_attr(feature = "python_binding", pyclass)]
 pub struct SolverErrorPatternLogger {
-    pub file: BufWriter<File>,
+    pub writer: SyndromeFileWriter,
 }
 
 #[cfg(feature = "python_binding")]
 bind_trait_primal_dual_solver! {SolverErrorPatternLogger}
 
 impl SolverErrorPatternLogger {
+    /// the file is in the binary format if `format` is `"binary"` or the filename ends with `.bin`, see [`SyndromeFileFormat`]
     pub fn new(initializer: &SolverInitializer, positions: &Vec<VisualizePosition>, mut config: serde_json::Value) -> Self {
         let mut filename = "tmp/syndrome_patterns.txt".to_string();
         let config = config.as_object_mut().expect("config must be JSON object");
         if let Some(value) = config.remove("filename") {
             filename = value.as_str().expect("filename string").to_string();
         }
+        let format = match config.remove("format") {
+            Some(value) => serde_json::from_value(value).expect("format: \"text\" or \"binary\""),
+            None => SyndromeFileFormat::from_filename(&filename),
+        };
         if !config.is_empty() {
             panic!("unknown config keys: {:?}", config.keys().collect::<Vec<&String>>());
         }
-        let file = File::create(filename).unwrap();
-        let mut file = BufWriter::new(file);
-        file.write_all(b"Syndrome Pattern v1.0   <initializer> <positions> <syndrome_pattern>*\n")
-            .unwrap();
-        serde_json::to_writer(&mut file, &initializer).unwrap(); // large object write to file directly
-        file.write_all(b"\n").unwrap();
-        serde_json::to_writer(&mut file, &positions).unwrap();
-        file.write_all(b"\n").unwrap();
-        Self { file }
+        let writer = SyndromeFileWriter::create(&filename, format, initializer, positions).unwrap();
+        Self { writer }
     }
 }
 
 impl PrimalDualSolver for SolverErrorPatternLogger {
     fn clear(&mut self) {}
     fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, _visualizer: Option<&mut Visualizer>) {
-        self.file
-            .write_all(
-                serde_json::to_string(&serde_json::json!(syndrome_pattern))
-                    .unwrap()
-                    .as_bytes(),
-            )
-            .unwrap();
-        self.file.write_all(b"\n").unwrap();
+        self.writer.write(syndrome_pattern).unwrap();
     }
     fn perfect_matching_visualizer(&mut self, _visualizer: Option<&mut Visualizer>) -> PerfectMatching {
         panic!("error pattern logger do not actually solve the problem, please use Verifier::None by `--verifier none`")
@@ -699,7 +1599,12 @@ impl PrimalDualSolver for SolverBlossomV {
     fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
         assert!(visualizer.is_none(), "not supported");
         assert!(syndrome_pattern.erasures.is_empty(), "doesn't support erasure for now");
+        assert!(
+            syndrome_pattern.dynamic_weights.is_empty(),
+            "doesn't support dynamic weights, because the complete graph is prebuilt"
+        );
         let defect_vertices = &syndrome_pattern.defect_vertices;
+        self.subgraph_builder.load_defect_vertices(defect_vertices);
         if defect_vertices.is_empty() {
             return;
         }
@@ -823,6 +1728,2447 @@ impl PrimalDualSolver for SolverBlossomV {
     fn generate_profiler_report(&self) -> serde_json::Value {
         json!({})
     }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.subgraph_builder.enable_parity_check = enabled;
+    }
+}
+
+/// the decoding result of a syndrome pattern memorized by [`SolverCached`]
+#[derive(Debug, Clone)]
+pub struct SolverCachedEntry {
+    /// matched pairs of defect vertices
+    pub peer_matchings: Vec<(VertexIndex, VertexIndex)>,
+    /// defect vertices matched to virtual vertices: (defect_vertex, virtual_vertex)
+    pub virtual_matchings: Vec<(VertexIndex, VertexIndex)>,
+    /// the subgraph of the matching
+    pub subgraph: Vec<EdgeIndex>,
+    /// the weight of the matching
+    pub sum_dual_variables: Weight,
+    /// the last time this entry is used, to find the least recently used entry
+    last_used: usize,
+}
+
+/// a solver that memorizes the results of recently decoded syndrome patterns and only calls the wrapped solver on a miss;
+/// this is useful for small codes where many shots produce identical syndrome patterns.
+/// when the cache is full, the least recently used entry is evicted.
+/// the visualizer is only passed to the wrapped solver on a miss: a hit doesn't run any solver and records no snapshot
+pub struct SolverCached {
+    /// the wrapped solver
+    pub solver: Box<dyn PrimalDualSolver>,
+    /// the maximum number of entries in the cache
+    pub capacity: usize,
+    /// the normalized syndrome pattern (sorted) to the decoding result
+    cache: HashMap<SyndromePattern, SolverCachedEntry>,
+    /// the order of usage: last_used -> syndrome pattern
+    usage: BTreeMap<usize, SyndromePattern>,
+    /// counter of solve calls, also used as the timestamp of usage
+    timestamp: usize,
+    /// the result of the current syndrome pattern
+    current: Option<SolverCachedEntry>,
+    /// number of cache hits
+    pub hits: usize,
+    /// number of cache misses
+    pub misses: usize,
+}
+
+impl SolverCached {
+    pub fn new(solver: Box<dyn PrimalDualSolver>, capacity: usize) -> Self {
+        assert!(capacity > 0, "cache capacity must be positive");
+        Self {
+            solver,
+            capacity,
+            cache: HashMap::new(),
+            usage: BTreeMap::new(),
+            timestamp: 0,
+            current: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// the ratio of solve calls that are served by the cache
+    pub fn hit_rate(&self) -> f64 {
+        if self.hits + self.misses == 0 {
+            return 0.;
+        }
+        self.hits as f64 / (self.hits + self.misses) as f64
+    }
+
+    /// the number of syndrome patterns currently in the cache
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    /// drop all cached entries and statistics
+    pub fn reset_cache(&mut self) {
+        self.cache.clear();
+        self.usage.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    fn normalize(syndrome_pattern: &SyndromePattern) -> SyndromePattern {
+        let mut key = syndrome_pattern.clone();
+        key.defect_vertices.sort_unstable();
+        key.erasures.sort_unstable();
+        key.dynamic_weights.sort_unstable();
+        key
+    }
+
+    fn get_vertex(dual_node_ptr: &DualNodePtr) -> VertexIndex {
+        match &dual_node_ptr.read_recursive().class {
+            DualNodeClass::DefectVertex { defect_index } => *defect_index,
+            _ => unreachable!("perfect matching can only contain defect vertices"),
+        }
+    }
+}
+
+impl PrimalDualSolver for SolverCached {
+    fn clear(&mut self) {
+        self.solver.clear();
+        self.current = None;
+    }
+    fn reset_profiler(&mut self) {
+        self.solver.reset_profiler();
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        self.timestamp += 1;
+        let key = Self::normalize(syndrome_pattern);
+        if let Some(entry) = self.cache.get_mut(&key) {
+            self.hits += 1;
+            self.usage.remove(&entry.last_used);
+            entry.last_used = self.timestamp;
+            self.usage.insert(self.timestamp, key);
+            self.current = Some(entry.clone());
+            return;
+        }
+        self.misses += 1;
+        self.solver.solve_visualizer(syndrome_pattern, visualizer);
+        let perfect_matching = self.solver.perfect_matching();
+        let entry = SolverCachedEntry {
+            peer_matchings: perfect_matching
+                .peer_matchings
+                .iter()
+                .map(|(ptr_1, ptr_2)| (Self::get_vertex(ptr_1), Self::get_vertex(ptr_2)))
+                .collect(),
+            virtual_matchings: perfect_matching
+                .virtual_matchings
+                .iter()
+                .map(|(ptr, virtual_vertex)| (Self::get_vertex(ptr), *virtual_vertex))
+                .collect(),
+            subgraph: self.solver.subgraph(),
+            sum_dual_variables: self.solver.sum_dual_variables(),
+            last_used: self.timestamp,
+        };
+        if self.cache.len() >= self.capacity {
+            let (_, evicted) = self.usage.pop_first().unwrap();
+            self.cache.remove(&evicted);
+        }
+        self.usage.insert(self.timestamp, key.clone());
+        self.cache.insert(key, entry.clone());
+        self.current = Some(entry);
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        assert!(visualizer.is_none(), "not supported");
+        let entry = self
+            .current
+            .as_ref()
+            .expect("call `solve` before getting the perfect matching");
+        let mut perfect_matching = PerfectMatching::new();
+        let interface_ptr = DualModuleInterfacePtr::new_empty();
+        let mut counter = 0;
+        let mut create_dual_node = |vertex_index: VertexIndex| {
+            counter += 1;
+            DualNodePtr::new_value(DualNode {
+                index: counter,
+                class: DualNodeClass::DefectVertex {
+                    defect_index: vertex_index,
+                },
+                grow_state: DualNodeGrowState::Grow,
+                parent_blossom: None,
+                dual_variable_cache: (0, 0),
+                belonging: interface_ptr.downgrade(),
+                defect_size: nz!(1usize),
+            })
+        };
+        for &(vertex_1, vertex_2) in entry.peer_matchings.iter() {
+            perfect_matching
+                .peer_matchings
+                .push((create_dual_node(vertex_1), create_dual_node(vertex_2)));
+        }
+        for &(vertex, virtual_vertex) in entry.virtual_matchings.iter() {
+            perfect_matching
+                .virtual_matchings
+                .push((create_dual_node(vertex), virtual_vertex));
+        }
+        perfect_matching
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        assert!(visualizer.is_none(), "not supported");
+        let entry = self.current.as_ref().expect("call `solve` before getting the subgraph");
+        entry.subgraph.clone()
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        let entry = self
+            .current
+            .as_ref()
+            .expect("call `solve` before getting the sum of dual variables");
+        entry.sum_dual_variables
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solver.generate_profiler_report(),
+            "cache": {
+                "hits": self.hits,
+                "misses": self.misses,
+                "hit_rate": self.hit_rate(),
+                "size": self.cache.len(),
+            },
+        })
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solver.effective_config(),
+            "cache_capacity": self.capacity,
+        })
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.solver.set_parity_check(enabled);
+    }
+    fn set_record_repro(&mut self, enabled: bool) {
+        self.solver.set_record_repro(enabled);
+    }
+    /// the wrapped solver only records the misses, so the syndrome pattern is replaced by that of the last solve
+    fn repro(&self) -> Option<Repro> {
+        let mut repro = self.solver.repro()?;
+        repro.syndrome_pattern = self.usage.get(&self.timestamp)?.clone();
+        Some(repro)
+    }
+}
+
+/// an ensemble of serial solvers that decode the same syndrome pattern with different deterministic tie-breaking seeds;
+/// in highly degenerate regimes many minimum-weight corrections exist with different logical outcomes, and a majority vote
+/// over the ensemble is more accurate than an arbitrary one. The cost scales linearly with the ensemble size.
+///
+/// Each member scales all weights by a common factor and adds a small seeded perturbation that is guaranteed to be smaller
+/// than the gap between two different (even) weights, so every member still returns a minimum-weight correction
+/// of the original graph.
+pub struct SolverEnsemble {
+    /// the members, each with differently perturbed weights
+    pub solvers: Vec<SolverSerial>,
+    /// the original edge weights
+    pub weights: Vec<Weight>,
+    /// the factor that all weights are scaled by in each member
+    pub weight_scale: Weight,
+    /// `logical_edges[edge_index]` is true if flipping this edge flips the logical observable
+    pub logical_edges: Vec<bool>,
+    /// the seed of the first member; member `i` uses `seed + i`
+    pub seed: u64,
+    /// the correction of each member for the current syndrome pattern
+    subgraphs: Vec<Vec<EdgeIndex>>,
+    /// the logical outcome of each member for the current syndrome pattern
+    outcomes: Vec<bool>,
+    /// the member whose correction is reported
+    chosen: Option<usize>,
+    /// the weights overwritten by erasures and dynamic weights of the current syndrome pattern
+    overwritten_weights: HashMap<EdgeIndex, Weight>,
+}
+
+impl SolverEnsemble {
+    /// the perturbation added to each scaled weight is an even number smaller than `2 * PERTURBATION_RANGE`
+    const PERTURBATION_RANGE: Weight = 16;
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer, ensemble_size: usize, logical_edges: Vec<bool>, seed: u64) -> Self {
+        assert!(ensemble_size > 0, "at least one member is required");
+        assert_eq!(
+            logical_edges.len(),
+            initializer.weighted_edges.len(),
+            "logical edges must be given for each edge"
+        );
+        for (i, j, weight) in initializer.weighted_edges.iter() {
+            assert!(weight % 2 == 0, "edge {}-{} has odd weight value {}", i, j, weight);
+        }
+        // any two corrections with different weights differ by at least 2 * weight_scale, which is larger than the sum of
+        // perturbations of all edges (each smaller than 2 * PERTURBATION_RANGE)
+        let weight_scale = (initializer.weighted_edges.len().max(1) as Weight) * Self::PERTURBATION_RANGE;
+        let weights: Vec<Weight> = initializer.weighted_edges.iter().map(|(_, _, weight)| *weight).collect();
+        let max_safe_weight = ((Weight::MAX as usize) / initializer.vertex_num.max(1) as usize) as Weight;
+        let max_weight = weights.iter().cloned().max().unwrap_or(0);
+        assert!(
+            max_weight <= (max_safe_weight - 2 * Self::PERTURBATION_RANGE) / weight_scale,
+            "weights are too large to be perturbed without overflow"
+        );
+        let solvers = (0..ensemble_size)
+            .map(|member| {
+                let mut rng = DeterministicRng::seed_from_u64(seed + member as u64);
+                let mut member_initializer = initializer.clone();
+                for (_, _, weight) in member_initializer.weighted_edges.iter_mut() {
+                    let perturbation = (rng.next_f64() * Self::PERTURBATION_RANGE as f64) as Weight;
+                    *weight = *weight * weight_scale + 2 * perturbation;
+                }
+                SolverSerial::new(&member_initializer)
+            })
+            .collect();
+        Self {
+            solvers,
+            weights,
+            weight_scale,
+            logical_edges,
+            seed,
+            subgraphs: vec![],
+            outcomes: vec![],
+            chosen: None,
+            overwritten_weights: HashMap::new(),
+        }
+    }
+
+    /// the logical outcome of each member for the current syndrome pattern
+    pub fn outcomes(&self) -> &[bool] {
+        &self.outcomes
+    }
+
+    /// the number of members voting for (no logical flip, logical flip)
+    pub fn vote_counts(&self) -> (usize, usize) {
+        let flips = self.outcomes.iter().filter(|&&outcome| outcome).count();
+        (self.outcomes.len() - flips, flips)
+    }
+
+    /// the majority logical outcome; a tie is resolved towards the first member
+    pub fn logical_outcome(&self) -> bool {
+        let member = self.chosen.expect("call `solve` before getting the logical outcome");
+        self.outcomes[member]
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn logical_outcome_of(&self, subgraph: &[EdgeIndex]) -> bool {
+        subgraph
+            .iter()
+            .filter(|&&edge_index| self.logical_edges[edge_index as usize])
+            .count()
+            % 2
+            == 1
+    }
+}
+
+impl PrimalDualSolver for SolverEnsemble {
+    fn clear(&mut self) {
+        for solver in self.solvers.iter_mut() {
+            solver.clear();
+        }
+        self.subgraphs.clear();
+        self.outcomes.clear();
+        self.chosen = None;
+        self.overwritten_weights.clear();
+    }
+    fn reset_profiler(&mut self) {
+        for solver in self.solvers.iter_mut() {
+            solver.reset_profiler();
+        }
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        assert!(visualizer.is_none(), "not supported");
+        let mut member_syndrome_pattern = syndrome_pattern.clone();
+        for (_, weight) in member_syndrome_pattern.dynamic_weights.iter_mut() {
+            *weight *= self.weight_scale;
+        }
+        self.overwritten_weights = (syndrome_pattern.erasures.iter().map(|&edge_index| (edge_index, 0)))
+            .chain(syndrome_pattern.dynamic_weights.iter().cloned())
+            .collect();
+        self.subgraphs = self
+            .solvers
+            .par_iter_mut()
+            .map(|solver| {
+                solver.solve(&member_syndrome_pattern);
+                solver.subgraph()
+            })
+            .collect();
+        self.outcomes = self
+            .subgraphs
+            .iter()
+            .map(|subgraph| self.logical_outcome_of(subgraph))
+            .collect();
+        let (no_flips, flips) = self.vote_counts();
+        let majority = if flips == no_flips {
+            self.outcomes[0]
+        } else {
+            flips > no_flips
+        };
+        self.chosen = self.outcomes.iter().position(|&outcome| outcome == majority);
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        let member = self.chosen.expect("call `solve` before getting the perfect matching");
+        self.solvers[member].perfect_matching_visualizer(visualizer)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        assert!(visualizer.is_none(), "not supported");
+        let member = self.chosen.expect("call `solve` before getting the subgraph");
+        self.subgraphs[member].clone()
+    }
+    /// the weight of the reported correction in the original graph, which is also the sum of dual variables
+    #[allow(clippy::unnecessary_cast)]
+    fn sum_dual_variables(&self) -> Weight {
+        let member = self.chosen.expect("call `solve` before getting the sum of dual variables");
+        self.subgraphs[member]
+            .iter()
+            .map(|edge_index| {
+                self.overwritten_weights
+                    .get(edge_index)
+                    .cloned()
+                    .unwrap_or(self.weights[*edge_index as usize])
+            })
+            .sum()
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        let (no_flips, flips) = self.vote_counts();
+        json!({
+            "members": self.solvers.iter().map(|solver| solver.generate_profiler_report()).collect::<Vec<_>>(),
+            "votes": [no_flips, flips],
+        })
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solvers[0].effective_config(),
+            "ensemble_size": self.solvers.len(),
+            "seed": self.seed,
+        })
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        for solver in self.solvers.iter_mut() {
+            solver.set_parity_check(enabled);
+        }
+    }
+}
+
+/// configuration of [`SolverRecalibrated`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecalibrationConfig {
+    /// the number of shots between two weight updates
+    #[serde(default = "recalibration_default_configs::update_interval")]
+    pub update_interval: usize,
+    /// how far the estimated probability moves towards the observed correction frequency in each update, in `(0, 1]`
+    #[serde(default = "recalibration_default_configs::learning_rate")]
+    pub learning_rate: f64,
+    /// guardrail: the estimated probability of an edge never deviates from its prior by more than this factor
+    #[serde(default = "recalibration_default_configs::max_probability_ratio")]
+    pub max_probability_ratio: f64,
+    /// guardrail: an edge is only updated when it is expected to be corrected at least this many times in a window,
+    /// otherwise the observed frequency is too noisy to be trusted
+    #[serde(default = "recalibration_default_configs::min_expected_count")]
+    pub min_expected_count: f64,
+    /// the maximum number of entries in the audit log; the oldest entries are dropped first
+    #[serde(default = "recalibration_default_configs::audit_log_capacity")]
+    pub audit_log_capacity: usize,
+}
+
+impl Default for RecalibrationConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+pub mod recalibration_default_configs {
+    pub fn update_interval() -> usize {
+        10000
+    }
+    pub fn learning_rate() -> f64 {
+        0.1
+    }
+    pub fn max_probability_ratio() -> f64 {
+        4.
+    }
+    pub fn min_expected_count() -> f64 {
+        10.
+    }
+    pub fn audit_log_capacity() -> usize {
+        100000
+    }
+}
+
+/// an entry of the audit log of [`SolverRecalibrated`]
+#[derive(Debug, Clone, Serialize)]
+pub struct WeightChange {
+    /// the number of shots decoded before this change
+    pub shot: usize,
+    pub edge_index: EdgeIndex,
+    pub old_weight: Weight,
+    pub new_weight: Weight,
+    /// the correction frequency of this edge observed in the last window
+    pub observed_frequency: f64,
+    /// the estimated error probability after this update
+    pub probability: f64,
+}
+
+/// a solver that slowly adjusts the edge weights during a long experiment ("online recalibration"): every `update_interval`
+/// shots, the estimated error probability of each edge moves towards how often the edge is actually corrected, and the
+/// weights are recomputed with the same scale as the initial weights. The new weights are passed to the wrapped solver
+/// as dynamic weights, so the wrapped solver is never rebuilt; weights given in the syndrome pattern take priority.
+/// The latest weight changes are recorded in [`SolverRecalibrated::audit_log`]
+pub struct SolverRecalibrated {
+    /// the wrapped solver
+    pub solver: Box<dyn PrimalDualSolver>,
+    pub config: RecalibrationConfig,
+    /// the initial edge weights
+    pub initial_weights: Vec<Weight>,
+    /// the prior error probabilities of edges, from which the initial weights are computed
+    pub prior_probabilities: Vec<f64>,
+    /// the current estimate of the error probabilities
+    pub probabilities: Vec<f64>,
+    /// the weights that differ from the initial weights
+    pub recalibrated_weights: BTreeMap<EdgeIndex, Weight>,
+    /// the latest weight changes, at most `audit_log_capacity` of them
+    pub audit_log: VecDeque<WeightChange>,
+    /// the number of weight changes since the beginning, including those dropped from the audit log
+    pub weight_changes: usize,
+    /// the number of shots decoded since the beginning
+    pub shots: usize,
+    /// the number of times each edge is corrected in the current window
+    correction_counts: Vec<usize>,
+    /// the number of shots in the current window
+    window_shots: usize,
+    /// the subgraph of the current syndrome pattern
+    current_subgraph: Option<Vec<EdgeIndex>>,
+}
+
+impl SolverRecalibrated {
+    pub fn new(
+        solver: Box<dyn PrimalDualSolver>,
+        initializer: &SolverInitializer,
+        prior_probabilities: Vec<f64>,
+        config: RecalibrationConfig,
+    ) -> Self {
+        assert_eq!(
+            prior_probabilities.len(),
+            initializer.weighted_edges.len(),
+            "prior probabilities must be given for each edge"
+        );
+        for (edge_index, &probability) in prior_probabilities.iter().enumerate() {
+            assert!(
+                probability > 0. && probability < 0.5,
+                "prior probability {} of edge {} must be in (0, 0.5)",
+                probability,
+                edge_index
+            );
+        }
+        assert!(config.update_interval > 0, "update interval must be positive");
+        assert!(
+            config.learning_rate > 0. && config.learning_rate <= 1.,
+            "learning rate must be in (0, 1]"
+        );
+        assert!(config.max_probability_ratio >= 1., "max probability ratio must be at least 1");
+        Self {
+            solver,
+            config,
+            initial_weights: initializer.weighted_edges.iter().map(|(_, _, weight)| *weight).collect(),
+            probabilities: prior_probabilities.clone(),
+            prior_probabilities,
+            recalibrated_weights: BTreeMap::new(),
+            audit_log: VecDeque::new(),
+            weight_changes: 0,
+            shots: 0,
+            correction_counts: vec![0; initializer.weighted_edges.len()],
+            window_shots: 0,
+            current_subgraph: None,
+        }
+    }
+
+    /// the current weight of an edge
+    #[allow(clippy::unnecessary_cast)]
+    pub fn weight(&self, edge_index: EdgeIndex) -> Weight {
+        match self.recalibrated_weights.get(&edge_index) {
+            Some(weight) => *weight,
+            None => self.initial_weights[edge_index as usize],
+        }
+    }
+
+    /// the weight of a probability, using the same scale as the initial weight of the edge; always even
+    fn weight_of(&self, edge_index: usize, probability: f64) -> Weight {
+        let log_odds = |p: f64| ((1. - p) / p).ln();
+        let prior = self.prior_probabilities[edge_index];
+        let weight = self.initial_weights[edge_index] as f64 * log_odds(probability) / log_odds(prior);
+        ((weight / 2.).round() as Weight).max(0) * 2
+    }
+
+    /// update the estimated probabilities and weights from the correction frequencies observed in the last window
+    #[allow(clippy::unnecessary_cast)]
+    fn recalibrate(&mut self) {
+        let window_shots = self.window_shots as f64;
+        for edge_index in 0..self.probabilities.len() {
+            let probability = self.probabilities[edge_index];
+            if probability * window_shots < self.config.min_expected_count {
+                continue;
+            }
+            let observed_frequency = self.correction_counts[edge_index] as f64 / window_shots;
+            let prior = self.prior_probabilities[edge_index];
+            let probability = (probability + self.config.learning_rate * (observed_frequency - probability)).clamp(
+                prior / self.config.max_probability_ratio,
+                (prior * self.config.max_probability_ratio).min(0.5),
+            );
+            self.probabilities[edge_index] = probability;
+            let old_weight = self.weight(edge_index as EdgeIndex);
+            let new_weight = self.weight_of(edge_index, probability);
+            if new_weight == old_weight {
+                continue;
+            }
+            if new_weight == self.initial_weights[edge_index] {
+                self.recalibrated_weights.remove(&(edge_index as EdgeIndex));
+            } else {
+                self.recalibrated_weights.insert(edge_index as EdgeIndex, new_weight);
+            }
+            if self.audit_log.len() >= self.config.audit_log_capacity {
+                self.audit_log.pop_front();
+            }
+            self.weight_changes += 1;
+            self.audit_log.push_back(WeightChange {
+                shot: self.shots,
+                edge_index: edge_index as EdgeIndex,
+                old_weight,
+                new_weight,
+                observed_frequency,
+                probability,
+            });
+        }
+        self.correction_counts.iter_mut().for_each(|count| *count = 0);
+        self.window_shots = 0;
+    }
+}
+
+impl PrimalDualSolver for SolverRecalibrated {
+    fn clear(&mut self) {
+        self.solver.clear();
+        self.current_subgraph = None;
+    }
+    fn reset_profiler(&mut self) {
+        self.solver.reset_profiler();
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        if self.recalibrated_weights.is_empty() {
+            self.solver.solve_visualizer(syndrome_pattern, visualizer);
+        } else {
+            let mut dynamic_weights = self.recalibrated_weights.clone();
+            dynamic_weights.extend(syndrome_pattern.erasures.iter().map(|&edge_index| (edge_index, 0)));
+            dynamic_weights.extend(syndrome_pattern.dynamic_weights.iter().cloned());
+            let recalibrated_syndrome_pattern = SyndromePattern::new_dynamic_weights(
+                syndrome_pattern.defect_vertices.clone(),
+                vec![],
+                dynamic_weights.into_iter().collect(),
+            );
+            self.solver.solve_visualizer(&recalibrated_syndrome_pattern, visualizer);
+        }
+        let subgraph = self.solver.subgraph();
+        for &edge_index in subgraph.iter() {
+            self.correction_counts[edge_index as usize] += 1;
+        }
+        self.current_subgraph = Some(subgraph);
+        self.shots += 1;
+        self.window_shots += 1;
+        if self.window_shots >= self.config.update_interval {
+            self.recalibrate();
+        }
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        self.solver.perfect_matching_visualizer(visualizer)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        if visualizer.is_some() {
+            return self.solver.subgraph_visualizer(visualizer);
+        }
+        self.current_subgraph
+            .clone()
+            .expect("call `solve` before getting the subgraph")
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        self.solver.sum_dual_variables()
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solver.generate_profiler_report(),
+            "recalibration": {
+                "shots": self.shots,
+                "recalibrated_edges": self.recalibrated_weights.len(),
+                "weight_changes": self.weight_changes,
+            },
+        })
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solver.effective_config(),
+            "recalibration": self.config,
+        })
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.solver.set_parity_check(enabled);
+    }
+    fn set_record_repro(&mut self, enabled: bool) {
+        self.solver.set_record_repro(enabled);
+    }
+    /// the reproduction of the wrapped solver, including the recalibrated weights of the last solve
+    fn repro(&self) -> Option<Repro> {
+        self.solver.repro()
+    }
+}
+
+/// configuration of [`SolverAdaptive`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AdaptiveConfig {
+    /// shots with at most this many defects are decoded by the union-find pre-pass, i.e. `max_tree_size = 0`, and the
+    /// others by the full MWPM; learned by [`SolverAdaptive::calibrate`]
+    #[serde(default = "adaptive_default_configs::union_find_max_defects")]
+    pub union_find_max_defects: usize,
+    /// calibration: the minimum fraction of shots for which the union-find pre-pass finds a minimum-weight matching
+    #[serde(default = "adaptive_default_configs::min_agreement")]
+    pub min_agreement: f64,
+    /// calibration: a defect count with fewer calibration shots is not trusted to be decoded by union-find
+    #[serde(default = "adaptive_default_configs::min_samples")]
+    pub min_samples: usize,
+}
+
+impl Default for AdaptiveConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+pub mod adaptive_default_configs {
+    pub fn union_find_max_defects() -> usize {
+        0
+    }
+    pub fn min_agreement() -> f64 {
+        0.99
+    }
+    pub fn min_samples() -> usize {
+        10
+    }
+}
+
+/// the algorithm chosen by [`SolverAdaptive`] or [`SolverHybrid`] for a shot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AdaptiveDecision {
+    /// no defect, the empty correction is returned without running the solver
+    Trivial,
+    /// the union-find pre-pass, which is much faster but not always minimum-weight
+    UnionFind,
+    /// the full MWPM
+    MWPM,
+}
+
+/// the decisions of [`SolverAdaptive`] or [`SolverHybrid`] since the beginning, reported in the profiler
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AdaptiveStatistics {
+    pub trivial_shots: usize,
+    pub union_find_shots: usize,
+    pub mwpm_shots: usize,
+    /// the total decoding time of the shots decoded by union-find
+    pub union_find_time: f64,
+    /// the total decoding time of the shots decoded by MWPM
+    pub mwpm_time: f64,
+}
+
+/// a serial solver that picks the cheapest algorithm that is good enough for each shot: empty shots take a fast path,
+/// sparse shots, where union-find almost always finds a minimum-weight matching, are decoded by the union-find
+/// pre-pass, and dense shots by the full MWPM. The defect count threshold is learned by [`SolverAdaptive::calibrate`]
+pub struct SolverAdaptive {
+    pub solver: SolverSerial,
+    pub config: AdaptiveConfig,
+    pub statistics: AdaptiveStatistics,
+    /// the decision of the current shot
+    decision: Option<AdaptiveDecision>,
+}
+
+impl SolverAdaptive {
+    pub fn new(initializer: &SolverInitializer, config: AdaptiveConfig) -> Self {
+        assert!(
+            config.min_agreement > 0. && config.min_agreement <= 1.,
+            "min agreement must be in (0, 1]"
+        );
+        Self {
+            solver: SolverSerial::new(initializer),
+            config,
+            statistics: AdaptiveStatistics::default(),
+            decision: None,
+        }
+    }
+
+    pub fn decide(&self, syndrome_pattern: &SyndromePattern) -> AdaptiveDecision {
+        let defect_num = syndrome_pattern.defect_vertices.len();
+        if defect_num == 0 {
+            AdaptiveDecision::Trivial
+        } else if defect_num <= self.config.union_find_max_defects {
+            AdaptiveDecision::UnionFind
+        } else {
+            AdaptiveDecision::MWPM
+        }
+    }
+
+    /// decode the calibration shots with both union-find and MWPM, and set `union_find_max_defects` to the largest defect
+    /// count such that union-find agrees with MWPM on the weight often enough for every defect count up to it;
+    /// returns the number of calibration shots and the agreements of each defect count
+    pub fn calibrate(&mut self, syndrome_patterns: &[SyndromePattern]) -> BTreeMap<usize, (usize, usize)> {
+        let mut bins: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+        for syndrome_pattern in syndrome_patterns.iter() {
+            let defect_num = syndrome_pattern.defect_vertices.len();
+            if defect_num == 0 {
+                continue;
+            }
+            let mut weights = [0; 2];
+            for (max_tree_size, weight) in [usize::MAX, 0].into_iter().zip(weights.iter_mut()) {
+                self.solver.primal_module.write().max_tree_size = max_tree_size;
+                self.solver.solve(syndrome_pattern);
+                self.solver.subgraph();
+                *weight = self.solver.subgraph_builder.total_weight();
+                self.solver.clear();
+            }
+            let bin = bins.entry(defect_num).or_default();
+            bin.0 += 1;
+            if weights[0] == weights[1] {
+                bin.1 += 1;
+            }
+        }
+        self.config.union_find_max_defects = 0;
+        for (&defect_num, &(samples, agreements)) in bins.iter() {
+            if samples < self.config.min_samples || (agreements as f64) < self.config.min_agreement * samples as f64 {
+                break;
+            }
+            self.config.union_find_max_defects = defect_num;
+        }
+        bins
+    }
+}
+
+impl PrimalDualSolver for SolverAdaptive {
+    fn clear(&mut self) {
+        if self.decision != Some(AdaptiveDecision::Trivial) {
+            self.solver.clear();
+        }
+        self.decision = None;
+    }
+    fn reset_profiler(&mut self) {
+        self.solver.reset_profiler();
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        let decision = self.decide(syndrome_pattern);
+        self.decision = Some(decision);
+        let max_tree_size = match decision {
+            AdaptiveDecision::Trivial => {
+                self.statistics.trivial_shots += 1;
+                return;
+            }
+            AdaptiveDecision::UnionFind => 0,
+            AdaptiveDecision::MWPM => usize::MAX,
+        };
+        self.solver.primal_module.write().max_tree_size = max_tree_size;
+        let begin = Instant::now();
+        self.solver.solve_visualizer(syndrome_pattern, visualizer);
+        let elapsed = begin.elapsed().as_secs_f64();
+        if decision == AdaptiveDecision::UnionFind {
+            self.statistics.union_find_shots += 1;
+            self.statistics.union_find_time += elapsed;
+        } else {
+            self.statistics.mwpm_shots += 1;
+            self.statistics.mwpm_time += elapsed;
+        }
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        if self.decision == Some(AdaptiveDecision::Trivial) {
+            return PerfectMatching::new();
+        }
+        self.solver.perfect_matching_visualizer(visualizer)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        if self.decision == Some(AdaptiveDecision::Trivial) {
+            return vec![];
+        }
+        self.solver.subgraph_visualizer(visualizer)
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        if self.decision == Some(AdaptiveDecision::Trivial) {
+            return 0;
+        }
+        self.solver.sum_dual_variables()
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solver.generate_profiler_report(),
+            "adaptive": self.statistics,
+        })
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solver.effective_config(),
+            "adaptive": self.config,
+        })
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.solver.set_parity_check(enabled);
+    }
+    fn set_record_repro(&mut self, enabled: bool) {
+        self.solver.set_record_repro(enabled);
+    }
+    fn repro(&self) -> Option<Repro> {
+        if self.decision == Some(AdaptiveDecision::Trivial) {
+            return None;
+        }
+        self.solver.repro()
+    }
+}
+
+/// configuration of [`SolverHybrid`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HybridConfig {
+    /// the union-find fast path gives up as soon as a cluster contains more defects than this, because a larger cluster
+    /// is more likely to be decoded differently from MWPM
+    #[serde(default = "hybrid_default_configs::max_cluster_defects")]
+    pub max_cluster_defects: usize,
+}
+
+impl Default for HybridConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+pub mod hybrid_default_configs {
+    pub fn max_cluster_defects() -> usize {
+        2
+    }
+}
+
+/// a serial solver for latency-sensitive workloads: each shot is first decoded by the union-find fast path, see
+/// [`PrimalModuleUnionFind`], and only if a cluster grows beyond `max_cluster_defects` or cannot become valid is the
+/// shot decoded again by the full MWPM. Unlike [`SolverAdaptive`], the decision is made per cluster rather than by the
+/// defect count of the whole shot. A solve with a visualizer always takes the MWPM path
+pub struct SolverHybrid {
+    pub union_find: PrimalModuleUnionFind,
+    pub solver: SolverSerial,
+    pub config: HybridConfig,
+    /// the time of a shot that falls back to MWPM includes the failed union-find attempt
+    pub statistics: AdaptiveStatistics,
+    /// the decision of the current shot
+    decision: Option<AdaptiveDecision>,
+    /// the defect nodes of the union-find fast path, loaded into the solver but never grown
+    defect_nodes: BTreeMap<VertexIndex, DualNodePtr>,
+}
+
+impl SolverHybrid {
+    pub fn new(initializer: &SolverInitializer, config: HybridConfig) -> Self {
+        let mut union_find = PrimalModuleUnionFind::new(initializer);
+        union_find.max_cluster_defects = config.max_cluster_defects;
+        Self {
+            union_find,
+            solver: SolverSerial::new(initializer),
+            config,
+            statistics: AdaptiveStatistics::default(),
+            decision: None,
+            defect_nodes: BTreeMap::new(),
+        }
+    }
+
+    /// the decision of the current shot, `None` before solving
+    pub fn decision(&self) -> Option<AdaptiveDecision> {
+        self.decision
+    }
+
+    /// load the defect nodes into the solver without growing them, so that the union-find matching can be expressed as
+    /// a regular [`PerfectMatching`]
+    fn load_defect_nodes(&mut self, syndrome_pattern: &SyndromePattern) {
+        for &vertex_index in syndrome_pattern.defect_vertices.iter() {
+            let node_ptr = (self.solver.interface_ptr).create_defect_node(vertex_index, &mut self.solver.dual_module);
+            self.defect_nodes.insert(vertex_index, node_ptr);
+        }
+        self.solver
+            .subgraph_builder
+            .load_defect_vertices(&syndrome_pattern.defect_vertices);
+    }
+
+    fn union_find_perfect_matching(&self) -> PerfectMatching {
+        let mut perfect_matching = PerfectMatching::new();
+        for (vertex_1, vertex_2) in self.union_find.peer_matchings.iter() {
+            let pair = (self.defect_nodes[vertex_1].clone(), self.defect_nodes[vertex_2].clone());
+            perfect_matching.peer_matchings.push(pair);
+        }
+        for (vertex_index, virtual_vertex) in self.union_find.virtual_matchings.iter() {
+            let pair = (self.defect_nodes[vertex_index].clone(), *virtual_vertex);
+            perfect_matching.virtual_matchings.push(pair);
+        }
+        perfect_matching
+    }
+}
+
+impl PrimalDualSolver for SolverHybrid {
+    fn clear(&mut self) {
+        self.union_find.clear();
+        if self.decision != Some(AdaptiveDecision::Trivial) {
+            self.solver.clear();
+        }
+        self.decision = None;
+        self.defect_nodes.clear();
+    }
+    fn reset_profiler(&mut self) {
+        self.solver.reset_profiler();
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        if syndrome_pattern.defect_vertices.is_empty() {
+            self.decision = Some(AdaptiveDecision::Trivial);
+            self.statistics.trivial_shots += 1;
+            return;
+        }
+        let begin = Instant::now();
+        let union_find_solved = visualizer.is_none()
+            && syndrome_pattern.detector_confidences.is_empty()
+            && self.union_find.solve(syndrome_pattern);
+        if union_find_solved {
+            self.decision = Some(AdaptiveDecision::UnionFind);
+            self.load_defect_nodes(syndrome_pattern);
+            self.statistics.union_find_shots += 1;
+            self.statistics.union_find_time += begin.elapsed().as_secs_f64();
+        } else {
+            self.decision = Some(AdaptiveDecision::MWPM);
+            self.solver.solve_visualizer(syndrome_pattern, visualizer);
+            self.statistics.mwpm_shots += 1;
+            self.statistics.mwpm_time += begin.elapsed().as_secs_f64();
+        }
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        match self.decision {
+            Some(AdaptiveDecision::Trivial) => PerfectMatching::new(),
+            Some(AdaptiveDecision::UnionFind) => self.union_find_perfect_matching(),
+            _ => self.solver.perfect_matching_visualizer(visualizer),
+        }
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        match self.decision {
+            Some(AdaptiveDecision::Trivial) => vec![],
+            Some(AdaptiveDecision::UnionFind) => {
+                self.solver.subgraph_builder.load_subgraph(&self.union_find.subgraph);
+                self.solver.subgraph_builder.get_subgraph()
+            }
+            _ => self.solver.subgraph_visualizer(visualizer),
+        }
+    }
+    /// the union-find fast path has no dual variables, so the weight of its correction is reported instead
+    fn sum_dual_variables(&self) -> Weight {
+        match self.decision {
+            Some(AdaptiveDecision::Trivial) => 0,
+            Some(AdaptiveDecision::UnionFind) => self.union_find.subgraph_weight(),
+            _ => self.solver.sum_dual_variables(),
+        }
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solver.generate_profiler_report(),
+            "hybrid": self.statistics,
+        })
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solver.effective_config(),
+            "hybrid": self.config,
+        })
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.solver.set_parity_check(enabled);
+    }
+    fn set_record_repro(&mut self, enabled: bool) {
+        self.solver.set_record_repro(enabled);
+    }
+    /// only the shots decoded by the MWPM solver can be reproduced
+    fn repro(&self) -> Option<Repro> {
+        if self.decision != Some(AdaptiveDecision::MWPM) {
+            return None;
+        }
+        self.solver.repro()
+    }
+}
+
+/// a step after decoding that refines the correction or derives data from it, e.g. the predicted observables or a
+/// compressed encoding; postprocessors are registered to a [`SolverPostprocessed`] and composed in order
+pub trait MatchingPostprocessor {
+    /// the key of the derived data in [`SolverPostprocessed::outputs`]
+    fn name(&self) -> String;
+    /// `subgraph` is the correction of `syndrome_pattern` on the decoding graph of `initializer`, as given by the previous
+    /// postprocessor; modify it in place to refine the correction, and return the derived data if any
+    fn postprocess(
+        &mut self,
+        initializer: &SolverInitializer,
+        syndrome_pattern: &SyndromePattern,
+        subgraph: &mut Vec<EdgeIndex>,
+    ) -> Option<serde_json::Value>;
+}
+
+/// predict the observables flipped by the correction, given the bit mask of the observables flipped by each edge in the
+/// same format as [`PrimalDualSolver::stim_integration_predict_bit_packed_data`]
+pub struct ObservablePostprocessor {
+    pub edge_masks: Vec<usize>,
+}
+
+impl MatchingPostprocessor for ObservablePostprocessor {
+    fn name(&self) -> String {
+        "observables".to_string()
+    }
+    #[allow(clippy::unnecessary_cast)]
+    fn postprocess(
+        &mut self,
+        _initializer: &SolverInitializer,
+        _syndrome_pattern: &SyndromePattern,
+        subgraph: &mut Vec<EdgeIndex>,
+    ) -> Option<serde_json::Value> {
+        let prediction = subgraph
+            .iter()
+            .fold(0, |prediction, &edge_index| prediction ^ self.edge_masks[edge_index as usize]);
+        Some(json!(prediction))
+    }
+}
+
+/// run-length encode the correction into straight chains, see [`crate::primal_module::CompressedSubgraph`]
+pub struct CompressionPostprocessor {
+    subgraph_builder: SubGraphBuilder,
+}
+
+impl CompressionPostprocessor {
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        Self {
+            subgraph_builder: SubGraphBuilder::new(initializer),
+        }
+    }
+}
+
+impl MatchingPostprocessor for CompressionPostprocessor {
+    fn name(&self) -> String {
+        "compressed".to_string()
+    }
+    fn postprocess(
+        &mut self,
+        _initializer: &SolverInitializer,
+        _syndrome_pattern: &SyndromePattern,
+        subgraph: &mut Vec<EdgeIndex>,
+    ) -> Option<serde_json::Value> {
+        Some(json!(self.subgraph_builder.compress_subgraph(subgraph)))
+    }
+}
+
+/// a solver that passes the correction of the wrapped solver through the registered postprocessors, so that features
+/// on top of the matching can be composed without changing the solvers; the perfect matching and the sum of dual
+/// variables are still the ones of the wrapped solver, while the subgraph is the one refined by the postprocessors
+pub struct SolverPostprocessed {
+    /// the wrapped solver
+    pub solver: Box<dyn PrimalDualSolver>,
+    /// the decoding graph given to the postprocessors
+    pub initializer: SolverInitializer,
+    /// applied in the order of registration
+    postprocessors: Vec<Box<dyn MatchingPostprocessor>>,
+    /// the postprocessed correction of the current syndrome pattern
+    subgraph: Option<Vec<EdgeIndex>>,
+    /// the derived data of the current syndrome pattern, by the name of the postprocessor
+    outputs: BTreeMap<String, serde_json::Value>,
+}
+
+impl SolverPostprocessed {
+    pub fn new(solver: Box<dyn PrimalDualSolver>, initializer: &SolverInitializer) -> Self {
+        Self {
+            solver,
+            initializer: initializer.clone(),
+            postprocessors: vec![],
+            subgraph: None,
+            outputs: BTreeMap::new(),
+        }
+    }
+
+    /// append a postprocessor, which receives the correction refined by all the previously registered ones
+    pub fn register(&mut self, postprocessor: Box<dyn MatchingPostprocessor>) {
+        let name = postprocessor.name();
+        assert!(
+            self.postprocessors.iter().all(|registered| registered.name() != name),
+            "postprocessor {} is already registered",
+            name
+        );
+        self.postprocessors.push(postprocessor);
+    }
+
+    /// the derived data of the current syndrome pattern, by the name of the postprocessor
+    pub fn outputs(&self) -> &BTreeMap<String, serde_json::Value> {
+        &self.outputs
+    }
+}
+
+impl PrimalDualSolver for SolverPostprocessed {
+    fn clear(&mut self) {
+        self.solver.clear();
+        self.subgraph = None;
+        self.outputs.clear();
+    }
+    fn reset_profiler(&mut self) {
+        self.solver.reset_profiler();
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        self.solver.solve_visualizer(syndrome_pattern, visualizer);
+        let mut subgraph = self.solver.subgraph();
+        self.outputs.clear();
+        for postprocessor in self.postprocessors.iter_mut() {
+            if let Some(output) = postprocessor.postprocess(&self.initializer, syndrome_pattern, &mut subgraph) {
+                self.outputs.insert(postprocessor.name(), output);
+            }
+        }
+        self.subgraph = Some(subgraph);
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        self.solver.perfect_matching_visualizer(visualizer)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        assert!(visualizer.is_none(), "not supported");
+        self.subgraph.clone().expect("call `solve` before getting the subgraph")
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        self.solver.sum_dual_variables()
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solver.generate_profiler_report(),
+        })
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        json!({
+            "solver": self.solver.effective_config(),
+            "postprocessors": self.postprocessors.iter().map(|postprocessor| postprocessor.name()).collect::<Vec<_>>(),
+        })
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.solver.set_parity_check(enabled);
+    }
+    fn set_record_repro(&mut self, enabled: bool) {
+        self.solver.set_record_repro(enabled);
+    }
+    /// the reproduction of the wrapped solver, i.e. the correction before postprocessing
+    fn repro(&self) -> Option<Repro> {
+        self.solver.repro()
+    }
+}
+
+/// a two-pass correlated decoder of a CSS code, e.g. for the Y errors that flip an edge in both the Z and the X decoding
+/// graphs: the Z graph is decoded first, the X edges correlated with the edges of the Z correction are reweighted according
+/// to the correlation table, and then the X graph is decoded. The two graphs share the vertex and edge indices of this
+/// solver, with the Z vertices (edges) first and the X vertices (edges) after them. The reweighted X edges may have
+/// negative weights, which are resolved by an [`EdgeReweighter`]; the sum of dual variables is the weight of the Z
+/// correction plus the weight of the X correction under the reweighted weights
+pub struct SolverCorrelated {
+    pub z_solver: SolverSerial,
+    pub x_solver: SolverSerial,
+    /// the number of vertices and edges of the Z graph, i.e. the offset of the X graph
+    pub z_vertex_num: VertexNum,
+    pub z_edge_num: usize,
+    /// the X edges reweighted when a Z edge is in the Z correction, with their conditional weights
+    correlations: Vec<Vec<(EdgeIndex, Weight)>>,
+    x_reweighter: EdgeReweighter,
+    /// the X syndrome of the current shot after reweighting
+    x_reweighted: Option<ReweightedSyndrome>,
+    /// the correction of the Z graph of the current shot
+    z_subgraph: Vec<EdgeIndex>,
+}
+
+impl SolverCorrelated {
+    /// `correlations` lists `(z_edge, x_edge, weight)`: the X edge takes the weight if the Z edge is in the Z correction,
+    /// both in the edge indices of their own graphs; if several Z edges in the correction reweight the same X edge, the
+    /// lowest weight is taken
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(
+        z_initializer: &SolverInitializer,
+        x_initializer: &SolverInitializer,
+        correlations: &[(EdgeIndex, EdgeIndex, Weight)],
+    ) -> Self {
+        let z_edge_num = z_initializer.weighted_edges.len();
+        let mut correlation_table = vec![vec![]; z_edge_num];
+        for &(z_edge, x_edge, weight) in correlations.iter() {
+            assert!((z_edge as usize) < z_edge_num, "invalid Z edge {}", z_edge);
+            assert!(
+                (x_edge as usize) < x_initializer.weighted_edges.len(),
+                "invalid X edge {}",
+                x_edge
+            );
+            correlation_table[z_edge as usize].push((x_edge, weight));
+        }
+        Self {
+            z_solver: SolverSerial::new(z_initializer),
+            x_solver: SolverSerial::new(x_initializer),
+            z_vertex_num: z_initializer.vertex_num,
+            z_edge_num,
+            correlations: correlation_table,
+            x_reweighter: EdgeReweighter::new(x_initializer),
+            x_reweighted: None,
+            z_subgraph: vec![],
+        }
+    }
+
+    /// the Z and X syndromes of a syndrome of this solver
+    #[allow(clippy::unnecessary_cast)]
+    pub fn split_syndrome_pattern(&self, syndrome_pattern: &SyndromePattern) -> (SyndromePattern, SyndromePattern) {
+        assert!(
+            syndrome_pattern.detector_confidences.is_empty(),
+            "detector confidences are not supported by the correlated solver"
+        );
+        let z_edge_num = self.z_edge_num as EdgeIndex;
+        let (mut z_syndrome, mut x_syndrome) = (SyndromePattern::new_empty(), SyndromePattern::new_empty());
+        for &vertex_index in syndrome_pattern.defect_vertices.iter() {
+            if vertex_index < self.z_vertex_num {
+                z_syndrome.defect_vertices.push(vertex_index);
+            } else {
+                x_syndrome.defect_vertices.push(vertex_index - self.z_vertex_num);
+            }
+        }
+        for &edge_index in syndrome_pattern.erasures.iter() {
+            if edge_index < z_edge_num {
+                z_syndrome.erasures.push(edge_index);
+            } else {
+                x_syndrome.erasures.push(edge_index - z_edge_num);
+            }
+        }
+        for &(edge_index, weight) in syndrome_pattern.dynamic_weights.iter() {
+            if edge_index < z_edge_num {
+                z_syndrome.dynamic_weights.push((edge_index, weight));
+            } else {
+                x_syndrome.dynamic_weights.push((edge_index - z_edge_num, weight));
+            }
+        }
+        (z_syndrome, x_syndrome)
+    }
+
+    /// the X edges reweighted by the correlations with a Z correction
+    #[allow(clippy::unnecessary_cast)]
+    pub fn correlated_weights(&self, z_subgraph: &[EdgeIndex]) -> Vec<(EdgeIndex, Weight)> {
+        let mut weights: BTreeMap<EdgeIndex, Weight> = BTreeMap::new();
+        for &z_edge in z_subgraph.iter() {
+            for &(x_edge, weight) in self.correlations[z_edge as usize].iter() {
+                let entry = weights.entry(x_edge).or_insert(weight);
+                *entry = Weight::min(*entry, weight);
+            }
+        }
+        weights.into_iter().collect()
+    }
+}
+
+impl PrimalDualSolver for SolverCorrelated {
+    fn clear(&mut self) {
+        self.z_solver.clear();
+        self.x_solver.clear();
+        self.x_reweighted = None;
+        self.z_subgraph.clear();
+    }
+    fn reset_profiler(&mut self) {
+        self.z_solver.reset_profiler();
+        self.x_solver.reset_profiler();
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        assert!(visualizer.is_none(), "not supported");
+        let (z_syndrome, x_syndrome) = self.split_syndrome_pattern(syndrome_pattern);
+        self.z_solver.solve(&z_syndrome);
+        self.z_subgraph = self.z_solver.subgraph();
+        let x_weights = self.correlated_weights(&self.z_subgraph);
+        let x_reweighted = self.x_reweighter.reweight(&x_syndrome, &x_weights);
+        self.x_solver.solve(&x_reweighted.syndrome_pattern);
+        self.x_reweighted = Some(x_reweighted);
+    }
+    /// the perfect matchings of the two graphs combined, with the X vertices offset by [`Self::z_vertex_num`]; the X
+    /// matching is of the reweighted X syndrome, which only differs from the original one if a correlated weight is
+    /// negative. The dual nodes are detached from the solvers like [`PerfectMatching::detached`]
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        assert!(visualizer.is_none(), "not supported");
+        let mut perfect_matching = self.z_solver.perfect_matching().detached();
+        let x_perfect_matching = self.x_solver.perfect_matching();
+        let z_vertex_num = self.z_vertex_num;
+        let offset = |ptr: &DualNodePtr| {
+            let mut node = ptr.read_recursive().clone();
+            node.parent_blossom = None;
+            if let DualNodeClass::DefectVertex { defect_index } = &mut node.class {
+                *defect_index += z_vertex_num;
+            }
+            DualNodePtr::new_value(node)
+        };
+        for (ptr_1, ptr_2) in x_perfect_matching.peer_matchings.iter() {
+            perfect_matching.peer_matchings.push((offset(ptr_1), offset(ptr_2)));
+        }
+        for (ptr, virtual_vertex) in x_perfect_matching.virtual_matchings.iter() {
+            perfect_matching
+                .virtual_matchings
+                .push((offset(ptr), virtual_vertex + z_vertex_num));
+        }
+        perfect_matching
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        assert!(visualizer.is_none(), "not supported");
+        let x_reweighted = self.x_reweighted.as_ref().expect("call `solve` before getting the subgraph");
+        let x_subgraph = x_reweighted.original_subgraph(&self.x_solver.subgraph());
+        let z_edge_num = self.z_edge_num as EdgeIndex;
+        self.z_subgraph
+            .iter()
+            .cloned()
+            .chain(x_subgraph.into_iter().map(|edge_index| edge_index + z_edge_num))
+            .collect()
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        let x_reweighted = self
+            .x_reweighted
+            .as_ref()
+            .expect("call `solve` before getting the sum of dual variables");
+        self.z_solver.sum_dual_variables() + x_reweighted.original_weight(self.x_solver.sum_dual_variables())
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        json!({
+            "z": self.z_solver.generate_profiler_report(),
+            "x": self.x_solver.generate_profiler_report(),
+        })
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        json!({
+            "z": self.z_solver.effective_config(),
+            "x": self.x_solver.effective_config(),
+            "correlations": self.correlations.iter().map(|x_edges| x_edges.len()).sum::<usize>(),
+        })
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.z_solver.set_parity_check(enabled);
+        self.x_solver.set_parity_check(enabled);
+    }
+}
+
+/// configuration of [`SolverStream`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreamConfig {
+    /// the number of rounds committed each time the window is full; it's also the length of each time-slice unit
+    /// fused in the window, and thus must be at least 2
+    #[serde(default = "stream_default_configs::commit_rounds")]
+    pub commit_rounds: usize,
+    /// the number of rounds after the committed ones in the window, which are decoded but not committed, because they
+    /// may still be matched with the future rounds; usually around the code distance
+    #[serde(default = "stream_default_configs::buffer_rounds")]
+    pub buffer_rounds: usize,
+    /// the config of the parallel solver decoding each window
+    #[serde(default = "stream_default_configs::primal_dual_config")]
+    pub primal_dual_config: serde_json::Value,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+pub mod stream_default_configs {
+    pub fn commit_rounds() -> usize {
+        5
+    }
+    pub fn buffer_rounds() -> usize {
+        5
+    }
+    pub fn primal_dual_config() -> serde_json::Value {
+        json!({})
+    }
+}
+
+/// the correction of some rounds committed by [`SolverStream`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StreamCommit {
+    /// the rounds in `start_round..end_round` are fully corrected by this commit
+    pub start_round: usize,
+    pub end_round: usize,
+    /// the corrected edges, given by the vertices in the stream, i.e. `round * round_vertex_num + vertex_index`;
+    /// a time-like edge from the last committed round reaches the first round after them
+    pub edges: Vec<(VertexIndex, VertexIndex)>,
+}
+
+/// a sliding-window decoder of an unbounded stream of measurement rounds, whose decoding graph repeats every round.
+/// Rounds are pushed one by one; once there are `commit_rounds + buffer_rounds` rounds, the whole window is re-solved
+/// from scratch by a [`SolverParallel`] that fuses one time-slice unit per `commit_rounds` rounds, and the correction
+/// of the oldest `commit_rounds` rounds is committed. Nothing is reused between windows: the buffer rounds are decoded
+/// again in the next window, so each round is decoded `(commit_rounds + buffer_rounds) / commit_rounds` times on
+/// average. The committed time-like edges reaching into the buffer flip the defects of the next round, which are
+/// carried to the next window. The committed corrections are exact in parity, and minimum-weight whenever no matching
+/// spans more than the buffer
+pub struct SolverStream {
+    pub config: StreamConfig,
+    /// the number of vertices in each round
+    pub round_vertex_num: VertexNum,
+    /// the edges inside a round, in the vertex indices of the round
+    intra_round_edges: Vec<(VertexIndex, VertexIndex, Weight)>,
+    /// the edges from a vertex of a round to a vertex of the next round
+    inter_round_edges: Vec<(VertexIndex, VertexIndex, Weight)>,
+    /// the virtual vertices of a round
+    virtual_vertices: Vec<VertexIndex>,
+    /// the solver of a full window, always present after construction
+    solver: Option<SolverParallel>,
+    /// the edges of a full window
+    window_edges: Vec<(VertexIndex, VertexIndex, Weight)>,
+    /// the defect vertices of each round in the window
+    rounds: VecDeque<Vec<VertexIndex>>,
+    /// the defects in the first round of the window flipped by the committed corrections
+    carried_defects: BTreeSet<VertexIndex>,
+    /// the index of the first round in the window
+    pub committed_rounds: usize,
+}
+
+impl SolverStream {
+    /// the repeating structure is learned from the first two rounds of `initializer`, whose vertices are ordered round
+    /// by round, e.g. a [`crate::example_codes::PhenomenologicalPlanarCode`] with at least one noisy measurement
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer, round_vertex_num: VertexNum, config: StreamConfig) -> Self {
+        assert!(round_vertex_num > 0, "empty round");
+        assert!(
+            initializer.vertex_num >= 2 * round_vertex_num,
+            "the decoding graph must have at least two rounds"
+        );
+        assert!(config.commit_rounds >= 2, "each time-slice unit needs at least 2 rounds");
+        let mut intra_round_edges = vec![];
+        let mut inter_round_edges = vec![];
+        for &(vertex_1, vertex_2, weight) in initializer.weighted_edges.iter() {
+            let (vertex_1, vertex_2) = (vertex_1.min(vertex_2), vertex_1.max(vertex_2));
+            match (vertex_1 / round_vertex_num, vertex_2 / round_vertex_num) {
+                (0, 0) => intra_round_edges.push((vertex_1, vertex_2, weight)),
+                (0, 1) => inter_round_edges.push((vertex_1, vertex_2 - round_vertex_num, weight)),
+                _ => {}
+            }
+        }
+        let virtual_vertices = initializer
+            .virtual_vertices
+            .iter()
+            .cloned()
+            .filter(|&vertex_index| vertex_index < round_vertex_num)
+            .collect();
+        let mut stream = Self {
+            round_vertex_num,
+            intra_round_edges,
+            inter_round_edges,
+            virtual_vertices,
+            solver: None,
+            window_edges: vec![],
+            rounds: VecDeque::new(),
+            carried_defects: BTreeSet::new(),
+            committed_rounds: 0,
+            config,
+        };
+        let window_rounds = stream.config.commit_rounds + stream.config.buffer_rounds;
+        let (window_initializer, partition_info) = stream.window(window_rounds);
+        stream.solver = Some(SolverParallel::new(
+            &window_initializer,
+            &partition_info,
+            stream.config.primal_dual_config.clone(),
+        ));
+        stream.window_edges = window_initializer.weighted_edges;
+        stream
+    }
+
+    /// the decoding graph of a window and its time partition, fused in a chain of units of at least `commit_rounds`
+    #[allow(clippy::unnecessary_cast)]
+    fn window(&self, window_rounds: usize) -> (SolverInitializer, PartitionInfo) {
+        let round_vertex_num = self.round_vertex_num;
+        let mut weighted_edges = vec![];
+        let mut virtual_vertices = vec![];
+        for round in 0..window_rounds as VertexIndex {
+            let bias = round * round_vertex_num;
+            for &(vertex_1, vertex_2, weight) in self.intra_round_edges.iter() {
+                weighted_edges.push((bias + vertex_1, bias + vertex_2, weight));
+            }
+            if round + 1 < window_rounds as VertexIndex {
+                for &(vertex_1, vertex_2, weight) in self.inter_round_edges.iter() {
+                    weighted_edges.push((bias + vertex_1, bias + round_vertex_num + vertex_2, weight));
+                }
+            }
+            virtual_vertices.extend(self.virtual_vertices.iter().map(|&vertex_index| bias + vertex_index));
+        }
+        let vertex_num = window_rounds as VertexNum * round_vertex_num;
+        let initializer = SolverInitializer::new(vertex_num, weighted_edges, virtual_vertices);
+        let partition_num = (window_rounds / self.config.commit_rounds).max(1);
+        let mut config = PartitionConfig::new(vertex_num);
+        config.partitions.clear();
+        for partition_index in 0..partition_num {
+            let start_round = (partition_index * window_rounds / partition_num) as VertexIndex;
+            let end_round = ((partition_index + 1) * window_rounds / partition_num) as VertexIndex;
+            // the first round of each time-slice unit except the first is the interface owned by the fusion unit
+            let start_round = if partition_index == 0 { start_round } else { start_round + 1 };
+            config
+                .partitions
+                .push(VertexRange::new(start_round * round_vertex_num, end_round * round_vertex_num));
+        }
+        config.fusions.clear();
+        for unit_index in partition_num..(2 * partition_num - 1) {
+            if unit_index == partition_num {
+                config.fusions.push((0, 1));
+            } else {
+                config.fusions.push((unit_index - 1, unit_index - partition_num + 1));
+            }
+        }
+        (initializer, config.info())
+    }
+
+    /// push the defect vertices of the next round, given by the vertex indices in the round; returns the corrections
+    /// of the rounds that fall out of the window
+    #[allow(clippy::unnecessary_cast)]
+    pub fn push_round(&mut self, syndrome_pattern: &SyndromePattern) -> Option<StreamCommit> {
+        assert!(
+            syndrome_pattern.erasures.is_empty() && syndrome_pattern.dynamic_weights.is_empty(),
+            "the stream only supports defect vertices"
+        );
+        for &vertex_index in syndrome_pattern.defect_vertices.iter() {
+            assert!(
+                vertex_index < self.round_vertex_num,
+                "defect vertex {} out of the round",
+                vertex_index
+            );
+        }
+        self.rounds.push_back(syndrome_pattern.defect_vertices.clone());
+        if self.rounds.len() < self.config.commit_rounds + self.config.buffer_rounds {
+            return None;
+        }
+        let defect_vertices = self.window_defect_vertices();
+        let solver = self.solver.as_mut().unwrap();
+        solver.solve(&SyndromePattern::new_vertices(defect_vertices));
+        let subgraph = solver.subgraph();
+        solver.clear();
+        let window_edges = std::mem::take(&mut self.window_edges);
+        let commit = self.commit(&subgraph, &window_edges, self.config.commit_rounds);
+        self.window_edges = window_edges;
+        Some(commit)
+    }
+
+    /// decode and commit all the remaining rounds, at the end of the stream
+    pub fn flush(&mut self) -> Option<StreamCommit> {
+        if self.rounds.is_empty() {
+            return None;
+        }
+        let window_rounds = self.rounds.len();
+        let (initializer, partition_info) = self.window(window_rounds);
+        let mut solver = SolverParallel::new(&initializer, &partition_info, self.config.primal_dual_config.clone());
+        solver.solve(&SyndromePattern::new_vertices(self.window_defect_vertices()));
+        let subgraph = solver.subgraph();
+        Some(self.commit(&subgraph, &initializer.weighted_edges, window_rounds))
+    }
+
+    /// drop the pending rounds and start a new stream
+    pub fn clear(&mut self) {
+        self.rounds.clear();
+        self.carried_defects.clear();
+        self.committed_rounds = 0;
+    }
+
+    /// the defect vertices of the window, including those flipped by the previous commits
+    #[allow(clippy::unnecessary_cast)]
+    fn window_defect_vertices(&self) -> Vec<VertexIndex> {
+        let mut defect_vertices = BTreeSet::new();
+        for (round, round_defect_vertices) in self.rounds.iter().enumerate() {
+            let bias = round as VertexIndex * self.round_vertex_num;
+            for &vertex_index in round_defect_vertices.iter() {
+                if !defect_vertices.remove(&(bias + vertex_index)) {
+                    defect_vertices.insert(bias + vertex_index);
+                }
+            }
+        }
+        for &vertex_index in self.carried_defects.iter() {
+            if !defect_vertices.remove(&vertex_index) {
+                defect_vertices.insert(vertex_index);
+            }
+        }
+        defect_vertices.into_iter().collect()
+    }
+
+    /// commit the edges of the subgraph touching the first `commit_rounds` rounds and slide the window
+    #[allow(clippy::unnecessary_cast)]
+    fn commit(
+        &mut self,
+        subgraph: &[EdgeIndex],
+        window_edges: &[(VertexIndex, VertexIndex, Weight)],
+        commit_rounds: usize,
+    ) -> StreamCommit {
+        let commit_vertex_num = commit_rounds as VertexNum * self.round_vertex_num;
+        let stream_bias = self.committed_rounds as VertexIndex * self.round_vertex_num;
+        let mut edges = vec![];
+        let mut carried_defects = BTreeSet::new();
+        for &edge_index in subgraph.iter() {
+            let (vertex_1, vertex_2, _) = window_edges[edge_index as usize];
+            let (vertex_1, vertex_2) = (vertex_1.min(vertex_2), vertex_1.max(vertex_2));
+            if vertex_1 >= commit_vertex_num {
+                continue; // in the buffer
+            }
+            edges.push((stream_bias + vertex_1, stream_bias + vertex_2));
+            if vertex_2 >= commit_vertex_num {
+                let vertex_index = vertex_2 - commit_vertex_num;
+                if !carried_defects.remove(&vertex_index) {
+                    carried_defects.insert(vertex_index);
+                }
+            }
+        }
+        self.carried_defects = carried_defects;
+        self.rounds.drain(..commit_rounds);
+        let start_round = self.committed_rounds;
+        self.committed_rounds += commit_rounds;
+        StreamCommit {
+            start_round,
+            end_round: self.committed_rounds,
+            edges,
+        }
+    }
+}
+
+/// a solver whose backend is selected at runtime from a single JSON config, e.g. `{"backend": "parallel", "partition": {...}}`,
+/// so that applications can switch backends via config files rather than recompiling with a different concrete type;
+/// the `partition` key is optional and defaults to a single partition, the optional `record_repro` key enables
+/// [`PrimalDualSolver::set_record_repro`], and the other keys are passed to the backend
+#[allow(clippy::large_enum_variant)]
+pub enum Solver {
+    Serial(SolverSerial),
+    DualParallel(SolverDualParallel),
+    Parallel(SolverParallel),
+    ErrorPatternLogger(SolverErrorPatternLogger),
+    BlossomV(SolverBlossomV),
+    ClusterParallel(SolverClusterParallel),
+}
+
+impl Solver {
+    /// `positions` is only used by the error pattern logger
+    pub fn new(initializer: &SolverInitializer, positions: &[VisualizePosition], mut config: serde_json::Value) -> Self {
+        let config_object = config.as_object_mut().expect("config must be JSON object");
+        let backend = match config_object.remove("backend") {
+            Some(value) => value.as_str().expect("backend string").to_string(),
+            None => panic!("missing `backend` in solver config"),
+        };
+        let partition_config = match config_object.remove("partition") {
+            Some(value) => serde_json::from_value(value).unwrap(),
+            None => PartitionConfig::new(initializer.vertex_num),
+        };
+        let record_repro = match config_object.remove("record_repro") {
+            Some(value) => value.as_bool().expect("record_repro bool"),
+            None => false,
+        };
+        let partition_info = partition_config.info();
+        let mut solver = match backend.as_str() {
+            "serial" | "blossom_v" => {
+                if !config_object.is_empty() {
+                    panic!("unknown config keys: {:?}", config_object.keys().collect::<Vec<&String>>());
+                }
+                assert_eq!(
+                    partition_info.config.partitions.len(),
+                    1,
+                    "no partition is supported by {} backend",
+                    backend
+                );
+                if backend == "serial" {
+                    Self::Serial(SolverSerial::new(initializer))
+                } else {
+                    Self::BlossomV(SolverBlossomV::new(initializer))
+                }
+            }
+            "dual_parallel" => Self::DualParallel(SolverDualParallel::new(initializer, &partition_info, config)),
+            "parallel" => Self::Parallel(SolverParallel::new(initializer, &partition_info, config)),
+            "cluster_parallel" => {
+                assert_eq!(
+                    partition_info.config.partitions.len(),
+                    1,
+                    "no partition is supported by {} backend",
+                    backend
+                );
+                Self::ClusterParallel(SolverClusterParallel::new(initializer, config))
+            }
+            "error_pattern_logger" => {
+                Self::ErrorPatternLogger(SolverErrorPatternLogger::new(initializer, &positions.to_vec(), config))
+            }
+            _ => panic!("unknown backend {:?}", backend),
+        };
+        solver.set_record_repro(record_repro);
+        solver
+    }
+
+    /// the name of the backend, as given in the config
+    pub fn backend(&self) -> &'static str {
+        match self {
+            Self::Serial(_) => "serial",
+            Self::DualParallel(_) => "dual_parallel",
+            Self::Parallel(_) => "parallel",
+            Self::ErrorPatternLogger(_) => "error_pattern_logger",
+            Self::BlossomV(_) => "blossom_v",
+            Self::ClusterParallel(_) => "cluster_parallel",
+        }
+    }
+
+    pub fn as_solver(&self) -> &dyn PrimalDualSolver {
+        match self {
+            Self::Serial(solver) => solver,
+            Self::DualParallel(solver) => solver,
+            Self::Parallel(solver) => solver,
+            Self::ErrorPatternLogger(solver) => solver,
+            Self::BlossomV(solver) => solver,
+            Self::ClusterParallel(solver) => solver,
+        }
+    }
+
+    pub fn as_solver_mut(&mut self) -> &mut dyn PrimalDualSolver {
+        match self {
+            Self::Serial(solver) => solver,
+            Self::DualParallel(solver) => solver,
+            Self::Parallel(solver) => solver,
+            Self::ErrorPatternLogger(solver) => solver,
+            Self::BlossomV(solver) => solver,
+            Self::ClusterParallel(solver) => solver,
+        }
+    }
+}
+
+impl PrimalDualSolver for Solver {
+    fn clear(&mut self) {
+        self.as_solver_mut().clear()
+    }
+    fn reset_profiler(&mut self) {
+        self.as_solver_mut().reset_profiler()
+    }
+    fn solve_visualizer(&mut self, syndrome_pattern: &SyndromePattern, visualizer: Option<&mut Visualizer>) {
+        self.as_solver_mut().solve_visualizer(syndrome_pattern, visualizer)
+    }
+    fn perfect_matching_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> PerfectMatching {
+        self.as_solver_mut().perfect_matching_visualizer(visualizer)
+    }
+    fn solve_with_budget(&mut self, syndrome_pattern: &SyndromePattern, budget: SolveBudget) -> BudgetedSolution {
+        self.as_solver_mut().solve_with_budget(syndrome_pattern, budget)
+    }
+    fn subgraph_visualizer(&mut self, visualizer: Option<&mut Visualizer>) -> Vec<EdgeIndex> {
+        self.as_solver_mut().subgraph_visualizer(visualizer)
+    }
+    fn sum_dual_variables(&self) -> Weight {
+        self.as_solver().sum_dual_variables()
+    }
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        self.as_solver().generate_profiler_report()
+    }
+    fn effective_config(&self) -> serde_json::Value {
+        let mut config = self.as_solver().effective_config();
+        config
+            .as_object_mut()
+            .unwrap()
+            .insert("backend".to_string(), json!(self.backend()));
+        config
+    }
+    fn set_parity_check(&mut self, enabled: bool) {
+        self.as_solver_mut().set_parity_check(enabled)
+    }
+    fn set_record_repro(&mut self, enabled: bool) {
+        self.as_solver_mut().set_record_repro(enabled)
+    }
+    fn repro(&self) -> Option<Repro> {
+        self.as_solver().repro()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::analysis::left_boundary_edges;
+    use crate::example_codes::*;
+    use crate::primal_module::CompressedSubgraph;
+
+    /// a hit returns the same result as the wrapped solver without calling it, and the least recently used entry is evicted
+    #[test]
+    fn mwpm_solver_cached_1() {
+        // cargo test mwpm_solver_cached_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let syndrome_patterns: Vec<_> = (0..3).map(|seed| code.generate_random_errors(seed)).collect();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut solver = SolverCached::new(Box::new(SolverSerial::new(&initializer)), 2);
+        for (index, expected_hits) in [0, 1, 0, 0, 1, 0].into_iter().enumerate() {
+            // the third pattern evicts the first one, which is then a miss again
+            let syndrome_pattern = &syndrome_patterns[[0, 0, 1, 2, 2, 0][index]];
+            let hits = solver.hits;
+            solver.solve(syndrome_pattern);
+            assert_eq!(solver.hits - hits, expected_hits);
+            serial_solver.solve(syndrome_pattern);
+            assert_eq!(solver.subgraph(), serial_solver.subgraph());
+            assert_eq!(solver.sum_dual_variables(), serial_solver.sum_dual_variables());
+            let mut matched_vertices = solver.perfect_matching().matched_vertices();
+            let mut expected = serial_solver.perfect_matching().matched_vertices();
+            matched_vertices.sort();
+            expected.sort();
+            assert_eq!(matched_vertices, expected);
+            serial_solver.clear();
+            solver.clear();
+        }
+        assert_eq!((solver.hits, solver.misses), (2, 4));
+        assert_eq!(solver.len(), 2);
+    }
+
+    /// the solvers without a budget fall back to a full solve that is never truncated
+    #[test]
+    fn mwpm_solver_budget_fallback_1() {
+        // cargo test mwpm_solver_budget_fallback_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let syndrome_pattern = code.generate_random_errors(0);
+        let mut serial_solver = SolverSerial::new(&initializer);
+        serial_solver.solve(&syndrome_pattern);
+        let mut solver = SolverCached::new(Box::new(SolverSerial::new(&initializer)), 2);
+        let budget = SolveBudget {
+            max_dual_growth_rounds: Some(0),
+            ..Default::default()
+        };
+        let solution = solver.solve_with_budget(&syndrome_pattern, budget);
+        assert!(!solution.truncated);
+        assert_eq!(solver.sum_dual_variables(), serial_solver.sum_dual_variables());
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn mwpm_solver_correlated_1() {
+        // cargo test mwpm_solver_correlated_1 -- --nocapture
+        // Y errors only: the X graph sees the same errors as the Z graph, which are certain given the Z correction
+        let mut code = CodeCapacityPlanarCode::new(7, 0.05, 500);
+        let initializer = code.get_initializer();
+        let vertex_num = initializer.vertex_num;
+        let edge_num = initializer.weighted_edges.len();
+        let correlations: Vec<(EdgeIndex, EdgeIndex, Weight)> = (0..edge_num)
+            .map(|edge_index| (edge_index as EdgeIndex, edge_index as EdgeIndex, 0))
+            .collect();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut uncorrelated_solver = SolverCorrelated::new(&initializer, &initializer, &[]);
+        let mut solver = SolverCorrelated::new(&initializer, &initializer, &correlations);
+        for seed in 0..50 {
+            let z_syndrome = code.generate_random_errors(seed);
+            let mut defect_vertices = z_syndrome.defect_vertices.clone();
+            defect_vertices.extend(
+                z_syndrome
+                    .defect_vertices
+                    .iter()
+                    .map(|&vertex_index| vertex_index + vertex_num),
+            );
+            let syndrome_pattern = SyndromePattern::new_vertices(defect_vertices);
+            serial_solver.solve(&z_syndrome);
+            let z_subgraph = serial_solver.subgraph();
+            let z_weight = serial_solver.sum_dual_variables();
+            uncorrelated_solver.solve(&syndrome_pattern);
+            assert_eq!(uncorrelated_solver.sum_dual_variables(), 2 * z_weight);
+            solver.solve(&syndrome_pattern);
+            assert_eq!(solver.sum_dual_variables(), z_weight);
+            let subgraph = solver.subgraph();
+            let (z_part, x_part): (Vec<EdgeIndex>, Vec<EdgeIndex>) =
+                subgraph.iter().partition(|&&edge_index| (edge_index as usize) < edge_num);
+            assert_eq!(z_part, z_subgraph);
+            let x_part: Vec<EdgeIndex> = x_part.iter().map(|&edge_index| edge_index - edge_num as EdgeIndex).collect();
+            assert_eq!(x_part, z_subgraph);
+            serial_solver.clear();
+            uncorrelated_solver.clear();
+            solver.clear();
+        }
+        // the default batch decoding relies on the combined perfect matching
+        let syndrome_patterns: Vec<_> = (0..5)
+            .map(|seed| {
+                let mut syndrome_pattern = code.generate_random_errors(seed);
+                let x_defect_vertices = code.generate_random_errors(seed + 100).defect_vertices;
+                (syndrome_pattern.defect_vertices)
+                    .extend(x_defect_vertices.iter().map(|&vertex_index| vertex_index + vertex_num));
+                syndrome_pattern
+            })
+            .collect();
+        let perfect_matchings = uncorrelated_solver.solve_batch(&syndrome_patterns);
+        for (syndrome_pattern, perfect_matching) in syndrome_patterns.iter().zip(perfect_matchings.iter()) {
+            let peer_num = perfect_matching.peer_matchings.len();
+            let mut matched_defects: Vec<VertexIndex> = vec![];
+            for (index, (vertex_1, vertex_2)) in perfect_matching.matched_vertices().into_iter().enumerate() {
+                assert_eq!(vertex_1 < vertex_num, vertex_2 < vertex_num, "matched across the two graphs");
+                matched_defects.push(vertex_1);
+                if index < peer_num {
+                    matched_defects.push(vertex_2);
+                }
+            }
+            matched_defects.sort();
+            let mut defect_vertices = syndrome_pattern.defect_vertices.clone();
+            defect_vertices.sort();
+            assert_eq!(matched_defects, defect_vertices);
+        }
+        let report = solver.generate_profiler_report();
+        assert!(report.get("z").is_some() && report.get("x").is_some());
+    }
+
+    /// every member of the ensemble returns a minimum-weight correction, and the votes decide the reported one
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn mwpm_solver_ensemble_1() {
+        // cargo test mwpm_solver_ensemble_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let logical_edges = left_boundary_edges(&code);
+        let mut ensemble = SolverEnsemble::new(&initializer, 5, logical_edges, 0);
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in 0..100 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            ensemble.solve(&syndrome_pattern);
+            assert_eq!(ensemble.sum_dual_variables(), solver.sum_dual_variables());
+            let (no_flips, flips) = ensemble.vote_counts();
+            assert_eq!(no_flips + flips, 5);
+            assert!(if ensemble.logical_outcome() {
+                flips >= no_flips
+            } else {
+                no_flips >= flips
+            });
+            let subgraph = ensemble.subgraph();
+            let weight: Weight = subgraph
+                .iter()
+                .map(|&edge_index| initializer.weighted_edges[edge_index as usize].2)
+                .sum();
+            assert_eq!(weight, solver.sum_dual_variables());
+            solver.clear();
+            ensemble.clear();
+        }
+    }
+
+    #[test]
+    fn mwpm_solver_serial_solve_shots_1() {
+        // cargo test mwpm_solver_serial_solve_shots_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let syndrome_patterns: Vec<_> = (0..200).map(|seed| code.generate_random_errors(seed)).collect();
+        let mut solver = SolverSerial::new(&initializer);
+        solver.primal_module.write().max_tree_size = 1000;
+        let subgraphs = solver.solve_shots(&syndrome_patterns);
+        let cloned = solver.clone_for_thread();
+        assert!(std::sync::Arc::ptr_eq(&cloned.initializer, &solver.initializer));
+        assert_eq!(cloned.primal_module.read_recursive().max_tree_size, 1000);
+        for (syndrome_pattern, subgraph) in syndrome_patterns.iter().zip(subgraphs.iter()) {
+            solver.solve(syndrome_pattern);
+            assert_eq!(&solver.subgraph(), subgraph);
+            solver.clear();
+        }
+    }
+
+    #[test]
+    fn mwpm_solver_backend_from_config_1() {
+        // cargo test mwpm_solver_backend_from_config_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let positions = code.get_positions();
+        let partition = json!({
+            "vertex_num": initializer.vertex_num,
+            "partitions": [[0, 24], [32, 56]],
+            "fusions": [[0, 1]],
+        });
+        let configs = [
+            json!({"backend": "serial"}),
+            json!({"backend": "dual_parallel", "partition": partition}),
+            json!({"backend": "parallel", "partition": partition}),
+            json!({"backend": "cluster_parallel", "thread_pool_size": 2}),
+        ];
+        let mut solvers: Vec<Solver> = configs
+            .iter()
+            .map(|config| Solver::new(&initializer, &positions, config.clone()))
+            .collect();
+        for (solver, config) in solvers.iter().zip(configs.iter()) {
+            assert_eq!(solver.effective_config()["backend"], config["backend"]);
+        }
+        for seed in 0..20 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let mut weights = vec![];
+            for solver in solvers.iter_mut() {
+                solver.solve(&syndrome_pattern);
+                weights.push(solver.sum_dual_variables());
+                solver.clear();
+            }
+            assert!(weights.iter().all(|weight| *weight == weights[0]), "{:?}", weights);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn mwpm_solver_recalibrated_1() {
+        // cargo test mwpm_solver_recalibrated_1 -- --nocapture
+        // the actual error rate is higher than the prior, so the weights should decrease within the guardrail
+        let mut code = CodeCapacityPlanarCode::new(5, 0.05, 500);
+        let initializer = code.get_initializer();
+        let prior_probabilities = vec![0.01; initializer.weighted_edges.len()];
+        let config = RecalibrationConfig {
+            update_interval: 1000,
+            learning_rate: 0.5,
+            max_probability_ratio: 2.,
+            ..Default::default()
+        };
+        let mut solver = SolverRecalibrated::new(
+            Box::new(SolverSerial::new(&initializer)),
+            &initializer,
+            prior_probabilities,
+            config,
+        );
+        for seed in 0..4000 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            solver.subgraph();
+            solver.clear();
+        }
+        assert_eq!(solver.shots, 4000);
+        assert!(!solver.audit_log.is_empty());
+        let min_weight = (1000. * (49f64).ln() / (99f64).ln()) as Weight;
+        for (&edge_index, &weight) in solver.recalibrated_weights.iter() {
+            assert!(weight < initializer.weighted_edges[edge_index as usize].2);
+            assert!(weight >= min_weight, "weight {weight} exceeds the guardrail");
+        }
+        // the audit log replays to the current weights
+        let mut replayed: BTreeMap<EdgeIndex, Weight> = BTreeMap::new();
+        for change in solver.audit_log.iter() {
+            let previous_weight = match replayed.get(&change.edge_index) {
+                Some(weight) => *weight,
+                None => initializer.weighted_edges[change.edge_index as usize].2,
+            };
+            assert_eq!(change.old_weight, previous_weight);
+            replayed.insert(change.edge_index, change.new_weight);
+        }
+        for (edge_index, weight) in replayed.iter() {
+            assert_eq!(solver.weight(*edge_index), *weight);
+        }
+        // a bounded audit log keeps the latest changes
+        assert_eq!(solver.weight_changes, solver.audit_log.len());
+        let mut bounded_solver = SolverRecalibrated::new(
+            Box::new(SolverSerial::new(&initializer)),
+            &initializer,
+            vec![0.01; initializer.weighted_edges.len()],
+            RecalibrationConfig {
+                audit_log_capacity: 3,
+                ..solver.config.clone()
+            },
+        );
+        for seed in 0..4000 {
+            bounded_solver.solve(&code.generate_random_errors(seed));
+            bounded_solver.clear();
+        }
+        assert_eq!(bounded_solver.weight_changes, solver.weight_changes);
+        assert_eq!(bounded_solver.audit_log.len(), 3);
+        let latest_changes: Vec<_> = solver.audit_log.iter().skip(solver.weight_changes - 3).collect();
+        for (change, expected) in bounded_solver.audit_log.iter().zip(latest_changes) {
+            assert_eq!((change.shot, change.edge_index), (expected.shot, expected.edge_index));
+        }
+    }
+
+    /// the adaptive solver learns a union-find threshold and always returns a valid correction
+    #[test]
+    fn mwpm_solver_adaptive_1() {
+        // cargo test mwpm_solver_adaptive_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.05, 500);
+        let initializer = code.get_initializer();
+        let calibration_shots: Vec<SyndromePattern> = (0..2000).map(|seed| code.generate_random_errors(seed)).collect();
+        let mut solver = SolverAdaptive::new(&initializer, AdaptiveConfig::default());
+        let bins = solver.calibrate(&calibration_shots);
+        println!("bins: {bins:?}, threshold: {}", solver.config.union_find_max_defects);
+        // isolated defects are always matched optimally by union-find
+        assert!(solver.config.union_find_max_defects >= 2);
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+        for seed in 2000..3000 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            let subgraph = solver.subgraph();
+            subgraph_builder.subgraph = subgraph.into_iter().collect();
+            let expected: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+            assert_eq!(subgraph_builder.subgraph_boundary(), expected);
+            serial_solver.solve(&syndrome_pattern);
+            let optimal_weight = serial_solver.sum_dual_variables();
+            match solver.decide(&syndrome_pattern) {
+                AdaptiveDecision::Trivial => assert_eq!(optimal_weight, 0),
+                AdaptiveDecision::UnionFind => assert!(subgraph_builder.total_weight() >= optimal_weight),
+                AdaptiveDecision::MWPM => assert_eq!(subgraph_builder.total_weight(), optimal_weight),
+            }
+            solver.clear();
+            serial_solver.clear();
+        }
+        let statistics = &solver.statistics;
+        assert_eq!(
+            statistics.trivial_shots + statistics.union_find_shots + statistics.mwpm_shots,
+            1000
+        );
+        assert!(statistics.trivial_shots > 0 && statistics.union_find_shots > 0);
+        let report = solver.generate_profiler_report();
+        assert_eq!(report["adaptive"]["union_find_shots"], json!(statistics.union_find_shots));
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn mwpm_solver_stream_1() {
+        // cargo test mwpm_solver_stream_1 -- --nocapture
+        let d = 5;
+        let total_rounds = 32;
+        let round_vertex_num = d * (d + 1);
+        let mut code = PhenomenologicalPlanarCode::new(d, total_rounds - 1, 0.03, 500);
+        let initializer = code.get_initializer();
+        let edge_weights: BTreeMap<(VertexIndex, VertexIndex), Weight> = initializer
+            .weighted_edges
+            .iter()
+            .map(|&(vertex_1, vertex_2, weight)| ((vertex_1.min(vertex_2), vertex_1.max(vertex_2)), weight))
+            .collect();
+        let virtual_vertices: BTreeSet<VertexIndex> = initializer.virtual_vertices.iter().cloned().collect();
+        let config = StreamConfig {
+            commit_rounds: 3,
+            buffer_rounds: 4,
+            ..Default::default()
+        };
+        let mut stream = SolverStream::new(&initializer, round_vertex_num, config);
+        let mut serial_solver = SolverSerial::new(&initializer);
+        for seed in 0..20 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let mut rounds = vec![vec![]; total_rounds as usize];
+            for &vertex_index in syndrome_pattern.defect_vertices.iter() {
+                rounds[(vertex_index / round_vertex_num) as usize].push(vertex_index % round_vertex_num);
+            }
+            let mut commits = vec![];
+            for round_defect_vertices in rounds.into_iter() {
+                commits.extend(stream.push_round(&SyndromePattern::new_vertices(round_defect_vertices)));
+            }
+            commits.extend(stream.flush());
+            let mut committed_rounds = 0;
+            let mut parity = BTreeSet::new();
+            let mut weight = 0;
+            for commit in commits.iter() {
+                assert_eq!(commit.start_round, committed_rounds);
+                committed_rounds = commit.end_round;
+                for &(vertex_1, vertex_2) in commit.edges.iter() {
+                    weight += edge_weights[&(vertex_1, vertex_2)];
+                    for vertex_index in [vertex_1, vertex_2] {
+                        if !parity.remove(&vertex_index) {
+                            parity.insert(vertex_index);
+                        }
+                    }
+                }
+            }
+            assert_eq!(committed_rounds, total_rounds as usize);
+            parity.retain(|vertex_index| !virtual_vertices.contains(vertex_index));
+            let expected: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+            assert_eq!(parity, expected);
+            serial_solver.solve(&syndrome_pattern);
+            let optimal_weight = serial_solver.sum_dual_variables();
+            println!("seed {seed}: stream weight {weight}, optimal weight {optimal_weight}");
+            assert!(weight >= optimal_weight);
+            serial_solver.clear();
+            stream.clear();
+        }
+    }
+
+    /// per-shot weights give the same result as a solver constructed with those weights, and don't leak into later shots
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn mwpm_solver_solve_with_weights_1() {
+        // cargo test mwpm_solver_solve_with_weights_1 -- --nocapture
+        use crate::example_partition::*;
+        use crate::rand_xoshiro::rand_core::RngCore;
+        let mut code = PhenomenologicalPlanarCode::new(5, 5, 0.03, 500);
+        let partition_info = PhenomenologicalPlanarCodeTimePartition::new(5, 5, 2)
+            .build_apply(&mut code)
+            .info();
+        let initializer = code.get_initializer();
+        let mut solvers: Vec<Box<dyn PrimalDualSolver>> = vec![
+            Box::new(SolverSerial::new(&initializer)),
+            Box::new(SolverDualParallel::new(&initializer, &partition_info, json!({}))),
+            Box::new(SolverParallel::new(&initializer, &partition_info, json!({}))),
+        ];
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        for seed in 0..50 {
+            for solver in solvers.iter_mut() {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                let mut weights: Vec<(EdgeIndex, Weight)> = vec![];
+                for edge_index in 0..initializer.weighted_edges.len() {
+                    if rng.next_u32() % 4 == 0 {
+                        weights.push((edge_index as EdgeIndex, 2 * (rng.next_u32() % 1000) as Weight));
+                    }
+                }
+                let mut weighted_initializer = initializer.clone();
+                for &(edge_index, weight) in weights.iter() {
+                    weighted_initializer.weighted_edges[edge_index as usize].2 = weight;
+                }
+                let mut expected_solver = SolverSerial::new(&weighted_initializer);
+                expected_solver.solve(&syndrome_pattern);
+                solver.solve_with_weights(&syndrome_pattern, &weights);
+                assert_eq!(solver.sum_dual_variables(), expected_solver.sum_dual_variables());
+                solver.clear();
+                // the original weights are restored
+                expected_solver = SolverSerial::new(&initializer);
+                expected_solver.solve(&syndrome_pattern);
+                solver.solve(&syndrome_pattern);
+                assert_eq!(solver.sum_dual_variables(), expected_solver.sum_dual_variables());
+                solver.clear();
+            }
+        }
+    }
+
+    /// the matchings of a batch stay valid after the solver moves on to later shots
+    #[test]
+    fn mwpm_solver_solve_batch_1() {
+        // cargo test mwpm_solver_solve_batch_1 -- --nocapture
+        use crate::example_partition::*;
+        let mut code = PhenomenologicalPlanarCode::new(5, 5, 0.03, 500);
+        let partition_info = PhenomenologicalPlanarCodeTimePartition::new(5, 5, 2)
+            .build_apply(&mut code)
+            .info();
+        let initializer = code.get_initializer();
+        let syndrome_patterns: Vec<_> = (0..100).map(|seed| code.generate_random_errors(seed)).collect();
+        let mut solvers: Vec<Box<dyn PrimalDualSolver>> = vec![
+            Box::new(SolverSerial::new(&initializer)),
+            Box::new(SolverParallel::new(&initializer, &partition_info, json!({}))),
+        ];
+        for solver in solvers.iter_mut() {
+            let perfect_matchings = solver.solve_batch(&syndrome_patterns);
+            assert_eq!(perfect_matchings.len(), syndrome_patterns.len());
+            for (syndrome_pattern, perfect_matching) in syndrome_patterns.iter().zip(perfect_matchings.iter()) {
+                solver.solve(syndrome_pattern);
+                let mut expected = solver.perfect_matching().matched_vertices();
+                let mut matched_vertices = perfect_matching.matched_vertices();
+                expected.sort();
+                matched_vertices.sort();
+                assert_eq!(matched_vertices, expected);
+                solver.clear();
+            }
+        }
+    }
+
+    /// sorts the correction and reports its size, to check that the postprocessors are composed in order
+    struct SortingPostprocessor;
+
+    impl MatchingPostprocessor for SortingPostprocessor {
+        fn name(&self) -> String {
+            "sorted".to_string()
+        }
+        fn postprocess(
+            &mut self,
+            _initializer: &SolverInitializer,
+            _syndrome_pattern: &SyndromePattern,
+            subgraph: &mut Vec<EdgeIndex>,
+        ) -> Option<serde_json::Value> {
+            subgraph.sort();
+            Some(json!(subgraph.len()))
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn mwpm_solver_postprocessed_1() {
+        // cargo test mwpm_solver_postprocessed_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let logical_edges = left_boundary_edges(&code);
+        let edge_masks = logical_edges.iter().map(|&is_logical| is_logical as usize).collect();
+        let mut solver = SolverPostprocessed::new(Box::new(SolverSerial::new(&initializer)), &initializer);
+        solver.register(Box::new(SortingPostprocessor));
+        solver.register(Box::new(ObservablePostprocessor { edge_masks }));
+        solver.register(Box::new(CompressionPostprocessor::new(&initializer)));
+        assert_eq!(
+            solver.effective_config()["postprocessors"],
+            json!(["sorted", "observables", "compressed"])
+        );
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let subgraph_builder = SubGraphBuilder::new(&initializer);
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            serial_solver.solve(&syndrome_pattern);
+            let mut expected = serial_solver.subgraph();
+            expected.sort();
+            let subgraph = solver.subgraph();
+            assert_eq!(subgraph, expected);
+            assert_eq!(solver.sum_dual_variables(), serial_solver.sum_dual_variables());
+            let outputs = solver.outputs();
+            assert_eq!(outputs["sorted"], json!(subgraph.len()));
+            let flips = subgraph
+                .iter()
+                .filter(|&&edge_index| logical_edges[edge_index as usize])
+                .count();
+            assert_eq!(outputs["observables"], json!(flips % 2));
+            let compressed: CompressedSubgraph = serde_json::from_value(outputs["compressed"].clone()).unwrap();
+            assert_eq!(subgraph_builder.expand_subgraph(&compressed), subgraph);
+            solver.clear();
+            serial_solver.clear();
+            assert!(solver.outputs().is_empty());
+        }
+    }
+
+    /// the hybrid solver takes the union-find fast path for most sparse shots and always returns a valid correction
+    #[test]
+    fn mwpm_solver_hybrid_1() {
+        // cargo test mwpm_solver_hybrid_1 -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(7, 6, 0.005, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverHybrid::new(&initializer, HybridConfig::default());
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+        let mut suboptimal_shots = 0;
+        for seed in 0..500 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            let subgraph = solver.subgraph();
+            subgraph_builder.subgraph = subgraph.into_iter().collect();
+            let expected: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+            assert_eq!(subgraph_builder.subgraph_boundary(), expected);
+            assert_eq!(solver.sum_dual_variables(), subgraph_builder.total_weight());
+            // the perfect matching is consistent with the correction in both paths
+            let matched_num = solver.perfect_matching().matched_vertices().len();
+            assert!(2 * matched_num >= expected.len() && matched_num <= expected.len());
+            serial_solver.solve(&syndrome_pattern);
+            let optimal_weight = serial_solver.sum_dual_variables();
+            match solver.decision().unwrap() {
+                AdaptiveDecision::Trivial => assert_eq!(optimal_weight, 0),
+                AdaptiveDecision::UnionFind => {
+                    assert!(subgraph_builder.total_weight() >= optimal_weight);
+                    if subgraph_builder.total_weight() > optimal_weight {
+                        suboptimal_shots += 1;
+                    }
+                }
+                AdaptiveDecision::MWPM => assert_eq!(subgraph_builder.total_weight(), optimal_weight),
+            }
+            solver.clear();
+            serial_solver.clear();
+        }
+        let statistics = &solver.statistics;
+        println!("{statistics:?}, suboptimal: {suboptimal_shots}");
+        assert_eq!(
+            statistics.trivial_shots + statistics.union_find_shots + statistics.mwpm_shots,
+            500
+        );
+        assert!(statistics.union_find_shots > statistics.mwpm_shots);
+        assert!(suboptimal_shots * 20 < statistics.union_find_shots);
+        let report = solver.generate_profiler_report();
+        assert_eq!(report["hybrid"]["mwpm_shots"], json!(statistics.mwpm_shots));
+    }
+
+    /// the clusters solved in parallel give the same minimum weight as the serial solver, including when every
+    /// defect vertex starts as its own cluster and the overlapping ones must be merged
+    #[test]
+    fn mwpm_solver_cluster_parallel_1() {
+        // cargo test mwpm_solver_cluster_parallel_1 -- --nocapture
+        let mut codes: Vec<Box<dyn ExampleCode>> = vec![
+            Box::new(CodeCapacityPlanarCode::new(11, 0.05, 500)),
+            Box::new(PhenomenologicalPlanarCode::new(7, 7, 0.02, 500)),
+        ];
+        for code in codes.iter_mut() {
+            let initializer = code.get_initializer();
+            let mut serial_solver = SolverSerial::new(&initializer);
+            for cluster_distance in [None, Some(0)] {
+                let config = json!({"cluster_distance": cluster_distance, "thread_pool_size": 4});
+                let mut solver = SolverClusterParallel::new(&initializer, config);
+                let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+                let mut merged = false;
+                for seed in 0..50 {
+                    let syndrome_pattern = code.generate_random_errors(seed);
+                    serial_solver.solve(&syndrome_pattern);
+                    solver.solve(&syndrome_pattern);
+                    assert_eq!(solver.sum_dual_variables(), serial_solver.sum_dual_variables());
+                    subgraph_builder.load_subgraph(&solver.subgraph());
+                    assert_eq!(subgraph_builder.total_weight(), serial_solver.sum_dual_variables());
+                    let statistics = &solver.statistics;
+                    assert_eq!(statistics.final_clusters, solver.clusters.len());
+                    assert!(statistics.final_clusters <= statistics.initial_clusters);
+                    merged |= statistics.rounds > 1;
+                    serial_solver.clear();
+                    solver.clear();
+                    subgraph_builder.clear();
+                }
+                if cluster_distance == Some(0) {
+                    assert!(merged, "overlapping clusters are expected to be merged");
+                }
+            }
+        }
+    }
 }
 
 #[cfg(feature = "python_binding")]
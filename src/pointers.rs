@@ -6,6 +6,8 @@
 //! After this, one can enable the feature `unsafe_pointer` to remove the unnecessary locks, thus improving the performance.
 //!
 
+#[cfg(feature = "lock_order_audit")]
+use super::lock_order_audit::{self, LockOrderAuditGuard};
 use super::util::*;
 use crate::parking_lot::lock_api::{RwLockReadGuard, RwLockWriteGuard};
 use crate::parking_lot::{RawRwLock, RwLock};
@@ -104,18 +106,49 @@ pub trait RwLockPtr<ObjType> {
 
     fn ptr_mut(&mut self) -> &mut Arc<RwLock<ObjType>>;
 
+    #[cfg(not(feature = "lock_order_audit"))]
     #[inline(always)]
     fn read_recursive(&self) -> RwLockReadGuard<RawRwLock, ObjType> {
         let ret = self.ptr().read_recursive();
         ret
     }
 
+    #[cfg(not(feature = "lock_order_audit"))]
     #[inline(always)]
     fn write(&self) -> RwLockWriteGuard<RawRwLock, ObjType> {
         let ret = self.ptr().write();
         ret
     }
 
+    #[cfg(feature = "lock_order_audit")]
+    fn read_recursive(&self) -> LockOrderAuditGuard<RwLockReadGuard<RawRwLock, ObjType>> {
+        let address = lock_order_audit::before_acquire(self.ptr(), false);
+        LockOrderAuditGuard::new(self.ptr().read_recursive(), address, false)
+    }
+
+    #[cfg(feature = "lock_order_audit")]
+    fn write(&self) -> LockOrderAuditGuard<RwLockWriteGuard<RawRwLock, ObjType>> {
+        let address = lock_order_audit::before_acquire(self.ptr(), true);
+        LockOrderAuditGuard::new(self.ptr().write(), address, true)
+    }
+
+    /// the exclusive lock if it's immediately available, without blocking
+    #[cfg(not(feature = "lock_order_audit"))]
+    #[inline(always)]
+    fn try_write(&self) -> Option<RwLockWriteGuard<'_, RawRwLock, ObjType>> {
+        self.ptr().try_write()
+    }
+
+    #[cfg(feature = "lock_order_audit")]
+    fn try_write(&self) -> Option<LockOrderAuditGuard<RwLockWriteGuard<'_, RawRwLock, ObjType>>> {
+        let guard = self.ptr().try_write()?;
+        Some(LockOrderAuditGuard::new(
+            guard,
+            lock_order_audit::tracked_address(self.ptr()),
+            true,
+        ))
+    }
+
     fn ptr_eq(&self, other: &Self) -> bool {
         Arc::ptr_eq(self.ptr(), other.ptr())
     }
@@ -305,6 +338,77 @@ impl<T: FastClear> weak_table::traits::WeakElement for FastClearWeakRwLock<T> {
     }
 }
 
+/// recycles the internal nodes of a module across decoding rounds to reduce the allocator pressure: a node released in
+/// the middle of a round may still be referred to by weak pointers, so it only becomes available after [`Self::clear`]
+#[derive(Debug)]
+pub struct ObjectPool<T> {
+    /// objects released in the current round
+    released: Vec<T>,
+    /// objects ready to be reused
+    available: Vec<T>,
+    pub statistics: ObjectPoolStatistics,
+}
+
+/// the allocation behavior of an [`ObjectPool`] since its creation, reported in the profiler
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct ObjectPoolStatistics {
+    /// the number of newly allocated objects
+    pub allocated: usize,
+    /// the number of acquired objects that are reused instead of allocated
+    pub recycled: usize,
+}
+
+impl<T> Default for ObjectPool<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ObjectPool<T> {
+    pub fn new() -> Self {
+        Self {
+            released: vec![],
+            available: vec![],
+            statistics: ObjectPoolStatistics::default(),
+        }
+    }
+
+    /// acquire an object: reuse `cached` if given, e.g. the object left in the same slot by the last round, or otherwise
+    /// an available object in the pool, after resetting it by `reset`; allocate a new one only when there is none
+    pub fn acquire(&mut self, cached: Option<T>, reset: impl FnOnce(&T), allocate: impl FnOnce() -> T) -> T {
+        match cached.or_else(|| self.available.pop()) {
+            Some(object) => {
+                reset(&object);
+                self.statistics.recycled += 1;
+                object
+            }
+            None => {
+                self.statistics.allocated += 1;
+                allocate()
+            }
+        }
+    }
+
+    /// release an object that is no longer used by the module
+    pub fn release(&mut self, object: T) {
+        self.released.push(object);
+    }
+
+    /// make all the released objects available, to be called when the module is cleared
+    pub fn clear(&mut self) {
+        self.available.append(&mut self.released);
+    }
+
+    /// the number of objects held by the pool
+    pub fn len(&self) -> usize {
+        self.released.len() + self.available.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 /*
  * unsafe APIs, used for production environment where speed matters
  */
@@ -768,6 +872,12 @@ cfg_if::cfg_if! {
             ($variable:ident, $lock:expr, $timestamp:expr) => { let $variable = $lock.write($timestamp); };
         }
         #[allow(unused_imports)] pub use lock_write;
+        #[macro_export]
+        macro_rules! lock_read {
+            ($variable:ident, $lock:expr) => { let $variable = $lock.read_recursive(); };
+            ($variable:ident, $lock:expr, $timestamp:expr) => { let $variable = $lock.read_recursive($timestamp); };
+        }
+        #[allow(unused_imports)] pub use lock_read;
         cfg_if::cfg_if! {
             if #[cfg(feature="dangerous_pointer")] {
                 pub type FastClearArcManualSafeLockDangerous<T> = FastClearArcUnsafeDangerous<T>;
@@ -788,6 +898,12 @@ cfg_if::cfg_if! {
             ($variable:ident, $lock:expr, $timestamp:expr) => { let mut $variable = $lock.write($timestamp); };
         }
         #[allow(unused_imports)] pub use lock_write;
+        #[macro_export]
+        macro_rules! lock_read {
+            ($variable:ident, $lock:expr) => { let $variable = $lock.read_recursive(); };
+            ($variable:ident, $lock:expr, $timestamp:expr) => { let $variable = $lock.read_recursive($timestamp); };
+        }
+        #[allow(unused_imports)] pub use lock_read;
         pub type FastClearArcManualSafeLockDangerous<T> = FastClearArcRwLock<T>;
         pub type FastClearWeakManualSafeLockDangerous<T> = FastClearWeakRwLock<T>;
     }
@@ -818,6 +934,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn pointers_object_pool_1() {
+        // cargo test pointers_object_pool_1 -- --nocapture
+        let mut pool = ObjectPool::<TesterPtr>::new();
+        let reset = |ptr: &TesterPtr| ptr.write().idx = 0;
+        let ptr = pool.acquire(None, reset, || TesterPtr::new_value(Tester { idx: 0 }));
+        ptr.write().idx = 1;
+        let ptr = pool.acquire(Some(ptr), reset, || unreachable!());
+        assert_eq!(ptr.read_recursive().idx, 0);
+        ptr.write().idx = 2;
+        pool.release(ptr);
+        // a released object is not reused until the pool is cleared
+        let ptr = pool.acquire(None, reset, || TesterPtr::new_value(Tester { idx: 3 }));
+        assert_eq!(ptr.read_recursive().idx, 3);
+        pool.clear();
+        assert_eq!(pool.len(), 1);
+        let ptr = pool.acquire(None, reset, || unreachable!());
+        assert_eq!(ptr.read_recursive().idx, 0);
+        assert!(pool.is_empty());
+        assert_eq!((pool.statistics.allocated, pool.statistics.recycled), (2, 2));
+    }
+
     #[test]
     fn pointers_test_1() {
         // cargo test pointers_test_1 -- --nocapture
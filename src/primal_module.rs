@@ -4,15 +4,24 @@
 //!
 
 #![cfg_attr(feature = "unsafe_pointer", allow(dropping_references))]
+#[cfg(feature = "track_allocations")]
+use super::allocation_tracker::*;
 use super::complete_graph::*;
+use super::degeneracy::*;
 use super::dual_module::*;
 use super::pointers::*;
 use super::util::*;
 use super::visualize::*;
 use crate::derivative::Derivative;
+use crate::rand_xoshiro::rand_core::SeedableRng;
+use crate::rayon::prelude::*;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 #[derive(Derivative)]
 #[derivative(Debug)]
@@ -25,7 +34,7 @@ pub struct IntermediateMatching {
     pub virtual_matchings: Vec<((DualNodePtr, DualNodeWeak), VertexIndex)>,
 }
 
-#[derive(Derivative)]
+#[derive(Derivative, Clone)]
 #[derivative(Debug)]
 #[cfg_attr(feature = "python_binding", cfg_eval)]
 #[cfg_attr(feature = "python_binding", pyclass)]
@@ -36,6 +45,52 @@ pub struct PerfectMatching {
     pub virtual_matchings: Vec<(DualNodePtr, VertexIndex)>,
 }
 
+/// a hard limit on the effort of a single solve, for real-time decoders that must meet a deadline; once exhausted, every
+/// alternating tree collapses to a union-find cluster (as if `max_tree_size` were 0), so that the solve finishes soon
+/// after with a valid but possibly suboptimal matching
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolveBudget {
+    /// the wall-clock time since the start of the solve
+    pub max_duration: Option<Duration>,
+    /// the number of dual growth rounds; counted in each unit for the parallel primal module
+    pub max_dual_growth_rounds: Option<usize>,
+}
+
+/// checks a [`SolveBudget`] at every step of the primal-dual loop
+#[derive(Debug, Clone)]
+pub struct SolveBudgetTracker {
+    pub budget: SolveBudget,
+    pub start: Instant,
+    pub dual_growth_rounds: usize,
+    /// whether the budget has been exhausted, i.e. the result may be suboptimal
+    pub truncated: bool,
+}
+
+impl SolveBudgetTracker {
+    pub fn new(budget: SolveBudget, start: Instant) -> Self {
+        Self {
+            budget,
+            start,
+            dual_growth_rounds: 0,
+            truncated: false,
+        }
+    }
+
+    /// record a step of the primal-dual loop; returns true only at the step when the budget becomes exhausted
+    pub fn step(&mut self, group_max_update_length: &GroupMaxUpdateLength) -> bool {
+        if group_max_update_length.get_none_zero_growth().is_some() {
+            self.dual_growth_rounds += 1;
+        }
+        if self.truncated {
+            return false;
+        }
+        let exceeds_rounds = matches!(self.budget.max_dual_growth_rounds, Some(max) if self.dual_growth_rounds > max);
+        let exceeds_duration = matches!(self.budget.max_duration, Some(max) if self.start.elapsed() > max);
+        self.truncated = exceeds_rounds || exceeds_duration;
+        self.truncated
+    }
+}
+
 /// common trait that must be implemented for each implementation of primal module
 pub trait PrimalModuleImpl {
     /// create a primal module given the dual module
@@ -175,8 +230,13 @@ pub trait PrimalModuleImpl {
     ) where
         F: FnMut(&DualModuleInterfacePtr, &mut D, &mut Self, &GroupMaxUpdateLength),
     {
-        interface.load(syndrome_pattern, dual_module);
-        self.load(interface);
+        {
+            trace_span!("load_syndrome", defects = syndrome_pattern.defect_vertices.len());
+            #[cfg(feature = "track_allocations")]
+            let _allocation_scope = AllocationScope::enter(AllocationModule::Dual);
+            interface.load(syndrome_pattern, dual_module);
+            self.load(interface);
+        }
         self.solve_step_callback_interface_loaded(interface, dual_module, callback);
     }
 
@@ -188,12 +248,22 @@ pub trait PrimalModuleImpl {
     ) where
         F: FnMut(&DualModuleInterfacePtr, &mut D, &mut Self, &GroupMaxUpdateLength),
     {
+        #[cfg(feature = "track_allocations")]
+        let _allocation_scope = AllocationScope::enter(AllocationModule::Dual);
         let mut group_max_update_length = dual_module.compute_maximum_update_length();
         while !group_max_update_length.is_empty() {
-            callback(interface, dual_module, self, &group_max_update_length);
+            {
+                #[cfg(feature = "track_allocations")]
+                let _allocation_scope = AllocationScope::enter(AllocationModule::Other);
+                callback(interface, dual_module, self, &group_max_update_length);
+            }
             if let Some(length) = group_max_update_length.get_none_zero_growth() {
+                trace_span!("grow", length);
                 interface.grow(length, dual_module);
             } else {
+                trace_span!("resolve");
+                #[cfg(feature = "track_allocations")]
+                let _allocation_scope = AllocationScope::enter(AllocationModule::Primal);
                 self.resolve(group_max_update_length, interface, dual_module);
             }
             group_max_update_length = dual_module.compute_maximum_update_length();
@@ -433,6 +503,129 @@ impl PerfectMatching {
     pub fn get_virtual_matchings(&self) -> Vec<(NodeIndex, VertexIndex)> {
         self.virtual_matchings.iter().map(|(a, b)| (a.updated_index(), *b)).collect()
     }
+
+    #[cfg(feature = "python_binding")]
+    fn __len__(&self) -> usize {
+        self.peer_matchings.len() + self.virtual_matchings.len()
+    }
+
+    /// get a matched pair of vertices by index, see [`PerfectMatching::matched_vertices`]
+    #[cfg(feature = "python_binding")]
+    fn __getitem__(&self, index: isize) -> PyResult<(VertexIndex, VertexIndex)> {
+        let matched_vertices = self.matched_vertices();
+        let length = matched_vertices.len() as isize;
+        let position = if index < 0 { index + length } else { index };
+        if position < 0 || position >= length {
+            return Err(pyo3::exceptions::PyIndexError::new_err("matching index out of range"));
+        }
+        Ok(matched_vertices[position as usize])
+    }
+
+    #[cfg(feature = "python_binding")]
+    fn __iter__(&self, py: Python<'_>) -> PyResult<PyObject> {
+        Ok(pyo3::types::PyList::new(py, self.matched_vertices())
+            .call_method0("__iter__")?
+            .into())
+    }
+}
+
+/// a matched pair of vertices that meet only in a fusion unit, i.e. matched across an interface instead of strictly inside
+/// a partition; this is what a fusion unit has to communicate, e.g. to validate the fusion messages of hardware decoders
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BoundaryPair {
+    /// the fusion unit where the two vertices first meet
+    pub unit_index: usize,
+    /// the defect vertex
+    pub vertex_1: VertexIndex,
+    /// the matched defect vertex, or the virtual vertex if `is_virtual`
+    pub vertex_2: VertexIndex,
+    /// whether the defect vertex is matched to a virtual vertex
+    pub is_virtual: bool,
+}
+
+impl PerfectMatching {
+    /// the matched pairs of vertices: first `(defect_vertex_1, defect_vertex_2)` of the peer matchings,
+    /// then `(defect_vertex, virtual_vertex)` of the virtual matchings
+    pub fn matched_vertices(&self) -> Vec<(VertexIndex, VertexIndex)> {
+        let peer_matchings = self
+            .peer_matchings
+            .iter()
+            .map(|(ptr_1, ptr_2)| (Self::defect_vertex_of(ptr_1), Self::defect_vertex_of(ptr_2)));
+        let virtual_matchings = self
+            .virtual_matchings
+            .iter()
+            .map(|(ptr, virtual_vertex)| (Self::defect_vertex_of(ptr), *virtual_vertex));
+        peer_matchings.chain(virtual_matchings).collect()
+    }
+
+    /// the logical observables flipped by the minimum-weight paths of the matched pairs; requires the observables to be
+    /// annotated in the initializer, see [`SolverInitializer::with_observables`]. The paths are computed with the
+    /// static weights of the initializer; with erasures or dynamic weights, use [`SubGraphBuilder::get_predicted_observables`]
+    /// of a builder that has loaded them instead
+    pub fn predicted_observables(&self, initializer: &SolverInitializer) -> Vec<bool> {
+        let mut subgraph_builder = SubGraphBuilder::new(initializer);
+        subgraph_builder.load_perfect_matching(self);
+        subgraph_builder.get_predicted_observables()
+    }
+
+    /// copy the matched dual nodes so that this matching stays valid after the solver is cleared and reused,
+    /// because the dual nodes are recycled by the next decoding problem
+    pub fn detached(&self) -> Self {
+        let detach = |ptr: &DualNodePtr| {
+            let mut node = ptr.read_recursive().clone();
+            node.parent_blossom = None;
+            DualNodePtr::new_value(node)
+        };
+        Self {
+            peer_matchings: (self.peer_matchings.iter())
+                .map(|(ptr_1, ptr_2)| (detach(ptr_1), detach(ptr_2)))
+                .collect(),
+            virtual_matchings: (self.virtual_matchings.iter())
+                .map(|(ptr, virtual_vertex)| (detach(ptr), *virtual_vertex))
+                .collect(),
+        }
+    }
+
+    fn defect_vertex_of(ptr: &DualNodePtr) -> VertexIndex {
+        let node = ptr.read_recursive();
+        if let DualNodeClass::DefectVertex { defect_index } = &node.class {
+            *defect_index
+        } else {
+            unreachable!("can only be syndrome")
+        }
+    }
+
+    /// list the matched pairs across the interfaces of a partitioned decoding graph, ordered by the fusion unit;
+    /// pairs matched strictly inside a partition are not included
+    #[allow(clippy::unnecessary_cast)]
+    pub fn boundary_pairs(&self, partition_info: &PartitionInfo) -> Vec<BoundaryPair> {
+        let pairs = self
+            .peer_matchings
+            .iter()
+            .map(|(ptr_1, ptr_2)| (Self::defect_vertex_of(ptr_1), Self::defect_vertex_of(ptr_2), false))
+            .chain(
+                self.virtual_matchings
+                    .iter()
+                    .map(|(ptr, virtual_vertex)| (Self::defect_vertex_of(ptr), *virtual_vertex, true)),
+            );
+        let mut boundary_pairs = vec![];
+        for (vertex_1, vertex_2, is_virtual) in pairs {
+            let unit_index = partition_info.lowest_common_unit(
+                partition_info.owner_of_vertex(vertex_1),
+                partition_info.owner_of_vertex(vertex_2),
+            );
+            if partition_info.units[unit_index].children.is_some() {
+                boundary_pairs.push(BoundaryPair {
+                    unit_index,
+                    vertex_1,
+                    vertex_2,
+                    is_virtual,
+                });
+            }
+        }
+        boundary_pairs.sort_by_key(|pair| (pair.unit_index, pair.vertex_1, pair.vertex_2));
+        boundary_pairs
+    }
 }
 
 impl FusionVisualizer for PerfectMatching {
@@ -484,37 +677,158 @@ impl FusionVisualizer for PerfectMatching {
     }
 }
 
+/// a straight chain of edges `start`, `start + stride`, `start + 2 * stride`, ..., `end`; for example, a time-like chain
+/// spanning many measurement rounds has a stride of the number of vertices in each round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubgraphSegment {
+    pub start: VertexIndex,
+    pub end: VertexIndex,
+    pub stride: VertexIndex,
+}
+
+impl SubgraphSegment {
+    /// the number of edges in this segment
+    #[allow(clippy::unnecessary_cast)]
+    pub fn edge_num(&self) -> usize {
+        ((self.end - self.start) / self.stride) as usize
+    }
+}
+
+/// a subgraph whose straight chains are compressed into segments, which is several times smaller to output or log when
+/// the matched pairs are connected by long chains, e.g. in experiments with many measurement rounds;
+/// it's lossless: [`SubGraphBuilder::expand_subgraph`] recovers the exact edges
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressedSubgraph {
+    pub segments: Vec<SubgraphSegment>,
+    /// the edges that cannot be identified by their vertices, e.g. one of the parallel edges, kept as they are
+    pub edges: Vec<EdgeIndex>,
+}
+
+impl CompressedSubgraph {
+    /// the number of edges in the original subgraph
+    pub fn edge_num(&self) -> usize {
+        self.segments.iter().map(|segment| segment.edge_num()).sum::<usize>() + self.edges.len()
+    }
+}
+
+/// the matched pairs are split into parallel jobs of at least this many pairs, because a job of a few short paths
+/// costs less than scheduling it on another thread
+pub const PARALLEL_SUBGRAPH_MIN_PAIRS: usize = 8;
+
+/// how [`SubGraphBuilder`] chooses among the minimum-weight paths of a matched pair; the choice doesn't change the weight
+/// of the correction, but it may change the logical observables it flips. All the policies except [`Self::Dijkstra`]
+/// choose among the minimum-weight paths with the fewest edges, which are well defined even with zero-weight edges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PathTieBreaking {
+    /// whichever path Dijkstra's algorithm finds first, which depends on the vertex indices; the fastest
+    #[default]
+    Dijkstra,
+    /// the path whose sequence of edge indices (from the first vertex of the pair) is lexicographically smallest
+    Lexicographic,
+    /// a path uniformly at random, deterministic given the seed and the pair
+    Randomized { seed: u64 },
+    /// the path along which most of the minimum-weight paths go, i.e. the valid path (parity projection) that maximizes
+    /// the sum over its edges of the fraction of minimum-weight paths using that edge
+    Consensus,
+}
+
 /// build a subgraph based on minimum-weight paths between matched pairs
 #[derive(Debug, Clone)]
 pub struct SubGraphBuilder {
-    /// number of vertices
+    /// number of vertices, including the boundary vertices of the weighted virtual vertices
     pub vertex_num: VertexNum,
-    /// mapping from vertex pair to edge index
-    vertex_pair_edges: HashMap<(VertexIndex, VertexIndex), EdgeIndex>,
+    /// number of edges in the decoding graph; the edges to the boundary vertices of the weighted virtual vertices come after
+    /// them, which count in [`Self::total_weight`] but are not reported by [`Self::get_subgraph`]
+    pub edge_num: usize,
+    /// mapping from vertex pair to edge index, immutable and thus shared between clones for threads
+    vertex_pair_edges: Arc<HashMap<(VertexIndex, VertexIndex), EdgeIndex>>,
     /// an instance of complete graph to compute minimum-weight path between any pair of vertices
     pub complete_graph: CompleteGraph,
     /// current subgraph, assuming edges are not very much
     pub subgraph: BTreeSet<EdgeIndex>,
+    /// whether a vertex is virtual, which is ignored when checking the parity of the subgraph
+    is_virtual: Arc<Vec<bool>>,
+    /// the defect vertices of the current syndrome, if loaded while the parity check is enabled
+    defect_vertices: Option<Vec<VertexIndex>>,
+    /// check that the boundary of the subgraph equals the defect vertices in [`Self::get_subgraph`];
+    /// it's cheap compared to decoding, but can be disabled for maximum performance
+    pub enable_parity_check: bool,
+    /// the logical observables flipped by each edge, if annotated in the initializer
+    observables: Option<Arc<ObservableMasks>>,
+    /// how to choose among the minimum-weight paths of a matched pair
+    pub tie_breaking: PathTieBreaking,
 }
 
 impl SubGraphBuilder {
+    #[allow(clippy::unnecessary_cast)]
     pub fn new(initializer: &SolverInitializer) -> Self {
+        let edge_num = initializer.weighted_edges.len();
+        let original_initializer = initializer;
+        let initializer = &*initializer.expand_weighted_virtual_vertices();
         let mut vertex_pair_edges = HashMap::with_capacity(initializer.weighted_edges.len());
         for (edge_index, (i, j, _)) in initializer.weighted_edges.iter().enumerate() {
             let id = if i < j { (*i, *j) } else { (*j, *i) };
             vertex_pair_edges.insert(id, edge_index as EdgeIndex);
         }
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        for &(virtual_vertex, _) in original_initializer.virtual_vertices_weighted.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
         Self {
             vertex_num: initializer.vertex_num,
-            vertex_pair_edges,
+            edge_num,
+            vertex_pair_edges: Arc::new(vertex_pair_edges),
             complete_graph: CompleteGraph::new(initializer.vertex_num, &initializer.weighted_edges),
             subgraph: BTreeSet::new(),
+            is_virtual: Arc::new(is_virtual),
+            defect_vertices: None,
+            enable_parity_check: true,
+            observables: initializer.observables.clone().map(Arc::new),
+            tie_breaking: PathTieBreaking::default(),
+        }
+    }
+
+    /// create a builder with fresh mutable state that shares the immutable lookup tables with this one
+    pub fn clone_fresh(&self) -> Self {
+        Self {
+            vertex_num: self.vertex_num,
+            edge_num: self.edge_num,
+            vertex_pair_edges: self.vertex_pair_edges.clone(),
+            complete_graph: CompleteGraph::new(self.vertex_num, &self.complete_graph.weighted_edges),
+            subgraph: BTreeSet::new(),
+            is_virtual: self.is_virtual.clone(),
+            defect_vertices: None,
+            enable_parity_check: self.enable_parity_check,
+            observables: self.observables.clone(),
+            tie_breaking: self.tie_breaking,
         }
     }
 
     pub fn clear(&mut self) {
         self.subgraph.clear();
         self.complete_graph.reset();
+        self.defect_vertices = None;
+    }
+
+    /// load the defect vertices of the syndrome, so that the subgraph can be verified against them; they're only kept
+    /// when the parity check is enabled
+    pub fn load_defect_vertices(&mut self, defect_vertices: &[VertexIndex]) {
+        self.defect_vertices = self.enable_parity_check.then(|| defect_vertices.to_vec());
+    }
+
+    /// the edge weights in effect that are changed by the loaded erasures and dynamic weights
+    #[allow(clippy::unnecessary_cast)]
+    pub fn loaded_dynamic_weights(&self) -> Vec<(EdgeIndex, Weight)> {
+        let complete_graph = &self.complete_graph;
+        complete_graph
+            .edge_modifier
+            .iter()
+            .map(|&(edge_index, _)| (edge_index, complete_graph.weighted_edges[edge_index as usize].2))
+            .collect()
     }
 
     /// temporarily set some edges to 0 weight, and when it resets, those edges will be reverted back to the original weight
@@ -526,38 +840,33 @@ impl SubGraphBuilder {
         self.complete_graph.load_dynamic_weights(dynamic_weights);
     }
 
-    /// load perfect matching to the subgraph builder
+    /// load perfect matching to the subgraph builder; the minimum-weight paths of the matched pairs are independent,
+    /// so they are computed in parallel by querying the shared complete graph
     pub fn load_perfect_matching(&mut self, perfect_matching: &PerfectMatching) {
         self.subgraph.clear();
+        let defect_index = |ptr: &DualNodePtr| {
+            let node = ptr.read_recursive();
+            if let DualNodeClass::DefectVertex { defect_index } = &node.class {
+                *defect_index
+            } else {
+                unreachable!("can only be syndrome")
+            }
+        };
+        let mut matched_pairs =
+            Vec::with_capacity(perfect_matching.peer_matchings.len() + perfect_matching.virtual_matchings.len());
         for (ptr_1, ptr_2) in perfect_matching.peer_matchings.iter() {
-            let a_vid = {
-                let node = ptr_1.read_recursive();
-                if let DualNodeClass::DefectVertex { defect_index } = &node.class {
-                    *defect_index
-                } else {
-                    unreachable!("can only be syndrome")
-                }
-            };
-            let b_vid = {
-                let node = ptr_2.read_recursive();
-                if let DualNodeClass::DefectVertex { defect_index } = &node.class {
-                    *defect_index
-                } else {
-                    unreachable!("can only be syndrome")
-                }
-            };
-            self.add_matching(a_vid, b_vid);
+            matched_pairs.push((defect_index(ptr_1), defect_index(ptr_2)));
         }
         for (ptr, virtual_vertex) in perfect_matching.virtual_matchings.iter() {
-            let a_vid = {
-                let node = ptr.read_recursive();
-                if let DualNodeClass::DefectVertex { defect_index } = &node.class {
-                    *defect_index
-                } else {
-                    unreachable!("can only be syndrome")
-                }
-            };
-            self.add_matching(a_vid, *virtual_vertex);
+            matched_pairs.push((defect_index(ptr), *virtual_vertex));
+        }
+        let paths: Vec<Vec<EdgeIndex>> = matched_pairs
+            .par_iter()
+            .with_min_len(PARALLEL_SUBGRAPH_MIN_PAIRS)
+            .map(|&(vertex_1, vertex_2)| self.path_edges(vertex_1, vertex_2))
+            .collect();
+        for edge_index in paths.into_iter().flatten() {
+            self.toggle_edge(edge_index);
         }
     }
 
@@ -569,18 +878,158 @@ impl SubGraphBuilder {
     /// add a matching, finding the minimum path and XOR them into the subgraph (if adding the same pair twice, they will cancel each other)
     pub fn add_matching(&mut self, vertex_1: VertexIndex, vertex_2: VertexIndex) {
         let (path, _) = self.complete_graph.get_path(vertex_1, vertex_2);
+        for edge_index in self.edges_along(vertex_1, &path) {
+            self.toggle_edge(edge_index);
+        }
+    }
+
+    /// the edges of the minimum-weight path between two vertices, without modifying the builder; ties are broken by
+    /// [`Self::tie_breaking`]
+    pub fn path_edges(&self, vertex_1: VertexIndex, vertex_2: VertexIndex) -> Vec<EdgeIndex> {
+        if self.tie_breaking == PathTieBreaking::Dijkstra {
+            let (path, _) = self.complete_graph.get_path_shared(vertex_1, vertex_2);
+            return self.edges_along(vertex_1, &path);
+        }
+        assert_ne!(vertex_1, vertex_2, "cannot get path between the same vertex");
+        let successors = self.minimum_path_successors(vertex_1, vertex_2);
+        // the vertices of the minimum-weight paths, in the order of decreasing hops to `vertex_2`
+        let mut vertices: Vec<VertexIndex> = successors.keys().copied().collect();
+        vertices.sort_by_key(|vertex| Reverse(successors[vertex].0));
+        vertices.push(vertex_2);
+        // the number of paths from `vertex_1` to each vertex and from each vertex to `vertex_2`
+        let mut counts_from = BTreeMap::from([(vertex_1, 1.)]);
+        for vertex in vertices.iter().filter(|&&vertex| vertex != vertex_2) {
+            let count = counts_from[vertex];
+            for &(_, next) in successors[vertex].1.iter() {
+                *counts_from.entry(next).or_insert(0.) += count;
+            }
+        }
+        let mut counts_to = BTreeMap::from([(vertex_2, 1.)]);
+        for vertex in vertices.iter().rev().filter(|&&vertex| vertex != vertex_2) {
+            let count = successors[vertex].1.iter().map(|(_, next)| counts_to[next]).sum();
+            counts_to.insert(*vertex, count);
+        }
+        // the score of a step towards `vertex_2` and the best total score from each vertex, only used by consensus
+        let fraction =
+            |vertex: &VertexIndex, next: &VertexIndex| counts_from[vertex] * counts_to[next] / counts_to[&vertex_1];
+        let mut best_scores = BTreeMap::from([(vertex_2, 0.)]);
+        if self.tie_breaking == PathTieBreaking::Consensus {
+            for vertex in vertices.iter().rev().filter(|&&vertex| vertex != vertex_2) {
+                let score = (successors[vertex].1.iter())
+                    .map(|(_, next)| fraction(vertex, next) + best_scores[next])
+                    .fold(f64::NEG_INFINITY, f64::max);
+                best_scores.insert(*vertex, score);
+            }
+        }
+        let mut rng = match self.tie_breaking {
+            PathTieBreaking::Randomized { seed } => {
+                DeterministicRng::seed_from_u64(seed ^ (vertex_1 as u64).rotate_left(21) ^ (vertex_2 as u64).rotate_left(42))
+            }
+            _ => DeterministicRng::seed_from_u64(0),
+        };
+        let mut edges = vec![];
+        let mut vertex = vertex_1;
+        while vertex != vertex_2 {
+            let steps = &successors[&vertex].1; // sorted by edge index
+            let &(edge_index, next) = match self.tie_breaking {
+                PathTieBreaking::Dijkstra => unreachable!(),
+                PathTieBreaking::Lexicographic => &steps[0],
+                PathTieBreaking::Randomized { .. } => {
+                    let mut remaining = rng.next_f64() * counts_to[&vertex];
+                    (steps.iter())
+                        .find(|(_, next)| {
+                            remaining -= counts_to[next];
+                            remaining < 0.
+                        })
+                        .unwrap_or(steps.last().unwrap())
+                }
+                PathTieBreaking::Consensus => {
+                    steps
+                        .iter()
+                        .fold(None, |best: Option<(&(EdgeIndex, VertexIndex), f64)>, step| {
+                            let score = fraction(&vertex, &step.1) + best_scores[&step.1];
+                            match best {
+                                Some((_, best_score)) if best_score >= score => best,
+                                _ => Some((step, score)),
+                            }
+                        })
+                        .unwrap()
+                        .0
+                }
+            };
+            edges.push(edge_index);
+            vertex = next;
+        }
+        edges
+    }
+
+    /// the minimum-weight paths with the fewest edges from `vertex_1` to `vertex_2`, as a directed acyclic graph: each
+    /// vertex on these paths except `vertex_2` maps to its hops to `vertex_2` and its next steps `(edge_index, vertex)`
+    /// sorted by edge index
+    #[allow(clippy::unnecessary_cast)]
+    fn minimum_path_successors(
+        &self,
+        vertex_1: VertexIndex,
+        vertex_2: VertexIndex,
+    ) -> BTreeMap<VertexIndex, (usize, Vec<(EdgeIndex, VertexIndex)>)> {
+        // Dijkstra's algorithm from `vertex_2` on (weight, hops), which stops once `vertex_1` is settled; the vertices
+        // on the paths have strictly smaller keys than `vertex_1` and thus are all settled
+        let mut keys = BTreeMap::from([(vertex_2, (0, 0))]);
+        let mut settled = BTreeSet::new();
+        let mut heap = BinaryHeap::from([Reverse(((0, 0), vertex_2))]);
+        while let Some(Reverse((key, target))) = heap.pop() {
+            if !settled.insert(target) {
+                continue;
+            }
+            if target == vertex_1 {
+                break;
+            }
+            for (&neighbor, &weight) in self.complete_graph.vertices[target as usize].edges.iter() {
+                let neighbor_key: (Weight, usize) = (key.0 + weight, key.1 + 1);
+                if keys.get(&neighbor).map_or(true, |&existing| neighbor_key < existing) {
+                    keys.insert(neighbor, neighbor_key);
+                    heap.push(Reverse((neighbor_key, neighbor)));
+                }
+            }
+        }
+        assert!(settled.contains(&vertex_1), "no path between {} and {}", vertex_1, vertex_2);
+        let mut successors = BTreeMap::new();
+        let mut stack = vec![vertex_1];
+        while let Some(vertex) = stack.pop() {
+            if vertex == vertex_2 || successors.contains_key(&vertex) {
+                continue;
+            }
+            let (weight, hops) = keys[&vertex];
+            let mut steps = vec![];
+            for (&next, &edge_weight) in self.complete_graph.vertices[vertex as usize].edges.iter() {
+                if settled.contains(&next) && keys[&next] == (weight - edge_weight, hops - 1) {
+                    let id = if vertex < next { (vertex, next) } else { (next, vertex) };
+                    steps.push((self.vertex_pair_edges[&id], next));
+                    stack.push(next);
+                }
+            }
+            steps.sort_unstable();
+            successors.insert(vertex, (hops, steps));
+        }
+        successors
+    }
+
+    fn edges_along(&self, vertex_1: VertexIndex, path: &[(VertexIndex, Weight)]) -> Vec<EdgeIndex> {
         let mut a = vertex_1;
+        let mut edges = Vec::with_capacity(path.len());
         for (vertex, _) in path.iter() {
             let b = *vertex;
             let id = if a < b { (a, b) } else { (b, a) };
-            let edge_index = *self.vertex_pair_edges.get(&id).expect("edge should exist");
-            if self.subgraph.contains(&edge_index) {
-                self.subgraph.remove(&edge_index);
-            } else {
-                self.subgraph.insert(edge_index);
-            }
+            edges.push(*self.vertex_pair_edges.get(&id).expect("edge should exist"));
             a = b;
         }
+        edges
+    }
+
+    fn toggle_edge(&mut self, edge_index: EdgeIndex) {
+        if !self.subgraph.remove(&edge_index) {
+            self.subgraph.insert(edge_index);
+        }
     }
 
     /// get the total weight of the subgraph
@@ -593,9 +1042,199 @@ impl SubGraphBuilder {
         weight
     }
 
+    /// the non-virtual vertices incident to an odd number of edges in the subgraph, i.e. the syndrome it generates
+    #[allow(clippy::unnecessary_cast)]
+    pub fn subgraph_boundary(&self) -> BTreeSet<VertexIndex> {
+        let mut boundary = BTreeSet::new();
+        for &edge_index in self.subgraph.iter() {
+            let (vertex_1, vertex_2, _) = self.complete_graph.weighted_edges[edge_index as usize];
+            for vertex_index in [vertex_1, vertex_2] {
+                if !self.is_virtual[vertex_index as usize] && !boundary.remove(&vertex_index) {
+                    boundary.insert(vertex_index);
+                }
+            }
+        }
+        boundary
+    }
+
+    /// compress a subgraph into straight chains, see [`CompressedSubgraph`]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn compress_subgraph(&self, subgraph: &[EdgeIndex]) -> CompressedSubgraph {
+        let mut compressed = CompressedSubgraph::default();
+        // (stride, lower vertex) of the edges that are uniquely identified by their vertices
+        let mut steps = BTreeSet::new();
+        for &edge_index in subgraph.iter() {
+            let (vertex_1, vertex_2, _) = self.complete_graph.weighted_edges[edge_index as usize];
+            let id = if vertex_1 < vertex_2 {
+                (vertex_1, vertex_2)
+            } else {
+                (vertex_2, vertex_1)
+            };
+            if id.0 != id.1 && self.vertex_pair_edges.get(&id) == Some(&edge_index) {
+                steps.insert((id.1 - id.0, id.0));
+            } else {
+                compressed.edges.push(edge_index);
+            }
+        }
+        for &(stride, start) in steps.iter() {
+            if start >= stride && steps.contains(&(stride, start - stride)) {
+                continue; // not the beginning of a chain
+            }
+            let mut end = start + stride;
+            while steps.contains(&(stride, end)) {
+                end += stride;
+            }
+            compressed.segments.push(SubgraphSegment { start, end, stride });
+        }
+        compressed
+    }
+
+    /// the compressed form of the current subgraph
+    pub fn get_compressed_subgraph(&self) -> CompressedSubgraph {
+        self.compress_subgraph(&self.subgraph.iter().cloned().collect::<Vec<_>>())
+    }
+
+    /// recover the exact edges of a compressed subgraph, sorted by edge index
+    pub fn expand_subgraph(&self, compressed: &CompressedSubgraph) -> Vec<EdgeIndex> {
+        let mut subgraph: BTreeSet<EdgeIndex> = compressed.edges.iter().cloned().collect();
+        for segment in compressed.segments.iter() {
+            let mut vertex = segment.start;
+            while vertex < segment.end {
+                let edge_index = *self
+                    .vertex_pair_edges
+                    .get(&(vertex, vertex + segment.stride))
+                    .expect("segment must follow existing edges");
+                subgraph.insert(edge_index);
+                vertex += segment.stride;
+            }
+        }
+        subgraph.into_iter().collect()
+    }
+
+    /// verify that the subgraph generates exactly the loaded defect vertices (modulo virtual vertices);
+    /// returns the vertices that differ, or `Ok` if no defect vertices are loaded
+    #[allow(clippy::unnecessary_cast)]
+    pub fn verify_parity(&self) -> Result<(), Vec<VertexIndex>> {
+        let defect_vertices = match self.defect_vertices.as_ref() {
+            Some(defect_vertices) => defect_vertices,
+            None => return Ok(()),
+        };
+        let mut mismatch = self.subgraph_boundary();
+        for &vertex_index in defect_vertices.iter() {
+            if !self.is_virtual[vertex_index as usize] && !mismatch.remove(&vertex_index) {
+                mismatch.insert(vertex_index);
+            }
+        }
+        if mismatch.is_empty() {
+            Ok(())
+        } else {
+            Err(mismatch.into_iter().collect())
+        }
+    }
+
+    /// enumerate up to `max_count` corrections of the defect vertices in the order of increasing weight, each at most
+    /// `max_weight_gap` heavier than the minimum-weight one which comes first; it respects the loaded erasures and
+    /// dynamic weights, and doesn't modify the current subgraph. It solves a MWPM problem for each candidate, so it's
+    /// much slower than decoding, see [`crate::degeneracy`]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn alternative_corrections(
+        &self,
+        defect_vertices: &[VertexIndex],
+        max_count: usize,
+        max_weight_gap: Weight,
+    ) -> Vec<AlternativeCorrection> {
+        let defect_num = defect_vertices.len();
+        let path_counts: Vec<_> = defect_vertices
+            .par_iter()
+            .map(|&vertex_index| self.complete_graph.minimum_weight_path_counts(vertex_index))
+            .collect();
+        let mut problem = DefectMatchingProblem {
+            defect_num,
+            pair_weights: BTreeMap::new(),
+            boundary_weights: vec![None; defect_num],
+        };
+        // the nearest virtual vertex of each defect and the number of minimum-weight paths to any of them
+        let mut boundaries: Vec<Option<(VertexIndex, f64)>> = vec![None; defect_num];
+        for (i, counts) in path_counts.iter().enumerate() {
+            for (j, peer_index) in defect_vertices.iter().enumerate().skip(i + 1) {
+                if let Some(&(weight, _)) = counts.get(peer_index) {
+                    problem.pair_weights.insert((i, j), weight);
+                }
+            }
+            for (&vertex_index, &(weight, count)) in counts.iter() {
+                if !self.is_virtual[vertex_index as usize] {
+                    continue;
+                }
+                match (problem.boundary_weights[i], boundaries[i].as_mut()) {
+                    (Some(boundary_weight), Some((_, boundary_count))) if boundary_weight == weight => {
+                        *boundary_count += count;
+                    }
+                    (Some(boundary_weight), _) if boundary_weight < weight => {}
+                    _ => {
+                        problem.boundary_weights[i] = Some(weight);
+                        boundaries[i] = Some((vertex_index, count));
+                    }
+                }
+            }
+        }
+        problem
+            .k_best_matchings(max_count, max_weight_gap)
+            .into_iter()
+            .map(|matching| {
+                let mut subgraph = BTreeSet::new();
+                let mut matched_pairs = vec![];
+                let mut degeneracy = 1.;
+                for (i, j) in matching.pairs() {
+                    let vertex_1 = defect_vertices[i];
+                    let (vertex_2, count) = if i == j {
+                        boundaries[i].unwrap()
+                    } else {
+                        (defect_vertices[j], path_counts[i][&defect_vertices[j]].1)
+                    };
+                    matched_pairs.push((vertex_1, vertex_2));
+                    degeneracy *= count;
+                    for edge_index in self.path_edges(vertex_1, vertex_2) {
+                        if !subgraph.remove(&edge_index) {
+                            subgraph.insert(edge_index);
+                        }
+                    }
+                }
+                AlternativeCorrection {
+                    matched_pairs,
+                    subgraph: subgraph.into_iter().collect(),
+                    weight: matching.weight,
+                    degeneracy,
+                }
+            })
+            .collect()
+    }
+
     /// get subgraph as a vec
+    #[allow(clippy::unnecessary_cast)]
     pub fn get_subgraph(&self) -> Vec<EdgeIndex> {
-        self.subgraph.iter().copied().collect()
+        if self.enable_parity_check {
+            if let Err(mismatch) = self.verify_parity() {
+                panic!(
+                    "corrupted matching: the subgraph does not generate the syndrome at vertices {:?}",
+                    mismatch
+                );
+            }
+        }
+        self.subgraph
+            .iter()
+            .copied()
+            .filter(|&edge_index| (edge_index as usize) < self.edge_num)
+            .collect()
+    }
+
+    /// the logical observables flipped by the current subgraph; requires the observables to be annotated in the
+    /// initializer, see [`SolverInitializer::with_observables`]
+    pub fn get_predicted_observables(&self) -> Vec<bool> {
+        let observables = self
+            .observables
+            .as_ref()
+            .expect("the initializer doesn't carry logical observable annotations");
+        observables.predict(&self.get_subgraph())
     }
 }
 
@@ -625,3 +1264,254 @@ pub(crate) fn register(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<PerfectMatching>()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::example_partition::*;
+    use super::super::mwpm_solver::*;
+    use super::*;
+
+    #[test]
+    fn primal_module_subgraph_parity_check_1() {
+        // cargo test primal_module_subgraph_parity_check_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        solver.set_parity_check(true);
+        let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            let subgraph = solver.subgraph();
+            subgraph_builder.clear();
+            subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
+            subgraph_builder.load_subgraph(&subgraph);
+            assert_eq!(subgraph_builder.verify_parity(), Ok(()));
+            // flipping any single edge corrupts the matching
+            let mut corrupted = subgraph.clone();
+            corrupted.push(
+                (0..initializer.weighted_edges.len() as EdgeIndex)
+                    .find(|edge_index| !subgraph.contains(edge_index))
+                    .unwrap(),
+            );
+            subgraph_builder.load_subgraph(&corrupted);
+            assert!(subgraph_builder.verify_parity().is_err());
+            solver.clear();
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn primal_module_predicted_observables_1() {
+        // cargo test primal_module_predicted_observables_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let logical_edges = super::super::analysis::left_boundary_edges(&code);
+        // observable 0 is the logical observable, observable 1 is the parity of the number of edges
+        let edge_masks = logical_edges.iter().map(|&is_logical| is_logical as u64 | 0b10).collect();
+        let initializer = code.get_initializer().with_observables(ObservableMasks::new(2, edge_masks));
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            let subgraph = solver.subgraph();
+            let logical_flips = subgraph
+                .iter()
+                .filter(|&&edge_index| logical_edges[edge_index as usize])
+                .count();
+            let expected = vec![logical_flips % 2 == 1, subgraph.len() % 2 == 1];
+            assert_eq!(solver.perfect_matching().predicted_observables(&initializer), expected);
+            solver.clear();
+        }
+    }
+
+    #[test]
+    fn primal_module_path_tie_breaking_1() {
+        // cargo test primal_module_path_tie_breaking_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+        subgraph_builder.enable_parity_check = true;
+        let policies = [
+            PathTieBreaking::Dijkstra,
+            PathTieBreaking::Lexicographic,
+            PathTieBreaking::Randomized { seed: 0 },
+            PathTieBreaking::Randomized { seed: 1 },
+            PathTieBreaking::Consensus,
+        ];
+        // the pair of non-virtual vertices with the most minimum-weight paths
+        let is_virtual = |vertex_index: &VertexIndex| initializer.virtual_vertices.contains(vertex_index);
+        let vertex_1 = (0..initializer.vertex_num)
+            .find(|vertex_index| !is_virtual(vertex_index))
+            .unwrap();
+        let counts = subgraph_builder.complete_graph.minimum_weight_path_counts(vertex_1);
+        let (&vertex_2, &(weight, count)) = (counts.iter())
+            .filter(|(vertex_index, _)| !is_virtual(vertex_index))
+            .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1))
+            .unwrap();
+        assert!(count > 2.);
+        let mut paths = vec![];
+        for tie_breaking in policies {
+            subgraph_builder.tie_breaking = tie_breaking;
+            let path = subgraph_builder.path_edges(vertex_1, vertex_2);
+            assert_eq!(path, subgraph_builder.path_edges(vertex_1, vertex_2), "must be deterministic");
+            subgraph_builder.load_subgraph(&path);
+            assert_eq!(subgraph_builder.subgraph_boundary(), BTreeSet::from([vertex_1, vertex_2]));
+            assert_eq!(subgraph_builder.total_weight(), weight);
+            paths.push(path);
+        }
+        println!("paths: {paths:?}");
+        assert_ne!(paths[2], paths[3], "different seeds should give different paths");
+        // the lexicographic path always takes the edge with the smallest index
+        let mut edges = paths[1].clone();
+        edges.sort();
+        assert_eq!(paths[1][0], edges[0]);
+        // the correction is still valid with every policy
+        let mut solver = SolverSerial::new(&initializer);
+        for tie_breaking in policies {
+            subgraph_builder.tie_breaking = tie_breaking;
+            for seed in 0..20 {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                solver.solve(&syndrome_pattern);
+                subgraph_builder.clear();
+                subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
+                subgraph_builder.load_perfect_matching(&solver.perfect_matching());
+                subgraph_builder.get_subgraph(); // checks the parity
+                let weight = subgraph_builder.total_weight();
+                subgraph_builder.load_subgraph(&solver.subgraph());
+                assert_eq!(weight, subgraph_builder.total_weight());
+                solver.clear();
+            }
+        }
+    }
+
+    #[test]
+    fn primal_module_boundary_pairs_1() {
+        // cargo test primal_module_boundary_pairs_1 -- --nocapture
+        let d = 7;
+        let code = CodeCapacityPlanarCode::new(d, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut partition = CodeCapacityPlanarCodeVerticalPartitionHalf::new(d, 4);
+        let partition_info = partition.build_partition(&code).info();
+        let position = |row: VertexIndex, column: VertexIndex| row * (d + 1) + column;
+        // one pair across the interface (row 3) and one pair inside the top partition
+        let syndrome_pattern =
+            SyndromePattern::new_vertices(vec![position(0, 3), position(0, 4), position(2, 3), position(4, 3)]);
+        let mut solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+        solver.solve(&syndrome_pattern);
+        assert_eq!(
+            solver.boundary_pairs(),
+            vec![BoundaryPair {
+                unit_index: 2,
+                vertex_1: position(2, 3),
+                vertex_2: position(4, 3),
+                is_virtual: false,
+            }]
+        );
+        let mut solver = SolverDualParallel::new(&initializer, &partition_info, json!({}));
+        solver.solve(&syndrome_pattern);
+        let boundary_pairs = solver.boundary_pairs();
+        assert_eq!(boundary_pairs.len(), 1);
+        assert_eq!(boundary_pairs[0].unit_index, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "corrupted matching")]
+    fn primal_module_subgraph_parity_check_2() {
+        // cargo test primal_module_subgraph_parity_check_2 -- --nocapture
+        let code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+        subgraph_builder.enable_parity_check = true;
+        subgraph_builder.load_defect_vertices(&[19, 20]);
+        subgraph_builder.load_subgraph(&[]);
+        subgraph_builder.get_subgraph();
+    }
+
+    #[test]
+    fn primal_module_matched_vertices_1() {
+        // cargo test primal_module_matched_vertices_1 -- --nocapture
+        let code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        solver.solve(&SyndromePattern::new_vertices(vec![3, 4, 25]));
+        let mut matched_vertices = solver.perfect_matching().matched_vertices();
+        assert_eq!(matched_vertices.len(), 2);
+        let virtual_matching = matched_vertices.pop().unwrap();
+        assert_eq!(virtual_matching.0, 25);
+        assert!(initializer.virtual_vertices.contains(&virtual_matching.1));
+        let (vertex_1, vertex_2) = matched_vertices[0];
+        assert_eq!((vertex_1.min(vertex_2), vertex_1.max(vertex_2)), (3, 4));
+    }
+
+    /// the paths computed in parallel from the shared complete graph give the same subgraph as adding the pairs one by one
+    #[test]
+    fn primal_module_parallel_subgraph_builder_1() {
+        // cargo test primal_module_parallel_subgraph_builder_1 -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(11, 11, 0.03, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+        let mut sequential_builder = SubGraphBuilder::new(&initializer);
+        for seed in 0..20 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            let perfect_matching = solver.perfect_matching();
+            let matched_vertices = perfect_matching.matched_vertices();
+            assert!(matched_vertices.len() > PARALLEL_SUBGRAPH_MIN_PAIRS);
+            subgraph_builder.clear();
+            subgraph_builder.load_perfect_matching(&perfect_matching);
+            sequential_builder.clear();
+            for &(vertex_1, vertex_2) in matched_vertices.iter() {
+                sequential_builder.add_matching(vertex_1, vertex_2);
+            }
+            assert_eq!(subgraph_builder.subgraph, sequential_builder.subgraph);
+            subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
+            assert_eq!(subgraph_builder.verify_parity(), Ok(()));
+            solver.clear();
+        }
+    }
+
+    /// a long time-like chain is compressed into a single segment, and the compression is lossless
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn primal_module_compressed_subgraph_1() {
+        // cargo test primal_module_compressed_subgraph_1 -- --nocapture
+        let (d, noisy_measurements) = (21, 15);
+        let mut code = PhenomenologicalPlanarCode::new(d, noisy_measurements, 0.002, 500);
+        let initializer = code.get_initializer();
+        let round_vertex_num = d * (d + 1);
+        let defect_vertex = 10 * (d + 1) + 10; // the center of the first round, far from the boundaries
+        let mut solver = SolverSerial::new(&initializer);
+        let subgraph_builder = SubGraphBuilder::new(&initializer);
+        solver.solve(&SyndromePattern::new_vertices(vec![
+            defect_vertex,
+            defect_vertex + noisy_measurements * round_vertex_num,
+        ]));
+        let subgraph = solver.subgraph();
+        assert_eq!(subgraph.len(), noisy_measurements as usize);
+        let compressed = subgraph_builder.compress_subgraph(&subgraph);
+        assert_eq!(
+            compressed.segments,
+            vec![SubgraphSegment {
+                start: defect_vertex,
+                end: defect_vertex + noisy_measurements * round_vertex_num,
+                stride: round_vertex_num,
+            }]
+        );
+        assert!(compressed.edges.is_empty());
+        assert_eq!(subgraph_builder.expand_subgraph(&compressed), subgraph);
+        solver.clear();
+        // random errors
+        for seed in 0..100 {
+            solver.solve(&code.generate_random_errors(seed));
+            let mut subgraph = solver.subgraph();
+            subgraph.sort();
+            let compressed = subgraph_builder.compress_subgraph(&subgraph);
+            assert_eq!(compressed.edge_num(), subgraph.len());
+            assert!(compressed.segments.len() + compressed.edges.len() <= subgraph.len());
+            assert_eq!(subgraph_builder.expand_subgraph(&compressed), subgraph);
+            solver.clear();
+        }
+    }
+}
@@ -13,6 +13,7 @@ use super::util::*;
 use super::visualize::*;
 use crate::rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::DerefMut;
 use std::sync::{Arc, Condvar, Mutex};
 use std::time::{Duration, Instant};
@@ -28,6 +29,8 @@ pub struct PrimalModuleParallel {
     pub thread_pool: Arc<rayon::ThreadPool>,
     /// the time of calling [`PrimalModuleParallel::parallel_solve_step_callback`] method
     pub last_solve_start_time: ArcRwLock<Instant>,
+    /// the budget of the current solve, see [`PrimalModuleParallel::parallel_solve_budget`]
+    pub solve_budget: Option<SolveBudget>,
 }
 
 pub struct PrimalModuleParallelUnit {
@@ -47,6 +50,10 @@ pub struct PrimalModuleParallelUnit {
     pub parent: Option<PrimalModuleParallelUnitWeak>,
     /// record the time of events
     pub event_time: Option<PrimalModuleParallelUnitEventTime>,
+    /// the cost of fusing the children estimated right before the fusion, for profiling purposes
+    pub fuse_cost: Option<FuseCostEstimate>,
+    /// whether the solve budget was exhausted when solving this unit
+    pub budget_truncated: bool,
     /// streaming decode mocker, if exists, base partition will wait until specified time and then start decoding
     pub streaming_decode_mocker: Option<StreamingDecodeMocker>,
 }
@@ -94,6 +101,37 @@ impl PrimalModuleParallelUnitEventTime {
     }
 }
 
+/// one bar of the Gantt chart of a parallel solve, see [`PrimalModuleParallel::generate_gantt_chart`];
+/// all the times are in seconds relative to the start of the solve
+#[derive(Debug, Clone, Serialize)]
+pub struct PrimalModuleParallelUnitTimeline {
+    /// the unit index
+    pub unit_index: usize,
+    /// human-readable name of the unit
+    pub name: String,
+    /// fusion level: 0 for the partitioned units, and one above the higher child for the fusion units
+    pub level: usize,
+    /// thread index
+    pub thread_index: usize,
+    /// the unit is ready to execute: for a fusion unit this is when both children end
+    pub ready: f64,
+    /// unit starts executing, the interval from `ready` to `start` is spent waiting for a thread
+    pub start: f64,
+    /// unit ends executing, the interval from `start` to `end` is busy
+    pub end: f64,
+}
+
+/// the work of fusing the children of a unit, estimated from the solved children without fusing them, see
+/// [`PrimalModuleParallelUnit::estimate_fuse_cost`]; a scheduler may run the cheap fusions first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FuseCostEstimate {
+    /// the dual nodes of the children frozen by a temporary match to a virtual vertex, all of them are checked at fusion
+    pub frozen_nodes: usize,
+    /// the interface vertices owned by the fusion unit that freeze at least one node: they become normal vertices at
+    /// fusion and the nodes touching them grow again, see [`fusion_adjustments`]
+    pub interface_obstacles: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct PrimalModuleParallelConfig {
@@ -119,6 +157,19 @@ pub struct PrimalModuleParallelConfig {
     /// max tree size for the serial modules, for faster speed at the cost of less accuracy
     #[serde(default = "primal_module_parallel_default_configs::max_tree_size")]
     pub max_tree_size: usize,
+    /// max number of dual nodes created in each unit, to empirically determine the memory size of hardware units
+    #[serde(default = "primal_module_parallel_default_configs::max_dual_node_count")]
+    pub max_dual_node_count: usize,
+    /// max nesting depth of blossoms created in each unit
+    #[serde(default = "primal_module_parallel_default_configs::max_blossom_depth")]
+    pub max_blossom_depth: usize,
+    /// when fusing, directly match the pairs of dual nodes that were frozen at the same interface vertex: they are already
+    /// tight with each other, so resuming their growth would only rediscover the conflict in the first post-fuse iteration
+    #[serde(default = "primal_module_parallel_default_configs::interface_prematch")]
+    pub interface_prematch: bool,
+    /// report the end-to-end latency of a distributed deployment with this network in the profiler report, see
+    /// [`PrimalModuleParallel::simulate_network_latency`]
+    pub network_simulation: Option<NetworkLatencyModel>,
 }
 
 impl Default for PrimalModuleParallelConfig {
@@ -127,6 +178,35 @@ impl Default for PrimalModuleParallelConfig {
     }
 }
 
+/// the classical communication between the machines of a distributed decoder, to evaluate a fusion topology before
+/// the networked backend exists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkLatencyModel {
+    /// fixed latency of every message in seconds
+    #[serde(default = "primal_module_parallel_default_configs::network_latency")]
+    pub latency: f64,
+    /// bandwidth of every link in bytes per second
+    #[serde(default = "primal_module_parallel_default_configs::network_bandwidth")]
+    pub bandwidth: f64,
+    /// the size of the state of a single interface vertex in a message
+    #[serde(default = "primal_module_parallel_default_configs::network_bytes_per_vertex")]
+    pub bytes_per_vertex: usize,
+}
+
+impl Default for NetworkLatencyModel {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+impl NetworkLatencyModel {
+    /// the time between sending the first byte and receiving the last byte of a message
+    pub fn message_delay(&self, bytes: usize) -> f64 {
+        self.latency + bytes as f64 / self.bandwidth
+    }
+}
+
 pub mod primal_module_parallel_default_configs {
     pub fn thread_pool_size() -> usize {
         0
@@ -150,6 +230,24 @@ pub mod primal_module_parallel_default_configs {
     pub fn max_tree_size() -> usize {
         usize::MAX
     } // by default do not limit tree size
+    pub fn max_dual_node_count() -> usize {
+        usize::MAX
+    } // by default do not limit memory
+    pub fn max_blossom_depth() -> usize {
+        usize::MAX
+    }
+    pub fn interface_prematch() -> bool {
+        true
+    }
+    pub fn network_latency() -> f64 {
+        1e-6
+    } // one-way latency within a rack
+    pub fn network_bandwidth() -> f64 {
+        1.25e9
+    } // 10 Gbps
+    pub fn network_bytes_per_vertex() -> usize {
+        8
+    } // the index of the dual node that propagates to the vertex
 }
 
 pub struct StreamingDecodeMocker {
@@ -188,7 +286,12 @@ impl PrimalModuleParallel {
                 .map(|unit_index| {
                     // println!("unit_index: {unit_index}");
                     let primal_module = PrimalModuleSerialPtr::new_empty(initializer);
-                    primal_module.write().max_tree_size = config.max_tree_size;
+                    let mut module = primal_module.write();
+                    module.unit_index = unit_index;
+                    module.max_tree_size = config.max_tree_size;
+                    module.max_dual_node_count = config.max_dual_node_count;
+                    module.max_blossom_depth = config.max_blossom_depth;
+                    drop(module);
                     PrimalModuleParallelUnitPtr::new_wrapper(primal_module, unit_index, Arc::clone(&partition_info))
                 })
                 .collect_into_vec(&mut units);
@@ -220,6 +323,7 @@ impl PrimalModuleParallel {
             partition_info,
             thread_pool: Arc::new(thread_pool),
             last_solve_start_time: ArcRwLock::new_value(Instant::now()),
+            solve_budget: None,
         }
     }
 }
@@ -277,13 +381,167 @@ impl PrimalModuleImpl for PrimalModuleParallel {
 
     fn generate_profiler_report(&self) -> serde_json::Value {
         let event_time_vec: Vec<_> = self.units.iter().map(|ptr| ptr.read_recursive().event_time.clone()).collect();
+        let unit_names: Vec<_> = self.partition_info.units.iter().map(|unit| unit.name.clone()).collect();
+        let memory_bound_errors: Vec<_> = self.memory_bound_errors().iter().map(|error| error.to_string()).collect();
+        let fuse_cost_vec: Vec<_> = self.units.iter().map(|ptr| ptr.read_recursive().fuse_cost).collect();
+        let unit_phases: Vec<_> = self
+            .units
+            .iter()
+            .map(|ptr| ptr.read_recursive().serial_module.read_recursive().phase_profiler.to_json())
+            .collect();
         json!({
             "event_time_vec": event_time_vec,
+            "fuse_cost_vec": fuse_cost_vec,
+            "unit_phases": unit_phases,
+            "unit_names": unit_names,
+            "memory_bound_errors": memory_bound_errors,
+            "gantt": self.generate_gantt_chart(),
+            "network_simulation": self.config.network_simulation.as_ref().map(|network| self.simulate_network_latency(network)),
         })
     }
 }
 
 impl PrimalModuleParallel {
+    /// the busy and wait intervals of every unit executed in the last solve, with a summary per fusion level;
+    /// a level whose `span` is close to its `busy` time runs its units one after another and serializes the solve
+    pub fn generate_gantt_chart(&self) -> serde_json::Value {
+        let timelines: Vec<_> = self.unit_timelines().into_iter().flatten().collect();
+        let level_num = timelines.iter().map(|timeline| timeline.level + 1).max().unwrap_or(0);
+        let level_summaries: Vec<_> = (0..level_num)
+            .map(|level| {
+                let level_timelines: Vec<_> = timelines.iter().filter(|timeline| timeline.level == level).collect();
+                let start = level_timelines
+                    .iter()
+                    .map(|timeline| timeline.start)
+                    .fold(f64::INFINITY, f64::min);
+                let end = level_timelines.iter().map(|timeline| timeline.end).fold(0., f64::max);
+                json!({
+                    "level": level,
+                    "unit_count": level_timelines.len(),
+                    "busy": level_timelines.iter().map(|timeline| timeline.end - timeline.start).sum::<f64>(),
+                    "wait": level_timelines.iter().map(|timeline| timeline.start - timeline.ready).sum::<f64>(),
+                    "start": start,
+                    "end": end,
+                    "span": end - start,
+                })
+            })
+            .collect();
+        json!({
+            "units": timelines,
+            "levels": level_summaries,
+            "total": timelines.iter().map(|timeline| timeline.end).fold(0., f64::max),
+        })
+    }
+
+    /// replay the last solve as if every unit ran on its own machine, charging each child-to-parent message with the
+    /// latency and bandwidth of the given network; a fusion unit becomes ready only after the messages of both children
+    /// arrive, and each unit keeps its measured busy time. The message of a child carries the interface vertices of the
+    /// fusion unit, which is the state the parent needs to resume the dual variables across the interface
+    pub fn simulate_network_latency(&self, network: &NetworkLatencyModel) -> serde_json::Value {
+        let timelines = self.unit_timelines();
+        let mut simulated_end: Vec<f64> = vec![0.; timelines.len()];
+        let mut simulated_units = vec![];
+        let mut message_count = 0;
+        let mut message_bytes = 0;
+        for (unit_index, timeline) in timelines.iter().enumerate() {
+            let timeline = match timeline {
+                Some(timeline) => timeline,
+                None => continue, // the unit was not executed in the last solve
+            };
+            let unit_info = &self.partition_info.units[unit_index];
+            let mut ready = self.units[unit_index]
+                .read_recursive()
+                .streaming_decode_mocker
+                .as_ref()
+                .map_or(0., |mocker| mocker.bias.as_secs_f64());
+            let mut unit_message_bytes = 0;
+            if let Some((left_index, right_index)) = unit_info.children {
+                unit_message_bytes = unit_info.owning_range.len() * network.bytes_per_vertex;
+                for child_index in [left_index, right_index] {
+                    if timelines[child_index].is_some() {
+                        ready = ready.max(simulated_end[child_index] + network.message_delay(unit_message_bytes));
+                        message_count += 1;
+                        message_bytes += unit_message_bytes;
+                    }
+                }
+            }
+            simulated_end[unit_index] = ready + (timeline.end - timeline.start);
+            simulated_units.push(json!({
+                "unit_index": unit_index,
+                "name": timeline.name,
+                "ready": ready,
+                "end": simulated_end[unit_index],
+                "message_bytes": unit_message_bytes,
+            }));
+        }
+        json!({
+            "network": network,
+            "units": simulated_units,
+            "message_count": message_count,
+            "message_bytes": message_bytes,
+            "total": simulated_end.iter().cloned().fold(0., f64::max),
+            "measured_total": timelines.iter().flatten().map(|timeline| timeline.end).fold(0., f64::max),
+        })
+    }
+
+    /// the timeline of every unit indexed by the unit index, or `None` if the unit was not executed in the last solve
+    fn unit_timelines(&self) -> Vec<Option<PrimalModuleParallelUnitTimeline>> {
+        let mut levels = vec![0; self.partition_info.units.len()];
+        let mut timelines: Vec<Option<PrimalModuleParallelUnitTimeline>> = Vec::with_capacity(self.units.len());
+        for (unit_index, unit_ptr) in self.units.iter().enumerate() {
+            let unit = unit_ptr.read_recursive();
+            let unit_info = &self.partition_info.units[unit_index];
+            // units are ordered such that children always come before the parent
+            let mut ready = unit
+                .streaming_decode_mocker
+                .as_ref()
+                .map_or(0., |mocker| mocker.bias.as_secs_f64());
+            if let Some((left_index, right_index)) = unit_info.children {
+                levels[unit_index] = levels[left_index].max(levels[right_index]) + 1;
+                for child_index in [left_index, right_index] {
+                    if let Some(child) = timelines[child_index].as_ref() {
+                        ready = ready.max(child.end);
+                    }
+                }
+            }
+            timelines.push(unit.event_time.as_ref().map(|event_time| PrimalModuleParallelUnitTimeline {
+                unit_index,
+                name: unit_info.name.clone(),
+                level: levels[unit_index],
+                thread_index: event_time.thread_index,
+                ready,
+                start: event_time.start,
+                end: event_time.end,
+            }));
+        }
+        timelines
+    }
+
+    /// estimate the cost of fusing the children of a fusion unit, which must be called after both children are solved
+    /// and before the unit is solved, see [`PrimalModuleParallelUnit::estimate_fuse_cost`]
+    pub fn estimate_fuse_cost(&self, unit_index: usize) -> FuseCostEstimate {
+        self.units[unit_index].read_recursive().estimate_fuse_cost()
+    }
+
+    /// the configuration in effect, where the thread pool size is resolved to the actual number of threads
+    pub fn effective_config(&self) -> serde_json::Value {
+        let mut config = serde_json::to_value(&self.config).unwrap();
+        config["thread_pool_size"] = json!(self.thread_pool.current_num_threads());
+        config
+    }
+
+    /// the units that exceeded their memory bound in the last decoding round
+    pub fn memory_bound_errors(&self) -> Vec<PrimalMemoryBoundError> {
+        self.units
+            .iter()
+            .filter_map(|unit_ptr| {
+                let unit = unit_ptr.read_recursive();
+                let serial_module = unit.serial_module.read_recursive();
+                serial_module.memory_bound_error.clone()
+            })
+            .collect()
+    }
+
     pub fn parallel_solve<DualSerialModule: DualModuleImpl + Send + Sync>(
         &mut self,
         syndrome_pattern: &SyndromePattern,
@@ -292,6 +550,26 @@ impl PrimalModuleParallel {
         self.parallel_solve_step_callback(syndrome_pattern, parallel_dual_module, |_, _, _, _| {})
     }
 
+    /// solve within a budget, see [`SolveBudget`], where the duration is measured from the start of the whole solve and
+    /// the dual growth rounds are counted in each unit; returns whether any unit exhausted the budget
+    pub fn parallel_solve_budget<DualSerialModule: DualModuleImpl + Send + Sync>(
+        &mut self,
+        syndrome_pattern: &SyndromePattern,
+        parallel_dual_module: &DualModuleParallel<DualSerialModule>,
+        budget: SolveBudget,
+    ) -> bool {
+        self.solve_budget = Some(budget);
+        self.parallel_solve(syndrome_pattern, parallel_dual_module);
+        self.solve_budget = None;
+        let mut truncated = false;
+        for unit_ptr in self.units.iter() {
+            let unit = unit_ptr.read_recursive();
+            truncated |= unit.budget_truncated;
+            unit.serial_module.write().max_tree_size = self.config.max_tree_size;
+        }
+        truncated
+    }
+
     pub fn parallel_solve_visualizer<DualSerialModule: DualModuleImpl + Send + Sync + FusionVisualizer>(
         &mut self,
         syndrome_pattern: &SyndromePattern,
@@ -299,13 +577,14 @@ impl PrimalModuleParallel {
         visualizer: Option<&mut Visualizer>,
     ) {
         if let Some(visualizer) = visualizer {
+            let verbose = parallel_dual_module.config.verbose;
             self.parallel_solve_step_callback(
                 syndrome_pattern,
                 parallel_dual_module,
                 |interface_ptr, dual_module, primal_module, group_max_update_length| {
                     if let Some(group_max_update_length) = group_max_update_length {
-                        if cfg!(debug_assertions) {
-                            println!("group_max_update_length: {:?}", group_max_update_length);
+                        if verbose {
+                            diagnostic!("group_max_update_length: {:?}", group_max_update_length);
                         }
                         if let Some(length) = group_max_update_length.get_none_zero_growth() {
                             visualizer
@@ -352,6 +631,7 @@ impl PrimalModuleParallel {
             Option<&GroupMaxUpdateLength>,
         ),
     {
+        trace_span!("parallel_solve", defects = syndrome_pattern.defect_vertices.len());
         let thread_pool = Arc::clone(&self.thread_pool);
         *self.last_solve_start_time.write() = Instant::now();
         if self.config.prioritize_base_partition {
@@ -372,86 +652,105 @@ impl PrimalModuleParallel {
                         .map(|_| Arc::new((Mutex::new(false), Condvar::new(), Arc::new(AtomicUsize::new(0)))))
                         .collect()
                 };
-                thread_pool.scope_fifo(|s| {
-                    let issue_unit = |unit_index: usize| {
-                        let ready_vec = &ready_vec;
-                        let units = &self.units;
-                        let partition_info = &self.partition_info;
-                        let parallel_unit = &self;
-                        let parallel_dual_module = &parallel_dual_module;
-                        let streaming_decode_use_spin_lock = self.config.streaming_decode_use_spin_lock;
-                        s.spawn_fifo(move |_| {
-                            let ready_pair = ready_vec[unit_index].clone();
-                            let (ready, condvar, spin_ready) = &*ready_pair;
-                            if streaming_decode_use_spin_lock {
-                                let unit_ptr = units[unit_index].clone();
-                                if unit_index >= partition_info.config.partitions.len() {
-                                    // wait for children to complete
-                                    let fusion_index = unit_index - partition_info.config.partitions.len();
-                                    let (left_unit_index, right_unit_index) = partition_info.config.fusions[fusion_index];
-                                    for child_unit_index in [left_unit_index, right_unit_index] {
-                                        let child_ready_pair = ready_vec[child_unit_index].clone();
-                                        let (_, _, child_spin_ready) = &*child_ready_pair;
-                                        while child_spin_ready.load(Ordering::SeqCst) != 1 {
-                                            // hopefully this asserts false at the beginning
-                                            std::hint::spin_loop();
-                                            // println!("spin_loop");
-                                        }
-                                    }
-                                }
-                                unit_ptr.children_ready_solve::<DualSerialModule, F>(
-                                    parallel_unit,
-                                    PartitionedSyndromePattern::new(syndrome_pattern),
-                                    parallel_dual_module,
-                                    &mut None,
-                                );
-                                spin_ready.store(1, Ordering::SeqCst);
-                            } else {
-                                let mut is_ready = ready.lock().unwrap();
-                                let unit_ptr = units[unit_index].clone();
-                                if unit_index >= partition_info.config.partitions.len() {
-                                    // wait for children to complete
-                                    let fusion_index = unit_index - partition_info.config.partitions.len();
-                                    let (left_unit_index, right_unit_index) = partition_info.config.fusions[fusion_index];
-                                    for child_unit_index in [left_unit_index, right_unit_index] {
-                                        let child_ready_pair = ready_vec[child_unit_index].clone();
-                                        let (child_ready, child_condvar, _) = &*child_ready_pair;
-                                        let mut child_is_ready = child_ready.lock().unwrap();
-                                        while !*child_is_ready {
-                                            // hopefully this asserts false at the beginning
-                                            child_is_ready = child_condvar.wait(child_is_ready).unwrap();
-                                        }
-                                    }
+                let units = &self.units;
+                let partition_info = &self.partition_info;
+                let parallel_unit = &*self;
+                let streaming_decode_use_spin_lock = self.config.streaming_decode_use_spin_lock;
+                let solve_unit = |unit_index: usize| {
+                    let ready_pair = ready_vec[unit_index].clone();
+                    let (ready, condvar, spin_ready) = &*ready_pair;
+                    if streaming_decode_use_spin_lock {
+                        let unit_ptr = units[unit_index].clone();
+                        if unit_index >= partition_info.config.partitions.len() {
+                            // wait for children to complete
+                            let fusion_index = unit_index - partition_info.config.partitions.len();
+                            let (left_unit_index, right_unit_index) = partition_info.config.fusions[fusion_index];
+                            for child_unit_index in [left_unit_index, right_unit_index] {
+                                let child_ready_pair = ready_vec[child_unit_index].clone();
+                                let (_, _, child_spin_ready) = &*child_ready_pair;
+                                while child_spin_ready.load(Ordering::SeqCst) != 1 {
+                                    // hopefully this asserts false at the beginning
+                                    std::hint::spin_loop();
+                                    // println!("spin_loop");
                                 }
-                                unit_ptr.children_ready_solve::<DualSerialModule, F>(
-                                    parallel_unit,
-                                    PartitionedSyndromePattern::new(syndrome_pattern),
-                                    parallel_dual_module,
-                                    &mut None,
-                                );
-                                *is_ready = true;
-                                condvar.notify_one();
                             }
-                        })
-                    };
-                    if self.config.interleaving_base_fusion >= self.partition_info.config.fusions.len() {
-                        for unit_index in 0..self.partition_info.units.len() {
-                            issue_unit(unit_index);
                         }
+                        unit_ptr.children_ready_solve::<DualSerialModule, F>(
+                            parallel_unit,
+                            PartitionedSyndromePattern::new(syndrome_pattern),
+                            parallel_dual_module,
+                            &mut None,
+                        );
+                        spin_ready.store(1, Ordering::SeqCst);
                     } else {
-                        for unit_index in 0..self.partition_info.config.partitions.len() {
-                            if unit_index >= self.config.interleaving_base_fusion {
-                                let fusion_index = self.partition_info.config.partitions.len()
-                                    + (unit_index - self.config.interleaving_base_fusion);
-                                issue_unit(fusion_index);
+                        let mut is_ready = ready.lock().unwrap();
+                        let unit_ptr = units[unit_index].clone();
+                        if unit_index >= partition_info.config.partitions.len() {
+                            // wait for children to complete
+                            let fusion_index = unit_index - partition_info.config.partitions.len();
+                            let (left_unit_index, right_unit_index) = partition_info.config.fusions[fusion_index];
+                            for child_unit_index in [left_unit_index, right_unit_index] {
+                                let child_ready_pair = ready_vec[child_unit_index].clone();
+                                let (child_ready, child_condvar, _) = &*child_ready_pair;
+                                let mut child_is_ready = child_ready.lock().unwrap();
+                                while !*child_is_ready {
+                                    // hopefully this asserts false at the beginning
+                                    child_is_ready = child_condvar.wait(child_is_ready).unwrap();
+                                }
                             }
-                            issue_unit(unit_index);
                         }
-                        for bias_index in 1..self.config.interleaving_base_fusion {
-                            issue_unit(self.partition_info.units.len() - self.config.interleaving_base_fusion + bias_index);
+                        unit_ptr.children_ready_solve::<DualSerialModule, F>(
+                            parallel_unit,
+                            PartitionedSyndromePattern::new(syndrome_pattern),
+                            parallel_dual_module,
+                            &mut None,
+                        );
+                        *is_ready = true;
+                        condvar.notify_one();
+                    }
+                };
+                // the children are issued before their parent, so that a unit waiting for its children never blocks them
+                let mut issue_order = vec![];
+                if self.config.interleaving_base_fusion >= self.partition_info.config.fusions.len() {
+                    issue_order.extend(0..self.partition_info.units.len());
+                } else {
+                    for unit_index in 0..self.partition_info.config.partitions.len() {
+                        if unit_index >= self.config.interleaving_base_fusion {
+                            let fusion_index = self.partition_info.config.partitions.len()
+                                + (unit_index - self.config.interleaving_base_fusion);
+                            issue_order.push(fusion_index);
                         }
+                        issue_order.push(unit_index);
+                    }
+                    for bias_index in 1..self.config.interleaving_base_fusion {
+                        issue_order
+                            .push(self.partition_info.units.len() - self.config.interleaving_base_fusion + bias_index);
                     }
-                });
+                }
+                if parallel_dual_module.numa_pools.is_empty() {
+                    thread_pool.scope_fifo(|s| {
+                        for &unit_index in issue_order.iter() {
+                            s.spawn_fifo(move |_| solve_unit(unit_index));
+                        }
+                    });
+                } else {
+                    // each NUMA pool issues its own units in the same order from a plain thread: a worker of another pool
+                    // would run jobs of its own pool while waiting, including units that block waiting for their children
+                    let (solve_unit, issue_order) = (&solve_unit, &issue_order);
+                    std::thread::scope(|scope| {
+                        for (node_index, numa_pool) in parallel_dual_module.numa_pools.iter().enumerate() {
+                            scope.spawn(move || {
+                                numa_pool.scope_fifo(|s| {
+                                    for &unit_index in issue_order.iter() {
+                                        if parallel_dual_module.unit_numa_nodes[unit_index] == node_index {
+                                            s.spawn_fifo(move |_| solve_unit(unit_index));
+                                        }
+                                    }
+                                });
+                            });
+                        }
+                    });
+                }
             }
         } else {
             let last_unit_ptr = self.units.last().unwrap().clone();
@@ -497,7 +796,9 @@ impl PrimalModuleParallelUnitPtr {
         let is_active = partition_unit_info.children.is_none();
         let interface_ptr = DualModuleInterfacePtr::new_empty();
         interface_ptr.write().unit_index = unit_index;
-        Self::new_value(PrimalModuleParallelUnit {
+        #[cfg(feature = "lock_order_audit")]
+        crate::lock_order_audit::register(&interface_ptr, format!("interface {unit_index}"));
+        let unit_ptr = Self::new_value(PrimalModuleParallelUnit {
             unit_index,
             interface_ptr,
             partition_info,
@@ -506,13 +807,44 @@ impl PrimalModuleParallelUnitPtr {
             children: None, // to be filled later
             parent: None,   // to be filled later
             event_time: None,
+            fuse_cost: None,
+            budget_truncated: false,
             streaming_decode_mocker: None,
+        });
+        #[cfg(feature = "lock_order_audit")]
+        crate::lock_order_audit::register(&unit_ptr, format!("primal unit {unit_index}"));
+        unit_ptr
+    }
+
+    /// call this only if children is guaranteed to be ready and solved; the unit is solved on the NUMA node of its dual
+    /// module, see [`DualModuleParallel::install_on_unit`]
+    fn children_ready_solve<DualSerialModule: DualModuleImpl + Send + Sync, F: Send + Sync>(
+        &self,
+        primal_module_parallel: &PrimalModuleParallel,
+        partitioned_syndrome_pattern: PartitionedSyndromePattern,
+        parallel_dual_module: &DualModuleParallel<DualSerialModule>,
+        callback: &mut Option<&mut F>,
+    ) where
+        F: FnMut(
+            &DualModuleInterfacePtr,
+            &DualModuleParallelUnit<DualSerialModule>,
+            &PrimalModuleSerialPtr,
+            Option<&GroupMaxUpdateLength>,
+        ),
+    {
+        let unit_index = self.read_recursive().unit_index;
+        parallel_dual_module.install_on_unit(unit_index, || {
+            self.children_ready_solve_local(
+                primal_module_parallel,
+                partitioned_syndrome_pattern,
+                parallel_dual_module,
+                callback,
+            )
         })
     }
 
-    /// call this only if children is guaranteed to be ready and solved
     #[allow(clippy::unnecessary_cast)]
-    fn children_ready_solve<DualSerialModule: DualModuleImpl + Send + Sync, F: Send + Sync>(
+    fn children_ready_solve_local<DualSerialModule: DualModuleImpl + Send + Sync, F: Send + Sync>(
         &self,
         primal_module_parallel: &PrimalModuleParallel,
         partitioned_syndrome_pattern: PartitionedSyndromePattern,
@@ -540,6 +872,7 @@ impl PrimalModuleParallelUnitPtr {
                 }
             }
         }
+        trace_span!("unit_solve", unit_index = primal_unit.unit_index);
         let mut event_time = PrimalModuleParallelUnitEventTime::new();
         event_time.start = primal_module_parallel
             .last_solve_start_time
@@ -548,9 +881,13 @@ impl PrimalModuleParallelUnitPtr {
             .as_secs_f64();
         let dual_module_ptr = parallel_dual_module.get_unit(primal_unit.unit_index);
         let mut dual_unit = dual_module_ptr.write();
+        let mut budget_tracker = primal_module_parallel
+            .solve_budget
+            .map(|budget| SolveBudgetTracker::new(budget, *primal_module_parallel.last_solve_start_time.read_recursive()));
         let partition_unit_info = &primal_unit.partition_info.units[primal_unit.unit_index];
         let (owned_defect_range, _) = partitioned_syndrome_pattern.partition(partition_unit_info);
         let interface_ptr = primal_unit.interface_ptr.clone();
+        let (unit_index, partition_info) = (primal_unit.unit_index, Arc::clone(&primal_unit.partition_info));
         if let Some((left_child_weak, right_child_weak)) = primal_unit.children.as_ref() {
             {
                 // set children to inactive to avoid being solved twice
@@ -561,11 +898,15 @@ impl PrimalModuleParallelUnitPtr {
                     child.is_active = false;
                 }
             }
+            primal_unit.fuse_cost = Some(primal_unit.estimate_fuse_cost());
             primal_unit.fuse(&mut dual_unit);
             if let Some(callback) = callback.as_mut() {
                 // do callback before actually breaking the matched pairs, for ease of visualization
                 callback(&primal_unit.interface_ptr, &dual_unit, &primal_unit.serial_module, None);
             }
+            if primal_module_parallel.config.interface_prematch {
+                primal_unit.prematch_interface();
+            }
             primal_unit.break_matching_with_mirror(dual_unit.deref_mut());
             for defect_index in owned_defect_range.whole_defect_range.iter() {
                 let defect_vertex = partitioned_syndrome_pattern.syndrome_pattern.defect_vertices[defect_index as usize];
@@ -577,6 +918,16 @@ impl PrimalModuleParallelUnitPtr {
                 &interface_ptr,
                 dual_unit.deref_mut(),
                 |interface, dual_module, primal_module, group_max_update_length| {
+                    debug_assert!(PrimalModuleParallelUnit::is_obstacle_local(
+                        &partition_info,
+                        unit_index,
+                        group_max_update_length
+                    ));
+                    if let Some(tracker) = budget_tracker.as_mut() {
+                        if tracker.step(group_max_update_length) {
+                            primal_module.write().max_tree_size = 0;
+                        }
+                    }
                     if let Some(callback) = callback.as_mut() {
                         callback(interface, dual_module, primal_module, Some(group_max_update_length));
                     }
@@ -593,6 +944,16 @@ impl PrimalModuleParallelUnitPtr {
                 &syndrome_pattern,
                 dual_unit.deref_mut(),
                 |interface, dual_module, primal_module, group_max_update_length| {
+                    debug_assert!(PrimalModuleParallelUnit::is_obstacle_local(
+                        &partition_info,
+                        unit_index,
+                        group_max_update_length
+                    ));
+                    if let Some(tracker) = budget_tracker.as_mut() {
+                        if tracker.step(group_max_update_length) {
+                            primal_module.write().max_tree_size = 0;
+                        }
+                    }
                     if let Some(callback) = callback.as_mut() {
                         callback(interface, dual_module, primal_module, Some(group_max_update_length));
                     }
@@ -602,6 +963,7 @@ impl PrimalModuleParallelUnitPtr {
                 callback(&primal_unit.interface_ptr, &dual_unit, &primal_unit.serial_module, None);
             }
         }
+        primal_unit.budget_truncated = budget_tracker.is_some_and(|tracker| tracker.truncated);
         primal_unit.is_active = true;
         event_time.end = primal_module_parallel
             .last_solve_start_time
@@ -686,6 +1048,26 @@ impl PrimalModuleParallelUnitPtr {
 }
 
 impl PrimalModuleParallelUnit {
+    /// whether the next obstacle to resolve is recorded to be computed by this unit from its own serial modules, so that the
+    /// unit resolving it is the one responsible for it
+    pub fn is_obstacle_local(
+        partition_info: &PartitionInfo,
+        unit_index: usize,
+        group_max_update_length: &GroupMaxUpdateLength,
+    ) -> bool {
+        if !matches!(group_max_update_length, GroupMaxUpdateLength::Conflicts(_)) {
+            return true;
+        }
+        let Some(max_update_length) = group_max_update_length.peek() else {
+            return true;
+        };
+        group_max_update_length.get_origin(max_update_length).is_some_and(|origin| {
+            origin.interface_index == unit_index
+                && (origin.unit_index == unit_index
+                    || partition_info.units[unit_index].descendants.contains(&origin.unit_index))
+        })
+    }
+
     /// fuse two units together, by copying the right child's content into the left child's content and resolve index;
     /// note that this operation doesn't update on the dual module, call [`Self::break_matching_with_mirror`] if needed
     pub fn fuse<DualSerialModule: DualModuleImpl + Send + Sync>(
@@ -698,38 +1080,159 @@ impl PrimalModuleParallelUnit {
         );
         let left_child = left_child_ptr.read_recursive();
         let right_child = right_child_ptr.read_recursive();
+        trace_span!("fuse", unit_index = self.unit_index);
+        let profiler_start = self.serial_module.read_recursive().phase_profiler.start();
         dual_unit.fuse(&self.interface_ptr, (&left_child.interface_ptr, &right_child.interface_ptr));
         self.serial_module.fuse(&left_child.serial_module, &right_child.serial_module);
+        self.serial_module
+            .write()
+            .phase_profiler
+            .record(ProfilerPhase::Fuse, profiler_start);
+    }
+
+    /// estimate the cost of fusing the children without changing anything; the children must be solved but not yet
+    /// fused, because the fusion resumes the frozen nodes
+    pub fn estimate_fuse_cost(&self) -> FuseCostEstimate {
+        let (left_child_weak, right_child_weak) = self.children.as_ref().expect("only a fusion unit has fuse cost");
+        let mut frozen_nodes = 0;
+        let mut interface_obstacles = BTreeSet::new();
+        for child_weak in [left_child_weak, right_child_weak] {
+            let child_ptr = child_weak.upgrade_force();
+            let temporary_matches = child_ptr.read_recursive().temporary_virtual_matches();
+            let nodes: BTreeSet<NodeIndex> = temporary_matches.iter().map(|&(node_index, _)| node_index).collect();
+            frozen_nodes += nodes.len();
+            for (_, vertex_index) in temporary_matches {
+                if self.partition_info.owner_of_vertex(vertex_index) == self.unit_index {
+                    interface_obstacles.insert(vertex_index);
+                }
+            }
+        }
+        FuseCostEstimate {
+            frozen_nodes,
+            interface_obstacles: interface_obstacles.len(),
+        }
+    }
+
+    /// match the pairs of nodes temporarily matched to the same interface vertex owned by this unit, which becomes a normal
+    /// vertex after fusion; both nodes touch the vertex, so the path between them is tight and they stay frozen.
+    /// Those left unpaired are resumed by [`Self::break_matching_with_mirror`], see [`fusion_adjustments`]
+    pub fn prematch_interface(&mut self) {
+        let temporary_matches = self.temporary_virtual_matches();
+        let adjustments = fusion_adjustments(&self.partition_info, self.unit_index, &temporary_matches, true);
+        let module = self.serial_module.read_recursive();
+        let mut possible_break = vec![];
+        for (node_index, adjustment) in adjustments {
+            match adjustment {
+                FusionAdjustment::MatchPeer(peer_index) => {
+                    let primal_node_ptr = module.get_node(node_index).unwrap();
+                    let peer_ptr = module.get_node(peer_index).unwrap();
+                    let mut primal_node = primal_node_ptr.write();
+                    let touching = primal_node.temporary_match.take().unwrap().1;
+                    primal_node.temporary_match = Some((MatchTarget::Peer(peer_ptr.downgrade()), touching));
+                }
+                FusionAdjustment::Keep | FusionAdjustment::Resume => possible_break.push(node_index),
+            }
+        }
+        drop(module);
+        self.serial_module.write().possible_break = possible_break;
     }
 
     /// break the matched pairs of interface vertices
-    #[allow(clippy::unnecessary_cast)]
     pub fn break_matching_with_mirror(&mut self, dual_module: &mut impl DualModuleImpl) {
         // use `possible_break` to efficiently break those
-        let mut possible_break = vec![];
+        let temporary_matches = self.temporary_virtual_matches();
+        let adjustments = fusion_adjustments(&self.partition_info, self.unit_index, &temporary_matches, false);
         let module = self.serial_module.read_recursive();
-        for node_index in module.possible_break.iter() {
-            let primal_node_ptr = module.get_node(*node_index);
-            if let Some(primal_node_ptr) = primal_node_ptr {
-                let mut primal_node = primal_node_ptr.write();
-                if let Some((MatchTarget::VirtualVertex(vertex_index), _)) = &primal_node.temporary_match {
-                    if self.partition_info.vertex_to_owning_unit[*vertex_index as usize] == self.unit_index {
-                        primal_node.temporary_match = None;
-                        self.interface_ptr.set_grow_state(
-                            &primal_node.origin.upgrade_force(),
-                            DualNodeGrowState::Grow,
-                            dual_module,
-                        );
-                    } else {
-                        // still possible break
-                        possible_break.push(*node_index);
-                    }
+        let mut possible_break = vec![];
+        for (node_index, adjustment) in adjustments {
+            match adjustment {
+                FusionAdjustment::Resume => {
+                    let primal_node_ptr = module.get_node(node_index).unwrap();
+                    let mut primal_node = primal_node_ptr.write();
+                    primal_node.temporary_match = None;
+                    self.interface_ptr.set_grow_state(
+                        &primal_node.origin.upgrade_force(),
+                        DualNodeGrowState::Grow,
+                        dual_module,
+                    );
                 }
+                FusionAdjustment::Keep => possible_break.push(node_index), // still possible break
+                FusionAdjustment::MatchPeer(_) => unreachable!("pairing is disabled"),
             }
         }
         drop(module);
         self.serial_module.write().possible_break = possible_break;
     }
+
+    /// the nodes in `possible_break` that are still temporarily matched to a virtual vertex, and the vertex
+    fn temporary_virtual_matches(&self) -> Vec<(NodeIndex, VertexIndex)> {
+        let module = self.serial_module.read_recursive();
+        let mut temporary_matches = vec![];
+        for node_index in module.possible_break.iter() {
+            if let Some(primal_node_ptr) = module.get_node(*node_index) {
+                if let Some((MatchTarget::VirtualVertex(vertex_index), _)) =
+                    &primal_node_ptr.read_recursive().temporary_match
+                {
+                    temporary_matches.push((*node_index, *vertex_index));
+                }
+            }
+        }
+        temporary_matches
+    }
+}
+
+/// the adjustment of a dual node at fusion, when it's temporarily matched to a virtual vertex, see [`fusion_adjustments`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FusionAdjustment {
+    /// the vertex is owned by an ancestor of the fusion unit and is still virtual after this fusion: the node stays
+    /// matched to it and frozen, and it's checked again at the next fusion
+    Keep,
+    /// the vertex is owned by the fusion unit and the other node is matched to the same vertex: both touch the vertex, so
+    /// the path between them is tight and they're matched to each other, staying frozen with their dual variables
+    MatchPeer(NodeIndex),
+    /// the vertex is owned by the fusion unit and becomes a normal vertex: the node is unmatched and grows again
+    /// starting from its current dual variable
+    Resume,
+}
+
+/// the rules to adjust the dual nodes when the children of `unit_index` are fused, given the nodes temporarily matched
+/// to a virtual vertex in the children, i.e. a mirrored interface vertex or a real virtual vertex, as
+/// `(node_index, vertex_index)` in the order they're recorded; a node recorded more than once is adjusted once.
+/// Without `interface_prematch`, a node is never matched to a peer. The dual variables are never changed at fusion:
+/// the frozen nodes keep their values and the resumed nodes continue from them, so the sum of dual variables of the
+/// fusion unit is the sum of its children. The adjustments are returned in the order of the first record of each node
+pub fn fusion_adjustments(
+    partition_info: &PartitionInfo,
+    unit_index: usize,
+    temporary_matches: &[(NodeIndex, VertexIndex)],
+    interface_prematch: bool,
+) -> Vec<(NodeIndex, FusionAdjustment)> {
+    let mut adjustments: Vec<(NodeIndex, FusionAdjustment)> = vec![];
+    let mut positions: BTreeMap<NodeIndex, usize> = BTreeMap::new();
+    // the position of the node waiting for a peer at each vertex owned by the fusion unit
+    let mut pending: BTreeMap<VertexIndex, usize> = BTreeMap::new();
+    for &(node_index, vertex_index) in temporary_matches.iter() {
+        if positions.contains_key(&node_index) {
+            continue; // the same node may be recorded more than once
+        }
+        positions.insert(node_index, adjustments.len());
+        if partition_info.owner_of_vertex(vertex_index) != unit_index {
+            adjustments.push((node_index, FusionAdjustment::Keep));
+            continue;
+        }
+        match pending.remove(&vertex_index) {
+            Some(peer_position) if interface_prematch => {
+                let peer_index = adjustments[peer_position].0;
+                adjustments[peer_position].1 = FusionAdjustment::MatchPeer(node_index);
+                adjustments.push((node_index, FusionAdjustment::MatchPeer(peer_index)));
+            }
+            _ => {
+                pending.insert(vertex_index, adjustments.len());
+                adjustments.push((node_index, FusionAdjustment::Resume));
+            }
+        }
+    }
+    adjustments
 }
 
 impl PrimalModuleImpl for PrimalModuleParallelUnit {
@@ -1180,4 +1683,368 @@ pub mod tests {
             Some(json!({ "max_tree_size": 0, "debug_sequential": true })),
         );
     }
+
+    /// every executed unit shows up in the Gantt chart, and a fusion unit never starts before its children end
+    #[test]
+    fn primal_module_parallel_gantt_chart_1() {
+        // cargo test primal_module_parallel_gantt_chart_1 -- --nocapture
+        use super::super::example_partition::*;
+        use super::super::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(7, 7, 0.1, 500);
+        let partition_info = PhenomenologicalPlanarCodeTimePartition::new(7, 7, 4)
+            .build_apply(&mut code)
+            .info();
+        let initializer = code.get_initializer();
+        let mut solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+        solver.solve(&code.generate_random_errors(0));
+        let gantt = solver.primal_module.generate_gantt_chart();
+        assert_eq!(solver.generate_profiler_report()["primal"]["gantt"], gantt);
+        let units = gantt["units"].as_array().unwrap();
+        assert_eq!(units.len(), partition_info.units.len());
+        for (unit_index, unit) in units.iter().enumerate() {
+            let (ready, start, end) = (
+                unit["ready"].as_f64().unwrap(),
+                unit["start"].as_f64().unwrap(),
+                unit["end"].as_f64().unwrap(),
+            );
+            assert!(ready <= start && start <= end);
+            if let Some((left_index, right_index)) = partition_info.units[unit_index].children {
+                for child_index in [left_index, right_index] {
+                    assert!(units[child_index]["end"].as_f64().unwrap() <= start);
+                    assert!(units[child_index]["level"].as_u64().unwrap() < unit["level"].as_u64().unwrap());
+                }
+            } else {
+                assert_eq!(unit["level"], 0);
+            }
+        }
+        // 4 partitions fused in a chain, so every fusion level has a single unit
+        let levels = gantt["levels"].as_array().unwrap();
+        assert_eq!(levels.len(), 4);
+        assert_eq!(levels[0]["unit_count"], 4);
+        for level in levels.iter().skip(1) {
+            assert_eq!(level["unit_count"], 1);
+        }
+    }
+
+    /// a slow network delays every fusion by at least one message, while each unit keeps its measured busy time
+    #[test]
+    fn primal_module_parallel_simulate_network_latency_1() {
+        // cargo test primal_module_parallel_simulate_network_latency_1 -- --nocapture
+        use super::super::example_partition::*;
+        use super::super::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(7, 7, 0.1, 500);
+        let partition_info = PhenomenologicalPlanarCodeTimePartition::new(7, 7, 4)
+            .build_apply(&mut code)
+            .info();
+        let initializer = code.get_initializer();
+        let network = json!({ "latency": 0.01, "bandwidth": 1e6, "bytes_per_vertex": 4 });
+        let mut solver = SolverParallel::new(
+            &initializer,
+            &partition_info,
+            json!({ "primal": { "network_simulation": network } }),
+        );
+        solver.solve(&code.generate_random_errors(0));
+        let network: NetworkLatencyModel = serde_json::from_value(network).unwrap();
+        let simulation = solver.primal_module.simulate_network_latency(&network);
+        assert_eq!(solver.generate_profiler_report()["primal"]["network_simulation"], simulation);
+        let gantt = solver.primal_module.generate_gantt_chart();
+        let units = simulation["units"].as_array().unwrap();
+        assert_eq!(units.len(), partition_info.units.len());
+        let mut message_bytes = 0;
+        for (unit_index, unit) in units.iter().enumerate() {
+            let (ready, end) = (unit["ready"].as_f64().unwrap(), unit["end"].as_f64().unwrap());
+            let measured = &gantt["units"][unit_index];
+            let busy = measured["end"].as_f64().unwrap() - measured["start"].as_f64().unwrap();
+            assert!((end - ready - busy).abs() < 1e-9);
+            let unit_info = &partition_info.units[unit_index];
+            if let Some((left_index, right_index)) = unit_info.children {
+                let bytes = unit_info.owning_range.len() * 4;
+                assert_eq!(unit["message_bytes"], bytes);
+                message_bytes += 2 * bytes;
+                for child_index in [left_index, right_index] {
+                    assert!(units[child_index]["end"].as_f64().unwrap() + network.message_delay(bytes) <= ready + 1e-9);
+                }
+            } else {
+                assert_eq!(unit["message_bytes"], 0);
+            }
+        }
+        assert_eq!(simulation["message_count"], 6);
+        assert_eq!(simulation["message_bytes"], message_bytes);
+        // the 3 fusions in a chain are on the critical path
+        assert!(simulation["total"].as_f64().unwrap() >= 3. * network.latency);
+        // without the option the report does not simulate anything
+        let mut solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+        solver.solve(&code.generate_random_errors(0));
+        assert!(solver.generate_profiler_report()["primal"]["network_simulation"].is_null());
+    }
+
+    /// pairing the nodes frozen at the same interface vertex must not change the minimum-weight result
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn primal_module_parallel_interface_prematch_1() {
+        // cargo test primal_module_parallel_interface_prematch_1 -- --nocapture
+        use super::super::example_partition::*;
+        use super::super::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(7, 7, 0.1, 500);
+        let mut partition = PhenomenologicalPlanarCodeTimePartition::new(7, 7, 4);
+        let partition_info = partition.build_apply(&mut code).info();
+        let initializer = code.get_initializer();
+        let mut serial_solver = SolverSerial::new(&initializer);
+        let mut prematch_solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+        let mut resume_solver = SolverParallel::new(
+            &initializer,
+            &partition_info,
+            json!({ "primal": { "interface_prematch": false } }),
+        );
+        for seed in 0..200 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            serial_solver.solve(&syndrome_pattern);
+            prematch_solver.solve(&syndrome_pattern);
+            resume_solver.solve(&syndrome_pattern);
+            let expected = serial_solver.sum_dual_variables();
+            assert_eq!(prematch_solver.sum_dual_variables(), expected, "seed {seed}");
+            assert_eq!(resume_solver.sum_dual_variables(), expected, "seed {seed}");
+            let subgraph_weight: Weight = (prematch_solver.subgraph().iter())
+                .map(|&edge_index| initializer.weighted_edges[edge_index as usize].2)
+                .sum();
+            assert_eq!(subgraph_weight, expected, "seed {seed}");
+            serial_solver.clear();
+            prematch_solver.clear();
+            resume_solver.clear();
+        }
+    }
+
+    /// the dual adjustments at fusion following the cases of nodes touching the interface
+    /// the fuse cost is estimated from the solved children right before they're fused
+    #[test]
+    fn primal_module_parallel_estimate_fuse_cost_1() {
+        // cargo test primal_module_parallel_estimate_fuse_cost_1 -- --nocapture
+        let half_weight = 500;
+        // put the left virtual vertex in the front so that the vertices form a chain
+        let mut reordered_vertices = vec![11];
+        reordered_vertices.extend(0..11);
+        let (primal_module, _dual_module) = primal_module_parallel_basic_standard_syndrome_optional_viz(
+            CodeCapacityRepetitionCode::new(11, 0.1, half_weight),
+            None,
+            vec![3, 5], // on both sides of the interface vertex
+            2 * half_weight,
+            |_initializer, config| {
+                config.partitions = vec![
+                    VertexRange::new(0, 5),  // unit 0
+                    VertexRange::new(6, 12), // unit 1
+                ];
+                config.fusions = vec![(0, 1)]; // unit 2, owning vertex 5
+            },
+            Some(reordered_vertices),
+        );
+        // each child freezes a node at the interface vertex
+        let expected = FuseCostEstimate {
+            frozen_nodes: 2,
+            interface_obstacles: 1,
+        };
+        assert_eq!(primal_module.units[2].read_recursive().fuse_cost, Some(expected));
+        let profile = primal_module.generate_profiler_report();
+        assert_eq!(
+            profile["fuse_cost_vec"],
+            json!([null, null, { "frozen_nodes": 2, "interface_obstacles": 1 }])
+        );
+    }
+
+    /// the budget is checked in every unit, including the fusion units
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn primal_module_parallel_solve_budget_1() {
+        // cargo test primal_module_parallel_solve_budget_1 -- --nocapture
+        use super::super::example_partition::*;
+        use super::super::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(7, 11, 0.03, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new(7, 11, 4).build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let partition_info = partition_config.info();
+        let solvers: Vec<Box<dyn PrimalDualSolver>> = vec![
+            Box::new(SolverParallel::new(&initializer, &partition_info, json!({}))),
+            Box::new(SolverDualParallel::new(&initializer, &partition_info, json!({}))),
+        ];
+        for mut solver in solvers {
+            solver.set_parity_check(true);
+            let mut truncated_count = 0;
+            for seed in 0..20 {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                solver.solve(&syndrome_pattern);
+                let optimal_weight = solver.sum_dual_variables();
+                solver.clear();
+                assert!(!solver.solve_with_budget(&syndrome_pattern, SolveBudget::default()).truncated);
+                assert_eq!(solver.sum_dual_variables(), optimal_weight);
+                solver.clear();
+                for max_dual_growth_rounds in [0, 2, 5] {
+                    let budget = SolveBudget {
+                        max_dual_growth_rounds: Some(max_dual_growth_rounds),
+                        ..Default::default()
+                    };
+                    let solution = solver.solve_with_budget(&syndrome_pattern, budget);
+                    truncated_count += solution.truncated as usize;
+                    let subgraph = solver.subgraph(); // checks the parity against the syndrome
+                    let weight: Weight = subgraph
+                        .iter()
+                        .map(|&edge_index| initializer.weighted_edges[edge_index as usize].2)
+                        .sum();
+                    assert!(weight >= optimal_weight);
+                    solver.clear();
+                }
+            }
+            assert!(truncated_count > 0);
+        }
+    }
+
+    #[test]
+    fn primal_module_parallel_fusion_adjustments_1() {
+        // cargo test primal_module_parallel_fusion_adjustments_1 -- --nocapture
+        let mut config = PartitionConfig::new(10);
+        config.partitions = vec![
+            VertexRange::new(0, 3),  // unit 0
+            VertexRange::new(4, 6),  // unit 1
+            VertexRange::new(7, 10), // unit 2
+        ];
+        config.fusions = vec![
+            (0, 1), // unit 3, owning vertex 3
+            (3, 2), // unit 4, owning vertex 6
+        ];
+        let partition_info = config.info();
+        assert_eq!(partition_info.owner_of_vertex(3), 3);
+        assert_eq!(partition_info.owner_of_vertex(6), 4);
+        use FusionAdjustment::*;
+        // two nodes touching the same interface vertex are matched to each other
+        assert_eq!(
+            fusion_adjustments(&partition_info, 3, &[(0, 3), (1, 3)], true),
+            vec![(0, MatchPeer(1)), (1, MatchPeer(0))]
+        );
+        // a single node touching the interface vertex grows again
+        assert_eq!(fusion_adjustments(&partition_info, 3, &[(0, 3)], true), vec![(0, Resume)]);
+        // the vertex owned by an ancestor is still virtual, even when two nodes touch it
+        assert_eq!(
+            fusion_adjustments(&partition_info, 3, &[(0, 6), (1, 6)], true),
+            vec![(0, Keep), (1, Keep)]
+        );
+        assert_eq!(
+            fusion_adjustments(&partition_info, 4, &[(0, 6), (1, 6)], true),
+            vec![(0, MatchPeer(1)), (1, MatchPeer(0))]
+        );
+        // a node recorded twice is adjusted once and is not matched to itself
+        assert_eq!(
+            fusion_adjustments(&partition_info, 3, &[(0, 3), (0, 3)], true),
+            vec![(0, Resume)]
+        );
+        // without prematching, all nodes touching the interface vertex grow again
+        assert_eq!(
+            fusion_adjustments(&partition_info, 3, &[(0, 3), (1, 3), (2, 6)], false),
+            vec![(0, Resume), (1, Resume), (2, Keep)]
+        );
+        // the nodes are paired in order, the third one grows again
+        assert_eq!(
+            fusion_adjustments(&partition_info, 3, &[(0, 3), (1, 3), (2, 3)], true),
+            vec![(0, MatchPeer(1)), (1, MatchPeer(0)), (2, Resume)]
+        );
+    }
+
+    /// every unit reports the latency histograms of its own phases, which are also exported as OpenMetrics text
+    #[cfg(feature = "profiling")]
+    #[test]
+    fn primal_module_parallel_phase_profiler_1() {
+        // cargo test --features profiling primal_module_parallel_phase_profiler_1 -- --nocapture
+        use super::super::example_partition::*;
+        use super::super::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(7, 11, 0.03, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new(7, 11, 4).build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let partition_info = partition_config.info();
+        let mut solver = SolverParallel::new(&initializer, &partition_info, json!({}));
+        for seed in 0..5 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            solver.clear();
+        }
+        let report = solver.generate_profiler_report();
+        let unit_count = partition_info.units.len();
+        let dual_unit_phases = report["dual"]["unit_phases"].as_array().unwrap();
+        let primal_unit_phases = report["primal"]["unit_phases"].as_array().unwrap();
+        assert_eq!(dual_unit_phases.len(), unit_count);
+        assert_eq!(primal_unit_phases.len(), unit_count);
+        for unit_index in 0..unit_count {
+            let is_leaf = partition_info.units[unit_index].children.is_none();
+            // every fusion unit fuses its children exactly once per shot
+            let expected_fuse_count = if is_leaf { json!(null) } else { json!(5) };
+            assert_eq!(dual_unit_phases[unit_index]["fuse"]["count"], expected_fuse_count);
+            assert_eq!(primal_unit_phases[unit_index]["fuse"]["count"], expected_fuse_count);
+        }
+        assert!(dual_unit_phases[0]["grow"]["count"].as_u64().unwrap() > 0);
+        assert!(primal_unit_phases[0]["resolve"]["count"].as_u64().unwrap() > 0);
+        let openmetrics = profiler_report_to_openmetrics(&report);
+        assert!(openmetrics.starts_with("# TYPE fusion_blossom_phase_latency_seconds histogram\n"));
+        assert!(openmetrics.ends_with("# EOF\n"));
+        assert!(openmetrics.contains("{module=\"primal\",phase=\"fuse\",unit=\"4\",le=\"+Inf\"} 5\n"));
+        let dual_grow_count = "fusion_blossom_phase_latency_seconds_count{module=\"dual\",phase=\"grow\",unit=\"0\"}";
+        assert!(openmetrics.contains(dual_grow_count));
+    }
+
+    /// the solver phases of every unit are traced as spans, including the fusion of the children
+    #[cfg(feature = "tracing_spans")]
+    #[test]
+    fn primal_module_parallel_tracing_spans_1() {
+        // cargo test --features tracing_spans primal_module_parallel_tracing_spans_1 -- --nocapture
+        use std::sync::atomic::{AtomicU64, Ordering};
+        use tracing::span;
+        #[derive(Default)]
+        struct SpanRecorder {
+            names: Mutex<Vec<(&'static str, String)>>,
+            next_id: AtomicU64,
+        }
+        impl tracing::Subscriber for SpanRecorder {
+            fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                self.names
+                    .lock()
+                    .unwrap()
+                    .push((span.metadata().name(), format!("{:?}", span.values())));
+                span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+            }
+            fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+            fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+            fn event(&self, _event: &tracing::Event<'_>) {}
+            fn enter(&self, _span: &span::Id) {}
+            fn exit(&self, _span: &span::Id) {}
+        }
+        let recorder = Arc::new(SpanRecorder::default());
+        let half_weight = 500;
+        let mut reordered_vertices = vec![11];
+        reordered_vertices.extend(0..11);
+        // the callback of the sequential solve runs every unit on this thread, where the subscriber is set
+        tracing::subscriber::with_default(recorder.clone(), || {
+            primal_module_parallel_basic_standard_syndrome_optional_viz(
+                CodeCapacityRepetitionCode::new(11, 0.1, half_weight),
+                None,
+                vec![3, 5],
+                2 * half_weight,
+                |_initializer, config| {
+                    config.partitions = vec![
+                        VertexRange::new(0, 5),  // unit 0
+                        VertexRange::new(6, 12), // unit 1
+                    ];
+                    config.fusions = vec![(0, 1)]; // unit 2, owning vertex 5
+                },
+                Some(reordered_vertices),
+            );
+        });
+        let names = recorder.names.lock().unwrap();
+        let count = |name: &str| names.iter().filter(|(span_name, _)| *span_name == name).count();
+        assert_eq!(count("parallel_solve"), 1);
+        assert_eq!(count("unit_solve"), 3);
+        assert_eq!(count("load_syndrome"), 2); // a fusion unit loads its own defects one by one instead
+        assert_eq!(count("fuse"), 1);
+        assert!(count("grow") > 0);
+        assert!(count("resolve") > 0);
+        let fuse_fields = &names.iter().find(|(span_name, _)| *span_name == "fuse").unwrap().1;
+        assert!(fuse_fields.contains("unit_index: 2"), "{fuse_fields}");
+    }
 }
@@ -10,7 +10,10 @@ use std::cmp::Ordering;
 use std::num::NonZeroUsize;
 
 use crate::derivative::Derivative;
+use serde::Serialize;
 
+#[cfg(feature = "track_allocations")]
+use super::allocation_tracker::*;
 use super::dual_module::*;
 use super::pointers::*;
 use super::primal_module::*;
@@ -26,6 +29,9 @@ pub struct PrimalModuleSerial {
     pub nodes: Vec<Option<PrimalNodeInternalPtr>>,
     /// current nodes length, to enable constant-time clear operation
     pub nodes_length: usize,
+    /// recycles the removed primal nodes across rounds
+    #[derivative(Debug = "ignore")]
+    pub node_pool: ObjectPool<PrimalNodeInternalPtr>,
     /// allow pointer reuse will reduce the time of reallocation, but it's unsafe if not owning it
     pub is_fusion: bool,
     /// the indices of primal nodes that is possibly matched to the mirrored vertex, and need to break when mirrored vertices are no longer mirrored
@@ -41,6 +47,40 @@ pub struct PrimalModuleSerial {
     pub children: Option<((PrimalModuleSerialWeak, NodeNum), (PrimalModuleSerialWeak, NodeNum))>,
     /// the maximum number of children in a tree before it collapses to a union-find decoder
     pub max_tree_size: usize,
+    /// the maximum number of dual nodes created in this unit, mirroring the fixed memory of a hardware unit
+    pub max_dual_node_count: usize,
+    /// the maximum nesting depth of blossoms created in this unit; a defect vertex has depth 0
+    pub max_blossom_depth: usize,
+    /// the first memory bound violation in this round; decoding continues so that the caller can inspect the result
+    pub memory_bound_error: Option<PrimalMemoryBoundError>,
+    /// the number of conflicts resolved in this round, reported per shot by the benchmark
+    pub conflicts_count: usize,
+    /// per-call latency histograms of resolve and of fusing the children into this module, see [`PhaseProfiler`]
+    #[derivative(Debug = "ignore")]
+    pub phase_profiler: PhaseProfiler,
+}
+
+/// the hardware memory bound of a primal unit is exceeded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum PrimalMemoryBoundError {
+    DualNodeCount { unit_index: usize, count: usize, max: usize },
+    BlossomDepth { unit_index: usize, depth: usize, max: usize },
+}
+
+impl std::fmt::Display for PrimalMemoryBoundError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::DualNodeCount { unit_index, count, max } => {
+                write!(f, "unit {unit_index} holds {count} dual nodes, exceeding the bound of {max}")
+            }
+            Self::BlossomDepth { unit_index, depth, max } => {
+                write!(
+                    f,
+                    "unit {unit_index} creates a blossom of depth {depth}, exceeding the bound of {max}"
+                )
+            }
+        }
+    }
 }
 
 pub type PrimalModuleSerialPtr = ArcManualSafeLock<PrimalModuleSerial>;
@@ -168,10 +208,13 @@ impl PrimalNodeInternalPtr {
 
 impl PrimalModuleImpl for PrimalModuleSerialPtr {
     fn new_empty(_initializer: &SolverInitializer) -> Self {
+        #[cfg(feature = "track_allocations")]
+        let _allocation_scope = AllocationScope::enter(AllocationModule::Primal);
         Self::new_value(PrimalModuleSerial {
             unit_index: 0, // if necessary, manually change it
             nodes: vec![],
             nodes_length: 0,
+            node_pool: ObjectPool::new(),
             is_fusion: false,
             possible_break: vec![],
             debug_resolve_only_one: false,
@@ -182,12 +225,20 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
             // max_tree_size: 0,
             // Minimum Weight Perfect Matching
             max_tree_size: usize::MAX,
+            max_dual_node_count: usize::MAX,
+            max_blossom_depth: usize::MAX,
+            memory_bound_error: None,
+            conflicts_count: 0,
+            phase_profiler: PhaseProfiler::new(),
         })
     }
 
     fn clear(&mut self) {
         let mut module = self.write();
+        module.memory_bound_error = None;
+        module.conflicts_count = 0;
         module.nodes_length = 0; // without actually dropping all the nodes, to enable constant time clear
+        module.node_pool.clear();
         module.possible_break.clear();
         module.is_fusion = false;
         module.parent = None;
@@ -206,31 +257,13 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
         let local_node_index = module.nodes_length;
         let node_index = module.nodes_count();
         debug_assert_eq!(node.index, node_index, "must load in order");
-        let primal_node_internal_ptr =
-            if !module.is_fusion && local_node_index < module.nodes.len() && module.nodes[local_node_index].is_some() {
-                let node_ptr = module.nodes[local_node_index].take().unwrap();
-                let mut node = node_ptr.write();
-                node.origin = dual_node_ptr.downgrade();
-                node.index = node_index;
-                node.tree_node = None;
-                node.temporary_match = None;
-                node.belonging = belonging;
-                drop(node);
-                node_ptr
-            } else {
-                PrimalNodeInternalPtr::new_value(PrimalNodeInternal {
-                    origin: dual_node_ptr.downgrade(),
-                    index: node_index,
-                    tree_node: None,
-                    temporary_match: None,
-                    belonging,
-                })
-            };
+        let primal_node_internal_ptr = module.acquire_node(dual_node_ptr, node_index, belonging);
         module.nodes_length += 1;
         if module.nodes.len() < module.nodes_length {
             module.nodes.push(None);
         }
         module.nodes[local_node_index] = Some(primal_node_internal_ptr);
+        module.check_dual_node_count();
     }
 
     #[allow(clippy::collapsible_else_if)]
@@ -241,7 +274,9 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
         dual_module: &mut D,
     ) {
         debug_assert!(!group_max_update_length.is_empty() && group_max_update_length.get_none_zero_growth().is_none());
+        let profiler_start = self.read_recursive().phase_profiler.start();
         let mut current_conflict_index = 0;
+        let mut resolved_conflicts = 0;
         let debug_resolve_only_one = self.read_recursive().debug_resolve_only_one;
         let max_tree_size = self.read_recursive().max_tree_size;
         while let Some(conflict) = group_max_update_length.pop() {
@@ -250,6 +285,7 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
                 // debug mode
                 break;
             }
+            resolved_conflicts += 1;
             // println!("conflict: {conflict:?}");
             match conflict {
                 MaxUpdateLength::Conflicting((node_ptr_1, touching_ptr_1), (node_ptr_2, touching_ptr_2)) => {
@@ -567,6 +603,7 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
                                 root_tree_node.tree_size = Some(tree_size);
                                 // update dual module interface
                                 if tree_size.get() > max_tree_size {
+                                    drop(root_node); // collapsing reads the whole tree
                                     drop(matched_node_internal);
                                     drop(leaf_node_internal);
                                     self.collapse_tree(root_node_ptr.clone(), interface_ptr, dual_module);
@@ -711,34 +748,16 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
                                 let mut module = self.write();
                                 let local_node_index = module.nodes_length;
                                 let node_index = module.nodes_count();
-                                let primal_node_internal_blossom_ptr = if !module.is_fusion
-                                    && local_node_index < module.nodes.len()
-                                    && module.nodes[local_node_index].is_some()
-                                {
-                                    let node_ptr = module.nodes[local_node_index].take().unwrap();
-                                    let mut node = node_ptr.write();
-                                    node.origin = blossom_node_ptr.downgrade();
-                                    node.index = node_index;
-                                    node.tree_node = None;
-                                    node.temporary_match = None;
-                                    node.belonging = belonging;
-                                    drop(node);
-                                    node_ptr
-                                } else {
-                                    PrimalNodeInternalPtr::new_value(PrimalNodeInternal {
-                                        origin: blossom_node_ptr.downgrade(),
-                                        index: node_index,
-                                        tree_node: None,
-                                        temporary_match: None,
-                                        belonging,
-                                    })
-                                };
+                                let primal_node_internal_blossom_ptr =
+                                    module.acquire_node(&blossom_node_ptr, node_index, belonging);
                                 module.nodes_length += 1;
                                 if module.nodes.len() < module.nodes_length {
                                     module.nodes.push(None);
                                 }
                                 let cloned_primal_node_internal_blossom_ptr = primal_node_internal_blossom_ptr.clone();
                                 module.nodes[local_node_index] = Some(primal_node_internal_blossom_ptr); // feature `dangerous_pointer`: must push the owner
+                                module.check_dual_node_count();
+                                module.check_blossom_depth(&blossom_node_ptr);
                                 cloned_primal_node_internal_blossom_ptr
                             };
                             // handle other part of the tree structure
@@ -1203,6 +1222,9 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
                 _ => unreachable!("should not resolve these issues"),
             }
         }
+        let mut module = self.write();
+        module.conflicts_count += resolved_conflicts;
+        module.phase_profiler.record(ProfilerPhase::Resolve, profiler_start);
     }
 
     fn intermediate_matching<D: DualModuleImpl>(
@@ -1258,6 +1280,16 @@ impl PrimalModuleImpl for PrimalModuleSerialPtr {
         }
         immediate_matching
     }
+
+    fn generate_profiler_report(&self) -> serde_json::Value {
+        let module = self.read_recursive();
+        json!({
+            "memory_bound_error": module.memory_bound_error.as_ref().map(|error| error.to_string()),
+            "conflicts": module.conflicts_count,
+            "node_pool": module.node_pool.statistics,
+            "phases": module.phase_profiler.to_json(),
+        })
+    }
 }
 
 impl FusionVisualizer for PrimalModuleSerialPtr {
@@ -1356,11 +1388,118 @@ impl PrimalModuleSerial {
             }
             bias = left_count + right_count;
         }
-        self.nodes[(relative_node_index - bias) as usize] = None;
+        if let Some(node_ptr) = self.nodes[(relative_node_index - bias) as usize].take() {
+            // a fused module may not own the node, see [`Self::slow_fuse`]
+            if !self.is_fusion {
+                self.node_pool.release(node_ptr);
+            }
+        }
+    }
+
+    /// reuse the primal node left in the slot by the last round or recycled by [`Self::node_pool`], or allocate one; a
+    /// fused module never reuses its slots because they may hold the nodes of its children
+    pub fn acquire_node(
+        &mut self,
+        dual_node_ptr: &DualNodePtr,
+        node_index: NodeIndex,
+        belonging: PrimalModuleSerialWeak,
+    ) -> PrimalNodeInternalPtr {
+        let local_node_index = self.nodes_length;
+        let cached = if !self.is_fusion && local_node_index < self.nodes.len() {
+            self.nodes[local_node_index].take()
+        } else {
+            None
+        };
+        self.node_pool.acquire(
+            cached,
+            |node_ptr| {
+                let mut node = node_ptr.write();
+                node.origin = dual_node_ptr.downgrade();
+                node.index = node_index;
+                node.tree_node = None;
+                node.temporary_match = None;
+                node.belonging = belonging.clone();
+            },
+            || {
+                PrimalNodeInternalPtr::new_value(PrimalNodeInternal {
+                    origin: dual_node_ptr.downgrade(),
+                    index: node_index,
+                    tree_node: None,
+                    temporary_match: None,
+                    belonging: belonging.clone(),
+                })
+            },
+        )
+    }
+
+    /// record a violation if the local dual nodes exceed the memory bound
+    fn check_dual_node_count(&mut self) {
+        if self.nodes_length > self.max_dual_node_count && self.memory_bound_error.is_none() {
+            self.memory_bound_error = Some(PrimalMemoryBoundError::DualNodeCount {
+                unit_index: self.unit_index,
+                count: self.nodes_length,
+                max: self.max_dual_node_count,
+            });
+        }
+    }
+
+    /// record a violation if the newly created blossom is nested too deep; only computed when the bound is set
+    fn check_blossom_depth(&mut self, blossom_node_ptr: &DualNodePtr) {
+        if self.max_blossom_depth == usize::MAX || self.memory_bound_error.is_some() {
+            return;
+        }
+        let depth = Self::blossom_depth(blossom_node_ptr);
+        if depth > self.max_blossom_depth {
+            self.memory_bound_error = Some(PrimalMemoryBoundError::BlossomDepth {
+                unit_index: self.unit_index,
+                depth,
+                max: self.max_blossom_depth,
+            });
+        }
+    }
+
+    /// the nesting depth of a dual node, where a defect vertex has depth 0
+    pub fn blossom_depth(dual_node_ptr: &DualNodePtr) -> usize {
+        let dual_node = dual_node_ptr.read_recursive();
+        match &dual_node.class {
+            DualNodeClass::Blossom { nodes_circle, .. } => {
+                1 + nodes_circle
+                    .iter()
+                    .map(|weak| Self::blossom_depth(&weak.upgrade_force()))
+                    .max()
+                    .unwrap_or(0)
+            }
+            DualNodeClass::DefectVertex { .. } => 0,
+        }
     }
 }
 
 impl PrimalModuleSerialPtr {
+    /// solve within a budget, see [`SolveBudget`]; returns whether the budget is exhausted and the matching may be
+    /// suboptimal. The `max_tree_size` is restored afterwards
+    pub fn solve_budget<D: DualModuleImpl>(
+        &mut self,
+        interface_ptr: &DualModuleInterfacePtr,
+        syndrome_pattern: &SyndromePattern,
+        dual_module: &mut D,
+        budget: SolveBudget,
+    ) -> bool {
+        let max_tree_size = self.read_recursive().max_tree_size;
+        let mut tracker = SolveBudgetTracker::new(budget, std::time::Instant::now());
+        self.solve_step_callback(
+            interface_ptr,
+            syndrome_pattern,
+            dual_module,
+            |_, _, primal_module, group_max_update_length| {
+                if tracker.step(group_max_update_length) {
+                    primal_module.write().max_tree_size = 0;
+                }
+            },
+        );
+        self.write().max_tree_size = max_tree_size;
+        tracker.truncated
+    }
+
     pub fn get_primal_node_internal_ptr_option(&self, dual_node_ptr: &DualNodePtr) -> Option<PrimalNodeInternalPtr> {
         let module = self.read_recursive();
         let dual_node = dual_node_ptr.read_recursive();
@@ -1902,7 +2041,13 @@ impl PrimalModuleSerialPtr {
         primal_node_internal_ptr.flatten_tree(&mut children);
         let nodes_circle: Vec<_> = children
             .iter()
-            .map(|ptr| ptr.read_recursive().origin.clone().upgrade_force())
+            .map(|ptr| {
+                // when fused, the nodes from the children may be outdated
+                ptr.update();
+                let dual_node_ptr = ptr.read_recursive().origin.upgrade_force();
+                dual_node_ptr.update();
+                dual_node_ptr
+            })
             .collect();
         // since we no longer care the internal structure of the tree, we can just construct arbitrary touching list
         let touching_children: Vec<_> = children
@@ -1926,31 +2071,14 @@ impl PrimalModuleSerialPtr {
             let mut module = self.write();
             let local_node_index = module.nodes_length;
             let node_index = module.nodes_count();
-            let primal_node_internal_blossom_ptr =
-                if !module.is_fusion && local_node_index < module.nodes.len() && module.nodes[local_node_index].is_some() {
-                    let node_ptr = module.nodes[local_node_index].take().unwrap();
-                    let mut node = node_ptr.write();
-                    node.origin = blossom_node_ptr.downgrade();
-                    node.index = node_index;
-                    node.tree_node = None;
-                    node.temporary_match = None;
-                    node.belonging = belonging;
-                    drop(node);
-                    node_ptr
-                } else {
-                    PrimalNodeInternalPtr::new_value(PrimalNodeInternal {
-                        origin: blossom_node_ptr.downgrade(),
-                        index: node_index,
-                        tree_node: None,
-                        temporary_match: None,
-                        belonging,
-                    })
-                };
+            let primal_node_internal_blossom_ptr = module.acquire_node(&blossom_node_ptr, node_index, belonging);
             module.nodes_length += 1;
             if module.nodes.len() < module.nodes_length {
                 module.nodes.push(None);
             }
             module.nodes[local_node_index] = Some(primal_node_internal_blossom_ptr.clone());
+            module.check_dual_node_count();
+            module.check_blossom_depth(&blossom_node_ptr);
         };
         // remove the tree structure
         for ptr in children.iter() {
@@ -2077,6 +2205,170 @@ pub mod tests {
         primal_module_serial_basic_standard_syndrome(7, visualize_filename, defect_vertices, 4);
     }
 
+    /// an exhausted budget collapses the remaining trees, giving a valid but possibly suboptimal matching
+    #[test]
+    fn primal_module_serial_solve_budget_1() {
+        // cargo test primal_module_serial_solve_budget_1 -- --nocapture
+        use super::super::mwpm_solver::*;
+        use std::time::Duration;
+        let mut code = CircuitLevelPlanarCode::new(7, 7, 0.01, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        let mut reference_solver = SolverSerial::new(&initializer);
+        let mut truncated_count = 0;
+        for seed in 0..30 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            reference_solver.solve(&syndrome_pattern);
+            let optimal_weight = reference_solver.sum_dual_variables();
+            reference_solver.clear();
+            // an unlimited budget gives the optimal matching
+            let solution = solver.solve_with_budget(&syndrome_pattern, SolveBudget::default());
+            assert!(!solution.truncated);
+            assert_eq!(solver.sum_dual_variables(), optimal_weight);
+            solver.clear();
+            for max_dual_growth_rounds in [0, 1, 3, 10] {
+                let budget = SolveBudget {
+                    max_dual_growth_rounds: Some(max_dual_growth_rounds),
+                    ..Default::default()
+                };
+                let solution = solver.solve_with_budget(&syndrome_pattern, budget);
+                truncated_count += solution.truncated as usize;
+                let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+                subgraph_builder.enable_parity_check = true;
+                subgraph_builder.load_defect_vertices(&syndrome_pattern.defect_vertices);
+                subgraph_builder.load_perfect_matching(&solution.perfect_matching);
+                subgraph_builder.get_subgraph(); // checks the parity against the syndrome
+                assert!(subgraph_builder.total_weight() >= optimal_weight);
+                solver.clear();
+            }
+            assert_eq!(solver.primal_module.read_recursive().max_tree_size, usize::MAX);
+        }
+        assert!(truncated_count > 0);
+        // the deadline has passed before the first step
+        let syndrome_pattern = code.generate_random_errors(0);
+        let budget = SolveBudget {
+            max_duration: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        assert!(solver.solve_with_budget(&syndrome_pattern, budget).truncated);
+    }
+
+    /// consecutive syndromes differing by a few defect vertices decode to the same weight as solving from scratch
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn primal_module_serial_solve_incremental_1() {
+        // cargo test primal_module_serial_solve_incremental_1 -- --nocapture
+        use super::super::mwpm_solver::*;
+        use crate::rand_xoshiro::rand_core::SeedableRng;
+        use rand::Rng;
+        let mut code = PhenomenologicalPlanarCode::new(7, 7, 0.005, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        solver.set_parity_check(true);
+        let mut reference_solver = SolverSerial::new(&initializer);
+        let mut rng = DeterministicRng::seed_from_u64(0);
+        let mut reused_count = 0;
+        for seed in 0..30 {
+            let mut defect_vertices = code.generate_random_errors(seed).defect_vertices;
+            solver.solve(&SyndromePattern::new_vertices(defect_vertices.clone()));
+            for _ in 0..5 {
+                let mut added = vec![];
+                for _ in 0..rng.gen_range(0..3) {
+                    let vertex_index = rng.gen_range(0..initializer.vertex_num);
+                    if !initializer.virtual_vertices.contains(&vertex_index)
+                        && !defect_vertices.contains(&vertex_index)
+                        && !added.contains(&vertex_index)
+                    {
+                        added.push(vertex_index);
+                    }
+                }
+                let removed: Vec<VertexIndex> = if rng.gen_bool(0.3) && !defect_vertices.is_empty() {
+                    vec![defect_vertices[rng.gen_range(0..defect_vertices.len())]]
+                } else {
+                    vec![]
+                };
+                defect_vertices.retain(|vertex_index| !removed.contains(vertex_index));
+                defect_vertices.extend(added.iter());
+                let reused = solver.solve_incremental(&added, &removed);
+                reused_count += reused as usize;
+                assert!(!reused || removed.is_empty());
+                reference_solver.solve(&SyndromePattern::new_vertices(defect_vertices.clone()));
+                assert_eq!(solver.sum_dual_variables(), reference_solver.sum_dual_variables());
+                solver.subgraph(); // checks the parity against the new syndrome
+                reference_solver.clear();
+            }
+            solver.clear();
+        }
+        assert!(reused_count > 20);
+    }
+
+    /// test memory bound violations are reported without interrupting the decoding
+    #[test]
+    fn primal_module_serial_memory_bound_1() {
+        // cargo test primal_module_serial_memory_bound_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        let mut primal_module = PrimalModuleSerialPtr::new_empty(&initializer);
+        code.set_defect_vertices(&[18, 26, 34]);
+        let interface_ptr = DualModuleInterfacePtr::new_empty();
+        // a blossom of depth 1 is required
+        primal_module.write().max_blossom_depth = 0;
+        primal_module.solve(&interface_ptr, &code.get_syndrome(), &mut dual_module);
+        assert_eq!(
+            primal_module.read_recursive().memory_bound_error,
+            Some(PrimalMemoryBoundError::BlossomDepth {
+                unit_index: 0,
+                depth: 1,
+                max: 0
+            })
+        );
+        assert_eq!(interface_ptr.sum_dual_variables(), 4 * 2 * 500);
+        // 3 defect vertices and 1 blossom
+        primal_module.clear();
+        interface_ptr.clear();
+        dual_module.clear();
+        primal_module.write().max_blossom_depth = usize::MAX;
+        primal_module.write().max_dual_node_count = 3;
+        primal_module.solve(&interface_ptr, &code.get_syndrome(), &mut dual_module);
+        assert_eq!(
+            primal_module.read_recursive().memory_bound_error,
+            Some(PrimalMemoryBoundError::DualNodeCount {
+                unit_index: 0,
+                count: 4,
+                max: 3
+            })
+        );
+        primal_module.clear();
+        assert_eq!(primal_module.read_recursive().memory_bound_error, None);
+    }
+
+    /// the nodes of the last round are recycled instead of allocated, in both the primal and the dual module
+    #[test]
+    fn primal_module_serial_node_pool_1() {
+        // cargo test primal_module_serial_node_pool_1 -- --nocapture
+        use super::super::mwpm_solver::*;
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let mut solver = SolverSerial::new(&initializer);
+        // 3 defect vertices and 1 blossom
+        code.set_defect_vertices(&[18, 26, 34]);
+        solver.solve(&code.get_syndrome());
+        let report = solver.generate_profiler_report();
+        assert_eq!(report["primal"]["node_pool"], json!({ "allocated": 4, "recycled": 0 }));
+        assert_eq!(report["dual"]["node_pool"], json!({ "allocated": 4, "recycled": 0 }));
+        solver.clear();
+        for seed in 0..20 {
+            solver.solve(&code.generate_random_errors(seed));
+            solver.clear();
+        }
+        let report = solver.generate_profiler_report();
+        let allocated = report["primal"]["node_pool"]["allocated"].as_u64().unwrap();
+        let recycled = report["primal"]["node_pool"]["recycled"].as_u64().unwrap();
+        assert!(recycled > 10 * allocated, "allocated: {allocated}, recycled: {recycled}");
+        assert_eq!(report["dual"]["node_pool"], report["primal"]["node_pool"]);
+    }
+
     /// test a free node conflict with a virtual boundary
     #[test]
     fn primal_module_serial_basic_2() {
@@ -2191,6 +2483,50 @@ pub mod tests {
         primal_module_serial_basic_standard_syndrome(15, visualize_filename, defect_vertices, 20);
     }
 
+    /// the toric code has no virtual vertex, so every defect vertex must be matched to another one
+    #[test]
+    fn primal_module_serial_toric_code_1() {
+        // cargo test primal_module_serial_toric_code_1 -- --nocapture
+        use super::super::cli::*;
+        use super::super::mwpm_solver::*;
+        let codes: Vec<Box<dyn ExampleCode>> = vec![
+            Box::new(CodeCapacityToricCode::new(7, 0.1, 500)),
+            Box::new(PhenomenologicalToricCode::new(5, 4, 0.03, 500)),
+        ];
+        for mut code in codes {
+            let initializer = code.get_initializer();
+            assert!(initializer.virtual_vertices.is_empty());
+            let mut solver: Box<dyn PrimalDualSolver> = Box::new(SolverSerial::new(&initializer));
+            let mut verifier = Verifier::BlossomV.build(&initializer);
+            for seed in 0..50 {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                solver.solve(&syndrome_pattern);
+                verifier.verify(&mut solver, &syndrome_pattern, None);
+                solver.clear();
+            }
+        }
+    }
+
+    /// the XZZX code under biased noise has two different edge weights
+    #[test]
+    fn primal_module_serial_xzzx_code_1() {
+        // cargo test primal_module_serial_xzzx_code_1 -- --nocapture
+        use super::super::cli::*;
+        use super::super::mwpm_solver::*;
+        for eta in [0.5, 10., 1000.] {
+            let mut code = CodeCapacityXZZXCode::new(7, 0.1, eta, 500);
+            let initializer = code.get_initializer();
+            let mut solver: Box<dyn PrimalDualSolver> = Box::new(SolverSerial::new(&initializer));
+            let mut verifier = Verifier::BlossomV.build(&initializer);
+            for seed in 0..50 {
+                let syndrome_pattern = code.generate_random_errors(seed);
+                solver.solve(&syndrome_pattern);
+                verifier.verify(&mut solver, &syndrome_pattern, None);
+                solver.clear();
+            }
+        }
+    }
+
     /// debug a case where it disagree with blossom V library, mine reports 11866, blossom V reports 12284
     #[test]
     fn primal_module_debug_1() {
@@ -0,0 +1,407 @@
+//! Union-Find Primal Module
+//!
+//! A standalone weighted union-find decoder on the decoding graph: every odd cluster grows uniformly until it meets
+//! another cluster or a virtual vertex, and the correction is peeled from a spanning forest of each valid cluster.
+//! It takes almost linear time but is not always minimum-weight, so it serves as the fast path of
+//! [`crate::mwpm_solver::SolverHybrid`], giving up as soon as a cluster grows beyond a threshold and leaving the
+//! syndrome to the full blossom algorithm
+//!
+
+use super::util::*;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// a cluster of the union-find decoder, stored at its root vertex
+#[derive(Debug, Clone)]
+struct UnionFindCluster {
+    defect_num: usize,
+    /// a cluster with a virtual vertex is valid regardless of its parity
+    touches_boundary: bool,
+    vertices: Vec<VertexIndex>,
+}
+
+impl UnionFindCluster {
+    fn new(defect_num: usize, vertex_index: VertexIndex) -> Self {
+        Self {
+            defect_num,
+            touches_boundary: false,
+            vertices: vec![vertex_index],
+        }
+    }
+
+    fn is_odd(&self) -> bool {
+        self.defect_num % 2 == 1 && !self.touches_boundary
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PrimalModuleUnionFind {
+    pub vertex_num: VertexNum,
+    /// the original edge weights
+    pub weighted_edges: Vec<(VertexIndex, VertexIndex, Weight)>,
+    /// the incident edges of each vertex as `(peer, edge index)`
+    neighbors: Vec<Vec<(VertexIndex, EdgeIndex)>>,
+    is_virtual: Vec<bool>,
+    /// the solve gives up as soon as a cluster contains more defect vertices than this, see [`Self::solve`]
+    pub max_cluster_defects: usize,
+    /// the edge weights of the current syndrome, including the erasures and the dynamic weights
+    weights: Vec<Weight>,
+    /// how much each edge has grown, from both sides
+    growth: Vec<Weight>,
+    /// the parent of each vertex in the union-find forest
+    parent: Vec<VertexIndex>,
+    /// the cluster at each root vertex
+    clusters: Vec<Option<UnionFindCluster>>,
+    /// the vertices and edges modified by the current syndrome, restored by [`Self::clear`]
+    touched_vertices: Vec<VertexIndex>,
+    touched_edges: Vec<EdgeIndex>,
+    defect_vertices: Vec<VertexIndex>,
+    /// matched pairs of defect vertices, valid after a successful solve
+    pub peer_matchings: Vec<(VertexIndex, VertexIndex)>,
+    /// defect vertices matched to a virtual vertex, valid after a successful solve
+    pub virtual_matchings: Vec<(VertexIndex, VertexIndex)>,
+    /// the correction, valid after a successful solve
+    pub subgraph: Vec<EdgeIndex>,
+}
+
+impl PrimalModuleUnionFind {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        let vertex_num = initializer.vertex_num as usize;
+        let mut neighbors = vec![vec![]; vertex_num];
+        for (edge_index, &(vertex_1, vertex_2, _)) in initializer.weighted_edges.iter().enumerate() {
+            neighbors[vertex_1 as usize].push((vertex_2, edge_index as EdgeIndex));
+            neighbors[vertex_2 as usize].push((vertex_1, edge_index as EdgeIndex));
+        }
+        let mut is_virtual = vec![false; vertex_num];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        Self {
+            vertex_num: initializer.vertex_num,
+            weighted_edges: initializer.weighted_edges.clone(),
+            neighbors,
+            is_virtual,
+            max_cluster_defects: usize::MAX,
+            weights: initializer.weighted_edges.iter().map(|&(_, _, weight)| weight).collect(),
+            growth: vec![0; initializer.weighted_edges.len()],
+            parent: (0..initializer.vertex_num).collect(),
+            clusters: vec![None; vertex_num],
+            touched_vertices: vec![],
+            touched_edges: vec![],
+            defect_vertices: vec![],
+            peer_matchings: vec![],
+            virtual_matchings: vec![],
+            subgraph: vec![],
+        }
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn clear(&mut self) {
+        for &vertex_index in self.touched_vertices.iter() {
+            self.parent[vertex_index as usize] = vertex_index;
+            self.clusters[vertex_index as usize] = None;
+        }
+        for &edge_index in self.touched_edges.iter() {
+            self.weights[edge_index as usize] = self.weighted_edges[edge_index as usize].2;
+            self.growth[edge_index as usize] = 0;
+        }
+        self.touched_vertices.clear();
+        self.touched_edges.clear();
+        self.defect_vertices.clear();
+        self.peer_matchings.clear();
+        self.virtual_matchings.clear();
+        self.subgraph.clear();
+    }
+
+    /// decode the syndrome, returning whether every cluster becomes valid, i.e. even or touching a virtual vertex,
+    /// before any of them contains more than [`Self::max_cluster_defects`] defect vertices; the matchings and the
+    /// correction are only valid on success. It must be cleared before the next solve
+    #[allow(clippy::unnecessary_cast)]
+    pub fn solve(&mut self, syndrome_pattern: &SyndromePattern) -> bool {
+        assert!(
+            self.touched_vertices.is_empty() && self.touched_edges.is_empty(),
+            "the union-find module must be cleared before the next solve"
+        );
+        for &edge_index in syndrome_pattern.erasures.iter() {
+            self.set_weight(edge_index, 0);
+        }
+        for &(edge_index, weight) in syndrome_pattern.dynamic_weights.iter() {
+            self.set_weight(edge_index, weight);
+        }
+        for &vertex_index in syndrome_pattern.defect_vertices.iter() {
+            assert!(!self.is_virtual[vertex_index as usize], "defect vertex cannot be virtual");
+            assert!(
+                self.clusters[vertex_index as usize].is_none(),
+                "same defect vertex appears twice"
+            );
+            self.touched_vertices.push(vertex_index);
+            self.clusters[vertex_index as usize] = Some(UnionFindCluster::new(1, vertex_index));
+        }
+        self.defect_vertices = syndrome_pattern.defect_vertices.clone();
+        if !self.defect_vertices.is_empty() && self.max_cluster_defects == 0 {
+            return false;
+        }
+        let mut odd_roots = self.defect_vertices.clone();
+        loop {
+            let roots: BTreeSet<VertexIndex> = odd_roots.iter().map(|&vertex_index| self.find(vertex_index)).collect();
+            odd_roots = roots
+                .into_iter()
+                .filter(|&root| self.clusters[root as usize].as_ref().unwrap().is_odd())
+                .collect();
+            if odd_roots.is_empty() {
+                break;
+            }
+            // the edges from the odd clusters to the outside, and how fast each of them grows
+            let mut boundary_edges = vec![];
+            for &root in odd_roots.iter() {
+                for &vertex_index in self.clusters[root as usize].as_ref().unwrap().vertices.iter() {
+                    for &(peer_index, edge_index) in self.neighbors[vertex_index as usize].iter() {
+                        boundary_edges.push((root, peer_index, edge_index));
+                    }
+                }
+            }
+            let mut length = Weight::MAX;
+            boundary_edges.retain(|&(root, peer_index, edge_index)| {
+                let peer_root = self.find(peer_index);
+                if peer_root == root {
+                    return false; // an edge inside the cluster
+                }
+                let peer_is_odd = self.clusters[peer_root as usize]
+                    .as_ref()
+                    .is_some_and(|cluster| cluster.is_odd());
+                let speed = if peer_is_odd { 2 } else { 1 };
+                let remaining = (self.weights[edge_index as usize] - self.growth[edge_index as usize]).max(0);
+                // rounded up, so that an edge grown from both sides may overshoot its weight by 1
+                length = length.min((remaining + speed - 1) / speed);
+                true
+            });
+            if boundary_edges.is_empty() {
+                return false; // an odd cluster cannot grow any more, e.g. an odd number of defects without boundary
+            }
+            for &(root, peer_index, edge_index) in boundary_edges.iter() {
+                if self.growth[edge_index as usize] == 0 && length > 0 {
+                    self.touched_edges.push(edge_index);
+                }
+                self.growth[edge_index as usize] += length;
+                if self.growth[edge_index as usize] >= self.weights[edge_index as usize] {
+                    let root = self.union(root, peer_index);
+                    if self.clusters[root as usize].as_ref().unwrap().defect_num > self.max_cluster_defects {
+                        return false;
+                    }
+                }
+            }
+        }
+        self.peel();
+        true
+    }
+
+    /// the total weight of the correction of the current syndrome
+    #[allow(clippy::unnecessary_cast)]
+    pub fn subgraph_weight(&self) -> Weight {
+        self.subgraph
+            .iter()
+            .map(|&edge_index| self.weights[edge_index as usize])
+            .sum()
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn set_weight(&mut self, edge_index: EdgeIndex, weight: Weight) {
+        self.touched_edges.push(edge_index);
+        self.weights[edge_index as usize] = weight;
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    fn find(&mut self, vertex_index: VertexIndex) -> VertexIndex {
+        let mut root = vertex_index;
+        while self.parent[root as usize] != root {
+            root = self.parent[root as usize];
+        }
+        let mut vertex_index = vertex_index;
+        while vertex_index != root {
+            let next = self.parent[vertex_index as usize];
+            self.parent[vertex_index as usize] = root;
+            vertex_index = next;
+        }
+        root
+    }
+
+    /// merge the cluster of `vertex_1` with the cluster of `vertex_2` by size, where a vertex out of any cluster joins as
+    /// a new cluster; returns the root of the merged cluster. A virtual vertex validates the cluster but never merges
+    /// two clusters, as if each cluster had a boundary of its own
+    #[allow(clippy::unnecessary_cast)]
+    fn union(&mut self, vertex_1: VertexIndex, vertex_2: VertexIndex) -> VertexIndex {
+        let (mut root_1, mut root_2) = (self.find(vertex_1), self.find(vertex_2));
+        if self.is_virtual[vertex_2 as usize] {
+            let cluster = self.clusters[root_1 as usize].as_mut().unwrap();
+            cluster.touches_boundary = true;
+            cluster.vertices.push(vertex_2);
+            return root_1;
+        }
+        if root_1 == root_2 {
+            return root_1;
+        }
+        if self.clusters[root_2 as usize].is_none() {
+            self.touched_vertices.push(root_2);
+            self.clusters[root_2 as usize] = Some(UnionFindCluster::new(0, root_2));
+        }
+        let size = |root: VertexIndex| self.clusters[root as usize].as_ref().unwrap().vertices.len();
+        if size(root_1) < size(root_2) {
+            std::mem::swap(&mut root_1, &mut root_2);
+        }
+        let cluster_2 = self.clusters[root_2 as usize].take().unwrap();
+        self.parent[root_2 as usize] = root_1;
+        let cluster_1 = self.clusters[root_1 as usize].as_mut().unwrap();
+        cluster_1.defect_num += cluster_2.defect_num;
+        cluster_1.touches_boundary |= cluster_2.touches_boundary;
+        cluster_1.vertices.extend(cluster_2.vertices);
+        root_1
+    }
+
+    /// peel each cluster from a spanning forest of its fully grown edges, rooted at its virtual vertices if any: from the
+    /// leaves, a defect passes its parity along the tree edge to the parent until it meets another defect, forming a
+    /// peer matching, or reaches a virtual vertex
+    #[allow(clippy::unnecessary_cast)]
+    fn peel(&mut self) {
+        let roots: Vec<VertexIndex> = self
+            .touched_vertices
+            .iter()
+            .cloned()
+            .filter(|&vertex_index| self.clusters[vertex_index as usize].is_some())
+            .collect();
+        // the defect vertex whose parity is carried by each vertex
+        let mut parities: HashMap<VertexIndex, VertexIndex> = self
+            .defect_vertices
+            .iter()
+            .map(|&vertex_index| (vertex_index, vertex_index))
+            .collect();
+        for root in roots {
+            let cluster = self.clusters[root as usize].as_ref().unwrap();
+            let members: HashSet<VertexIndex> = cluster.vertices.iter().cloned().collect();
+            let virtual_vertices: BTreeSet<VertexIndex> = cluster
+                .vertices
+                .iter()
+                .cloned()
+                .filter(|&vertex_index| self.is_virtual[vertex_index as usize])
+                .collect();
+            let mut sources: Vec<VertexIndex> = virtual_vertices.into_iter().collect();
+            if sources.is_empty() {
+                sources.push(cluster.vertices[0]);
+            }
+            // the tree edge to the parent of each vertex, in the breadth-first order
+            let mut tree_edges: HashMap<VertexIndex, (VertexIndex, EdgeIndex)> = HashMap::new();
+            let mut order = vec![];
+            let mut queue: VecDeque<VertexIndex> = sources.iter().cloned().collect();
+            let mut visited: HashSet<VertexIndex> = sources.iter().cloned().collect();
+            while let Some(vertex_index) = queue.pop_front() {
+                order.push(vertex_index);
+                for &(peer_index, edge_index) in self.neighbors[vertex_index as usize].iter() {
+                    let is_grown = self.growth[edge_index as usize] >= self.weights[edge_index as usize];
+                    if is_grown && members.contains(&peer_index) && visited.insert(peer_index) {
+                        tree_edges.insert(peer_index, (vertex_index, edge_index));
+                        queue.push_back(peer_index);
+                    }
+                }
+            }
+            for &vertex_index in order.iter().rev() {
+                let Some(&(parent_index, edge_index)) = tree_edges.get(&vertex_index) else {
+                    continue; // a source
+                };
+                if let Some(defect_vertex) = parities.remove(&vertex_index) {
+                    self.subgraph.push(edge_index);
+                    match parities.remove(&parent_index) {
+                        Some(peer_vertex) => self.peer_matchings.push((peer_vertex, defect_vertex)),
+                        None => {
+                            parities.insert(parent_index, defect_vertex);
+                        }
+                    }
+                }
+            }
+            for source in sources {
+                if let Some(defect_vertex) = parities.remove(&source) {
+                    assert!(
+                        self.is_virtual[source as usize],
+                        "a valid cluster must be even or touch the boundary"
+                    );
+                    self.virtual_matchings.push((defect_vertex, source));
+                }
+            }
+        }
+        self.subgraph.sort_unstable();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::primal_module::*;
+    use super::*;
+
+    #[test]
+    fn primal_module_union_find_1() {
+        // cargo test primal_module_union_find_1 -- --nocapture
+        // vertices 0..6 form a chain between the virtual vertices 7 (left) and 6 (right), each edge of weight 1000
+        let code = CodeCapacityRepetitionCode::new(7, 0.1, 500);
+        let mut primal_module = PrimalModuleUnionFind::new(&code.get_initializer());
+        assert!(primal_module.solve(&SyndromePattern::new_vertices(vec![1, 4])));
+        assert_eq!(primal_module.peer_matchings.len(), 1);
+        assert_eq!(primal_module.subgraph, vec![1, 2, 3]);
+        assert_eq!(primal_module.subgraph_weight(), 3000);
+        primal_module.clear();
+        // the left boundary is closer
+        assert!(primal_module.solve(&SyndromePattern::new_vertices(vec![2])));
+        assert_eq!(primal_module.virtual_matchings, vec![(2, 7)]);
+        assert_eq!(primal_module.subgraph, vec![0, 1, 6]);
+        primal_module.clear();
+        // the two defects are in the same cluster before it becomes even
+        primal_module.max_cluster_defects = 1;
+        assert!(!primal_module.solve(&SyndromePattern::new_vertices(vec![1, 4])));
+        primal_module.clear();
+        // each defect matches to the boundary on its own
+        assert!(primal_module.solve(&SyndromePattern::new_vertices(vec![0, 5])));
+        assert_eq!(primal_module.virtual_matchings, vec![(0, 7), (5, 6)]);
+    }
+
+    /// the correction always generates the syndrome, and it's never lighter than the minimum-weight one
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn primal_module_union_find_2() {
+        // cargo test primal_module_union_find_2 -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(7, 6, 0.02, 500);
+        code.set_erasure_probability(0.01);
+        let initializer = code.get_initializer();
+        let mut primal_module = PrimalModuleUnionFind::new(&initializer);
+        let mut subgraph_builder = SubGraphBuilder::new(&initializer);
+        for seed in 0..100 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            assert!(primal_module.solve(&syndrome_pattern));
+            subgraph_builder.load_subgraph(&primal_module.subgraph);
+            let expected: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+            assert_eq!(subgraph_builder.subgraph_boundary(), expected, "seed {seed}");
+            let mut matched: Vec<VertexIndex> = primal_module
+                .peer_matchings
+                .iter()
+                .flat_map(|&(vertex_1, vertex_2)| [vertex_1, vertex_2])
+                .chain(primal_module.virtual_matchings.iter().map(|&(vertex_index, _)| vertex_index))
+                .collect();
+            matched.sort_unstable();
+            assert_eq!(matched, expected.into_iter().collect::<Vec<_>>());
+            let optimal_weight = {
+                let mut initializer = initializer.clone();
+                for &edge_index in syndrome_pattern.erasures.iter() {
+                    initializer.weighted_edges[edge_index as usize].2 = 0;
+                }
+                let mwpm_result = super::super::blossom_rs_mwpm(&initializer, &syndrome_pattern.defect_vertices);
+                super::super::detailed_matching(&initializer, &syndrome_pattern.defect_vertices, &mwpm_result)
+                    .iter()
+                    .map(|detail| detail.weight)
+                    .sum::<Weight>()
+            };
+            assert!(primal_module.subgraph_weight() >= optimal_weight);
+            primal_module.clear();
+        }
+        // an odd number of defects cannot be matched without boundary
+        let code = CodeCapacityToricCode::new(5, 0.1, 500);
+        let mut primal_module = PrimalModuleUnionFind::new(&code.get_initializer());
+        assert!(!primal_module.solve(&SyndromePattern::new_vertices(vec![0, 7, 13])));
+    }
+}
@@ -0,0 +1,194 @@
+//! Reproduction
+//!
+//! A single JSON file with everything needed to rerun the last solve of a solver: the decoding graph, the partition,
+//! the effective solver config, the syndrome and the version that produced it, see [`PrimalDualSolver::export_repro`].
+//! Attach it to a bug report, and it can be rerun by [`run`] or `fusion_blossom repro <path>`
+//!
+
+use super::mwpm_solver::*;
+use super::pointers::*;
+use super::primal_module_serial::*;
+use super::util::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReproSolverType {
+    Serial,
+    DualParallel,
+    Parallel,
+    ChannelParallel,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Repro {
+    /// the version that produced the file and the hash of the solver config
+    pub fingerprint: OutputFingerprint,
+    pub solver_type: ReproSolverType,
+    pub initializer: SolverInitializer,
+    /// the partition of the parallel solvers
+    pub partition_config: Option<PartitionConfig>,
+    /// the effective config of the solver, see [`PrimalDualSolver::effective_config`]
+    pub solver_config: serde_json::Value,
+    pub syndrome_pattern: SyndromePattern,
+}
+
+/// the outcome of rerunning a [`Repro`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReproOutcome {
+    pub subgraph: Vec<EdgeIndex>,
+    pub sum_dual_variables: Weight,
+}
+
+impl Repro {
+    pub fn new(
+        solver_type: ReproSolverType,
+        initializer: &SolverInitializer,
+        partition_config: Option<&PartitionConfig>,
+        solver_config: serde_json::Value,
+        syndrome_pattern: &SyndromePattern,
+    ) -> Self {
+        Self {
+            fingerprint: OutputFingerprint::new(&solver_config),
+            solver_type,
+            initializer: initializer.clone(),
+            partition_config: partition_config.cloned(),
+            solver_config,
+            syndrome_pattern: syndrome_pattern.clone(),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let file = File::create(path)?;
+        serde_json::to_writer(BufWriter::new(file), self)?;
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> std::io::Result<Self> {
+        let file = File::open(path)?;
+        Ok(serde_json::from_reader(BufReader::new(file))?)
+    }
+
+    /// construct the solver with the recorded config, checking the parity of the subgraph against the syndrome
+    pub fn build_solver(&self) -> Box<dyn PrimalDualSolver> {
+        let partition_info = || {
+            self.partition_config
+                .as_ref()
+                .expect("parallel solver requires a partition")
+                .info()
+        };
+        let mut solver: Box<dyn PrimalDualSolver> = match self.solver_type {
+            ReproSolverType::Serial => {
+                let solver = SolverSerial::new(&self.initializer);
+                self.apply_serial_primal_config(&solver.primal_module);
+                Box::new(solver)
+            }
+            ReproSolverType::DualParallel => {
+                let solver =
+                    SolverDualParallel::new(&self.initializer, &partition_info(), self.solver_config["dual"].clone());
+                self.apply_serial_primal_config(&solver.primal_module);
+                Box::new(solver)
+            }
+            ReproSolverType::ChannelParallel => {
+                let solver =
+                    SolverChannelParallel::new(&self.initializer, &partition_info(), self.solver_config["dual"].clone());
+                self.apply_serial_primal_config(&solver.primal_module);
+                Box::new(solver)
+            }
+            ReproSolverType::Parallel => Box::new(SolverParallel::new(
+                &self.initializer,
+                &partition_info(),
+                json!({
+                    "dual": self.solver_config["dual"],
+                    "primal": self.solver_config["primal"],
+                }),
+            )),
+        };
+        solver.set_parity_check(true);
+        solver
+    }
+
+    /// the limits of a serial primal module, which are set after construction
+    fn apply_serial_primal_config(&self, primal_module: &PrimalModuleSerialPtr) {
+        let primal_config = &self.solver_config["primal"];
+        let limit = |key: &str| primal_config[key].as_u64().map_or(usize::MAX, |value| value as usize);
+        let mut primal_module = primal_module.write();
+        primal_module.max_tree_size = limit("max_tree_size");
+        primal_module.max_dual_node_count = limit("max_dual_node_count");
+        primal_module.max_blossom_depth = limit("max_blossom_depth");
+    }
+
+    /// rerun the solve, where the parity of the subgraph is checked against the syndrome
+    pub fn run(&self) -> ReproOutcome {
+        if self.fingerprint.version != env!("CARGO_PKG_VERSION") {
+            eprintln!(
+                "[warning] reproducing a solve of version {} with version {}",
+                self.fingerprint.version,
+                env!("CARGO_PKG_VERSION")
+            );
+        }
+        let mut solver = self.build_solver();
+        solver.solve(&self.syndrome_pattern);
+        ReproOutcome {
+            subgraph: solver.subgraph(),
+            sum_dual_variables: solver.sum_dual_variables(),
+        }
+    }
+}
+
+/// load a file written by [`PrimalDualSolver::export_repro`] and rerun it
+pub fn run(path: &str) -> ReproOutcome {
+    let repro = Repro::load(path).unwrap_or_else(|error| panic!("cannot load reproduction {path}: {error}"));
+    repro.run()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::example_partition::*;
+    use super::super::visualize::*;
+    use super::*;
+
+    #[test]
+    fn repro_export_run_1() {
+        // cargo test repro_export_run_1 -- --nocapture
+        let mut code = PhenomenologicalPlanarCode::new(5, 4, 0.05, 500);
+        let partition_config = PhenomenologicalPlanarCodeTimePartition::new(5, 4, 2).build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let partition_info = partition_config.info();
+        let syndrome_pattern = code.generate_random_errors(1);
+        assert!(!syndrome_pattern.defect_vertices.is_empty());
+        let solvers: Vec<Box<dyn PrimalDualSolver>> = vec![
+            Box::new(SolverSerial::new(&initializer)),
+            Box::new(SolverDualParallel::new(&initializer, &partition_info, json!({}))),
+            Box::new(SolverChannelParallel::new(&initializer, &partition_info, json!({}))),
+            Box::new(SolverParallel::new(
+                &initializer,
+                &partition_info,
+                json!({ "primal": { "max_tree_size": 10 } }),
+            )),
+        ];
+        for (index, mut solver) in solvers.into_iter().enumerate() {
+            solver.solve(&syndrome_pattern);
+            assert!(solver.repro().is_none(), "recording is disabled by default");
+            solver.set_record_repro(true);
+            solver.clear();
+            solver.solve(&syndrome_pattern);
+            let expected = ReproOutcome {
+                subgraph: solver.subgraph(),
+                sum_dual_variables: solver.sum_dual_variables(),
+            };
+            solver.clear(); // the last syndrome is still recorded after clear
+            let path = format!("{}repro_export_run_1_{index}.json", visualize_data_folder());
+            solver.export_repro(&path).unwrap();
+            let repro = Repro::load(&path).unwrap();
+            assert_eq!(repro.fingerprint.version, env!("CARGO_PKG_VERSION"));
+            assert_eq!(repro.syndrome_pattern.defect_vertices, syndrome_pattern.defect_vertices);
+            assert_eq!(repro.solver_config, solver.effective_config());
+            assert_eq!(run(&path), expected);
+        }
+    }
+}
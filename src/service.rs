@@ -0,0 +1,377 @@
+//! Decoding Service
+//!
+//! A long-running TCP server exposing the solver to clients written in any language. The protocol is line-delimited
+//! JSON: the client first sends a [`ServiceRequest::Initialize`] with the decoding graph, and then streams
+//! [`ServiceRequest::Decode`] with syndrome patterns; every request is answered by exactly one [`ServiceResponse`] line.
+//! Each connection has its own solver, so multiple clients can decode different graphs at the same time, up to the
+//! limits in [`ServiceConfig`]. Run it with `fusion_blossom serve <address>`
+//!
+
+use super::mwpm_solver::*;
+use super::util::*;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// the limits of the server, so that misbehaving clients cannot exhaust its threads or memory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServiceConfig {
+    /// the maximum number of connections served at the same time, each by its own thread; a client connecting beyond
+    /// this limit receives an error and is disconnected
+    #[serde(default = "service_default_configs::max_connections")]
+    pub max_connections: usize,
+    /// the maximum length of a request line in bytes; a longer request is discarded without buffering it and answered
+    /// by an error
+    #[serde(default = "service_default_configs::max_request_length")]
+    pub max_request_length: usize,
+}
+
+impl Default for ServiceConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+pub mod service_default_configs {
+    pub fn max_connections() -> usize {
+        64
+    }
+    pub fn max_request_length() -> usize {
+        256 * 1024 * 1024 // a large decoding graph is sent in a single `Initialize` line
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceRequest {
+    /// build the solver of this connection; a partition config selects the parallel solver
+    Initialize {
+        initializer: SolverInitializer,
+        #[serde(default)]
+        partition_config: Option<PartitionConfig>,
+        /// the config of the parallel solver, see [`SolverParallel::new`]
+        #[serde(default)]
+        solver_config: Option<serde_json::Value>,
+    },
+    /// decode a syndrome pattern and reply with the correction
+    Decode { syndrome_pattern: SyndromePattern },
+    /// reply with the latency statistics of this connection
+    Statistics,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceResponse {
+    Initialized,
+    Correction {
+        subgraph: Vec<EdgeIndex>,
+        /// the decoding time of this syndrome pattern in seconds, not including the network
+        latency: f64,
+    },
+    Statistics(LatencyStatistics),
+    Error {
+        message: String,
+    },
+}
+
+/// the decoding latency of the syndrome patterns of a connection, in seconds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyStatistics {
+    pub count: usize,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl LatencyStatistics {
+    pub fn record(&mut self, latency: f64) {
+        if self.count == 0 || latency < self.min {
+            self.min = latency;
+        }
+        if latency > self.max {
+            self.max = latency;
+        }
+        self.count += 1;
+        self.sum += latency;
+    }
+
+    pub fn average(&self) -> f64 {
+        if self.count == 0 {
+            0.
+        } else {
+            self.sum / self.count as f64
+        }
+    }
+}
+
+/// the state of a connection
+#[derive(Default)]
+pub struct ServiceSession {
+    pub solver: Option<Box<dyn PrimalDualSolver + Send>>,
+    pub statistics: LatencyStatistics,
+}
+
+impl ServiceSession {
+    pub fn handle(&mut self, request: ServiceRequest) -> ServiceResponse {
+        match request {
+            ServiceRequest::Initialize {
+                initializer,
+                partition_config,
+                solver_config,
+            } => {
+                self.solver = Some(match partition_config {
+                    Some(partition_config) => Box::new(SolverParallel::new(
+                        &initializer,
+                        &partition_config.info(),
+                        solver_config.unwrap_or(json!({})),
+                    )),
+                    None => Box::new(SolverSerial::new(&initializer)),
+                });
+                self.statistics = LatencyStatistics::default();
+                ServiceResponse::Initialized
+            }
+            ServiceRequest::Decode { syndrome_pattern } => {
+                let Some(solver) = self.solver.as_mut() else {
+                    return ServiceResponse::Error {
+                        message: "the solver is not initialized".to_string(),
+                    };
+                };
+                let begin = Instant::now();
+                solver.solve(&syndrome_pattern);
+                let subgraph = solver.subgraph();
+                solver.clear();
+                let latency = begin.elapsed().as_secs_f64();
+                self.statistics.record(latency);
+                ServiceResponse::Correction { subgraph, latency }
+            }
+            ServiceRequest::Statistics => ServiceResponse::Statistics(self.statistics.clone()),
+        }
+    }
+
+    /// serve a connection until the client closes it
+    pub fn serve(stream: TcpStream, max_request_length: usize) -> std::io::Result<()> {
+        stream.set_nodelay(true)?; // the messages are small and latency sensitive
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = BufWriter::new(stream);
+        let mut session = Self::default();
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let length = (&mut reader)
+                .take(max_request_length as u64 + 1)
+                .read_until(b'\n', &mut line)?;
+            if length == 0 {
+                break; // closed by the client
+            }
+            if line.last() != Some(&b'\n') && length > max_request_length {
+                skip_line(&mut reader)?;
+                let response = ServiceResponse::Error {
+                    message: format!("the request exceeds {max_request_length} bytes"),
+                };
+                write_response(&mut writer, &response)?;
+                continue;
+            }
+            let line = String::from_utf8_lossy(&line);
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<ServiceRequest>(&line) {
+                Ok(request) => match std::panic::catch_unwind(AssertUnwindSafe(|| session.handle(request))) {
+                    Ok(response) => response,
+                    Err(_) => {
+                        session.solver = None; // the solver may be left in an inconsistent state
+                        ServiceResponse::Error {
+                            message: "the solver panicked on this request, please initialize again".to_string(),
+                        }
+                    }
+                },
+                Err(error) => ServiceResponse::Error {
+                    message: format!("invalid request: {error}"),
+                },
+            };
+            write_response(&mut writer, &response)?;
+        }
+        Ok(())
+    }
+}
+
+/// discard the rest of a line without buffering it
+fn skip_line(reader: &mut impl BufRead) -> std::io::Result<()> {
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(());
+        }
+        if let Some(position) = buffer.iter().position(|&byte| byte == b'\n') {
+            reader.consume(position + 1);
+            return Ok(());
+        }
+        let length = buffer.len();
+        reader.consume(length);
+    }
+}
+
+fn write_response(writer: &mut impl Write, response: &ServiceResponse) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, response)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// accept connections forever, each served by a separate thread
+pub fn serve(address: impl ToSocketAddrs, config: ServiceConfig) -> std::io::Result<()> {
+    serve_listener(TcpListener::bind(address)?, config)
+}
+
+/// accept connections forever; a failed accept is reported and skipped, so that it doesn't bring down the server
+pub fn serve_listener(listener: TcpListener, config: ServiceConfig) -> std::io::Result<()> {
+    let connections = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("[service] failed to accept a connection: {error}");
+                continue;
+            }
+        };
+        if connections.fetch_add(1, Ordering::SeqCst) >= config.max_connections {
+            connections.fetch_sub(1, Ordering::SeqCst);
+            let response = ServiceResponse::Error {
+                message: format!(
+                    "the server is busy with {} connections, please retry later",
+                    config.max_connections
+                ),
+            };
+            if let Err(error) = write_response(&mut &stream, &response) {
+                eprintln!("[service] failed to reject a connection: {error}");
+            }
+            continue;
+        }
+        let connections = connections.clone();
+        let max_request_length = config.max_request_length;
+        std::thread::spawn(move || {
+            if let Err(error) = ServiceSession::serve(stream, max_request_length) {
+                eprintln!("[service] connection closed with error: {error}");
+            }
+            connections.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::*;
+
+    #[test]
+    fn service_decode_1() {
+        // cargo test service_decode_1 -- --nocapture
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || serve_listener(listener, ServiceConfig::default()));
+        let code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let initializer = code.get_initializer();
+        let stream = TcpStream::connect(address).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        let mut request = |request: &ServiceRequest| -> ServiceResponse {
+            writeln!(writer, "{}", serde_json::to_string(request).unwrap()).unwrap();
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            serde_json::from_str(&line).unwrap()
+        };
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![16, 17, 23, 25]);
+        assert!(matches!(
+            request(&ServiceRequest::Decode {
+                syndrome_pattern: syndrome_pattern.clone()
+            }),
+            ServiceResponse::Error { .. }
+        ));
+        assert!(matches!(
+            request(&ServiceRequest::Initialize {
+                initializer: initializer.clone(),
+                partition_config: None,
+                solver_config: None,
+            }),
+            ServiceResponse::Initialized
+        ));
+        let mut solver = SolverSerial::new(&initializer);
+        for _ in 0..3 {
+            let ServiceResponse::Correction { subgraph, latency } = request(&ServiceRequest::Decode {
+                syndrome_pattern: syndrome_pattern.clone(),
+            }) else {
+                panic!("expect a correction");
+            };
+            assert!(latency >= 0.);
+            solver.solve(&syndrome_pattern);
+            assert_eq!(subgraph, solver.subgraph());
+            solver.clear();
+        }
+        let ServiceResponse::Statistics(statistics) = request(&ServiceRequest::Statistics) else {
+            panic!("expect statistics");
+        };
+        assert_eq!(statistics.count, 3);
+        assert!(statistics.min <= statistics.average() && statistics.average() <= statistics.max);
+        // an invalid syndrome pattern doesn't bring down the server
+        let response = request(&ServiceRequest::Decode {
+            syndrome_pattern: SyndromePattern::new_vertices(vec![1000]),
+        });
+        assert!(matches!(response, ServiceResponse::Error { .. }));
+        assert!(matches!(request(&ServiceRequest::Statistics), ServiceResponse::Statistics(_)));
+    }
+    #[test]
+    fn service_limits_1() {
+        // cargo test service_limits_1 -- --nocapture
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let config = ServiceConfig {
+            max_connections: 1,
+            max_request_length: 64,
+        };
+        std::thread::spawn(move || serve_listener(listener, config));
+        let read_response = |reader: &mut BufReader<TcpStream>| -> Option<ServiceResponse> {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            (!line.is_empty()).then(|| serde_json::from_str(&line).unwrap())
+        };
+        let stream = TcpStream::connect(address).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        writeln!(writer, "{}", serde_json::to_string(&ServiceRequest::Statistics).unwrap()).unwrap();
+        assert!(matches!(read_response(&mut reader), Some(ServiceResponse::Statistics(_))));
+        // the second connection is rejected while the first one is open
+        let rejected = TcpStream::connect(address).unwrap();
+        let mut rejected_reader = BufReader::new(rejected);
+        assert!(matches!(
+            read_response(&mut rejected_reader),
+            Some(ServiceResponse::Error { .. })
+        ));
+        assert!(read_response(&mut rejected_reader).is_none());
+        // a request that is too long is answered by an error, without breaking the following requests
+        writeln!(writer, "{}", " ".repeat(100000)).unwrap();
+        assert!(matches!(read_response(&mut reader), Some(ServiceResponse::Error { .. })));
+        writeln!(writer, "{}", serde_json::to_string(&ServiceRequest::Statistics).unwrap()).unwrap();
+        assert!(matches!(read_response(&mut reader), Some(ServiceResponse::Statistics(_))));
+        // closing the connection frees the slot for a new one
+        drop(reader);
+        drop(writer);
+        let mut accepted = None;
+        for _ in 0..100 {
+            let stream = TcpStream::connect(address).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            writeln!(writer, "{}", serde_json::to_string(&ServiceRequest::Statistics).unwrap()).unwrap();
+            accepted = read_response(&mut reader);
+            if matches!(accepted, Some(ServiceResponse::Statistics(_))) {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10)); // the served thread may not have exited yet
+        }
+        assert!(matches!(accepted, Some(ServiceResponse::Statistics(_))));
+    }
+}
@@ -0,0 +1,315 @@
+//! Syndrome File
+//!
+//! Files of syndrome patterns written by [`crate::mwpm_solver::SolverErrorPatternLogger`] and read by
+//! [`crate::example_codes::ErrorPatternReader`]. Both start with the decoding graph and the vertex positions, followed by
+//! any number of syndrome patterns, in one of two formats:
+//! - text (`Syndrome Pattern v1.0`): one JSON object per line, easy to inspect but large for long runs
+//! - binary: the magic bytes [`BINARY_MAGIC`] and a varint version, the graph and the positions as length-prefixed
+//!   JSON, and then each syndrome pattern as varint-encoded lists. The indices of a list are stored as zigzag deltas
+//!   from the previous one, which keeps the order of the list and takes a single byte for nearby sorted indices
+//!
+//! The reader detects the format from the beginning of the file
+//!
+
+use super::util::*;
+use super::visualize::*;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+
+/// the first line of a text syndrome file
+pub const TEXT_HEADER: &str = "Syndrome Pattern v1.0   <initializer> <positions> <syndrome_pattern>*";
+/// the first bytes of a binary syndrome file
+pub const BINARY_MAGIC: &[u8; 8] = b"\x89FBSYND\n";
+/// the version of the binary format written by [`SyndromeFileWriter`]
+pub const BINARY_VERSION: u64 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyndromeFileFormat {
+    Text,
+    Binary,
+}
+
+impl SyndromeFileFormat {
+    /// binary for files ending with `.bin`, otherwise text
+    pub fn from_filename(filename: &str) -> Self {
+        if filename.ends_with(".bin") {
+            Self::Binary
+        } else {
+            Self::Text
+        }
+    }
+}
+
+pub struct SyndromeFileWriter {
+    pub format: SyndromeFileFormat,
+    pub file: BufWriter<File>,
+}
+
+impl SyndromeFileWriter {
+    pub fn create(
+        filename: &str,
+        format: SyndromeFileFormat,
+        initializer: &SolverInitializer,
+        positions: &Vec<VisualizePosition>,
+    ) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(filename)?);
+        match format {
+            SyndromeFileFormat::Text => {
+                file.write_all(TEXT_HEADER.as_bytes())?;
+                file.write_all(b"\n")?;
+                serde_json::to_writer(&mut file, &initializer)?; // large object write to file directly
+                file.write_all(b"\n")?;
+                serde_json::to_writer(&mut file, &positions)?;
+                file.write_all(b"\n")?;
+            }
+            SyndromeFileFormat::Binary => {
+                file.write_all(BINARY_MAGIC)?;
+                write_varint(&mut file, BINARY_VERSION)?;
+                for value in [serde_json::to_vec(initializer)?, serde_json::to_vec(positions)?] {
+                    write_varint(&mut file, value.len() as u64)?;
+                    file.write_all(&value)?;
+                }
+            }
+        }
+        Ok(Self { format, file })
+    }
+
+    #[allow(clippy::unnecessary_cast)]
+    pub fn write(&mut self, syndrome_pattern: &SyndromePattern) -> io::Result<()> {
+        match self.format {
+            SyndromeFileFormat::Text => {
+                serde_json::to_writer(&mut self.file, syndrome_pattern)?;
+                self.file.write_all(b"\n")
+            }
+            SyndromeFileFormat::Binary => {
+                let file = &mut self.file;
+                write_indices(file, syndrome_pattern.defect_vertices.iter().map(|&x| x as u64))?;
+                write_indices(file, syndrome_pattern.erasures.iter().map(|&x| x as u64))?;
+                write_indices(file, syndrome_pattern.dynamic_weights.iter().map(|&(x, _)| x as u64))?;
+                for &(_, weight) in syndrome_pattern.dynamic_weights.iter() {
+                    write_varint(file, zigzag(weight as i64))?;
+                }
+                write_indices(file, syndrome_pattern.detector_confidences.iter().map(|&(x, _)| x as u64))?;
+                for &(_, confidence) in syndrome_pattern.detector_confidences.iter() {
+                    file.write_all(&confidence.to_le_bytes())?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// the content of a syndrome file in either format
+pub struct SyndromeFileReader {
+    pub format: SyndromeFileFormat,
+    pub initializer: SolverInitializer,
+    pub positions: Vec<VisualizePosition>,
+    reader: BufReader<File>,
+}
+
+impl SyndromeFileReader {
+    pub fn open(filename: &str) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(filename)?);
+        let is_binary = reader.fill_buf()?.starts_with(BINARY_MAGIC);
+        if is_binary {
+            reader.consume(BINARY_MAGIC.len());
+            let version = read_varint(&mut reader)?;
+            if version != BINARY_VERSION {
+                return Err(invalid_data(format!("unsupported binary syndrome file version {version}")));
+            }
+            let mut read_json = || -> io::Result<Vec<u8>> {
+                let mut value = vec![0; read_varint(&mut reader)? as usize];
+                reader.read_exact(&mut value)?;
+                Ok(value)
+            };
+            let initializer = serde_json::from_slice(&read_json()?)?;
+            let positions = serde_json::from_slice(&read_json()?)?;
+            Ok(Self {
+                format: SyndromeFileFormat::Binary,
+                initializer,
+                positions,
+                reader,
+            })
+        } else {
+            let mut read_line = || -> io::Result<String> {
+                let mut line = String::new();
+                reader.read_line(&mut line)?;
+                Ok(line)
+            };
+            if !read_line()?.starts_with("Syndrome Pattern v1.0 ") {
+                return Err(invalid_data("incompatible file version".to_string()));
+            }
+            let initializer = serde_json::from_str(&read_line()?)?;
+            let positions = serde_json::from_str(&read_line()?)?;
+            Ok(Self {
+                format: SyndromeFileFormat::Text,
+                initializer,
+                positions,
+                reader,
+            })
+        }
+    }
+
+    /// read the next syndrome pattern, or `None` at the end of the file
+    #[allow(clippy::unnecessary_cast)]
+    pub fn read(&mut self) -> io::Result<Option<SyndromePattern>> {
+        match self.format {
+            SyndromeFileFormat::Text => loop {
+                let mut line = String::new();
+                if self.reader.read_line(&mut line)? == 0 {
+                    return Ok(None);
+                }
+                if !line.trim().is_empty() {
+                    return Ok(Some(serde_json::from_str(&line)?));
+                }
+            },
+            SyndromeFileFormat::Binary => {
+                if self.reader.fill_buf()?.is_empty() {
+                    return Ok(None);
+                }
+                let reader = &mut self.reader;
+                let mut syndrome_pattern =
+                    SyndromePattern::new_vertices(read_indices(reader)?.into_iter().map(|x| x as VertexIndex).collect());
+                syndrome_pattern.erasures = read_indices(reader)?.into_iter().map(|x| x as EdgeIndex).collect();
+                for edge_index in read_indices(reader)? {
+                    let weight = unzigzag(read_varint(reader)?) as Weight;
+                    syndrome_pattern.dynamic_weights.push((edge_index as EdgeIndex, weight));
+                }
+                for vertex_index in read_indices(reader)? {
+                    let mut bytes = [0; 8];
+                    reader.read_exact(&mut bytes)?;
+                    syndrome_pattern
+                        .detector_confidences
+                        .push((vertex_index as VertexIndex, f64::from_le_bytes(bytes)));
+                }
+                Ok(Some(syndrome_pattern))
+            }
+        }
+    }
+
+    /// read all the remaining syndrome patterns
+    pub fn read_all(&mut self) -> io::Result<Vec<SyndromePattern>> {
+        let mut syndrome_patterns = vec![];
+        while let Some(syndrome_pattern) = self.read()? {
+            syndrome_patterns.push(syndrome_pattern);
+        }
+        Ok(syndrome_patterns)
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// LEB128: 7 bits per byte, with the highest bit set on all but the last byte
+pub fn write_varint(writer: &mut impl Write, mut value: u64) -> io::Result<()> {
+    while value >= 0x80 {
+        writer.write_all(&[(value as u8) | 0x80])?;
+        value >>= 7;
+    }
+    writer.write_all(&[value as u8])
+}
+
+pub fn read_varint(reader: &mut impl Read) -> io::Result<u64> {
+    let mut value = 0;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        value |= ((byte[0] & 0x7f) as u64) << shift;
+        if byte[0] < 0x80 {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data("varint is too long".to_string()))
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// the length followed by the zigzag delta of each index from the previous one
+fn write_indices(writer: &mut impl Write, indices: impl ExactSizeIterator<Item = u64>) -> io::Result<()> {
+    write_varint(writer, indices.len() as u64)?;
+    let mut previous = 0;
+    for index in indices {
+        write_varint(writer, zigzag(index.wrapping_sub(previous) as i64))?;
+        previous = index;
+    }
+    Ok(())
+}
+
+fn read_indices(reader: &mut impl Read) -> io::Result<Vec<u64>> {
+    let length = read_varint(reader)? as usize;
+    let mut indices = Vec::with_capacity(length);
+    let mut previous: u64 = 0;
+    for _ in 0..length {
+        previous = previous.wrapping_add(unzigzag(read_varint(reader)?) as u64);
+        indices.push(previous);
+    }
+    Ok(indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::*;
+
+    #[test]
+    fn syndrome_file_varint_1() {
+        // cargo test syndrome_file_varint_1 -- --nocapture
+        let mut buffer = vec![];
+        let values = [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX];
+        for value in values {
+            write_varint(&mut buffer, value).unwrap();
+        }
+        assert_eq!(&buffer[..5], &[0, 1, 127, 0x80, 1]);
+        let mut reader = buffer.as_slice();
+        for value in values {
+            assert_eq!(read_varint(&mut reader).unwrap(), value);
+        }
+        for value in [0, 1, -1, i64::MAX, i64::MIN] {
+            assert_eq!(unzigzag(zigzag(value)), value);
+        }
+    }
+
+    #[test]
+    fn syndrome_file_binary_1() {
+        // cargo test syndrome_file_binary_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(11, 0.1, 500);
+        let initializer = code.get_initializer();
+        let positions = code.get_positions();
+        let mut syndrome_patterns: Vec<SyndromePattern> = (0..20).map(|seed| code.generate_random_errors(seed)).collect();
+        syndrome_patterns[0].defect_vertices = vec![100, 3, 50];
+        syndrome_patterns[1].erasures = vec![7, 2];
+        syndrome_patterns[1].dynamic_weights = vec![(5, 100), (1, -2)];
+        syndrome_patterns[2].detector_confidences = vec![(4, 0.25)];
+        let mut sizes = vec![];
+        for format in [SyndromeFileFormat::Text, SyndromeFileFormat::Binary] {
+            let filename = format!("tmp/syndrome_file_binary_1.{format:?}");
+            std::fs::create_dir_all("tmp").unwrap();
+            let mut writer = SyndromeFileWriter::create(&filename, format, &initializer, &positions).unwrap();
+            for syndrome_pattern in syndrome_patterns.iter() {
+                writer.write(syndrome_pattern).unwrap();
+            }
+            drop(writer);
+            let mut reader = SyndromeFileReader::open(&filename).unwrap();
+            assert_eq!(reader.format, format);
+            assert_eq!(reader.initializer.weighted_edges, initializer.weighted_edges);
+            assert_eq!(reader.positions.len(), positions.len());
+            let read_patterns = reader.read_all().unwrap();
+            assert_eq!(json!(read_patterns), json!(syndrome_patterns));
+            sizes.push(std::fs::metadata(&filename).unwrap().len());
+        }
+        println!("text vs binary file size: {sizes:?}");
+    }
+}
@@ -1,11 +1,13 @@
 use super::mwpm_solver::PrimalDualSolver;
 use super::pointers::*;
 use super::rand_xoshiro;
+use super::visualize::*;
 use crate::rand_xoshiro::rand_core::RngCore;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeSet;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs::File;
 use std::io::prelude::*;
 use std::time::Instant;
@@ -40,6 +42,37 @@ cfg_if::cfg_if! {
     }
 }
 
+/// enter a `tracing` span with the given name and fields until the end of the current scope when the `tracing_spans`
+/// feature is enabled; otherwise it expands to nothing and the fields are not evaluated
+#[cfg(feature = "tracing_spans")]
+macro_rules! trace_span {
+    ($($span:tt)*) => {
+        let _trace_span_guard = tracing::info_span!($($span)*).entered();
+    };
+}
+#[cfg(not(feature = "tracing_spans"))]
+macro_rules! trace_span {
+    ($($span:tt)*) => {};
+}
+pub(crate) use trace_span;
+
+/// report a diagnostic message on demand, e.g. when a `verbose` config is set: a `tracing` debug event when the
+/// `tracing_spans` feature is enabled, so that the subscriber of the application decides where it goes, and stderr
+/// otherwise; never stdout, which may carry piped JSON output
+#[cfg(feature = "tracing_spans")]
+macro_rules! diagnostic {
+    ($($arg:tt)*) => {
+        tracing::debug!($($arg)*)
+    };
+}
+#[cfg(not(feature = "tracing_spans"))]
+macro_rules! diagnostic {
+    ($($arg:tt)*) => {
+        eprintln!($($arg)*)
+    };
+}
+pub(crate) use diagnostic;
+
 #[cfg(feature = "python_binding")]
 macro_rules! bind_trait_python_json {
     ($struct_name:ident) => {
@@ -72,6 +105,50 @@ pub struct SolverInitializer {
     /// the virtual vertices
     #[cfg_attr(feature = "python_binding", pyo3(get, set))]
     pub virtual_vertices: Vec<VertexIndex>,
+    /// the virtual vertices that cost a boundary weight to match into, e.g. a lossy boundary; they must not be listed in
+    /// `virtual_vertices` as well. Only [`crate::dual_module_serial::DualModuleSerial`] and [`crate::primal_module::SubGraphBuilder`]
+    /// support them, see [`SolverInitializer::expand_weighted_virtual_vertices`]
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub virtual_vertices_weighted: Vec<(VertexIndex, Weight)>,
+    /// the logical observables flipped by each edge, if known, see [`SolverInitializer::with_observables`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observables: Option<ObservableMasks>,
+}
+
+/// the logical observables flipped by each edge, e.g. the `L` targets of the error mechanisms in a stim detector error
+/// model, so that the observables flipped by a correction can be predicted without knowing the code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObservableMasks {
+    pub observable_num: usize,
+    /// bit `i` of `edge_masks[edge_index]` is set if the edge flips observable `i`
+    pub edge_masks: Vec<u64>,
+}
+
+impl ObservableMasks {
+    pub fn new(observable_num: usize, edge_masks: Vec<u64>) -> Self {
+        assert!(observable_num <= 64, "too many observables");
+        assert!(
+            edge_masks
+                .iter()
+                .all(|&mask| observable_num == 64 || mask >> observable_num == 0),
+            "edge mask has bits beyond {} observables",
+            observable_num
+        );
+        Self {
+            observable_num,
+            edge_masks,
+        }
+    }
+
+    /// the observables flipped by the correction, given as the edges of a subgraph
+    #[allow(clippy::unnecessary_cast)]
+    pub fn predict(&self, subgraph: &[EdgeIndex]) -> Vec<bool> {
+        let mask = subgraph
+            .iter()
+            .fold(0, |mask, &edge_index| mask ^ self.edge_masks[edge_index as usize]);
+        (0..self.observable_num).map(|i| mask & (1 << i) != 0).collect()
+    }
 }
 
 #[cfg(feature = "python_binding")]
@@ -94,6 +171,45 @@ pub struct SyndromePattern {
     #[cfg_attr(feature = "python_binding", pyo3(get, set))]
     #[serde(default = "default_dynamic_weights")]
     pub dynamic_weights: Vec<(EdgeIndex, Weight)>,
+    /// analog classification confidence of detectors in `[0, 1]`, which scales the weights of the time-like edges
+    /// incident to the detector; must be converted into `dynamic_weights` by [`SyndromePattern::apply_detector_confidences`]
+    /// before being loaded into a solver
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    #[serde(default = "default_detector_confidences")]
+    pub detector_confidences: Vec<(VertexIndex, f64)>,
+}
+
+/// confidences are compared bitwise so that syndrome patterns can still be used as hash keys
+impl PartialEq for SyndromePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.defect_vertices == other.defect_vertices
+            && self.erasures == other.erasures
+            && self.dynamic_weights == other.dynamic_weights
+            && self.detector_confidences.len() == other.detector_confidences.len()
+            && self
+                .detector_confidences
+                .iter()
+                .zip(other.detector_confidences.iter())
+                .all(|((v1, c1), (v2, c2))| v1 == v2 && c1.to_bits() == c2.to_bits())
+    }
+}
+
+impl Eq for SyndromePattern {}
+
+impl std::hash::Hash for SyndromePattern {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.defect_vertices.hash(state);
+        self.erasures.hash(state);
+        self.dynamic_weights.hash(state);
+        for (vertex_index, confidence) in self.detector_confidences.iter() {
+            vertex_index.hash(state);
+            confidence.to_bits().hash(state);
+        }
+    }
+}
+
+pub fn default_detector_confidences() -> Vec<(VertexIndex, f64)> {
+    vec![]
 }
 
 pub fn default_dynamic_weights() -> Vec<(EdgeIndex, Weight)> {
@@ -110,6 +226,7 @@ impl SyndromePattern {
             defect_vertices,
             erasures,
             dynamic_weights: vec![],
+            detector_confidences: vec![],
         }
     }
     pub fn new_dynamic_weights(
@@ -121,7 +238,94 @@ impl SyndromePattern {
             defect_vertices,
             erasures,
             dynamic_weights,
+            detector_confidences: vec![],
+        }
+    }
+
+    /// a copy of this syndrome with the given per-shot weights overriding the edge weights; the erasures are converted to
+    /// zero weights first, because erasures and dynamic weights cannot be loaded at the same time
+    pub fn with_dynamic_weights(&self, weights: &[(EdgeIndex, Weight)]) -> Self {
+        let mut dynamic_weights: BTreeMap<EdgeIndex, Weight> =
+            self.erasures.iter().map(|&edge_index| (edge_index, 0)).collect();
+        dynamic_weights.extend(self.dynamic_weights.iter().cloned());
+        dynamic_weights.extend(weights.iter().cloned());
+        Self {
+            defect_vertices: self.defect_vertices.clone(),
+            erasures: vec![],
+            dynamic_weights: dynamic_weights.into_iter().collect(),
+            detector_confidences: self.detector_confidences.clone(),
+        }
+    }
+
+    /// convert the detector confidences into dynamic weights: the weight of a time-like edge is scaled by the
+    /// confidences of both its endpoints, so that an unreliable measurement makes the adjacent time-like errors cheaper;
+    /// `timelike_edges` lists the time-like edges incident to each vertex, e.g. from [`crate::example_codes::ExampleCode::timelike_edges`]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn apply_detector_confidences(&mut self, initializer: &SolverInitializer, timelike_edges: &[Vec<EdgeIndex>]) {
+        if self.detector_confidences.is_empty() {
+            return;
+        }
+        assert!(
+            self.erasures.is_empty(),
+            "erasures and detector_confidences cannot be provided at the same time"
+        );
+        assert_eq!(
+            timelike_edges.len(),
+            initializer.vertex_num as usize,
+            "time-like edges must be given for every vertex"
+        );
+        let mut factors: BTreeMap<EdgeIndex, f64> = BTreeMap::new();
+        for &(vertex_index, confidence) in self.detector_confidences.iter() {
+            assert!(
+                (0. ..=1.).contains(&confidence),
+                "confidence {} of detector {} out of range [0, 1]",
+                confidence,
+                vertex_index
+            );
+            for &edge_index in timelike_edges[vertex_index as usize].iter() {
+                *factors.entry(edge_index).or_insert(1.) *= confidence;
+            }
+        }
+        let mut weights: BTreeMap<EdgeIndex, Weight> = self.dynamic_weights.iter().cloned().collect();
+        for (edge_index, factor) in factors.into_iter() {
+            let weight = match weights.get(&edge_index) {
+                Some(weight) => *weight,
+                None => initializer.weighted_edges[edge_index as usize].2,
+            };
+            // keep the weight even so that it remains compatible with the half-weight dual variables
+            weights.insert(edge_index, ((weight as f64 * factor / 2.).round() as Weight) * 2);
+        }
+        self.dynamic_weights = weights.into_iter().collect();
+        self.detector_confidences.clear();
+    }
+
+    /// ignore some detectors in this shot, e.g. for post-selection or known-bad ancilla rounds, without rebuilding the
+    /// decoding graph: their defects are removed and their time-like edges are set to weight 0 (as erasures, or as
+    /// dynamic weights if the syndrome already uses them), so that they no longer constrain the matching;
+    /// returns the number of defects that were ignored
+    #[allow(clippy::unnecessary_cast)]
+    pub fn ignore_detectors(&mut self, ignored_detectors: &[VertexIndex], timelike_edges: &[Vec<EdgeIndex>]) -> usize {
+        if ignored_detectors.is_empty() {
+            return 0;
+        }
+        let ignored: BTreeSet<VertexIndex> = ignored_detectors.iter().cloned().collect();
+        let defect_num = self.defect_vertices.len();
+        self.defect_vertices.retain(|vertex_index| !ignored.contains(vertex_index));
+        let ignored_defect_num = defect_num - self.defect_vertices.len();
+        let freed_edges: BTreeSet<EdgeIndex> = ignored
+            .iter()
+            .flat_map(|&vertex_index| timelike_edges[vertex_index as usize].iter().cloned())
+            .collect();
+        if self.dynamic_weights.is_empty() {
+            let mut erasures: BTreeSet<EdgeIndex> = self.erasures.iter().cloned().collect();
+            erasures.extend(freed_edges);
+            self.erasures = erasures.into_iter().collect();
+        } else {
+            let mut weights: BTreeMap<EdgeIndex, Weight> = self.dynamic_weights.iter().cloned().collect();
+            weights.extend(freed_edges.into_iter().map(|edge_index| (edge_index, 0)));
+            self.dynamic_weights = weights.into_iter().collect();
         }
+        ignored_defect_num
     }
 }
 
@@ -149,6 +353,16 @@ impl SyndromePattern {
         );
         Self::new_dynamic_weights(defect_vertices, erasures, dynamic_weights)
     }
+    #[cfg(feature = "python_binding")]
+    #[pyo3(name = "apply_detector_confidences")]
+    fn py_apply_detector_confidences(&mut self, initializer: &SolverInitializer, timelike_edges: Vec<Vec<EdgeIndex>>) {
+        self.apply_detector_confidences(initializer, &timelike_edges)
+    }
+    #[cfg(feature = "python_binding")]
+    #[pyo3(name = "ignore_detectors")]
+    fn py_ignore_detectors(&mut self, ignored_detectors: Vec<VertexIndex>, timelike_edges: Vec<Vec<EdgeIndex>>) -> usize {
+        self.ignore_detectors(&ignored_detectors, &timelike_edges)
+    }
     #[cfg_attr(feature = "python_binding", staticmethod)]
     pub fn new_vertices(defect_vertices: Vec<VertexIndex>) -> Self {
         Self::new(defect_vertices, vec![])
@@ -172,6 +386,26 @@ pub struct PartitionedSyndromePattern<'a> {
     pub whole_defect_range: DefectRange,
 }
 
+/// overlay of the partition, so that the units can be identified by their names in the visualizer
+impl FusionVisualizer for PartitionInfo {
+    fn snapshot(&self, abbrev: bool) -> serde_json::Value {
+        let units: Vec<_> = self
+            .units
+            .iter()
+            .map(|unit| {
+                json!({
+                    if abbrev { "n" } else { "name" }: unit.name,
+                    if abbrev { "w" } else { "whole_range" }: unit.whole_range,
+                    if abbrev { "o" } else { "owning_range" }: unit.owning_range,
+                })
+            })
+            .collect();
+        json!({
+            "partition_units": units,
+        })
+    }
+}
+
 impl<'a> PartitionedSyndromePattern<'a> {
     pub fn new(syndrome_pattern: &'a SyndromePattern) -> Self {
         assert!(
@@ -318,6 +552,10 @@ pub struct PartitionConfig {
     /// detailed plan of interfacing vertices
     #[cfg_attr(feature = "python_binding", pyo3(get, set))]
     pub fusions: Vec<(usize, usize)>,
+    /// optional human-readable names of units, e.g. "rounds 0-99" or "left half"; unnamed units are called "unit {index}"
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub names: BTreeMap<usize, String>,
 }
 
 #[cfg(feature = "python_binding")]
@@ -332,9 +570,15 @@ impl PartitionConfig {
             vertex_num,
             partitions: vec![VertexRange::new(0, vertex_num as VertexIndex)],
             fusions: vec![],
+            names: BTreeMap::new(),
         }
     }
 
+    /// give a human-readable name to a unit, which is carried through profiler reports and visualizer snapshots
+    pub fn set_name(&mut self, unit_index: usize, name: String) {
+        self.names.insert(unit_index, name);
+    }
+
     #[cfg(feature = "python_binding")]
     fn __repr__(&self) -> String {
         format!("{:?}", self)
@@ -342,63 +586,11 @@ impl PartitionConfig {
 
     #[allow(clippy::unnecessary_cast)]
     pub fn info(&self) -> PartitionInfo {
-        assert!(!self.partitions.is_empty(), "at least one partition must exist");
-        let mut whole_ranges = vec![];
-        let mut owning_ranges = vec![];
-        for &partition in self.partitions.iter() {
-            partition.sanity_check();
-            assert!(
-                partition.end() <= self.vertex_num as VertexIndex,
-                "invalid vertex index {} in partitions",
-                partition.end()
-            );
-            whole_ranges.push(partition);
-            owning_ranges.push(partition);
-        }
-        let unit_count = self.partitions.len() + self.fusions.len();
-        let mut parents: Vec<Option<usize>> = (0..unit_count).map(|_| None).collect();
-        for (fusion_index, (left_index, right_index)) in self.fusions.iter().enumerate() {
-            let unit_index = fusion_index + self.partitions.len();
-            assert!(
-                *left_index < unit_index,
-                "dependency wrong, {} depending on {}",
-                unit_index,
-                left_index
-            );
-            assert!(
-                *right_index < unit_index,
-                "dependency wrong, {} depending on {}",
-                unit_index,
-                right_index
-            );
-            assert!(parents[*left_index].is_none(), "cannot fuse {} twice", left_index);
-            assert!(parents[*right_index].is_none(), "cannot fuse {} twice", right_index);
-            parents[*left_index] = Some(unit_index);
-            parents[*right_index] = Some(unit_index);
-            // fusing range
-            let (whole_range, interface_range) = whole_ranges[*left_index].fuse(&whole_ranges[*right_index]);
-            whole_ranges.push(whole_range);
-            owning_ranges.push(interface_range);
-        }
-        // check that all nodes except for the last one has been merged
-        for (unit_index, parent) in parents.iter().enumerate().take(unit_count - 1) {
-            assert!(parent.is_some(), "found unit {} without being fused", unit_index);
-        }
-        // check that the final node has the full range
-        let last_unit_index = self.partitions.len() + self.fusions.len() - 1;
-        assert!(
-            whole_ranges[last_unit_index].start() == 0,
-            "final range not covering all vertices {:?}",
-            whole_ranges[last_unit_index]
-        );
-        assert!(
-            whole_ranges[last_unit_index].end() == self.vertex_num as VertexIndex,
-            "final range not covering all vertices {:?}",
-            whole_ranges[last_unit_index]
-        );
+        let (whole_ranges, owning_ranges, parents) = self.fused_ranges().unwrap_or_else(|message| panic!("{}", message));
         // construct partition info
         let mut partition_unit_info: Vec<_> = (0..self.partitions.len() + self.fusions.len())
             .map(|i| PartitionUnitInfo {
+                name: self.names.get(&i).cloned().unwrap_or_else(|| format!("unit {i}")),
                 whole_range: whole_ranges[i],
                 owning_range: owning_ranges[i],
                 children: if i >= self.partitions.len() {
@@ -439,6 +631,140 @@ impl PartitionConfig {
     }
 }
 
+impl PartitionConfig {
+    /// fuse the leaf partitions one after another in a chain `((0, 1), 2), 3, ...`; the fusion depth grows linearly
+    /// with the number of leaves, but each fusion only waits for one more leaf, which suits the rounds of a time partition
+    /// arriving one after another
+    pub fn new_linear(vertex_num: VertexNum, partitions: Vec<VertexRange>) -> Self {
+        let mut config = Self::new_leaves(vertex_num, partitions);
+        let mut last_unit_index = 0;
+        for leaf_index in 1..config.partitions.len() {
+            last_unit_index = config.push_fusion(last_unit_index, leaf_index);
+        }
+        config
+    }
+
+    /// fuse the two halves of the leaf partitions recursively, so that the fusion depth is logarithmic in the number of
+    /// leaves and the fusions of different subtrees run in parallel
+    pub fn new_balanced_tree(vertex_num: VertexNum, partitions: Vec<VertexRange>) -> Self {
+        let mut config = Self::new_leaves(vertex_num, partitions);
+        let leaves: Vec<usize> = (0..config.partitions.len()).collect();
+        config.push_balanced_fusions(&leaves);
+        config
+    }
+
+    /// `rows * cols` leaf partitions given in row-major order: the units of each row are fused as a balanced tree, and
+    /// then the rows as a balanced tree. The vertices between two neighboring units of a row must lie between their
+    /// partitions, and the vertices between two rows after the last partition of the upper row
+    pub fn new_2d_grid(vertex_num: VertexNum, partitions: Vec<VertexRange>, rows: usize, cols: usize) -> Self {
+        assert!(rows >= 1 && cols >= 1, "empty grid");
+        assert_eq!(partitions.len(), rows * cols, "expecting {} x {} partitions", rows, cols);
+        let mut config = Self::new_leaves(vertex_num, partitions);
+        let row_roots: Vec<usize> = (0..rows)
+            .map(|row| {
+                let leaves: Vec<usize> = (row * cols..(row + 1) * cols).collect();
+                config.push_balanced_fusions(&leaves)
+            })
+            .collect();
+        config.push_balanced_fusions(&row_roots);
+        config
+    }
+
+    fn new_leaves(vertex_num: VertexNum, partitions: Vec<VertexRange>) -> Self {
+        assert!(!partitions.is_empty(), "at least one partition must exist");
+        Self {
+            vertex_num,
+            partitions,
+            fusions: vec![],
+            names: BTreeMap::new(),
+        }
+    }
+
+    /// fuse two units, returning the index of the fused unit
+    fn push_fusion(&mut self, left_index: usize, right_index: usize) -> usize {
+        self.fusions.push((left_index, right_index));
+        self.partitions.len() + self.fusions.len() - 1
+    }
+
+    /// fuse consecutive units as a balanced tree, returning the index of the root
+    fn push_balanced_fusions(&mut self, unit_indices: &[usize]) -> usize {
+        if unit_indices.len() == 1 {
+            return unit_indices[0];
+        }
+        let middle = unit_indices.len() / 2;
+        let left_index = self.push_balanced_fusions(&unit_indices[..middle]);
+        let right_index = self.push_balanced_fusions(&unit_indices[middle..]);
+        self.push_fusion(left_index, right_index)
+    }
+
+    /// check that the partitions and fusions form a valid fusion tree covering all the vertices, without panicking as
+    /// [`Self::info`] does
+    pub fn sanity_check(&self) -> Result<(), String> {
+        self.fused_ranges().map(|_| ())
+    }
+
+    /// the whole range, the owning range and the parent of every unit
+    #[allow(clippy::type_complexity, clippy::unnecessary_cast)]
+    fn fused_ranges(&self) -> Result<(Vec<VertexRange>, Vec<VertexRange>, Vec<Option<usize>>), String> {
+        if self.partitions.is_empty() {
+            return Err("at least one partition must exist".to_string());
+        }
+        let mut whole_ranges = vec![];
+        let mut owning_ranges = vec![];
+        for &partition in self.partitions.iter() {
+            if partition.start() > partition.end() {
+                return Err(format!("invalid vertex range {:?}", partition));
+            }
+            if partition.end() > self.vertex_num as VertexIndex {
+                return Err(format!("invalid vertex index {} in partitions", partition.end()));
+            }
+            whole_ranges.push(partition);
+            owning_ranges.push(partition);
+        }
+        let unit_count = self.partitions.len() + self.fusions.len();
+        for unit_index in self.names.keys() {
+            if *unit_index >= unit_count {
+                return Err(format!("cannot name a non-existing unit {}", unit_index));
+            }
+        }
+        let mut parents: Vec<Option<usize>> = (0..unit_count).map(|_| None).collect();
+        for (fusion_index, &(left_index, right_index)) in self.fusions.iter().enumerate() {
+            let unit_index = fusion_index + self.partitions.len();
+            for child_index in [left_index, right_index] {
+                if child_index >= unit_index {
+                    return Err(format!("dependency wrong, {} depending on {}", unit_index, child_index));
+                }
+                if parents[child_index].is_some() {
+                    return Err(format!("cannot fuse {} twice", child_index));
+                }
+                parents[child_index] = Some(unit_index);
+            }
+            if whole_ranges[left_index].end() > whole_ranges[right_index].start() {
+                return Err(format!(
+                    "only lower range can fuse higher range, fusing {:?} and {:?}",
+                    whole_ranges[left_index], whole_ranges[right_index]
+                ));
+            }
+            // fusing range
+            let (whole_range, interface_range) = whole_ranges[left_index].fuse(&whole_ranges[right_index]);
+            whole_ranges.push(whole_range);
+            owning_ranges.push(interface_range);
+        }
+        // check that all nodes except for the last one has been merged
+        for (unit_index, parent) in parents.iter().enumerate().take(unit_count - 1) {
+            if parent.is_none() {
+                return Err(format!("found unit {} without being fused", unit_index));
+            }
+        }
+        // check that the final node has the full range
+        let last_range = whole_ranges[unit_count - 1];
+        if last_range.start() != 0 || last_range.end() != self.vertex_num as VertexIndex {
+            return Err(format!("final range not covering all vertices {:?}", last_range));
+        }
+        Ok((whole_ranges, owning_ranges, parents))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "python_binding", cfg_eval)]
 #[cfg_attr(feature = "python_binding", pyclass)]
@@ -473,6 +799,73 @@ impl PartitionInfo {
         partitioned_syndrome
     }
 
+    /// the number of fusions on the longest path from a leaf to the root, i.e. the fusions that must run one after
+    /// another in the critical path of a solve
+    pub fn fusion_depth(&self) -> usize {
+        let mut depths = vec![0; self.units.len()];
+        for (unit_index, unit) in self.units.iter().enumerate() {
+            if let Some((left_index, right_index)) = unit.children {
+                depths[unit_index] = 1 + std::cmp::max(depths[left_index], depths[right_index]);
+            }
+        }
+        depths.last().cloned().unwrap_or(0)
+    }
+
+    /// the unit that owns a vertex: a leaf unit for a real vertex, or the fusion unit that fuses the two sides of an
+    /// interface vertex
+    #[allow(clippy::unnecessary_cast)]
+    pub fn owner_of_vertex(&self, vertex_index: VertexIndex) -> usize {
+        assert!(
+            (vertex_index as usize) < self.vertex_to_owning_unit.len(),
+            "vertex {} out of range",
+            vertex_index
+        );
+        self.vertex_to_owning_unit[vertex_index as usize]
+    }
+
+    /// the unit that owns an edge, i.e. the first unit where both of its vertices are present
+    #[allow(clippy::unnecessary_cast)]
+    pub fn owner_of_edge(&self, initializer: &SolverInitializer, edge_index: EdgeIndex) -> usize {
+        assert!(
+            (edge_index as usize) < initializer.weighted_edges.len(),
+            "edge {} out of range",
+            edge_index
+        );
+        let (vertex_1, vertex_2, _) = initializer.weighted_edges[edge_index as usize];
+        self.lowest_common_unit(self.owner_of_vertex(vertex_1), self.owner_of_vertex(vertex_2))
+    }
+
+    /// whether a vertex lies on the interface between partitions, i.e. it's owned by a fusion unit
+    pub fn is_interface_vertex(&self, vertex_index: VertexIndex) -> bool {
+        self.units[self.owner_of_vertex(vertex_index)].children.is_some()
+    }
+
+    /// the human-readable name of a unit
+    pub fn unit_name(&self, unit_index: usize) -> String {
+        self.units[unit_index].name.clone()
+    }
+
+    /// the unit where vertices owned by the two units first meet, i.e. the lowest common ancestor in the fusion tree
+    pub fn lowest_common_unit(&self, unit_1: usize, unit_2: usize) -> usize {
+        let mut unit_index = unit_1;
+        loop {
+            let unit = &self.units[unit_index];
+            if unit_index == unit_2 || unit.descendants.contains(&unit_2) {
+                return unit_index;
+            }
+            unit_index = unit.parent.expect("the two units are not in the same fusion tree");
+        }
+    }
+
+    /// the level of a unit in the fusion tree: 0 for the leaf units, and one more than the highest child for a fusion unit,
+    /// so that the units of the same level can be fused at the same time
+    pub fn fusion_level(&self, unit_index: usize) -> usize {
+        match self.units[unit_index].children {
+            Some((left_index, right_index)) => 1 + self.fusion_level(left_index).max(self.fusion_level(right_index)),
+            None => 0,
+        }
+    }
+
     #[cfg(feature = "python_binding")]
     fn __repr__(&self) -> String {
         format!("{:?}", self)
@@ -545,6 +938,10 @@ impl<'a> PartitionedSyndromePattern<'a> {
 #[cfg_attr(feature = "python_binding", cfg_eval)]
 #[cfg_attr(feature = "python_binding", pyclass)]
 pub struct PartitionUnitInfo {
+    /// human-readable name of the unit
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    #[serde(default)]
+    pub name: String,
     /// the whole range of units
     #[cfg_attr(feature = "python_binding", pyo3(get, set))]
     pub whole_range: VertexRange,
@@ -576,7 +973,9 @@ impl PartitionUnitInfo {
     }
 }
 
-#[derive(Debug, Clone)]
+/// serialized with the partition units written as their unit indices, see [`Self::bind_partition_units`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "PartitionedSolverInitializerData", from = "PartitionedSolverInitializerData")]
 pub struct PartitionedSolverInitializer {
     /// unit index
     pub unit_index: usize,
@@ -597,6 +996,137 @@ pub struct PartitionedSolverInitializer {
     pub weighted_edges: Vec<(VertexIndex, VertexIndex, Weight, EdgeIndex)>,
     /// the virtual vertices
     pub virtual_vertices: Vec<VertexIndex>,
+    /// the partition units referred to by this initializer when it is not bound to the units of a parallel dual module,
+    /// e.g. after deserialization; they are kept alive here so that the weak references stay valid
+    pub detached_units: Vec<PartitionUnitPtr>,
+}
+
+impl PartitionedSolverInitializer {
+    /// refer to the shared partition units of a parallel dual module, indexed by the unit index, instead of the ones it
+    /// currently refers to
+    pub fn bind_partition_units(&mut self, partition_units: &[PartitionUnitPtr]) {
+        let bind = |partition_unit_weak: &PartitionUnitWeak| {
+            let unit_index = partition_unit_weak.upgrade_force().read_recursive().unit_index;
+            partition_units[unit_index].downgrade()
+        };
+        if let Some(owning_interface) = self.owning_interface.as_mut() {
+            *owning_interface = bind(owning_interface);
+        }
+        for (partition_unit_weak, _) in self.interfaces.iter_mut() {
+            *partition_unit_weak = bind(partition_unit_weak);
+        }
+        self.detached_units.clear();
+    }
+}
+
+/// the serialized form of [`PartitionedSolverInitializer`], where the partition units are replaced by their unit indices
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PartitionedSolverInitializerData {
+    unit_index: usize,
+    vertex_num: VertexNum,
+    edge_num: usize,
+    owning_range: VertexRange,
+    owning_interface: Option<usize>,
+    interfaces: Vec<(usize, Vec<(VertexIndex, bool)>)>,
+    weighted_edges: Vec<(VertexIndex, VertexIndex, Weight, EdgeIndex)>,
+    virtual_vertices: Vec<VertexIndex>,
+}
+
+impl From<PartitionedSolverInitializer> for PartitionedSolverInitializerData {
+    fn from(initializer: PartitionedSolverInitializer) -> Self {
+        let unit_index_of =
+            |partition_unit_weak: &PartitionUnitWeak| partition_unit_weak.upgrade_force().read_recursive().unit_index;
+        Self {
+            unit_index: initializer.unit_index,
+            vertex_num: initializer.vertex_num,
+            edge_num: initializer.edge_num,
+            owning_range: initializer.owning_range,
+            owning_interface: initializer.owning_interface.as_ref().map(unit_index_of),
+            interfaces: initializer
+                .interfaces
+                .into_iter()
+                .map(|(partition_unit_weak, mirror_vertices)| (unit_index_of(&partition_unit_weak), mirror_vertices))
+                .collect(),
+            weighted_edges: initializer.weighted_edges,
+            virtual_vertices: initializer.virtual_vertices,
+        }
+    }
+}
+
+impl From<PartitionedSolverInitializerData> for PartitionedSolverInitializer {
+    /// the referred units are fusion units, which are disabled until their children are fused
+    fn from(data: PartitionedSolverInitializerData) -> Self {
+        let mut detached_units: Vec<PartitionUnitPtr> = vec![];
+        let mut detached_unit = |unit_index: usize| {
+            let partition_unit_ptr = PartitionUnitPtr::new_value(PartitionUnit {
+                unit_index,
+                enabled: false,
+            });
+            let partition_unit_weak = partition_unit_ptr.downgrade();
+            detached_units.push(partition_unit_ptr);
+            partition_unit_weak
+        };
+        let owning_interface = data.owning_interface.map(&mut detached_unit);
+        let interfaces = data
+            .interfaces
+            .into_iter()
+            .map(|(unit_index, mirror_vertices)| (detached_unit(unit_index), mirror_vertices))
+            .collect();
+        Self {
+            unit_index: data.unit_index,
+            vertex_num: data.vertex_num,
+            edge_num: data.edge_num,
+            owning_range: data.owning_range,
+            owning_interface,
+            interfaces,
+            weighted_edges: data.weighted_edges,
+            virtual_vertices: data.virtual_vertices,
+            detached_units,
+        }
+    }
+}
+
+/// read a 1-dimensional numpy integer array (or anything numpy can convert) directly from its buffer and convert each
+/// element, raising `ValueError` if the array is not integral or an element doesn't fit
+#[cfg(feature = "python_binding")]
+fn numpy_to_vec<T: TryFrom<i64> + TryFrom<u64>>(py: Python, array: &PyAny, name: &str) -> PyResult<Vec<T>> {
+    let value_error = |message: String| pyo3::exceptions::PyValueError::new_err(message);
+    let numpy = py.import("numpy")?;
+    let array = numpy.call_method1("asarray", (array,))?;
+    let dtype = array.getattr("dtype")?;
+    let kind: char = dtype.getattr("kind")?.extract()?;
+    if kind != 'i' && kind != 'u' {
+        return Err(value_error(format!("{name} must be an integer array, got dtype {dtype}")));
+    }
+    let ndim: usize = array.getattr("ndim")?.extract()?;
+    if ndim != 1 {
+        return Err(value_error(format!("{name} must be 1-dimensional, got {ndim} dimensions")));
+    }
+    // the native 64-bit integers of the same signedness, which is a no-op for the usual int64 arrays
+    let native_dtype = if kind == 'u' { "u8" } else { "i8" };
+    let array = numpy.call_method1("require", (array, native_dtype, ["C", "A"]))?;
+    let length: usize = array.getattr("size")?.extract()?;
+    if length == 0 {
+        return Ok(vec![]);
+    }
+    let (address, _read_only): (usize, bool) = array.getattr("__array_interface__")?.get_item("data")?.extract()?;
+    let out_of_range = |x: &dyn std::fmt::Display| value_error(format!("{name} has an out-of-range value {x}"));
+    // safety: `array` is a C-contiguous and aligned array of `length` native 64-bit integers, which stays alive and
+    // cannot be modified by other Python threads while this function holds the GIL
+    unsafe {
+        if kind == 'u' {
+            std::slice::from_raw_parts(address as *const u64, length)
+                .iter()
+                .map(|&x| T::try_from(x).map_err(|_| out_of_range(&x)))
+                .collect()
+        } else {
+            std::slice::from_raw_parts(address as *const i64, length)
+                .iter()
+                .map(|&x| T::try_from(x).map_err(|_| out_of_range(&x)))
+                .collect()
+        }
+    }
 }
 
 /// perform index transformation
@@ -636,8 +1166,48 @@ impl SolverInitializer {
             vertex_num,
             weighted_edges,
             virtual_vertices,
+            virtual_vertices_weighted: vec![],
+            observables: None,
         }
     }
+    /// see [`SolverInitializer::from_float_weights`]; invalid weights, i.e. NaN, infinite or negative, raise `ValueError`
+    #[cfg(feature = "python_binding")]
+    #[staticmethod]
+    #[pyo3(name = "from_float_weights")]
+    fn py_from_float_weights(
+        vertex_num: VertexNum,
+        weighted_edges: Vec<(VertexIndex, VertexIndex, f64)>,
+        virtual_vertices: Vec<VertexIndex>,
+        max_half_weight: Weight,
+    ) -> PyResult<SolverInitializer> {
+        Self::check_float_weights(&weighted_edges, max_half_weight).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(Self::from_float_weights(
+            vertex_num,
+            weighted_edges,
+            virtual_vertices,
+            max_half_weight,
+        ))
+    }
+    /// build from the CSR arrays of a sparse adjacency matrix given as numpy arrays, see [`SolverInitializer::from_csr`];
+    /// the integer arrays are read directly from their buffers instead of converting each element into a Python
+    /// object. Invalid arrays, e.g. a float dtype, negative or out-of-range values, or an asymmetric matrix, raise
+    /// `ValueError`
+    #[cfg(feature = "python_binding")]
+    #[staticmethod]
+    #[pyo3(name = "from_csr")]
+    fn py_from_csr(
+        py: Python,
+        indptr: &PyAny,
+        indices: &PyAny,
+        weights: &PyAny,
+        virtual_vertices: Vec<VertexIndex>,
+    ) -> PyResult<SolverInitializer> {
+        let indptr: Vec<usize> = numpy_to_vec(py, indptr, "indptr")?;
+        let indices: Vec<VertexIndex> = numpy_to_vec(py, indices, "indices")?;
+        let weights: Vec<Weight> = numpy_to_vec(py, weights, "weights")?;
+        Self::check_csr(&indptr, &indices, &weights).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        Ok(Self::from_csr(&indptr, &indices, &weights, virtual_vertices))
+    }
     #[cfg(feature = "python_binding")]
     fn __repr__(&self) -> String {
         format!("{:?}", self)
@@ -663,27 +1233,588 @@ impl SolverInitializer {
     }
 }
 
-/// timestamp type determines how many fast clear before a hard clear is required, see [`FastClear`]
-pub type FastClearTimestamp = usize;
-
-#[allow(dead_code)]
-/// use Xoshiro256StarStar for deterministic random number generator
-pub type DeterministicRng = rand_xoshiro::Xoshiro256StarStar;
-
-pub trait F64Rng {
-    fn next_f64(&mut self) -> f64;
-}
+impl SolverInitializer {
+    /// build from a stream of weighted edges, e.g. generated procedurally for a large circuit-level graph, without
+    /// materializing them in an intermediate `Vec` first; the edges are stored in a buffer of exactly `edge_num`
+    /// elements, so that the peak memory is not doubled by reallocation when the buffer grows
+    pub fn from_edges(
+        vertex_num: VertexNum,
+        edge_num: usize,
+        weighted_edges: impl IntoIterator<Item = (VertexIndex, VertexIndex, Weight)>,
+        virtual_vertices: Vec<VertexIndex>,
+    ) -> SolverInitializer {
+        let mut edges = Vec::with_capacity(edge_num);
+        for edge in weighted_edges {
+            assert!(edges.len() < edge_num, "more than {} edges are given", edge_num);
+            edges.push(edge);
+        }
+        assert_eq!(edges.len(), edge_num, "fewer edges than expected are given");
+        Self::new(vertex_num, edges, virtual_vertices)
+    }
 
-impl F64Rng for DeterministicRng {
-    fn next_f64(&mut self) -> f64 {
-        f64::from_bits(0x3FF << 52 | self.next_u64() >> 12) - 1.
+    /// build from floating-point weights, e.g. log-likelihood ratios, which are scaled such that the maximum weight
+    /// becomes `2 * max_half_weight` and rounded to even integers; a larger `max_half_weight` keeps more precision.
+    /// This only quantizes the input: the solvers still decode the integer weights, so the matching is minimum-weight
+    /// up to a rounding error of `max_weight / max_half_weight / 2` per edge, and the reported weights are in the
+    /// scaled integer unit. NaN, infinite and negative weights are rejected
+    pub fn from_float_weights(
+        vertex_num: VertexNum,
+        weighted_edges: Vec<(VertexIndex, VertexIndex, f64)>,
+        virtual_vertices: Vec<VertexIndex>,
+        max_half_weight: Weight,
+    ) -> SolverInitializer {
+        if let Err(message) = Self::check_float_weights(&weighted_edges, max_half_weight) {
+            panic!("{}", message);
+        }
+        let max_weight = weighted_edges.iter().map(|&(_, _, weight)| weight).fold(0., f64::max);
+        let weighted_edges = weighted_edges
+            .into_iter()
+            .map(|(i, j, weight)| {
+                let half_weight = if max_weight == 0. {
+                    0
+                } else {
+                    ((max_half_weight as f64) * weight / max_weight).round() as Weight
+                };
+                (i, j, 2 * half_weight)
+            })
+            .collect();
+        Self::new(vertex_num, weighted_edges, virtual_vertices)
+    }
+    /// check the input of [`Self::from_float_weights`], returning the first problem found
+    fn check_float_weights(
+        weighted_edges: &[(VertexIndex, VertexIndex, f64)],
+        max_half_weight: Weight,
+    ) -> Result<(), String> {
+        if max_half_weight <= 0 {
+            return Err("max_half_weight must be positive".to_string());
+        }
+        for &(i, j, weight) in weighted_edges.iter() {
+            if !weight.is_finite() {
+                return Err(format!("edge ({}, {}) has a non-finite weight {}", i, j, weight));
+            }
+            if weight < 0. {
+                return Err(format!("edge ({}, {}) is negative-weighted", i, j));
+            }
+        }
+        Ok(())
     }
-}
+
+    /// build from the compressed sparse row (CSR) arrays of a weighted adjacency matrix, e.g. `scipy.sparse.csr_matrix`:
+    /// the neighbors of vertex `i` are `indices[indptr[i]..indptr[i+1]]` with the corresponding `weights`. Each edge is
+    /// taken from whichever triangle contains it, so a symmetric matrix, its upper triangle and its lower triangle give
+    /// the same graph; if both triangles contain an edge, the weights must agree
+    #[allow(clippy::unnecessary_cast)]
+    pub fn from_csr(
+        indptr: &[usize],
+        indices: &[VertexIndex],
+        weights: &[Weight],
+        virtual_vertices: Vec<VertexIndex>,
+    ) -> SolverInitializer {
+        if let Err(message) = Self::check_csr(indptr, indices, weights) {
+            panic!("{}", message);
+        }
+        let vertex_num = indptr.len() - 1;
+        let row = |i: usize| &indices[indptr[i]..indptr[i + 1]];
+        // an entry of the lower triangle is skipped if the upper triangle also has it, with the same weight as checked
+        let is_duplicate = |i: usize, j: usize| j < i && row(j).iter().any(|&k| k as usize == i);
+        let edge_num = (0..vertex_num)
+            .map(|i| row(i).iter().filter(|&&j| !is_duplicate(i, j as usize)).count())
+            .sum();
+        let mut edges = Vec::with_capacity(edge_num);
+        for i in 0..vertex_num {
+            for entry in indptr[i]..indptr[i + 1] {
+                let j = indices[entry] as usize;
+                if !is_duplicate(i, j) {
+                    edges.push((i.min(j) as VertexIndex, i.max(j) as VertexIndex, weights[entry]));
+                }
+            }
+        }
+        edges.sort_unstable_by_key(|&(i, j, _)| (i, j));
+        Self::new(vertex_num as VertexNum, edges, virtual_vertices)
+    }
+
+    /// check the shape of the CSR arrays of [`Self::from_csr`], returning the first problem found
+    #[allow(clippy::unnecessary_cast)]
+    fn check_csr(indptr: &[usize], indices: &[VertexIndex], weights: &[Weight]) -> Result<(), String> {
+        if indptr.is_empty() {
+            return Err("indptr must have `vertex_num + 1` elements".to_string());
+        }
+        if indices.len() != weights.len() {
+            return Err("indices and weights must have the same length".to_string());
+        }
+        if *indptr.last().unwrap() != indices.len() {
+            return Err("the last element of indptr must be the number of entries".to_string());
+        }
+        let vertex_num = indptr.len() - 1;
+        for i in 0..vertex_num {
+            if indptr[i] > indptr[i + 1] {
+                return Err("indptr must be non-decreasing".to_string());
+            }
+            for &j in indices[indptr[i]..indptr[i + 1]].iter() {
+                if j as usize >= vertex_num {
+                    return Err(format!("vertex {} out of range", j));
+                }
+                if j as usize == i {
+                    return Err(format!("self loop at vertex {}", i));
+                }
+            }
+        }
+        // `last_row[j]` is the last row with an entry at column `j`, to find the duplicate entries of a row
+        let mut last_row = vec![usize::MAX; vertex_num];
+        for i in 0..vertex_num {
+            for entry in indptr[i]..indptr[i + 1] {
+                let j = indices[entry] as usize;
+                if last_row[j] == i {
+                    return Err(format!("duplicate entry ({}, {})", i, j));
+                }
+                last_row[j] = i;
+                if j < i {
+                    let transposed = (indptr[j]..indptr[j + 1]).find(|&entry| indices[entry] as usize == i);
+                    if transposed.is_some_and(|transposed| weights[transposed] != weights[entry]) {
+                        return Err(format!(
+                            "the weights of edge ({}, {}) disagree between the two triangles",
+                            j, i
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// annotate the logical observables flipped by each edge, in the same order as `weighted_edges`
+    pub fn with_observables(mut self, observables: ObservableMasks) -> Self {
+        assert_eq!(
+            observables.edge_masks.len(),
+            self.weighted_edges.len(),
+            "an observable mask is required for each edge"
+        );
+        self.observables = Some(observables);
+        self
+    }
+
+    /// give each of the virtual vertices a boundary weight to match into, see [`SolverInitializer::virtual_vertices_weighted`]
+    pub fn with_weighted_virtual_vertices(mut self, virtual_vertices_weighted: Vec<(VertexIndex, Weight)>) -> Self {
+        for &(vertex_index, weight) in virtual_vertices_weighted.iter() {
+            assert!(vertex_index < self.vertex_num, "invalid virtual vertex {}", vertex_index);
+            assert!(
+                !self.virtual_vertices.contains(&vertex_index),
+                "virtual vertex {} is listed as both weighted and unweighted",
+                vertex_index
+            );
+            assert!(
+                weight >= 0 && weight % 2 == 0,
+                "virtual vertex {} has boundary weight {}; it should be even and non-negative",
+                vertex_index,
+                weight
+            );
+        }
+        self.virtual_vertices_weighted = virtual_vertices_weighted;
+        self
+    }
+
+    /// the equivalent decoding graph with only unweighted virtual vertices: each weighted virtual vertex becomes an
+    /// ordinary vertex connected by an edge of its boundary weight to a new virtual vertex, and the new vertices and edges
+    /// are appended after the existing ones in the order of `virtual_vertices_weighted`. Matching into a weighted virtual
+    /// vertex is then matching into its new virtual vertex through the boundary edge
+    #[allow(clippy::unnecessary_cast)]
+    pub fn expand_weighted_virtual_vertices(&self) -> Cow<'_, SolverInitializer> {
+        if self.virtual_vertices_weighted.is_empty() {
+            return Cow::Borrowed(self);
+        }
+        let mut expanded = self.clone();
+        expanded.virtual_vertices_weighted.clear();
+        for (index, &(vertex_index, weight)) in self.virtual_vertices_weighted.iter().enumerate() {
+            let boundary_vertex = self.vertex_num + index as VertexNum;
+            expanded.weighted_edges.push((vertex_index, boundary_vertex, weight));
+            expanded.virtual_vertices.push(boundary_vertex);
+        }
+        expanded.vertex_num += self.virtual_vertices_weighted.len() as VertexNum;
+        if let Some(observables) = expanded.observables.as_mut() {
+            observables.edge_masks.resize(expanded.weighted_edges.len(), 0);
+        }
+        Cow::Owned(expanded)
+    }
+}
+
+/// decode under vertex removal (e.g. heralded loss of ancilla qubits) without rebuilding the decoding graph:
+/// each removed vertex is contracted into one of its neighbors through a bypass edge, which is set to weight 0
+/// so that all the other incident edges effectively reconnect to the neighbor (supergraph contraction);
+/// the parity of the merged super-vertex is the XOR of the defects within it, and the decoded subgraph is mapped back
+/// to the original graph by [`VertexRemover::original_subgraph`]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+#[derive(Debug, Clone)]
+pub struct VertexRemover {
+    /// the number of vertices in the decoding graph
+    pub vertex_num: VertexNum,
+    /// the virtual vertices, which absorb any parity merged into them
+    pub is_virtual: Vec<bool>,
+    /// the candidate bypass edges of each vertex, precomputed in ascending order of weight: `(weight, edge, peer)`
+    pub bypass_edges: Vec<Vec<(Weight, EdgeIndex, VertexIndex)>>,
+}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl VertexRemover {
+    #[cfg_attr(feature = "python_binding", new)]
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        let vertex_num = initializer.vertex_num;
+        let mut is_virtual = vec![false; vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        let mut bypass_edges = vec![vec![]; vertex_num as usize];
+        for (edge_index, &(left, right, weight)) in initializer.weighted_edges.iter().enumerate() {
+            if left == right {
+                continue;
+            }
+            bypass_edges[left as usize].push((weight, edge_index as EdgeIndex, right));
+            bypass_edges[right as usize].push((weight, edge_index as EdgeIndex, left));
+        }
+        for edges in bypass_edges.iter_mut() {
+            edges.sort_unstable();
+        }
+        Self {
+            vertex_num,
+            is_virtual,
+            bypass_edges,
+        }
+    }
+
+    /// the bypass edges used to contract the removed vertices, in the order of `removed_vertices`;
+    /// a removed vertex prefers the cheapest edge towards a vertex that is not removed or already contracted,
+    /// so that chains of adjacent removed vertices are resolved as well
+    #[allow(clippy::unnecessary_cast)]
+    pub fn contraction_edges(&self, removed_vertices: Vec<VertexIndex>) -> Vec<(VertexIndex, EdgeIndex, VertexIndex)> {
+        let mut is_removed = vec![false; self.vertex_num as usize];
+        for &vertex_index in removed_vertices.iter() {
+            assert!(vertex_index < self.vertex_num, "removed vertex {vertex_index} out of range");
+            assert!(
+                !self.is_virtual[vertex_index as usize],
+                "cannot remove virtual vertex {vertex_index}"
+            );
+            is_removed[vertex_index as usize] = true;
+        }
+        let mut contractions: Vec<Option<(EdgeIndex, VertexIndex)>> = vec![None; removed_vertices.len()];
+        let mut unresolved = removed_vertices.len();
+        while unresolved > 0 {
+            let mut resolved_this_round = vec![];
+            for (index, &vertex_index) in removed_vertices.iter().enumerate() {
+                if contractions[index].is_some() {
+                    continue;
+                }
+                let bypass = self.bypass_edges[vertex_index as usize]
+                    .iter()
+                    .find(|(_, _, peer)| !is_removed[*peer as usize]);
+                if let Some(&(_, edge_index, peer)) = bypass {
+                    contractions[index] = Some((edge_index, peer));
+                    resolved_this_round.push(vertex_index);
+                }
+            }
+            assert!(
+                !resolved_this_round.is_empty(),
+                "cannot contract removed vertices: some connected component is entirely removed"
+            );
+            // vertices contracted in this round can serve as bypass targets in the next round
+            for vertex_index in resolved_this_round.iter() {
+                is_removed[*vertex_index as usize] = false;
+            }
+            unresolved -= resolved_this_round.len();
+        }
+        removed_vertices
+            .iter()
+            .zip(contractions)
+            .map(|(&vertex_index, contraction)| {
+                let (edge_index, peer) = contraction.unwrap();
+                (vertex_index, edge_index, peer)
+            })
+            .collect()
+    }
+
+    /// generate the syndrome pattern on the contracted graph: bypass edges are loaded as erasures (or as zero-weight
+    /// dynamic weights if the syndrome already uses them), and the defects of removed vertices are merged into
+    /// the super-vertex they are contracted into
+    #[allow(clippy::unnecessary_cast)]
+    pub fn remove_vertices(
+        &self,
+        syndrome_pattern: &SyndromePattern,
+        removed_vertices: Vec<VertexIndex>,
+    ) -> SyndromePattern {
+        let contractions = self.contraction_edges(removed_vertices);
+        // contractions are ordered such that a peer is either kept or contracted in a previous round;
+        // follow the peers to find the representative of each removed vertex
+        let mut contracted_peer: BTreeMap<VertexIndex, VertexIndex> = BTreeMap::new();
+        for &(vertex_index, _, peer) in contractions.iter() {
+            contracted_peer.insert(vertex_index, peer);
+        }
+        let representative_of = |mut vertex_index: VertexIndex| -> VertexIndex {
+            while let Some(&peer) = contracted_peer.get(&vertex_index) {
+                vertex_index = peer;
+            }
+            vertex_index
+        };
+        let mut defects: BTreeSet<VertexIndex> = BTreeSet::new();
+        for &defect_vertex in syndrome_pattern.defect_vertices.iter() {
+            let representative = representative_of(defect_vertex);
+            if self.is_virtual[representative as usize] {
+                continue; // the boundary absorbs the parity
+            }
+            if !defects.remove(&representative) {
+                defects.insert(representative);
+            }
+        }
+        let mut result = syndrome_pattern.clone();
+        result.defect_vertices = defects.into_iter().collect();
+        if syndrome_pattern.dynamic_weights.is_empty() {
+            let existing: BTreeSet<EdgeIndex> = result.erasures.iter().cloned().collect();
+            for &(_, edge_index, _) in contractions.iter() {
+                if !existing.contains(&edge_index) {
+                    result.erasures.push(edge_index);
+                }
+            }
+        } else {
+            let contracted: BTreeSet<EdgeIndex> = contractions.iter().map(|(_, edge_index, _)| *edge_index).collect();
+            result
+                .dynamic_weights
+                .retain(|(edge_index, _)| !contracted.contains(edge_index));
+            result
+                .dynamic_weights
+                .extend(contracted.iter().map(|&edge_index| (edge_index, 0)));
+        }
+        result
+    }
+
+    /// map the subgraph decoded on the contracted graph back to the original graph: a bypass edge lies inside a
+    /// super-vertex, so it doesn't change the parity of any super-vertex and is not part of the correction
+    pub fn original_subgraph(&self, subgraph: Vec<EdgeIndex>, removed_vertices: Vec<VertexIndex>) -> Vec<EdgeIndex> {
+        let contracted: BTreeSet<EdgeIndex> = self
+            .contraction_edges(removed_vertices)
+            .into_iter()
+            .map(|(_, edge_index, _)| edge_index)
+            .collect();
+        subgraph
+            .into_iter()
+            .filter(|edge_index| !contracted.contains(edge_index))
+            .collect()
+    }
+}
+
+/// decode with per-shot edge weights that may be negative, e.g. after a correlated reweighting pass, although the dual
+/// module only accepts non-negative weights: an edge of negative weight `w` is taken into the correction in advance,
+/// which flips the parity of its endpoints, and the matching may remove it again at the cost of `-w`. The minimum weight
+/// of the original problem is then the minimum weight of the reweighted one plus the sum of the negative weights
+#[derive(Debug, Clone)]
+pub struct EdgeReweighter {
+    /// the virtual vertices, which absorb the parity flipped by a negative edge
+    pub is_virtual: Vec<bool>,
+    /// the vertices of each edge
+    pub edge_endpoints: Vec<(VertexIndex, VertexIndex)>,
+}
+
+/// a syndrome with only non-negative weights, generated by [`EdgeReweighter::reweight`]
+#[derive(Debug, Clone)]
+pub struct ReweightedSyndrome {
+    /// the syndrome to decode instead of the original one
+    pub syndrome_pattern: SyndromePattern,
+    /// the edges of negative weight, which are in the correction unless the matching removes them
+    pub flipped_edges: Vec<EdgeIndex>,
+    /// the sum of the negative weights, which is the difference of the weight of the original problem from the
+    /// reweighted one
+    pub weight_offset: Weight,
+}
+
+impl EdgeReweighter {
+    #[allow(clippy::unnecessary_cast)]
+    pub fn new(initializer: &SolverInitializer) -> Self {
+        let mut is_virtual = vec![false; initializer.vertex_num as usize];
+        for &virtual_vertex in initializer.virtual_vertices.iter() {
+            is_virtual[virtual_vertex as usize] = true;
+        }
+        Self {
+            is_virtual,
+            edge_endpoints: initializer.weighted_edges.iter().map(|&(i, j, _)| (i, j)).collect(),
+        }
+    }
+
+    /// override the edge weights of this shot, see [`SyndromePattern::with_dynamic_weights`], where some of the weights
+    /// (including the existing dynamic weights of the syndrome) may be negative
+    #[allow(clippy::unnecessary_cast)]
+    pub fn reweight(&self, syndrome_pattern: &SyndromePattern, weights: &[(EdgeIndex, Weight)]) -> ReweightedSyndrome {
+        let mut reweighted = syndrome_pattern.with_dynamic_weights(weights);
+        let mut defect_vertices: BTreeSet<VertexIndex> = reweighted.defect_vertices.iter().cloned().collect();
+        let mut flipped_edges = vec![];
+        let mut weight_offset = 0;
+        for (edge_index, weight) in reweighted.dynamic_weights.iter_mut() {
+            assert!(
+                *weight % 2 == 0,
+                "edge {} has odd weight value {}; weight should be even",
+                edge_index,
+                weight
+            );
+            if *weight >= 0 {
+                continue;
+            }
+            let (left, right) = self.edge_endpoints[*edge_index as usize];
+            for vertex_index in [left, right] {
+                if !self.is_virtual[vertex_index as usize] && !defect_vertices.remove(&vertex_index) {
+                    defect_vertices.insert(vertex_index);
+                }
+            }
+            flipped_edges.push(*edge_index);
+            weight_offset += *weight;
+            *weight = -*weight;
+        }
+        reweighted.defect_vertices = defect_vertices.into_iter().collect();
+        ReweightedSyndrome {
+            syndrome_pattern: reweighted,
+            flipped_edges,
+            weight_offset,
+        }
+    }
+}
+
+impl ReweightedSyndrome {
+    /// the correction of the original problem given the correction of the reweighted one, i.e. their symmetric difference
+    /// with the negative edges
+    pub fn original_subgraph(&self, subgraph: &[EdgeIndex]) -> Vec<EdgeIndex> {
+        let mut edges: BTreeSet<EdgeIndex> = self.flipped_edges.iter().cloned().collect();
+        for edge_index in subgraph.iter() {
+            if !edges.remove(edge_index) {
+                edges.insert(*edge_index);
+            }
+        }
+        edges.into_iter().collect()
+    }
+
+    /// the weight of the original problem given the weight of the reweighted one, e.g. the sum of the dual variables
+    pub fn original_weight(&self, weight: Weight) -> Weight {
+        weight + self.weight_offset
+    }
+}
+
+/// timestamp type determines how many fast clear before a hard clear is required, see [`FastClear`]
+pub type FastClearTimestamp = usize;
+
+#[allow(dead_code)]
+/// use Xoshiro256StarStar for deterministic random number generator
+pub type DeterministicRng = rand_xoshiro::Xoshiro256StarStar;
+
+pub trait F64Rng {
+    fn next_f64(&mut self) -> f64;
+}
+
+impl F64Rng for DeterministicRng {
+    fn next_f64(&mut self) -> f64 {
+        f64::from_bits(0x3FF << 52 | self.next_u64() >> 12) - 1.
+    }
+}
+
+/// the CPU resources of the current machine, used to suggest parallel configurations
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CpuTopology {
+    /// the number of logical cores available to this process
+    pub logical_cores: usize,
+    /// L1 data cache size in bytes per core, if known
+    pub l1d_cache_size: Option<usize>,
+    /// L2 cache size in bytes, if known
+    pub l2_cache_size: Option<usize>,
+    /// L3 cache size in bytes, if known
+    pub l3_cache_size: Option<usize>,
+}
+
+impl CpuTopology {
+    /// detect the topology of the current machine; cache sizes are only available on Linux
+    pub fn detect() -> Self {
+        let logical_cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let mut topology = Self {
+            logical_cores,
+            l1d_cache_size: None,
+            l2_cache_size: None,
+            l3_cache_size: None,
+        };
+        for index in 0.. {
+            let folder = format!("/sys/devices/system/cpu/cpu0/cache/index{index}");
+            let read =
+                |name: &str| std::fs::read_to_string(format!("{folder}/{name}")).map(|value| value.trim().to_string());
+            let (Ok(level), Ok(cache_type), Ok(size)) = (read("level"), read("type"), read("size")) else {
+                break;
+            };
+            let Some(size) = Self::parse_cache_size(&size) else {
+                continue;
+            };
+            match (level.as_str(), cache_type.as_str()) {
+                ("1", "Data" | "Unified") => topology.l1d_cache_size = Some(size),
+                ("2", "Data" | "Unified") => topology.l2_cache_size = Some(size),
+                ("3", "Data" | "Unified") => topology.l3_cache_size = Some(size),
+                _ => {}
+            }
+        }
+        topology
+    }
+
+    /// the logical cores of each NUMA node, only available on Linux; otherwise all the cores are considered in a single node
+    pub fn detect_numa_nodes() -> Vec<Vec<usize>> {
+        let online = std::fs::read_to_string("/sys/devices/system/node/online").unwrap_or_default();
+        let mut numa_nodes = Self::numa_nodes_from(&online, |node_index| {
+            std::fs::read_to_string(format!("/sys/devices/system/node/node{node_index}/cpulist")).ok()
+        });
+        if numa_nodes.is_empty() {
+            let logical_cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+            numa_nodes.push((0..logical_cores).collect());
+        }
+        numa_nodes
+    }
+
+    /// the cores of each node in the list of online nodes, which may have holes like `0,2`; nodes without any core, e.g.
+    /// memory-only nodes, are skipped
+    pub fn numa_nodes_from(online: &str, read_cpu_list: impl Fn(usize) -> Option<String>) -> Vec<Vec<usize>> {
+        Self::parse_cpu_list(online)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|node_index| Self::parse_cpu_list(&read_cpu_list(node_index)?))
+            .filter(|cores| !cores.is_empty())
+            .collect()
+    }
+
+    /// parse core lists like `0-3,8-11` into the core indices
+    pub fn parse_cpu_list(cpu_list: &str) -> Option<Vec<usize>> {
+        let mut cores = vec![];
+        for range in cpu_list.trim().split(',').filter(|range| !range.is_empty()) {
+            match range.split_once('-') {
+                Some((start, end)) => cores.extend(start.trim().parse::<usize>().ok()?..=end.trim().parse::<usize>().ok()?),
+                None => cores.push(range.trim().parse().ok()?),
+            }
+        }
+        Some(cores)
+    }
+
+    /// parse cache sizes like `48K` or `2048K` or `32M` into bytes
+    pub fn parse_cache_size(size: &str) -> Option<usize> {
+        let size = size.trim();
+        let (number, unit) = match size.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+            Some((position, _)) => size.split_at(position),
+            None => (size, ""),
+        };
+        let number: usize = number.parse().ok()?;
+        let multiplier = match unit.trim() {
+            "" => 1,
+            "K" | "KB" | "KiB" => 1 << 10,
+            "M" | "MB" | "MiB" => 1 << 20,
+            "G" | "GB" | "GiB" => 1 << 30,
+            _ => return None,
+        };
+        Some(number * multiplier)
+    }
+}
 
 /// record the decoding time of multiple syndrome patterns
 pub struct BenchmarkProfiler {
     /// each record corresponds to a different syndrome pattern
     pub records: Vec<BenchmarkProfilerEntry>,
+    /// the number of steady-state records, excluding the warmup ones
+    pub records_count: usize,
     /// summation of all decoding time
     pub sum_round_time: f64,
     /// syndrome count
@@ -692,11 +1823,21 @@ pub struct BenchmarkProfiler {
     pub noisy_measurements: VertexNum,
     /// the file to output the profiler results
     pub benchmark_profiler_output: Option<File>,
+    /// the number of shots at the beginning that are excluded from the steady-state statistics, because cold-start
+    /// effects like page faults and lazy allocation make them much slower than the rest
+    pub warmup_rounds: usize,
+    /// the number of warmup records since the beginning
+    pub warmup_records_count: usize,
+    /// summation of the decoding time of warmup shots
+    pub warmup_sum_round_time: f64,
 }
 
 impl BenchmarkProfiler {
-    pub fn new(noisy_measurements: VertexNum, detail_log_file: Option<(String, &PartitionInfo)>) -> Self {
-        let benchmark_profiler_output = detail_log_file.map(|(filename, partition_info)| {
+    pub fn new(
+        noisy_measurements: VertexNum,
+        detail_log_file: Option<(String, &PartitionInfo, &OutputFingerprint)>,
+    ) -> Self {
+        let benchmark_profiler_output = detail_log_file.map(|(filename, partition_info, fingerprint)| {
             let mut file = File::create(filename).unwrap();
             file.write_all(serde_json::to_string(&partition_info.config).unwrap().as_bytes())
                 .unwrap();
@@ -704,6 +1845,7 @@ impl BenchmarkProfiler {
             file.write_all(
                 serde_json::to_string(&json!({
                     "noisy_measurements": noisy_measurements,
+                    "fingerprint": fingerprint,
                 }))
                 .unwrap()
                 .as_bytes(),
@@ -714,12 +1856,20 @@ impl BenchmarkProfiler {
         });
         Self {
             records: vec![],
+            records_count: 0,
             sum_round_time: 0.,
             sum_syndrome: 0,
             noisy_measurements,
             benchmark_profiler_output,
+            warmup_rounds: 0,
+            warmup_records_count: 0,
+            warmup_sum_round_time: 0.,
         }
     }
+    /// whether the latest record is a warmup shot, which is reported separately and not written to the output file
+    pub fn is_warmup(&self) -> bool {
+        self.records.last().is_some_and(|entry| entry.is_warmup)
+    }
     /// record the beginning of a decoding procedure
     pub fn begin(&mut self, syndrome_pattern: &SyndromePattern) {
         // sanity check last entry, if exists, is complete
@@ -729,7 +1879,13 @@ impl BenchmarkProfiler {
                 "the last benchmark profiler entry is not complete, make sure to call `begin` and `end` in pairs"
             );
         }
-        let entry = BenchmarkProfilerEntry::new(syndrome_pattern);
+        let mut entry = BenchmarkProfilerEntry::new(syndrome_pattern);
+        if self.warmup_records_count < self.warmup_rounds {
+            entry.is_warmup = true;
+            self.warmup_records_count += 1;
+        } else {
+            self.records_count += 1;
+        }
         self.records.push(entry);
         self.records.last_mut().unwrap().record_begin();
     }
@@ -740,6 +1896,14 @@ impl BenchmarkProfiler {
             .expect("last entry not exists, call `begin` before `end`");
         last_entry.record_event(event_name);
     }
+    /// record the seed that generated the current shot, written to the profiler output for reproduction
+    pub fn set_seed(&mut self, seed: u64) {
+        let last_entry = self
+            .records
+            .last_mut()
+            .expect("last entry not exists, call `begin` before `set_seed`");
+        last_entry.seed = Some(seed);
+    }
     /// record the ending of a decoding procedure
     pub fn end(&mut self, solver: Option<&dyn PrimalDualSolver>) {
         let last_entry = self
@@ -747,6 +1911,10 @@ impl BenchmarkProfiler {
             .last_mut()
             .expect("last entry not exists, call `begin` before `end`");
         last_entry.record_end();
+        if last_entry.is_warmup {
+            self.warmup_sum_round_time += last_entry.round_time.unwrap();
+            return;
+        }
         self.sum_round_time += last_entry.round_time.unwrap();
         self.sum_syndrome += last_entry.syndrome_pattern.defect_vertices.len();
         if let Some(file) = self.benchmark_profiler_output.as_mut() {
@@ -759,6 +1927,9 @@ impl BenchmarkProfiler {
                 "defect_num": last_entry.syndrome_pattern.defect_vertices.len(),
                 "events": events,
             });
+            if let Some(seed) = last_entry.seed {
+                value.as_object_mut().unwrap().insert("seed".to_string(), json!(seed));
+            }
             if let Some(solver) = solver {
                 let solver_profile = solver.generate_profiler_report();
                 value
@@ -770,12 +1941,131 @@ impl BenchmarkProfiler {
             file.write_all(b"\n").unwrap();
         }
     }
+    /// the steady-state statistics in [`Self::brief`] as named metrics, e.g. to be recorded in a results database
+    pub fn summary_metrics(&self) -> Vec<(&'static str, f64)> {
+        let total = self.sum_round_time / (self.records_count as f64);
+        vec![
+            ("total_time", total),
+            ("round_time", total / (1. + self.noisy_measurements as f64)),
+            ("defect_time", self.sum_round_time / (self.sum_syndrome as f64)),
+        ]
+    }
     /// print out a brief one-line statistics
     pub fn brief(&self) -> String {
-        let total = self.sum_round_time / (self.records.len() as f64);
+        let total = self.sum_round_time / (self.records_count as f64);
         let per_round = total / (1. + self.noisy_measurements as f64);
         let per_defect = self.sum_round_time / (self.sum_syndrome as f64);
-        format!("total: {total:.3e}, round: {per_round:.3e}, defect: {per_defect:.3e},")
+        let mut brief = format!("total: {total:.3e}, round: {per_round:.3e}, defect: {per_defect:.3e},");
+        if self.warmup_records_count > 0 {
+            let cold = self.warmup_sum_round_time / (self.warmup_records_count as f64);
+            brief += &format!(" cold: {cold:.3e},");
+        }
+        brief
+    }
+}
+
+/// stamped into the output artifacts to make them self-describing: an artifact can be reproduced by running the same
+/// version with the config of the same hash and the recorded seed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputFingerprint {
+    /// the version of this crate
+    pub version: String,
+    /// the hash of the effective config, see [`config_hash`]
+    pub config_hash: String,
+    /// the seed of the random number generator, if the artifact comes from a single shot
+    pub seed: Option<u64>,
+}
+
+impl OutputFingerprint {
+    pub fn new(config: &serde_json::Value) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            config_hash: config_hash(config),
+            seed: None,
+        }
+    }
+
+    pub fn with_seed(&self, seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..self.clone()
+        }
+    }
+}
+
+/// a hash of the config that is stable across runs and platforms: 64-bit FNV-1a of the compact JSON string, whose
+/// object keys are always sorted
+pub fn config_hash(config: &serde_json::Value) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in config.to_string().bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{hash:016x}")
+}
+
+/// per-shot outcome of a benchmark, written as a single row of [`BenchmarkResultsWriter`]
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResultsRow {
+    /// the index of the shot
+    pub round: u64,
+    /// the seed used to generate the syndrome pattern
+    pub seed: u64,
+    /// the number of defect vertices
+    pub defect_num: usize,
+    /// the number of erasures
+    pub erasure_num: usize,
+    /// the weight of the minimum-weight perfect matching, i.e. the sum of dual variables
+    pub weight: Weight,
+    /// whether the correction is free of logical error; `None` if the code cannot tell which edges flipped or has no
+    /// logical observable, see [`crate::analysis::left_boundary_edges`]
+    pub success: Option<bool>,
+    /// the number of conflicts resolved by the primal module; `None` if the solver doesn't report it
+    pub conflicts: Option<usize>,
+    /// the time from beginning to the end of decoding
+    pub decode_time: f64,
+    /// the time of the whole round, including verification and clear
+    pub round_time: f64,
+}
+
+/// write per-shot benchmark results in CSV format incrementally, so that the memory usage is bounded regardless of the number of shots
+pub struct BenchmarkResultsWriter {
+    writer: std::io::BufWriter<File>,
+    /// stamped into every row, so that rows copied out of the file remain self-describing
+    fingerprint: OutputFingerprint,
+}
+
+impl BenchmarkResultsWriter {
+    /// the header of the CSV file; `success` and `conflicts` are left empty when unknown
+    pub const HEADER: &'static str =
+        "round,seed,defect_num,erasure_num,weight,success,conflicts,decode_time,round_time,version,config_hash";
+
+    pub fn new(filename: &str, fingerprint: OutputFingerprint) -> std::io::Result<Self> {
+        let mut writer = std::io::BufWriter::new(File::create(filename)?);
+        writeln!(writer, "{}", Self::HEADER)?;
+        Ok(Self { writer, fingerprint })
+    }
+
+    pub fn write_row(&mut self, row: &BenchmarkResultsRow) -> std::io::Result<()> {
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{},{},{:.6e},{:.6e},{},{}",
+            row.round,
+            row.seed,
+            row.defect_num,
+            row.erasure_num,
+            row.weight,
+            row.success.map(|success| if success { "1" } else { "0" }).unwrap_or_default(),
+            row.conflicts.map(|conflicts| conflicts.to_string()).unwrap_or_default(),
+            row.decode_time,
+            row.round_time,
+            self.fingerprint.version,
+            self.fingerprint.config_hash
+        )
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
     }
 }
 
@@ -788,6 +2078,10 @@ pub struct BenchmarkProfilerEntry {
     pub events: Vec<(String, f64)>,
     /// interval between calling [`Self::record_begin`] to calling [`Self::record_end`]
     pub round_time: Option<f64>,
+    /// warmup shots are not counted in the steady-state statistics
+    pub is_warmup: bool,
+    /// the seed that generated this shot, if known
+    pub seed: Option<u64>,
 }
 
 impl BenchmarkProfilerEntry {
@@ -797,6 +2091,8 @@ impl BenchmarkProfilerEntry {
             begin_time: None,
             events: vec![],
             round_time: None,
+            is_warmup: false,
+            seed: None,
         }
     }
     /// record the beginning of a decoding procedure
@@ -824,6 +2120,315 @@ impl BenchmarkProfilerEntry {
     }
 }
 
+/// the phases of the algorithm whose per-call latency is recorded by [`PhaseProfiler`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ProfilerPhase {
+    Grow,
+    ComputeMaximumUpdateLength,
+    Resolve,
+    Fuse,
+}
+
+impl ProfilerPhase {
+    pub const ALL: [Self; 4] = [Self::Grow, Self::ComputeMaximumUpdateLength, Self::Resolve, Self::Fuse];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Grow => "grow",
+            Self::ComputeMaximumUpdateLength => "compute_maximum_update_length",
+            Self::Resolve => "resolve",
+            Self::Fuse => "fuse",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|phase| phase.name() == name)
+    }
+}
+
+/// the number of buckets in a [`LatencyHistogram`], enough to cover any latency representable in nanoseconds by `u64`
+pub const LATENCY_HISTOGRAM_BUCKETS: usize = 64;
+
+/// a latency histogram with logarithmic buckets: bucket `i` counts the calls taking less than `2^i` nanoseconds but
+/// no less than `2^(i-1)` nanoseconds, so that recording is a constant-time bit operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_HISTOGRAM_BUCKETS],
+    /// the number of recorded calls
+    pub count: u64,
+    /// the total latency of all calls in nanoseconds
+    pub sum_ns: u128,
+    /// the minimum latency in nanoseconds, `u64::MAX` if nothing is recorded
+    pub min_ns: u64,
+    /// the maximum latency in nanoseconds
+    pub max_ns: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            buckets: [0; LATENCY_HISTOGRAM_BUCKETS],
+            count: 0,
+            sum_ns: 0,
+            min_ns: u64::MAX,
+            max_ns: 0,
+        }
+    }
+
+    /// the bucket that a latency falls into
+    pub fn bucket_of(latency_ns: u64) -> usize {
+        std::cmp::min(
+            (u64::BITS - latency_ns.leading_zeros()) as usize,
+            LATENCY_HISTOGRAM_BUCKETS - 1,
+        )
+    }
+
+    /// the exclusive upper bound of a bucket in nanoseconds; the last bucket is unbounded
+    pub fn bucket_upper_bound_ns(bucket: usize) -> u64 {
+        1 << bucket
+    }
+
+    pub fn record(&mut self, latency: std::time::Duration) {
+        self.record_ns(u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX));
+    }
+
+    pub fn record_ns(&mut self, latency_ns: u64) {
+        self.buckets[Self::bucket_of(latency_ns)] += 1;
+        self.count += 1;
+        self.sum_ns += latency_ns as u128;
+        self.min_ns = std::cmp::min(self.min_ns, latency_ns);
+        self.max_ns = std::cmp::max(self.max_ns, latency_ns);
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        for (bucket, other_bucket) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            *bucket += other_bucket;
+        }
+        self.count += other.count;
+        self.sum_ns += other.sum_ns;
+        self.min_ns = std::cmp::min(self.min_ns, other.min_ns);
+        self.max_ns = std::cmp::max(self.max_ns, other.max_ns);
+    }
+
+    pub fn buckets(&self) -> &[u64; LATENCY_HISTOGRAM_BUCKETS] {
+        &self.buckets
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// an upper estimate of the `q`-quantile latency in nanoseconds, i.e. the upper bound of the bucket containing it,
+    /// tightened by the maximum latency
+    pub fn quantile_ns(&self, q: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = std::cmp::max(1, (q * self.count as f64).ceil() as u64);
+        let mut accumulated = 0;
+        for (bucket, &count) in self.buckets.iter().enumerate() {
+            accumulated += count;
+            if accumulated >= rank {
+                if bucket == LATENCY_HISTOGRAM_BUCKETS - 1 {
+                    return self.max_ns;
+                }
+                return std::cmp::min(Self::bucket_upper_bound_ns(bucket), self.max_ns);
+            }
+        }
+        self.max_ns
+    }
+
+    /// summary statistics in seconds and the non-empty buckets as `[upper_bound_ns, count]` pairs,
+    /// which can be parsed back by [`Self::from_json`]
+    pub fn to_json(&self) -> serde_json::Value {
+        let buckets: Vec<_> = self
+            .buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(bucket, &count)| json!([Self::bucket_upper_bound_ns(bucket), count]))
+            .collect();
+        let to_seconds = |latency_ns: u64| latency_ns as f64 * 1e-9;
+        json!({
+            "count": self.count,
+            "sum": self.sum_ns as f64 * 1e-9,
+            "mean": if self.count == 0 { 0. } else { self.sum_ns as f64 * 1e-9 / self.count as f64 },
+            "min": if self.count == 0 { 0. } else { to_seconds(self.min_ns) },
+            "max": to_seconds(self.max_ns),
+            "p50": to_seconds(self.quantile_ns(0.5)),
+            "p90": to_seconds(self.quantile_ns(0.9)),
+            "p99": to_seconds(self.quantile_ns(0.99)),
+            "sum_ns": self.sum_ns as u64,
+            "min_ns": self.min_ns,
+            "max_ns": self.max_ns,
+            "buckets": buckets,
+        })
+    }
+
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        let mut histogram = Self::new();
+        for pair in value.get("buckets")?.as_array()? {
+            let upper_bound_ns = pair.get(0)?.as_u64()?;
+            let count = pair.get(1)?.as_u64()?;
+            histogram.buckets[upper_bound_ns.trailing_zeros() as usize] += count;
+            histogram.count += count;
+        }
+        histogram.sum_ns = value.get("sum_ns")?.as_u64()? as u128;
+        histogram.min_ns = value.get("min_ns")?.as_u64()?;
+        histogram.max_ns = value.get("max_ns")?.as_u64()?;
+        Some(histogram)
+    }
+
+    /// append the histogram as an OpenMetrics metric point of the `fusion_blossom_phase_latency_seconds` family,
+    /// see [`profiler_report_to_openmetrics`]
+    pub fn write_openmetrics(&self, output: &mut String, labels: &[(&str, String)]) {
+        let labels: Vec<String> = labels.iter().map(|(key, value)| format!("{key}=\"{value}\"")).collect();
+        let labels = labels.join(",");
+        let mut accumulated = 0;
+        let last_bucket = self.buckets.iter().rposition(|&count| count > 0).unwrap_or(0);
+        for bucket in 0..std::cmp::min(last_bucket + 1, LATENCY_HISTOGRAM_BUCKETS - 1) {
+            accumulated += self.buckets[bucket];
+            let upper_bound = Self::bucket_upper_bound_ns(bucket) as f64 * 1e-9;
+            output.push_str(&format!(
+                "{OPENMETRICS_PHASE_LATENCY}_bucket{{{labels},le=\"{upper_bound:e}\"}} {accumulated}\n"
+            ));
+        }
+        output.push_str(&format!(
+            "{OPENMETRICS_PHASE_LATENCY}_bucket{{{labels},le=\"+Inf\"}} {}\n",
+            self.count
+        ));
+        output.push_str(&format!(
+            "{OPENMETRICS_PHASE_LATENCY}_sum{{{labels}}} {:e}\n",
+            self.sum_ns as f64 * 1e-9
+        ));
+        output.push_str(&format!("{OPENMETRICS_PHASE_LATENCY}_count{{{labels}}} {}\n", self.count));
+    }
+}
+
+const OPENMETRICS_PHASE_LATENCY: &str = "fusion_blossom_phase_latency_seconds";
+
+/// the start of a timed call, carrying nothing when the `profiling` feature is disabled
+#[derive(Debug, Clone, Copy)]
+pub struct PhaseProfilerStart {
+    #[cfg(feature = "profiling")]
+    instant: Instant,
+}
+
+/// records per-call latency histograms of [`ProfilerPhase`] in a module; every method compiles to nothing unless the
+/// `profiling` feature is enabled, so that the modules can always hold one without paying for it
+#[derive(Debug, Clone, Default)]
+pub struct PhaseProfiler {
+    #[cfg(feature = "profiling")]
+    histograms: BTreeMap<ProfilerPhase, LatencyHistogram>,
+}
+
+impl PhaseProfiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn start(&self) -> PhaseProfilerStart {
+        PhaseProfilerStart {
+            #[cfg(feature = "profiling")]
+            instant: Instant::now(),
+        }
+    }
+
+    #[inline]
+    #[allow(unused_variables)]
+    pub fn record(&mut self, phase: ProfilerPhase, start: PhaseProfilerStart) {
+        #[cfg(feature = "profiling")]
+        self.histograms.entry(phase).or_default().record(start.instant.elapsed());
+    }
+
+    #[allow(unused_variables)]
+    pub fn histogram(&self, phase: ProfilerPhase) -> Option<&LatencyHistogram> {
+        let histogram: Option<&LatencyHistogram> = None;
+        #[cfg(feature = "profiling")]
+        let histogram = self.histograms.get(&phase);
+        histogram
+    }
+
+    #[allow(unused_variables)]
+    pub fn merge(&mut self, other: &Self) {
+        #[cfg(feature = "profiling")]
+        for (phase, histogram) in other.histograms.iter() {
+            self.histograms.entry(*phase).or_default().merge(histogram);
+        }
+    }
+
+    /// a map from the phase name to [`LatencyHistogram::to_json`], empty when the `profiling` feature is disabled
+    pub fn to_json(&self) -> serde_json::Value {
+        #[cfg(feature = "profiling")]
+        let phases = self
+            .histograms
+            .iter()
+            .map(|(phase, histogram)| (phase.name().to_string(), histogram.to_json()))
+            .collect();
+        #[cfg(not(feature = "profiling"))]
+        let phases = serde_json::Map::new();
+        serde_json::Value::Object(phases)
+    }
+}
+
+/// convert the per-phase latency histograms in a profiler report into OpenMetrics text: every `"phases"` map is
+/// exported with a `module` label of its path in the report, and every `"unit_phases"` list additionally with a
+/// `unit` label of the index in the list
+pub fn profiler_report_to_openmetrics(report: &serde_json::Value) -> String {
+    fn collect(value: &serde_json::Value, path: &mut Vec<String>, output: &mut String) {
+        let serde_json::Value::Object(map) = value else {
+            if let serde_json::Value::Array(array) = value {
+                for (index, element) in array.iter().enumerate() {
+                    path.push(index.to_string());
+                    collect(element, path, output);
+                    path.pop();
+                }
+            }
+            return;
+        };
+        let module = path.join(".");
+        let write_phases = |phases: &serde_json::Value, unit: Option<usize>, output: &mut String| {
+            let Some(phases) = phases.as_object() else { return };
+            for (name, histogram) in phases.iter() {
+                let Some(histogram) = LatencyHistogram::from_json(histogram) else {
+                    continue;
+                };
+                let mut labels = vec![("module", module.clone()), ("phase", name.clone())];
+                if let Some(unit) = unit {
+                    labels.push(("unit", unit.to_string()));
+                }
+                histogram.write_openmetrics(output, &labels);
+            }
+        };
+        for (key, value) in map.iter() {
+            match key.as_str() {
+                "phases" => write_phases(value, None, output),
+                "unit_phases" => {
+                    for (unit, phases) in value.as_array().into_iter().flatten().enumerate() {
+                        write_phases(phases, Some(unit), output);
+                    }
+                }
+                _ => {
+                    path.push(key.clone());
+                    collect(value, path, output);
+                    path.pop();
+                }
+            }
+        }
+    }
+    let mut output = format!("# TYPE {OPENMETRICS_PHASE_LATENCY} histogram\n# UNIT {OPENMETRICS_PHASE_LATENCY} seconds\n");
+    collect(report, &mut vec![], &mut output);
+    output.push_str("# EOF\n");
+    output
+}
+
 /**
  * If you want to modify a field of a Rust struct, it will return a copy of it to avoid memory unsafety.
  * Thus, typical way of modifying a python field doesn't work, e.g. `obj.a.b.c = 1` won't actually modify `obj`.
@@ -959,6 +2564,7 @@ pub(crate) fn register(py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<PartitionInfo>()?;
     m.add_class::<PartitionConfig>()?;
     m.add_class::<SyndromePattern>()?;
+    m.add_class::<VertexRemover>()?;
     use crate::pyo3::PyTypeInfo;
     // m.add_class::<IndexRange>()?;
     m.add("VertexRange", VertexRange::type_object(py))?;
@@ -972,6 +2578,228 @@ pub(crate) fn register(py: Python<'_>, m: &PyModule) -> PyResult<()> {
 pub mod tests {
     use super::*;
 
+    /// the negative weights are flipped, and the minimum-weight correction of the original problem is recovered
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn util_edge_reweighter_1() {
+        // cargo test util_edge_reweighter_1 -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        use crate::rand_xoshiro::rand_core::SeedableRng;
+        let mut code = CodeCapacityPlanarCode::new(3, 0.2, 500);
+        let initializer = code.get_initializer();
+        let edge_num = initializer.weighted_edges.len();
+        assert!(edge_num <= 16, "too many edges to enumerate all the corrections");
+        let reweighter = EdgeReweighter::new(&initializer);
+        let mut solver = SolverSerial::new(&initializer);
+        let mut rng = DeterministicRng::seed_from_u64(123);
+        let mut flipped = false;
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            let weights: Vec<(EdgeIndex, Weight)> = (0..edge_num)
+                .map(|edge_index| (edge_index as EdgeIndex, 2 * (rng.next_u32() % 9) as Weight - 6))
+                .collect();
+            let reweighted = reweighter.reweight(&syndrome_pattern, &weights);
+            assert!(reweighted
+                .syndrome_pattern
+                .dynamic_weights
+                .iter()
+                .all(|&(_, weight)| weight >= 0));
+            flipped |= !reweighted.flipped_edges.is_empty();
+            solver.solve(&reweighted.syndrome_pattern);
+            let weight = reweighted.original_weight(solver.sum_dual_variables());
+            let subgraph = reweighted.original_subgraph(&solver.subgraph());
+            solver.clear();
+            // the correction generates the original syndrome with the minimum weight
+            let boundary = |edges: &mut dyn Iterator<Item = usize>| -> BTreeSet<VertexIndex> {
+                let mut boundary = BTreeSet::new();
+                for edge_index in edges {
+                    let (left, right) = reweighter.edge_endpoints[edge_index];
+                    for vertex_index in [left, right] {
+                        if !reweighter.is_virtual[vertex_index as usize] && !boundary.remove(&vertex_index) {
+                            boundary.insert(vertex_index);
+                        }
+                    }
+                }
+                boundary
+            };
+            let defect_vertices: BTreeSet<VertexIndex> = syndrome_pattern.defect_vertices.iter().cloned().collect();
+            assert_eq!(
+                boundary(&mut subgraph.iter().map(|&edge_index| edge_index as usize)),
+                defect_vertices
+            );
+            assert_eq!(
+                subgraph
+                    .iter()
+                    .map(|&edge_index| weights[edge_index as usize].1)
+                    .sum::<Weight>(),
+                weight
+            );
+            let minimum_weight = (0..1usize << edge_num)
+                .filter(|mask| {
+                    boundary(&mut (0..edge_num).filter(|edge_index| mask >> edge_index & 1 == 1)) == defect_vertices
+                })
+                .map(|mask| {
+                    (0..edge_num)
+                        .filter(|edge_index| mask >> edge_index & 1 == 1)
+                        .map(|edge_index| weights[edge_index].1)
+                        .sum::<Weight>()
+                })
+                .min()
+                .unwrap();
+            assert_eq!(weight, minimum_weight);
+        }
+        assert!(flipped);
+    }
+
+    /// a weighted virtual vertex costs its boundary weight to match into
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn util_solver_initializer_weighted_virtual_vertices_1() {
+        // cargo test util_solver_initializer_weighted_virtual_vertices_1 -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        use crate::primal_module::SubGraphBuilder;
+        // a chain 0 - 1 - 2 - 3 - 4 with both ends on the boundary
+        let weighted_edges = vec![(0, 1, 2), (1, 2, 2), (2, 3, 2), (3, 4, 2)];
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![1]);
+        let initializer = SolverInitializer::new(5, weighted_edges.clone(), vec![0, 4]);
+        let mut solver = SolverSerial::new(&initializer);
+        solver.solve(&syndrome_pattern);
+        assert_eq!((solver.sum_dual_variables(), solver.subgraph()), (2, vec![0]));
+        // the left boundary is lossy, so that matching through the right boundary is cheaper
+        let initializer = SolverInitializer::new(5, weighted_edges, vec![4]).with_weighted_virtual_vertices(vec![(0, 6)]);
+        let mut solver = SolverSerial::new(&initializer);
+        solver.solve(&syndrome_pattern);
+        assert_eq!((solver.sum_dual_variables(), solver.subgraph()), (6, vec![1, 2, 3]));
+        // the same as an explicit boundary edge
+        let code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let mut initializer = code.get_initializer();
+        let virtual_vertices = std::mem::take(&mut initializer.virtual_vertices);
+        let boundary_weights: Vec<(VertexIndex, Weight)> = virtual_vertices
+            .iter()
+            .enumerate()
+            .map(|(index, &vertex_index)| (vertex_index, 100 * (index as Weight % 3)))
+            .collect();
+        let weighted_initializer = initializer.with_weighted_virtual_vertices(boundary_weights);
+        let expanded_initializer = weighted_initializer.expand_weighted_virtual_vertices().into_owned();
+        assert_eq!(
+            expanded_initializer.vertex_num as usize,
+            code.vertices.len() + virtual_vertices.len()
+        );
+        let mut solver = SolverSerial::new(&weighted_initializer);
+        let mut expanded_solver = SolverSerial::new(&expanded_initializer);
+        let edge_num = weighted_initializer.weighted_edges.len();
+        let mut subgraph_builder = SubGraphBuilder::new(&weighted_initializer);
+        for seed in 0..50 {
+            let mut code = code.clone();
+            let syndrome_pattern = code.generate_random_errors(seed);
+            solver.solve(&syndrome_pattern);
+            expanded_solver.solve(&syndrome_pattern);
+            assert_eq!(solver.sum_dual_variables(), expanded_solver.sum_dual_variables());
+            let subgraph = solver.subgraph();
+            assert!(subgraph.iter().all(|&edge_index| (edge_index as usize) < edge_num));
+            subgraph_builder.load_subgraph(&subgraph);
+            assert!(subgraph_builder.total_weight() <= solver.sum_dual_variables());
+            subgraph_builder.clear();
+            solver.clear();
+            expanded_solver.clear();
+        }
+    }
+
+    /// floating-point weights are scaled to even integers, keeping their ratios up to rounding
+    #[test]
+    fn util_solver_initializer_from_float_weights_1() {
+        // cargo test util_solver_initializer_from_float_weights_1 -- --nocapture
+        let initializer = SolverInitializer::from_float_weights(
+            4,
+            vec![(0, 1, 2.3), (1, 2, 4.6), (2, 3, 0.), (0, 3, 0.0001)],
+            vec![3],
+            1000,
+        );
+        let weights: Vec<Weight> = initializer.weighted_edges.iter().map(|&(_, _, weight)| weight).collect();
+        assert_eq!(weights, vec![1000, 2000, 0, 0]);
+        let initializer = SolverInitializer::from_float_weights(2, vec![(0, 1, 0.)], vec![], 1000);
+        assert_eq!(initializer.weighted_edges, vec![(0, 1, 0)]);
+        for weight in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -1.] {
+            let weighted_edges = vec![(0, 1, 1.), (1, 2, weight)];
+            let message = SolverInitializer::check_float_weights(&weighted_edges, 1000).unwrap_err();
+            assert!(message.starts_with("edge (1, 2)"), "{}", message);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "non-finite weight NaN")]
+    fn util_solver_initializer_from_float_weights_nan() {
+        // cargo test util_solver_initializer_from_float_weights_nan -- --nocapture
+        SolverInitializer::from_float_weights(3, vec![(0, 1, 1.), (1, 2, f64::NAN)], vec![], 1000);
+    }
+
+    /// a procedurally generated edge stream gives the same initializer as the materialized edges
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn util_solver_initializer_from_edges_1() {
+        // cargo test util_solver_initializer_from_edges_1 -- --nocapture
+        let vertex_num = 1000;
+        let edges =
+            (0..vertex_num - 1).map(|vertex_index| (vertex_index, vertex_index + 1, 2 * (vertex_index % 7 + 1) as Weight));
+        let initializer =
+            SolverInitializer::from_edges(vertex_num, vertex_num as usize - 1, edges.clone(), vec![0, vertex_num - 1]);
+        assert_eq!(initializer.weighted_edges, edges.collect::<Vec<_>>());
+        assert_eq!(initializer.weighted_edges.capacity(), vertex_num as usize - 1);
+        assert_eq!(initializer.virtual_vertices, vec![0, vertex_num - 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "fewer edges")]
+    fn util_solver_initializer_from_edges_2() {
+        // cargo test util_solver_initializer_from_edges_2 -- --nocapture
+        SolverInitializer::from_edges(3, 3, [(0, 1, 2), (1, 2, 2)], vec![]);
+    }
+
+    /// a symmetric CSR adjacency matrix and its upper triangle give the same edges as a list of weighted edges
+    #[test]
+    fn util_solver_initializer_from_csr_1() {
+        // cargo test util_solver_initializer_from_csr_1 -- --nocapture
+        // 0 - 1 - 2 - 3 and 0 - 2
+        let indptr = [0, 2, 4, 7, 8];
+        let indices = [1, 2, 0, 2, 0, 1, 3, 2];
+        let weights = [2, 6, 2, 4, 6, 4, 8, 8];
+        let initializer = SolverInitializer::from_csr(&indptr, &indices, &weights, vec![3]);
+        assert_eq!(initializer.vertex_num, 4);
+        assert_eq!(initializer.weighted_edges, vec![(0, 1, 2), (0, 2, 6), (1, 2, 4), (2, 3, 8)]);
+        assert_eq!(initializer.weighted_edges.capacity(), 4);
+        assert_eq!(initializer.virtual_vertices, vec![3]);
+        let upper_triangle = SolverInitializer::from_csr(&[0, 2, 3, 4, 4], &[1, 2, 2, 3], &[2, 6, 4, 8], vec![3]);
+        assert_eq!(upper_triangle.weighted_edges, initializer.weighted_edges);
+        let lower_triangle = SolverInitializer::from_csr(&[0, 0, 1, 3, 4], &[0, 0, 1, 2], &[2, 6, 4, 8], vec![3]);
+        assert_eq!(lower_triangle.weighted_edges, initializer.weighted_edges);
+    }
+
+    #[test]
+    #[should_panic(expected = "disagree")]
+    fn util_solver_initializer_from_csr_2() {
+        // cargo test util_solver_initializer_from_csr_2 -- --nocapture
+        SolverInitializer::from_csr(&[0, 1, 2], &[1, 0], &[2, 4], vec![]);
+    }
+
+    /// invalid CSR arrays are reported instead of building a wrong graph
+    #[test]
+    fn util_solver_initializer_from_csr_3() {
+        // cargo test util_solver_initializer_from_csr_3 -- --nocapture
+        let check = |indptr: &[usize], indices: &[VertexIndex], weights: &[Weight]| {
+            SolverInitializer::check_csr(indptr, indices, weights).unwrap_err()
+        };
+        assert!(check(&[0, 1, 2], &[1, 0], &[2, 4]).contains("disagree"));
+        assert!(check(&[0, 2, 2], &[1, 1], &[2, 2]).contains("duplicate"));
+        assert!(check(&[0, 1, 1], &[0], &[2]).contains("self loop"));
+        assert!(check(&[0, 1, 1], &[2], &[2]).contains("out of range"));
+        // an edge in only one of the triangles is fine
+        assert!(SolverInitializer::check_csr(&[0, 1, 2, 2], &[1, 0], &[2, 2]).is_ok());
+        let mixed = SolverInitializer::from_csr(&[0, 1, 2, 3], &[1, 0, 1], &[2, 2, 4], vec![]);
+        assert_eq!(mixed.weighted_edges, vec![(0, 1, 2), (1, 2, 4)]);
+    }
+
     /// test syndrome partition utilities
     #[test]
     fn util_partitioned_syndrome_pattern_1() {
@@ -1003,4 +2831,340 @@ pub mod tests {
             assert_eq!(owned_partitioned.whole_defect_range, expected_defect_range);
         }
     }
+
+    /// test human-readable unit names survive a JSON round trip
+    #[test]
+    fn util_partition_unit_names_1() {
+        // cargo test util_partition_unit_names_1 -- --nocapture
+        let mut partition_config = PartitionConfig::new(132);
+        partition_config.partitions = vec![
+            VertexRange::new(0, 72),   // unit 0
+            VertexRange::new(84, 132), // unit 1
+        ];
+        partition_config.fusions = vec![
+            (0, 1), // unit 2, by fusing 0 and 1
+        ];
+        partition_config.set_name(0, "top half".to_string());
+        partition_config.set_name(1, "bottom half".to_string());
+        let partition_info = partition_config.info();
+        assert_eq!(partition_info.unit_name(0), "top half");
+        assert_eq!(partition_info.unit_name(2), "unit 2");
+        let json = serde_json::to_string(&partition_info).unwrap();
+        let recovered: PartitionInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(recovered.config.names, partition_config.names);
+        assert_eq!(recovered.unit_name(1), "bottom half");
+        // configs without names are still accepted
+        let config: PartitionConfig = serde_json::from_str(r#"{"vertex_num":4,"partitions":[[0,4]],"fusions":[]}"#).unwrap();
+        assert!(config.names.is_empty());
+        assert_eq!(config.info().unit_name(0), "unit 0");
+    }
+
+    /// test the owners of vertices and edges
+    #[test]
+    fn util_partition_owners_1() {
+        // cargo test util_partition_owners_1 -- --nocapture
+        let initializer = SolverInitializer::new(6, vec![(0, 1, 2), (1, 2, 2), (2, 3, 2), (3, 4, 2), (4, 5, 2)], vec![0, 5]);
+        let mut partition_config = PartitionConfig::new(6);
+        partition_config.partitions = vec![VertexRange::new(0, 2), VertexRange::new(3, 6)];
+        partition_config.fusions = vec![(0, 1)];
+        let partition_info = partition_config.info();
+        let owners: Vec<usize> = (0..6)
+            .map(|vertex_index| partition_info.owner_of_vertex(vertex_index))
+            .collect();
+        assert_eq!(owners, vec![0, 0, 2, 1, 1, 1]);
+        let interface_vertices: Vec<VertexIndex> = (0..6)
+            .filter(|&vertex_index| partition_info.is_interface_vertex(vertex_index))
+            .collect();
+        assert_eq!(interface_vertices, vec![2]);
+        let edge_owners: Vec<usize> = (0..5)
+            .map(|edge_index| partition_info.owner_of_edge(&initializer, edge_index))
+            .collect();
+        assert_eq!(edge_owners, vec![0, 2, 2, 1, 1]);
+    }
+
+    /// test the shapes of the fusion trees and that every shape decodes a time partition to the same result
+    #[test]
+    fn util_partition_fusion_trees_1() {
+        // cargo test util_partition_fusion_trees_1 -- --nocapture
+        use crate::example_codes::*;
+        use crate::example_partition::*;
+        use crate::mwpm_solver::*;
+        // 8 leaves with a single interface vertex between each pair of neighbors
+        let leaves: Vec<VertexRange> = (0..8).map(|i| VertexRange::new(4 * i, 4 * i + 3)).collect();
+        let linear = PartitionConfig::new_linear(31, leaves.clone());
+        assert_eq!(linear.fusions[..3], [(0, 1), (8, 2), (9, 3)]);
+        assert_eq!(linear.info().fusion_depth(), 7);
+        let balanced = PartitionConfig::new_balanced_tree(31, leaves.clone());
+        assert_eq!(balanced.fusions[..3], [(0, 1), (2, 3), (8, 9)]);
+        assert_eq!(balanced.info().fusion_depth(), 3);
+        let grid = PartitionConfig::new_2d_grid(31, leaves.clone(), 2, 4);
+        assert_eq!(grid.sanity_check(), Ok(()));
+        assert_eq!(grid.info().units[14].children, Some((10, 13)));
+        assert_eq!(grid.info().fusion_depth(), 3);
+        assert_eq!(
+            PartitionConfig::new_2d_grid(31, leaves.clone(), 8, 1).info().fusion_depth(),
+            3
+        );
+        // invalid configurations are reported without panicking
+        let mut invalid = balanced.clone();
+        invalid.vertex_num = 32;
+        assert!(invalid
+            .sanity_check()
+            .unwrap_err()
+            .contains("final range not covering all vertices"));
+        invalid = balanced.clone();
+        invalid.fusions[1] = (1, 3);
+        assert!(invalid.sanity_check().unwrap_err().contains("cannot fuse 1 twice"));
+        invalid = balanced.clone();
+        invalid.fusions[0] = (1, 0);
+        assert!(invalid
+            .sanity_check()
+            .unwrap_err()
+            .contains("only lower range can fuse higher range"));
+        // the same leaves of a time partition decode to the same result with any fusion tree
+        let mut code = PhenomenologicalPlanarCode::new(5, 7, 0.03, 500);
+        let time_partition = PhenomenologicalPlanarCodeTimePartition::new(5, 7, 4).build_apply(&mut code);
+        let initializer = code.get_initializer();
+        let (vertex_num, partitions) = (time_partition.vertex_num, time_partition.partitions.clone());
+        let mut solvers = vec![
+            SolverParallel::new(
+                &initializer,
+                &PartitionConfig::new_linear(vertex_num, partitions.clone()).info(),
+                json!({}),
+            ),
+            SolverParallel::new(
+                &initializer,
+                &PartitionConfig::new_balanced_tree(vertex_num, partitions).info(),
+                json!({}),
+            ),
+        ];
+        let mut serial_solver = SolverSerial::new(&initializer);
+        for seed in 0..50 {
+            let syndrome_pattern = code.generate_random_errors(seed);
+            serial_solver.solve(&syndrome_pattern);
+            for solver in solvers.iter_mut() {
+                solver.solve(&syndrome_pattern);
+                assert_eq!(solver.sum_dual_variables(), serial_solver.sum_dual_variables());
+                solver.clear();
+            }
+            serial_solver.clear();
+        }
+    }
+
+    /// test vertex removal by contracting into neighbors
+    #[test]
+    fn util_vertex_remover_1() {
+        // cargo test util_vertex_remover_1 -- --nocapture
+        use crate::mwpm_solver::*;
+        let initializer = SolverInitializer::new(5, vec![(0, 1, 2), (1, 2, 2), (2, 3, 2), (3, 4, 2)], vec![0, 4]);
+        let remover = VertexRemover::new(&initializer);
+        assert_eq!(remover.contraction_edges(vec![2]), vec![(2, 1, 1)]);
+        assert_eq!(remover.contraction_edges(vec![2, 3]), vec![(2, 1, 1), (3, 3, 4)]);
+        // vertex 2 has no kept neighbor at first, so it is contracted into vertex 1 in the second round
+        assert_eq!(
+            remover.contraction_edges(vec![1, 2, 3]),
+            vec![(1, 0, 0), (2, 1, 1), (3, 3, 4)]
+        );
+        // the parity of the merged super-vertex {1, 2}
+        let syndrome_pattern = remover.remove_vertices(&SyndromePattern::new_vertices(vec![1, 2]), vec![2]);
+        assert!(syndrome_pattern.defect_vertices.is_empty());
+        let syndrome_pattern = remover.remove_vertices(&SyndromePattern::new_vertices(vec![2, 3]), vec![2]);
+        assert_eq!(syndrome_pattern.defect_vertices, vec![1, 3]);
+        assert_eq!(syndrome_pattern.erasures, vec![1]);
+        let mut solver = SolverSerial::new(&initializer);
+        solver.solve(&syndrome_pattern);
+        assert_eq!(solver.subgraph(), vec![1, 2]);
+        // the bypass edge 1 is inside the super-vertex {1, 2}
+        assert_eq!(remover.original_subgraph(solver.subgraph(), vec![2]), vec![2]);
+        // dynamic weights are overwritten instead of adding erasures
+        let syndrome_pattern = remover.remove_vertices(
+            &SyndromePattern::new_dynamic_weights(vec![3], vec![], vec![(1, 4), (3, 6)]),
+            vec![2],
+        );
+        assert!(syndrome_pattern.erasures.is_empty());
+        assert_eq!(syndrome_pattern.dynamic_weights, vec![(3, 6), (1, 0)]);
+    }
+
+    /// test detector confidences scale the weights of time-like edges only
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn util_detector_confidences_1() {
+        // cargo test util_detector_confidences_1 -- --nocapture
+        use crate::example_codes::*;
+        let code = PhenomenologicalPlanarCode::new(3, 3, 0.01, 500);
+        let initializer = code.get_initializer();
+        let timelike_edges = code.timelike_edges();
+        let vertex_index: VertexIndex = 5;
+        assert!(!timelike_edges[vertex_index as usize].is_empty());
+        let mut syndrome_pattern = SyndromePattern::new_vertices(vec![vertex_index]);
+        syndrome_pattern.detector_confidences = vec![(vertex_index, 0.5)];
+        let json = serde_json::to_string(&syndrome_pattern).unwrap();
+        let recovered: SyndromePattern = serde_json::from_str(&json).unwrap();
+        assert_eq!(recovered, syndrome_pattern);
+        syndrome_pattern.apply_detector_confidences(&initializer, &timelike_edges);
+        assert!(syndrome_pattern.detector_confidences.is_empty());
+        let modified: Vec<EdgeIndex> = syndrome_pattern.dynamic_weights.iter().map(|(e, _)| *e).collect();
+        assert_eq!(modified, timelike_edges[vertex_index as usize]);
+        for &(edge_index, weight) in syndrome_pattern.dynamic_weights.iter() {
+            assert_eq!(weight, initializer.weighted_edges[edge_index as usize].2 / 2);
+        }
+        // patterns without confidences are unaffected
+        let mut plain = SyndromePattern::new_vertices(vec![vertex_index]);
+        plain.apply_detector_confidences(&initializer, &timelike_edges);
+        assert!(plain.dynamic_weights.is_empty());
+    }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn util_ignore_detectors_1() {
+        // cargo test util_ignore_detectors_1 -- --nocapture
+        use crate::example_codes::*;
+        use crate::mwpm_solver::*;
+        let mut code = PhenomenologicalPlanarCode::new(5, 5, 0.05, 500);
+        let initializer = code.get_initializer();
+        let timelike_edges = code.timelike_edges();
+        let mut solver = SolverSerial::new(&initializer);
+        for seed in 0..50 {
+            let mut syndrome_pattern = code.generate_random_errors(seed);
+            let erasures = syndrome_pattern.erasures.clone();
+            // ignore every other defect and a detector without defect
+            let mut ignored_detectors: Vec<VertexIndex> =
+                syndrome_pattern.defect_vertices.iter().step_by(2).cloned().collect();
+            let expected_ignored = ignored_detectors.len();
+            let kept: Vec<VertexIndex> = syndrome_pattern.defect_vertices.iter().skip(1).step_by(2).cloned().collect();
+            let quiet_detector = (0..initializer.vertex_num)
+                .find(|vertex_index| {
+                    !syndrome_pattern.defect_vertices.contains(vertex_index)
+                        && !timelike_edges[*vertex_index as usize].is_empty()
+                })
+                .unwrap();
+            ignored_detectors.push(quiet_detector);
+            assert_eq!(
+                syndrome_pattern.ignore_detectors(&ignored_detectors, &timelike_edges),
+                expected_ignored
+            );
+            assert_eq!(syndrome_pattern.defect_vertices, kept);
+            for &vertex_index in ignored_detectors.iter() {
+                for edge_index in timelike_edges[vertex_index as usize].iter() {
+                    assert!(syndrome_pattern.erasures.contains(edge_index));
+                }
+            }
+            for edge_index in erasures.iter() {
+                assert!(syndrome_pattern.erasures.contains(edge_index));
+            }
+            // the remaining defects are still explained by the correction
+            solver.solve(&syndrome_pattern);
+            let subgraph = solver.subgraph();
+            let mut defects: Vec<_> = initializer.syndrome_of(&subgraph).into_iter().collect();
+            defects.sort();
+            assert_eq!(defects, kept);
+            solver.clear();
+        }
+        // nothing to ignore
+        let mut syndrome_pattern = SyndromePattern::new_vertices(vec![1]);
+        assert_eq!(syndrome_pattern.ignore_detectors(&[], &timelike_edges), 0);
+        assert!(syndrome_pattern.erasures.is_empty());
+    }
+
+    /// test warmup shots are reported separately from the steady-state statistics
+    #[test]
+    fn util_benchmark_profiler_warmup_1() {
+        // cargo test util_benchmark_profiler_warmup_1 -- --nocapture
+        let mut benchmark_profiler = BenchmarkProfiler::new(0, None);
+        benchmark_profiler.warmup_rounds = 2;
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![0, 1]);
+        let mut warmup_flags = vec![];
+        for _ in 0..5 {
+            benchmark_profiler.begin(&syndrome_pattern);
+            benchmark_profiler.end(None);
+            warmup_flags.push(benchmark_profiler.is_warmup());
+        }
+        assert_eq!(warmup_flags, vec![true, true, false, false, false]);
+        assert_eq!(benchmark_profiler.warmup_records_count, 2);
+        assert_eq!(benchmark_profiler.records_count, 3);
+        assert_eq!(benchmark_profiler.sum_syndrome, 6);
+        assert!(benchmark_profiler.brief().contains("cold: "));
+    }
+
+    /// test the fingerprint is stable and stamped into the benchmark outputs
+    #[test]
+    fn util_output_fingerprint_1() {
+        // cargo test util_output_fingerprint_1 -- --nocapture
+        assert_eq!(config_hash(&json!({})), "08f44b07b5901a25");
+        let config = json!({ "a": 1, "b": [2, 3] });
+        assert_eq!(config_hash(&config), config_hash(&json!({ "b": [2, 3], "a": 1 })));
+        assert_ne!(config_hash(&config), config_hash(&json!({ "a": 1, "b": [3, 2] })));
+        let fingerprint = OutputFingerprint::new(&config);
+        assert_eq!(fingerprint.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(fingerprint.seed, None);
+        assert_eq!(fingerprint.with_seed(7).seed, Some(7));
+        // benchmark results
+        let filename = std::env::temp_dir().join("util_output_fingerprint_1.csv");
+        let mut writer = BenchmarkResultsWriter::new(filename.to_str().unwrap(), fingerprint.clone()).unwrap();
+        let row = BenchmarkResultsRow {
+            round: 0,
+            seed: 7,
+            defect_num: 2,
+            erasure_num: 0,
+            weight: 1000,
+            success: Some(false),
+            conflicts: None,
+            decode_time: 1e-6,
+            round_time: 2e-6,
+        };
+        writer.write_row(&row).unwrap();
+        writer.flush().unwrap();
+        let content = std::fs::read_to_string(&filename).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], BenchmarkResultsWriter::HEADER);
+        assert!(lines[1].starts_with("0,7,2,0,1000,0,,"));
+        assert!(lines[1].ends_with(&format!(",{},{}", fingerprint.version, fingerprint.config_hash)));
+        // profiler output
+        let filename = std::env::temp_dir().join("util_output_fingerprint_1.profile");
+        let partition_info = PartitionConfig::new(2).info();
+        let mut benchmark_profiler = BenchmarkProfiler::new(
+            0,
+            Some((filename.to_str().unwrap().to_string(), &partition_info, &fingerprint)),
+        );
+        benchmark_profiler.begin(&SyndromePattern::new_vertices(vec![0, 1]));
+        benchmark_profiler.set_seed(7);
+        benchmark_profiler.end(None);
+        drop(benchmark_profiler);
+        let content = std::fs::read_to_string(&filename).unwrap();
+        let lines: Vec<serde_json::Value> = content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines[1]["fingerprint"], json!(fingerprint));
+        assert_eq!(lines[2]["seed"], json!(7));
+    }
+
+    /// the buckets are powers of two in nanoseconds, and the quantiles are the upper bounds of their buckets
+    #[test]
+    fn util_latency_histogram_1() {
+        // cargo test util_latency_histogram_1 -- --nocapture
+        let mut histogram = LatencyHistogram::new();
+        for latency_ns in [0, 1, 3, 100, 100, 1000] {
+            histogram.record_ns(latency_ns);
+        }
+        assert_eq!(LatencyHistogram::bucket_of(0), 0);
+        assert_eq!(LatencyHistogram::bucket_of(3), 2);
+        assert_eq!(LatencyHistogram::bucket_of(100), 7);
+        assert_eq!(LatencyHistogram::bucket_of(u64::MAX), LATENCY_HISTOGRAM_BUCKETS - 1);
+        assert_eq!(histogram.count, 6);
+        assert_eq!(histogram.sum_ns, 1204);
+        assert_eq!((histogram.min_ns, histogram.max_ns), (0, 1000));
+        assert_eq!(histogram.quantile_ns(0.5), 4);
+        assert_eq!(histogram.quantile_ns(0.8), 128);
+        assert_eq!(histogram.quantile_ns(1.), 1000);
+        let mut merged = histogram.clone();
+        merged.merge(&histogram);
+        assert_eq!(merged.count, 12);
+        assert_eq!(merged.buckets()[7], 4);
+        let value = histogram.to_json();
+        assert_eq!(value["buckets"], json!([[1, 1], [2, 1], [4, 1], [128, 2], [1024, 1]]));
+        assert_eq!(LatencyHistogram::from_json(&value), Some(histogram.clone()));
+        let mut output = String::new();
+        histogram.write_openmetrics(&mut output, &[("phase", "grow".to_string())]);
+        assert!(output.contains("fusion_blossom_phase_latency_seconds_bucket{phase=\"grow\",le=\"1.28e-7\"} 5\n"));
+        assert!(output.contains("fusion_blossom_phase_latency_seconds_bucket{phase=\"grow\",le=\"+Inf\"} 6\n"));
+        assert!(output.contains("fusion_blossom_phase_latency_seconds_count{phase=\"grow\"} 6\n"));
+    }
 }
@@ -7,12 +7,12 @@ use crate::chrono::Local;
 use crate::serde::{Deserialize, Serialize};
 use crate::serde_json;
 use crate::urlencoding;
-#[cfg(feature = "python_binding")]
 use crate::util::*;
+pub use crate::visualize_sink::*;
 #[cfg(feature = "python_binding")]
 use pyo3::prelude::*;
 use std::fs::File;
-use std::io::{Seek, SeekFrom, Write};
+use std::io::Write;
 
 pub trait FusionVisualizer {
     /// take a snapshot, set `abbrev` to true to save space
@@ -64,17 +64,152 @@ impl VisualizePosition {
     }
 }
 
+/// the initial direction of the camera in the viewer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub enum CameraPreset {
+    /// look down the time axis, i.e. a single round of the decoding graph
+    Top,
+    /// look along the `j` axis
+    Left,
+    /// look along the `i` axis, with the rounds stacked vertically
+    Front,
+    /// look from a corner, to see all three axes of a time-domain decoding graph
+    Isometric,
+}
+
+/// how the viewer initially shows the decoding graph, written in the header of the file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pyclass)]
+pub struct VisualizeView {
+    /// the initial direction of the camera
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub camera: CameraPreset,
+    /// use the perspective camera instead of the orthogonal one
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub perspective: bool,
+    /// the distinct `t` positions of the vertices in ascending order, i.e. the rounds of a time-domain decoding graph;
+    /// the viewer slices the graph by a range of these layers, each vertex belonging to the layer nearest to its `t`
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub layers: Vec<f64>,
+}
+
+#[cfg_attr(feature = "python_binding", cfg_eval)]
+#[cfg_attr(feature = "python_binding", pymethods)]
+impl VisualizeView {
+    /// the rounds of a decoding graph are viewed from a corner, and a single round from the top
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    pub fn from_positions(positions: Vec<VisualizePosition>) -> Self {
+        let mut layers: Vec<f64> = positions.iter().map(|position| position.t).collect();
+        layers.sort_by(|a, b| a.partial_cmp(b).expect("position must not be NaN"));
+        layers.dedup_by(|a, b| (*a - *b).abs() < 1e-6);
+        Self {
+            camera: if layers.len() > 1 {
+                CameraPreset::Isometric
+            } else {
+                CameraPreset::Top
+            },
+            perspective: false,
+            layers,
+        }
+    }
+
+    /// the layer of a vertex given its `t` position
+    pub fn layer_of(&self, t: f64) -> usize {
+        let index = self.layers.partition_point(|layer| *layer < t);
+        if index == self.layers.len() || (index > 0 && t - self.layers[index - 1] < self.layers[index] - t) {
+            index.saturating_sub(1)
+        } else {
+            index
+        }
+    }
+
+    #[cfg(feature = "python_binding")]
+    fn __repr__(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
 #[derive(Debug)]
 #[cfg_attr(feature = "python_binding", cfg_eval)]
 #[cfg_attr(feature = "python_binding", pyclass)]
 pub struct Visualizer {
-    /// save to file if applicable
-    file: Option<File>,
-    /// if waiting for the first snapshot
-    empty_snapshot: bool,
-    /// names of the snapshots
+    /// where the snapshots go, see [`visualize_sink`](crate::visualize_sink)
+    sink: Box<dyn SnapshotSink>,
+    /// names of the snapshots; not recorded in the streamed mode to keep the memory bounded
     #[cfg_attr(feature = "python_binding", pyo3(get))]
     pub snapshots: Vec<String>,
+    /// the memory is bounded regardless of the number of snapshots, e.g. an append-only JSON Lines file where the first
+    /// line is the header with positions, followed by one snapshot per line
+    #[cfg_attr(feature = "python_binding", pyo3(get))]
+    pub streamed: bool,
+    /// the number of snapshots saved so far
+    #[cfg_attr(feature = "python_binding", pyo3(get))]
+    pub snapshot_num: usize,
+    /// the positions written in the header of the file, or kept in memory by a visualizer without a file
+    recorded_positions: Vec<VisualizePosition>,
+    /// written in the header of the file, see [`VisualizeView`]
+    view: Option<VisualizeView>,
+    /// the path of the file if the sink writes to one, used to serve it to the embedded frontend
+    #[cfg_attr(feature = "python_binding", pyo3(get))]
+    pub filepath: Option<String>,
+    /// written in the header of the file, see [`Visualizer::new_fingerprint`]
+    fingerprint: Option<OutputFingerprint>,
+    /// the seed of the current shot, stamped into the following snapshots
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub seed: Option<u64>,
+    /// record the dual variables of the following snapshots in memory, so that the timeline is available without reading
+    /// back the file, e.g. for a streamed visualizer of a long run; see [`Visualizer::timeline`]
+    #[cfg_attr(feature = "python_binding", pyo3(get, set))]
+    pub record_timeline: bool,
+    /// the recorded dual variables of each snapshot, when `record_timeline` is set
+    timeline: Vec<TimelinePoint>,
+}
+
+/// the dual variables of a snapshot, to plot the convergence of the algorithm over the snapshots
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelinePoint {
+    /// the name of the snapshot
+    pub name: String,
+    /// the sum of all dual variables, if the interface is included in the snapshot
+    pub sum_dual_variables: Option<Weight>,
+    /// the conflict that triggered the resolve step following this snapshot, see [`PrimalModuleImpl::solve_visualizer`]
+    ///
+    /// [`PrimalModuleImpl::solve_visualizer`]: crate::primal_module::PrimalModuleImpl::solve_visualizer
+    pub conflict: Option<String>,
+    /// the dual variable of each dual node, or `None` if the node is not in the snapshot
+    pub dual_variables: Vec<Option<Weight>>,
+}
+
+impl TimelinePoint {
+    /// extract the dual variables from a snapshot, abbreviated or not
+    pub fn from_snapshot(name: &str, value: &serde_json::Value) -> Self {
+        let as_weight = |value: &serde_json::Value| value.as_i64().map(|value| value as Weight);
+        let field = |object: &serde_json::Value, abbrev_key: &str, key: &str| {
+            object.get(abbrev_key).or_else(|| object.get(key)).and_then(as_weight)
+        };
+        let sum_dual_variables = value
+            .get("interface")
+            .and_then(|interface| field(interface, "d", "sum_dual_variables"));
+        let dual_variables = match value.get("dual_nodes").and_then(|dual_nodes| dual_nodes.as_array()) {
+            Some(dual_nodes) => dual_nodes
+                .iter()
+                .map(|dual_node| {
+                    // the dual module may not report the nodes of other units, then fall back to the value of the interface
+                    field(dual_node, "d", "dual_variable").or_else(|| field(dual_node, "di", "interface_dual_variable"))
+                })
+                .collect(),
+            None => vec![],
+        };
+        Self {
+            name: name.to_string(),
+            sum_dual_variables,
+            conflict: name.strip_prefix("resolve ").map(|conflict| conflict.to_string()),
+            dual_variables,
+        }
+    }
 }
 
 pub fn snapshot_fix_missing_fields(value: &mut serde_json::Value, abbrev: bool) {
@@ -411,40 +546,84 @@ pub fn center_positions(mut positions: Vec<VisualizePosition>) -> Vec<VisualizeP
 #[cfg_attr(feature = "python_binding", cfg_eval)]
 #[cfg_attr(feature = "python_binding", pymethods)]
 impl Visualizer {
-    /// create a new visualizer with target filename and node layout
+    /// create a new visualizer with target filename and node layout;
+    /// a filename ending with `.jsonl` creates a streamed visualizer that appends snapshots with bounded memory,
+    /// suitable for long monitored runs
     #[cfg_attr(feature = "python_binding", new)]
     #[cfg_attr(feature = "python_binding", pyo3(signature = (filepath, positions=vec![], center=true)))]
-    pub fn new(mut filepath: Option<String>, mut positions: Vec<VisualizePosition>, center: bool) -> std::io::Result<Self> {
-        if cfg!(feature = "disable_visualizer") {
-            filepath = None; // do not open file
-        }
+    pub fn new(filepath: Option<String>, positions: Vec<VisualizePosition>, center: bool) -> std::io::Result<Self> {
+        Self::new_fingerprint(filepath, positions, center, None)
+    }
+
+    /// create a visualizer that keeps only the latest `capacity` snapshots in memory, to bound the memory of a long run
+    #[cfg_attr(feature = "python_binding", staticmethod)]
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (capacity, positions=vec![], center=true)))]
+    pub fn new_ring_buffer(capacity: usize, positions: Vec<VisualizePosition>, center: bool) -> std::io::Result<Self> {
+        Self::new_sink(Box::new(RingBufferSink::new(capacity)), positions, center, None)
+    }
+
+    /// replace the positions of the vertices before the first snapshot, e.g. with a 3D layout of a time-domain decoding
+    /// graph where `t` is the round; the layers of the view are updated accordingly
+    #[cfg_attr(feature = "python_binding", pyo3(signature = (positions, center=true)))]
+    pub fn set_positions(&mut self, mut positions: Vec<VisualizePosition>, center: bool) -> std::io::Result<()> {
         if center {
             positions = center_positions(positions);
         }
-        let mut file = match filepath {
-            Some(filepath) => Some(File::create(filepath)?),
-            None => None,
+        let layers = VisualizeView::from_positions(positions.clone()).layers;
+        self.view = match self.view.take() {
+            Some(view) => Some(VisualizeView { layers, ..view }),
+            None => Some(VisualizeView::from_positions(positions.clone())).filter(|view| view.layers.len() > 1),
         };
-        if let Some(file) = file.as_mut() {
-            file.set_len(0)?; // truncate the file
-            file.seek(SeekFrom::Start(0))?; // move the cursor to the front
-            file.write_all(
-                format!(
-                    "{{\"format\":\"fusion_blossom\",\"version\":\"{}\"",
-                    env!("CARGO_PKG_VERSION")
-                )
-                .as_bytes(),
-            )?;
-            file.write_all(b",\"positions\":")?;
-            file.write_all(json!(positions).to_string().as_bytes())?;
-            file.write_all(b",\"snapshots\":[]}")?;
-            file.sync_all()?;
-        }
-        Ok(Self {
-            file,
-            empty_snapshot: true,
-            snapshots: vec![],
-        })
+        self.recorded_positions = positions;
+        self.write_header()
+    }
+
+    /// set the camera preset and the layers before the first snapshot
+    pub fn set_view(&mut self, view: VisualizeView) -> std::io::Result<()> {
+        self.view = Some(view);
+        self.write_header()
+    }
+
+    /// the number of snapshots kept in memory, see [`Visualizer::recorded_snapshots`]
+    #[cfg(feature = "python_binding")]
+    fn __len__(&self) -> usize {
+        self.sink.recorded_snapshots().len()
+    }
+
+    /// get a `(name, snapshot)` pair by index, or the latest snapshot with the given name
+    #[cfg(feature = "python_binding")]
+    fn __getitem__(&self, py: Python<'_>, key: &PyAny) -> PyResult<PyObject> {
+        let recorded_snapshots = self.sink.recorded_snapshots();
+        if let Ok(name) = key.extract::<String>() {
+            return match recorded_snapshots
+                .iter()
+                .rev()
+                .find(|(snapshot_name, _)| snapshot_name == &name)
+            {
+                Some((_, value)) => Ok(json_to_pyobject_locked(value.clone(), py)),
+                None => Err(pyo3::exceptions::PyKeyError::new_err(name)),
+            };
+        }
+        let index: isize = key.extract()?;
+        let length = recorded_snapshots.len() as isize;
+        let position = if index < 0 { index + length } else { index };
+        if position < 0 || position >= length {
+            return Err(pyo3::exceptions::PyIndexError::new_err("snapshot index out of range"));
+        }
+        let (name, value) = recorded_snapshots[position as usize];
+        Ok((name.clone(), json_to_pyobject_locked(value.clone(), py)).into_py(py))
+    }
+
+    /// iterate over the `(name, snapshot)` pairs kept in memory
+    #[cfg(feature = "python_binding")]
+    fn __iter__(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let pairs: Vec<PyObject> = self
+            .sink
+            .recorded_snapshots()
+            .into_iter()
+            .map(|(name, value)| (name.clone(), json_to_pyobject_locked(value.clone(), py)).into_py(py))
+            .collect();
+        Ok(pyo3::types::PyList::new(py, pairs).call_method0("__iter__")?.into())
     }
 
     #[cfg(feature = "python_binding")]
@@ -489,22 +668,116 @@ impl Visualizer {
         let value = pyobject_to_json(value_py);
         self.snapshot_value(name, value)
     }
+
+    /// see [`Visualizer::save_html`]
+    #[cfg(feature = "python_binding")]
+    #[pyo3(name = "save_html")]
+    pub fn save_html_py(&self, path: String) -> std::io::Result<()> {
+        self.save_html(&path)
+    }
+
+    /// see [`Visualizer::save_timeline_csv`]
+    #[cfg(feature = "python_binding")]
+    #[pyo3(name = "save_timeline_csv")]
+    pub fn save_timeline_csv_py(&self, path: String) -> std::io::Result<()> {
+        self.save_timeline_csv(&path)
+    }
 }
 
 impl Visualizer {
-    pub fn incremental_save(&mut self, name: String, value: serde_json::Value) -> std::io::Result<()> {
-        if let Some(file) = self.file.as_mut() {
+    /// create a new visualizer whose file header records the fingerprint of the run that produces it
+    pub fn new_fingerprint(
+        mut filepath: Option<String>,
+        positions: Vec<VisualizePosition>,
+        center: bool,
+        fingerprint: Option<OutputFingerprint>,
+    ) -> std::io::Result<Self> {
+        if cfg!(feature = "disable_visualizer") {
+            filepath = None; // do not open file
+        }
+        Self::new_sink(snapshot_sink_from_filepath(filepath)?, positions, center, fingerprint)
+    }
+
+    /// create a new visualizer writing to any snapshot sink, e.g. a [`RingBufferSink`] that keeps the latest snapshots
+    pub fn new_sink(
+        sink: Box<dyn SnapshotSink>,
+        mut positions: Vec<VisualizePosition>,
+        center: bool,
+        fingerprint: Option<OutputFingerprint>,
+    ) -> std::io::Result<Self> {
+        if center {
+            positions = center_positions(positions);
+        }
+        let view = Some(VisualizeView::from_positions(positions.clone())).filter(|view| view.layers.len() > 1);
+        let mut visualizer = Self {
+            snapshots: vec![],
+            streamed: sink.is_bounded(),
+            snapshot_num: 0,
+            recorded_positions: positions,
+            view,
+            filepath: sink.filepath().map(|filepath| filepath.to_string()),
+            fingerprint,
+            seed: None,
+            record_timeline: false,
+            timeline: vec![],
+            sink,
+        };
+        visualizer.write_header()?;
+        Ok(visualizer)
+    }
+
+    /// (re)write the header of the file, which is only possible before the first snapshot
+    fn write_header(&mut self) -> std::io::Result<()> {
+        if self.snapshot_num > 0 {
+            return Err(std::io::Error::other("cannot change the header after taking snapshots"));
+        }
+        let mut header = json!({
+            "format": "fusion_blossom",
+            "version": env!("CARGO_PKG_VERSION"),
+            "positions": self.recorded_positions,
+        });
+        if let Some(fingerprint) = self.fingerprint.as_ref() {
+            header["fingerprint"] = json!(fingerprint);
+        }
+        if let Some(view) = self.view.as_ref() {
+            header["view"] = json!(view);
+        }
+        self.sink.write_header(&header)
+    }
+
+    /// the view written in the header of the file
+    pub fn view(&self) -> Option<&VisualizeView> {
+        self.view.as_ref()
+    }
+
+    /// the fingerprint written in the header of the file
+    pub fn fingerprint(&self) -> Option<&OutputFingerprint> {
+        self.fingerprint.as_ref()
+    }
+
+    /// the `(name, snapshot)` pairs kept in memory; only the in-memory sinks record them, e.g. a visualizer created without
+    /// a file
+    pub fn recorded_snapshots(&self) -> Vec<&(String, serde_json::Value)> {
+        self.sink.recorded_snapshots()
+    }
+
+    /// the whole visualizer data in the same format as the file, read back from the file if any
+    pub fn data(&self) -> std::io::Result<serde_json::Value> {
+        self.sink.data()
+    }
+
+    pub fn incremental_save(&mut self, name: String, mut value: serde_json::Value) -> std::io::Result<()> {
+        if let (Some(seed), Some(object)) = (self.seed, value.as_object_mut()) {
+            object.insert("seed".to_string(), json!(seed));
+        }
+        if self.record_timeline {
+            self.timeline.push(TimelinePoint::from_snapshot(&name, &value));
+        }
+        self.snapshot_num += 1;
+        if !self.streamed {
             self.snapshots.push(name.clone());
-            file.seek(SeekFrom::End(-2))?; // move the cursor before the ending ]}
-            if !self.empty_snapshot {
-                file.write_all(b",")?;
-            }
-            self.empty_snapshot = false;
-            file.write_all(json!((name, value)).to_string().as_bytes())?;
-            file.write_all(b"]}")?;
-            file.sync_all()?;
         }
-        Ok(())
+        self.sink.write_snapshot(&name, value)
     }
 
     /// append another snapshot of the fusion type, and also update the file in case
@@ -558,6 +831,247 @@ impl Visualizer {
         self.incremental_save(name, value)?;
         Ok(())
     }
+
+    /// the dual variables of every snapshot: the recorded ones if `record_timeline` is set, otherwise extracted from
+    /// [`Visualizer::data`]
+    pub fn timeline(&self) -> std::io::Result<Vec<TimelinePoint>> {
+        if self.record_timeline {
+            return Ok(self.timeline.clone());
+        }
+        let data = self.data()?;
+        let snapshots = data["snapshots"].as_array().cloned().unwrap_or_default();
+        Ok(snapshots
+            .iter()
+            .map(|snapshot| TimelinePoint::from_snapshot(snapshot[0].as_str().unwrap_or(""), &snapshot[1]))
+            .collect())
+    }
+
+    /// export the timeline as a CSV file with one row per snapshot: the index, name, sum of dual variables, the conflict
+    /// being resolved, and then the dual variable of each node as `node_0`, `node_1`, ...; missing values are left empty
+    pub fn save_timeline_csv(&self, path: &str) -> std::io::Result<()> {
+        let timeline = self.timeline()?;
+        let node_num = timeline.iter().map(|point| point.dual_variables.len()).max().unwrap_or(0);
+        let optional = |value: Option<Weight>| value.map(|value| value.to_string()).unwrap_or_default();
+        let mut file = std::io::BufWriter::new(File::create(path)?);
+        write!(file, "snapshot,name,sum_dual_variables,conflict")?;
+        for node_index in 0..node_num {
+            write!(file, ",node_{node_index}")?;
+        }
+        writeln!(file)?;
+        for (index, point) in timeline.iter().enumerate() {
+            write!(
+                file,
+                "{index},{},{},{}",
+                csv_field(&point.name),
+                optional(point.sum_dual_variables),
+                csv_field(point.conflict.as_deref().unwrap_or(""))
+            )?;
+            for node_index in 0..node_num {
+                write!(file, ",{}", optional(point.dual_variables.get(node_index).cloned().flatten()))?;
+            }
+            writeln!(file)?;
+        }
+        file.flush()
+    }
+}
+
+/// quote a CSV field if needed, e.g. the debug print of a conflict contains commas
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(feature = "embed_visualizer")]
+impl Visualizer {
+    /// serve the embedded frontend together with the file of this visualizer on an ephemeral local port, returning the
+    /// link to open; the server runs in a background thread until the process exits
+    pub fn serve_in_background(&self) -> std::io::Result<String> {
+        let filepath = self.filepath.clone().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "visualizer without a file cannot be served")
+        })?;
+        if filepath.ends_with(".zst") {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "compressed visualizer file cannot be served, use `save_html` instead",
+            ));
+        }
+        let filename = match std::path::Path::new(&filepath).file_name() {
+            Some(filename) => filename.to_string_lossy().to_string(),
+            None => filepath.clone(),
+        };
+        let listener = std::net::TcpListener::bind(("127.0.0.1", 0))?;
+        let link = format!(
+            "http://localhost:{}/?filename={}",
+            listener.local_addr()?.port(),
+            urlencoding::encode(&filename)
+        );
+        let website = embedded_visualizer_website();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                // a broken connection only affects the request being served
+                let _ = serve_embedded_request(stream, &website, &filename, &filepath);
+            }
+        });
+        Ok(link)
+    }
+
+    /// serve the visualizer file with the embedded frontend and open it in the default browser, without the need of
+    /// locating the `visualize/` folder that matches the installed version
+    pub fn open_in_browser(&self) -> std::io::Result<String> {
+        let link = self.serve_in_background()?;
+        open_link_in_browser(&link)?;
+        Ok(link)
+    }
+}
+
+#[cfg(any(feature = "embed_visualizer", feature = "python_binding"))]
+impl Visualizer {
+    /// save a single self-contained HTML file with the embedded frontend and all the snapshots, which can be opened
+    /// directly in a browser without a server, e.g. to share a decoding trace in a paper or a bug report; the
+    /// third-party libraries (three.js, Vue and Quasar) are still loaded from CDN, the same as `visualize/index.html`
+    pub fn save_html(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, standalone_visualizer_html(&self.data()?))
+    }
+}
+
+/// the frontend in a single HTML page: the ES modules are inlined as `data:` URLs in the import map, with their relative
+/// imports rewritten to bare specifiers that resolve through the import map, and the data is embedded as a JSON script
+#[cfg(any(feature = "embed_visualizer", feature = "python_binding"))]
+pub fn standalone_visualizer_html(data: &serde_json::Value) -> String {
+    let website = embedded_visualizer_website();
+    let modules = ["index.js", "gui3d.js", "patches.js", "primal.js"];
+    // `</script` cannot appear inside an inline script; in JSON it can only be in a string, where `\/` is `/`
+    let inline_script = |content: &str| content.replace("</script", "<\\/script");
+    let mut html = website["index.html"].to_string();
+    let importmap_begin = html.find("<script type=\"importmap\">").expect("import map in index.html");
+    let importmap_end = importmap_begin + html[importmap_begin..].find("</script>").unwrap() + "</script>".len();
+    let importmap_content = &html[importmap_begin + "<script type=\"importmap\">".len()..importmap_end - "</script>".len()];
+    let importmap: serde_json::Value = serde_json::from_str(importmap_content).expect("valid import map");
+    let mut imports = serde_json::Map::new();
+    for (specifier, url) in importmap["imports"].as_object().unwrap() {
+        imports.insert(specifier.trim_start_matches("./node_modules/").to_string(), url.clone());
+    }
+    for module in modules {
+        let mut source = website[module].replace("'./node_modules/", "'");
+        for other in modules {
+            source = source.replace(&format!("'./{other}'"), &format!("'fusion-blossom/{other}'"));
+        }
+        let url = format!("data:text/javascript;charset=utf-8,{}", urlencoding::encode(&source));
+        imports.insert(format!("fusion-blossom/{module}"), json!(url));
+    }
+    let importmap = format!(
+        "<script type=\"importmap\">\n{}\n</script>",
+        inline_script(&json!({ "imports": imports }).to_string())
+    );
+    html.replace_range(importmap_begin..importmap_end, &importmap);
+    let icon = format!("data:image/svg+xml,{}", urlencoding::encode(website["icon.svg"]));
+    html.replace("<link rel=\"icon\" href=\"icon.svg\">", &format!("<link rel=\"icon\" href=\"{icon}\">"))
+        .replace(
+            "<script src=\"./cmd.js\"></script>",
+            &format!("<script>\n{}\n</script>", inline_script(website["cmd.js"])),
+        )
+        .replace(
+            "<script type=\"module\" src=\"./index.js\"></script>",
+            &format!(
+                "<script type=\"application/json\" id=\"fusion-blossom-data\">{}</script>\n    <script type=\"module\">import 'fusion-blossom/index.js'</script>",
+                inline_script(&data.to_string())
+            ),
+        )
+}
+
+#[cfg(any(feature = "embed_visualizer", feature = "python_binding"))]
+macro_rules! include_visualize_file {
+    ($mapping:ident, $filepath:expr) => {
+        $mapping.insert($filepath, include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/visualize/", $filepath)));
+    };
+    ($mapping:ident, $filepath:expr, $($other_filepath:expr),+) => {
+        include_visualize_file!($mapping, $filepath);
+        include_visualize_file!($mapping, $($other_filepath),+);
+    };
+}
+
+/// the static frontend files in the `visualize/` folder, embedded at compile time
+#[cfg(any(feature = "embed_visualizer", feature = "python_binding"))]
+pub fn embedded_visualizer_website() -> std::collections::BTreeMap<&'static str, &'static str> {
+    let mut mapping = std::collections::BTreeMap::new();
+    include_visualize_file!(
+        mapping,
+        "gui3d.js",
+        "index.js",
+        "patches.js",
+        "primal.js",
+        "cmd.js",
+        "mocker.js"
+    );
+    include_visualize_file!(mapping, "index.html", "partition-profile.html", "icon.svg");
+    include_visualize_file!(mapping, "package.json", "package-lock.json");
+    mapping
+}
+
+/// answer a single HTTP GET request with either an embedded frontend file or the visualizer data file
+#[cfg(feature = "embed_visualizer")]
+fn serve_embedded_request(
+    mut stream: std::net::TcpStream,
+    website: &std::collections::BTreeMap<&'static str, &'static str>,
+    filename: &str,
+    filepath: &str,
+) -> std::io::Result<()> {
+    use std::io::BufRead;
+    let mut reader = std::io::BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        // skip the headers
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+    }
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let path = path.split(['?', '#']).next().unwrap().trim_start_matches('/');
+    let path = urlencoding::decode(path).map(|path| path.into_owned()).unwrap_or_default();
+    let path = if path.is_empty() { "index.html".to_string() } else { path };
+    let content_type = match path.rsplit('.').next() {
+        Some("html") => "text/html; charset=utf8",
+        Some("js") => "text/javascript; charset=utf8",
+        Some("json") | Some("jsonl") => "application/json; charset=utf8",
+        Some("svg") => "image/svg+xml",
+        _ => "application/octet-stream",
+    };
+    let (status, body) = if path.strip_prefix("data/") == Some(filename) {
+        match std::fs::read(filepath) {
+            Ok(body) => ("200 OK", body),
+            Err(_) => ("404 Not Found", vec![]),
+        }
+    } else {
+        match website.get(path.as_str()) {
+            Some(content) => ("200 OK", content.as_bytes().to_vec()),
+            None => ("404 Not Found", vec![]),
+        }
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+/// open a link in the default browser of the system
+pub fn open_link_in_browser(link: &str) -> std::io::Result<()> {
+    let program = if cfg!(target_os = "macos") {
+        "open"
+    } else if cfg!(target_os = "windows") {
+        "explorer"
+    } else {
+        "xdg-open"
+    };
+    std::process::Command::new(program).arg(link).spawn()?;
+    Ok(())
 }
 
 const DEFAULT_VISUALIZE_DATA_FOLDER: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/visualize/data/");
@@ -606,6 +1120,8 @@ pub fn print_visualize_link(filename: String) {
 #[pyfunction]
 pub(crate) fn register(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_class::<VisualizePosition>()?;
+    m.add_class::<CameraPreset>()?;
+    m.add_class::<VisualizeView>()?;
     m.add_class::<Visualizer>()?;
     m.add_function(wrap_pyfunction!(static_visualize_data_filename, m)?)?;
     m.add_function(wrap_pyfunction!(auto_visualize_data_filename, m)?)?;
@@ -996,4 +1512,264 @@ mod tests {
                 .unwrap();
         }
     }
+
+    #[test]
+    #[allow(clippy::unnecessary_cast)]
+    fn visualize_streamed_1() {
+        // cargo test visualize_streamed_1 -- --nocapture
+        let visualize_filename = "visualize_streamed_1.jsonl".to_string();
+        let filepath = visualize_data_folder() + visualize_filename.as_str();
+        let mut code = CodeCapacityPlanarCode::new(5, 0.1, 500);
+        let mut visualizer = Visualizer::new(Some(filepath.clone()), code.get_positions(), true).unwrap();
+        assert!(visualizer.streamed);
+        print_visualize_link(visualize_filename.clone());
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        code.vertices[7].is_defect = true;
+        let interface_ptr = DualModuleInterfacePtr::new_load(&code.get_syndrome(), &mut dual_module);
+        for i in 0..3 {
+            visualizer
+                .snapshot_combined(format!("snapshot {i}"), vec![&interface_ptr, &dual_module])
+                .unwrap();
+        }
+        assert_eq!(visualizer.snapshot_num, 3);
+        assert!(
+            visualizer.snapshots.is_empty(),
+            "streamed visualizer should not accumulate in memory"
+        );
+        let content = std::fs::read_to_string(filepath).unwrap();
+        let lines: Vec<serde_json::Value> = content.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["format"], "fusion_blossom");
+        assert_eq!(
+            lines[0]["positions"].as_array().unwrap().len(),
+            initializer.vertex_num as usize
+        );
+        for (i, line) in lines[1..].iter().enumerate() {
+            assert_eq!(line[0], format!("snapshot {i}"));
+            assert!(line[1]["vertices"].is_array());
+        }
+    }
+
+    #[test]
+    fn visualize_in_memory_1() {
+        // cargo test visualize_in_memory_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(5, 0.1, 500);
+        let mut visualizer = Visualizer::new(None, code.get_positions(), true).unwrap();
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        code.vertices[7].is_defect = true;
+        let interface_ptr = DualModuleInterfacePtr::new_load(&code.get_syndrome(), &mut dual_module);
+        for i in 0..3 {
+            visualizer
+                .snapshot_combined(format!("snapshot {i}"), vec![&interface_ptr, &dual_module])
+                .unwrap();
+        }
+        assert_eq!(visualizer.snapshot_num, 3);
+        let recorded = visualizer.recorded_snapshots();
+        assert_eq!(recorded.len(), 3);
+        for (i, (name, value)) in recorded.iter().enumerate() {
+            assert_eq!(name, &format!("snapshot {i}"));
+            assert!(value["vertices"].is_array());
+        }
+        // a ring buffer keeps the latest snapshots of a long run
+        let mut visualizer = Visualizer::new_ring_buffer(2, code.get_positions(), true).unwrap();
+        for i in 0..5 {
+            visualizer
+                .snapshot_combined(format!("snapshot {i}"), vec![&interface_ptr, &dual_module])
+                .unwrap();
+        }
+        assert_eq!(visualizer.snapshot_num, 5);
+        assert!(visualizer.snapshots.is_empty(), "the names are not recorded either");
+        let data = visualizer.data().unwrap();
+        assert_eq!(data["positions"].as_array().unwrap().len(), code.vertices.len());
+        assert_eq!(data["snapshots"][0][0], "snapshot 3");
+        assert_eq!(data["dropped_snapshots"], 3);
+    }
+
+    /// the embedded frontend and the visualizer file are served without the `visualize/` folder
+    #[cfg(feature = "embed_visualizer")]
+    #[test]
+    fn visualize_embedded_server_1() {
+        // cargo test --features embed_visualizer visualize_embedded_server_1 -- --nocapture
+        use std::io::Read;
+        let visualize_filename = "visualize_embedded_server_1.json".to_string();
+        let code = CodeCapacityPlanarCode::new(3, 0.1, 500);
+        let visualizer = Visualizer::new(
+            Some(visualize_data_folder() + visualize_filename.as_str()),
+            code.get_positions(),
+            true,
+        )
+        .unwrap();
+        let link = visualizer.serve_in_background().unwrap();
+        println!("{link}");
+        let address = link.trim_start_matches("http://").split('/').next().unwrap().to_string();
+        let get = |path: &str| -> String {
+            let mut stream = std::net::TcpStream::connect(&address).unwrap();
+            write!(stream, "GET {path} HTTP/1.1\r\nHost: {address}\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+        let index = get("/?filename=visualize_embedded_server_1.json");
+        assert!(index.starts_with("HTTP/1.1 200 OK"));
+        assert!(index.contains("<title>Fusion Visualize</title>"));
+        assert!(get("/index.js").contains("text/javascript"));
+        let data = get(&format!("/data/{visualize_filename}"));
+        assert!(data.starts_with("HTTP/1.1 200 OK"));
+        assert!(data.contains("\"format\":\"fusion_blossom\""));
+        assert!(get("/data/other.json").starts_with("HTTP/1.1 404 Not Found"));
+        assert!(get("/server.py").starts_with("HTTP/1.1 404 Not Found"));
+    }
+
+    /// the standalone HTML file embeds the frontend and the data of both in-memory and file visualizers
+    #[cfg(feature = "embed_visualizer")]
+    #[test]
+    fn visualize_save_html_1() {
+        // cargo test --features embed_visualizer visualize_save_html_1 -- --nocapture
+        use super::super::mwpm_solver::{PrimalDualSolver, SolverSerial};
+        let mut code = CodeCapacityPlanarCode::new(5, 0.1, 500);
+        let syndrome_pattern = code.generate_random_errors(1);
+        for filepath in [
+            None,
+            Some(visualize_data_folder() + "visualize_save_html_1.json"),
+            Some(visualize_data_folder() + "visualize_save_html_1.jsonl"),
+        ] {
+            let mut visualizer = Visualizer::new(filepath, code.get_positions(), true).unwrap();
+            let mut solver = SolverSerial::new(&code.get_initializer());
+            solver.solve_visualizer(&syndrome_pattern, Some(&mut visualizer));
+            let data = visualizer.data().unwrap();
+            assert_eq!(data["format"], "fusion_blossom");
+            assert_eq!(data["positions"].as_array().unwrap().len(), code.vertices.len());
+            assert_eq!(data["snapshots"].as_array().unwrap().len(), visualizer.snapshot_num);
+            let html_path = "tmp/visualize_save_html_1.html";
+            std::fs::create_dir_all("tmp").unwrap();
+            visualizer.save_html(html_path).unwrap();
+            let html = std::fs::read_to_string(html_path).unwrap();
+            for asset in ["src=\"./cmd.js\"", "src=\"./index.js\"", "href=\"icon.svg\"", "'./gui3d.js'"] {
+                assert!(!html.contains(asset), "{asset} is not embedded");
+            }
+            assert!(html.contains("\"fusion-blossom/gui3d.js\":\"data:text/javascript"));
+            let begin_tag = "<script type=\"application/json\" id=\"fusion-blossom-data\">";
+            let begin = html.find(begin_tag).unwrap() + begin_tag.len();
+            let end = begin + html[begin..].find("</script>").unwrap();
+            let embedded: serde_json::Value = serde_json::from_str(&html[begin..end]).unwrap();
+            assert_eq!(embedded, data);
+        }
+    }
+
+    /// the fingerprint is written in the header and the seed in the following snapshots
+    #[test]
+    fn visualize_fingerprint_1() {
+        // cargo test visualize_fingerprint_1 -- --nocapture
+        let visualize_filename = "visualize_fingerprint_1.json".to_string();
+        let mut code = CodeCapacityPlanarCode::new(3, 0.1, 500);
+        let fingerprint = OutputFingerprint::new(&json!({ "d": 3 })).with_seed(1);
+        let mut visualizer = Visualizer::new_fingerprint(
+            Some(visualize_data_folder() + visualize_filename.as_str()),
+            code.get_positions(),
+            true,
+            Some(fingerprint.clone()),
+        )
+        .unwrap();
+        print_visualize_link(visualize_filename.clone());
+        let initializer = code.get_initializer();
+        let mut dual_module = DualModuleSerial::new_empty(&initializer);
+        code.vertices[1].is_defect = true;
+        let interface_ptr = DualModuleInterfacePtr::new_load(&code.get_syndrome(), &mut dual_module);
+        visualizer
+            .snapshot_combined("no seed".to_string(), vec![&interface_ptr, &dual_module])
+            .unwrap();
+        visualizer.seed = Some(1);
+        visualizer
+            .snapshot_combined("seed".to_string(), vec![&interface_ptr, &dual_module])
+            .unwrap();
+        let content = std::fs::read_to_string(visualize_data_folder() + visualize_filename.as_str()).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(value["fingerprint"], json!(fingerprint));
+        assert_eq!(visualizer.fingerprint(), Some(&fingerprint));
+        assert!(value["snapshots"][0][1].get("seed").is_none());
+        assert_eq!(value["snapshots"][1][1]["seed"], json!(1));
+    }
+
+    /// the rounds of a time-domain decoding graph are the layers of the view in the header
+    #[test]
+    fn visualize_view_layers_1() {
+        // cargo test visualize_view_layers_1 -- --nocapture
+        let visualize_filename = "visualize_view_layers_1.json".to_string();
+        let code = PhenomenologicalPlanarCode::new(5, 3, 0.1, 500);
+        let mut visualizer = Visualizer::new(
+            Some(visualize_data_folder() + visualize_filename.as_str()),
+            CodeCapacityPlanarCode::new(5, 0.1, 500).get_positions(),
+            true,
+        )
+        .unwrap();
+        assert!(visualizer.view().is_none(), "a single round has no layers");
+        visualizer.set_positions(code.get_positions(), true).unwrap();
+        let view = visualizer.view().unwrap().clone();
+        assert_eq!(view.camera, CameraPreset::Isometric);
+        assert_eq!(view.layers.len(), 4);
+        for position in center_positions(code.get_positions()) {
+            assert_eq!(view.layers[view.layer_of(position.t)], position.t);
+        }
+        assert_eq!(view.layer_of(view.layers[0] - 10.), 0);
+        assert_eq!(view.layer_of(view.layers[3] + 10.), 3);
+        assert_eq!(view.layer_of(view.layers[1] * 0.4 + view.layers[2] * 0.6), 2);
+        visualizer
+            .set_view(VisualizeView {
+                camera: CameraPreset::Front,
+                ..view.clone()
+            })
+            .unwrap();
+        let initializer = code.get_initializer();
+        let dual_module = DualModuleSerial::new_empty(&initializer);
+        let interface_ptr = DualModuleInterfacePtr::new_empty();
+        visualizer
+            .snapshot_combined("initial".to_string(), vec![&interface_ptr, &dual_module])
+            .unwrap();
+        assert!(visualizer.set_positions(code.get_positions(), true).is_err());
+        let data = visualizer.data().unwrap();
+        assert_eq!(data["view"]["camera"], json!("front"));
+        assert_eq!(data["view"]["layers"], json!(view.layers));
+        assert_eq!(data["snapshots"].as_array().unwrap().len(), 1);
+    }
+
+    /// the timeline is the same whether recorded in memory or extracted from the file
+    #[test]
+    fn visualize_timeline_csv_1() {
+        // cargo test visualize_timeline_csv_1 -- --nocapture
+        use super::super::mwpm_solver::{PrimalDualSolver, SolverSerial};
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        code.set_defect_vertices(&[10, 11, 25]);
+        let syndrome_pattern = code.get_syndrome();
+        let mut timelines = vec![];
+        for (filepath, record_timeline) in [
+            (None, false),
+            (Some(visualize_data_folder() + "visualize_timeline_csv_1.jsonl"), false),
+            (Some(visualize_data_folder() + "visualize_timeline_csv_1.jsonl"), true),
+        ] {
+            let mut visualizer = Visualizer::new(filepath, code.get_positions(), true).unwrap();
+            visualizer.record_timeline = record_timeline;
+            let mut solver = SolverSerial::new(&code.get_initializer());
+            solver.solve_visualizer(&syndrome_pattern, Some(&mut visualizer));
+            let timeline = visualizer.timeline().unwrap();
+            assert_eq!(timeline.len(), visualizer.snapshot_num);
+            let last = timeline.last().unwrap();
+            assert_eq!(last.name, "solved");
+            assert_eq!(last.sum_dual_variables, Some(last.dual_variables.iter().flatten().sum()));
+            assert!(timeline.iter().any(|point| point.conflict.is_some()));
+            std::fs::create_dir_all("tmp").unwrap();
+            visualizer.save_timeline_csv("tmp/visualize_timeline_csv_1.csv").unwrap();
+            let csv = std::fs::read_to_string("tmp/visualize_timeline_csv_1.csv").unwrap();
+            let mut lines = csv.lines();
+            assert!(lines
+                .next()
+                .unwrap()
+                .starts_with("snapshot,name,sum_dual_variables,conflict,node_0,node_1,node_2"));
+            assert_eq!(lines.count(), timeline.len());
+            timelines.push(timeline);
+        }
+        assert_eq!(timelines[0], timelines[1]);
+        assert_eq!(timelines[0], timelines[2]);
+    }
 }
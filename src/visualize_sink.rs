@@ -0,0 +1,391 @@
+//! Snapshot Sinks
+//!
+//! Where the snapshots of a [`Visualizer`] go: a JSON file that is valid after every snapshot, an append-only JSON Lines
+//! file, a zstd-compressed JSON Lines file, the memory (for tests and notebooks to assert on the snapshots), or a ring
+//! buffer that keeps only the latest snapshots of a long run. Without such a bound, monitoring a long run easily produces
+//! visualization files of several GB
+//!
+//! [`Visualizer`]: crate::visualize::Visualizer
+//!
+
+use crate::serde_json;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+
+pub trait SnapshotSink: Send + Sync + std::fmt::Debug {
+    /// (re)write the header, i.e. the data except the snapshots; only called before the first snapshot
+    fn write_header(&mut self, header: &serde_json::Value) -> std::io::Result<()>;
+
+    /// append a snapshot
+    fn write_snapshot(&mut self, name: &str, value: serde_json::Value) -> std::io::Result<()>;
+
+    /// the whole data in the format of a visualizer file: the header with all the snapshots that are still available
+    fn data(&self) -> std::io::Result<serde_json::Value>;
+
+    /// the `(name, snapshot)` pairs kept in memory
+    fn recorded_snapshots(&self) -> Vec<&(String, serde_json::Value)> {
+        vec![]
+    }
+
+    /// the file being written, if any
+    fn filepath(&self) -> Option<&str> {
+        None
+    }
+
+    /// whether the memory of the sink is bounded regardless of the number of snapshots, in which case the visualizer
+    /// does not record the names of the snapshots either
+    fn is_bounded(&self) -> bool {
+        false
+    }
+}
+
+/// the snapshot sink given the file name: in memory without a file, JSON Lines for `.jsonl`, zstd-compressed JSON Lines
+/// for `.zst` (requires the `compress_visualizer` feature), and a single JSON file otherwise
+pub fn snapshot_sink_from_filepath(filepath: Option<String>) -> std::io::Result<Box<dyn SnapshotSink>> {
+    Ok(match filepath {
+        None => Box::new(MemorySink::new()),
+        Some(filepath) if filepath.ends_with(".jsonl") => Box::new(JsonLinesFileSink::new(filepath)?),
+        #[cfg(feature = "compress_visualizer")]
+        Some(filepath) if filepath.ends_with(".zst") => Box::new(ZstdFileSink::new(filepath, ZstdFileSink::DEFAULT_LEVEL)?),
+        #[cfg(not(feature = "compress_visualizer"))]
+        Some(filepath) if filepath.ends_with(".zst") => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("writing {filepath} requires the `compress_visualizer` feature"),
+            ))
+        }
+        Some(filepath) => Box::new(JsonFileSink::new(filepath)?),
+    })
+}
+
+fn with_snapshots(header: &serde_json::Value, snapshots: Vec<serde_json::Value>) -> serde_json::Value {
+    let mut data = header.clone();
+    data["snapshots"] = json!(snapshots);
+    data
+}
+
+/// parse the content of a JSON Lines file: a header line followed by one snapshot per line
+fn parse_json_lines(content: impl BufRead) -> std::io::Result<serde_json::Value> {
+    let mut lines = content
+        .lines()
+        .filter(|line| !line.as_ref().is_ok_and(|line| line.trim().is_empty()));
+    let header: serde_json::Value = match lines.next() {
+        Some(line) => serde_json::from_str(&line?)?,
+        None => json!({}),
+    };
+    let mut snapshots = vec![];
+    for line in lines {
+        snapshots.push(serde_json::from_str(&line?)?);
+    }
+    Ok(with_snapshots(&header, snapshots))
+}
+
+/// a single JSON file that is valid after every snapshot, so that it can be opened in the viewer during a run
+#[derive(Debug)]
+pub struct JsonFileSink {
+    filepath: String,
+    file: File,
+    /// if waiting for the first snapshot
+    empty_snapshot: bool,
+}
+
+impl JsonFileSink {
+    pub fn new(filepath: String) -> std::io::Result<Self> {
+        Ok(Self {
+            file: File::create(&filepath)?,
+            filepath,
+            empty_snapshot: true,
+        })
+    }
+}
+
+impl SnapshotSink for JsonFileSink {
+    fn write_header(&mut self, header: &serde_json::Value) -> std::io::Result<()> {
+        let header = header.as_object().expect("header must be an object");
+        // keep the format and version at the front of the file, so that they are recognized quickly
+        let leading_keys = ["format", "version", "fingerprint", "positions"];
+        let keys = leading_keys
+            .iter()
+            .map(|key| key.to_string())
+            .filter(|key| header.contains_key(key))
+            .chain(header.keys().filter(|key| !leading_keys.contains(&key.as_str())).cloned());
+        let fields: Vec<String> = keys.map(|key| format!("{}:{}", json!(key), header[&key])).collect();
+        self.file.set_len(0)?; // truncate the file
+        self.file.seek(SeekFrom::Start(0))?; // move the cursor to the front
+        self.file
+            .write_all(format!("{{{},\"snapshots\":[]}}", fields.join(",")).as_bytes())?;
+        self.file.sync_all()?;
+        self.empty_snapshot = true;
+        Ok(())
+    }
+
+    fn write_snapshot(&mut self, name: &str, value: serde_json::Value) -> std::io::Result<()> {
+        self.file.seek(SeekFrom::End(-2))?; // move the cursor before the ending ]}
+        if !self.empty_snapshot {
+            self.file.write_all(b",")?;
+        }
+        self.empty_snapshot = false;
+        self.file.write_all(json!((name, value)).to_string().as_bytes())?;
+        self.file.write_all(b"]}")?;
+        self.file.sync_all()
+    }
+
+    fn data(&self) -> std::io::Result<serde_json::Value> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(&self.filepath)?)?)
+    }
+
+    fn filepath(&self) -> Option<&str> {
+        Some(&self.filepath)
+    }
+}
+
+/// append-only JSON Lines file: the first line is the header, followed by one snapshot per line
+#[derive(Debug)]
+pub struct JsonLinesFileSink {
+    filepath: String,
+    file: File,
+}
+
+impl JsonLinesFileSink {
+    pub fn new(filepath: String) -> std::io::Result<Self> {
+        Ok(Self {
+            file: File::create(&filepath)?,
+            filepath,
+        })
+    }
+}
+
+impl SnapshotSink for JsonLinesFileSink {
+    fn write_header(&mut self, header: &serde_json::Value) -> std::io::Result<()> {
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(header.to_string().as_bytes())?;
+        self.file.write_all(b"\n")
+    }
+
+    fn write_snapshot(&mut self, name: &str, value: serde_json::Value) -> std::io::Result<()> {
+        let mut line = json!((name, value)).to_string();
+        line.push('\n');
+        self.file.write_all(line.as_bytes())
+    }
+
+    fn data(&self) -> std::io::Result<serde_json::Value> {
+        parse_json_lines(BufReader::new(File::open(&self.filepath)?))
+    }
+
+    fn filepath(&self) -> Option<&str> {
+        Some(&self.filepath)
+    }
+
+    fn is_bounded(&self) -> bool {
+        true
+    }
+}
+
+/// zstd-compressed JSON Lines file; the header and each snapshot are compressed as separate frames, so that the file is
+/// always complete and `zstd -d` recovers the `.jsonl` file for the viewer at any time
+#[cfg(feature = "compress_visualizer")]
+#[derive(Debug)]
+pub struct ZstdFileSink {
+    filepath: String,
+    file: File,
+    /// compression level, 1 (fastest) to 22 (smallest)
+    pub level: i32,
+}
+
+#[cfg(feature = "compress_visualizer")]
+impl ZstdFileSink {
+    /// the default level of the `zstd` command
+    pub const DEFAULT_LEVEL: i32 = 3;
+
+    pub fn new(filepath: String, level: i32) -> std::io::Result<Self> {
+        Ok(Self {
+            file: File::create(&filepath)?,
+            filepath,
+            level,
+        })
+    }
+
+    fn write_frame(&mut self, line: String) -> std::io::Result<()> {
+        let frame = zstd::stream::encode_all(line.as_bytes(), self.level)?;
+        self.file.write_all(&frame)
+    }
+}
+
+#[cfg(feature = "compress_visualizer")]
+impl SnapshotSink for ZstdFileSink {
+    fn write_header(&mut self, header: &serde_json::Value) -> std::io::Result<()> {
+        self.file.set_len(0)?;
+        self.file.seek(SeekFrom::Start(0))?;
+        self.write_frame(format!("{header}\n"))
+    }
+
+    fn write_snapshot(&mut self, name: &str, value: serde_json::Value) -> std::io::Result<()> {
+        self.write_frame(format!("{}\n", json!((name, value))))
+    }
+
+    fn data(&self) -> std::io::Result<serde_json::Value> {
+        // the decoder reads all the concatenated frames
+        let decoder = zstd::stream::read::Decoder::new(File::open(&self.filepath)?)?;
+        parse_json_lines(BufReader::new(decoder))
+    }
+
+    fn filepath(&self) -> Option<&str> {
+        Some(&self.filepath)
+    }
+
+    fn is_bounded(&self) -> bool {
+        true
+    }
+}
+
+/// keep all the snapshots in memory, so that they can be inspected directly, e.g. in tests and notebooks
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    header: serde_json::Value,
+    snapshots: Vec<(String, serde_json::Value)>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl SnapshotSink for MemorySink {
+    fn write_header(&mut self, header: &serde_json::Value) -> std::io::Result<()> {
+        self.header = header.clone();
+        Ok(())
+    }
+
+    fn write_snapshot(&mut self, name: &str, value: serde_json::Value) -> std::io::Result<()> {
+        self.snapshots.push((name.to_string(), value));
+        Ok(())
+    }
+
+    fn data(&self) -> std::io::Result<serde_json::Value> {
+        Ok(with_snapshots(
+            &self.header,
+            self.snapshots.iter().map(|snapshot| json!(snapshot)).collect(),
+        ))
+    }
+
+    fn recorded_snapshots(&self) -> Vec<&(String, serde_json::Value)> {
+        self.snapshots.iter().collect()
+    }
+}
+
+/// keep only the latest `capacity` snapshots in memory, e.g. to look at what happened right before a failure in a long run
+#[derive(Debug)]
+pub struct RingBufferSink {
+    pub capacity: usize,
+    header: serde_json::Value,
+    snapshots: VecDeque<(String, serde_json::Value)>,
+    /// the number of the earliest snapshots that are dropped
+    pub dropped_snapshots: usize,
+}
+
+impl RingBufferSink {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "the ring buffer must keep at least one snapshot");
+        Self {
+            capacity,
+            header: json!({}),
+            snapshots: VecDeque::with_capacity(capacity),
+            dropped_snapshots: 0,
+        }
+    }
+}
+
+impl SnapshotSink for RingBufferSink {
+    fn write_header(&mut self, header: &serde_json::Value) -> std::io::Result<()> {
+        self.header = header.clone();
+        Ok(())
+    }
+
+    fn write_snapshot(&mut self, name: &str, value: serde_json::Value) -> std::io::Result<()> {
+        if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front();
+            self.dropped_snapshots += 1;
+        }
+        self.snapshots.push_back((name.to_string(), value));
+        Ok(())
+    }
+
+    /// the data also records the number of dropped snapshots in `dropped_snapshots`
+    fn data(&self) -> std::io::Result<serde_json::Value> {
+        let mut data = with_snapshots(&self.header, self.snapshots.iter().map(|snapshot| json!(snapshot)).collect());
+        data["dropped_snapshots"] = json!(self.dropped_snapshots);
+        Ok(data)
+    }
+
+    fn recorded_snapshots(&self) -> Vec<&(String, serde_json::Value)> {
+        self.snapshots.iter().collect()
+    }
+
+    fn is_bounded(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visualize_sink_ring_buffer_1() {
+        // cargo test visualize_sink_ring_buffer_1 -- --nocapture
+        let mut sink = RingBufferSink::new(2);
+        sink.write_header(&json!({ "format": "fusion_blossom" })).unwrap();
+        for i in 0..5 {
+            sink.write_snapshot(&format!("snapshot {i}"), json!({ "i": i })).unwrap();
+        }
+        let names: Vec<_> = sink.recorded_snapshots().iter().map(|(name, _)| name.clone()).collect();
+        assert_eq!(names, ["snapshot 3", "snapshot 4"]);
+        let data = sink.data().unwrap();
+        assert_eq!(data["format"], "fusion_blossom");
+        assert_eq!(data["dropped_snapshots"], 3);
+        assert_eq!(
+            data["snapshots"],
+            json!([["snapshot 3", { "i": 3 }], ["snapshot 4", { "i": 4 }]])
+        );
+    }
+
+    /// every file sink reads back the same data as the memory sink, even when the header is rewritten
+    #[test]
+    fn visualize_sink_files_1() {
+        // cargo test visualize_sink_files_1 -- --nocapture
+        // cargo test --features compress_visualizer visualize_sink_files_1 -- --nocapture
+        std::fs::create_dir_all("tmp").unwrap();
+        let mut filepaths = vec![
+            None,
+            Some("tmp/visualize_sink_files_1.json".to_string()),
+            Some("tmp/visualize_sink_files_1.jsonl".to_string()),
+        ];
+        if cfg!(feature = "compress_visualizer") {
+            filepaths.push(Some("tmp/visualize_sink_files_1.jsonl.zst".to_string()));
+        } else {
+            assert!(snapshot_sink_from_filepath(Some("tmp/visualize_sink_files_1.jsonl.zst".to_string())).is_err());
+        }
+        let mut all_data = vec![];
+        for filepath in filepaths {
+            let mut sink = snapshot_sink_from_filepath(filepath.clone()).unwrap();
+            assert_eq!(sink.filepath(), filepath.as_deref());
+            sink.write_header(&json!({ "format": "fusion_blossom", "positions": [] }))
+                .unwrap();
+            sink.write_header(&json!({ "format": "fusion_blossom", "positions": [1] }))
+                .unwrap();
+            assert_eq!(sink.data().unwrap()["snapshots"], json!([]));
+            for i in 0..3 {
+                sink.write_snapshot(&format!("snapshot {i}"), json!({ "i": i })).unwrap();
+            }
+            all_data.push(sink.data().unwrap());
+        }
+        assert_eq!(all_data[0]["positions"], json!([1]));
+        assert_eq!(all_data[0]["snapshots"].as_array().unwrap().len(), 3);
+        for data in all_data.iter() {
+            assert_eq!(data, &all_data[0]);
+        }
+    }
+}
@@ -0,0 +1,322 @@
+//! Static Visualization
+//!
+//! A headless renderer of the visualizer snapshots into 2D SVG images, so that figures can be produced in CI or scripts
+//! without a browser. It draws the top view (looking at the -t direction) of the decoding graph like the web frontend:
+//! the grown part of each edge from either end, which is how far the dual nodes reach, the hull of each top-level dual
+//! node and blossom, and the matched subgraph if the snapshot has one. The SVG can be rasterized into PNG with the
+//! `png_export` feature
+//!
+
+use super::visualize::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SvgRenderConfig {
+    /// pixels per unit of the positions
+    #[serde(default = "svg_render_default_configs::scale")]
+    pub scale: f64,
+    /// margin around the graph in pixels
+    #[serde(default = "svg_render_default_configs::padding")]
+    pub padding: f64,
+    /// in units of the positions
+    #[serde(default = "svg_render_default_configs::vertex_radius")]
+    pub vertex_radius: f64,
+    /// in units of the positions
+    #[serde(default = "svg_render_default_configs::edge_width")]
+    pub edge_width: f64,
+}
+
+pub mod svg_render_default_configs {
+    pub fn scale() -> f64 {
+        100.
+    }
+    pub fn padding() -> f64 {
+        20.
+    }
+    pub fn vertex_radius() -> f64 {
+        0.15
+    }
+    pub fn edge_width() -> f64 {
+        0.06
+    }
+}
+
+impl Default for SvgRenderConfig {
+    fn default() -> Self {
+        serde_json::from_value(json!({})).unwrap()
+    }
+}
+
+/// the same colors as the web frontend
+const EDGE_COLOR: &str = "#000000";
+const GROWN_EDGE_COLOR: &str = "#ff0000";
+const SUBGRAPH_EDGE_COLOR: &str = "#0000ff";
+const DEFECT_VERTEX_COLOR: &str = "#ff0000";
+const VIRTUAL_VERTEX_COLOR: &str = "#ffff00";
+const REAL_VERTEX_COLOR: &str = "#ffffff";
+const HULL_COLOR: &str = "#00ff00";
+
+/// render a snapshot of the visualizer, see [`Visualizer::data`], given the positions of the vertices
+#[allow(clippy::unnecessary_cast)]
+pub fn render_snapshot_svg(
+    positions: &[VisualizePosition],
+    snapshot: &serde_json::Value,
+    config: &SvgRenderConfig,
+) -> String {
+    let points: Vec<(f64, f64)> = positions.iter().map(|position| (position.j, position.i)).collect();
+    let (min_x, max_x) = bounds(points.iter().map(|point| point.0));
+    let (min_y, max_y) = bounds(points.iter().map(|point| point.1));
+    let margin = config.padding + config.vertex_radius * config.scale;
+    let project = |(x, y): (f64, f64)| ((x - min_x) * config.scale + margin, (y - min_y) * config.scale + margin);
+    let width = (max_x - min_x) * config.scale + 2. * margin;
+    let height = (max_y - min_y) * config.scale + 2. * margin;
+    let vertex_radius = config.vertex_radius * config.scale;
+    let edge_width = config.edge_width * config.scale;
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.1}" height="{height:.1}" viewBox="0 0 {width:.1} {height:.1}">"#
+    )
+    .unwrap();
+    writeln!(svg, r##"<rect width="100%" height="100%" fill="#ffffff"/>"##).unwrap();
+    let null_array = vec![];
+    let array = |key: &str| snapshot[key].as_array().unwrap_or(&null_array);
+    let vertices = array("vertices");
+    let edges = array("edges");
+    let dual_nodes = array("dual_nodes");
+    let number = |value: &serde_json::Value| value.as_f64().unwrap_or(0.);
+    let endpoints = |edge: &serde_json::Value| {
+        let left = project(points[edge["l"].as_u64().unwrap() as usize]);
+        let right = project(points[edge["r"].as_u64().unwrap() as usize]);
+        (left, right)
+    };
+    let interpolate = |(a, b): ((f64, f64), (f64, f64)), ratio: f64| (a.0 + (b.0 - a.0) * ratio, a.1 + (b.1 - a.1) * ratio);
+    // the hulls of the top-level dual nodes, below everything else
+    for dual_node in dual_nodes.iter().filter(|dual_node| !dual_node.is_null()) {
+        if !dual_node["p"].is_null() || (number(&dual_node["d"]) <= 0. && dual_node["o"].is_null()) {
+            continue;
+        }
+        let mut hull_points = vec![];
+        for boundary in dual_node["b"].as_array().unwrap_or(&null_array) {
+            let is_left = boundary[0].as_bool().unwrap_or(false);
+            let edge = &edges[boundary[1].as_u64().unwrap() as usize];
+            let weight = number(&edge["w"]);
+            let (left_growth, right_growth) = (number(&edge["lg"]), number(&edge["rg"]));
+            if edge["ld"] == edge["rd"] && left_growth + right_growth >= weight {
+                continue; // internal edge
+            }
+            let line = endpoints(edge);
+            hull_points.push(match (is_left, weight > 0.) {
+                (true, true) => interpolate(line, left_growth / weight),
+                (false, true) => interpolate(line, 1. - right_growth / weight),
+                (true, false) => line.0,
+                (false, false) => line.1,
+            });
+        }
+        let hull = convex_hull(hull_points);
+        if hull.len() >= 3 {
+            writeln!(
+                svg,
+                r#"<polygon points="{}" fill="{HULL_COLOR}" fill-opacity="0.2" stroke="{HULL_COLOR}" stroke-width="{:.2}"/>"#,
+                polygon_points(&hull),
+                edge_width / 2.
+            )
+            .unwrap();
+        }
+    }
+    // the edges, with the grown part from either end
+    let subgraph: std::collections::BTreeSet<u64> = array("subgraph").iter().filter_map(|edge| edge.as_u64()).collect();
+    for (edge_index, edge) in edges.iter().enumerate() {
+        if edge.is_null() {
+            continue;
+        }
+        let line = endpoints(edge);
+        let weight = number(&edge["w"]);
+        if subgraph.contains(&(edge_index as u64)) {
+            write_line(&mut svg, line, SUBGRAPH_EDGE_COLOR, 1., edge_width * 2.);
+            continue;
+        }
+        write_line(&mut svg, line, EDGE_COLOR, 0.1, edge_width);
+        if weight <= 0. {
+            continue;
+        }
+        let (left_growth, right_growth) = (number(&edge["lg"]), number(&edge["rg"]));
+        if left_growth > 0. {
+            let end = interpolate(line, (left_growth / weight).min(1.));
+            write_line(&mut svg, (line.0, end), GROWN_EDGE_COLOR, 1., edge_width);
+        }
+        if right_growth > 0. {
+            let end = interpolate(line, 1. - (right_growth / weight).min(1.));
+            write_line(&mut svg, (end, line.1), GROWN_EDGE_COLOR, 1., edge_width);
+        }
+    }
+    // the vertices on top
+    for (vertex_index, vertex) in vertices.iter().enumerate() {
+        if vertex.is_null() {
+            continue;
+        }
+        let (x, y) = project(points[vertex_index]);
+        let color = if number(&vertex["s"]) != 0. {
+            DEFECT_VERTEX_COLOR
+        } else if number(&vertex["v"]) != 0. {
+            VIRTUAL_VERTEX_COLOR
+        } else {
+            REAL_VERTEX_COLOR
+        };
+        writeln!(
+            svg,
+            r##"<circle cx="{x:.2}" cy="{y:.2}" r="{vertex_radius:.2}" fill="{color}" stroke="#000000" stroke-width="{:.2}"/>"##,
+            edge_width / 2.
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// rasterize an SVG image into PNG
+#[cfg(feature = "png_export")]
+pub fn svg_to_png(svg: &str) -> Result<Vec<u8>, String> {
+    use resvg::{tiny_skia, usvg};
+    let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).map_err(|error| error.to_string())?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height()).ok_or("empty image")?;
+    resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|error| error.to_string())
+}
+
+impl Visualizer {
+    /// render the snapshot of the given index into an SVG image, see [`render_snapshot_svg`]
+    pub fn snapshot_svg(&self, snapshot_index: usize, config: &SvgRenderConfig) -> std::io::Result<String> {
+        let data = self.data()?;
+        let positions: Vec<VisualizePosition> = serde_json::from_value(data["positions"].clone())?;
+        let snapshot = &data["snapshots"][snapshot_index][1];
+        if snapshot.is_null() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("snapshot {snapshot_index} doesn't exist"),
+            ));
+        }
+        Ok(render_snapshot_svg(&positions, snapshot, config))
+    }
+
+    pub fn save_svg(&self, snapshot_index: usize, path: &str, config: &SvgRenderConfig) -> std::io::Result<()> {
+        std::fs::write(path, self.snapshot_svg(snapshot_index, config)?)
+    }
+
+    #[cfg(feature = "png_export")]
+    pub fn save_png(&self, snapshot_index: usize, path: &str, config: &SvgRenderConfig) -> std::io::Result<()> {
+        let png = svg_to_png(&self.snapshot_svg(snapshot_index, config)?)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))?;
+        std::fs::write(path, png)
+    }
+}
+
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    });
+    if min > max {
+        (0., 0.) // no vertex
+    } else {
+        (min, max)
+    }
+}
+
+fn write_line(svg: &mut String, ((x1, y1), (x2, y2)): ((f64, f64), (f64, f64)), color: &str, opacity: f64, width: f64) {
+    writeln!(
+        svg,
+        r#"<line x1="{x1:.2}" y1="{y1:.2}" x2="{x2:.2}" y2="{y2:.2}" stroke="{color}" stroke-opacity="{opacity}" stroke-width="{width:.2}" stroke-linecap="round"/>"#
+    )
+    .unwrap();
+}
+
+fn polygon_points(points: &[(f64, f64)]) -> String {
+    let points: Vec<String> = points.iter().map(|(x, y)| format!("{x:.2},{y:.2}")).collect();
+    points.join(" ")
+}
+
+/// Andrew's monotone chain, in counter-clockwise order without collinear points
+fn convex_hull(mut points: Vec<(f64, f64)>) -> Vec<(f64, f64)> {
+    points.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<(f64, f64)> = Vec::with_capacity(2 * points.len());
+    for pass in 0..2 {
+        let start = hull.len();
+        let iter: Box<dyn Iterator<Item = &(f64, f64)>> = if pass == 0 {
+            Box::new(points.iter())
+        } else {
+            Box::new(points.iter().rev())
+        };
+        for &point in iter {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0. {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop(); // the last point is the first of the other chain
+    }
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::example_codes::*;
+    use super::super::mwpm_solver::*;
+    use super::super::util::*;
+    use super::*;
+
+    #[test]
+    fn visualize_svg_convex_hull_1() {
+        // cargo test visualize_svg_convex_hull_1 -- --nocapture
+        let hull = convex_hull(vec![(0., 0.), (1., 0.), (2., 0.), (2., 2.), (1., 1.), (0., 2.), (0., 2.)]);
+        assert_eq!(hull, vec![(0., 0.), (2., 0.), (2., 2.), (0., 2.)]);
+        assert_eq!(convex_hull(vec![(1., 1.), (0., 0.)]), vec![(0., 0.), (1., 1.)]);
+    }
+
+    #[test]
+    fn visualize_svg_render_1() {
+        // cargo test visualize_svg_render_1 -- --nocapture
+        let mut code = CodeCapacityPlanarCode::new(7, 0.1, 500);
+        let syndrome_pattern = SyndromePattern::new_vertices(vec![10, 11, 25, 32, 39]);
+        code.set_defect_vertices(&syndrome_pattern.defect_vertices);
+        let mut visualizer = Visualizer::new(None, code.get_positions(), true).unwrap();
+        let mut solver = SolverSerial::new(&code.get_initializer());
+        solver.solve_visualizer(&syndrome_pattern, Some(&mut visualizer));
+        solver.subgraph_visualizer(Some(&mut visualizer));
+        let config = SvgRenderConfig::default();
+        std::fs::create_dir_all("tmp").unwrap();
+        for snapshot_index in 0..visualizer.snapshot_num {
+            let svg = visualizer.snapshot_svg(snapshot_index, &config).unwrap();
+            assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+            assert_eq!(svg.matches("<circle").count(), code.vertices.len());
+            assert_eq!(
+                svg.matches(&format!("fill=\"{DEFECT_VERTEX_COLOR}\"")).count(),
+                syndrome_pattern.defect_vertices.len()
+            );
+        }
+        // the grown edges and the hulls in the middle, the matched edges at the end
+        let middle = visualizer.snapshot_svg(visualizer.snapshot_num / 2, &config).unwrap();
+        assert!(middle.contains(GROWN_EDGE_COLOR) && middle.contains("<polygon"));
+        let last = visualizer.snapshot_svg(visualizer.snapshot_num - 1, &config).unwrap();
+        assert!(last.contains(SUBGRAPH_EDGE_COLOR));
+        assert!(visualizer.snapshot_svg(visualizer.snapshot_num, &config).is_err());
+        visualizer
+            .save_svg(visualizer.snapshot_num - 1, "tmp/visualize_svg_render_1.svg", &config)
+            .unwrap();
+        #[cfg(feature = "png_export")]
+        {
+            visualizer
+                .save_png(visualizer.snapshot_num - 1, "tmp/visualize_svg_render_1.png", &config)
+                .unwrap();
+            let png = std::fs::read("tmp/visualize_svg_render_1.png").unwrap();
+            assert!(png.starts_with(b"\x89PNG"));
+        }
+    }
+}
@@ -103,3 +103,23 @@ def test_repetition_code():
     solver, visualizer = prepare_repetition_code_solver(100)
     solver.solve_visualizer(fb.SyndromePattern([]), visualizer)
     solver.subgraph(visualizer)
+
+
+def test_solve_with_weights():
+    solver = prepare_solver()
+    solver.solve_with_weights(fb.SyndromePattern([1, 2]), [(0, 48), (2, 48)])
+    subgraph = solver.subgraph()
+    assert subgraph == [0, 2]
+    solver.clear()
+
+    # the per-shot weights do not persist
+    solver.solve(fb.SyndromePattern([1, 2]))
+    subgraph = solver.subgraph()
+    assert subgraph == [1]
+    solver.clear()
+
+    # the per-shot weights override the erasures
+    solver.solve_with_weights(fb.SyndromePattern([1, 2], erasures=[0, 2]), [(0, 200)])
+    subgraph = solver.subgraph()
+    assert subgraph == [1]
+    solver.clear()
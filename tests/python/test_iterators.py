@@ -0,0 +1,31 @@
+import fusion_blossom as fb
+
+
+def prepare_solver():
+    code = fb.CodeCapacityPlanarCode(d=7, p=0.1, max_half_weight=500)
+    initializer = code.get_initializer()
+    return fb.SolverSerial(initializer), code
+
+
+def test_perfect_matching_iterator():
+    solver, _ = prepare_solver()
+    solver.solve(fb.SyndromePattern([3, 4, 25]))
+    perfect_matching = solver.perfect_matching()
+    assert len(perfect_matching) == 2
+    pairs = list(perfect_matching)
+    assert len(pairs) == 2
+    assert sorted(pairs[0]) == [3, 4]
+    assert pairs[1][0] == 25
+    assert perfect_matching[-1] == pairs[1]
+
+
+def test_visualizer_snapshots_iterator():
+    solver, code = prepare_solver()
+    visualizer = fb.Visualizer(filepath=None, positions=code.get_positions())
+    solver.solve(fb.SyndromePattern([3, 4, 25]), visualizer)
+    assert len(visualizer) > 0
+    for name, snapshot in visualizer:
+        assert isinstance(name, str)
+        assert "vertices" in snapshot
+    name, last_snapshot = visualizer[-1]
+    assert visualizer[name] == last_snapshot
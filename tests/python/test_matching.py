@@ -0,0 +1,52 @@
+"""
+`fusion_blossom.Matching` mirrors the API of `pymatching.Matching`
+
+repetition code of 5 qubits, where check i measures qubits i and i+1:
+
+   0     1     2     3     4     qubit (edge)
+o --- * --- * --- * --- * --- o
+      0     1     2     3          check (vertex)
+"""
+
+import fusion_blossom as fb
+
+
+def repetition_code_check_matrix(n):
+    return [[1 if j in (i, i + 1) else 0 for j in range(n)] for i in range(n - 1)]
+
+
+def test_decode():
+    matching = fb.Matching.from_check_matrix(repetition_code_check_matrix(5))
+    assert matching.num_detectors == 4
+    assert matching.num_fault_ids == 5
+    assert list(matching.decode([0, 1, 1, 0])) == [0, 0, 1, 0, 0]
+    assert list(matching.decode([1, 0, 0, 0])) == [1, 0, 0, 0, 0]
+    correction, weight = matching.decode([0, 0, 0, 1], return_weight=True)
+    assert list(correction) == [0, 0, 0, 0, 1]
+    assert weight == 1
+
+
+def test_decode_with_error_probabilities():
+    # qubit 0 is very unlikely to flip, so the defect at check 0 is matched to the right boundary
+    matching = fb.Matching.from_check_matrix(repetition_code_check_matrix(5)
+        , error_probabilities=[0.0001, 0.1, 0.1, 0.1, 0.1])
+    assert list(matching.decode([1, 0, 0, 0])) == [0, 1, 1, 1, 1]
+
+
+def test_decode_batch():
+    matching = fb.Matching.from_check_matrix(repetition_code_check_matrix(5))
+    shots = [[0, 1, 1, 0], [1, 0, 0, 0], [0, 0, 0, 0]]
+    predictions, weights = matching.decode_batch(shots, return_weights=True)
+    assert [list(prediction) for prediction in predictions] == [[0, 0, 1, 0, 0], [1, 0, 0, 0, 0], [0, 0, 0, 0, 0]]
+    assert list(weights) == [1, 1, 0]
+
+
+def test_repetitions():
+    matching = fb.Matching.from_check_matrix(repetition_code_check_matrix(5), repetitions=3)
+    assert matching.num_detectors == 12
+    # a measurement error on check 1 in the second round flips the detectors in rounds 1 and 2
+    z = [[0, 0, 0], [0, 1, 1], [0, 0, 0], [0, 0, 0]]
+    assert list(matching.decode(z)) == [0, 0, 0, 0, 0]
+    # a data qubit error in the first round
+    z = [[0, 0, 0], [1, 0, 0], [1, 0, 0], [0, 0, 0]]
+    assert list(matching.decode(z)) == [0, 0, 1, 0, 0]
@@ -115,6 +115,9 @@ export function reset_camera_position(direction = "top") {
         camera.position.x = (direction == "left" ? -distance : 0)
         camera.position.y = (direction == "top" ? distance : 0)
         camera.position.z = (direction == "front" ? distance : 0)
+        if (direction == "isometric") {  // look from a corner to see all three axes
+            camera.position.set(-distance, distance, distance).divideScalar(Math.sqrt(3))
+        }
         camera.lookAt(0, 0, 0)
     }
 }
@@ -246,6 +249,54 @@ export const blossom_convex_material = new THREE.MeshStandardMaterial({
 export const blossom_convex_material_2d = blossom_convex_material.clone()
 blossom_convex_material_2d.side = THREE.DoubleSide
 
+// color map rendering mode: "none" uses the plain materials above, "magnitude" colors grown edges by their growth fraction
+// and blossoms by their dual variables, so that quantitative state is visible, and "partition" colors the units of a parallel solver
+export const color_map = ref(urlParams.get('color_map') || "none")
+const color_map_stops = [[0.267, 0.005, 0.329], [0.229, 0.322, 0.546], [0.128, 0.567, 0.551], [0.369, 0.789, 0.383], [0.993, 0.906, 0.144]]  // viridis
+const color_map_levels = 32
+const color_map_materials = {}
+export function color_map_color(value) {  // value in [0, 1]
+    value = Math.min(Math.max(value, 0), 1) * (color_map_stops.length - 1)
+    const index = Math.min(Math.floor(value), color_map_stops.length - 2)
+    const ratio = value - index
+    const [a, b] = [color_map_stops[index], color_map_stops[index + 1]]
+    return new THREE.Color(a[0] + (b[0] - a[0]) * ratio, a[1] + (b[1] - a[1]) * ratio, a[2] + (b[2] - a[2]) * ratio)
+}
+export function color_map_material(base_material, value) {  // cached so that materials are shared among meshes
+    const level = Math.round(Math.min(Math.max(value, 0), 1) * (color_map_levels - 1))
+    const key = `${base_material.uuid}-${level}`
+    if (color_map_materials[key] == null) {
+        const material = base_material.clone()
+        material.color = color_map_color(level / (color_map_levels - 1))
+        color_map_materials[key] = material
+    }
+    return color_map_materials[key]
+}
+// "partition" color map: vertices and edges of a parallel solver are colored by the unit that currently holds them, i.e. the
+// active unit among the owning unit and its ancestors, so that the colors merge as the units are fused
+const partition_materials = {}
+export function partition_material(base_material, unit_index) {
+    const key = `${base_material.uuid}-${unit_index}`
+    if (partition_materials[key] == null) {
+        const material = base_material.clone()
+        material.color = new THREE.Color().setHSL((unit_index * 0.618034) % 1, 0.7, 0.5)  // golden ratio spreads the hues
+        material.opacity = Math.max(base_material.opacity, 0.5)
+        partition_materials[key] = material
+    }
+    return partition_materials[key]
+}
+export function partition_display_unit(snapshot, unit_index) {
+    const fusion_units = snapshot.fusion_units
+    if (fusion_units == null || unit_index == null) { return unit_index }
+    for (let current = unit_index; current != null && fusion_units[current] != null; current = fusion_units[current].p) {
+        if (fusion_units[current].a) { return current }
+    }
+    return unit_index  // an interface vertex whose units are not fused yet
+}
+function dual_node_magnitude(dual_node) {
+    return dual_node.d != null ? dual_node.d : dual_node.di
+}
+
 // meshes that can be reused across different snapshots
 export var vertex_meshes = []
 window.vertex_meshes = vertex_meshes
@@ -301,6 +352,39 @@ export function load_position(mesh_position, data_position) {
     mesh_position.y = data_position.t
 }
 
+/// the periods of the lattice along i and j when the decoding graph has no virtual vertex, e.g. the toric code, or null;
+/// an edge spanning more than half of a period is a wrap-around edge, see `edge_relative_position`
+export function compute_lattice_periods(positions, vertices) {
+    for (let vertex of vertices) {
+        if (vertex != null && vertex.v) { return null }
+    }
+    let periods = {}
+    for (let axis of ["i", "j"]) {
+        const values = [...new Set(positions.map(position => position[axis]))].sort((a, b) => a - b)
+        // evenly spaced values repeat one spacing after the last one
+        periods[axis] = values.length < 3 ? null : (values[values.length - 1] - values[0]) * values.length / (values.length - 1)
+    }
+    return periods
+}
+
+/// the vector from the left vertex to the right vertex of an edge; a wrap-around edge is drawn from the left vertex
+/// across the nearest side of the lattice instead of through the whole lattice
+export function edge_relative_position(left_position, right_position, periods) {
+    let relative = {
+        i: right_position.i - left_position.i,
+        j: right_position.j - left_position.j,
+        t: right_position.t - left_position.t,
+    }
+    if (periods != null) {
+        for (let axis of ["i", "j"]) {
+            if (periods[axis] != null && Math.abs(relative[axis]) > periods[axis] / 2) {
+                relative[axis] -= Math.sign(relative[axis]) * periods[axis]
+            }
+        }
+    }
+    return compute_vector3(relative)
+}
+
 /// translate to a format that is easy to plot (gracefully handle the overgrown edges)
 export function translate_edge(left_grown, right_grown, weight) {
     console.assert(left_grown >= 0 && right_grown >= 0, "grown should be non-negative")
@@ -318,6 +402,18 @@ export function translate_edge(left_grown, right_grown, weight) {
     }
 }
 
+// the layers of a time-domain decoding graph, see `VisualizeView` in the header; only the vertices whose layer is within
+// [layer_min, layer_max] are shown, together with the edges between them
+export const layer_min = ref(0)
+export const layer_max = ref(0)
+function vertex_layer(layers, position) {  // the layer nearest to the t position
+    let nearest = 0
+    for (let [index, t] of layers.entries()) {
+        if (Math.abs(t - position.t) < Math.abs(layers[nearest] - position.t)) { nearest = index }
+    }
+    return nearest
+}
+
 export const active_fusion_data = ref(null)
 export const active_snapshot_idx = ref(0)
 window.is_vertices_2d_plane = false  // will be true only if all vertices' t position = 0
@@ -346,6 +442,13 @@ export async function refresh_snapshot_data() {
                 }
             })
         }
+        // slice the layers
+        const layers = fusion_data.view?.layers
+        const vertex_in_layers = fusion_data.positions.map(position => {
+            if (layers == null) { return true }
+            const layer = vertex_layer(layers, position)
+            return layer >= layer_min.value && layer <= layer_max.value
+        })
         // draw vertices
         for (let [i, vertex] of snapshot.vertices.entries()) {
             if (vertex == null) {
@@ -373,10 +476,12 @@ export async function refresh_snapshot_data() {
                 vertex_mesh.material = defect_vertex_material
             } else if (vertex.v) {
                 vertex_mesh.material = virtual_vertex_material
+            } else if (color_map.value == "partition" && vertex.pu != null) {
+                vertex_mesh.material = partition_material(real_vertex_material, partition_display_unit(snapshot, vertex.pu))
             } else {
                 vertex_mesh.material = real_vertex_material
             }
-            vertex_mesh.visible = true
+            vertex_mesh.visible = vertex_in_layers[i]
         }
         for (let i = snapshot.vertices.length; i < vertex_meshes.length; ++i) {
             vertex_meshes[i].visible = false
@@ -393,6 +498,7 @@ export async function refresh_snapshot_data() {
             edge_offset = Math.sqrt(Math.pow(scaled_vertex_outline_radius.value, 2) - Math.pow(scaled_edge_radius.value, 2))
         }
         edge_caches = []  // clear cache
+        const lattice_periods = compute_lattice_periods(fusion_data.positions, snapshot.vertices)
         for (let [i, edge] of snapshot.edges.entries()) {
             if (edge == null) {
                 if (i < left_edge_meshes.length) {  // hide
@@ -406,7 +512,7 @@ export async function refresh_snapshot_data() {
             }
             const left_position = fusion_data.positions[edge.l]
             const right_position = fusion_data.positions[edge.r]
-            const relative = compute_vector3(right_position).add(compute_vector3(left_position).multiplyScalar(-1))
+            const relative = edge_relative_position(left_position, right_position, lattice_periods)
             const direction = relative.clone().normalize()
             // console.log(direction)
             const quaternion = new THREE.Quaternion()
@@ -457,11 +563,18 @@ export async function refresh_snapshot_data() {
                     edge_mesh.position.copy(j == 0 ? start_position : end_position)
                     edge_mesh.scale.set(1, (end - start) / 2, 1)
                     edge_mesh.setRotationFromQuaternion(j == 0 ? quaternion : reverse_quaternion)
-                    edge_mesh.visible = true
+                    edge_mesh.visible = vertex_in_layers[edge.l] && vertex_in_layers[edge.r]
                     if (start >= end) {
                         edge_mesh.visible = false
                     }
                     edge_mesh.material = is_grown_part ? grown_edge_material : edge_material
+                    if (!is_grown_part && color_map.value == "partition" && edge.pu != null) {
+                        edge_mesh.material = partition_material(edge_material, partition_display_unit(snapshot, edge.pu))
+                    }
+                    if (is_grown_part && color_map.value == "magnitude") {
+                        const growth_fraction = edge.gf != null ? edge.gf : (edge.w == 0 ? 1 : (edge.lg + edge.rg) / edge.w)
+                        edge_mesh.material = color_map_material(grown_edge_material, growth_fraction)
+                    }
                     if (snapshot.subgraph != null) {
                         edge_mesh.material = edge_material  // do not display grown edges
                     }
@@ -503,7 +616,7 @@ export async function refresh_snapshot_data() {
             } else {
                 vertex_outline_mesh.material = real_vertex_outline_material
             }
-            vertex_outline_mesh.visible = true
+            vertex_outline_mesh.visible = vertex_in_layers[i]
         }
         for (let i = snapshot.vertices.length; i < vertex_meshes.length; ++i) {
             vertex_outline_meshes[i].visible = false
@@ -514,6 +627,16 @@ export async function refresh_snapshot_data() {
                 scene.remove(blossom_convex_mesh)
                 blossom_convex_mesh.geometry.dispose()
             }
+            let max_dual_variable = 0
+            for (let dual_node of snapshot.dual_nodes) {
+                if (dual_node != null && dual_node.p == null && dual_node_magnitude(dual_node) > max_dual_variable) {
+                    max_dual_variable = dual_node_magnitude(dual_node)
+                }
+            }
+            const convex_material = (base_material, dual_node) => {
+                if (color_map.value != "magnitude" || max_dual_variable == 0) { return base_material }
+                return color_map_material(base_material, dual_node_magnitude(dual_node) / max_dual_variable)
+            }
             for (let [i, dual_node] of snapshot.dual_nodes.entries()) {
                 if (dual_node == null) { continue }
                 if (snapshot.subgraph != null) { continue }  // do not display convex if subgraph is displayed
@@ -561,14 +684,14 @@ export async function refresh_snapshot_data() {
                             }
                             const shape = new THREE.Shape(shape_points)
                             const geometry = new THREE.ShapeGeometry(shape)
-                            const blossom_convex_mesh = new THREE.Mesh(geometry, blossom_convex_material_2d)
+                            const blossom_convex_mesh = new THREE.Mesh(geometry, convex_material(blossom_convex_material_2d, dual_node))
                             blossom_convex_mesh.position.set(0, -0.2, 0)  // place the plane to slightly below the vertices for better viz
                             blossom_convex_mesh.rotation.set(Math.PI / 2, 0, 0);
                             scene.add(blossom_convex_mesh)
                             blossom_convex_meshes.push(blossom_convex_mesh)
                         } else {
                             const geometry = new ConvexGeometry(points)
-                            const blossom_convex_mesh = new THREE.Mesh(geometry, blossom_convex_material)
+                            const blossom_convex_mesh = new THREE.Mesh(geometry, convex_material(blossom_convex_material, dual_node))
                             scene.add(blossom_convex_mesh)
                             blossom_convex_meshes.push(blossom_convex_mesh)
                         }
@@ -583,7 +706,7 @@ export async function refresh_snapshot_data() {
         }
     }
 }
-watch([active_fusion_data, active_snapshot_idx, scaled_vertex_outline_radius], refresh_snapshot_data)
+watch([active_fusion_data, active_snapshot_idx, scaled_vertex_outline_radius, color_map, layer_min, layer_max], refresh_snapshot_data)
 export function show_snapshot(snapshot_idx, fusion_data) {
     active_snapshot_idx.value = snapshot_idx
     active_fusion_data.value = fusion_data
@@ -631,11 +754,13 @@ const conf = {
     outline_ratio: outline_ratio.value,
     vertex_radius_scale: vertex_radius_scale.value,
     edge_radius_scale: edge_radius_scale.value,
+    color_map: color_map.value,
 }
 const side_options = { "FrontSide": THREE.FrontSide, "BackSide": THREE.BackSide, "DoubleSide": THREE.DoubleSide }
 export const controller = {}
 window.controller = controller
 controller.scene_background = gui.addColor(conf, 'scene_background').onChange(function (value) { scene.background = value })
+controller.color_map = gui.add(conf, 'color_map', ["none", "magnitude", "partition"]).onChange(function (value) { color_map.value = value })
 const vertex_folder = gui.addFolder('vertex')
 controller.defect_vertex_color = vertex_folder.addColor(conf, 'defect_vertex_color').onChange(function (value) { defect_vertex_material.color = value })
 controller.defect_vertex_opacity = vertex_folder.add(conf, 'defect_vertex_opacity', 0, 1).onChange(function (value) { defect_vertex_material.opacity = Number(value) })
@@ -666,6 +791,23 @@ const size_folder = gui.addFolder('size')
 controller.outline_ratio = size_folder.add(conf, 'outline_ratio', 0.99, 2).onChange(function (value) { outline_ratio.value = Number(value) })
 controller.vertex_radius_scale = size_folder.add(conf, 'vertex_radius_scale', 0.1, 5).onChange(function (value) { vertex_radius_scale.value = Number(value) })
 controller.edge_radius_scale = size_folder.add(conf, 'edge_radius_scale', 0.1, 10).onChange(function (value) { edge_radius_scale.value = Number(value) })
+
+// apply the view in the header of the data: camera preset and the layer sliders, overridden by the `camera` url parameter
+export function apply_view(view) {
+    const camera_preset = urlParams.get('camera') || view?.camera
+    if (camera_preset != null) { reset_camera_position(camera_preset) }
+    if (view == null) { return }
+    use_perspective_camera.value = view.perspective
+    const layer_num = view.layers.length
+    layer_min.value = 0
+    layer_max.value = layer_num - 1
+    if (layer_num > 1) {
+        const layers_conf = { layer_min: layer_min.value, layer_max: layer_max.value }
+        const layer_folder = gui.addFolder('layers')
+        controller.layer_min = layer_folder.add(layers_conf, 'layer_min', 0, layer_num - 1, 1).onChange(function (value) { layer_min.value = Number(value) })
+        controller.layer_max = layer_folder.add(layers_conf, 'layer_max', 0, layer_num - 1, 1).onChange(function (value) { layer_max.value = Number(value) })
+    }
+}
 watch(sizes, () => {
     gui.domElement.style.transform = `scale(${sizes.scale})`
     gui.domElement.style["transform-origin"] = "right top"
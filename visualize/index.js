@@ -67,24 +67,38 @@ const App = {
     },
     async mounted() {
         gui3d.root.style.setProperty('--control-visibility', 'visible')
-        let response = null
-        try {
-            response = await fetch('./data/' + filename, { cache: 'no-cache', })
-        } catch (e) {
-            this.error_message = "fetch file error"
-            throw e
-        }
-        if (response.ok || is_mock) {
-            fusion_data = await response.json()
-            // console.log(fusion_data)
-            if (fusion_data.format != "fusion_blossom") {
-                this.error_message = `visualization file format error, get "${fusion_data.format}" expected "fusion_data"`
+        const embedded_data = document.getElementById('fusion-blossom-data')
+        if (embedded_data != null) {
+            // standalone HTML file with the data embedded, see `Visualizer::save_html`
+            fusion_data = JSON.parse(embedded_data.textContent)
+        } else {
+            let response = null
+            try {
+                response = await fetch('./data/' + filename, { cache: 'no-cache', })
+            } catch (e) {
+                this.error_message = "fetch file error"
+                throw e
+            }
+            if (response.ok || is_mock) {
+                if (filename.endsWith(".jsonl")) {
+                    // streamed file: a header line followed by one snapshot per line
+                    const lines = (await response.text()).split("\n").filter(line => line.trim() != "")
+                    fusion_data = JSON.parse(lines[0])
+                    fusion_data.snapshots = lines.slice(1).map(line => JSON.parse(line))
+                } else {
+                    fusion_data = await response.json()
+                }
+            } else {
+                this.error_message = `fetch file error ${response.status}: ${response.statusText}`
                 throw this.error_message
             }
-        } else {
-            this.error_message = `fetch file error ${response.status}: ${response.statusText}`
+        }
+        // console.log(fusion_data)
+        if (fusion_data.format != "fusion_blossom") {
+            this.error_message = `visualization file format error, get "${fusion_data.format}" expected "fusion_data"`
             throw this.error_message
         }
+        gui3d.apply_view(fusion_data.view)
         // hook primal div
         primal.initialize_primal_div()
         // load snapshot
@@ -107,6 +121,8 @@ const App = {
                     this.reset_camera("left")
                 } else if (event.key == "f" || event.key == "F") {
                     this.reset_camera("front")
+                } else if (event.key == "i" || event.key == "I") {
+                    this.reset_camera("isometric")
                 } else if (event.key == "c" || event.key == "C") {
                     this.show_config = !this.show_config
                 } else if (event.key == "s" || event.key == "S") {
@@ -63,6 +63,9 @@ global.fetch = async (filepath) => {
         async json() {
             return JSON.parse(await fs.promises.readFile(filepath, 'utf-8'))
         },
+        async text() {
+            return await fs.promises.readFile(filepath, 'utf-8')
+        },
         async arrayBuffer() {
             const buffer = await fs.promises.readFile(filepath)
             return buffer